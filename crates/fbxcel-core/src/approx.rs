@@ -0,0 +1,154 @@
+//! Approximate comparison.
+//!
+//! Loaded types derive `PartialEq` with exact float equality, which is too strict for
+//! round-trip tests where compression or conversion introduces tiny deltas.
+//! `ApproxEq` compares floats with an absolute tolerance, and everything else exactly.
+
+use std::collections::{HashMap, HashSet};
+use std::hash::{BuildHasher, Hash};
+use std::sync::Arc;
+
+
+/// Compares values with a tolerance for floating point numbers.
+pub trait ApproxEq {
+    /// Returns `true` if the values are equal, allowing floats to differ by at most `epsilon`.
+    ///
+    /// Non-float values are compared exactly, and `NaN` is never equal to anything.
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool;
+}
+
+
+macro_rules! impl_approx_eq_for_exact {
+    ($($ty:ty),*) => {
+        $(
+            impl ApproxEq for $ty {
+                fn approx_eq(&self, other: &Self, _epsilon: f64) -> bool {
+                    self == other
+                }
+            }
+        )*
+    };
+}
+
+impl_approx_eq_for_exact!(
+    bool, char, u8, i8, u16, i16, u32, i32, u64, i64, usize, isize, (), String, str
+);
+
+
+impl ApproxEq for f64 {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self == other || (self - other).abs() <= epsilon
+    }
+}
+
+impl ApproxEq for f32 {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        f64::from(*self).approx_eq(&f64::from(*other), epsilon)
+    }
+}
+
+
+impl<T: ApproxEq> ApproxEq for [T] {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.len() == other.len() &&
+            self.iter().zip(other).all(|(a, b)| a.approx_eq(b, epsilon))
+    }
+}
+
+impl<T: ApproxEq> ApproxEq for Vec<T> {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self[..].approx_eq(&other[..], epsilon)
+    }
+}
+
+impl<T: ApproxEq + ?Sized> ApproxEq for Box<T> {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        (**self).approx_eq(&**other, epsilon)
+    }
+}
+
+impl<T: ApproxEq + ?Sized> ApproxEq for Arc<T> {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        Arc::ptr_eq(self, other) || (**self).approx_eq(&**other, epsilon)
+    }
+}
+
+impl<T: ApproxEq, const N: usize> ApproxEq for [T; N] {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self[..].approx_eq(&other[..], epsilon)
+    }
+}
+
+impl<T: ApproxEq> ApproxEq for Option<T> {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        match (self, other) {
+            (Some(a), Some(b)) => a.approx_eq(b, epsilon),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+impl<T: ApproxEq, E: ApproxEq> ApproxEq for Result<T, E> {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        match (self, other) {
+            (Ok(a), Ok(b)) => a.approx_eq(b, epsilon),
+            (Err(a), Err(b)) => a.approx_eq(b, epsilon),
+            _ => false,
+        }
+    }
+}
+
+impl<A: ApproxEq, B: ApproxEq> ApproxEq for (A, B) {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.0.approx_eq(&other.0, epsilon) && self.1.approx_eq(&other.1, epsilon)
+    }
+}
+
+impl<A: ApproxEq, B: ApproxEq, C: ApproxEq, D: ApproxEq> ApproxEq for (A, B, C, D) {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.0.approx_eq(&other.0, epsilon) && self.1.approx_eq(&other.1, epsilon) &&
+            self.2.approx_eq(&other.2, epsilon) &&
+            self.3.approx_eq(&other.3, epsilon)
+    }
+}
+
+impl<K, V, S> ApproxEq for HashMap<K, V, S>
+where
+    K: Eq + Hash,
+    V: ApproxEq,
+    S: BuildHasher,
+{
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.len() == other.len() &&
+            self.iter().all(|(k, v)| other.get(k).is_some_and(|o| v.approx_eq(o, epsilon)))
+    }
+}
+
+impl<T, S> ApproxEq for HashSet<T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+    fn approx_eq(&self, other: &Self, _epsilon: f64) -> bool {
+        self == other
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_approx_eq() {
+        assert!(1.0f64.approx_eq(&(1.0 + 1e-9), 1e-6));
+        assert!(!1.0f64.approx_eq(&1.1, 1e-6));
+        assert!(!f64::NAN.approx_eq(&f64::NAN, 1.0));
+        assert!(f64::INFINITY.approx_eq(&f64::INFINITY, 0.0));
+        assert!(vec![1.0f32, 2.0].approx_eq(&vec![1.0, 2.000_001], 1e-5));
+        assert!(!vec![1.0f32].approx_eq(&vec![1.0, 2.0], 1e-5));
+        assert!(Some((1, 0.5f64)).approx_eq(&Some((1, 0.5 + 1e-12)), 1e-9));
+        assert!(!Some((1, 0.5f64)).approx_eq(&Some((2, 0.5)), 1e-9));
+    }
+}
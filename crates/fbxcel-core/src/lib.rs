@@ -0,0 +1,21 @@
+//! Core of fbxcel: pull parsers of FBX binary and ASCII files.
+//!
+//! Owned node trees live in `fbxcel-dom` and the binary writer in `fbxcel-writer`; the `fbxcel`
+//! crate re-exports all of them.
+#![warn(missing_docs)]
+
+#[cfg(feature = "flate2")]
+extern crate flate2;
+extern crate fnv;
+#[cfg(feature = "libflate")]
+extern crate libflate;
+#[macro_use]
+extern crate log;
+#[cfg(feature = "sha2")]
+extern crate sha2;
+#[cfg(feature = "twox-hash")]
+extern crate twox_hash;
+
+pub mod approx;
+pub mod memory;
+pub mod pull_parser;
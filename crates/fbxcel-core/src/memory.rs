@@ -0,0 +1,140 @@
+//! Memory footprint report.
+//!
+//! Reported sizes are estimations: allocator overheads are not counted, and hash tables are
+//! assumed to use one control byte per bucket.
+
+use std::collections::{HashMap, HashSet};
+use std::hash::{BuildHasher, Hash};
+use std::mem;
+
+
+/// Reports memory usage of a value.
+pub trait MemoryUsage {
+    /// Returns the size of heap memory owned by the value, in bytes.
+    ///
+    /// This does not include `mem::size_of_val(self)`.
+    fn heap_usage(&self) -> usize;
+
+    /// Returns the total size of memory used by the value, in bytes.
+    fn memory_usage(&self) -> usize {
+        mem::size_of_val(self) + self.heap_usage()
+    }
+}
+
+
+macro_rules! impl_memory_usage_for_plain {
+    ($($ty:ty),*) => {
+        $(
+            impl MemoryUsage for $ty {
+                fn heap_usage(&self) -> usize {
+                    0
+                }
+            }
+        )*
+    };
+}
+
+impl_memory_usage_for_plain!(
+    bool, char, u8, i8, u16, i16, u32, i32, u64, i64, usize, isize, f32, f64, ()
+);
+
+
+/// Returns the heap usage of the elements themselves (not counting the buffer).
+fn elements_heap_usage<'a, T, I>(iter: I) -> usize
+where
+    T: 'a + MemoryUsage,
+    I: IntoIterator<Item = &'a T>,
+{
+    iter.into_iter().map(MemoryUsage::heap_usage).sum()
+}
+
+
+impl MemoryUsage for String {
+    fn heap_usage(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl<T: MemoryUsage> MemoryUsage for Vec<T> {
+    fn heap_usage(&self) -> usize {
+        self.capacity() * mem::size_of::<T>() + elements_heap_usage(self)
+    }
+}
+
+impl<T: MemoryUsage> MemoryUsage for Box<[T]> {
+    fn heap_usage(&self) -> usize {
+        mem::size_of_val(&**self) + elements_heap_usage(self.iter())
+    }
+}
+
+impl<T: MemoryUsage, const N: usize> MemoryUsage for [T; N] {
+    fn heap_usage(&self) -> usize {
+        elements_heap_usage(self.iter())
+    }
+}
+
+impl<T: MemoryUsage> MemoryUsage for Option<T> {
+    fn heap_usage(&self) -> usize {
+        self.as_ref().map_or(0, MemoryUsage::heap_usage)
+    }
+}
+
+impl<T: MemoryUsage, E: MemoryUsage> MemoryUsage for Result<T, E> {
+    fn heap_usage(&self) -> usize {
+        match *self {
+            Ok(ref v) => v.heap_usage(),
+            Err(ref e) => e.heap_usage(),
+        }
+    }
+}
+
+impl<A: MemoryUsage, B: MemoryUsage> MemoryUsage for (A, B) {
+    fn heap_usage(&self) -> usize {
+        self.0.heap_usage() + self.1.heap_usage()
+    }
+}
+
+impl<K, V, S> MemoryUsage for HashMap<K, V, S>
+where
+    K: Eq + Hash + MemoryUsage,
+    V: MemoryUsage,
+    S: BuildHasher,
+{
+    fn heap_usage(&self) -> usize {
+        let bucket_size = mem::size_of::<(K, V)>() + 1;
+        let entries = self.iter()
+            .map(|(k, v)| k.heap_usage() + v.heap_usage())
+            .sum::<usize>();
+        self.capacity() * bucket_size + entries
+    }
+}
+
+impl<T, S> MemoryUsage for HashSet<T, S>
+where
+    T: Eq + Hash + MemoryUsage,
+    S: BuildHasher,
+{
+    fn heap_usage(&self) -> usize {
+        let bucket_size = mem::size_of::<T>() + 1;
+        self.capacity() * bucket_size + elements_heap_usage(self.iter())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heap_usage() {
+        let s = String::with_capacity(16);
+        assert_eq!(s.heap_usage(), 16);
+        let mut v: Vec<String> = Vec::with_capacity(4);
+        v.push(String::with_capacity(8));
+        assert_eq!(v.heap_usage(), 4 * mem::size_of::<String>() + 8);
+        assert_eq!(v.memory_usage(), mem::size_of::<Vec<String>>() + v.heap_usage());
+        let b: Box<[i32]> = vec![0; 3].into_boxed_slice();
+        assert_eq!(b.heap_usage(), 12);
+        assert_eq!(Some(s).heap_usage(), 16);
+    }
+}
@@ -0,0 +1,171 @@
+//! Parser for FBX files of unknown format.
+//!
+//! `from_reader()` detects the format (binary or ASCII) from the head of the source and returns
+//! `AnyParser`, which is the binary parser or the ASCII parser.
+//! Both parsers emit the same events, so loaders can be called for either variant.
+//!
+//! ```no_run
+//! use std::fs::File;
+//! use std::io::BufReader;
+//! use fbxcel_core::pull_parser::{Event, Parser, ParserSource};
+//! use fbxcel_core::pull_parser::any::{self, AnyParser};
+//!
+//! fn count_toplevel_nodes<R: ParserSource, P: Parser<R>>(parser: &mut P) -> usize {
+//!     let (mut depth, mut count) = (0, 0);
+//!     loop {
+//!         match parser.next_event().unwrap() {
+//!             Event::StartFbx(_) => {},
+//!             Event::EndFbx(_) => return count,
+//!             Event::StartNode(_) => {
+//!                 count += (depth == 0) as usize;
+//!                 depth += 1;
+//!             },
+//!             Event::EndNode => depth -= 1,
+//!         }
+//!     }
+//! }
+//!
+//! let file = BufReader::new(File::open("scene.fbx").unwrap());
+//! let count = match any::from_reader(file).unwrap() {
+//!     AnyParser::Binary(mut parser) => count_toplevel_nodes(&mut parser),
+//!     AnyParser::Ascii(mut parser) => count_toplevel_nodes(&mut parser),
+//! };
+//! println!("{} toplevel nodes", count);
+//! ```
+
+use std::io::{self, Read, Seek, SeekFrom};
+use pull_parser::{RootParser, SeekableSource};
+use pull_parser::ascii::{AsciiParser, Error, Result};
+
+
+/// Magic bytes at the head of FBX binary files.
+const BINARY_MAGIC: &[u8] = b"Kaydara FBX Binary  \x00";
+
+
+/// Header comment at the head of FBX ASCII files.
+const ASCII_HEADER: &[u8] = b"; FBX";
+
+
+/// Max number of bytes read to detect the format.
+///
+/// This allows some blank lines before the header comment of FBX ASCII files.
+const DETECTION_LEN: usize = 256;
+
+
+/// FBX file format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Format {
+    /// FBX binary.
+    Binary,
+    /// FBX ASCII.
+    Ascii,
+}
+
+
+/// Parser returned by `from_reader()`.
+#[derive(Debug)]
+pub enum AnyParser<R> {
+    /// Parser of FBX binary source.
+    Binary(RootParser<SeekableSource<R>>),
+    /// Parser of FBX ASCII source.
+    Ascii(AsciiParser),
+}
+
+impl<R> AnyParser<R> {
+    /// Returns the format of the source.
+    pub fn format(&self) -> Format {
+        match *self {
+            AnyParser::Binary(_) => Format::Binary,
+            AnyParser::Ascii(_) => Format::Ascii,
+        }
+    }
+}
+
+
+/// Detects the format from the head of the source, and rewinds the reader to the start position.
+///
+/// Sources with the FBX binary magic are FBX binary, and sources starting with the `; FBX`
+/// header comment (after an optional UTF-8 BOM and whitespaces) are FBX ASCII.
+/// Returns `Ok(None)` if the format is neither of them.
+pub fn detect_format<R: Read + Seek>(mut reader: R) -> io::Result<Option<Format>> {
+    let start = reader.stream_position()?;
+    let mut head = Vec::with_capacity(DETECTION_LEN);
+    (&mut reader).take(DETECTION_LEN as u64).read_to_end(&mut head)?;
+    reader.seek(SeekFrom::Start(start))?;
+    if head.starts_with(BINARY_MAGIC) {
+        return Ok(Some(Format::Binary));
+    }
+    let text = head.strip_prefix(b"\xef\xbb\xbf").unwrap_or(&head);
+    let text_start = text.iter().position(|b| !b.is_ascii_whitespace()).unwrap_or(text.len());
+    if text[text_start..].starts_with(ASCII_HEADER) {
+        Ok(Some(Format::Ascii))
+    } else {
+        Ok(None)
+    }
+}
+
+
+/// Creates a parser for FBX binary or ASCII source.
+///
+/// ASCII sources are read to the end here.
+/// Returns `Err(Error::UnknownFormat)` if the source is neither FBX binary nor FBX ASCII (see
+/// `detect_format()`).
+pub fn from_reader<R: Read + Seek>(mut reader: R) -> Result<AnyParser<R>> {
+    match detect_format(&mut reader).map_err(Error::Io)? {
+        Some(Format::Binary) => Ok(AnyParser::Binary(RootParser::from_seekable(reader))),
+        Some(Format::Ascii) => AsciiParser::new(reader).map(AnyParser::Ascii),
+        None => Err(Error::UnknownFormat),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use pull_parser::{Event, Parser, ParserSource};
+    use pull_parser::ascii::AsciiDocument;
+    use super::*;
+
+    /// Returns the name of the first node.
+    fn first_node_name<R: ParserSource, P: Parser<R>>(mut parser: P) -> String {
+        loop {
+            match parser.next_event().unwrap() {
+                Event::StartFbx(_) => {},
+                Event::StartNode(node) => return node.name().unwrap().to_owned(),
+                ev => panic!("Unexpected event: {:?}", ev),
+            }
+        }
+    }
+
+    const SOURCE: &str = "; FBX 7.4.0 project file\nCreator: \"fbxcel\"\n";
+
+    #[test]
+    fn detect_and_parse() {
+        let binary = AsciiDocument::parse(SOURCE).unwrap().to_binary().unwrap();
+        let sources = [
+            (binary, Format::Binary),
+            (SOURCE.as_bytes().to_vec(), Format::Ascii),
+            (format!("\u{feff}\r\n\n{}", SOURCE).into_bytes(), Format::Ascii),
+        ];
+        for (source, format) in &sources {
+            assert_eq!(detect_format(Cursor::new(source)).unwrap(), Some(*format));
+            let name = match from_reader(Cursor::new(source)).unwrap() {
+                AnyParser::Binary(parser) => first_node_name(parser),
+                AnyParser::Ascii(parser) => first_node_name(parser),
+            };
+            assert_eq!(name, "Creator");
+        }
+    }
+
+    #[test]
+    fn unknown_format() {
+        let sources: [&[u8]; 4] = [b"", b"Creator: \"fbxcel\"\n", b"\x89PNG\r\n\x1a\n", b"; FB"];
+        for source in &sources {
+            assert_eq!(detect_format(Cursor::new(source)).unwrap(), None);
+            match from_reader(Cursor::new(source)) {
+                Err(Error::UnknownFormat) => {},
+                res => panic!("Unexpected result: {:?}", res.map(|p| p.format())),
+            }
+        }
+    }
+}
@@ -0,0 +1,194 @@
+//! Encoding of FBX ASCII documents as FBX binary.
+//!
+//! `AsciiParser` reads the encoded binary with `RootParser`, so the loaders consume ASCII files
+//! through the same events as binary files.
+//! Arrays are not compressed, and the layout (null records and the footer) follows the
+//! convention of the official SDK, as the binary writer does by default.
+
+use pull_parser::OwnedAttribute;
+use pull_parser::ascii::{AsciiDocument, AsciiNode, Error, Result};
+use pull_parser::ascii::transcode::typed_attributes;
+use pull_parser::limits::MAX_NODE_NAME_LEN;
+
+
+/// Magic binary at the head of FBX binary files.
+const MAGIC: &[u8; 21] = b"Kaydara FBX Binary  \x00";
+
+/// Unknown part 1 of the footer, taken from an SDK-written file.
+const FOOTER_UNKNOWN1: [u8; 16] = [
+    0xfa, 0xbc, 0xab, 0x09, 0xd0, 0xc8, 0xd4, 0x66, 0xb1, 0x76, 0xfb, 0x83, 0x1c, 0xf7, 0x26, 0x7e,
+];
+
+/// Unknown part 2 of the footer, which is written by the official SDK.
+const FOOTER_UNKNOWN2: [u8; 16] = [
+    0xf8, 0x5a, 0x8c, 0x6a, 0xde, 0xf5, 0xd9, 0x7e, 0xec, 0xe9, 0x0c, 0xe3, 0x75, 0x8f, 0x29, 0x0b,
+];
+
+
+/// Encodes the document as uncompressed FBX binary.
+///
+/// Supported versions are `6000..7600`.
+pub fn encode(doc: &AsciiDocument) -> Result<Vec<u8>> {
+    if !(6000..7600).contains(&doc.version) {
+        return Err(Error::Transcode(format!("Unsupported FBX version: {}", doc.version)));
+    }
+    let mut encoder = Encoder {
+        buf: Vec::new(),
+        version: doc.version,
+    };
+    encoder.buf.extend_from_slice(MAGIC);
+    encoder.buf.extend_from_slice(&[0x1a, 0x00]);
+    encoder.buf.extend_from_slice(&doc.version.to_le_bytes());
+    for node in &doc.nodes {
+        encoder.encode_node(node, "")?;
+    }
+    encoder.finish()
+}
+
+
+/// FBX binary encoder.
+struct Encoder {
+    /// Encoded bytes.
+    buf: Vec<u8>,
+    /// FBX version.
+    version: u32,
+}
+
+impl Encoder {
+    /// Encodes the node and its descendants.
+    fn encode_node(&mut self, node: &AsciiNode, parent: &str) -> Result<()> {
+        if node.name.len() > MAX_NODE_NAME_LEN {
+            return Err(Error::Transcode(format!(
+                "Node name is too long ({} bytes): {:?}",
+                node.name.len(),
+                node.name
+            )));
+        }
+        let attributes = typed_attributes(node, parent);
+        let mut attrs_buf = Vec::new();
+        for (index, attr) in attributes.iter().enumerate() {
+            if attr.payload_len() > u64::from(u32::MAX) {
+                return Err(Error::Transcode(format!(
+                    "Attribute {} of node `{}` is too large",
+                    index, node.name
+                )));
+            }
+            encode_attribute(&mut attrs_buf, attr);
+        }
+
+        let header_pos = self.buf.len();
+        // End offset is patched after the children are encoded.
+        self.write_header_value(0);
+        self.write_header_value(attributes.len() as u64);
+        self.write_header_value(attrs_buf.len() as u64);
+        self.buf.push(node.name.len() as u8);
+        self.buf.extend_from_slice(node.name.as_bytes());
+        self.buf.extend_from_slice(&attrs_buf);
+        for child in &node.children {
+            self.encode_node(child, &node.name)?;
+        }
+        if !node.children.is_empty() || attributes.is_empty() {
+            self.write_null_record();
+        }
+
+        let end = self.buf.len() as u64;
+        if self.version >= 7500 {
+            self.buf[header_pos..header_pos + 8].copy_from_slice(&end.to_le_bytes());
+        } else if end <= u64::from(u32::MAX) {
+            self.buf[header_pos..header_pos + 4].copy_from_slice(&(end as u32).to_le_bytes());
+        } else {
+            return Err(Error::Transcode(format!(
+                "Node `{}` ends at {}, which is too large for FBX version {}",
+                node.name, end, self.version
+            )));
+        }
+        Ok(())
+    }
+
+    /// Writes the end of the implicit root node and the FBX footer.
+    fn finish(mut self) -> Result<Vec<u8>> {
+        self.write_null_record();
+        self.buf.extend_from_slice(&FOOTER_UNKNOWN1);
+        let padding_len = (16 - (self.buf.len() & 0x0f)) & 0x0f;
+        self.buf.resize(self.buf.len() + padding_len + 4, 0);
+        self.buf.extend_from_slice(&self.version.to_le_bytes());
+        self.buf.resize(self.buf.len() + 120, 0);
+        self.buf.extend_from_slice(&FOOTER_UNKNOWN2);
+        Ok(self.buf)
+    }
+
+    /// Writes an offset or a length in a node header.
+    fn write_header_value(&mut self, v: u64) {
+        if self.version >= 7500 {
+            self.buf.extend_from_slice(&v.to_le_bytes());
+        } else {
+            self.buf.extend_from_slice(&(v as u32).to_le_bytes());
+        }
+    }
+
+    /// Writes a null node record.
+    fn write_null_record(&mut self) {
+        let len = if self.version >= 7500 { 25 } else { 13 };
+        self.buf.resize(self.buf.len() + len, 0);
+    }
+}
+
+
+/// Encodes an attribute into the buffer.
+fn encode_attribute(buf: &mut Vec<u8>, attr: &OwnedAttribute) {
+    match *attr {
+        OwnedAttribute::Bool(v) => {
+            buf.push(b'C');
+            buf.push(if v { b'Y' } else { b'T' });
+        },
+        OwnedAttribute::I16(v) => primitive(buf, b'Y', &v.to_le_bytes()),
+        OwnedAttribute::I32(v) => primitive(buf, b'I', &v.to_le_bytes()),
+        OwnedAttribute::I64(v) => primitive(buf, b'L', &v.to_le_bytes()),
+        OwnedAttribute::F32(v) => primitive(buf, b'F', &v.to_le_bytes()),
+        OwnedAttribute::F64(v) => primitive(buf, b'D', &v.to_le_bytes()),
+        OwnedAttribute::ArrBool(ref v) => {
+            let bytes = v.iter().map(|&v| v as u8).collect::<Vec<_>>();
+            array(buf, b'b', v.len(), &bytes);
+        },
+        OwnedAttribute::ArrI32(ref v) => {
+            array(buf, b'i', v.len(), &v.iter().flat_map(|v| v.to_le_bytes()).collect::<Vec<_>>());
+        },
+        OwnedAttribute::ArrI64(ref v) => {
+            array(buf, b'l', v.len(), &v.iter().flat_map(|v| v.to_le_bytes()).collect::<Vec<_>>());
+        },
+        OwnedAttribute::ArrF32(ref v) => {
+            array(buf, b'f', v.len(), &v.iter().flat_map(|v| v.to_le_bytes()).collect::<Vec<_>>());
+        },
+        OwnedAttribute::ArrF64(ref v) => {
+            array(buf, b'd', v.len(), &v.iter().flat_map(|v| v.to_le_bytes()).collect::<Vec<_>>());
+        },
+        OwnedAttribute::String(Ok(ref v)) => special(buf, b'S', v.as_bytes()),
+        OwnedAttribute::String(Err(ref v)) => special(buf, b'S', v),
+        OwnedAttribute::Binary(ref v) => special(buf, b'R', v),
+    }
+}
+
+
+/// Encodes a primitive attribute into the buffer.
+fn primitive(buf: &mut Vec<u8>, type_code: u8, bytes: &[u8]) {
+    buf.push(type_code);
+    buf.extend_from_slice(bytes);
+}
+
+
+/// Encodes an uncompressed array attribute into the buffer.
+fn array(buf: &mut Vec<u8>, type_code: u8, num_elements: usize, bytes: &[u8]) {
+    buf.push(type_code);
+    buf.extend_from_slice(&(num_elements as u32).to_le_bytes());
+    buf.extend_from_slice(&0u32.to_le_bytes());
+    buf.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    buf.extend_from_slice(bytes);
+}
+
+
+/// Encodes a special (string or binary) attribute into the buffer.
+fn special(buf: &mut Vec<u8>, type_code: u8, bytes: &[u8]) {
+    buf.push(type_code);
+    buf.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    buf.extend_from_slice(bytes);
+}
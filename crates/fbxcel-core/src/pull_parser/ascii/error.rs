@@ -0,0 +1,144 @@
+//! FBX ASCII parsing error.
+
+use std::error;
+use std::fmt;
+use std::io;
+
+
+/// Result of ASCII parser function.
+pub type Result<T> = ::std::result::Result<T, Error>;
+
+
+/// FBX ASCII parsing error.
+#[derive(Debug)]
+pub enum Error {
+    /// Array length differs from the declared one.
+    ArrayLengthMismatch {
+        /// Declared length.
+        declared: usize,
+        /// Actual length.
+        actual: usize,
+        /// Line number (1-based).
+        line: usize,
+    },
+    /// Source is not valid UTF-8 text.
+    InvalidUtf8,
+    /// Number literal is invalid.
+    InvalidNumber {
+        /// Literal.
+        literal: String,
+        /// Line number (1-based).
+        line: usize,
+    },
+    /// FBX version in `FBXHeaderExtension` is out of range.
+    InvalidVersion(i64),
+    /// I/O error.
+    Io(io::Error),
+    /// FBX version is found neither in the header comment nor in `FBXHeaderExtension`.
+    MissingVersion,
+    /// Nodes are nested too deeply.
+    NodeTooDeep {
+        /// Line number (1-based).
+        line: usize,
+    },
+    /// Encoding into FBX binary failed.
+    Transcode(String),
+    /// Unexpected character.
+    UnexpectedChar {
+        /// Got character.
+        got: char,
+        /// Line number (1-based).
+        line: usize,
+    },
+    /// Unexpected end of the source.
+    UnexpectedEof,
+    /// Unexpected token.
+    UnexpectedToken {
+        /// Description of the expected token.
+        expected: &'static str,
+        /// Line number (1-based).
+        line: usize,
+    },
+    /// Source is neither FBX binary nor FBX ASCII.
+    ///
+    /// This is returned only by `pull_parser::any::from_reader()`.
+    UnknownFormat,
+}
+
+impl Error {
+    /// Returns the stable numeric code of the error.
+    ///
+    /// ASCII parser error codes are in the range `4000..5000`.
+    /// Codes are never reused nor changed, so they can be used by FFI wrappers and logging
+    /// systems.
+    pub fn code(&self) -> u32 {
+        match *self {
+            Error::ArrayLengthMismatch { .. } => 4000,
+            Error::InvalidUtf8 => 4001,
+            Error::InvalidNumber { .. } => 4002,
+            Error::Io(_) => 4003,
+            Error::MissingVersion => 4004,
+            Error::UnexpectedChar { .. } => 4005,
+            Error::UnexpectedEof => 4006,
+            Error::UnexpectedToken { .. } => 4007,
+            Error::Transcode(_) => 4008,
+            Error::InvalidVersion(_) => 4009,
+            Error::NodeTooDeep { .. } => 4010,
+            Error::UnknownFormat => 4011,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::ArrayLengthMismatch {
+                declared,
+                actual,
+                line,
+            } => {
+                write!(
+                    f,
+                    "Array has {} elements but {} are declared (line {})",
+                    actual,
+                    declared,
+                    line
+                )
+            },
+            Error::InvalidNumber { ref literal, line } => {
+                write!(f, "Invalid number {:?} (line {})", literal, line)
+            },
+            Error::InvalidUtf8 => write!(f, "Source is not valid UTF-8 text"),
+            Error::InvalidVersion(ver) => write!(f, "Invalid FBX version: {}", ver),
+            Error::Io(ref err) => write!(f, "I/O error: {}", err),
+            Error::MissingVersion => write!(f, "FBX version not found"),
+            Error::NodeTooDeep { line } => write!(f, "Nodes are nested too deeply (line {})", line),
+            Error::Transcode(ref reason) => {
+                write!(f, "Transcoding into FBX binary failed: {}", reason)
+            },
+            Error::UnexpectedChar { got, line } => {
+                write!(f, "Unexpected character {:?} (line {})", got, line)
+            },
+            Error::UnexpectedEof => write!(f, "Unexpected end of the source"),
+            Error::UnexpectedToken { expected, line } => {
+                write!(f, "Expected {} (line {})", expected, line)
+            },
+            Error::UnknownFormat => write!(f, "Source is neither FBX binary nor FBX ASCII"),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Io(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
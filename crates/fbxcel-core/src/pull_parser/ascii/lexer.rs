@@ -0,0 +1,173 @@
+//! Tokenizer of FBX ASCII.
+
+use pull_parser::ascii::error::{Error, Result};
+
+
+/// Token of FBX ASCII.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    /// Node name followed by a colon (`Name:`).
+    Key(String),
+    /// Bare identifier (such as `Y` and `T`).
+    Ident(String),
+    /// Quoted string.
+    Str(String),
+    /// Number literal.
+    Number(String),
+    /// Array length (`*N`).
+    ArrayLen(usize),
+    /// `{`.
+    OpenBrace,
+    /// `}`.
+    CloseBrace,
+    /// `,`.
+    Comma,
+}
+
+
+/// Tokenizer of FBX ASCII.
+#[derive(Debug, Clone)]
+pub struct Lexer<'a> {
+    /// Source text.
+    source: &'a str,
+    /// Current byte position.
+    pos: usize,
+    /// Current line number (1-based).
+    line: usize,
+    /// Peeked token and its line number.
+    peeked: Option<Option<(Token, usize)>>,
+}
+
+impl<'a> Lexer<'a> {
+    /// Creates a new `Lexer`.
+    pub fn new(source: &'a str) -> Self {
+        Lexer {
+            source,
+            pos: 0,
+            line: 1,
+            peeked: None,
+        }
+    }
+
+    /// Returns the next token without consuming it.
+    pub fn peek(&mut self) -> Result<Option<&(Token, usize)>> {
+        if self.peeked.is_none() {
+            let token = self.read_token()?;
+            self.peeked = Some(token);
+        }
+        Ok(self.peeked.as_ref().and_then(|v| v.as_ref()))
+    }
+
+    /// Returns the next token and its line number.
+    pub fn next_token(&mut self) -> Result<Option<(Token, usize)>> {
+        match self.peeked.take() {
+            Some(token) => Ok(token),
+            None => self.read_token(),
+        }
+    }
+
+    /// Returns the length of the unread source in bytes.
+    pub fn remaining_len(&self) -> usize {
+        self.source.len() - self.pos
+    }
+
+    /// Returns the next character without consuming it.
+    fn peek_char(&self) -> Option<char> {
+        self.source[self.pos..].chars().next()
+    }
+
+    /// Consumes the next character.
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek_char()?;
+        self.pos += c.len_utf8();
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    /// Consumes characters while the predicate returns `true`, and returns them.
+    fn take_while<F: Fn(char) -> bool>(&mut self, f: F) -> &'a str {
+        let start = self.pos;
+        while self.peek_char().is_some_and(&f) {
+            self.bump();
+        }
+        &self.source[start..self.pos]
+    }
+
+    /// Skips whitespaces and comments.
+    fn skip_trivia(&mut self) {
+        loop {
+            match self.peek_char() {
+                Some(c) if c.is_whitespace() => {
+                    self.bump();
+                },
+                Some(';') => {
+                    self.take_while(|c| c != '\n');
+                },
+                _ => return,
+            }
+        }
+    }
+
+    /// Reads a token from the source.
+    fn read_token(&mut self) -> Result<Option<(Token, usize)>> {
+        self.skip_trivia();
+        let line = self.line;
+        let c = match self.peek_char() {
+            Some(c) => c,
+            None => return Ok(None),
+        };
+        let token = match c {
+            '{' => {
+                self.bump();
+                Token::OpenBrace
+            },
+            '}' => {
+                self.bump();
+                Token::CloseBrace
+            },
+            ',' => {
+                self.bump();
+                Token::Comma
+            },
+            '"' => {
+                self.bump();
+                let s = self.take_while(|c| c != '"');
+                if self.bump().is_none() {
+                    return Err(Error::UnexpectedEof);
+                }
+                Token::Str(s.to_owned())
+            },
+            '*' => {
+                self.bump();
+                let digits = self.take_while(|c| c.is_ascii_digit());
+                let len = digits.parse().map_err(|_| {
+                    Error::InvalidNumber {
+                        literal: format!("*{}", digits),
+                        line,
+                    }
+                })?;
+                Token::ArrayLen(len)
+            },
+            c if c.is_ascii_digit() || c == '-' || c == '+' || c == '.' => {
+                let s = self.take_while(|c| c.is_ascii_alphanumeric() || "+-.#".contains(c));
+                Token::Number(s.to_owned())
+            },
+            c if c.is_alphabetic() || c == '_' => {
+                let s = self.take_while(|c| c.is_alphanumeric() || c == '_' || c == '|');
+                // Keys may be separated from colons by spaces, but not by newlines.
+                let rest = &self.source[self.pos..];
+                let trimmed = rest.trim_start_matches([' ', '\t']);
+                if trimmed.starts_with(':') {
+                    self.pos += rest.len() - trimmed.len() + 1;
+                    Token::Key(s.to_owned())
+                } else {
+                    Token::Ident(s.to_owned())
+                }
+            },
+            c => return Err(Error::UnexpectedChar { got: c, line }),
+        };
+        Ok(Some((token, line)))
+    }
+}
@@ -0,0 +1,430 @@
+//! FBX ASCII parser.
+//!
+//! FBX ASCII files are parsed into a tree of `AsciiNode`s.
+//! `AsciiParser` encodes the tree as FBX binary in memory and emits the same `Event`s as
+//! `pull_parser::RootParser`, so the loaders can consume ASCII files transparently.
+//!
+//! ASCII files don't have attribute types, so they are guessed from node names, property types
+//! of `P` nodes, and literals (see `transcode`).
+
+use std::convert::TryFrom;
+use std::io::{self, Read};
+use pull_parser::{OwnedAttribute, SeekableSource, Event, Parser, RootParser, SubtreeParser};
+use pull_parser::Result as BinaryResult;
+
+pub use self::error::{Error, Result};
+use self::lexer::{Lexer, Token};
+
+mod encode;
+mod error;
+mod lexer;
+mod transcode;
+
+
+/// Max depth of nested nodes.
+///
+/// Nodes are parsed recursively, so deeper nesting is rejected to avoid stack overflow.
+/// This is twice `limits::MAX_NODE_DEPTH`, the limit known to be safe for FBX SDK.
+pub const MAX_NESTING_DEPTH: usize = 256;
+
+
+/// Source of the parser which reads the transcoded FBX binary.
+pub type AsciiSource = SeekableSource<io::Cursor<Vec<u8>>>;
+
+
+/// Attribute value of an FBX ASCII node.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AsciiValue {
+    /// Integer.
+    Int(i64),
+    /// Floating point number.
+    Float(f64),
+    /// Quoted string.
+    String(String),
+    /// Bare identifier (such as `Y` and `T`).
+    Ident(String),
+    /// Array of integers (`*N { a: ... }`).
+    IntArray(Vec<i64>),
+    /// Array of numbers with at least one floating point number.
+    FloatArray(Vec<f64>),
+}
+
+
+/// FBX ASCII node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AsciiNode {
+    /// Name.
+    pub name: String,
+    /// Attributes.
+    pub attributes: Vec<AsciiValue>,
+    /// Child nodes.
+    pub children: Vec<AsciiNode>,
+}
+
+impl AsciiNode {
+    /// Returns the attributes with the types guessed from the node name, the name of the parent
+    /// node (empty for toplevel nodes), and the literals.
+    pub fn typed_attributes(&self, parent: &str) -> Vec<OwnedAttribute> {
+        transcode::typed_attributes(self, parent)
+    }
+}
+
+
+/// FBX ASCII document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AsciiDocument {
+    /// FBX version.
+    pub version: u32,
+    /// Top-level nodes.
+    pub nodes: Vec<AsciiNode>,
+}
+
+impl AsciiDocument {
+    /// Parses the FBX ASCII source.
+    ///
+    /// A leading UTF-8 BOM is ignored.
+    pub fn parse(source: &str) -> Result<Self> {
+        let source = source.strip_prefix('\u{feff}').unwrap_or(source);
+        let mut lexer = Lexer::new(source);
+        let mut nodes = Vec::new();
+        while lexer.peek()?.is_some() {
+            nodes.push(parse_node(&mut lexer, 1)?);
+        }
+        let version = match header_extension_version(&nodes)? {
+            Some(v) => v,
+            None => header_comment_version(source).ok_or(Error::MissingVersion)?,
+        };
+        Ok(AsciiDocument {
+            version,
+            nodes,
+        })
+    }
+
+    /// Reads and parses the FBX ASCII source.
+    pub fn read_from<R: Read>(mut source: R) -> Result<Self> {
+        let mut bytes = Vec::new();
+        source.read_to_end(&mut bytes)?;
+        let text = String::from_utf8(bytes).map_err(|_| Error::InvalidUtf8)?;
+        Self::parse(&text)
+    }
+
+    /// Encodes the document as uncompressed FBX binary.
+    ///
+    /// Returns `Err(Error::Transcode(_))` if the version is not in `6000..7600`, or the document
+    /// is too large for the version.
+    pub fn to_binary(&self) -> Result<Vec<u8>> {
+        encode::encode(self)
+    }
+}
+
+
+/// FBX ASCII parser.
+///
+/// This reads the whole source on creation, and emits the same `Event`s as the binary parser.
+#[derive(Debug)]
+pub struct AsciiParser {
+    /// Parser of the transcoded binary.
+    inner: RootParser<AsciiSource>,
+}
+
+impl AsciiParser {
+    /// Creates a new `AsciiParser` from the source.
+    pub fn new<R: Read>(source: R) -> Result<Self> {
+        Self::from_document(&AsciiDocument::read_from(source)?)
+    }
+
+    /// Creates a new `AsciiParser` from the parsed document.
+    pub fn from_document(doc: &AsciiDocument) -> Result<Self> {
+        let bytes = doc.to_binary()?;
+        Ok(AsciiParser { inner: RootParser::from_seekable(io::Cursor::new(bytes)) })
+    }
+
+    /// Returns the parser of the transcoded binary.
+    pub fn into_inner(self) -> RootParser<AsciiSource> {
+        self.inner
+    }
+}
+
+impl Parser<AsciiSource> for AsciiParser {
+    fn root_parser(&self) -> &RootParser<AsciiSource> {
+        &self.inner
+    }
+
+    fn next_event(&mut self) -> BinaryResult<Event<'_, AsciiSource>> {
+        self.inner.next_event()
+    }
+
+    fn skip_current_node(&mut self) -> BinaryResult<bool> {
+        self.inner.skip_current_node()
+    }
+
+    fn subtree_parser(&mut self) -> SubtreeParser<'_, AsciiSource> {
+        self.inner.subtree_parser()
+    }
+}
+
+
+/// Parses a node at the given depth (`1` for toplevel nodes).
+fn parse_node(lexer: &mut Lexer<'_>, depth: usize) -> Result<AsciiNode> {
+    let name = match lexer.next_token()? {
+        Some((Token::Key(_), line)) if depth > MAX_NESTING_DEPTH => {
+            return Err(Error::NodeTooDeep { line })
+        },
+        Some((Token::Key(name), _)) => name,
+        Some((_, line)) => {
+            return Err(Error::UnexpectedToken {
+                expected: "node name",
+                line,
+            })
+        },
+        None => return Err(Error::UnexpectedEof),
+    };
+    let mut attributes = Vec::new();
+    let mut children = Vec::new();
+    if lexer.peek()?.is_some_and(is_value) {
+        attributes.push(parse_value(lexer)?);
+        while lexer.peek()?.is_some_and(|(t, _)| *t == Token::Comma) {
+            lexer.next_token()?;
+            attributes.push(parse_value(lexer)?);
+        }
+    }
+    if lexer.peek()?.is_some_and(|(t, _)| *t == Token::OpenBrace) {
+        lexer.next_token()?;
+        loop {
+            match lexer.peek()? {
+                Some(&(Token::CloseBrace, _)) => {
+                    lexer.next_token()?;
+                    break;
+                },
+                Some(_) => children.push(parse_node(lexer, depth + 1)?),
+                None => return Err(Error::UnexpectedEof),
+            }
+        }
+    }
+    Ok(AsciiNode {
+        name,
+        attributes,
+        children,
+    })
+}
+
+
+/// Returns `true` if the token starts an attribute value.
+fn is_value(token: &(Token, usize)) -> bool {
+    matches!(
+        token.0,
+        Token::Str(_) | Token::Number(_) | Token::Ident(_) | Token::ArrayLen(_)
+    )
+}
+
+
+/// Parses an attribute value.
+fn parse_value(lexer: &mut Lexer<'_>) -> Result<AsciiValue> {
+    let (token, line) = lexer.next_token()?.ok_or(Error::UnexpectedEof)?;
+    match token {
+        Token::Str(s) => Ok(AsciiValue::String(s)),
+        Token::Number(s) => parse_number(&s, line),
+        Token::Ident(s) => {
+            match s.as_str() {
+                "inf" | "nan" => parse_number(&s, line),
+                _ => Ok(AsciiValue::Ident(s)),
+            }
+        },
+        Token::ArrayLen(len) => parse_array(lexer, len, line),
+        _ => {
+            Err(Error::UnexpectedToken {
+                expected: "attribute value",
+                line,
+            })
+        },
+    }
+}
+
+
+/// Parses a number literal.
+fn parse_number(literal: &str, line: usize) -> Result<AsciiValue> {
+    if let Ok(v) = literal.parse() {
+        return Ok(AsciiValue::Int(v));
+    }
+    literal.parse().map(AsciiValue::Float).map_err(|_| {
+        Error::InvalidNumber {
+            literal: literal.to_owned(),
+            line,
+        }
+    })
+}
+
+
+/// Parses an array body (`{ a: ... }`) after `*N`.
+fn parse_array(lexer: &mut Lexer<'_>, len: usize, line: usize) -> Result<AsciiValue> {
+    match lexer.next_token()? {
+        Some((Token::OpenBrace, _)) => {},
+        _ => {
+            return Err(Error::UnexpectedToken {
+                expected: "`{` of an array",
+                line,
+            })
+        },
+    }
+    // The declared length is not trusted: each element takes at least two bytes (a digit and a
+    // separator), so larger reservation would never be filled.
+    let mut values = Vec::with_capacity(len.min(lexer.remaining_len() / 2 + 1));
+    let mut is_int = true;
+    loop {
+        let (token, line) = lexer.next_token()?.ok_or(Error::UnexpectedEof)?;
+        match token {
+            Token::CloseBrace => break,
+            Token::Key(ref key) if key == "a" && values.is_empty() => {},
+            Token::Comma if !values.is_empty() => {},
+            Token::Number(s) => {
+                match parse_number(&s, line)? {
+                    AsciiValue::Int(v) => values.push((v, v as f64)),
+                    AsciiValue::Float(v) => {
+                        is_int = false;
+                        values.push((0, v));
+                    },
+                    _ => unreachable!("`parse_number()` should return a number"),
+                }
+            },
+            _ => {
+                return Err(Error::UnexpectedToken {
+                    expected: "array element",
+                    line,
+                })
+            },
+        }
+    }
+    if values.len() != len {
+        return Err(Error::ArrayLengthMismatch {
+            declared: len,
+            actual: values.len(),
+            line,
+        });
+    }
+    if is_int {
+        Ok(AsciiValue::IntArray(values.into_iter().map(|v| v.0).collect()))
+    } else {
+        Ok(AsciiValue::FloatArray(values.into_iter().map(|v| v.1).collect()))
+    }
+}
+
+
+/// Returns the version in `FBXHeaderExtension/FBXVersion`.
+///
+/// Returns `Err(Error::InvalidVersion(_))` if the version doesn't fit in `u32`.
+fn header_extension_version(nodes: &[AsciiNode]) -> Result<Option<u32>> {
+    let version = nodes
+        .iter()
+        .find(|node| node.name == "FBXHeaderExtension")
+        .and_then(|ext| ext.children.iter().find(|node| node.name == "FBXVersion"))
+        .and_then(|node| node.attributes.first());
+    match version {
+        Some(&AsciiValue::Int(v)) => {
+            u32::try_from(v).map(Some).map_err(|_| Error::InvalidVersion(v))
+        },
+        _ => Ok(None),
+    }
+}
+
+
+/// Returns the version in the header comment (such as `; FBX 7.4.0 project file`).
+fn header_comment_version(source: &str) -> Option<u32> {
+    let line = source.lines().find(|line| !line.trim().is_empty())?;
+    let line = line.trim_start().strip_prefix(';')?.trim();
+    let ver = line.strip_prefix("FBX ")?.split_whitespace().next()?;
+    let mut parts = ver.split('.').map(|s| s.parse::<u32>());
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(Ok(major)), Some(Ok(minor)), Some(Ok(patch))) => {
+            major
+                .checked_mul(1000)?
+                .checked_add(minor.checked_mul(100)?)?
+                .checked_add(patch.checked_mul(10)?)
+        },
+        _ => None,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use pull_parser::FbxFooter;
+    use super::*;
+
+    /// Loads the names and the attributes of all nodes in document order, and the footer.
+    fn load_nodes(parser: &mut AsciiParser) -> (Vec<(String, Vec<OwnedAttribute>)>, FbxFooter) {
+        let mut nodes = Vec::new();
+        loop {
+            match parser.next_event().unwrap() {
+                Event::StartNode(node) => {
+                    let name = node.name().unwrap().to_owned();
+                    let attrs = OwnedAttribute::load_attrs_from_parser_event(node.attributes);
+                    nodes.push((name, attrs.unwrap()));
+                },
+                Event::EndFbx(footer) => return (nodes, footer.unwrap()),
+                Event::StartFbx(_) | Event::EndNode => {},
+            }
+        }
+    }
+
+    const SOURCE: &str = r#"; FBX 7.4.0 project file
+; ----------------------------------------------------
+
+FBXHeaderExtension:  {
+    FBXHeaderVersion: 1003
+    FBXVersion: 7400
+}
+GlobalSettings:  {
+    Version: 1000
+    Properties70:  {
+        P: "UpAxis", "int", "Integer", "",1
+        P: "UnitScaleFactor", "double", "Number", "",100
+    }
+}
+Objects:  {
+    Geometry: 140000, "Geometry::Cube", "Mesh" {
+        Vertices: *6 {
+            a: 0,0,0,1.5,-2,3e2
+        }
+        PolygonVertexIndex: *3 {
+            a: 0,1,-3
+        }
+    }
+}
+Connections:  {
+    C: "OO",140000,0
+}
+"#;
+
+    #[test]
+    fn parse_tree() {
+        let doc = AsciiDocument::parse(SOURCE).unwrap();
+        assert_eq!(doc.version, 7400);
+        let geometry = &doc.nodes[2].children[0];
+        assert_eq!(geometry.attributes[0], AsciiValue::Int(140000));
+        assert_eq!(
+            geometry.children[0].attributes[0],
+            AsciiValue::FloatArray(vec![0.0, 0.0, 0.0, 1.5, -2.0, 300.0])
+        );
+        assert_eq!(geometry.children[1].attributes[0], AsciiValue::IntArray(vec![0, 1, -3]));
+    }
+
+    #[test]
+    fn load_transcoded() {
+        let mut parser = AsciiParser::new(SOURCE.as_bytes()).unwrap();
+        let (nodes, footer) = load_nodes(&mut parser);
+        assert_eq!(footer.version, 7400);
+        let names = nodes.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names[8..12], ["Objects", "Geometry", "Vertices", "PolygonVertexIndex"]);
+        assert_eq!(names.len(), 14);
+        assert_eq!(nodes[6].1[4], OwnedAttribute::I32(1));
+        assert_eq!(nodes[7].1[4], OwnedAttribute::F64(100.0));
+        let geometry = &nodes[9].1;
+        assert_eq!(geometry[0], OwnedAttribute::I64(140000));
+        assert_eq!(
+            geometry[1],
+            OwnedAttribute::String(Ok("Cube\u{0}\u{1}Geometry".to_owned()))
+        );
+        assert_eq!(nodes[11].1[0], OwnedAttribute::ArrI32(vec![0, 1, -3].into_boxed_slice()));
+        assert_eq!(nodes[13].1[2], OwnedAttribute::I64(0));
+    }
+}
@@ -0,0 +1,235 @@
+//! Typing of FBX ASCII attributes.
+//!
+//! Attribute types are guessed as below:
+//!
+//! - Values of `P` nodes (and FBX 6.x `Property` nodes) are typed by the property type (the
+//!   second attribute).
+//! - Object IDs (the first attribute of object nodes) and IDs in `C` nodes are `i64`.
+//! - Arrays are typed by well-known node names (such as `Vertices` and `KeyTime`), or by the
+//!   literals.
+//! - Other integers are `i32` if they fit, and floating point numbers are `f64`.
+//! - Strings in `Content` nodes are decoded as base64 binaries.
+//! - Identifiers `Y` and `T` are booleans (`true` and `false` respectively).
+
+use pull_parser::OwnedAttribute;
+use pull_parser::ascii::{AsciiNode, AsciiValue};
+
+
+/// `P` property types with `i32` values.
+const I32_PROPERTY_TYPES: &[&str] = &[
+    "int",
+    "Integer",
+    "enum",
+    "Enum",
+    "bool",
+    "Bool",
+    "Visibility Inheritance",
+];
+
+/// `P` property types with `i64` values.
+const I64_PROPERTY_TYPES: &[&str] = &["KTime", "ULongLong", "LongLong"];
+
+/// Nodes with `i64` scalar values.
+const I64_NODES: &[&str] = &[
+    "LocalTime",
+    "ReferenceTime",
+    "LocalStart",
+    "LocalStop",
+    "ReferenceStart",
+    "ReferenceStop",
+];
+
+/// Nodes with `f64` scalar values.
+const F64_NODES: &[&str] = &[
+    "Default",
+    "ModelUVScaling",
+    "ModelUVTranslation",
+    "DeformPercent",
+    "Link_DeformAcuracy",
+];
+
+/// Nodes with `i64` arrays.
+const I64_ARRAYS: &[&str] = &["KeyTime"];
+
+/// Nodes with `f32` arrays.
+const F32_ARRAYS: &[&str] = &["KeyValueFloat", "KeyAttrDataFloat"];
+
+/// Nodes with `f64` arrays.
+const F64_ARRAYS: &[&str] = &[
+    "Vertices",
+    "Normals",
+    "NormalsW",
+    "Binormals",
+    "BinormalsW",
+    "Tangents",
+    "TangentsW",
+    "UV",
+    "Colors",
+    "Weights",
+    "Transform",
+    "TransformLink",
+    "TransformAssociateModel",
+    "Matrix",
+    "Points",
+    "KnotVector",
+    "KnotVectorU",
+    "KnotVectorV",
+    "FullWeights",
+];
+
+
+/// Returns the typed attributes of the node.
+///
+/// `parent` is the name of the parent node (empty for toplevel nodes).
+pub fn typed_attributes(node: &AsciiNode, parent: &str) -> Vec<OwnedAttribute> {
+    let property_type = match node.attributes.get(1) {
+        Some(AsciiValue::String(s)) if node.name == "P" || node.name == "Property" => {
+            Some(s.as_str())
+        },
+        _ => None,
+    };
+    node.attributes
+        .iter()
+        .enumerate()
+        .map(|(index, value)| {
+            let attr = AttributeContext {
+                parent,
+                node: &node.name,
+                index,
+                property_type,
+            };
+            attr.convert(value)
+        })
+        .collect()
+}
+
+
+/// Position of an attribute, which determines its type.
+struct AttributeContext<'a> {
+    /// Name of the parent node.
+    parent: &'a str,
+    /// Name of the node.
+    node: &'a str,
+    /// Index of the attribute.
+    index: usize,
+    /// Property type if the node is `P` or `Property`.
+    property_type: Option<&'a str>,
+}
+
+impl<'a> AttributeContext<'a> {
+    /// Converts the value into a typed attribute.
+    fn convert(&self, value: &AsciiValue) -> OwnedAttribute {
+        match *value {
+            AsciiValue::Int(v) => self.convert_int(v),
+            AsciiValue::Float(v) => OwnedAttribute::F64(v),
+            AsciiValue::String(ref s) => {
+                if self.node == "Content" {
+                    if let Some(bytes) = decode_base64(s) {
+                        return OwnedAttribute::Binary(bytes.into());
+                    }
+                }
+                if self.is_name_class() {
+                    if let Some(sep) = s.find("::") {
+                        let name_class = format!("{}\u{0}\u{1}{}", &s[sep + 2..], &s[..sep]);
+                        return OwnedAttribute::String(Ok(name_class));
+                    }
+                }
+                OwnedAttribute::String(Ok(s.clone()))
+            },
+            AsciiValue::Ident(ref s) => {
+                match s.as_str() {
+                    "Y" => OwnedAttribute::Bool(true),
+                    "T" => OwnedAttribute::Bool(false),
+                    _ => OwnedAttribute::String(Ok(s.clone())),
+                }
+            },
+            AsciiValue::IntArray(ref values) => {
+                let fits_i32 = values.iter().all(|&v| v as i32 as i64 == v);
+                if F64_ARRAYS.contains(&self.node) {
+                    OwnedAttribute::ArrF64(values.iter().map(|&v| v as f64).collect())
+                } else if F32_ARRAYS.contains(&self.node) {
+                    OwnedAttribute::ArrF32(values.iter().map(|&v| v as f32).collect())
+                } else if fits_i32 && !I64_ARRAYS.contains(&self.node) {
+                    OwnedAttribute::ArrI32(values.iter().map(|&v| v as i32).collect())
+                } else {
+                    OwnedAttribute::ArrI64(values.clone().into())
+                }
+            },
+            AsciiValue::FloatArray(ref values) => {
+                if F32_ARRAYS.contains(&self.node) {
+                    OwnedAttribute::ArrF32(values.iter().map(|&v| v as f32).collect())
+                } else {
+                    OwnedAttribute::ArrF64(values.clone().into())
+                }
+            },
+        }
+    }
+
+    /// Converts the integer into a typed attribute.
+    fn convert_int(&self, v: i64) -> OwnedAttribute {
+        if let Some(ty) = self.property_type.filter(|_| self.index >= self.first_value_index()) {
+            return if I32_PROPERTY_TYPES.contains(&ty) {
+                OwnedAttribute::I32(v as i32)
+            } else if I64_PROPERTY_TYPES.contains(&ty) {
+                OwnedAttribute::I64(v)
+            } else {
+                OwnedAttribute::F64(v as f64)
+            };
+        }
+        let is_id = (self.parent == "Objects" && self.index == 0) ||
+            (self.node == "C" && self.index >= 1);
+        if is_id || I64_NODES.contains(&self.node) || v as i32 as i64 != v {
+            OwnedAttribute::I64(v)
+        } else if F64_NODES.contains(&self.node) {
+            OwnedAttribute::F64(v as f64)
+        } else {
+            OwnedAttribute::I32(v as i32)
+        }
+    }
+
+    /// Returns the index of the first property value.
+    ///
+    /// `P` has name, type, label, and flags, and FBX 6.x `Property` has name, type, and flags.
+    fn first_value_index(&self) -> usize {
+        if self.node == "Property" {
+            3
+        } else {
+            4
+        }
+    }
+
+    /// Returns `true` if the attribute is an object name with the class (`Class::Name`).
+    ///
+    /// FBX 6.x objects have no IDs, so the name is the first attribute, and `Connect` nodes
+    /// refer to the objects by names.
+    fn is_name_class(&self) -> bool {
+        (self.parent == "Objects" && self.index <= 1) ||
+            (self.node == "SceneInfo" && self.index == 0) ||
+            (self.node == "Connect" && (self.index == 1 || self.index == 2))
+    }
+}
+
+
+/// Decodes a base64 string.
+fn decode_base64(s: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(s.len() * 3 / 4);
+    let mut buf = 0u32;
+    let mut bits = 0;
+    for c in s.bytes().filter(|&c| c != b'=' && !c.is_ascii_whitespace()) {
+        let v = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        buf = ((buf << 6) | u32::from(v)) & 0xffff;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buf >> bits) as u8);
+        }
+    }
+    Some(out)
+}
@@ -0,0 +1,440 @@
+//! FBX parsing error.
+
+use std::error;
+use std::fmt;
+use std::io;
+use std::str;
+use pull_parser::limits;
+
+
+/// Result of parser function.
+pub type Result<T> = ::std::result::Result<T, Error>;
+
+
+/// FBX parsing error.
+#[derive(Debug)]
+pub enum Error {
+    /// Attribute value is too large to be loaded into a buffer on the platform.
+    ///
+    /// This can happen on 32-bit platforms, or if the number of array elements in the header
+    /// is larger than the non-compressed array data.
+    /// Such attributes can be read partially by streaming readers (such as
+    /// `SpecialAttribute::reader()` and `ArrayAttributeReader::read_into_buf()`).
+    AttributeTooLargeForPlatform {
+        /// Length of the value in bytes.
+        bytelen: u64,
+    },
+    /// FBX footer is broken.
+    BrokenFbxFooter,
+    /// Successfully finished parsing the target FBX node(s).
+    Finished,
+    /// Specified FBX versions mismatched in header and footer.
+    HeaderFooterVersionMismatch {
+        /// Version specified in header.
+        header: u32,
+        /// Version specified in footer.
+        footer: u32,
+    },
+    /// Internal parser state is inconsistent.
+    ///
+    /// This is returned instead of panicking when the parser (or its low-level API) is used out
+    /// of order, or when the parser has a bug.
+    InternalState(&'static str),
+    /// Invalid node attribute type code.
+    InvalidNodeAttributeTypeCode {
+        /// Got type code.
+        got: u8,
+        /// Position of the type code.
+        position: u64,
+    },
+    /// Node structure is inconsistent.
+    ///
+    /// This is returned when the end offset of a node does not go beyond the node attributes, or
+    /// goes beyond the end of the parent node.
+    /// Such nodes would make the parser loop or read the data of other nodes.
+    InvalidNodeStructure {
+        /// Start offset of the node header.
+        begin: u64,
+        /// End offset of the node told by node header.
+        end: u64,
+        /// Minimum valid end offset (end of the node attributes).
+        min_end: u64,
+        /// Maximum valid end offset (end of the parent node), if the node has a parent.
+        max_end: Option<u64>,
+    },
+    /// Magic binary not detected.
+    MagicNotDetected([u8; 21]),
+    /// Node offset cannot be represented in the FBX version.
+    ///
+    /// FBX before 7.5 uses 32-bit node offsets, so nodes cannot exist beyond 4 GiB.
+    NodeOffsetOverflow {
+        /// Position of the node.
+        position: u64,
+        /// FBX version.
+        fbx_version: u32,
+    },
+    /// Node name has invalid UTF-8 sequences.
+    NodeNameInvalidUtf8(str::Utf8Error),
+    /// I/O error.
+    Io(io::Error),
+    /// Unknown array attribute encoding.
+    UnknownArrayAttributeEncoding(u32),
+    /// End offset of a node is wrong.
+    WrongNodeEndOffset {
+        /// Start offset of the node.
+        begin: u64,
+        /// End offset of the node told by node header.
+        expected_end: u64,
+        /// Position of the end of the node detected while reading input.
+        real_end: u64,
+    },
+}
+
+impl Error {
+    /// Returns the stable numeric code of the error.
+    ///
+    /// Parser error codes are in the range `1000..2000`.
+    /// Codes are never reused nor changed, so they can be used by FFI wrappers and logging
+    /// systems.
+    pub fn code(&self) -> u32 {
+        match *self {
+            Error::AttributeTooLargeForPlatform { .. } => 1000,
+            Error::BrokenFbxFooter => 1001,
+            Error::Finished => 1002,
+            Error::HeaderFooterVersionMismatch { .. } => 1003,
+            Error::InvalidNodeAttributeTypeCode { .. } => 1004,
+            Error::MagicNotDetected(_) => 1005,
+            Error::NodeOffsetOverflow { .. } => 1006,
+            Error::NodeNameInvalidUtf8(_) => 1007,
+            Error::Io(_) => 1008,
+            Error::UnknownArrayAttributeEncoding(_) => 1009,
+            Error::WrongNodeEndOffset { .. } => 1010,
+            Error::InternalState(_) => 1011,
+            Error::InvalidNodeStructure { .. } => 1012,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::AttributeTooLargeForPlatform { bytelen } => {
+                write!(
+                    f,
+                    "Attribute value ({} bytes) is too large to be loaded on the platform",
+                    bytelen
+                )
+            },
+            Error::HeaderFooterVersionMismatch { header, footer } => {
+                write!(
+                    f,
+                    "FBX version is {} in the FBX header but {} in the footer",
+                    header,
+                    footer
+                )
+            },
+            Error::InternalState(msg) => write!(f, "Internal parser state error: {}", msg),
+            Error::InvalidNodeAttributeTypeCode { got, position } => {
+                write!(
+                    f,
+                    "Invalid node attribute type code: Got {:?} at position {}",
+                    got,
+                    position
+                )
+            },
+            Error::InvalidNodeStructure {
+                begin,
+                end,
+                min_end,
+                max_end,
+            } => {
+                write!(
+                    f,
+                    "Invalid node structure: begin={}, end={}, min_end={}, max_end={:?}",
+                    begin,
+                    end,
+                    min_end,
+                    max_end
+                )
+            },
+            Error::MagicNotDetected(ref bytes) => {
+                write!(f, "Magic binary not detected: Got {:?}", bytes)
+            },
+            Error::NodeOffsetOverflow {
+                position,
+                fbx_version,
+            } => {
+                write!(
+                    f,
+                    "Node at position {} cannot be represented in FBX version {}",
+                    position,
+                    fbx_version
+                )
+            },
+            Error::NodeNameInvalidUtf8(ref err) => {
+                write!(f, "Node name is not valid UTF-8 string: {}", err)
+            },
+            Error::UnknownArrayAttributeEncoding(val) => {
+                write!(f, "Unknown array attribute encoding: encoding={}", val)
+            },
+            Error::WrongNodeEndOffset {
+                begin,
+                expected_end,
+                real_end,
+            } => {
+                write!(
+                    f,
+                    "Node ends with unexpected position: begin={}, expected_end={}, real_end={}",
+                    begin,
+                    expected_end,
+                    real_end
+                )
+            },
+            Error::BrokenFbxFooter => write!(f, "FBX footer is broken"),
+            Error::Finished => {
+                write!(f, "Successfully finished parsing and there are no more data")
+            },
+            Error::Io(ref err) => write!(f, "I/O error: {}", err),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::NodeNameInvalidUtf8(ref err) => Some(err),
+            Error::Io(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl Clone for Error {
+    fn clone(&self) -> Self {
+        match *self {
+            Error::AttributeTooLargeForPlatform { bytelen } => {
+                Error::AttributeTooLargeForPlatform { bytelen }
+            },
+            Error::BrokenFbxFooter => Error::BrokenFbxFooter,
+            Error::Finished => Error::Finished,
+            Error::InternalState(msg) => Error::InternalState(msg),
+            Error::InvalidNodeAttributeTypeCode { got, position } => {
+                Error::InvalidNodeAttributeTypeCode {
+                    got,
+                    position,
+                }
+            },
+            Error::HeaderFooterVersionMismatch { header, footer } => {
+                Error::HeaderFooterVersionMismatch {
+                    header,
+                    footer,
+                }
+            },
+            Error::InvalidNodeStructure {
+                begin,
+                end,
+                min_end,
+                max_end,
+            } => {
+                Error::InvalidNodeStructure {
+                    begin,
+                    end,
+                    min_end,
+                    max_end,
+                }
+            },
+            Error::MagicNotDetected(v) => Error::MagicNotDetected(v),
+            Error::NodeOffsetOverflow {
+                position,
+                fbx_version,
+            } => {
+                Error::NodeOffsetOverflow {
+                    position,
+                    fbx_version,
+                }
+            },
+            Error::NodeNameInvalidUtf8(err) => Error::NodeNameInvalidUtf8(err),
+            Error::Io(ref err) => {
+                // To clone `io::Error`, convert inner error into string and use it
+                // as a new inner error.
+                Error::Io(io::Error::new(err.kind(), err.to_string()))
+            },
+            Error::UnknownArrayAttributeEncoding(v) => Error::UnknownArrayAttributeEncoding(v),
+            Error::WrongNodeEndOffset {
+                begin,
+                expected_end,
+                real_end,
+            } => {
+                Error::WrongNodeEndOffset {
+                    begin,
+                    expected_end,
+                    real_end,
+                }
+            },
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+/// FBX parser warning.
+#[derive(Debug, Clone, Copy)]
+pub enum Warning {
+    /// Number of node attributes in the node header is larger than the actual number.
+    ///
+    /// The parser trusts byte length of the attributes and regards the node as having `read`
+    /// attributes.
+    AttributeCountMismatch {
+        /// Number of attributes declared in the node header.
+        declared: u64,
+        /// Number of attributes actually read.
+        read: u64,
+        /// Position of the end of the attributes.
+        position: u64,
+    },
+    /// Invalid node attribute of boolean value.
+    ///
+    /// Non-canonical encodings (i.e. other than `b'Y'` and `b'T'`) are normalized: `0x00` is
+    /// regarded as `false` and any other byte is regarded as `true`.
+    InvalidBooleanAttributeValue {
+        /// Got value.
+        got: u8,
+        /// Assumed value.
+        assumed: bool,
+        /// Position of the attribute value.
+        position: u64,
+    },
+    /// FBX footer has invalid padding.
+    InvalidPaddingInFbxFooter {
+        /// Expected padding length.
+        expected: u8,
+        /// Actual padding length.
+        actual: u8,
+    },
+    /// Node is nested deeper than the known safe limit.
+    ///
+    /// See [`limits::MAX_NODE_DEPTH`](limits/constant.MAX_NODE_DEPTH.html).
+    NodeTooDeep {
+        /// Depth of the node.
+        depth: usize,
+        /// Position of the node attributes.
+        position: u64,
+    },
+    /// Node has more attributes than the known safe limit.
+    ///
+    /// See [`limits::MAX_NODE_ATTRIBUTES`](limits/constant.MAX_NODE_ATTRIBUTES.html).
+    TooManyAttributes {
+        /// Number of the node attributes.
+        num_attributes: u64,
+        /// Position of the node attributes.
+        position: u64,
+    },
+    /// Unknown 2 bytes right after FBX magic is unexpected.
+    UnexpectedBytesAfterMagic([u8; 2]),
+}
+
+impl Warning {
+    /// Returns the stable numeric code of the warning.
+    ///
+    /// Parser warning codes are in the range `2000..3000`.
+    /// Codes are never reused nor changed, so they can be used by FFI wrappers and logging
+    /// systems.
+    pub fn code(&self) -> u32 {
+        match *self {
+            Warning::AttributeCountMismatch { .. } => 2000,
+            Warning::InvalidBooleanAttributeValue { .. } => 2001,
+            Warning::InvalidPaddingInFbxFooter { .. } => 2002,
+            Warning::NodeTooDeep { .. } => 2003,
+            Warning::TooManyAttributes { .. } => 2004,
+            Warning::UnexpectedBytesAfterMagic(_) => 2005,
+        }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Warning::AttributeCountMismatch {
+                declared,
+                read,
+                position,
+            } => {
+                write!(
+                    f,
+                    "Node header declares {} attributes but attributes end at position {} after {} \
+                        attributes",
+                    declared,
+                    position,
+                    read
+                )
+            },
+            Warning::InvalidBooleanAttributeValue {
+                got,
+                assumed,
+                position,
+            } => {
+                write!(
+                    f,
+                    "Invalid boolean node attribute value at position {}: got {:?}, assumed {}",
+                    position,
+                    got,
+                    assumed
+                )
+            },
+            Warning::InvalidPaddingInFbxFooter { expected, actual } => {
+                write!(
+                    f,
+                    "Invalid padding in FBX footer: expected {} bytes but got {} bytes",
+                    expected,
+                    actual
+                )
+            },
+            Warning::NodeTooDeep { depth, position } => {
+                write!(
+                    f,
+                    "Node at position {} has depth {}, which exceeds the known safe limit {}",
+                    position,
+                    depth,
+                    limits::MAX_NODE_DEPTH
+                )
+            },
+            Warning::TooManyAttributes {
+                num_attributes,
+                position,
+            } => {
+                write!(
+                    f,
+                    "Node at position {} has {} attributes, which exceeds the known safe limit {}",
+                    position,
+                    num_attributes,
+                    limits::MAX_NODE_ATTRIBUTES
+                )
+            },
+            Warning::UnexpectedBytesAfterMagic(ref bytes) => {
+                write!(
+                    f,
+                    "Unexpected bytes right after magic binary: expected [0x1a, 0x00] but got \
+                        {:?}",
+                    bytes
+                )
+            },
+        }
+    }
+}
+
+impl error::Error for Warning {
+    fn description(&self) -> &str {
+        match *self {
+            Warning::AttributeCountMismatch { .. } => "Node attribute count mismatch",
+            Warning::InvalidBooleanAttributeValue { .. } => "Invalid boolean node attribute value",
+            Warning::InvalidPaddingInFbxFooter { .. } => "Invalid padding in FBX footer",
+            Warning::NodeTooDeep { .. } => "Node is too deep",
+            Warning::TooManyAttributes { .. } => "Node has too many attributes",
+            Warning::UnexpectedBytesAfterMagic(_) => "Unexpected bytes right after magic binary",
+        }
+    }
+}
@@ -0,0 +1,594 @@
+//! Array type node attribute.
+
+use std::cmp;
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem;
+use std::io;
+use std::io::Read;
+#[cfg(feature = "flate2")]
+use flate2::read::{DeflateDecoder, ZlibDecoder};
+#[cfg(all(feature = "libflate", not(feature = "flate2")))]
+use libflate::{deflate, zlib};
+
+use pull_parser::error::{Result, Error};
+use pull_parser::reader::{ParserSource, ReadLittleEndian};
+use super::checked_buffer_len;
+
+
+/// Read array type attribute from the given parser.
+pub fn read_array_attribute<'a, R>(
+    source: &'a mut R,
+    type_code: u8,
+    verify_zlib_checksum: bool,
+) -> Result<(ArrayAttribute<'a, R>, u64)>
+where
+    R: ParserSource,
+{
+    let header = ArrayAttributeHeader::read_from_parser_source(source)?;
+    let current_pos = source.position();
+    let reader = ArrayDecoder::new(source, &header, verify_zlib_checksum)?;
+
+    let value = match type_code {
+        b'b' => ArrayAttribute::Bool(ArrayAttributeReader::new(&header, reader)),
+        b'i' => ArrayAttribute::I32(ArrayAttributeReader::new(&header, reader)),
+        b'l' => ArrayAttribute::I64(ArrayAttributeReader::new(&header, reader)),
+        b'f' => ArrayAttribute::F32(ArrayAttributeReader::new(&header, reader)),
+        b'd' => ArrayAttribute::F64(ArrayAttributeReader::new(&header, reader)),
+        _ => return Err(Error::InternalState("Non-array type code is passed to array reader")),
+    };
+    Ok((value, current_pos + header.bytelen_elements as u64))
+}
+
+
+/// Header of an array attribute.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct ArrayAttributeHeader {
+    /// Number of elements.
+    num_elements: u32,
+    /// Array encoding, i.e. compression method.
+    encoding: u32,
+    /// Length of the array (excluding this header) in bytes.
+    bytelen_elements: u32,
+}
+
+impl ArrayAttributeHeader {
+    fn read_from_parser_source<R>(source: &mut R) -> io::Result<Self>
+    where
+        R: ParserSource,
+    {
+        let num_elements = source.read_u32()?;
+        let encoding = source.read_u32()?;
+        let bytelen_elements = source.read_u32()?;
+
+        Ok(ArrayAttributeHeader {
+            num_elements,
+            encoding,
+            bytelen_elements,
+        })
+    }
+}
+
+
+/// Array type attribute.
+#[derive(Debug)]
+pub enum ArrayAttribute<'a, R: 'a> {
+    /// Array of `bool`.
+    Bool(ArrayAttributeReader<'a, R, bool>),
+    /// Array of `i32`.
+    I32(ArrayAttributeReader<'a, R, i32>),
+    /// Array of `i64`.
+    I64(ArrayAttributeReader<'a, R, i64>),
+    /// Array of `f32`.
+    F32(ArrayAttributeReader<'a, R, f32>),
+    /// Array of `f64`.
+    F64(ArrayAttributeReader<'a, R, f64>),
+}
+
+
+impl<'a, R: 'a + Read> ArrayAttribute<'a, R> {
+    /// Returns the lazy iterator of the elements.
+    ///
+    /// See `ArrayAttributeReader::iter()`.
+    pub fn iter(&mut self) -> ArrayAttributeIter<'_, 'a, R> {
+        match *self {
+            ArrayAttribute::Bool(ref mut reader) => ArrayAttributeIter::Bool(reader.iter()),
+            ArrayAttribute::I32(ref mut reader) => ArrayAttributeIter::I32(reader.iter()),
+            ArrayAttribute::I64(ref mut reader) => ArrayAttributeIter::I64(reader.iter()),
+            ArrayAttribute::F32(ref mut reader) => ArrayAttributeIter::F32(reader.iter()),
+            ArrayAttribute::F64(ref mut reader) => ArrayAttributeIter::F64(reader.iter()),
+        }
+    }
+}
+
+
+/// Reader of array attribute elements.
+#[derive(Debug)]
+pub struct ArrayAttributeReader<'a, R: 'a, T> {
+    num_elements: u64,
+    rest_elements: u64,
+    reader: ArrayDecoder<'a, R>,
+    _value_type: PhantomData<T>,
+}
+
+impl<'a, R: 'a + Read, T> ArrayAttributeReader<'a, R, T> {
+    fn new<'b>(
+        header: &'b ArrayAttributeHeader,
+        reader: ArrayDecoder<'a, R>,
+    ) -> Self {
+        ArrayAttributeReader {
+            num_elements: header.num_elements as u64,
+            rest_elements: header.num_elements as u64,
+            reader,
+            _value_type: PhantomData,
+        }
+    }
+
+    /// Returns number of elements.
+    pub fn num_elements(&self) -> u64 {
+        self.num_elements
+    }
+
+    /// Returns number of rest elements.
+    pub fn rest_elements(&self) -> u64 {
+        self.rest_elements
+    }
+
+    /// Returns the lazy iterator of the rest elements.
+    ///
+    /// Elements are decoded from the (possibly compressed) stream in small chunks, so the whole
+    /// array is never buffered in memory.
+    pub fn iter(&mut self) -> ArrayElements<'_, 'a, R, T> {
+        ArrayElements {
+            reader: self,
+            buf: Vec::new(),
+            pos: 0,
+            done: false,
+        }
+    }
+}
+
+impl<'a, R: 'a + Read> ArrayAttributeReader<'a, R, bool> {
+    /// Reads elements into the given buffer and returns the number of read elements.
+    pub fn read_into_buf(&mut self, buf: &mut [bool]) -> io::Result<usize> {
+        let size = ::std::cmp::min(buf.len(), self.rest_elements as usize);
+        for elem in &mut buf[0..size] {
+            self.rest_elements -= 1;
+            *elem = (self.reader.read_u8()? & 1) == 1;
+        }
+        Ok(size)
+    }
+
+    /// Reads elements into the given buffer and returns the number of read elements.
+    ///
+    /// See `ArrayAttributeReader::<R, i32>::read_into()`.
+    pub fn read_into(&mut self, buf: &mut [bool]) -> Result<usize> {
+        let size = self.read_into_buf(buf)?;
+        if self.rest_elements == 0 {
+            self.reader.finish()?;
+        }
+        Ok(size)
+    }
+
+    /// Reads all elements into `Vec`.
+    ///
+    /// Returns `Err(Error::AttributeTooLargeForPlatform { .. })` if the array is too large to be
+    /// loaded into a buffer on the platform.
+    /// Such arrays can be read partially by `read_into_buf()` or iterator.
+    pub fn into_vec(self) -> Result<Vec<bool>> {
+        let mut vec = Vec::new();
+        self.append_to_vec(&mut vec)?;
+        Ok(vec)
+    }
+
+    /// Reads all elements and appends them to the given `Vec`.
+    ///
+    /// See `ArrayAttributeReader::<R, i32>::append_to_vec()`.
+    pub fn append_to_vec(mut self, vec: &mut Vec<bool>) -> Result<()> {
+        let len = checked_buffer_len(
+            self.rest_elements,
+            mem::size_of::<bool>(),
+            self.reader.max_bytelen(),
+        )?;
+        read_in_chunks(vec, len, false, |buf| self.read_into_buf(buf))?;
+        self.reader.finish().inspect_err(|_| vec.truncate(vec.len() - len))?;
+        Ok(())
+    }
+}
+
+/// Number of elements allocated at first by `read_in_chunks()`.
+const INITIAL_CHUNK_ELEMENTS: usize = 4096;
+
+
+/// Reads `len` elements and appends them to `vec`, growing it as data is actually read.
+///
+/// The element count comes from the input, so the whole buffer is not allocated upfront;
+/// otherwise a broken header could request gigabytes of memory for a few bytes of data.
+/// On error, `vec` is truncated to the original length.
+fn read_in_chunks<T, F>(vec: &mut Vec<T>, len: usize, zero: T, mut read: F) -> io::Result<()>
+where
+    T: Copy,
+    F: FnMut(&mut [T]) -> io::Result<usize>,
+{
+    let start = vec.len();
+    vec.reserve(cmp::min(len, INITIAL_CHUNK_ELEMENTS));
+    while vec.len() - start < len {
+        let begin = vec.len();
+        let done = begin - start;
+        let chunk_len = cmp::min(len - done, cmp::max(done, INITIAL_CHUNK_ELEMENTS));
+        vec.resize(begin + chunk_len, zero);
+        if let Err(e) = read(&mut vec[begin..]) {
+            vec.truncate(start);
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+
+macro_rules! impl_attr_array_read {
+    ($ty:ty, $f:ident) => {
+        impl<'a, R: 'a + Read> ArrayAttributeReader<'a, R, $ty> {
+            /// Reads elements into the given buffer and returns the number of read elements.
+            pub fn read_into_buf(&mut self, buf: &mut [$ty]) -> io::Result<usize> {
+                let size = ::std::cmp::min(buf.len(), self.rest_elements as usize);
+                self.rest_elements -= size as u64;
+                self.reader.$f(&mut buf[0..size])?;
+                Ok(size)
+            }
+
+            /// Reads elements into the given buffer and returns the number of read elements.
+            ///
+            /// This reads `min(buf.len(), self.rest_elements())` elements, so arrays can be
+            /// decoded into pre-allocated (or memory-mapped) buffers without an extra `Vec`.
+            /// Large arrays can be read into a smaller buffer by calling this repeatedly.
+            ///
+            /// Unlike `read_into_buf()`, this verifies the checksum of zlib-compressed arrays
+            /// when the last element is read.
+            pub fn read_into(&mut self, buf: &mut [$ty]) -> Result<usize> {
+                let size = self.read_into_buf(buf)?;
+                if self.rest_elements == 0 {
+                    self.reader.finish()?;
+                }
+                Ok(size)
+            }
+
+            /// Reads all elements into `Vec`.
+            ///
+            /// Returns `Err(Error::AttributeTooLargeForPlatform { .. })` if the array is too large
+            /// to be loaded into a buffer on the platform.
+            /// Such arrays can be read partially by `read_into_buf()` or iterator.
+            pub fn into_vec(self) -> Result<Vec<$ty>> {
+                let mut vec = Vec::new();
+                self.append_to_vec(&mut vec)?;
+                Ok(vec)
+            }
+
+            /// Reads all elements and appends them to the given `Vec`.
+            ///
+            /// This allows arrays of many nodes to be stored in a single buffer, such as the
+            /// arena of `dom::tree::Tree`.
+            /// On error, `vec` is left with its original elements.
+            ///
+            /// Returns `Err(Error::AttributeTooLargeForPlatform { .. })` if the array is too large
+            /// to be loaded into a buffer on the platform.
+            pub fn append_to_vec(mut self, vec: &mut Vec<$ty>) -> Result<()> {
+                let len = checked_buffer_len(
+                    self.rest_elements,
+                    mem::size_of::<$ty>(),
+                    self.reader.max_bytelen(),
+                )?;
+                read_in_chunks(vec, len, 0 as $ty, |buf| self.read_into_buf(buf))?;
+                self.reader.finish().inspect_err(|_| vec.truncate(vec.len() - len))?;
+                Ok(())
+            }
+        }
+    }
+}
+
+impl_attr_array_read!(i32, read_i32_arr);
+impl_attr_array_read!(i64, read_i64_arr);
+impl_attr_array_read!(f32, read_f32_arr);
+impl_attr_array_read!(f64, read_f64_arr);
+
+impl<'a, R: 'a + Read> Iterator for ArrayAttributeReader<'a, R, bool> {
+    type Item = io::Result<bool>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rest_elements == 0 {
+            return None;
+        }
+        self.rest_elements -= 1;
+        let raw = match self.reader.read_u8() {
+            Ok(val) => val,
+            Err(err) => return Some(Err(err)),
+        };
+        let val = (raw & 1) == 1;
+        Some(Ok(val))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (
+            self.rest_elements as usize,
+            Some(self.rest_elements as usize),
+        )
+    }
+
+    fn count(self) -> usize {
+        self.rest_elements as usize
+    }
+}
+
+macro_rules! impl_attr_array_iter {
+    ($ty:ty, $f:ident) => {
+        impl<'a, R: 'a + Read> Iterator for ArrayAttributeReader<'a, R, $ty> {
+            type Item = io::Result<$ty>;
+
+            fn next(&mut self) -> Option<Self::Item> {
+                if self.rest_elements == 0 {
+                    return None;
+                }
+                self.rest_elements -= 1;
+                Some(self.reader.$f())
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                (self.rest_elements as usize, Some(self.rest_elements as usize))
+            }
+
+            fn count(self) -> usize {
+                self.rest_elements as usize
+            }
+        }
+    }
+}
+
+impl_attr_array_iter!(i32, read_i32);
+impl_attr_array_iter!(i64, read_i64);
+impl_attr_array_iter!(f32, read_f32);
+impl_attr_array_iter!(f64, read_f64);
+
+
+/// Lazy iterator of array attribute elements.
+///
+/// This is created by `ArrayAttributeReader::iter()`.
+/// For zlib-compressed arrays, the checksum is verified after the last element is read.
+#[derive(Debug)]
+pub struct ArrayElements<'r, 'a: 'r, R: 'a, T: 'r> {
+    /// Array reader.
+    reader: &'r mut ArrayAttributeReader<'a, R, T>,
+    /// Decoded chunk.
+    buf: Vec<T>,
+    /// Position of the next element in the chunk.
+    pos: usize,
+    /// Whether the iteration finished (or failed).
+    done: bool,
+}
+
+macro_rules! impl_array_elements_iter {
+    ($ty:ty, $zero:expr) => {
+        impl<'r, 'a: 'r, R: 'a + Read> Iterator for ArrayElements<'r, 'a, R, $ty> {
+            type Item = io::Result<$ty>;
+
+            fn next(&mut self) -> Option<Self::Item> {
+                if self.pos == self.buf.len() {
+                    if self.done {
+                        return None;
+                    }
+                    let rest = self.reader.rest_elements;
+                    if rest == 0 {
+                        self.done = true;
+                        return self.reader.reader.finish().err().map(Err);
+                    }
+                    let chunk_len = cmp::min(rest, ELEMENTS_CHUNK_LEN as u64) as usize;
+                    self.buf.clear();
+                    self.buf.resize(chunk_len, $zero);
+                    self.pos = 0;
+                    if let Err(err) = self.reader.read_into_buf(&mut self.buf) {
+                        self.buf.clear();
+                        self.done = true;
+                        return Some(Err(err));
+                    }
+                }
+                let value = self.buf[self.pos];
+                self.pos += 1;
+                Some(Ok(value))
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                let len = self.reader.rest_elements as usize + (self.buf.len() - self.pos);
+                (len, Some(len))
+            }
+        }
+    }
+}
+
+impl_array_elements_iter!(bool, false);
+impl_array_elements_iter!(i32, 0);
+impl_array_elements_iter!(i64, 0);
+impl_array_elements_iter!(f32, 0.0);
+impl_array_elements_iter!(f64, 0.0);
+
+
+/// Lazy iterator of elements of `ArrayAttribute`.
+///
+/// This is created by `ArrayAttribute::iter()`.
+#[derive(Debug)]
+pub enum ArrayAttributeIter<'r, 'a: 'r, R: 'a> {
+    /// Elements of `bool` array.
+    Bool(ArrayElements<'r, 'a, R, bool>),
+    /// Elements of `i32` array.
+    I32(ArrayElements<'r, 'a, R, i32>),
+    /// Elements of `i64` array.
+    I64(ArrayElements<'r, 'a, R, i64>),
+    /// Elements of `f32` array.
+    F32(ArrayElements<'r, 'a, R, f32>),
+    /// Elements of `f64` array.
+    F64(ArrayElements<'r, 'a, R, f64>),
+}
+
+
+/// Number of elements decoded at once by `ArrayElements`.
+const ELEMENTS_CHUNK_LEN: usize = 1024;
+
+
+/// Attribute array decoder.
+enum ArrayDecoder<'a, R: 'a> {
+    /// Non-compressed stream.
+    ///
+    /// `encoding` == 0.
+    Raw(io::Take<&'a mut R>),
+    /// Zlib-compressed stream.
+    ///
+    /// `encoding` == 1.
+    #[cfg(feature = "flate2")]
+    Zlib(ZlibDecoder<io::Take<&'a mut R>>),
+    /// Zlib-compressed stream.
+    ///
+    /// `encoding` == 1.
+    #[cfg(all(feature = "libflate", not(feature = "flate2")))]
+    Zlib(zlib::Decoder<io::Take<&'a mut R>>),
+    /// Zlib-compressed stream read without checksum verification.
+    ///
+    /// `encoding` == 1.
+    #[cfg(feature = "flate2")]
+    Deflate(DeflateDecoder<io::Take<&'a mut R>>),
+    /// Zlib-compressed stream read without checksum verification.
+    ///
+    /// `encoding` == 1.
+    #[cfg(all(feature = "libflate", not(feature = "flate2")))]
+    Deflate(deflate::Decoder<io::Take<&'a mut R>>),
+}
+
+impl<'a, R: 'a + Read> ArrayDecoder<'a, R> {
+    /// Creates a new decoder for the array.
+    ///
+    /// Without `flate2` nor `libflate` feature, compressed arrays are not supported.
+    #[cfg_attr(
+        not(any(feature = "flate2", feature = "libflate")),
+        allow(unused_variables)
+    )]
+    fn new(
+        reader: &'a mut R,
+        header: &ArrayAttributeHeader,
+        verify_zlib_checksum: bool,
+    ) -> Result<Self> {
+        match header.encoding {
+            0 => Ok(ArrayDecoder::Raw(
+                reader.take(header.bytelen_elements as u64),
+            )),
+            #[cfg(feature = "flate2")]
+            1 if !verify_zlib_checksum => {
+                let reader = skip_zlib_header(reader, header)?;
+                Ok(ArrayDecoder::Deflate(DeflateDecoder::new(reader)))
+            },
+            #[cfg(all(feature = "libflate", not(feature = "flate2")))]
+            1 if !verify_zlib_checksum => {
+                let reader = skip_zlib_header(reader, header)?;
+                Ok(ArrayDecoder::Deflate(deflate::Decoder::new(reader)))
+            },
+            #[cfg(feature = "flate2")]
+            1 => {
+                Ok(ArrayDecoder::Zlib(ZlibDecoder::new(
+                    reader.take(header.bytelen_elements as u64),
+                )))
+            },
+            #[cfg(all(feature = "libflate", not(feature = "flate2")))]
+            1 => {
+                Ok(ArrayDecoder::Zlib(zlib::Decoder::new(
+                    reader.take(header.bytelen_elements as u64),
+                )?))
+            },
+            _ => Err(Error::UnknownArrayAttributeEncoding(header.encoding)),
+        }
+    }
+
+    /// Returns the maximum number of the rest bytes of the decoded stream.
+    ///
+    /// This is exact for non-compressed streams, and unknown (`u64::MAX`) for compressed ones.
+    fn max_bytelen(&self) -> u64 {
+        match *self {
+            ArrayDecoder::Raw(ref reader) => reader.limit(),
+            #[cfg(any(feature = "flate2", feature = "libflate"))]
+            _ => u64::MAX,
+        }
+    }
+
+    /// Reads the rest of the stream.
+    ///
+    /// For zlib-compressed streams, this verifies the checksum at the end of the stream.
+    #[cfg_attr(
+        not(any(feature = "flate2", feature = "libflate")),
+        allow(irrefutable_let_patterns)
+    )]
+    fn finish(&mut self) -> io::Result<()> {
+        if let ArrayDecoder::Raw(_) = *self {
+            return Ok(());
+        }
+        let mut buf = [0; 64];
+        while self.read(&mut buf)? != 0 {}
+        Ok(())
+    }
+}
+
+/// Reads and checks the zlib header, and returns the reader of the following deflate stream.
+///
+/// The returned reader includes the trailing adler32 checksum, but it is never read by deflate
+/// decoders.
+#[cfg(any(feature = "flate2", feature = "libflate"))]
+fn skip_zlib_header<'a, R: Read>(
+    reader: &'a mut R,
+    header: &ArrayAttributeHeader,
+) -> io::Result<io::Take<&'a mut R>> {
+    let mut zlib_header = [0; 2];
+    reader.read_exact(&mut zlib_header)?;
+    let (cmf, flg) = (zlib_header[0], zlib_header[1]);
+    // Compression method should be deflate, and preset dictionary should not be used.
+    let is_valid = cmf & 0x0f == 8 && flg & 0x20 == 0 &&
+        (u16::from(cmf) << 8 | u16::from(flg)) % 31 == 0;
+    if !is_valid {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Invalid zlib header: {:?}", zlib_header),
+        ));
+    }
+    let rest_len = u64::from(header.bytelen_elements).saturating_sub(2);
+    Ok(reader.take(rest_len))
+}
+
+
+impl<'a, R: 'a + Read> Read for ArrayDecoder<'a, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match *self {
+            ArrayDecoder::Raw(ref mut reader) => reader.read(buf),
+            #[cfg(feature = "flate2")]
+            ArrayDecoder::Zlib(ref mut reader) => reader.read(buf),
+            #[cfg(all(feature = "libflate", not(feature = "flate2")))]
+            ArrayDecoder::Zlib(ref mut reader) => reader.read(buf),
+            #[cfg(feature = "flate2")]
+            ArrayDecoder::Deflate(ref mut reader) => reader.read(buf),
+            #[cfg(all(feature = "libflate", not(feature = "flate2")))]
+            ArrayDecoder::Deflate(ref mut reader) => reader.read(buf),
+        }
+    }
+}
+
+impl<'a, R: 'a> fmt::Debug for ArrayDecoder<'a, R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "ArrayDecoder::{}",
+            match *self {
+                ArrayDecoder::Raw(_) => "Raw",
+                #[cfg(feature = "flate2")]
+                ArrayDecoder::Zlib(_) => "Zlib",
+                #[cfg(all(feature = "libflate", not(feature = "flate2")))]
+                ArrayDecoder::Zlib(_) => "Zlib",
+                #[cfg(feature = "flate2")]
+                ArrayDecoder::Deflate(_) => "Deflate",
+                #[cfg(all(feature = "libflate", not(feature = "flate2")))]
+                ArrayDecoder::Deflate(_) => "Deflate",
+            }
+        )
+    }
+}
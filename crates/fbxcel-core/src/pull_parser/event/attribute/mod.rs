@@ -1,12 +1,12 @@
 //! Node attributes.
 
-use parser::binary::Warnings;
-use parser::binary::error::{Result, Error, Warning};
-use parser::binary::event::NodeHeader;
-use parser::binary::reader::{ParserSource, ReadLittleEndian};
-use parser::binary::utils::{AttributeValues, AttributeValue};
+use pull_parser::Warnings;
+use pull_parser::error::{Result, Error, Warning};
+use pull_parser::event::NodeHeader;
+use pull_parser::reader::{ParserSource, ReadLittleEndian};
+use pull_parser::utils::{AttributeValues, AttributeValue};
 use self::array::read_array_attribute;
-pub use self::array::{ArrayAttribute, ArrayAttributeReader};
+pub use self::array::{ArrayAttribute, ArrayAttributeIter, ArrayAttributeReader, ArrayElements};
 use self::special::read_special_attribute;
 pub use self::special::{SpecialAttribute, SpecialAttributeType};
 
@@ -23,10 +23,14 @@ pub struct Attributes<'a, R: 'a> {
     rest_attributes: u64,
     /// End offset of the previous attribute.
     prev_attr_end: Option<u64>,
+    /// End offset of the attributes.
+    attributes_end: u64,
     /// Parser source.
     source: &'a mut R,
     /// Parser warnings.
     warnings: &'a mut Warnings,
+    /// Whether to verify the checksum of zlib-compressed arrays.
+    verify_zlib_checksum: bool,
 }
 
 impl<'a, R: 'a + ParserSource> Attributes<'a, R> {
@@ -41,7 +45,7 @@ impl<'a, R: 'a + ParserSource> Attributes<'a, R> {
     }
 
     /// Returns the next attribute if available.
-    pub fn next_attribute(&mut self) -> Result<Option<Attribute<R>>> {
+    pub fn next_attribute(&mut self) -> Result<Option<Attribute<'_, R>>> {
         if self.rest_attributes == 0 {
             return Ok(None);
         }
@@ -52,6 +56,19 @@ impl<'a, R: 'a + ParserSource> Attributes<'a, R> {
             self.prev_attr_end = None;
         }
 
+        // Number of attributes in the node header might be wrong.
+        // If so, trust the byte length of the attributes rather than the number.
+        if self.source.position() >= self.attributes_end {
+            self.warnings.warn(Warning::AttributeCountMismatch {
+                declared: self.num_attributes,
+                read: self.num_attributes - self.rest_attributes,
+                position: self.source.position(),
+            });
+            self.num_attributes -= self.rest_attributes;
+            self.rest_attributes = 0;
+            return Ok(None);
+        }
+
         self.rest_attributes -= 1;
         let type_code = self.source.read_u8()?;
         let position = self.source.position();
@@ -59,12 +76,12 @@ impl<'a, R: 'a + ParserSource> Attributes<'a, R> {
             // Primitive type attributes.
             b'C' => {
                 let raw = self.source.read_u8()?;
-                let val = (raw & 0x01) == 1;
-                if raw != b'T' && raw != b'Y' {
+                let (val, is_canonical) = decode_bool(raw);
+                if !is_canonical {
                     self.warnings.warn(Warning::InvalidBooleanAttributeValue {
                         got: raw,
                         assumed: val,
-                        position: position,
+                        position,
                     });
                 }
                 Ok(Some(PrimitiveAttribute::Bool(val).into()))
@@ -93,7 +110,7 @@ impl<'a, R: 'a + ParserSource> Attributes<'a, R> {
             // Array type attributes.
             b'b' | b'i' | b'l' | b'f' | b'd' => {
                 let (attr, end_offset) =
-                    read_array_attribute(self.source, self.warnings, type_code)?;
+                    read_array_attribute(self.source, type_code, self.verify_zlib_checksum)?;
                 self.prev_attr_end = Some(end_offset);
                 Ok(Some(attr.into()))
             },
@@ -101,7 +118,7 @@ impl<'a, R: 'a + ParserSource> Attributes<'a, R> {
             _ => {
                 Err(Error::InvalidNodeAttributeTypeCode {
                     got: type_code,
-                    position: position,
+                    position,
                 })
             },
         }
@@ -135,18 +152,73 @@ impl<'a, R: 'a + ParserSource> From<ArrayAttribute<'a, R>> for Attribute<'a, R>
 }
 
 
+/// Decodes a raw value of boolean attribute.
+///
+/// Returns `(value, is_canonical)`.
+///
+/// | Raw byte | Value | Canonical |
+/// |----------|-------|-----------|
+/// | `b'Y'` | `true` | yes |
+/// | `b'T'` | `false` | yes |
+/// | `0x00` | `false` | no |
+/// | any other byte (such as `0x01`, `0x02`, and `b'N'`) | `true` | no |
+///
+/// FBX SDK writes `b'Y'` and `b'T'`. Some exporters use C-style `0x00` and `0x01`, or any
+/// nonzero byte for `true`.
+/// Note that this differs from older versions of this crate, which used the lowest bit of the
+/// byte (so `0x02` and `b'N'` were `false`).
+/// Non-canonical values are reported as `Warning::InvalidBooleanAttributeValue` by the caller.
+fn decode_bool(raw: u8) -> (bool, bool) {
+    match raw {
+        b'Y' => (true, true),
+        b'T' => (false, true),
+        0 => (false, false),
+        _ => (true, false),
+    }
+}
+
+
+/// Returns the length of the buffer to load the given number of elements.
+///
+/// `max_bytelen` is the number of bytes available for the elements, such as the byte length of
+/// a non-compressed array.
+///
+/// Returns `Err(Error::AttributeTooLargeForPlatform { .. })` if such buffer cannot be allocated
+/// on the platform (for example, buffer larger than 2 GiB on 32-bit platforms), or if the
+/// elements cannot be in `max_bytelen` bytes.
+/// This is checked before any allocation, so broken headers cannot request huge buffers.
+fn checked_buffer_len(num_elements: u64, elem_size: usize, max_bytelen: u64) -> Result<usize> {
+    let bytelen = num_elements.checked_mul(elem_size as u64);
+    match bytelen {
+        Some(bytelen) if bytelen <= isize::MAX as u64 && bytelen <= max_bytelen => {
+            Ok(num_elements as usize)
+        },
+        _ => {
+            Err(Error::AttributeTooLargeForPlatform {
+                bytelen: bytelen.unwrap_or(u64::MAX),
+            })
+        },
+    }
+}
+
+
 /// Creates a new `Attributes`.
-pub fn new_attributes<'a, R: 'a>(
+///
+/// The current position of the source should be the beginning of the attributes.
+pub fn new_attributes<'a, R: 'a + ParserSource>(
     source: &'a mut R,
     warnings: &'a mut Warnings,
     header: &NodeHeader,
+    verify_zlib_checksum: bool,
 ) -> Attributes<'a, R> {
     Attributes {
         num_attributes: header.num_attributes,
         rest_attributes: header.num_attributes,
         prev_attr_end: None,
-        source: source,
-        warnings: warnings,
+        attributes_end: source.position() + header.bytelen_attributes,
+        source,
+        warnings,
+        verify_zlib_checksum,
     }
 }
 
@@ -209,3 +281,39 @@ impl PrimitiveAttribute {
         }
     }
 }
+
+
+#[cfg(test)]
+mod tests {
+    use pull_parser::error::Error;
+    use super::{checked_buffer_len, decode_bool};
+
+    #[test]
+    fn bool_encodings() {
+        assert_eq!(decode_bool(b'Y'), (true, true));
+        assert_eq!(decode_bool(b'T'), (false, true));
+        assert_eq!(decode_bool(0x00), (false, false));
+        assert_eq!(decode_bool(0x01), (true, false));
+        assert_eq!(decode_bool(0x02), (true, false));
+        assert_eq!(decode_bool(b'N'), (true, false));
+        assert_eq!(decode_bool(0xff), (true, false));
+    }
+
+    #[test]
+    fn buffer_len_limits() {
+        assert_eq!(checked_buffer_len(3, 8, 24).unwrap(), 3);
+        assert_eq!(checked_buffer_len(0, 8, 0).unwrap(), 0);
+        let too_large = |num_elements, elem_size, max_bytelen| {
+            match checked_buffer_len(num_elements, elem_size, max_bytelen) {
+                Err(Error::AttributeTooLargeForPlatform { bytelen }) => bytelen,
+                res => panic!("Unexpected result: {:?}", res),
+            }
+        };
+        // Overflow of the byte length.
+        assert_eq!(too_large(u64::MAX, 8, u64::MAX), u64::MAX);
+        // Larger than any buffer on the platform.
+        assert_eq!(too_large(isize::MAX as u64 + 1, 1, u64::MAX), isize::MAX as u64 + 1);
+        // Larger than the available data.
+        assert_eq!(too_large(4, 8, 31), 32);
+    }
+}
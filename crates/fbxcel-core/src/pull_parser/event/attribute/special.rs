@@ -1,9 +1,10 @@
 //! Special type node attribute.
 
-use std::io;
 use std::io::Read;
 
-use parser::binary::reader::{ParserSource, ReadLittleEndian, LimitedSeekReader};
+use pull_parser::error::{Result, Error};
+use pull_parser::reader::{ParserSource, ReadLittleEndian, LimitedSeekReader};
+use super::checked_buffer_len;
 
 
 /// Attribute type of special value.
@@ -60,15 +61,23 @@ impl<'a, R: 'a + ParserSource> SpecialAttribute<'a, R> {
     }
 
     /// Read the attribute to the vector.
-    pub fn into_vec(mut self) -> io::Result<Vec<u8>> {
-        let mut buf = Vec::with_capacity(self.rest_len() as usize);
+    ///
+    /// Returns `Err(Error::AttributeTooLargeForPlatform { .. })` if the value is too large to be
+    /// loaded into a buffer on the platform.
+    /// Such values can be read partially by `reader()`.
+    pub fn into_vec(mut self) -> Result<Vec<u8>> {
+        let mut buf = Vec::with_capacity(checked_buffer_len(self.rest_len(), 1, u64::MAX)?);
         self.reader().read_to_end(&mut buf)?;
         Ok(buf)
     }
 
     /// Read the attribute to the string.
-    pub fn into_string(mut self) -> io::Result<String> {
-        let mut buf = String::with_capacity(self.rest_len() as usize);
+    ///
+    /// Returns `Err(Error::AttributeTooLargeForPlatform { .. })` if the value is too large to be
+    /// loaded into a buffer on the platform.
+    /// Such values can be read partially by `reader()`.
+    pub fn into_string(mut self) -> Result<String> {
+        let mut buf = String::with_capacity(checked_buffer_len(self.rest_len(), 1, u64::MAX)?);
         self.reader().read_to_string(&mut buf)?;
         Ok(buf)
     }
@@ -79,25 +88,27 @@ impl<'a, R: 'a + ParserSource> SpecialAttribute<'a, R> {
 pub fn read_special_attribute<R>(
     source: &mut R,
     type_code: u8,
-) -> io::Result<(SpecialAttribute<R>, u64)>
+) -> Result<(SpecialAttribute<'_, R>, u64)>
 where
     R: ParserSource,
 {
-    let byte_length = source.read_u32()?;
     let value_type = match type_code {
         b'R' => SpecialAttributeType::Binary,
         b'S' => SpecialAttributeType::String,
-        _ => unreachable!(),
+        _ => {
+            return Err(Error::InternalState("Non-special type code is passed to special reader"))
+        },
     };
+    let byte_length = source.read_u32()?;
     let current_pos = source.position();
     let end_offset = current_pos + byte_length as u64;
 
     Ok((
         SpecialAttribute {
-            source: source,
-            value_type: value_type,
-            byte_length: byte_length,
-            end_offset: end_offset,
+            source,
+            value_type,
+            byte_length,
+            end_offset,
         },
         end_offset,
     ))
@@ -1,13 +1,14 @@
 //! Parser event.
 
-use std::io;
+use std::str;
 
-use parser::binary::RootParser;
-use parser::binary::error::{Result, Error, Warning};
-use parser::binary::reader::{ParserSource, ReadLittleEndian};
+use pull_parser::RootParser;
+use pull_parser::error::{Result, Error, Warning};
+use pull_parser::reader::{ParserSource, ReadLittleEndian};
+use pull_parser::utils::{AttributeValue, AttributeValues};
 pub use self::attribute::{Attributes, Attribute, SpecialAttributeType};
 pub use self::attribute::{PrimitiveAttribute, ArrayAttribute, SpecialAttribute};
-pub use self::attribute::ArrayAttributeReader;
+pub use self::attribute::{ArrayAttributeIter, ArrayAttributeReader, ArrayElements};
 
 mod attribute;
 
@@ -57,14 +58,13 @@ pub fn read_fbx_header<R>(parser: &mut RootParser<R>) -> Result<FbxHeader>
 where
     R: ParserSource,
 {
-    assert!(
-        parser.fbx_version.is_none(),
-        "Parser should read FBX header only once"
-    );
+    if parser.fbx_version.is_some() {
+        return Err(Error::InternalState("FBX header is already read"));
+    }
     // Check magic binary.
     {
         const MAGIC_LEN: usize = 21;
-        const MAGIC: &'static [u8; MAGIC_LEN] = b"Kaydara FBX Binary  \x00";
+        const MAGIC: &[u8; MAGIC_LEN] = b"Kaydara FBX Binary  \x00";
         let mut buf = [0u8; MAGIC_LEN];
         parser.source.read_exact(&mut buf)?;
         if buf != *MAGIC {
@@ -74,7 +74,7 @@ where
     // Read unknown 2 bytes.
     {
         const UNKNOWN_BYTES_LEN: usize = 2;
-        const UNKNOWN_BYTES: &'static [u8; UNKNOWN_BYTES_LEN] = b"\x1a\x00";
+        const UNKNOWN_BYTES: &[u8; UNKNOWN_BYTES_LEN] = b"\x1a\x00";
         let mut buf = [0u8; UNKNOWN_BYTES_LEN];
         parser.source.read_exact(&mut buf)?;
         if buf != *UNKNOWN_BYTES {
@@ -177,9 +177,9 @@ impl FbxFooter {
             (buf[ver_offset] as u32) | (buf[ver_offset + 1] as u32) << 8 |
                 (buf[ver_offset + 2] as u32) << 16 | (buf[ver_offset + 3] as u32) << 24
         };
-        let header_fbx_version = parser.fbx_version.expect(
-            "Parser should remember FBX version in the FBX header but it doesn't",
-        );
+        let header_fbx_version = parser.fbx_version.ok_or(Error::InternalState(
+            "FBX footer is read before the FBX header",
+        ))?;
         if header_fbx_version != footer_fbx_version {
             return Err(Error::HeaderFooterVersionMismatch {
                 header: header_fbx_version,
@@ -188,9 +188,9 @@ impl FbxFooter {
         }
 
         Ok(FbxFooter {
-            unknown1: unknown1,
+            unknown1,
             version: footer_fbx_version,
-            unknown2: unknown2,
+            unknown2,
         })
     }
 }
@@ -199,11 +199,63 @@ impl FbxFooter {
 #[derive(Debug)]
 pub struct StartNode<'a, R: 'a> {
     /// Node name.
-    pub name: &'a str,
+    ///
+    /// This may be invalid as UTF-8 sequence.
+    name: &'a [u8],
     /// Node attributes.
     pub attributes: Attributes<'a, R>,
 }
 
+impl<'a, R: 'a> StartNode<'a, R> {
+    /// Returns the node name.
+    ///
+    /// Returns `Err(Error::NodeNameInvalidUtf8(_))` if the name is not valid UTF-8 sequence.
+    pub fn name(&self) -> Result<&'a str> {
+        str::from_utf8(self.name).map_err(Error::NodeNameInvalidUtf8)
+    }
+
+    /// Returns the raw node name.
+    pub fn name_bytes(&self) -> &'a [u8] {
+        self.name
+    }
+}
+
+impl<'a, R: 'a + ParserSource> StartNode<'a, R> {
+    /// Reads the attribute at the given index as a value of a specific type.
+    ///
+    /// Attributes before the index are skipped, and they cannot be read after this call.
+    /// Returns `Ok(None)` if the attribute is absent or of incompatible type, and
+    /// `Err(Error::InternalState(_))` if the attribute is already read.
+    pub fn attr<T>(&mut self, index: u64) -> Result<Option<T>>
+    where
+        T: AttributeValue,
+    {
+        let read = self.attributes.num_attributes() - self.attributes.rest_attributes();
+        if index < read {
+            return Err(Error::InternalState("The node attribute is already read"));
+        }
+        for _ in read..index {
+            if self.attributes.next_attribute()?.is_none() {
+                return Ok(None);
+            }
+        }
+        match self.attributes.next_attribute()? {
+            Some(attr) => T::from_attribute_loose(attr),
+            None => Ok(None),
+        }
+    }
+
+    /// Reads the rest attributes as values of specific types, such as `(i64, String, String)`.
+    ///
+    /// Returns `Ok(None)` if the attributes are incompatible with the types.
+    pub fn attrs_as<A>(&mut self) -> Result<Option<A>>
+    where
+        A: AttributeValues,
+    {
+        self.attributes.convert_into()
+    }
+}
+
 
 /// Parser event without reference to a parser.
 #[derive(Debug, Clone)]
@@ -220,7 +272,7 @@ pub enum EventBuilder {
 
 impl EventBuilder {
     /// Creates `Event` from the `EventBuilder` and the given parser.
-    pub fn build<R>(self, parser: &mut RootParser<R>) -> Event<R>
+    pub fn build<R>(self, parser: &mut RootParser<R>) -> Event<'_, R>
     where
         R: ParserSource,
     {
@@ -261,7 +313,7 @@ pub struct StartNodeBuilder {
 
 impl StartNodeBuilder {
     /// Creates `StartNode` from the `StartNodeBuilder` and the given parser.
-    pub fn build<R>(self, parser: &mut RootParser<R>) -> StartNode<R>
+    pub fn build<R>(self, parser: &mut RootParser<R>) -> StartNode<'_, R>
     where
         R: ParserSource,
     {
@@ -269,13 +321,19 @@ impl StartNodeBuilder {
             ref mut source,
             ref mut warnings,
             ref recent_node_name,
+            verify_zlib_checksum,
             ..
         } = *parser;
         StartNode {
             name: recent_node_name.as_ref().expect(
                 "`RootParser::recent_node_name` must not be empty",
             ),
-            attributes: attribute::new_attributes(source, warnings, &self.header),
+            attributes: attribute::new_attributes(
+                source,
+                warnings,
+                &self.header,
+                verify_zlib_checksum,
+            ),
         }
     }
 }
@@ -302,13 +360,15 @@ impl NodeHeader {
     }
 
     /// Reads node header from the given parser and returns it.
-    pub fn read_from_parser<R>(parser: &mut RootParser<R>) -> io::Result<Self>
+    ///
+    /// Returns `Err(Error::InternalState(_))` if the FBX header is not read yet.
+    pub fn read_from_parser<R>(parser: &mut RootParser<R>) -> Result<Self>
     where
         R: ParserSource,
     {
-        let fbx_version = parser.fbx_version.expect(
-            "Attempt to read FBX node header but the parser doesn't know FBX version",
-        );
+        let fbx_version = parser.fbx_version.ok_or(Error::InternalState(
+            "FBX node header is read before the FBX header",
+        ))?;
         let (end_offset, num_attributes, bytelen_attributes) = if fbx_version < 7500 {
             let eo = parser.source.read_u32()? as u64;
             let na = parser.source.read_u32()? as u64;
@@ -322,10 +382,10 @@ impl NodeHeader {
         };
         let bytelen_name = parser.source.read_u8()?;
         Ok(NodeHeader {
-            end_offset: end_offset,
-            num_attributes: num_attributes,
-            bytelen_attributes: bytelen_attributes,
-            bytelen_name: bytelen_name,
+            end_offset,
+            num_attributes,
+            bytelen_attributes,
+            bytelen_name,
         })
     }
 }
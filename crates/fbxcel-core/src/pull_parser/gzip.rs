@@ -0,0 +1,104 @@
+//! Reader for gzip-compressed FBX data (such as `.fbx.gz` files).
+
+use std::fmt;
+use std::io;
+#[cfg(feature = "flate2")]
+use flate2::read::GzDecoder;
+#[cfg(all(feature = "libflate", not(feature = "flate2")))]
+use libflate::gzip;
+
+
+/// Magic bytes of gzip streams.
+pub const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+
+/// Returns `true` if the given bytes seem to be the start of a gzip stream.
+pub fn is_gzip(header: &[u8]) -> bool {
+    header.starts_with(&GZIP_MAGIC)
+}
+
+
+/// Reader which decompresses the whole gzip-compressed stream.
+///
+/// The decompressed stream is non-seekable, so the reader should be used with
+/// `RootParser::new()` (or `RootParser::from_gzip()`).
+pub struct GzipReader<R: io::Read> {
+    /// Decoder.
+    #[cfg(feature = "flate2")]
+    decoder: GzDecoder<R>,
+    /// Decoder.
+    #[cfg(all(feature = "libflate", not(feature = "flate2")))]
+    decoder: gzip::Decoder<R>,
+}
+
+impl<R: io::Read> GzipReader<R> {
+    /// Creates a new `GzipReader`, reading the gzip header immediately.
+    pub fn new(source: R) -> io::Result<Self> {
+        #[cfg(feature = "flate2")]
+        let decoder = GzDecoder::new(source)?;
+        #[cfg(all(feature = "libflate", not(feature = "flate2")))]
+        let decoder = gzip::Decoder::new(source)?;
+        Ok(GzipReader { decoder })
+    }
+}
+
+impl<R: io::Read> io::Read for GzipReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.decoder.read(buf)
+    }
+}
+
+impl<R: io::Read> fmt::Debug for GzipReader<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("GzipReader").finish()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+    #[cfg(feature = "flate2")]
+    use flate2::{Compression, write::GzEncoder};
+    use pull_parser::{Event, OwnedAttribute, Parser, RootParser};
+    use pull_parser::ascii::AsciiDocument;
+    use super::*;
+
+    #[cfg(feature = "flate2")]
+    fn compress(bytes: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::Default);
+        encoder.write_all(bytes).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[cfg(all(feature = "libflate", not(feature = "flate2")))]
+    fn compress(bytes: &[u8]) -> Vec<u8> {
+        let mut encoder = gzip::Encoder::new(Vec::new()).unwrap();
+        encoder.write_all(bytes).unwrap();
+        encoder.finish().into_result().unwrap()
+    }
+
+    #[test]
+    fn parse_gzip_compressed() {
+        let source = "; FBX 7.4.0 project file\nCreator: \"fbxcel\"\n";
+        let binary = AsciiDocument::parse(source).unwrap().to_binary().unwrap();
+        let compressed = compress(&binary);
+        assert!(is_gzip(&compressed));
+
+        let mut parser = RootParser::from_gzip(Cursor::new(compressed)).unwrap();
+        assert!(matches!(parser.next_event().unwrap(), Event::StartFbx(_)));
+        match parser.next_event().unwrap() {
+            Event::StartNode(node) => {
+                assert_eq!(node.name().unwrap(), "Creator");
+                let attrs = OwnedAttribute::load_attrs_from_parser_event(node.attributes).unwrap();
+                assert_eq!(attrs, [OwnedAttribute::from("fbxcel")]);
+            },
+            ev => panic!("Unexpected event: {:?}", ev),
+        }
+        assert!(matches!(parser.next_event().unwrap(), Event::EndNode));
+        match parser.next_event().unwrap() {
+            Event::EndFbx(footer) => assert_eq!(footer.ok().map(|f| f.version), Some(7400)),
+            ev => panic!("Unexpected event: {:?}", ev),
+        }
+    }
+}
@@ -0,0 +1,18 @@
+//! Known limits of FBX tools.
+//!
+//! Files exceeding these limits may be read by this crate, but may fail to be loaded by
+//! Autodesk FBX SDK and tools based on it.
+//! The parser emits warnings when the soft limits are exceeded.
+
+/// Max length of a node name in bytes.
+///
+/// This is a hard limit, because FBX binary stores the length of a node name as `u8`.
+pub const MAX_NODE_NAME_LEN: usize = 255;
+
+/// Max number of attributes of a node, which is known to be safe for FBX SDK.
+pub const MAX_NODE_ATTRIBUTES: u64 = 65_535;
+
+/// Max depth of nodes, which is known to be safe for FBX SDK.
+///
+/// Toplevel nodes have depth `1`.
+pub const MAX_NODE_DEPTH: usize = 128;
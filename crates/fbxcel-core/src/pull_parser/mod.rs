@@ -0,0 +1,1238 @@
+//! FBX pull parsers.
+//!
+//! The binary parser (`RootParser`) is at the top level of this module, and the ASCII parser is
+//! in `ascii`.
+//! `any` detects the format and parses both.
+//!
+//! # Malformed input
+//!
+//! Parsers never panic on malformed or truncated input: broken data is reported as `Err(_)`
+//! (and as `Error::InternalState` if the parser ends up in an unexpected state).
+//! Remaining panics are caused only by misuse of the API, and they are documented in the
+//! `# Panics` section of each function.
+
+use std::io;
+use std::io::Read;
+use std::str;
+use fnv::FnvHashMap;
+
+pub use self::error::{Result, Error, Warning};
+pub use self::event::{Event, FbxHeader, FbxFooter, StartNode};
+pub use self::event::{Attributes, Attribute, SpecialAttributeType};
+pub use self::event::{PrimitiveAttribute, ArrayAttribute, SpecialAttribute};
+pub use self::event::{ArrayAttributeIter, ArrayAttributeReader, ArrayElements};
+pub use self::owned_attribute::OwnedAttribute;
+use self::event::{EventBuilder, NodeHeader, StartNodeBuilder};
+use self::event::read_fbx_header;
+pub use self::reader::{ParserSource, BasicSource, SeekableSource, LimitedSeekReader};
+pub use self::reader::{HashingSource, InputHasher, SourceCapabilities, TeeSource};
+
+pub mod any;
+pub mod ascii;
+mod error;
+mod event;
+#[cfg(feature = "gzip")]
+pub mod gzip;
+pub mod limits;
+mod owned_attribute;
+mod reader;
+pub mod utils;
+
+
+/// Max number of warnings stored for each group of the same kind and node name.
+///
+/// Further warnings in the group are only counted.
+pub const MAX_WARNINGS_PER_GROUP: usize = 8;
+
+/// Max number of warning groups distinguished by node names.
+///
+/// Warnings which would create more groups are grouped by kind only (with no node name).
+pub const MAX_WARNING_GROUPS: usize = 256;
+
+
+/// Summary of warnings of the same kind in the same node name.
+#[derive(Debug, Clone)]
+pub struct WarningSummary {
+    /// Warning code (see `Warning::code()`).
+    pub code: u32,
+    /// Name of the recent opened node when the warnings are emitted.
+    ///
+    /// `None` if no nodes are read yet or there are too many groups (see `MAX_WARNING_GROUPS`).
+    pub node_name: Option<String>,
+    /// Number of the warnings, including the ones not stored.
+    pub count: u64,
+    /// The first warning.
+    pub first: Warning,
+}
+
+
+/// Warnings store.
+///
+/// Duplicate warnings (of the same kind in the same node name) are collapsed into
+/// `WarningSummary`s, so that broken files don't fill the memory with warnings.
+#[derive(Default, Debug, Clone)]
+pub struct Warnings {
+    /// Stored warnings.
+    warnings: Vec<Warning>,
+    /// Warning groups.
+    summaries: Vec<WarningSummary>,
+    /// Map from the warning code and the node name to the index of the group.
+    groups: FnvHashMap<(u32, Option<Vec<u8>>), usize>,
+    /// Name of the recent opened node.
+    node_name: Option<Vec<u8>>,
+}
+
+impl Warnings {
+    /// Creates a new `Warnings`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Add a warning.
+    pub fn warn(&mut self, warning: Warning) {
+        let mut key = (warning.code(), self.node_name.clone());
+        if !self.groups.contains_key(&key) && self.groups.len() >= MAX_WARNING_GROUPS {
+            key.1 = None;
+        }
+        let index = match self.groups.get(&key) {
+            Some(&index) => index,
+            None => {
+                let node_name = key.1
+                    .as_ref()
+                    .map(|name| String::from_utf8_lossy(name).into_owned());
+                self.summaries.push(WarningSummary {
+                    code: warning.code(),
+                    node_name,
+                    count: 0,
+                    first: warning,
+                });
+                self.groups.insert(key, self.summaries.len() - 1);
+                self.summaries.len() - 1
+            },
+        };
+        let summary = &mut self.summaries[index];
+        summary.count += 1;
+        if summary.count <= MAX_WARNINGS_PER_GROUP as u64 {
+            warn!("FBX binary parser warning: {}", warning);
+            self.warnings.push(warning);
+        } else {
+            debug!("FBX binary parser warning (suppressed): {}", warning);
+        }
+    }
+
+    /// Sets the name of the recent opened node.
+    fn set_node_name(&mut self, name: &[u8]) {
+        let buf = self.node_name.get_or_insert_with(Vec::new);
+        buf.clear();
+        buf.extend_from_slice(name);
+    }
+
+    /// Returns the summaries of the warnings, in order of the first occurrences.
+    pub fn summaries(&self) -> &[WarningSummary] {
+        &self.summaries
+    }
+
+    /// Returns the total number of the warnings, including the ones not stored.
+    pub fn total_count(&self) -> u64 {
+        self.summaries.iter().map(|summary| summary.count).sum()
+    }
+
+    /// Returns the inner vector.
+    pub fn inner(self) -> Vec<Warning> {
+        self.warnings
+    }
+}
+
+impl ::std::ops::Deref for Warnings {
+    type Target = [Warning];
+
+    fn deref(&self) -> &Self::Target {
+        &self.warnings
+    }
+}
+
+
+/// Byte range of a node in the FBX binary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeSpan {
+    /// Offset of the node header.
+    pub offset: u64,
+    /// Length of the node in bytes, including the header and the children.
+    pub len: u64,
+}
+
+impl NodeSpan {
+    /// Returns the end offset of the node.
+    pub fn end(&self) -> u64 {
+        self.offset + self.len
+    }
+}
+
+
+/// Parser of a FBX binary node.
+pub trait Parser<R: ParserSource> {
+    /// Returns the root parser.
+    fn root_parser(&self) -> &RootParser<R>;
+    /// Parses FBX from the given stream and returns the next event.
+    fn next_event(&mut self) -> Result<Event<'_, R>>;
+    /// Skips to the end of the current node.
+    ///
+    /// With seekable sources (see `RootParser::from_seekable()`), skipped data is not read.
+    ///
+    /// Returns `Ok(true)` if the current node is skipped and closed,
+    /// `Ok(false)` if no nodes are open (i.e. the parser is reading under implicit root node),
+    /// `Err(err)` if error happened.
+    fn skip_current_node(&mut self) -> Result<bool>;
+    /// Creates subtree parser for the current node.
+    fn subtree_parser(&mut self) -> SubtreeParser<'_, R>;
+}
+
+
+/// Parser state without error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// Reading file header.
+    Header,
+    /// A node started.
+    ///
+    /// This status means:
+    ///
+    /// - there might be some attributes remain unread, or
+    /// - the most recent opened node might close without no null node header.
+    NodeStarted,
+    /// A node ended.
+    ///
+    /// This status means:
+    ///
+    /// - if the next event is `NodeEnd`, there must be a null node header, and
+    /// - if the parser got an extra null header, it indicates end of implicit root node.
+    NodeEnded,
+}
+
+
+/// Information about opened (but not yet closed) node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct OpenNode {
+    /// Start offset of the node header.
+    header_begin: u64,
+    /// Start offset of the node attribute.
+    ///
+    /// Note that this doesn't mean start offset of node header.
+    begin: u64,
+    /// End offset of the node.
+    end: u64,
+    /// End offset of attributes of the node.
+    attributes_end: u64,
+}
+
+
+/// Pull parser for whole FBX with binary format.
+#[derive(Debug)]
+pub struct RootParser<R> {
+    /// Source reader.
+    source: R,
+    /// Parser state.
+    ///
+    /// `Ok(State)` if the parser is working without critical error,
+    /// `Err(Error)` if the parsing failed and cannot be continued.
+    state: Result<State>,
+    /// Warnings.
+    warnings: Warnings,
+    /// FBX version.
+    fbx_version: Option<u32>,
+    /// Open nodes stack.
+    open_nodes: Vec<OpenNode>,
+    /// Node name of the recent opened node.
+    ///
+    /// This may be invalid as UTF-8 sequence.
+    recent_node_name: Option<Vec<u8>>,
+    /// Whether to verify the checksum of zlib-compressed arrays.
+    verify_zlib_checksum: bool,
+}
+
+impl<R: Read> RootParser<BasicSource<R>> {
+    /// Creates a new binary parser.
+    ///
+    /// This works with non-seekable sources such as stdin: skipped data is read and discarded.
+    /// Use `from_seekable()` to skip data by seeking.
+    pub fn new(source: R) -> Self {
+        RootParser::from_source(BasicSource::new(source))
+    }
+}
+
+impl<R: Read + io::Seek> RootParser<SeekableSource<R>> {
+    /// Creates a new binary parser.
+    pub fn from_seekable(source: R) -> Self {
+        RootParser::from_source(SeekableSource::new(source))
+    }
+}
+
+impl<R: Read, H: InputHasher> RootParser<HashingSource<R, H>> {
+    /// Creates a new binary parser which computes hash of the consumed input.
+    ///
+    /// The hasher can be retrieved by `parser.source().hasher()` or
+    /// `parser.into_source().into_hasher()`.
+    pub fn from_hashing(source: R, hasher: H) -> Self {
+        RootParser::from_source(HashingSource::new(source, hasher))
+    }
+}
+
+impl<R: Read, W: io::Write> RootParser<TeeSource<R, W>> {
+    /// Creates a new binary parser which copies the consumed input to the writer.
+    ///
+    /// The writer can be retrieved by `parser.into_source().into_writer()`.
+    pub fn from_tee(source: R, writer: W) -> Self {
+        RootParser::from_source(TeeSource::new(source, writer))
+    }
+}
+
+#[cfg(feature = "gzip")]
+impl<R: Read> RootParser<BasicSource<gzip::GzipReader<R>>> {
+    /// Creates a new binary parser for the gzip-compressed FBX data (such as `.fbx.gz` files).
+    pub fn from_gzip(source: R) -> io::Result<Self> {
+        Ok(RootParser::new(gzip::GzipReader::new(source)?))
+    }
+}
+
+impl<R: ParserSource> RootParser<R> {
+    /// Creates a new binary parser from the given parser source.
+    pub fn from_source(source: R) -> Self {
+        RootParser {
+            source,
+            state: Ok(State::Header),
+            warnings: Warnings::new(),
+            fbx_version: None,
+            open_nodes: Vec::new(),
+            recent_node_name: None,
+            verify_zlib_checksum: true,
+        }
+    }
+
+    /// Creates a new binary parser for the nodes following the FBX header of the given version.
+    ///
+    /// The FBX header is not read: the first event is `StartNode` (or `EndFbx`), not `StartFbx`.
+    /// This is for tools which have already consumed the header or know the version in advance.
+    ///
+    /// Node headers refer to absolute offsets, so the position of the source should be the
+    /// offset in the FBX data (see `BasicSource::with_position()`).
+    pub fn from_source_with_version(source: R, fbx_version: u32) -> Self {
+        let mut parser = RootParser::from_source(source);
+        parser.fbx_version = Some(fbx_version);
+        parser.state = Ok(State::NodeEnded);
+        parser
+    }
+
+    /// Returns the reference to the parser source.
+    pub fn source(&self) -> &R {
+        &self.source
+    }
+
+    /// Returns the parser source.
+    pub fn into_source(self) -> R {
+        self.source
+    }
+
+    /// Returns the capabilities of the parser source.
+    ///
+    /// Callers can check which fast paths are active, e.g. seeking on skip.
+    pub fn source_capabilities(&self) -> SourceCapabilities {
+        self.source.capabilities()
+    }
+
+    /// Returns FBX version of the reading input.
+    ///
+    /// Returns `None` if unknown yet, i.e. before `StartFbx` event is read.
+    /// Node events are never read without the version: the parser always reads the FBX header
+    /// first, unless it is created by `from_source_with_version()`.
+    pub fn fbx_version(&self) -> Option<u32> {
+        self.fbx_version
+    }
+
+    /// Returns the parser error if available.
+    pub fn error(&self) -> Option<&Error> {
+        self.state.as_ref().err()
+    }
+
+    /// Returns reference to the warnings.
+    ///
+    /// Only the first `MAX_WARNINGS_PER_GROUP` warnings are stored for each group; use
+    /// `warning_summaries()` to get the numbers of all warnings.
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    /// Returns the summaries of the warnings grouped by kinds and node names.
+    pub fn warning_summaries(&self) -> &[WarningSummary] {
+        self.warnings.summaries()
+    }
+
+    /// Returns whether the checksum of zlib-compressed arrays is verified.
+    pub fn verify_zlib_checksum(&self) -> bool {
+        self.verify_zlib_checksum
+    }
+
+    /// Sets whether the checksum of zlib-compressed arrays is verified.
+    ///
+    /// Verification is enabled by default.
+    /// If disabled, the adler32 checksum at the end of compressed arrays is ignored and
+    /// the arrays are decompressed as raw deflate streams, which is faster.
+    /// This should be disabled only for trusted inputs, because corrupted arrays may then be read
+    /// without errors.
+    pub fn set_verify_zlib_checksum(&mut self, verify: bool) {
+        self.verify_zlib_checksum = verify;
+    }
+
+    /// Returns the number of the opened (and not closed) node.
+    pub fn num_open_nodes(&self) -> usize {
+        self.open_nodes.len()
+    }
+
+    /// Returns the byte range of the current (most recently opened and not closed) node, from
+    /// the node header to the end of the node.
+    ///
+    /// Returns `None` if no nodes are open.
+    pub fn current_node_span(&self) -> Option<NodeSpan> {
+        self.open_nodes.last().map(|node| {
+            NodeSpan {
+                offset: node.header_begin,
+                len: node.end - node.header_begin,
+            }
+        })
+    }
+
+    /// Returns the node name of the recent opened node.
+    ///
+    /// Returns `None` if no nodes are read yet or the name is not valid UTF-8 sequence.
+    pub fn recent_node_name(&self) -> Option<&str> {
+        self.recent_node_name_bytes().and_then(|name| str::from_utf8(name).ok())
+    }
+
+    /// Returns the raw node name of the recent opened node.
+    pub fn recent_node_name_bytes(&self) -> Option<&[u8]> {
+        self.recent_node_name.as_deref()
+    }
+
+    /// Returns the raw node name of the recent opened node with ownership.
+    pub fn take_recent_node_name(&mut self) -> Option<Vec<u8>> {
+        self.recent_node_name.take()
+    }
+
+    /// Set the parser state as finished parsing.
+    fn set_finish(&mut self) {
+        self.state = Err(Error::Finished);
+    }
+
+    /// Set the parser state as error.
+    fn set_error(&mut self, err: &Error) {
+        error!("FBX binary parser error: {}", err);
+        debug!("Parser: {:#?}", self);
+        self.state = Err(err.clone());
+    }
+
+    /// Add warning.
+    fn warn(&mut self, warning: Warning) {
+        self.warnings.warn(warning);
+        debug!("Parser: {:#?}", self);
+    }
+
+    /// Reads FBX header.
+    fn read_fbx_header(&mut self) -> Result<EventBuilder> {
+        let header = read_fbx_header(self)?;
+        self.fbx_version = Some(header.version);
+        self.state = Ok(State::NodeEnded);
+        Ok(header.into())
+    }
+
+    /// Gets event after node start.
+    fn read_after_node_start(&mut self) -> Result<EventBuilder> {
+        // Attributes of recent opened node might remain partially unread.
+        // They should be skipped before getting a next node event.
+        self.skip_attributes()?;
+
+        // Most recent opened node might ends here without a null node header.
+        if let Some(end) = self.open_nodes.last().map(|v| v.end) {
+            if self.source.position() == end {
+                // Most recent opened node ends here (without a null node header).
+                self.state = Ok(State::NodeEnded);
+                self.open_nodes.pop();
+                return Ok(EventBuilder::EndNode);
+            }
+        }
+
+        let builder = self.read_node_event()?;
+        Ok(builder)
+    }
+
+    /// Gets event after node end.
+    fn read_after_node_end(&mut self) -> Result<EventBuilder> {
+        self.read_node_event()
+    }
+
+    /// Reads a next node-related event from the source.
+    ///
+    /// This always returns `Ok(EventBuilder::StartNode)`, `Ok(EventBuilder::EndNode)`,
+    /// `Ok(EventBuilder::EndFbx)` or `Err(_)`.
+    fn read_node_event(&mut self) -> Result<EventBuilder> {
+        // FBX before 7.5 uses 32-bit node offsets, and they cannot point beyond 4 GiB.
+        if let Some(fbx_version) = self.fbx_version {
+            let position = self.source.position();
+            if fbx_version < 7500 && position > u32::MAX as u64 {
+                return Err(Error::NodeOffsetOverflow {
+                    position,
+                    fbx_version,
+                });
+            }
+        }
+        let header_begin = self.source.position();
+        let header = NodeHeader::read_from_parser(self)?;
+        if header.is_node_end() {
+            if let Some(last_node) = self.open_nodes.pop() {
+                // There is open nodes, so this is not end of the FBX.
+                let current_pos = self.source.position();
+                if current_pos != last_node.end {
+                    // Invalid node header.
+                    return Err(Error::WrongNodeEndOffset {
+                        begin: last_node.begin,
+                        expected_end: last_node.end,
+                        real_end: current_pos,
+                    });
+                }
+            } else {
+                if self.state.as_ref().ok() != Some(&State::NodeEnded) {
+                    return Err(Error::InternalState(
+                        "End of implicit root node is read with unexpected parser state",
+                    ));
+                }
+                // No open nodes, so this `EndNode` event indicates the end of
+                // the implicit root node.
+                // FBX file has no more nodes.
+                let footer = self.read_fbx_footer();
+                return Ok(footer.into());
+            }
+
+            self.state = Ok(State::NodeEnded);
+            Ok(EventBuilder::EndNode)
+        } else {
+            // Reuse node name buffer.
+            self.recent_node_name = {
+                // Take node name buffer if the buffer remains.
+                // Create a new buffer if the buffer was already taken.
+                let mut vecbuf = self.recent_node_name
+                    .take()
+                    .map(|mut v| {
+                        // Resize buffer.
+                        // This reallocates only if the buffer is too small.
+                        v.resize(header.bytelen_name as usize, 0);
+                        v
+                    })
+                    .unwrap_or_else(|| vec![0; header.bytelen_name as usize]);
+                // Read the node name into the buffer.
+                // Note that the name is not validated here, because some files have names
+                // which are not valid UTF-8 sequences.
+                self.source.read_exact(&mut vecbuf)?;
+                self.warnings.set_node_name(&vecbuf);
+                Some(vecbuf)
+            };
+
+            let current_pos = self.source.position();
+            let attributes_end = current_pos.saturating_add(header.bytelen_attributes);
+            // Nodes must end after their attributes and inside their parents.
+            // Otherwise skipping nodes may not advance, and the parser may loop forever.
+            let parent_end = self.open_nodes.last().map(|parent| parent.end);
+            if header.end_offset < attributes_end ||
+                parent_end.is_some_and(|parent_end| header.end_offset > parent_end)
+            {
+                return Err(Error::InvalidNodeStructure {
+                    begin: header_begin,
+                    end: header.end_offset,
+                    min_end: attributes_end,
+                    max_end: parent_end,
+                });
+            }
+            self.open_nodes.push(OpenNode {
+                header_begin,
+                begin: current_pos,
+                end: header.end_offset,
+                attributes_end,
+            });
+            if self.open_nodes.len() > limits::MAX_NODE_DEPTH {
+                let depth = self.open_nodes.len();
+                self.warn(Warning::NodeTooDeep {
+                    depth,
+                    position: current_pos,
+                });
+            }
+            if header.num_attributes > limits::MAX_NODE_ATTRIBUTES {
+                self.warn(Warning::TooManyAttributes {
+                    num_attributes: header.num_attributes,
+                    position: current_pos,
+                });
+            }
+
+            // Zero or more attributes come after node start.
+            self.state = Ok(State::NodeStarted);
+            Ok(StartNodeBuilder { header }.into())
+        }
+    }
+
+    /// Reads an FBX footer.
+    fn read_fbx_footer(&mut self) -> Result<FbxFooter> {
+        self.set_finish();
+        FbxFooter::read_from_parser(self)
+    }
+
+    /// Skip attributes of the most recent opened node.
+    fn skip_attributes(&mut self) -> Result<()> {
+        let attributes_end = self.open_nodes
+            .last()
+            .ok_or(Error::InternalState("Attributes are skipped but no nodes are open"))?
+            .attributes_end;
+        self.source.skip_to(attributes_end)?;
+        Ok(())
+    }
+}
+
+impl<R: ParserSource> Parser<R> for RootParser<R> {
+    fn root_parser(&self) -> &RootParser<R> {
+        self
+    }
+
+    fn next_event(&mut self) -> Result<Event<'_, R>> {
+        let builder = match self.state.clone()? {
+            State::Header => self.read_fbx_header(),
+            State::NodeStarted => self.read_after_node_start(),
+            State::NodeEnded => self.read_after_node_end(),
+        };
+        if let Err(ref err) = builder {
+            self.set_error(err);
+        }
+        Ok(builder?.build(self))
+    }
+
+    fn skip_current_node(&mut self) -> Result<bool> {
+        if let Some(end) = self.open_nodes.pop().map(|v| v.end) {
+            self.source.skip_to(end)?;
+            self.state = Ok(State::NodeEnded);
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn subtree_parser(&mut self) -> SubtreeParser<'_, R> {
+        SubtreeParser::new(self)
+    }
+}
+
+
+/// Pull parser for a subtree of the FBX binary.
+pub struct SubtreeParser<'a, R: 'a> {
+    /// Root parser.
+    root_parser: &'a mut RootParser<R>,
+    /// Initial depth.
+    ///
+    /// Depth of the implicit root node is `0`.
+    initial_depth: usize,
+}
+
+impl<'a, R: 'a + ParserSource> SubtreeParser<'a, R> {
+    /// Creates a new `SubtreeParser`.
+    pub fn new(root_parser: &'a mut RootParser<R>) -> Self {
+        let initial_depth = root_parser.num_open_nodes();
+        SubtreeParser {
+            root_parser,
+            initial_depth,
+        }
+    }
+
+    /// Checks if the subtree parser can emit more events.
+    ///
+    /// Returns `Ok(())` if more events can be read,
+    /// `Err(Error::Finished)` if the subtree is all read,
+    /// `Err(_)` if error happened.
+    fn ensure_not_finished(&self) -> Result<()> {
+        if self.is_finished()? {
+            Err(Error::Finished)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Checks if the subtree parser finished reading events.
+    fn is_finished(&self) -> Result<bool> {
+        if let Some(err) = self.root_parser.error() {
+            return Err(err.clone());
+        }
+        Ok(self.root_parser.num_open_nodes() < self.initial_depth)
+    }
+
+    /// Skip to the end of the subtree parser's readable range.
+    ///
+    /// Note that this is not intended to called for `SubtreeParser` reading implicit root node.
+    /// If the subtree parser can read whole data, the parser skips all events including `FbxEnd`
+    /// and returns `Err(Error::Finished)`.
+    pub fn skip_to_end(self) -> Result<()> {
+        if self.is_finished()? {
+            return Ok(());
+        }
+        self.root_parser.open_nodes.truncate(self.initial_depth);
+        if let Some(end) = self.root_parser.open_nodes.pop().map(|v| v.end) {
+            self.root_parser.source.skip_to(end)?;
+            self.root_parser.state = Ok(State::NodeEnded);
+            Ok(())
+        } else {
+            self.root_parser.state = Err(Error::Finished);
+            Err(Error::Finished)
+        }
+    }
+}
+
+impl<'a, R: 'a + ParserSource> Parser<R> for SubtreeParser<'a, R> {
+    fn root_parser(&self) -> &RootParser<R> {
+        self.root_parser
+    }
+
+    fn next_event(&mut self) -> Result<Event<'_, R>> {
+        self.ensure_not_finished()?;
+        self.root_parser.next_event()
+    }
+
+    fn skip_current_node(&mut self) -> Result<bool> {
+        self.ensure_not_finished()?;
+        self.root_parser.skip_current_node()
+    }
+
+    fn subtree_parser(&mut self) -> SubtreeParser<'_, R> {
+        SubtreeParser::new(self.root_parser)
+    }
+}
+
+impl<R: ParserSource, P: Parser<R>> Parser<R> for &mut P {
+    fn root_parser(&self) -> &RootParser<R> {
+        (**self).root_parser()
+    }
+
+    fn next_event(&mut self) -> Result<Event<'_, R>> {
+        (**self).next_event()
+    }
+
+    fn skip_current_node(&mut self) -> Result<bool> {
+        (**self).skip_current_node()
+    }
+
+    fn subtree_parser(&mut self) -> SubtreeParser<'_, R> {
+        (**self).subtree_parser()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::cmp;
+    use std::io::{self, Read, Seek, SeekFrom};
+    use super::{Parser, ParserSource, BasicSource, RootParser, Event, Error, Warning, Warnings};
+    use super::MAX_WARNINGS_PER_GROUP;
+
+    /// Sparse stream with zero-filled gaps.
+    struct SparseSource {
+        /// Data chunks and their offsets.
+        chunks: Vec<(u64, Vec<u8>)>,
+        /// Length of the stream.
+        len: u64,
+        /// Current position.
+        position: u64,
+    }
+
+    impl Read for SparseSource {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let size = cmp::min(buf.len() as u64, self.len - self.position) as usize;
+            for (i, byte) in buf[..size].iter_mut().enumerate() {
+                let pos = self.position + i as u64;
+                *byte = self.chunks
+                    .iter()
+                    .find(|&&(offset, ref data)| offset <= pos && pos < offset + data.len() as u64)
+                    .map_or(0, |&(offset, ref data)| data[(pos - offset) as usize]);
+            }
+            self.position += size as u64;
+            Ok(size)
+        }
+    }
+
+    impl Seek for SparseSource {
+        fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+            self.position = match pos {
+                SeekFrom::Start(v) => v,
+                SeekFrom::Current(v) => (self.position as i64 + v) as u64,
+                SeekFrom::End(v) => (self.len as i64 + v) as u64,
+            };
+            Ok(self.position)
+        }
+    }
+
+    /// Creates FBX 7.5 node header.
+    fn node_header_7500(
+        end_offset: u64,
+        num_attrs: u64,
+        bytelen_attrs: u64,
+        name: &str,
+    ) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&end_offset.to_le_bytes());
+        bytes.extend_from_slice(&num_attrs.to_le_bytes());
+        bytes.extend_from_slice(&bytelen_attrs.to_le_bytes());
+        bytes.push(name.len() as u8);
+        bytes.extend_from_slice(name.as_bytes());
+        bytes
+    }
+
+    fn fbx_header(version: u32) -> Vec<u8> {
+        let mut bytes = b"Kaydara FBX Binary  \x00\x1a\x00".to_vec();
+        bytes.extend_from_slice(&version.to_le_bytes());
+        bytes
+    }
+
+    #[test]
+    fn test_large_file_7500() {
+        const GIB: u64 = 1 << 30;
+        // Node `A` has three binary attributes of 2 GiB, and node `B` follows it.
+        let attr_len = 2 * GIB;
+        let header = fbx_header(7500);
+        let a_header_len = 25 + 1;
+        let a_attrs_begin = header.len() as u64 + a_header_len;
+        let a_end = a_attrs_begin + 3 * (5 + attr_len);
+        let mut chunks = vec![(0, header)];
+        chunks.push((
+            chunks[0].1.len() as u64,
+            node_header_7500(a_end, 3, 3 * (5 + attr_len), "A"),
+        ));
+        for i in 0..3 {
+            let mut attr_header = vec![b'R'];
+            attr_header.extend_from_slice(&(attr_len as u32).to_le_bytes());
+            chunks.push((a_attrs_begin + i * (5 + attr_len), attr_header));
+        }
+        let b_header = node_header_7500(a_end + 26, 0, 0, "B");
+        chunks.push((a_end, b_header));
+        let len = a_end + 26 + 25;
+        let source = SparseSource {
+            chunks,
+            len,
+            position: 0,
+        };
+
+        let mut parser = RootParser::from_seekable(source);
+        match parser.next_event().expect("Failed to read FBX header") {
+            Event::StartFbx(header) => assert_eq!(header.version, 7500),
+            ev => panic!("Unexpected event: {:?}", ev),
+        }
+        match parser.next_event().expect("Failed to read node `A`") {
+            Event::StartNode(mut node) => {
+                assert_eq!(node.name().expect("Invalid node name"), "A");
+                let mut num_attrs = 0;
+                while node.attributes.next_attribute().expect("Invalid attribute").is_some() {
+                    num_attrs += 1;
+                }
+                assert_eq!(num_attrs, 3);
+            },
+            ev => panic!("Unexpected event: {:?}", ev),
+        }
+        match parser.next_event().expect("Failed to close node `A`") {
+            Event::EndNode => {},
+            ev => panic!("Unexpected event: {:?}", ev),
+        }
+        match parser.next_event().expect("Failed to read node `B`") {
+            Event::StartNode(node) => assert_eq!(node.name().expect("Invalid node name"), "B"),
+            ev => panic!("Unexpected event: {:?}", ev),
+        }
+        assert!(parser.source.position() > 4 * GIB);
+    }
+
+    #[test]
+    fn test_node_offset_overflow_7400() {
+        let source = SparseSource {
+            chunks: vec![(0, fbx_header(7400))],
+            len: 1 << 33,
+            position: 0,
+        };
+        let mut parser = RootParser::from_seekable(source);
+        parser.next_event().expect("Failed to read FBX header");
+        parser.source.seek(SeekFrom::Start(1 << 32)).expect("Failed to seek");
+        match parser.next_event() {
+            Err(Error::NodeOffsetOverflow { .. }) => {},
+            res => panic!("Unexpected result: {:?}", res),
+        }
+    }
+
+    #[test]
+    fn test_invalid_node_structure() {
+        // Node `A` ends before its header.
+        let mut data = fbx_header(7500);
+        data.extend(node_header_7500(5, 0, 0, "A"));
+        let mut parser = RootParser::new(io::Cursor::new(data));
+        parser.next_event().expect("Failed to read FBX header");
+        match parser.next_event() {
+            Err(Error::InvalidNodeStructure { max_end: None, .. }) => {},
+            res => panic!("Unexpected result: {:?}", res),
+        }
+
+        // Node `B` ends beyond its parent `A`.
+        let mut data = fbx_header(7500);
+        let a_end = (data.len() + 2 * 26) as u64;
+        data.extend(node_header_7500(a_end, 0, 0, "A"));
+        data.extend(node_header_7500(a_end + 1, 0, 0, "B"));
+        let mut parser = RootParser::new(io::Cursor::new(data));
+        parser.next_event().expect("Failed to read FBX header");
+        parser.next_event().expect("Failed to read node `A`");
+        match parser.next_event() {
+            Err(Error::InvalidNodeStructure { end, max_end, .. }) => {
+                assert_eq!((end, max_end), (a_end + 1, Some(a_end)));
+            },
+            res => panic!("Unexpected result: {:?}", res),
+        }
+    }
+
+    #[test]
+    fn attribute_count_mismatch() {
+        use super::{Attribute, PrimitiveAttribute};
+
+        // Node `A` declares three attributes, but its attributes are only 5 bytes long.
+        let mut attrs = vec![b'I'];
+        attrs.extend_from_slice(&42i32.to_le_bytes());
+        let mut data = fbx_header(7500);
+        let attrs_end = (data.len() + 25 + 1 + attrs.len()) as u64;
+        data.extend(node_header_7500(attrs_end, 3, attrs.len() as u64, "A"));
+        data.extend(attrs);
+        data.extend(node_header_7500(attrs_end + 26, 0, 0, "B"));
+        let mut parser = RootParser::new(io::Cursor::new(data));
+        parser.next_event().expect("Failed to read FBX header");
+        match parser.next_event().expect("Failed to read node `A`") {
+            Event::StartNode(mut node) => {
+                assert_eq!(node.attributes.num_attributes(), 3);
+                match node.attributes.next_attribute().expect("Failed to read attribute") {
+                    Some(Attribute::Primitive(PrimitiveAttribute::I32(42))) => {},
+                    attr => panic!("Unexpected attribute: {:?}", attr),
+                }
+                assert!(node.attributes.next_attribute().expect("Unexpected error").is_none());
+                assert_eq!(node.attributes.num_attributes(), 1);
+                assert_eq!(node.attributes.rest_attributes(), 0);
+            },
+            ev => panic!("Unexpected event: {:?}", ev),
+        }
+        match *parser.warnings() {
+            [Warning::AttributeCountMismatch { declared, read, position }] => {
+                assert_eq!((declared, read, position), (3, 1, attrs_end));
+            },
+            ref warnings => panic!("Unexpected warnings: {:?}", warnings),
+        }
+        // The next node is read from the end of the attributes given by the byte length.
+        parser.next_event().expect("Failed to read the end of node `A`");
+        match parser.next_event().expect("Failed to read node `B`") {
+            Event::StartNode(node) => assert_eq!(node.name().expect("Invalid node name"), "B"),
+            ev => panic!("Unexpected event: {:?}", ev),
+        }
+    }
+
+    #[test]
+    fn array_longer_than_data() {
+        use super::{Attribute, ArrayAttribute};
+
+        // Arrays declare more elements than their non-compressed data holds.
+        let mut attrs = Vec::new();
+        for &num_elements in &[4u32, u32::MAX] {
+            attrs.push(b'd');
+            attrs.extend_from_slice(&num_elements.to_le_bytes());
+            attrs.extend_from_slice(&0u32.to_le_bytes());
+            attrs.extend_from_slice(&8u32.to_le_bytes());
+            attrs.extend_from_slice(&1.5f64.to_le_bytes());
+        }
+        let mut data = fbx_header(7500);
+        let a_end = (data.len() + 25 + 1 + attrs.len()) as u64;
+        data.extend(node_header_7500(a_end, 2, attrs.len() as u64, "A"));
+        data.extend(attrs);
+        data.extend(node_header_7500(a_end + 26, 0, 0, "B"));
+        let mut parser = RootParser::new(io::Cursor::new(data));
+        parser.next_event().expect("Failed to read FBX header");
+        match parser.next_event().expect("Failed to read node `A`") {
+            Event::StartNode(mut node) => {
+                for &bytelen in &[32, u64::from(u32::MAX) * 8] {
+                    let mut vec = Vec::new();
+                    match node.attributes.next_attribute().expect("Failed to read attribute") {
+                        Some(Attribute::Array(ArrayAttribute::F64(arr))) => {
+                            match arr.append_to_vec(&mut vec) {
+                                Err(Error::AttributeTooLargeForPlatform { bytelen: len }) => {
+                                    assert_eq!(len, bytelen)
+                                },
+                                res => panic!("Unexpected result: {:?}", res),
+                            }
+                        },
+                        attr => panic!("Unexpected attribute: {:?}", attr),
+                    }
+                    // The error is returned before allocation.
+                    assert_eq!(vec.capacity(), 0);
+                }
+            },
+            ev => panic!("Unexpected event: {:?}", ev),
+        }
+        parser.next_event().expect("Failed to read the end of node `A`");
+        match parser.next_event().expect("Failed to read node `B`") {
+            Event::StartNode(node) => assert_eq!(node.name().expect("Invalid node name"), "B"),
+            ev => panic!("Unexpected event: {:?}", ev),
+        }
+    }
+
+    /// Reads `i32` array attribute of the node `A` in the given FBX 7.5 data.
+    #[cfg(any(feature = "flate2", feature = "libflate"))]
+    fn read_i32_array(data: Vec<u8>, verify_checksum: bool) -> super::Result<Vec<i32>> {
+        use super::{Attribute, ArrayAttribute};
+
+        let mut parser = RootParser::new(io::Cursor::new(data));
+        parser.set_verify_zlib_checksum(verify_checksum);
+        parser.next_event().expect("Failed to read FBX header");
+        match parser.next_event().expect("Failed to read node `A`") {
+            Event::StartNode(mut node) => {
+                match node.attributes.next_attribute()? {
+                    Some(Attribute::Array(ArrayAttribute::I32(arr))) => arr.into_vec(),
+                    attr => panic!("Unexpected attribute: {:?}", attr),
+                }
+            },
+            ev => panic!("Unexpected event: {:?}", ev),
+        }
+    }
+
+    #[test]
+    #[cfg(any(feature = "flate2", feature = "libflate"))]
+    fn test_zlib_checksum_verification() {
+        // Zlib stream with a stored (non-compressed) deflate block and broken adler32 checksum.
+        let elements = [1i32, -2, 3];
+        let mut zlib = vec![0x78, 0x01, 0x01, 12, 0, !12, !0];
+        for v in &elements {
+            zlib.extend_from_slice(&v.to_le_bytes());
+        }
+        zlib.extend_from_slice(&[0xde, 0xad, 0xbe, 0xef]);
+        let mut attrs = vec![b'i'];
+        attrs.extend_from_slice(&(elements.len() as u32).to_le_bytes());
+        attrs.extend_from_slice(&1u32.to_le_bytes());
+        attrs.extend_from_slice(&(zlib.len() as u32).to_le_bytes());
+        attrs.extend_from_slice(&zlib);
+        let mut data = fbx_header(7500);
+        let end = (data.len() + 25 + 1 + attrs.len()) as u64;
+        data.extend(node_header_7500(end, 1, attrs.len() as u64, "A"));
+        data.extend(attrs);
+
+        assert!(read_i32_array(data.clone(), true).is_err());
+        assert_eq!(
+            read_i32_array(data, false).expect("Failed to read array"),
+            elements
+        );
+    }
+
+    #[test]
+    fn test_node_too_deep_warning() {
+        use super::limits::MAX_NODE_DEPTH;
+        use super::Warning;
+
+        // Nested nodes without attributes.
+        let depth = MAX_NODE_DEPTH + 1;
+        let mut data = fbx_header(7500);
+        let mut end = data.len() as u64 + 26 * depth as u64 + 25 * (depth as u64 - 1);
+        for _ in 0..depth {
+            data.extend(node_header_7500(end, 0, 0, "N"));
+            end -= 25;
+        }
+        let mut parser = RootParser::new(io::Cursor::new(data));
+        parser.next_event().expect("Failed to read FBX header");
+        for _ in 0..depth {
+            match parser.next_event().expect("Failed to read node") {
+                Event::StartNode(_) => {},
+                ev => panic!("Unexpected event: {:?}", ev),
+            }
+        }
+        match *parser.warnings() {
+            [Warning::NodeTooDeep { depth: d, .. }] => assert_eq!(d, depth),
+            ref warnings => panic!("Unexpected warnings: {:?}", warnings),
+        }
+    }
+
+    #[test]
+    fn warning_dedup() {
+        let warning = |position| Warning::InvalidBooleanAttributeValue {
+            got: 1,
+            assumed: true,
+            position,
+        };
+        let mut warnings = Warnings::new();
+        warnings.set_node_name(b"A");
+        for i in 0..100 {
+            warnings.warn(warning(i));
+        }
+        warnings.set_node_name(b"B");
+        warnings.warn(warning(100));
+        warnings.warn(Warning::UnexpectedBytesAfterMagic([0, 0]));
+
+        assert_eq!(warnings.len(), MAX_WARNINGS_PER_GROUP + 2);
+        assert_eq!(warnings.total_count(), 102);
+        let summaries = warnings.summaries();
+        assert_eq!(summaries.len(), 3);
+        assert_eq!(summaries[0].node_name.as_deref(), Some("A"));
+        assert_eq!(summaries[0].count, 100);
+        assert_eq!(summaries[1].code, summaries[0].code);
+        assert_eq!(summaries[2].count, 1);
+    }
+
+    /// Reads all events and attributes until the end of the document or an error.
+    fn drain<R: ParserSource>(mut parser: RootParser<R>) -> super::Result<()> {
+        loop {
+            match parser.next_event()? {
+                Event::StartNode(node) => {
+                    super::OwnedAttribute::load_attrs_from_parser_event(node.attributes)?;
+                },
+                Event::EndFbx(_) => return Ok(()),
+                _ => {},
+            }
+        }
+    }
+
+    /// Parses the given bytes to the end and discards the result.
+    fn parse_all(bytes: &[u8]) {
+        let _ = drain(RootParser::new(io::Cursor::new(bytes)));
+        let _ = drain(RootParser::from_seekable(io::Cursor::new(bytes)));
+    }
+
+    #[test]
+    fn malformed_input_no_panic() {
+        let source = concat!(
+            "; FBX 7.4.0 project file\n",
+            "A: 1, 2.5, \"str\", \"\" {\n",
+            "\tB: *4 {\n\t\ta: 1.0,2.0,3.0,4.0\n\t}\n",
+            "\tC: *3 {\n\t\ta: 1,2,3\n\t}\n",
+            "}\n",
+            "D: 42\n",
+        );
+        let bytes = ::pull_parser::ascii::AsciiDocument::parse(source)
+            .unwrap()
+            .to_binary()
+            .unwrap();
+        for len in 0..bytes.len() {
+            parse_all(&bytes[..len]);
+        }
+        let mut broken = bytes.clone();
+        for i in 0..bytes.len() {
+            for &mask in &[0x01, 0x80, 0xff] {
+                broken[i] = bytes[i] ^ mask;
+                parse_all(&broken);
+            }
+            broken[i] = bytes[i];
+        }
+
+        // ASCII lexer and parser.
+        let parse_ascii = |src: &str| {
+            if let Ok(doc) = ::pull_parser::ascii::AsciiDocument::parse(src) {
+                if let Ok(bytes) = doc.to_binary() {
+                    parse_all(&bytes);
+                }
+            }
+        };
+        for len in (0..source.len()).filter(|&len| source.is_char_boundary(len)) {
+            parse_ascii(&source[..len]);
+        }
+        let mut broken = source.as_bytes().to_vec();
+        for i in 0..source.len() {
+            for &mask in &[0x01, 0x20, 0x7f] {
+                broken[i] = source.as_bytes()[i] ^ mask;
+                if let Ok(src) = ::std::str::from_utf8(&broken) {
+                    parse_ascii(src);
+                }
+            }
+            broken[i] = source.as_bytes()[i];
+        }
+        let huge_arrays = [
+            "; FBX 7.4.0 project file\nA: *18446744073709551615 {\n\ta: 1\n}\n",
+            "; FBX 7.4.0 project file\nA: *4000000000000 {\n\ta: 1\n}\n",
+        ];
+        for src in &huge_arrays {
+            assert!(::pull_parser::ascii::AsciiDocument::parse(src).is_err());
+        }
+        let deep = "A: {".repeat(100_000);
+        assert!(::pull_parser::ascii::AsciiDocument::parse(&deep).is_err());
+        let bad_version = "FBXHeaderExtension: {\n\tFBXVersion: 4294967296\n}\n";
+        assert!(::pull_parser::ascii::AsciiDocument::parse(bad_version).is_err());
+    }
+
+    #[test]
+    fn parse_without_header() {
+        let source = "; FBX 7.4.0 project file\nA: 1 {\n\tB: 2\n}\n";
+        let bytes = ::pull_parser::ascii::AsciiDocument::parse(source)
+            .unwrap()
+            .to_binary()
+            .unwrap();
+        // Magic (21 bytes), unknown 2 bytes, and version (4 bytes).
+        let header_len = 27;
+        let source = BasicSource::with_position(&bytes[header_len..], header_len as u64);
+        let mut parser = RootParser::from_source_with_version(source, 7400);
+        assert_eq!(parser.fbx_version(), Some(7400));
+        match parser.next_event().expect("Failed to read node `A`") {
+            Event::StartNode(node) => assert_eq!(node.name().expect("Invalid node name"), "A"),
+            ev => panic!("Unexpected event: {:?}", ev),
+        }
+        let result = drain(RootParser::from_source_with_version(
+            BasicSource::with_position(&bytes[header_len..], header_len as u64),
+            7400,
+        ));
+        assert!(result.is_ok());
+    }
+
+    /// Seekable stream which counts the read bytes.
+    struct CountingSource<'a> {
+        inner: io::Cursor<&'a [u8]>,
+        read_len: &'a Cell<usize>,
+    }
+
+    impl<'a> Read for CountingSource<'a> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let size = self.inner.read(buf)?;
+            self.read_len.set(self.read_len.get() + size);
+            Ok(size)
+        }
+    }
+
+    impl<'a> Seek for CountingSource<'a> {
+        fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+            self.inner.seek(pos)
+        }
+    }
+
+    #[test]
+    fn skip_by_seek() {
+        let mut source = "; FBX 7.4.0 project file\nA: 1 {\n\tB: *4096 {\n\t\ta: 0".to_owned();
+        for _ in 1..4096 {
+            source.push_str(",0");
+        }
+        source.push_str("\n\t}\n}\nC: 2\n");
+        let bytes = ::pull_parser::ascii::AsciiDocument::parse(&source)
+            .unwrap()
+            .to_binary()
+            .unwrap();
+        assert!(bytes.len() > 4096);
+        let read_len = Cell::new(0);
+        let mut parser = RootParser::from_seekable(CountingSource {
+            inner: io::Cursor::new(&bytes),
+            read_len: &read_len,
+        });
+        loop {
+            match parser.next_event().expect("Failed to read event") {
+                Event::StartNode(node) => if node.name().expect("Invalid node name") == "A" {
+                    break;
+                },
+                Event::StartFbx(_) => {},
+                ev => panic!("Unexpected event: {:?}", ev),
+            }
+        }
+        assert!(parser.skip_current_node().expect("Failed to skip node `A`"));
+        match parser.next_event().expect("Failed to read node `C`") {
+            Event::StartNode(node) => assert_eq!(node.name().expect("Invalid node name"), "C"),
+            ev => panic!("Unexpected event: {:?}", ev),
+        }
+        // The array in node `B` is not read.
+        assert!(read_len.get() < 256);
+    }
+}
@@ -0,0 +1,337 @@
+//! Owned node attribute.
+
+use std::cmp;
+use std::convert::TryFrom;
+
+use pull_parser::{Attributes, Attribute, ParserSource};
+use pull_parser::Result as ParseResult;
+use pull_parser::utils::AttributeValues;
+use approx::ApproxEq;
+use memory::MemoryUsage;
+
+
+/// Owned node attribute.
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub enum OwnedAttribute {
+    /// `bool`.
+    Bool(bool),
+    /// `i16`.
+    I16(i16),
+    /// `i32`.
+    I32(i32),
+    /// `i64`.
+    I64(i64),
+    /// `f32`.
+    F32(f32),
+    /// `f64`.
+    F64(f64),
+    /// `Box<[bool]>`.
+    ArrBool(Box<[bool]>),
+    /// `Box<[i32]>`.
+    ArrI32(Box<[i32]>),
+    /// `Box<[i64]>`.
+    ArrI64(Box<[i64]>),
+    /// `Box<[f32]>`.
+    ArrF32(Box<[f32]>),
+    /// `Box<[f64]>`.
+    ArrF64(Box<[f64]>),
+    /// `String`.
+    String(::std::result::Result<String, Vec<u8>>),
+    /// `Box<[u8]>`.
+    Binary(Box<[u8]>),
+}
+
+impl OwnedAttribute {
+    /// Returns the length of the variable-length value in bytes.
+    ///
+    /// This is the uncompressed length for arrays, and `0` for primitive values.
+    /// FBX binary stores these lengths as `u32`.
+    pub fn payload_len(&self) -> u64 {
+        use std::mem::size_of;
+
+        /// Returns the length of the array in bytes.
+        fn arr_len<T>(arr: &[T]) -> u64 {
+            (arr.len() as u64).saturating_mul(size_of::<T>() as u64)
+        }
+
+        match *self {
+            OwnedAttribute::Bool(_) |
+            OwnedAttribute::I16(_) |
+            OwnedAttribute::I32(_) |
+            OwnedAttribute::I64(_) |
+            OwnedAttribute::F32(_) |
+            OwnedAttribute::F64(_) => 0,
+            OwnedAttribute::ArrBool(ref v) => arr_len(v),
+            OwnedAttribute::ArrI32(ref v) => arr_len(v),
+            OwnedAttribute::ArrI64(ref v) => arr_len(v),
+            OwnedAttribute::ArrF32(ref v) => arr_len(v),
+            OwnedAttribute::ArrF64(ref v) => arr_len(v),
+            OwnedAttribute::String(Ok(ref v)) => v.len() as u64,
+            OwnedAttribute::String(Err(ref v)) => v.len() as u64,
+            OwnedAttribute::Binary(ref v) => v.len() as u64,
+        }
+    }
+
+    /// Loads `OwnedAttribute`s from `pull_parser::Attributes`.
+    pub fn load_attrs_from_parser_event<R>(mut attrs: Attributes<R>) -> ParseResult<Vec<Self>>
+    where
+        R: ParserSource,
+    {
+        // The attribute count comes from the input and is not trusted for preallocation.
+        let mut result = Vec::with_capacity(cmp::min(attrs.num_attributes(), 64) as usize);
+        while let Some(attr) = attrs.next_attribute()? {
+            result.push(Self::load_from_parser_event(attr)?);
+        }
+        Ok(result)
+    }
+
+    /// Loads an `OwnedAttribute` from `pull_parser::Attribute`.
+    pub fn load_from_parser_event<R>(attr: Attribute<R>) -> ParseResult<Self>
+    where
+        R: ParserSource,
+    {
+        use pull_parser::{PrimitiveAttribute, ArrayAttribute, SpecialAttributeType};
+        Ok(match attr {
+            Attribute::Primitive(PrimitiveAttribute::Bool(v)) => OwnedAttribute::Bool(v),
+            Attribute::Primitive(PrimitiveAttribute::I16(v)) => OwnedAttribute::I16(v),
+            Attribute::Primitive(PrimitiveAttribute::I32(v)) => OwnedAttribute::I32(v),
+            Attribute::Primitive(PrimitiveAttribute::I64(v)) => OwnedAttribute::I64(v),
+            Attribute::Primitive(PrimitiveAttribute::F32(v)) => OwnedAttribute::F32(v),
+            Attribute::Primitive(PrimitiveAttribute::F64(v)) => OwnedAttribute::F64(v),
+            Attribute::Array(ArrayAttribute::Bool(arr)) => {
+                OwnedAttribute::ArrBool(arr.into_vec()?.into_boxed_slice())
+            },
+            Attribute::Array(ArrayAttribute::I32(arr)) => {
+                OwnedAttribute::ArrI32(arr.into_vec()?.into_boxed_slice())
+            },
+            Attribute::Array(ArrayAttribute::I64(arr)) => {
+                OwnedAttribute::ArrI64(arr.into_vec()?.into_boxed_slice())
+            },
+            Attribute::Array(ArrayAttribute::F32(arr)) => {
+                OwnedAttribute::ArrF32(arr.into_vec()?.into_boxed_slice())
+            },
+            Attribute::Array(ArrayAttribute::F64(arr)) => {
+                OwnedAttribute::ArrF64(arr.into_vec()?.into_boxed_slice())
+            },
+            Attribute::Special(v) => {
+                match v.value_type() {
+                    SpecialAttributeType::Binary => {
+                        OwnedAttribute::Binary(v.into_vec()?.into_boxed_slice())
+                    },
+                    SpecialAttributeType::String => {
+                        OwnedAttribute::String(match String::from_utf8(v.into_vec()?) {
+                            Ok(s) => Ok(s),
+                            Err(e) => Err(e.into_bytes()),
+                        })
+                    },
+                }
+            },
+        })
+    }
+
+    /// Converts the numeric value into `i64` without loss.
+    ///
+    /// Floating point numbers are accepted only if they are integers in the range of `i64`.
+    /// Returns `None` for booleans, arrays, and strings.
+    pub fn to_i64(&self) -> Option<i64> {
+        /// Converts the integral float into `i64`.
+        fn float_to_i64(v: f64) -> Option<i64> {
+            // `i64::MAX as f64` is 2^63, which is out of range.
+            if v.fract() == 0.0 && v >= i64::MIN as f64 && v < i64::MAX as f64 {
+                Some(v as i64)
+            } else {
+                None
+            }
+        }
+
+        match *self {
+            OwnedAttribute::I16(v) => Some(i64::from(v)),
+            OwnedAttribute::I32(v) => Some(i64::from(v)),
+            OwnedAttribute::I64(v) => Some(v),
+            OwnedAttribute::F32(v) => float_to_i64(f64::from(v)),
+            OwnedAttribute::F64(v) => float_to_i64(v),
+            _ => None,
+        }
+    }
+
+    /// Converts the numeric value into `f64` without loss.
+    ///
+    /// `i64` values are accepted only if their absolute values are at most 2^53.
+    /// Returns `None` for booleans, arrays, and strings.
+    pub fn to_f64(&self) -> Option<f64> {
+        /// Max integer which can be exactly represented by `f64`.
+        const MAX_EXACT_INT: i64 = 1 << 53;
+
+        match *self {
+            OwnedAttribute::I16(v) => Some(f64::from(v)),
+            OwnedAttribute::I32(v) => Some(f64::from(v)),
+            OwnedAttribute::I64(v) if v.abs() <= MAX_EXACT_INT => Some(v as f64),
+            OwnedAttribute::F32(v) => Some(f64::from(v)),
+            OwnedAttribute::F64(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Converts the scalar value into `i64`, possibly with loss.
+    ///
+    /// Booleans are `0` or `1`, and floating point numbers are truncated and saturated.
+    /// Returns `None` for NaN, arrays, and strings.
+    pub fn to_i64_lossy(&self) -> Option<i64> {
+        match *self {
+            OwnedAttribute::Bool(v) => Some(i64::from(v)),
+            OwnedAttribute::F32(v) if !v.is_nan() => Some(v as i64),
+            OwnedAttribute::F64(v) if !v.is_nan() => Some(v as i64),
+            _ => self.to_i64(),
+        }
+    }
+
+    /// Converts the scalar value into `f64`, possibly with loss.
+    ///
+    /// Booleans are `0.0` or `1.0`.
+    /// Returns `None` for arrays and strings.
+    pub fn to_f64_lossy(&self) -> Option<f64> {
+        match *self {
+            OwnedAttribute::Bool(v) => Some(if v { 1.0 } else { 0.0 }),
+            OwnedAttribute::I64(v) => Some(v as f64),
+            _ => self.to_f64(),
+        }
+    }
+}
+
+
+/// Implements `From<$ty>` for `OwnedAttribute` and `TryFrom<OwnedAttribute>` for `$ty`.
+///
+/// `TryFrom` returns the attribute as is if the type doesn't match.
+macro_rules! impl_owned_attribute_conversion {
+    ($ty:ty, $variant:ident, |$v:ident| $from:expr, |$w:ident| $into:expr) => {
+        impl From<$ty> for OwnedAttribute {
+            fn from($v: $ty) -> Self {
+                OwnedAttribute::$variant($from)
+            }
+        }
+
+        impl TryFrom<OwnedAttribute> for $ty {
+            type Error = OwnedAttribute;
+
+            fn try_from(attr: OwnedAttribute) -> ::std::result::Result<Self, OwnedAttribute> {
+                match attr {
+                    OwnedAttribute::$variant($w) => Ok($into),
+                    attr => Err(attr),
+                }
+            }
+        }
+    };
+    ($ty:ty, $variant:ident) => {
+        impl_owned_attribute_conversion!($ty, $variant, |v| v, |v| v);
+    };
+}
+
+impl_owned_attribute_conversion!(bool, Bool);
+impl_owned_attribute_conversion!(i16, I16);
+impl_owned_attribute_conversion!(i32, I32);
+impl_owned_attribute_conversion!(i64, I64);
+impl_owned_attribute_conversion!(f32, F32);
+impl_owned_attribute_conversion!(f64, F64);
+impl_owned_attribute_conversion!(Vec<bool>, ArrBool, |v| v.into(), |v| v.into());
+impl_owned_attribute_conversion!(Vec<i32>, ArrI32, |v| v.into(), |v| v.into());
+impl_owned_attribute_conversion!(Vec<i64>, ArrI64, |v| v.into(), |v| v.into());
+impl_owned_attribute_conversion!(Vec<f32>, ArrF32, |v| v.into(), |v| v.into());
+impl_owned_attribute_conversion!(Vec<f64>, ArrF64, |v| v.into(), |v| v.into());
+impl_owned_attribute_conversion!(Vec<u8>, Binary, |v| v.into(), |v| v.into());
+
+impl From<String> for OwnedAttribute {
+    fn from(v: String) -> Self {
+        OwnedAttribute::String(Ok(v))
+    }
+}
+
+impl<'a> From<&'a str> for OwnedAttribute {
+    fn from(v: &'a str) -> Self {
+        OwnedAttribute::String(Ok(v.to_owned()))
+    }
+}
+
+impl TryFrom<OwnedAttribute> for String {
+    type Error = OwnedAttribute;
+
+    /// Converts the attribute into `String`.
+    ///
+    /// Strings which are not valid UTF-8 are rejected.
+    fn try_from(attr: OwnedAttribute) -> ::std::result::Result<Self, OwnedAttribute> {
+        match attr {
+            OwnedAttribute::String(Ok(v)) => Ok(v),
+            attr => Err(attr),
+        }
+    }
+}
+
+// Catch-all for the rest attributes.
+impl AttributeValues for Vec<OwnedAttribute> {
+    fn from_attributes<R>(attrs: &mut Attributes<R>) -> ParseResult<Option<Self>>
+    where
+        R: ParserSource,
+    {
+        let mut result = Vec::new();
+        while let Some(attr) = attrs.next_attribute()? {
+            result.push(OwnedAttribute::load_from_parser_event(attr)?);
+        }
+        Ok(Some(result))
+    }
+}
+
+
+impl MemoryUsage for OwnedAttribute {
+    fn heap_usage(&self) -> usize {
+        match *self {
+            OwnedAttribute::Bool(_) |
+            OwnedAttribute::I16(_) |
+            OwnedAttribute::I32(_) |
+            OwnedAttribute::I64(_) |
+            OwnedAttribute::F32(_) |
+            OwnedAttribute::F64(_) => 0,
+            OwnedAttribute::ArrBool(ref v) => v.heap_usage(),
+            OwnedAttribute::ArrI32(ref v) => v.heap_usage(),
+            OwnedAttribute::ArrI64(ref v) => v.heap_usage(),
+            OwnedAttribute::ArrF32(ref v) => v.heap_usage(),
+            OwnedAttribute::ArrF64(ref v) => v.heap_usage(),
+            OwnedAttribute::String(ref v) => v.heap_usage(),
+            OwnedAttribute::Binary(ref v) => v.heap_usage(),
+        }
+    }
+}
+
+
+impl ApproxEq for OwnedAttribute {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        use self::OwnedAttribute::*;
+
+        match (self, other) {
+            (F32(a), F32(b)) => a.approx_eq(b, epsilon),
+            (F64(a), F64(b)) => a.approx_eq(b, epsilon),
+            (ArrF32(a), ArrF32(b)) => a.approx_eq(b, epsilon),
+            (ArrF64(a), ArrF64(b)) => a.approx_eq(b, epsilon),
+            _ => self == other,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attribute_conversions() {
+        assert_eq!(OwnedAttribute::from(vec![1i32, 2]), OwnedAttribute::ArrI32(vec![1, 2].into()));
+        assert_eq!(i32::try_from(OwnedAttribute::I32(3)), Ok(3));
+        assert_eq!(i32::try_from(OwnedAttribute::I64(3)), Err(OwnedAttribute::I64(3)));
+        assert_eq!(String::try_from(OwnedAttribute::from("a")), Ok("a".to_owned()));
+
+        assert_eq!(OwnedAttribute::F64(3.0).to_i64(), Some(3));
+        assert_eq!(OwnedAttribute::F64(3.5).to_i64(), None);
+        assert_eq!(OwnedAttribute::F64(3.5).to_i64_lossy(), Some(3));
+        assert_eq!(OwnedAttribute::I64((1 << 53) + 1).to_f64(), None);
+        assert_eq!(OwnedAttribute::Bool(true).to_f64(), None);
+        assert_eq!(OwnedAttribute::Bool(true).to_f64_lossy(), Some(1.0));
+    }
+}
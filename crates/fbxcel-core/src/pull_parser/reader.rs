@@ -0,0 +1,760 @@
+//! Wrapper for `std::io::Read`.
+
+use std::cmp;
+use std::fmt;
+use std::mem;
+use std::io;
+use std::slice;
+
+
+macro_rules! impl_read_little_endian {
+    ($ty:ident, $name:ident) => {
+        /// Reads a little-endian value and returns it.
+        fn $name(&mut self) -> io::Result<$ty> {
+            let mut bytes = [0; mem::size_of::<$ty>()];
+            self.read_exact(&mut bytes)?;
+            Ok($ty::from_le_bytes(bytes))
+        }
+    }
+}
+
+macro_rules! impl_read_little_endian_array {
+    ($ty:ident, $name:ident) => {
+        /// Reads little-endian values into the given buffer.
+        fn $name(&mut self, buf: &mut [$ty]) -> io::Result<()> {
+            // Safe because any bit pattern is a valid value of primitive numeric types.
+            let bytes = unsafe {
+                slice::from_raw_parts_mut(buf.as_mut_ptr() as *mut u8, mem::size_of_val(buf))
+            };
+            self.read_exact(bytes)?;
+            for elem in buf {
+                *elem = $ty::from_le_bytes(elem.to_ne_bytes());
+            }
+            Ok(())
+        }
+    }
+}
+
+/// The `ReadLittleEndian` trait allows for reading little-endian primitive type values from a
+/// source.
+pub trait ReadLittleEndian: io::Read {
+    impl_read_little_endian!(u8, read_u8);
+    impl_read_little_endian!(u32, read_u32);
+    impl_read_little_endian!(u64, read_u64);
+    impl_read_little_endian!(i16, read_i16);
+    impl_read_little_endian!(i32, read_i32);
+    impl_read_little_endian!(i64, read_i64);
+    impl_read_little_endian!(f32, read_f32);
+    impl_read_little_endian!(f64, read_f64);
+
+    impl_read_little_endian_array!(i32, read_i32_arr);
+    impl_read_little_endian_array!(i64, read_i64_arr);
+    impl_read_little_endian_array!(f32, read_f32_arr);
+    impl_read_little_endian_array!(f64, read_f64_arr);
+}
+
+impl<R: io::Read> ReadLittleEndian for R {}
+
+
+/// Checks that the skip destination is not behind the current position.
+///
+/// Malformed input (such as broken node end offsets) can request backward skips, so this is
+/// reported as an error rather than a panic.
+fn ensure_forward_skip(dest_pos: u64, position: u64) -> io::Result<()> {
+    if dest_pos < position {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Destination position should be after current position: dest_pos={}, \
+                 position={}",
+                dest_pos, position
+            ),
+        ));
+    }
+    Ok(())
+}
+
+
+/// Checks that the source is at the destination position after a skip.
+///
+/// Sources with unexpected behavior (such as a reader reporting wrong lengths) can leave the
+/// position elsewhere, so this is reported as an error rather than a panic.
+fn ensure_skipped_to(dest_pos: u64, position: u64) -> io::Result<()> {
+    if dest_pos != position {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Source position is inconsistent after skip: dest_pos={}, position={}",
+                dest_pos, position
+            ),
+        ));
+    }
+    Ok(())
+}
+
+
+/// Skips to the given position by reading and discarding the bytes.
+///
+/// This is used by sources which cannot seek, or which should see every byte of the stream.
+fn read_skip<R: ParserSource>(reader: &mut R, dest_pos: u64) -> io::Result<()> {
+    /// Length of the buffer for discarded bytes.
+    const TEMP_BUF_LEN: usize = 4096;
+
+    ensure_forward_skip(dest_pos, reader.position())?;
+    let mut temp_buf = [0u8; TEMP_BUF_LEN];
+    let mut rest_len = dest_pos - reader.position();
+    while rest_len > 0 {
+        let len = cmp::min(rest_len, TEMP_BUF_LEN as u64) as usize;
+        reader.read_exact(&mut temp_buf[..len])?;
+        rest_len -= len as u64;
+    }
+    ensure_skipped_to(dest_pos, reader.position())
+}
+
+
+/// Capabilities of a parser source.
+///
+/// Every feature of the parser works with non-seekable sources (such as stdin); capabilities only
+/// tell which fast paths are active.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SourceCapabilities {
+    /// Whether the source skips data by seeking.
+    ///
+    /// If `false`, skipped data (e.g. attributes or nodes not read by the user) is read and
+    /// discarded.
+    pub seekable_skip: bool,
+}
+
+
+/// Source stream for `RootParser`.
+pub trait ParserSource: fmt::Debug + io::Read {
+    /// Returns the current position from the start of the stream.
+    fn position(&self) -> u64;
+
+    /// Skips to the given position.
+    ///
+    /// # Errors
+    /// Returns `io::ErrorKind::InvalidData` error if a byte at the given position has been
+    /// already read.
+    fn skip_to(&mut self, dest_pos: u64) -> io::Result<()>;
+
+    /// Returns the capabilities of the source.
+    ///
+    /// Default implementation reports no fast paths.
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities::default()
+    }
+}
+
+impl<R: ParserSource> ParserSource for &mut R {
+    fn position(&self) -> u64 {
+        (**self).position()
+    }
+
+    fn skip_to(&mut self, dest_pos: u64) -> io::Result<()> {
+        (**self).skip_to(dest_pos)
+    }
+
+    fn capabilities(&self) -> SourceCapabilities {
+        (**self).capabilities()
+    }
+}
+
+
+/// Reader with position info.
+pub struct BasicSource<R> {
+    /// Source reader.
+    source: R,
+    /// Current position from the start of the stream..
+    position: u64,
+}
+
+impl<R: io::Read> BasicSource<R> {
+    /// Creates a new `BasicSource`.
+    pub fn new(source: R) -> Self {
+        BasicSource {
+            source,
+            position: 0,
+        }
+    }
+
+    /// Creates a new `BasicSource` whose first byte is at the given offset of the FBX data.
+    ///
+    /// This is useful to parse data following the already consumed part of the stream.
+    pub fn with_position(source: R, position: u64) -> Self {
+        BasicSource {
+            source,
+            position,
+        }
+    }
+}
+
+impl<R: io::Read> io::Read for BasicSource<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read_len = self.source.read(buf)?;
+        self.position += read_len as u64;
+        Ok(read_len)
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        self.source.read_exact(buf)?;
+        self.position += buf.len() as u64;
+        Ok(())
+    }
+}
+
+impl<R: io::BufRead> io::BufRead for BasicSource<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.source.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.position += amt as u64;
+        self.source.consume(amt);
+    }
+}
+
+impl<R> fmt::Debug for BasicSource<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BasicSource")
+            .field("position", &self.position)
+            .finish()
+    }
+}
+
+impl<R: io::Read> ParserSource for BasicSource<R> {
+    fn position(&self) -> u64 {
+        self.position
+    }
+
+    fn skip_to(&mut self, dest_pos: u64) -> io::Result<()> {
+        read_skip(self, dest_pos)
+    }
+}
+
+
+/// Reader with position info and seek feature.
+///
+/// This wrapper doesn't manage offset, i.e. the start of the source stream should be the start of
+/// the FBX data.
+pub struct SeekableSource<R> {
+    /// Source reader.
+    source: R,
+    /// Current position from the start of the stream..
+    position: u64,
+}
+
+impl<R: io::Read + io::Seek> SeekableSource<R> {
+    /// Creates a new `SeekableSource`.
+    pub fn new(source: R) -> Self {
+        SeekableSource {
+            source,
+            position: 0,
+        }
+    }
+}
+
+impl<R: io::Read> io::Read for SeekableSource<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read_len = self.source.read(buf)?;
+        self.position += read_len as u64;
+        Ok(read_len)
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        self.source.read_exact(buf)?;
+        self.position += buf.len() as u64;
+        Ok(())
+    }
+}
+
+impl<R: io::Read + io::Seek> io::Seek for SeekableSource<R> {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        self.position = self.source.seek(pos)?;
+        Ok(self.position)
+    }
+}
+
+impl<R> fmt::Debug for SeekableSource<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SeekableSource")
+            .field("position", &self.position)
+            .finish()
+    }
+}
+
+impl<R: io::Read + io::Seek> ParserSource for SeekableSource<R> {
+    fn position(&self) -> u64 {
+        self.position
+    }
+
+    fn skip_to(&mut self, dest_pos: u64) -> io::Result<()> {
+        use std::io::{Seek, SeekFrom};
+
+        ensure_forward_skip(dest_pos, self.position())?;
+        self.seek(SeekFrom::Start(dest_pos))?;
+        ensure_skipped_to(dest_pos, self.position())
+    }
+
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities { seekable_skip: true }
+    }
+}
+
+impl<R: io::BufRead> io::BufRead for SeekableSource<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.source.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.position += amt as u64;
+        self.source.consume(amt);
+    }
+}
+
+
+/// Hasher of the input bytes, used by `HashingSource`.
+pub trait InputHasher {
+    /// Feeds the given bytes to the hasher.
+    fn update(&mut self, data: &[u8]);
+}
+
+#[cfg(feature = "sha2")]
+impl InputHasher for ::sha2::Sha256 {
+    fn update(&mut self, data: &[u8]) {
+        ::sha2::Digest::update(self, data);
+    }
+}
+
+#[cfg(feature = "twox-hash")]
+impl InputHasher for ::twox_hash::XxHash64 {
+    fn update(&mut self, data: &[u8]) {
+        ::std::hash::Hasher::write(self, data);
+    }
+}
+
+
+/// Reader with position info, which computes running hash of the consumed bytes.
+///
+/// Skipped bytes are also read and hashed (i.e. this never seeks), so the hash covers the whole
+/// stream from the start to the current position.
+/// With `sha2` and `twox-hash` features, `sha2::Sha256` and `twox_hash::XxHash64` can be used as
+/// hashers.
+pub struct HashingSource<R, H> {
+    /// Source reader.
+    source: BasicSource<R>,
+    /// Hasher.
+    hasher: H,
+}
+
+impl<R: io::Read, H: InputHasher> HashingSource<R, H> {
+    /// Creates a new `HashingSource`.
+    pub fn new(source: R, hasher: H) -> Self {
+        HashingSource {
+            source: BasicSource::new(source),
+            hasher,
+        }
+    }
+
+    /// Returns the reference to the hasher.
+    ///
+    /// The hasher has consumed the bytes from the start of the stream to the current position.
+    pub fn hasher(&self) -> &H {
+        &self.hasher
+    }
+
+    /// Returns the hasher.
+    pub fn into_hasher(self) -> H {
+        self.hasher
+    }
+}
+
+impl<R: io::Read, H: InputHasher> io::Read for HashingSource<R, H> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read_len = self.source.read(buf)?;
+        self.hasher.update(&buf[..read_len]);
+        Ok(read_len)
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        self.source.read_exact(buf)?;
+        self.hasher.update(buf);
+        Ok(())
+    }
+}
+
+impl<R, H> fmt::Debug for HashingSource<R, H> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("HashingSource")
+            .field("position", &self.source.position)
+            .finish()
+    }
+}
+
+impl<R: io::Read, H: InputHasher> ParserSource for HashingSource<R, H> {
+    fn position(&self) -> u64 {
+        self.source.position()
+    }
+
+    fn skip_to(&mut self, dest_pos: u64) -> io::Result<()> {
+        read_skip(self, dest_pos)
+    }
+}
+
+
+/// Reader with position info, which copies the consumed bytes to the writer.
+///
+/// Skipped bytes are also read and copied (i.e. this never seeks), so the writer receives the
+/// whole stream from the start to the current position.
+/// Errors on writing are reported as read errors.
+pub struct TeeSource<R, W> {
+    /// Source reader.
+    source: BasicSource<R>,
+    /// Writer.
+    writer: W,
+}
+
+impl<R: io::Read, W: io::Write> TeeSource<R, W> {
+    /// Creates a new `TeeSource`.
+    pub fn new(source: R, writer: W) -> Self {
+        TeeSource {
+            source: BasicSource::new(source),
+            writer,
+        }
+    }
+
+    /// Returns the reference to the writer.
+    pub fn writer(&self) -> &W {
+        &self.writer
+    }
+
+    /// Returns the mutable reference to the writer.
+    pub fn writer_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Returns the writer.
+    pub fn into_writer(self) -> W {
+        self.writer
+    }
+}
+
+impl<R: io::Read, W: io::Write> io::Read for TeeSource<R, W> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read_len = self.source.read(buf)?;
+        self.writer.write_all(&buf[..read_len])?;
+        Ok(read_len)
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        self.source.read_exact(buf)?;
+        self.writer.write_all(buf)
+    }
+}
+
+impl<R, W> fmt::Debug for TeeSource<R, W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TeeSource")
+            .field("position", &self.source.position)
+            .finish()
+    }
+}
+
+impl<R: io::Read, W: io::Write> ParserSource for TeeSource<R, W> {
+    fn position(&self) -> u64 {
+        self.source.position()
+    }
+
+    fn skip_to(&mut self, dest_pos: u64) -> io::Result<()> {
+        read_skip(self, dest_pos)
+    }
+}
+
+
+/// Reader which can read and seek limited area of a stream.
+pub struct LimitedSeekReader<R> {
+    /// Source stream.
+    source: R,
+    /// Current position
+    current: u64,
+    /// Start position.
+    begin: u64,
+    /// End position.
+    end: u64,
+}
+
+impl<R: io::Read> LimitedSeekReader<R> {
+    /// Creates a new `LimitedSeekReader`.
+    pub fn new(source: R, current: u64, begin: u64, end: u64) -> Self {
+        assert!(begin <= end);
+        assert!(begin <= current);
+        assert!(current <= end);
+
+        LimitedSeekReader {
+            source,
+            current,
+            begin,
+            end,
+        }
+    }
+}
+
+impl<R> LimitedSeekReader<R> {
+    /// Returns length of region allowed to be read in bytes.
+    pub fn len(&self) -> u64 {
+        assert!(self.begin <= self.end);
+        self.end - self.begin
+    }
+
+    /// Returns `true` if the reader can read no data.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns rest length of region allowed to be read in bytes.
+    fn rest_len(&self) -> u64 {
+        assert!(self.current <= self.end);
+        self.end - self.current
+    }
+
+    /// Returns the current position relative to the start of the limited region.
+    fn rel_pos(&self) -> u64 {
+        assert!(self.begin <= self.current);
+        self.current - self.begin
+    }
+}
+
+impl<R: io::Read> io::Read for LimitedSeekReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let limit = self.rest_len();
+        let size = self.source.by_ref().take(limit).read(buf)?;
+        self.current += size as u64;
+        Ok(size)
+    }
+}
+
+impl<R: io::BufRead> io::BufRead for LimitedSeekReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.source.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.current += amt as u64;
+        self.source.consume(amt);
+    }
+}
+
+impl<R: io::Seek> io::Seek for LimitedSeekReader<R> {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        match pos {
+            io::SeekFrom::Start(val) => {
+                let offset = ::std::cmp::min(self.len(), val);
+                let target = self.begin + offset;
+                assert!(target >= self.begin);
+                assert!(target <= self.end);
+                self.current = self.source.seek(io::SeekFrom::Start(target))?;
+            },
+            io::SeekFrom::Current(val) => {
+                let offset = if val < 0 {
+                    ::std::cmp::max(-(self.rel_pos() as i64), val)
+                } else {
+                    ::std::cmp::min(self.rest_len(), val as u64) as i64
+                };
+                let target = self.current as i64 + offset;
+                assert!(target as u64 >= self.begin);
+                assert!(target as u64 <= self.end);
+                self.current = self.source.seek(io::SeekFrom::Current(offset))?;
+            },
+            io::SeekFrom::End(val) => {
+                let offset = ::std::cmp::max(-(self.len() as i64), ::std::cmp::min(0, val));
+                let target = (self.end as i64 + offset) as u64;
+                assert!(target >= self.begin);
+                assert!(target <= self.end);
+                self.current = self.source.seek(io::SeekFrom::Start(target))?;
+            },
+        }
+        Ok(self.rel_pos())
+    }
+}
+
+impl<R> fmt::Debug for LimitedSeekReader<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("LimitedSeekReader")
+            .field("current", &self.current)
+            .field("begin", &self.begin)
+            .field("end", &self.end)
+            .finish()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Cursor, Read, Seek, SeekFrom, Write};
+    use super::{ParserSource, BasicSource, SeekableSource, TeeSource};
+
+    fn do_test_skip_to(buf_size: usize, skip_dest: u64) {
+        do_test_basic_skip_to(buf_size, skip_dest);
+        do_test_seekable_skip_to(buf_size, skip_dest);
+    }
+
+    fn do_test_basic_skip_to(buf_size: usize, skip_dest: u64) {
+        let mut short_buf = Cursor::new(vec![0; buf_size]);
+        let short_count = {
+            let mut reader = BasicSource::new(&mut short_buf);
+            reader.skip_to(skip_dest).expect("Failed to skip");
+            reader.position()
+        };
+        assert_eq!(short_count, skip_dest);
+        assert_eq!(
+            short_count,
+            short_buf.stream_position().expect(
+                "Failed to seek",
+            )
+        );
+    }
+
+    fn do_test_seekable_skip_to(buf_size: usize, skip_dest: u64) {
+        let mut short_buf = Cursor::new(vec![0; buf_size]);
+        let mut reader = SeekableSource::new(&mut short_buf);
+        reader.skip_to(skip_dest).expect("Failed to skip");
+        assert_eq!(reader.position(), skip_dest);
+        reader.seek(SeekFrom::End(0)).expect("Failed to seek");
+        assert_eq!(reader.position(), buf_size as u64);
+        reader.seek(SeekFrom::Start(0)).expect("Failed to seek");
+        assert_eq!(reader.position(), 0);
+    }
+
+    #[test]
+    fn test_skip_to() {
+        do_test_skip_to(0, 0);
+        do_test_skip_to(15, 0);
+        do_test_skip_to(30, 23);
+        do_test_skip_to(512, 401);
+        do_test_skip_to(64, 64);
+    }
+
+    /// Writer which always fails.
+    struct FailingWriter;
+
+    impl Write for FailingWriter {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::Error::new(io::ErrorKind::WriteZero, "disk full"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn tee_source_copies_skipped_bytes() {
+        let data = (0..10000).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+        let mut source = TeeSource::new(Cursor::new(&data), Vec::new());
+        let mut buf = [0; 10];
+        source.read_exact(&mut buf).unwrap();
+        // Longer than the buffer for skipped bytes.
+        source.skip_to(9000).unwrap();
+        source.skip_to(9000).unwrap();
+        assert_eq!(source.read(&mut buf).unwrap(), 10);
+        assert_eq!(source.position(), 9010);
+        assert_eq!(source.into_writer(), &data[..9010]);
+    }
+
+    #[test]
+    fn tee_source_with_parser() {
+        use pull_parser::{Event, Parser, RootParser};
+        use pull_parser::ascii::AsciiDocument;
+
+        let source = "; FBX 7.4.0 project file\n\
+                      A:  {\n\tB: *3 {\n\t\ta: 1,2,3\n\t}\n\tC: \"c\"\n}\n\
+                      D: 1\n";
+        let bytes = AsciiDocument::parse(source).unwrap().to_binary().unwrap();
+        let mut parser = RootParser::from_tee(Cursor::new(&bytes), Vec::new());
+        loop {
+            match parser.next_event().unwrap() {
+                // Skip the children of `A` without reading them.
+                Event::StartNode(ref node) if node.name().unwrap() == "A" => {},
+                Event::StartNode(_) => {},
+                Event::EndFbx(_) => break,
+                _ => continue,
+            }
+            parser.skip_current_node().unwrap();
+        }
+        let position = parser.source().position();
+        let written = parser.into_source().into_writer();
+        assert_eq!(written.len() as u64, position);
+        assert_eq!(written, &bytes[..written.len()]);
+    }
+
+    #[test]
+    fn tee_source_write_error() {
+        use pull_parser::{Error, Parser, RootParser};
+
+        let data = vec![0; 100];
+        let mut source = TeeSource::new(Cursor::new(&data), FailingWriter);
+        let err = source.skip_to(50).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+        assert_eq!(err.to_string(), "disk full");
+
+        let mut parser = RootParser::from_tee(Cursor::new(&data), FailingWriter);
+        match parser.next_event() {
+            Err(Error::Io(ref err)) => assert_eq!(err.to_string(), "disk full"),
+            res => panic!("Unexpected result: {:?}", res),
+        }
+    }
+
+    /// Parses the binary FBX data while skipping the children of every top-level node, and
+    /// returns the hasher.
+    #[cfg(any(feature = "sha2", feature = "twox-hash"))]
+    fn hash_skipping_nodes<H: super::InputHasher>(bytes: &[u8], hasher: H) -> H {
+        use pull_parser::{Event, Parser, RootParser};
+
+        let mut parser = RootParser::from_hashing(bytes, hasher);
+        loop {
+            match parser.next_event().unwrap() {
+                Event::StartNode(_) => assert!(parser.skip_current_node().unwrap()),
+                Event::EndFbx(_) => break,
+                _ => {},
+            }
+        }
+        assert_eq!(parser.source().position(), bytes.len() as u64);
+        parser.into_source().into_hasher()
+    }
+
+
+    /// Binary FBX data with nested nodes and arrays.
+    #[cfg(any(feature = "sha2", feature = "twox-hash"))]
+    fn hashing_fixture() -> Vec<u8> {
+        use pull_parser::ascii::AsciiDocument;
+
+        let source = "; FBX 7.4.0 project file\n\
+                      A: 1 {\n\tB: *4 {\n\t\ta: 1.5,2,3,4\n\t}\n\tC: \"c\" {\n\
+                      \t\tD: 2\n\t}\n}\n\
+                      E: \"e\", 3\n";
+        AsciiDocument::parse(source).unwrap().to_binary().unwrap()
+    }
+
+    #[cfg(feature = "sha2")]
+    #[test]
+    fn hashing_source_sha2() {
+        use sha2::{Digest, Sha256};
+
+        let bytes = hashing_fixture();
+        let hasher = hash_skipping_nodes(&bytes, Sha256::new());
+        assert_eq!(hasher.finalize(), Sha256::digest(&bytes));
+    }
+
+    #[cfg(feature = "twox-hash")]
+    #[test]
+    fn hashing_source_twox_hash() {
+        use std::hash::Hasher;
+        use twox_hash::XxHash64;
+
+        let bytes = hashing_fixture();
+        let hasher = hash_skipping_nodes(&bytes, XxHash64::with_seed(0));
+        let mut expected = XxHash64::with_seed(0);
+        expected.write(&bytes);
+        assert_eq!(hasher.finish(), expected.finish());
+    }
+}
@@ -1,9 +1,10 @@
 //! Traits for types which can be converted from node attributes.
 
+use std::convert::TryFrom;
 use std::io::Result as IoResult;
 
-use parser::binary::{Result, ParserSource, Attributes, Attribute, ArrayAttributeReader};
-use parser::binary::{PrimitiveAttribute, ArrayAttribute, SpecialAttributeType};
+use pull_parser::{Result, ParserSource, Attributes, Attribute, ArrayAttributeReader};
+use pull_parser::{PrimitiveAttribute, ArrayAttribute, SpecialAttributeType};
 
 
 /// Types which can be converted from a node attribute.
@@ -22,6 +23,8 @@ pub trait AttributeValue: Sized {
     /// Reads the given attribute and converts into `Self` type.
     ///
     /// The value type will be loosely checked.
+    /// For example, integer values of wider types are accepted if they are in the range of
+    /// `Self` type (with warnings).
     ///
     /// Returns `Ok(Some(Self))` if successfully converted,
     /// `Ok(None)` if successfully read but the types were incompatible,
@@ -31,6 +34,33 @@ pub trait AttributeValue: Sized {
         R: ParserSource;
 }
 
+/// Converts the given integer into narrower integer type with range check.
+///
+/// Returns `None` if the value is out of range of the target type.
+fn narrow_integer<T>(val: i64, from: &str, to: &str) -> Option<T>
+where
+    T: TryFrom<i64>,
+{
+    let converted = T::try_from(val).ok();
+    if converted.is_some() {
+        warn!(
+            "Integer of type `{}` is expected but got `{}` value {}, converting",
+            to,
+            from,
+            val
+        );
+    } else {
+        warn!(
+            "Integer of type `{}` is expected but got `{}` value {} which is out of range",
+            to,
+            from,
+            val
+        );
+    }
+    converted
+}
+
+
 // Simply ignore the attribute.
 impl AttributeValue for () {
     fn from_attribute<R>(_: Attribute<R>) -> Result<Option<Self>>
@@ -84,7 +114,16 @@ impl AttributeValue for i16 {
     where
         R: ParserSource,
     {
-        Self::from_attribute(attr)
+        match attr {
+            Attribute::Primitive(PrimitiveAttribute::I16(val)) => Ok(Some(val)),
+            Attribute::Primitive(PrimitiveAttribute::I32(val)) => {
+                Ok(narrow_integer(val as i64, "i32", "i16"))
+            },
+            Attribute::Primitive(PrimitiveAttribute::I64(val)) => {
+                Ok(narrow_integer(val, "i64", "i16"))
+            },
+            _ => Ok(None),
+        }
     }
 }
 
@@ -107,6 +146,9 @@ impl AttributeValue for i32 {
         match attr {
             Attribute::Primitive(PrimitiveAttribute::I16(val)) => Ok(Some(val as i32)),
             Attribute::Primitive(PrimitiveAttribute::I32(val)) => Ok(Some(val)),
+            Attribute::Primitive(PrimitiveAttribute::I64(val)) => {
+                Ok(narrow_integer(val, "i64", "i32"))
+            },
             _ => Ok(None),
         }
     }
@@ -201,7 +243,19 @@ impl AttributeValue for Vec<i32> {
     where
         R: ParserSource,
     {
-        Self::from_attribute(attr)
+        match attr {
+            Attribute::Array(ArrayAttribute::I32(arr)) => Ok(Some(arr.into_vec()?)),
+            Attribute::Array(ArrayAttribute::I64(arr)) => {
+                let vec = arr.into_vec()?;
+                if vec.iter().any(|&v| v < i32::MIN as i64 || v > i32::MAX as i64) {
+                    warn!("Array of `i64` has values out of range of `i32`");
+                    return Ok(None);
+                }
+                warn!("Array of `i32` is expected but got array of `i64`, converting");
+                Ok(Some(vec.into_iter().map(|v| v as i32).collect()))
+            },
+            _ => Ok(None),
+        }
     }
 }
 
@@ -314,7 +368,7 @@ macro_rules! def_fn_array_attr_into_vec {
                     components_len,
                 );
             }
-            let mut vec = Vec::with_capacity(num_vecs as usize);
+            let mut vec = vec![[<$t>::default(); $len]; num_vecs as usize];
             assert_eq!(
                 ::std::mem::align_of::<[$t; $len]>(),
                 ::std::mem::align_of::<$t>(),
@@ -330,9 +384,13 @@ macro_rules! def_fn_array_attr_into_vec {
             let components_buf = unsafe {
                 ::std::slice::from_raw_parts_mut(vec.as_mut_ptr() as *mut $t, components_buf_len)
             };
-            assert!(components_buf.len() <= vec.len() * $len);
             let size = arr.read_into_buf(components_buf)?;
-            assert_eq!(size, components_buf_len);
+            if size != components_buf_len {
+                return Err(::std::io::Error::new(
+                    ::std::io::ErrorKind::UnexpectedEof,
+                    "Array attribute ended before the expected number of elements",
+                ));
+            }
             Ok(vec)
         }
     }
@@ -636,6 +694,13 @@ impl AttributeValue for Vec<u8> {
 
 
 /// Attribute values list (tuple).
+///
+/// Each element of a tuple reads attributes by `AttributeValues`, so elements can be:
+///
+/// * `T: AttributeValue`, which reads one attribute,
+/// * `Option<T>` where `T: AttributeValue`, which reads one optional trailing attribute,
+/// * `Vec<dom::OwnedAttribute>`, which reads all the rest attributes, and
+/// * tuples.
 pub trait AttributeValues: Sized {
     /// Construct `Self` via a conversion if possible.
     ///
@@ -649,20 +714,14 @@ pub trait AttributeValues: Sized {
 
 macro_rules! impl_attribute_values {
     ($($name:ident: $t:ident),+,) => {
-        impl<$($t: AttributeValue),+> AttributeValues for ($($t),+,) {
+        impl<$($t: AttributeValues),+> AttributeValues for ($($t),+,) {
             fn from_attributes<R>(attrs: &mut Attributes<R>) -> Result<Option<Self>>
                 where R: ParserSource
             {
                 $(
-                    let $name = {
-                        let attr = match attrs.next_attribute()? {
-                            Some(attr) => attr,
-                            None => return Ok(None),
-                        };
-                        match $t::from_attribute_loose(attr)? {
-                            Some(val) => val,
-                            None => return Ok(None),
-                        }
+                    let $name = match $t::from_attributes(attrs)? {
+                        Some(val) => val,
+                        None => return Ok(None),
                     };
                 )+
                 Ok(Some(($($name),+,)))
@@ -676,7 +735,23 @@ impl<T: AttributeValue> AttributeValues for T {
     where
         R: ParserSource,
     {
-        <(T,)>::from_attributes(attrs).map(|v_opt| v_opt.map(|v| v.0))
+        match attrs.next_attribute()? {
+            Some(attr) => T::from_attribute_loose(attr),
+            None => Ok(None),
+        }
+    }
+}
+
+// Absent attribute is `Some(None)`, and attribute of incompatible type is `None`.
+impl<T: AttributeValue> AttributeValues for Option<T> {
+    fn from_attributes<R>(attrs: &mut Attributes<R>) -> Result<Option<Self>>
+    where
+        R: ParserSource,
+    {
+        match attrs.next_attribute()? {
+            Some(attr) => T::from_attribute_loose(attr).map(|v_opt| v_opt.map(Some)),
+            None => Ok(Some(None)),
+        }
     }
 }
 
@@ -732,3 +807,89 @@ impl_attribute_values! {
     t7: T7,
     t8: T8,
 }
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use pull_parser::{Parser, RootParser, Event};
+    use pull_parser::OwnedAttribute;
+    use super::AttributeValues;
+
+    /// Creates FBX 7.4 binary beginning with a node with the given attributes.
+    fn fbx_with_node_attrs(num_attrs: u32, attrs: &[u8]) -> Vec<u8> {
+        let mut bytes = b"Kaydara FBX Binary  \x00\x1a\x00".to_vec();
+        bytes.extend_from_slice(&7400u32.to_le_bytes());
+        let end_offset = bytes.len() + 13 + 1 + attrs.len();
+        bytes.extend_from_slice(&(end_offset as u32).to_le_bytes());
+        bytes.extend_from_slice(&num_attrs.to_le_bytes());
+        bytes.extend_from_slice(&(attrs.len() as u32).to_le_bytes());
+        bytes.push(1);
+        bytes.push(b'N');
+        bytes.extend_from_slice(attrs);
+        bytes
+    }
+
+    fn load_attrs_as<T: AttributeValues>(num_attrs: u32, attrs: &[u8]) -> Option<T> {
+        let mut parser = RootParser::new(Cursor::new(fbx_with_node_attrs(num_attrs, attrs)));
+        parser.next_event().expect("Failed to read FBX header");
+        match parser.next_event().expect("Failed to read a node") {
+            Event::StartNode(mut node) => node.attrs_as().expect("Failed to read attributes"),
+            ev => panic!("Unexpected event: {:?}", ev),
+        }
+    }
+
+    fn load_i64_as<T: AttributeValues>(val: i64) -> Option<T> {
+        let mut attrs = vec![b'L'];
+        attrs.extend_from_slice(&val.to_le_bytes());
+        load_attrs_as(1, &attrs)
+    }
+
+    #[test]
+    fn test_integer_narrowing() {
+        assert_eq!(load_i64_as::<i64>(1 << 40), Some(1 << 40));
+        assert_eq!(load_i64_as::<i32>(7400), Some(7400));
+        assert_eq!(load_i64_as::<i32>(-1), Some(-1));
+        assert_eq!(load_i64_as::<i32>(1 << 40), None);
+        assert_eq!(load_i64_as::<i16>(100), Some(100));
+        assert_eq!(load_i64_as::<i16>(1 << 20), None);
+    }
+
+    #[test]
+    fn test_optional_and_rest() {
+        let mut attrs = vec![b'L'];
+        attrs.extend_from_slice(&42i64.to_le_bytes());
+        attrs.push(b'S');
+        attrs.extend_from_slice(&3u32.to_le_bytes());
+        attrs.extend_from_slice(b"abc");
+        assert_eq!(
+            load_attrs_as::<(i64, Option<String>, Option<i32>)>(2, &attrs),
+            Some((42, Some("abc".to_owned()), None))
+        );
+        assert_eq!(load_attrs_as::<(i64, Option<i32>)>(2, &attrs), None);
+        assert_eq!(
+            load_attrs_as::<(i64, Vec<OwnedAttribute>)>(2, &attrs),
+            Some((42, vec![OwnedAttribute::String(Ok("abc".to_owned()))]))
+        );
+        assert_eq!(load_attrs_as::<(i64, String, ())>(2, &attrs), None);
+    }
+
+    #[test]
+    fn test_start_node_attr() {
+        let mut attrs = vec![b'L'];
+        attrs.extend_from_slice(&42i64.to_le_bytes());
+        attrs.push(b'S');
+        attrs.extend_from_slice(&3u32.to_le_bytes());
+        attrs.extend_from_slice(b"abc");
+        let mut parser = RootParser::new(Cursor::new(fbx_with_node_attrs(2, &attrs)));
+        parser.next_event().expect("Failed to read FBX header");
+        match parser.next_event().expect("Failed to read a node") {
+            Event::StartNode(mut node) => {
+                assert_eq!(node.attr::<String>(1).unwrap(), Some("abc".to_owned()));
+                assert!(node.attr::<i64>(0).is_err());
+                assert_eq!(node.attr::<i64>(2).unwrap(), None);
+            },
+            ev => panic!("Unexpected event: {:?}", ev),
+        }
+    }
+}
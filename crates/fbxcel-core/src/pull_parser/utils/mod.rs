@@ -1,5 +1,7 @@
 //! Utilities for parser-related stuff.
 
 pub use self::attribute_value::{AttributeValues, AttributeValue};
+pub use self::string_table::StringTable;
 
 mod attribute_value;
+mod string_table;
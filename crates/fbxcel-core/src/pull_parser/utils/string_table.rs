@@ -0,0 +1,145 @@
+//! String table extraction.
+
+use std::cmp::Reverse;
+use fnv::FnvHashMap;
+use pull_parser::{Result, Parser, ParserSource, Event, Attribute, SpecialAttributeType};
+
+
+/// Distinct strings in FBX data, with occurrence counts.
+///
+/// This is useful for localization audits and for building external dictionaries.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct StringTable {
+    /// Node names.
+    pub node_names: FnvHashMap<String, usize>,
+    /// String attributes (including property names and object names).
+    ///
+    /// Strings which are not valid UTF-8 are converted lossily.
+    pub attributes: FnvHashMap<String, usize>,
+}
+
+impl StringTable {
+    /// Creates a new empty `StringTable`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Collects strings from the parser, until the end of the (sub)tree.
+    pub fn collect<R, P>(mut parser: P) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        let mut table = StringTable::new();
+        let mut depth = 0usize;
+        loop {
+            match parser.next_event()? {
+                Event::StartFbx(_) => {},
+                Event::EndFbx(_) => break,
+                Event::StartNode(mut info) => {
+                    depth += 1;
+                    let name = String::from_utf8_lossy(info.name_bytes()).into_owned();
+                    *table.node_names.entry(name).or_insert(0) += 1;
+                    while let Some(attr) = info.attributes.next_attribute()? {
+                        if let Attribute::Special(attr) = attr {
+                            if attr.value_type() == SpecialAttributeType::String {
+                                let value = String::from_utf8_lossy(&attr.into_vec()?)
+                                    .into_owned();
+                                *table.attributes.entry(value).or_insert(0) += 1;
+                            }
+                        }
+                    }
+                },
+                Event::EndNode => {
+                    if depth == 0 {
+                        break;
+                    }
+                    depth -= 1;
+                },
+            }
+        }
+        Ok(table)
+    }
+
+    /// Returns all distinct strings (node names and string attributes) with total occurrence
+    /// counts, most frequent first.
+    pub fn all_sorted(&self) -> Vec<(&str, usize)> {
+        let mut counts = FnvHashMap::<&str, usize>::default();
+        for (s, &count) in self.node_names.iter().chain(&self.attributes) {
+            *counts.entry(s.as_str()).or_insert(0) += count;
+        }
+        let mut sorted = counts.into_iter().collect::<Vec<_>>();
+        sorted.sort_by_key(|&(s, count)| (Reverse(count), s));
+        sorted
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use fnv::FnvHashMap;
+    use pull_parser::RootParser;
+    use pull_parser::ascii::AsciiDocument;
+    use super::StringTable;
+
+    /// Returns the strings and counts sorted by the strings.
+    fn sorted(map: &FnvHashMap<String, usize>) -> Vec<(&str, usize)> {
+        let mut v = map.iter().map(|(s, &n)| (s.as_str(), n)).collect::<Vec<_>>();
+        v.sort();
+        v
+    }
+
+    #[test]
+    fn collect_strings() {
+        let source = "; FBX 7.4.0 project file\n\
+                      Creator: \"exporter\"\n\
+                      Objects:  {\n\
+                      \tModel: 1, \"Model::Cube\", \"Mesh\" {\n\
+                      \t\tProperties70:  {\n\
+                      \t\t\tP: \"Lcl Translation\", \"Lcl Translation\", \"\", \"A\",1,2,3\n\
+                      \t\t\tP: \"Visibility\", \"Visibility\", \"\", \"A\",1\n\
+                      \t\t}\n\
+                      \t}\n\
+                      \tModel: 2, \"Model::Cube\", \"Mesh\" {\n\t}\n\
+                      }\n";
+        let bytes = AsciiDocument::parse(source).unwrap().to_binary().unwrap();
+        let table = StringTable::collect(RootParser::new(Cursor::new(bytes))).unwrap();
+
+        assert_eq!(
+            sorted(&table.node_names),
+            vec![("Creator", 1), ("Model", 2), ("Objects", 1), ("P", 2), ("Properties70", 1)]
+        );
+        // Property names and object names are string attributes.
+        assert_eq!(
+            sorted(&table.attributes),
+            vec![
+                ("", 2),
+                ("A", 2),
+                ("Cube\u{0}\u{1}Model", 2),
+                ("Lcl Translation", 2),
+                ("Mesh", 2),
+                ("Visibility", 2),
+                ("exporter", 1),
+            ]
+        );
+        // Node names and string attributes are merged, most frequent first.
+        assert_eq!(
+            table.all_sorted(),
+            vec![
+                ("", 2),
+                ("A", 2),
+                ("Cube\u{0}\u{1}Model", 2),
+                ("Lcl Translation", 2),
+                ("Mesh", 2),
+                ("Model", 2),
+                ("P", 2),
+                ("Visibility", 2),
+                ("Creator", 1),
+                ("Objects", 1),
+                ("Properties70", 1),
+                ("exporter", 1),
+            ]
+        );
+    }
+}
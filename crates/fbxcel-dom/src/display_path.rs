@@ -0,0 +1,62 @@
+//! Readable display of node and object names.
+
+use std::fmt;
+
+
+/// Max number of characters to display.
+pub const MAX_DISPLAY_CHARS: usize = 64;
+
+
+/// Wrapper to display node names, object names, and node paths in human-readable form.
+///
+/// This is used for error messages which may be shown to artists.
+///
+/// - Object names with class (`"name\u{0}\u{1}class"`) are shown as `class::name`.
+/// - Other control characters are escaped.
+/// - Names longer than `MAX_DISPLAY_CHARS` characters are truncated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DisplayPath<'a>(pub &'a str);
+
+impl<'a> DisplayPath<'a> {
+    /// Creates a new `DisplayPath`.
+    pub fn new(path: &'a str) -> Self {
+        DisplayPath(path)
+    }
+}
+
+impl<'a> fmt::Display for DisplayPath<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let prettified = match self.0.find("\u{0}\u{1}") {
+            Some(sep_pos) => format!("{}::{}", &self.0[sep_pos + 2..], &self.0[..sep_pos]),
+            None => self.0.to_owned(),
+        };
+        for (i, c) in prettified.chars().enumerate() {
+            if i == MAX_DISPLAY_CHARS {
+                return f.write_str("...");
+            }
+            if c.is_control() {
+                write!(f, "{}", c.escape_default())?;
+            } else {
+                write!(f, "{}", c)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{DisplayPath, MAX_DISPLAY_CHARS};
+
+    #[test]
+    fn test_display_path() {
+        assert_eq!(DisplayPath("Cube\u{0}\u{1}Model").to_string(), "Model::Cube");
+        assert_eq!(DisplayPath("a\tb").to_string(), "a\\tb");
+        let long = "x".repeat(MAX_DISPLAY_CHARS + 10);
+        assert_eq!(
+            DisplayPath(&long).to_string(),
+            format!("{}...", "x".repeat(MAX_DISPLAY_CHARS))
+        );
+    }
+}
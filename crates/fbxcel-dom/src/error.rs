@@ -0,0 +1,245 @@
+//! Simple loader error.
+
+use std::error;
+use std::fmt;
+use std::io;
+use pull_parser::Error as ParseError;
+use pull_parser::ascii::Error as AsciiParseError;
+use crate::DisplayPath;
+
+
+/// Load result.
+pub type Result<T> = ::std::result::Result<T, Error>;
+
+
+/// Load error.
+#[derive(Debug)]
+pub enum Error {
+    /// FBX ASCII parse error.
+    AsciiParse(AsciiParseError),
+    /// Attribute value is too large to be written as FBX binary.
+    AttributeTooLarge {
+        /// Path of the node (node names joined with `/`).
+        path: String,
+        /// Index of the attribute.
+        index: usize,
+        /// Length of the attribute value in bytes.
+        len: u64,
+    },
+    /// Connection makes a cycle of parent-child relations.
+    ConnectionCycle {
+        /// Source (child) object ID.
+        source: i64,
+        /// Destination (parent) object ID.
+        destination: i64,
+    },
+    /// Multiple objects have the same ID.
+    DuplicateObjectId(i64),
+    /// Property value cannot be converted into the requested type.
+    IncompatibleProperty {
+        /// Property name.
+        property: String,
+        /// Requested type.
+        expected: &'static str,
+        /// Type of the stored value.
+        found: &'static str,
+    },
+    /// Attribute is invalid.
+    ///
+    /// This includes the cases below:
+    /// - The node has too few or too many node attributes
+    /// - The node has wrong types of node attribute values.
+    /// - The node has wrong (or unsupported) values of node attributes values.
+    InvalidAttribute(String),
+    /// Object load error.
+    LoadObject(Box<dyn error::Error + Send + Sync>),
+    /// Required node is missing.
+    MissingNode {
+        /// Parent node.
+        parent: String,
+        /// The missing node, which is child node of the `parent` node.
+        ///
+        /// This may be `None` if the missing node is unknown or cannot be identified.
+        child: Option<String>,
+    },
+    /// Node name is too long to be written as FBX binary.
+    NodeNameTooLong {
+        /// Path of the node (node names joined with `/`).
+        path: String,
+        /// Length of the node name in bytes.
+        len: usize,
+    },
+    /// Parse error (including I/O error).
+    Parse(ParseError),
+    /// Got an unexpected parser event.
+    ///
+    /// This happens when the parser has already emitted some events (for example, a parser
+    /// created by `RootParser::from_source_with_version()`), and the loader cannot get `StartFbx`
+    /// as the first event.
+    UnexpectedEvent(String),
+    /// Got an unexpected node.
+    UnexpectedNode(String),
+    /// Object with the given ID is not known.
+    UnknownObject(i64),
+    /// Property is not known for the object.
+    UnknownProperty {
+        /// Object ID.
+        object: i64,
+        /// Property name.
+        property: String,
+    },
+    /// FBX version is not supported by the loader.
+    UnsupportedVersion(u32),
+}
+
+impl Error {
+    /// Creates a new `Error::MissingNode`.
+    pub fn missing_node<'a, S, T>(parent: S, child: T) -> Self
+    where
+        S: Into<String>,
+        T: Into<Option<&'a str>>,
+    {
+        Error::MissingNode {
+            parent: parent.into(),
+            child: child.into().map(|s| s.to_owned()),
+        }
+    }
+}
+
+impl Error {
+    /// Returns the stable numeric code of the error.
+    ///
+    /// Loader error codes are in the range `3000..4000`, except for `Error::Parse` and
+    /// `Error::AsciiParse`, which return the code of the inner parser error (see
+    /// `pull_parser::Error::code()` and `pull_parser::ascii::Error::code()`).
+    /// Codes are never reused nor changed, so they can be used by FFI wrappers and logging
+    /// systems.
+    pub fn code(&self) -> u32 {
+        match *self {
+            Error::AsciiParse(ref err) => err.code(),
+            Error::AttributeTooLarge { .. } => 3008,
+            Error::ConnectionCycle { .. } => 3000,
+            Error::DuplicateObjectId(_) => 3010,
+            Error::IncompatibleProperty { .. } => 3011,
+            Error::InvalidAttribute(_) => 3001,
+            Error::LoadObject(_) => 3002,
+            Error::MissingNode { .. } => 3003,
+            Error::NodeNameTooLong { .. } => 3004,
+            Error::Parse(ref err) => err.code(),
+            Error::UnexpectedEvent(_) => 3012,
+            Error::UnexpectedNode(_) => 3005,
+            Error::UnknownObject(_) => 3006,
+            Error::UnknownProperty { .. } => 3007,
+            Error::UnsupportedVersion(_) => 3009,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::AsciiParse(ref err) => write!(f, "FBX ASCII parse error: {}", err),
+            Error::AttributeTooLarge {
+                ref path,
+                index,
+                len,
+            } => {
+                write!(
+                    f,
+                    "Attribute is too large ({} bytes): index={}, node={}",
+                    len,
+                    index,
+                    DisplayPath(path)
+                )
+            },
+            Error::ConnectionCycle {
+                source,
+                destination,
+            } => {
+                write!(
+                    f,
+                    "Connection makes a cycle: source={}, destination={}",
+                    source,
+                    destination
+                )
+            },
+            Error::DuplicateObjectId(id) => write!(f, "Duplicate object ID: {}", id),
+            Error::IncompatibleProperty {
+                ref property,
+                expected,
+                found,
+            } => {
+                write!(
+                    f,
+                    "Property {} has `{}` value, which cannot be converted into `{}`",
+                    DisplayPath(property),
+                    found,
+                    expected
+                )
+            },
+            Error::InvalidAttribute(ref name) => {
+                write!(f, "Invalid attribute for node: {}", DisplayPath(name))
+            },
+            Error::LoadObject(ref err) => write!(f, "Object load error: {}", err),
+            Error::MissingNode {
+                ref parent,
+                ref child,
+            } => {
+                if let Some(child) = child.as_ref() {
+                    write!(
+                        f,
+                        "Missing node: {} (parent={})",
+                        DisplayPath(child),
+                        DisplayPath(parent)
+                    )
+                } else {
+                    write!(f, "Missing node: parent={}", DisplayPath(parent))
+                }
+            },
+            Error::NodeNameTooLong { ref path, len } => {
+                write!(f, "Node name is too long ({} bytes): {}", len, DisplayPath(path))
+            },
+            Error::UnexpectedEvent(ref ev) => {
+                write!(f, "Expected `StartFbx` as the first event but got `{}`", ev)
+            },
+            Error::UnexpectedNode(ref name) => write!(f, "Unexpected node: {}", DisplayPath(name)),
+            Error::UnknownObject(id) => write!(f, "Unknown object: id={}", id),
+            Error::UnknownProperty {
+                object,
+                ref property,
+            } => {
+                write!(
+                    f,
+                    "Unknown property {} for the object (id={})",
+                    DisplayPath(property),
+                    object
+                )
+            },
+            Error::UnsupportedVersion(version) => write!(f, "Unsupported FBX version: {}", version),
+            Error::Parse(ref err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::AsciiParse(ref err) => Some(err),
+            Error::LoadObject(ref err) => Some(err.as_ref()),
+            Error::Parse(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Self {
+        Error::Parse(e)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Parse(e.into())
+    }
+}
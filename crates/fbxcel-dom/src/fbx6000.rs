@@ -0,0 +1,434 @@
+//! Loader of FBX 6.x (6000 to 6199) documents.
+//!
+//! FBX 6.x files are structurally different from FBX 7.4:
+//!
+//! - Objects have no numeric IDs and are identified by their names with classes (such as
+//!   `Model::Cube`).
+//! - `Connect` nodes in `Connections` refer to the objects by the names.
+//! - Object properties are stored in `Properties60` (`Property` nodes with name, type, flags, and
+//!   values) instead of `Properties70`.
+//!
+//! `Properties60` is converted into `Properties70`, so that the properties can be handled in the
+//! same way as FBX 7.4 objects.
+
+use pull_parser::{Parser, ParserSource, Event, FbxFooter};
+use crate::{Result, Error, DisplayPath, GenericNode, OwnedAttribute};
+use crate::fbx7400::{separate_name_class, Properties70};
+
+
+/// FBX 6.x document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fbx6000Document {
+    /// FBX version.
+    pub version: u32,
+    /// Creator of the file.
+    pub creator: Option<String>,
+    /// Objects.
+    pub objects: Vec<Object6000>,
+    /// Connections.
+    pub connections: Vec<Connection6000>,
+    /// Toplevel nodes other than `Objects` and `Connections`.
+    pub other_nodes: Vec<GenericNode>,
+    /// FBX footer.
+    pub footer: Option<FbxFooter>,
+}
+
+impl Fbx6000Document {
+    /// Loads the document from the given parser.
+    ///
+    /// Returns `Err(Error::UnexpectedEvent(_))` if the parser has already emitted some event (i.e.
+    /// if the given parser didn't return the `StartFbx` first).
+    pub fn load<R, P>(mut parser: P) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        let version = match parser.next_event()? {
+            Event::StartFbx(header) => header.version,
+            ev => return Err(Error::UnexpectedEvent(format!("{:?}", ev))),
+        };
+        Self::load_from_parser(version, parser)
+    }
+
+    /// Loads the document from the given parser, which has already emitted `StartFbx`.
+    pub fn load_from_parser<R, P>(version: u32, mut parser: P) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        let (nodes, footer) = GenericNode::load_from_parser(&mut parser)?;
+        let mut doc = Fbx6000Document {
+            version,
+            creator: None,
+            objects: Vec::new(),
+            connections: Vec::new(),
+            other_nodes: Vec::new(),
+            footer,
+        };
+        for node in nodes {
+            match &node.name[..] {
+                b"Objects" => {
+                    for child in &node.children {
+                        doc.objects.push(Object6000::from_generic_node(child)?);
+                    }
+                },
+                b"Connections" => {
+                    for child in node.children_by_name("Connect") {
+                        doc.connections.push(Connection6000::from_generic_node(child)?);
+                    }
+                },
+                _ => {
+                    if node.name == b"Creator" && doc.creator.is_none() {
+                        doc.creator = first_string(&node).map(ToOwned::to_owned);
+                    }
+                    doc.other_nodes.push(node);
+                },
+            }
+        }
+        if doc.creator.is_none() {
+            doc.creator = doc.other_nodes
+                .iter()
+                .filter(|node| node.name == b"FBXHeaderExtension")
+                .flat_map(|node| node.children_by_name("Creator"))
+                .filter_map(first_string)
+                .next()
+                .map(ToOwned::to_owned);
+        }
+        Ok(doc)
+    }
+
+    /// Returns the object with the given key (such as `Model::Cube`).
+    pub fn object_by_key(&self, key: &str) -> Option<&Object6000> {
+        self.objects.iter().find(|obj| obj.is_key(key))
+    }
+
+    /// Returns an iterator of the objects connected to the given object as sources.
+    pub fn sources_of<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a Object6000> {
+        self.connections
+            .iter()
+            .filter(move |conn| conn.destination == key)
+            .filter_map(move |conn| self.object_by_key(&conn.source))
+    }
+}
+
+
+/// FBX 6.x object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Object6000 {
+    /// Node name (such as `Model` and `Material`).
+    pub node_name: String,
+    /// Name.
+    pub name: String,
+    /// Class.
+    ///
+    /// This is the node name if the object has no name (such as `GlobalSettings`).
+    pub class: String,
+    /// Subclass.
+    pub subclass: String,
+    /// `Properties60` of the object.
+    ///
+    /// This is empty if the object has no `Properties60` node.
+    pub properties: Properties70,
+    /// Child nodes other than `Properties60`.
+    pub nodes: Vec<GenericNode>,
+}
+
+impl Object6000 {
+    /// Returns the key of the object (`Class::Name`), which is used in `Connect` nodes.
+    pub fn key(&self) -> String {
+        format!("{}::{}", self.class, self.name)
+    }
+
+    /// Returns `true` if the key of the object is the given one.
+    fn is_key(&self, key: &str) -> bool {
+        key.len() == self.class.len() + 2 + self.name.len() && key.starts_with(&self.class) &&
+            key[self.class.len()..].starts_with("::") && key.ends_with(&self.name)
+    }
+
+    /// Converts the object node.
+    fn from_generic_node(node: &GenericNode) -> Result<Self> {
+        let (name, class) = match node.attributes.first() {
+            Some(OwnedAttribute::String(Ok(name_class))) => {
+                match separate_name_class(name_class) {
+                    Some((name, class)) => (name.to_owned(), class.to_owned()),
+                    None => (name_class.clone(), node.name_lossy().into_owned()),
+                }
+            },
+            Some(_) => return Err(Error::InvalidAttribute(node.name_lossy().into_owned())),
+            None => (String::new(), node.name_lossy().into_owned()),
+        };
+        let subclass = match node.attributes.get(1) {
+            Some(OwnedAttribute::String(Ok(subclass))) => subclass.clone(),
+            _ => String::new(),
+        };
+        let mut properties = Properties70::new();
+        let mut nodes = Vec::with_capacity(node.children.len());
+        for child in &node.children {
+            if child.name == b"Properties60" {
+                load_properties60(&mut properties, child)?;
+            } else {
+                nodes.push(child.clone());
+            }
+        }
+        Ok(Object6000 {
+            node_name: node.name_lossy().into_owned(),
+            name,
+            class,
+            subclass,
+            properties,
+            nodes,
+        })
+    }
+}
+
+
+/// FBX 6.x connection (`Connect` node).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Connection6000 {
+    /// Key (`Class::Name`) of the source object.
+    pub source: String,
+    /// Key (`Class::Name`) of the destination object.
+    pub destination: String,
+    /// Property of the connection.
+    pub property: Option<String>,
+    /// `true` if the source is property.
+    pub source_is_prop: bool,
+    /// `true` if the destination is property.
+    pub destination_is_prop: bool,
+}
+
+impl Connection6000 {
+    /// Converts the `Connect` node.
+    fn from_generic_node(node: &GenericNode) -> Result<Self> {
+        let invalid_attr = || Error::InvalidAttribute("Connect".to_owned());
+        let string_at = |index: usize| match node.attributes.get(index) {
+            Some(OwnedAttribute::String(Ok(s))) => Some(s.as_str()),
+            _ => None,
+        };
+        let (source_is_prop, destination_is_prop) = match string_at(0) {
+            Some("OO") => (false, false),
+            Some("OP") => (false, true),
+            Some("PO") => (true, false),
+            Some("PP") => (true, true),
+            Some(v) => {
+                error!("Unknown connection type: {:?}", v);
+                return Err(invalid_attr());
+            },
+            None => return Err(invalid_attr()),
+        };
+        let source = string_at(1).ok_or_else(invalid_attr)?;
+        let destination = string_at(2).ok_or_else(invalid_attr)?;
+        Ok(Connection6000 {
+            source: to_key(source),
+            destination: to_key(destination),
+            property: string_at(3).map(ToOwned::to_owned),
+            source_is_prop,
+            destination_is_prop,
+        })
+    }
+}
+
+
+/// Converts the name with the class (`Name\u{0}\u{1}Class`) into a key (`Class::Name`).
+///
+/// Names without classes are returned as is.
+fn to_key(name_class: &str) -> String {
+    match separate_name_class(name_class) {
+        Some((name, class)) => format!("{}::{}", class, name),
+        None => name_class.to_owned(),
+    }
+}
+
+
+/// Returns the first attribute of the node if it is a string.
+fn first_string(node: &GenericNode) -> Option<&str> {
+    match node.attributes.first() {
+        Some(OwnedAttribute::String(Ok(s))) => Some(s.as_str()),
+        _ => None,
+    }
+}
+
+
+/// Loads `Property` nodes in `Properties60` into `Properties70`.
+///
+/// `Property` nodes have name, type, and flags, followed by the values.
+/// Properties with unsupported values are ignored with warnings.
+pub fn load_properties60(props: &mut Properties70, node: &GenericNode) -> Result<()> {
+    for child in &node.children {
+        if child.name != b"Property" {
+            warn!(
+                "Expected `Property` node but got `{}` in `Properties60`",
+                DisplayPath(&child.name_lossy())
+            );
+            continue;
+        }
+        let name = first_string(child)
+            .ok_or_else(|| Error::InvalidAttribute("Property".to_owned()))?
+            .to_owned();
+        let values = child.attributes.get(3..).unwrap_or(&[]);
+        match values {
+            [] => {
+                props.values_empty.insert(name);
+            },
+            [ref value] => load_single_value(props, name, value),
+            _ => {
+                let floats = values
+                    .iter()
+                    .map(OwnedAttribute::to_f64_lossy)
+                    .collect::<Option<Vec<_>>>();
+                match floats {
+                    Some(ref v) if v.len() == 2 => {
+                        props.values_f64_2.insert(name, [v[0], v[1]].into());
+                    },
+                    Some(ref v) if v.len() == 3 => {
+                        props.values_f64_3.insert(name, [v[0], v[1], v[2]].into());
+                    },
+                    Some(ref v) if v.len() == 4 => {
+                        props.values_f64_4.insert(name, [v[0], v[1], v[2], v[3]].into());
+                    },
+                    Some(ref v) if v.len() == 16 => {
+                        let mut mat = [[0.0; 4]; 4];
+                        for (row, chunk) in mat.iter_mut().zip(v.chunks(4)) {
+                            row.copy_from_slice(chunk);
+                        }
+                        props.values_f64_4x4.insert(name, mat.into());
+                    },
+                    _ => {
+                        warn!(
+                            "Ignoring property `{}` with unsupported values: {:?}",
+                            name,
+                            values
+                        );
+                    },
+                }
+            },
+        }
+    }
+    Ok(())
+}
+
+
+/// Loads a property with a single value.
+fn load_single_value(props: &mut Properties70, name: String, value: &OwnedAttribute) {
+    match *value {
+        OwnedAttribute::Bool(v) => {
+            props.values_i64.insert(name, i64::from(v).into());
+        },
+        OwnedAttribute::I16(v) => {
+            props.values_i64.insert(name, i64::from(v).into());
+        },
+        OwnedAttribute::I32(v) => {
+            props.values_i64.insert(name, i64::from(v).into());
+        },
+        OwnedAttribute::I64(v) => {
+            props.values_i64.insert(name, v.into());
+        },
+        OwnedAttribute::F32(v) => {
+            props.values_f64.insert(name, f64::from(v).into());
+        },
+        OwnedAttribute::F64(v) => {
+            props.values_f64.insert(name, v.into());
+        },
+        OwnedAttribute::String(Ok(ref v)) => {
+            props.values_string.insert(name, v.clone().into());
+        },
+        OwnedAttribute::String(Err(ref v)) => {
+            props.values_binary.insert(name, v.clone().into());
+        },
+        OwnedAttribute::Binary(ref v) => {
+            props.values_binary.insert(name, v.to_vec().into());
+        },
+        _ => {
+            warn!("Ignoring property `{}` with array value", name);
+        },
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use crate::{Error, FbxLoader, Result};
+    use crate::fbx7400::Fbx7400Document;
+    use pull_parser::{BasicSource, RootParser};
+    use pull_parser::ascii::AsciiDocument;
+    use super::Fbx6000Document;
+
+    const SOURCE: &str = r#"; FBX 6.1.0 project file
+FBXHeaderExtension:  {
+	FBXHeaderVersion: 1003
+	FBXVersion: 6100
+}
+Creator: "FBX SDK/FBX Plugins version 2006.11"
+Objects:  {
+	Model: "Model::Cube", "Mesh" {
+		Version: 232
+		Properties60:  {
+			Property: "Lcl Translation", "Lcl Translation", "A+",1,2,3
+			Property: "Show", "bool", "",1
+			Property: "Size", "double", "",100
+			Property: "Compound", "Compound", ""
+		}
+	}
+	GlobalSettings:  {
+		Version: 1000
+		Properties60:  {
+			Property: "UpAxis", "int", "",1
+		}
+	}
+}
+Connections:  {
+	Connect: "OO", "Model::Cube", "Model::Scene"
+}
+"#;
+
+    #[test]
+    fn test_load_6100() -> Result<()> {
+        let bytes = AsciiDocument::parse(SOURCE).unwrap().to_binary().unwrap();
+        let parser = RootParser::new(Cursor::new(&bytes));
+        let doc = match FbxLoader::load_from_parser(parser)? {
+            FbxLoader::Fbx6000(loader) => loader.load()?,
+            loader => return Err(Error::UnsupportedVersion(loader.fbx_version())),
+        };
+        assert_eq!(doc.version, 6100);
+        assert_eq!(doc.creator.as_deref(), Some(
+            "FBX SDK/FBX Plugins version 2006.11",
+        ));
+
+        let cube = doc.object_by_key("Model::Cube").unwrap();
+        assert_eq!(cube.subclass, "Mesh");
+        let props = &cube.properties;
+        assert_eq!(props.values_f64_3["Lcl Translation"].value(), &[1.0, 2.0, 3.0]);
+        assert_eq!(*props.values_i64["Show"].value(), 1);
+        assert_eq!(*props.values_f64["Size"].value(), 100.0);
+        assert!(props.values_empty.contains("Compound"));
+        assert_eq!(cube.nodes.len(), 1);
+
+        let settings = doc.object_by_key("GlobalSettings::").unwrap();
+        assert_eq!(*settings.properties.values_i64["UpAxis"].value(), 1);
+
+        assert_eq!(doc.connections.len(), 1);
+        assert_eq!(doc.connections[0].destination, "Model::Scene");
+        assert_eq!(doc.sources_of("Model::Scene").count(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn load_without_start_fbx() {
+        let bytes = AsciiDocument::parse(SOURCE).unwrap().to_binary().unwrap();
+        // Magic (21 bytes), unknown 2 bytes, and version (4 bytes).
+        let header_len = 27;
+        let parser = || {
+            let source = BasicSource::with_position(&bytes[header_len..], header_len as u64);
+            RootParser::from_source_with_version(source, 6100)
+        };
+        let is_unexpected_event = |err: Error| match err {
+            Error::UnexpectedEvent(ev) => ev.contains("StartNode"),
+            _ => false,
+        };
+        assert!(is_unexpected_event(FbxLoader::load_from_parser(parser()).unwrap_err()));
+        assert!(is_unexpected_event(Fbx6000Document::load(parser()).unwrap_err()));
+        assert!(is_unexpected_event(Fbx7400Document::load(parser()).unwrap_err()));
+    }
+}
@@ -0,0 +1,894 @@
+//! Animation-related helpers.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+use fnv::FnvHashMap;
+use approx::ApproxEq;
+use crate::{GenericNode, OwnedAttribute};
+use crate::fbx7400::{CurveChannel, Fbx7400Document, GenericObject, KTime, Properties70};
+
+
+/// Default values of channels of an `AnimationCurveNode`.
+///
+/// `AnimationCurveNode` objects have default values of channels in `Properties70` (such as
+/// `d|X`).
+/// Channels without connected curves should be evaluated to these values.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CurveNodeDefaults {
+    /// Default values for each channel.
+    pub channels: BTreeMap<CurveChannel, f64>,
+}
+
+impl CurveNodeDefaults {
+    /// Reads default channel values of the given `AnimationCurveNode` object.
+    ///
+    /// Returns `None` if the object is not an `AnimationCurveNode`.
+    pub fn from_generic(obj: &GenericObject) -> Option<Self> {
+        if obj.props.class != "AnimationCurveNode" {
+            return None;
+        }
+        Some(Self::from_properties(&obj.properties))
+    }
+
+    /// Reads default channel values from the properties of an `AnimationCurveNode`.
+    pub fn from_properties(properties: &Properties70) -> Self {
+        let channels = properties
+            .values_f64
+            .iter()
+            .filter_map(|(name, value)| {
+                CurveChannel::parse(name).map(|channel| (channel, *value.value()))
+            })
+            .collect();
+        CurveNodeDefaults { channels }
+    }
+
+    /// Returns the default value of the given channel.
+    pub fn get(&self, channel: &CurveChannel) -> Option<f64> {
+        self.channels.get(channel).cloned()
+    }
+
+    /// Returns the value of the channel, using the default value if no curve value is available.
+    ///
+    /// `curve_value` should be the value evaluated from the connected curve, or `None` if the
+    /// channel has no connected curve.
+    pub fn value(&self, channel: &CurveChannel, curve_value: Option<f64>) -> Option<f64> {
+        curve_value.or_else(|| self.get(channel))
+    }
+
+    /// Returns the default values of `d|X`, `d|Y`, and `d|Z` channels.
+    ///
+    /// Returns `None` if any of them is missing.
+    pub fn xyz(&self) -> Option<[f64; 3]> {
+        Some([
+            self.get(&CurveChannel::X)?,
+            self.get(&CurveChannel::Y)?,
+            self.get(&CurveChannel::Z)?,
+        ])
+    }
+}
+
+
+/// Extrapolation mode of animation curves, used outside the keyed range.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExtrapolationMode {
+    /// Keeps the value of the first (or last) key.
+    #[default]
+    Constant,
+    /// Repeats the keyed range.
+    Repeat,
+    /// Repeats the keyed range, mirroring every other repetition.
+    MirrorRepeat,
+    /// Extends the curve with the slope at the first (or last) key.
+    KeepSlope,
+}
+
+impl ExtrapolationMode {
+    /// Creates the mode from the type code (`C`, `R`, `M`, or `K`).
+    pub fn from_code(code: u8) -> Option<Self> {
+        match code {
+            b'C' => Some(ExtrapolationMode::Constant),
+            b'R' => Some(ExtrapolationMode::Repeat),
+            b'M' => Some(ExtrapolationMode::MirrorRepeat),
+            b'K' => Some(ExtrapolationMode::KeepSlope),
+            _ => None,
+        }
+    }
+}
+
+/// Extrapolation setting of one side of an animation curve.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Extrapolation {
+    /// Mode.
+    pub mode: ExtrapolationMode,
+    /// Number of repetitions for `Repeat` and `MirrorRepeat` modes.
+    ///
+    /// Zero or negative value means unlimited repetitions.
+    pub repetition: i32,
+}
+
+impl Extrapolation {
+    /// Reads the extrapolation from the `Pre-Extrapolation` or `Post-Extrapolation` node.
+    ///
+    /// Returns `None` if the node has no valid `Type` child.
+    pub fn from_node(node: &GenericNode) -> Option<Self> {
+        let mode = node.children_by_name("Type")
+            .next()
+            .and_then(|child| match child.attributes.first() {
+                Some(OwnedAttribute::String(Ok(v))) => v.bytes().next(),
+                Some(&OwnedAttribute::I32(v)) if (0..=0xff).contains(&v) => Some(v as u8),
+                Some(&OwnedAttribute::I16(v)) if (0..=0xff).contains(&v) => Some(v as u8),
+                _ => None,
+            })
+            .and_then(ExtrapolationMode::from_code)?;
+        let repetition = node.children_by_name("Repetition")
+            .next()
+            .and_then(|child| match child.attributes.first() {
+                Some(&OwnedAttribute::I32(v)) => Some(v),
+                _ => None,
+            })
+            .unwrap_or(0);
+        Some(Extrapolation {
+            mode,
+            repetition,
+        })
+    }
+}
+
+
+/// Time mapped into the keyed range by extrapolation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExtrapolatedTime {
+    /// Curve should be evaluated at the time in the keyed range.
+    At(i64),
+    /// Curve should be evaluated at the edge key, and extended linearly by the slope.
+    ///
+    /// The value is `value(edge) + slope(edge) * delta`.
+    Slope {
+        /// Time of the edge key.
+        edge: i64,
+        /// Offset from the edge key.
+        delta: i64,
+    },
+}
+
+
+/// Extrapolation settings of an animation curve.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CurveExtrapolation {
+    /// Extrapolation before the first key.
+    pub pre: Extrapolation,
+    /// Extrapolation after the last key.
+    pub post: Extrapolation,
+}
+
+impl CurveExtrapolation {
+    /// Reads the extrapolation settings of the given `AnimationCurve` object.
+    ///
+    /// Missing settings are treated as `Constant`.
+    /// Returns `None` if the object is not an `AnimationCurve`.
+    pub fn from_generic(obj: &GenericObject) -> Option<Self> {
+        if obj.props.class != "AnimationCurve" {
+            return None;
+        }
+        Some(Self::from_nodes(&obj.nodes))
+    }
+
+    /// Reads the extrapolation settings from the child nodes of an `AnimationCurve`.
+    ///
+    /// Missing settings are treated as `Constant`.
+    pub fn from_nodes(nodes: &[GenericNode]) -> Self {
+        let read = |name: &str| {
+            nodes
+                .iter()
+                .find(|node| node.name == name.as_bytes())
+                .and_then(Extrapolation::from_node)
+                .unwrap_or_default()
+        };
+        CurveExtrapolation {
+            pre: read("Pre-Extrapolation"),
+            post: read("Post-Extrapolation"),
+        }
+    }
+
+    /// Maps the given time into the keyed range `[first, last]`.
+    pub fn map_time(&self, time: i64, first: i64, last: i64) -> ExtrapolatedTime {
+        assert!(first <= last, "Keyed range should not be reversed");
+        let span = last - first;
+        let (setting, edge, distance) = if time < first {
+            (&self.pre, first, first - time)
+        } else if time > last {
+            (&self.post, last, time - last)
+        } else {
+            return ExtrapolatedTime::At(time);
+        };
+        if span == 0 {
+            return match setting.mode {
+                ExtrapolationMode::KeepSlope => ExtrapolatedTime::Slope {
+                    edge,
+                    delta: time - edge,
+                },
+                _ => ExtrapolatedTime::At(edge),
+            };
+        }
+        // Number of completed repetitions, and offset in the current repetition.
+        let cycle = (distance - 1) / span;
+        let offset = (distance - 1) % span + 1;
+        let exceeded = setting.repetition > 0 && cycle >= i64::from(setting.repetition);
+        match setting.mode {
+            ExtrapolationMode::Constant => ExtrapolatedTime::At(edge),
+            ExtrapolationMode::KeepSlope => ExtrapolatedTime::Slope {
+                edge,
+                delta: time - edge,
+            },
+            _ if exceeded => {
+                // Stays at the end of the last repetition.
+                let mirrored = setting.mode == ExtrapolationMode::MirrorRepeat &&
+                    setting.repetition % 2 == 1;
+                ExtrapolatedTime::At(if mirrored { first + last - edge } else { edge })
+            },
+            ExtrapolationMode::Repeat => {
+                // Moving away from `edge` means moving backwards in the range from the other edge.
+                if time < first {
+                    ExtrapolatedTime::At(last - offset)
+                } else {
+                    ExtrapolatedTime::At(first + offset)
+                }
+            },
+            ExtrapolationMode::MirrorRepeat => {
+                let mirrored = cycle % 2 == 0;
+                let from_edge = if mirrored { offset } else { span - offset };
+                if edge == first {
+                    ExtrapolatedTime::At(first + from_edge)
+                } else {
+                    ExtrapolatedTime::At(last - from_edge)
+                }
+            },
+        }
+    }
+}
+
+
+
+/// Key interpolation flags (`eInterpolation*` in the FBX SDK).
+mod key_flags {
+    /// Constant interpolation.
+    pub const INTERPOLATION_CONSTANT: u32 = 0x0000_0002;
+    /// Linear interpolation.
+    pub const INTERPOLATION_LINEAR: u32 = 0x0000_0004;
+    /// Cubic interpolation.
+    pub const INTERPOLATION_CUBIC: u32 = 0x0000_0008;
+    /// Constant interpolation uses the value of the next key.
+    pub const CONSTANT_NEXT: u32 = 0x0000_0100;
+    /// Auto tangent.
+    pub const TANGENT_AUTO: u32 = 0x0000_0100;
+    /// TCB tangent.
+    pub const TANGENT_TCB: u32 = 0x0000_0200;
+    /// User tangent.
+    pub const TANGENT_USER: u32 = 0x0000_0400;
+    /// Broken tangent (left and right slopes are independent).
+    pub const TANGENT_GENERIC_BREAK: u32 = 0x0000_0800;
+    /// Clamped tangent.
+    pub const TANGENT_GENERIC_CLAMP: u32 = 0x0000_1000;
+    /// Right side is weighted.
+    pub const WEIGHTED_RIGHT: u32 = 0x0100_0000;
+    /// Left side of the next key is weighted.
+    pub const WEIGHTED_NEXT_LEFT: u32 = 0x0200_0000;
+}
+
+
+/// Default weight of tangents.
+pub const DEFAULT_TANGENT_WEIGHT: f64 = 1.0 / 3.0;
+
+/// Scale of packed weights in `KeyAttrDataFloat`.
+const WEIGHT_SCALE: f64 = 9999.0;
+
+
+/// Interpolation of a curve segment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Interpolation {
+    /// Constant.
+    Constant {
+        /// Whether the value of the next key is used.
+        next: bool,
+    },
+    /// Linear.
+    Linear,
+    /// Cubic.
+    Cubic,
+}
+
+
+/// Tangent mode of a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TangentMode {
+    /// Auto (slopes are computed by the exporter and stored).
+    Auto,
+    /// TCB (Kochanek-Bartels); slopes are computed from tension, continuity, and bias.
+    Tcb,
+    /// User-specified slopes.
+    User,
+    /// User-specified broken slopes.
+    Break,
+}
+
+
+/// Attributes of a key, decoded from `KeyAttrFlags` and `KeyAttrDataFloat`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyAttribute {
+    /// Raw flags.
+    pub flags: u32,
+    /// Raw data.
+    pub data: [f32; 4],
+}
+
+impl KeyAttribute {
+    /// Returns the interpolation of the segment starting from the key.
+    pub fn interpolation(&self) -> Interpolation {
+        use self::key_flags::*;
+
+        if self.flags & INTERPOLATION_CONSTANT != 0 {
+            Interpolation::Constant { next: self.flags & CONSTANT_NEXT != 0 }
+        } else if self.flags & INTERPOLATION_LINEAR != 0 {
+            Interpolation::Linear
+        } else {
+            debug_assert!(self.flags & INTERPOLATION_CUBIC != 0 || self.flags == 0);
+            Interpolation::Cubic
+        }
+    }
+
+    /// Returns the tangent mode of the key.
+    pub fn tangent_mode(&self) -> TangentMode {
+        use self::key_flags::*;
+
+        if self.flags & TANGENT_TCB != 0 {
+            TangentMode::Tcb
+        } else if self.flags & TANGENT_USER != 0 {
+            if self.flags & TANGENT_GENERIC_BREAK != 0 {
+                TangentMode::Break
+            } else {
+                TangentMode::User
+            }
+        } else {
+            debug_assert!(self.flags & TANGENT_AUTO != 0 || self.flags & 0x7f00 == 0);
+            TangentMode::Auto
+        }
+    }
+
+    /// Returns whether the tangent is clamped.
+    pub fn is_clamped(&self) -> bool {
+        self.flags & key_flags::TANGENT_GENERIC_CLAMP != 0
+    }
+
+    /// Returns the stored right slope.
+    pub fn right_slope(&self) -> f64 {
+        f64::from(self.data[0])
+    }
+
+    /// Returns the stored left slope of the next key.
+    pub fn next_left_slope(&self) -> f64 {
+        f64::from(self.data[1])
+    }
+
+    /// Returns the right weight.
+    pub fn right_weight(&self) -> f64 {
+        if self.flags & key_flags::WEIGHTED_RIGHT != 0 {
+            f64::from(self.data[2].to_bits() & 0xffff) / WEIGHT_SCALE
+        } else {
+            DEFAULT_TANGENT_WEIGHT
+        }
+    }
+
+    /// Returns the left weight of the next key.
+    pub fn next_left_weight(&self) -> f64 {
+        if self.flags & key_flags::WEIGHTED_NEXT_LEFT != 0 {
+            f64::from(self.data[2].to_bits() >> 16) / WEIGHT_SCALE
+        } else {
+            DEFAULT_TANGENT_WEIGHT
+        }
+    }
+
+    /// Returns tension, continuity, and bias of TCB tangent.
+    pub fn tcb(&self) -> [f64; 3] {
+        [
+            f64::from(self.data[0]),
+            f64::from(self.data[1]),
+            f64::from(self.data[2]),
+        ]
+    }
+}
+
+
+/// Keys of an `AnimationCurve`.
+///
+/// Arrays are immutable and shared by `Arc`, so the keys can be cheaply cloned and shared
+/// between threads.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CurveKeys {
+    /// Key times.
+    pub times: Arc<[i64]>,
+    /// Key values.
+    pub values: Arc<[f32]>,
+    /// Key attributes, one for each key.
+    pub attributes: Arc<[KeyAttribute]>,
+}
+
+impl CurveKeys {
+    /// Reads keys of the given `AnimationCurve` object.
+    ///
+    /// Shared attributes are expanded using `KeyAttrRefCount`.
+    /// Returns `None` if the object is not an `AnimationCurve` or the key arrays are inconsistent.
+    pub fn from_generic(obj: &GenericObject) -> Option<Self> {
+        if obj.props.class != "AnimationCurve" {
+            return None;
+        }
+        let attr = |name: &str| {
+            obj.nodes
+                .iter()
+                .find(|node| node.name == name.as_bytes())
+                .and_then(|node| node.attributes.first())
+        };
+        let times = match attr("KeyTime") {
+            Some(OwnedAttribute::ArrI64(v)) => v.to_vec(),
+            _ => return None,
+        };
+        let values = match attr("KeyValueFloat") {
+            Some(OwnedAttribute::ArrF32(v)) => v.to_vec(),
+            Some(OwnedAttribute::ArrF64(v)) => v.iter().map(|&v| v as f32).collect(),
+            _ => return None,
+        };
+        let flags = match attr("KeyAttrFlags") {
+            Some(OwnedAttribute::ArrI32(v)) => v.to_vec(),
+            _ => Vec::new(),
+        };
+        let data = match attr("KeyAttrDataFloat") {
+            Some(OwnedAttribute::ArrF32(v)) => v.to_vec(),
+            _ => Vec::new(),
+        };
+        let ref_counts = match attr("KeyAttrRefCount") {
+            Some(OwnedAttribute::ArrI32(v)) => v.to_vec(),
+            _ => Vec::new(),
+        };
+        Self::from_arrays(times, values, &flags, &data, &ref_counts)
+    }
+
+    /// Creates keys from the arrays of an `AnimationCurve`.
+    ///
+    /// The arguments are the contents of `KeyTime`, `KeyValueFloat`, `KeyAttrFlags`,
+    /// `KeyAttrDataFloat`, and `KeyAttrRefCount`.
+    /// Returns `None` if the arrays are inconsistent.
+    pub fn from_arrays(
+        times: Vec<i64>,
+        values: Vec<f32>,
+        flags: &[i32],
+        data: &[f32],
+        ref_counts: &[i32],
+    ) -> Option<Self> {
+        if times.len() != values.len() || data.len() < flags.len() * 4 {
+            return None;
+        }
+
+        let mut attributes = Vec::with_capacity(times.len());
+        for (i, &flags) in flags.iter().enumerate() {
+            let attr = KeyAttribute {
+                flags: flags as u32,
+                data: [data[i * 4], data[i * 4 + 1], data[i * 4 + 2], data[i * 4 + 3]],
+            };
+            let count = ref_counts.get(i).map_or(1, |&c| c.max(0) as usize);
+            attributes.extend((0..count).map(|_| attr));
+        }
+        if attributes.len() != times.len() {
+            return None;
+        }
+        Some(CurveKeys {
+            times: times.into(),
+            values: values.into(),
+            attributes: attributes.into(),
+        })
+    }
+
+    /// Evaluates the curve at the given time, applying the extrapolation outside the keyed range.
+    ///
+    /// Returns `None` if the curve has no keys.
+    pub fn evaluate_extrapolated(
+        &self,
+        time: i64,
+        extrapolation: &CurveExtrapolation,
+    ) -> Option<f64> {
+        let first = *self.times.first()?;
+        let last = *self.times.last()?;
+        match extrapolation.map_time(time, first, last) {
+            ExtrapolatedTime::At(time) => self.evaluate(time),
+            ExtrapolatedTime::Slope { edge, delta } => {
+                let slope = if self.times.len() < 2 {
+                    0.0
+                } else if edge == first {
+                    self.segment_slopes(0).0
+                } else {
+                    self.segment_slopes(self.times.len() - 2).1
+                };
+                Some(self.evaluate(edge)? + slope * delta as f64)
+            },
+        }
+    }
+
+    /// Returns the index of the segment containing the given time.
+    fn segment_index(&self, time: i64) -> usize {
+        match self.times.binary_search(&time) {
+            Ok(i) => i.min(self.times.len().saturating_sub(2)),
+            Err(i) => i.saturating_sub(1).min(self.times.len().saturating_sub(2)),
+        }
+    }
+
+    /// Returns the right slope of the key and the left slope of the next key.
+    fn segment_slopes(&self, index: usize) -> (f64, f64) {
+        let attr = &self.attributes[index];
+        let right = match attr.tangent_mode() {
+            TangentMode::Tcb => self.tcb_slopes(index).1,
+            _ => attr.right_slope(),
+        };
+        let next_left = match self.attributes[index + 1].tangent_mode() {
+            TangentMode::Tcb => self.tcb_slopes(index + 1).0,
+            _ => attr.next_left_slope(),
+        };
+        (right, next_left)
+    }
+
+    /// Computes left and right slopes of the TCB key.
+    ///
+    /// Slopes of a clamped key are flat if the key has the same value as an adjacent key.
+    fn tcb_slopes(&self, index: usize) -> (f64, f64) {
+        if self.attributes[index].is_clamped() && self.has_equal_neighbor(index) {
+            return (0.0, 0.0);
+        }
+        let slope = |i: usize| {
+            let dt = (self.times[i + 1] - self.times[i]) as f64;
+            (f64::from(self.values[i + 1]) - f64::from(self.values[i])) / dt
+        };
+        let last = self.times.len() - 1;
+        let prev = if index > 0 { Some(slope(index - 1)) } else { None };
+        let next = if index < last { Some(slope(index)) } else { None };
+        let (prev, next) = match (prev, next) {
+            (Some(p), Some(n)) => (p, n),
+            (Some(p), None) => (p, p),
+            (None, Some(n)) => (n, n),
+            (None, None) => (0.0, 0.0),
+        };
+        let [t, c, b] = self.attributes[index].tcb();
+        let left = (1.0 - t) * ((1.0 - c) * (1.0 + b) * prev + (1.0 + c) * (1.0 - b) * next) / 2.0;
+        let right = (1.0 - t) * ((1.0 + c) * (1.0 + b) * prev + (1.0 - c) * (1.0 - b) * next) / 2.0;
+        (left, right)
+    }
+
+    /// Returns whether the key has the same value as the previous or next key.
+    fn has_equal_neighbor(&self, index: usize) -> bool {
+        let value = self.values[index];
+        (index > 0 && self.values[index - 1] == value) ||
+            self.values.get(index + 1) == Some(&value)
+    }
+
+    /// Evaluates the curve at the given time.
+    ///
+    /// Times outside the keyed range are clamped; use `CurveExtrapolation::map_time()` beforehand
+    /// for extrapolation.
+    /// Returns `None` if the curve has no keys.
+    pub fn evaluate(&self, time: i64) -> Option<f64> {
+        let first = *self.times.first()?;
+        let last = *self.times.last()?;
+        if time <= first {
+            return Some(f64::from(self.values[0]));
+        }
+        if time >= last {
+            return Some(f64::from(self.values[self.values.len() - 1]));
+        }
+        let i = self.segment_index(time);
+        let (t0, t1) = (self.times[i] as f64, self.times[i + 1] as f64);
+        let (v0, v1) = (f64::from(self.values[i]), f64::from(self.values[i + 1]));
+        let dt = t1 - t0;
+        let x = (time as f64 - t0) / dt;
+        let attr = &self.attributes[i];
+        Some(match attr.interpolation() {
+            Interpolation::Constant { next: false } => v0,
+            Interpolation::Constant { next: true } => v1,
+            Interpolation::Linear => v0 + (v1 - v0) * x,
+            Interpolation::Cubic => {
+                let (right, next_left) = self.segment_slopes(i);
+                let (w0, w1) = (attr.right_weight(), attr.next_left_weight());
+                // Control points of the bezier curve, with time normalized to `[0, 1]`.
+                let (x1, y1) = (w0, v0 + right * w0 * dt);
+                let (x2, y2) = (1.0 - w1, v1 - next_left * w1 * dt);
+                let u = solve_bezier_parameter(x1, x2, x);
+                bezier(v0, y1, y2, v1, u)
+            },
+        })
+    }
+}
+
+
+impl ApproxEq for KeyAttribute {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.flags == other.flags && self.data.approx_eq(&other.data, epsilon)
+    }
+}
+
+
+impl ApproxEq for CurveKeys {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.times == other.times &&
+            self.values.approx_eq(&other.values, epsilon) &&
+            self.attributes.approx_eq(&other.attributes, epsilon)
+    }
+}
+
+
+/// Sampled animation of an object property.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertyTrack {
+    /// ID of the animated object.
+    pub object: i64,
+    /// Name of the animated property (such as `Lcl Translation`, `Visibility`, or a user-defined
+    /// property).
+    pub property: String,
+    /// Channels of the curve node, in the order of the sample components.
+    pub channels: Vec<CurveChannel>,
+    /// Sampled values.
+    ///
+    /// `samples[i][c]` is the value of `channels[c]` at the `i`-th time.
+    pub samples: Vec<Vec<f64>>,
+}
+
+
+/// Samples all object properties animated in the given `AnimationLayer`.
+///
+/// Every `AnimationCurveNode` in the layer is sampled at the given times, and a track is emitted
+/// for each property (OP connection) it animates: not only transforms (`Lcl Translation` etc.)
+/// but also `Visibility` and custom user properties.
+/// Channels without curves are filled with the default values of the curve node, and
+/// extrapolation settings of curves are respected.
+///
+/// Tracks are sorted by object ID and property name.
+/// Times at a fixed frame rate can be created by `KTime::from_frame()`.
+pub fn bake_properties(doc: &Fbx7400Document, layer: i64, times: &[KTime]) -> Vec<PropertyTrack> {
+    let objects = doc.objects
+        .iter()
+        .flat_map(|objs| objs.iter())
+        .map(|obj| (obj.props.id, obj))
+        .collect::<FnvHashMap<_, _>>();
+    let graph = match doc.connections {
+        Some(ref connections) => connections.graph(),
+        None => return Vec::new(),
+    };
+    let object_of_class = |id: i64, class: &str| {
+        objects.get(&id).cloned().filter(|obj| obj.props.class == class)
+    };
+
+    let mut tracks = Vec::new();
+    let nodes = graph
+        .child_objects(layer)
+        .filter_map(|id| object_of_class(id, "AnimationCurveNode"));
+    for node in nodes {
+        let defaults = CurveNodeDefaults::from_generic(node).unwrap_or_default();
+        let mut curves = BTreeMap::new();
+        for conn in graph.children_of(node.props.id) {
+            let channel = match conn.curve_channel() {
+                Some(channel) => channel,
+                None => continue,
+            };
+            let curve = match object_of_class(conn.source, "AnimationCurve") {
+                Some(curve) => curve,
+                None => continue,
+            };
+            if let Some(keys) = CurveKeys::from_generic(curve) {
+                let extrapolation = CurveExtrapolation::from_generic(curve).unwrap_or_default();
+                curves.insert(channel, (keys, extrapolation));
+            }
+        }
+        let channels = defaults.channels
+            .keys()
+            .chain(curves.keys())
+            .cloned()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        if channels.is_empty() {
+            continue;
+        }
+        let samples = times
+            .iter()
+            .map(|&time| {
+                channels
+                    .iter()
+                    .map(|channel| {
+                        let value = curves.get(channel).and_then(|(keys, extrapolation)| {
+                            keys.evaluate_extrapolated(time.0, extrapolation)
+                        });
+                        defaults.value(channel, value).unwrap_or(0.0)
+                    })
+                    .collect()
+            })
+            .collect::<Vec<_>>();
+        for conn in graph.parents_of(node.props.id) {
+            let property = match conn.property {
+                Some(ref property) if conn.curve_channel().is_none() => property,
+                _ => continue,
+            };
+            tracks.push(PropertyTrack {
+                object: conn.destination,
+                property: property.clone(),
+                channels: channels.clone(),
+                samples: samples.clone(),
+            });
+        }
+    }
+    tracks.sort_by(|a, b| (a.object, &a.property).cmp(&(b.object, &b.property)));
+    tracks
+}
+
+
+/// Evaluates the 1D cubic bezier curve.
+fn bezier(p0: f64, p1: f64, p2: f64, p3: f64, u: f64) -> f64 {
+    let v = 1.0 - u;
+    v * v * v * p0 + 3.0 * v * v * u * p1 + 3.0 * v * u * u * p2 + u * u * u * p3
+}
+
+
+/// Finds the bezier parameter `u` where the time component of the curve `(0, x1, x2, 1)` is `x`.
+fn solve_bezier_parameter(x1: f64, x2: f64, x: f64) -> f64 {
+    /// Max number of bisection iterations.
+    const MAX_ITERATIONS: usize = 64;
+    /// Tolerance of the time.
+    const EPSILON: f64 = 1e-12;
+
+    let (mut lo, mut hi) = (0.0f64, 1.0f64);
+    let mut u = x;
+    for _ in 0..MAX_ITERATIONS {
+        let bx = bezier(0.0, x1, x2, 1.0, u);
+        if (bx - x).abs() < EPSILON {
+            break;
+        }
+        if bx < x {
+            lo = u;
+        } else {
+            hi = u;
+        }
+        u = (lo + hi) / 2.0;
+    }
+    u
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extrapolation_map_time() {
+        let mut extra = CurveExtrapolation::default();
+        assert_eq!(extra.map_time(-5, 0, 10), ExtrapolatedTime::At(0));
+        assert_eq!(extra.map_time(15, 0, 10), ExtrapolatedTime::At(10));
+
+        extra.post.mode = ExtrapolationMode::Repeat;
+        assert_eq!(extra.map_time(13, 0, 10), ExtrapolatedTime::At(3));
+        assert_eq!(extra.map_time(20, 0, 10), ExtrapolatedTime::At(10));
+        assert_eq!(extra.map_time(21, 0, 10), ExtrapolatedTime::At(1));
+
+        extra.post.mode = ExtrapolationMode::MirrorRepeat;
+        assert_eq!(extra.map_time(13, 0, 10), ExtrapolatedTime::At(7));
+        assert_eq!(extra.map_time(23, 0, 10), ExtrapolatedTime::At(3));
+        extra.post.repetition = 1;
+        assert_eq!(extra.map_time(23, 0, 10), ExtrapolatedTime::At(0));
+
+        extra.pre.mode = ExtrapolationMode::Repeat;
+        assert_eq!(extra.map_time(-3, 0, 10), ExtrapolatedTime::At(7));
+
+        extra.pre.mode = ExtrapolationMode::KeepSlope;
+        assert_eq!(
+            extra.map_time(-3, 0, 10),
+            ExtrapolatedTime::Slope { edge: 0, delta: -3 }
+        );
+    }
+
+    fn keys(flags: u32, data: [f32; 4]) -> CurveKeys {
+        let attr = KeyAttribute {
+            flags,
+            data,
+        };
+        CurveKeys {
+            times: vec![0, 100].into(),
+            values: vec![0.0, 1.0].into(),
+            attributes: vec![attr, attr].into(),
+        }
+    }
+
+    #[test]
+    fn test_curve_key_evaluation() {
+        let close = |a: Option<f64>, b: f64| (a.unwrap() - b).abs() < 1e-6;
+
+        let constant = keys(key_flags::INTERPOLATION_CONSTANT, [0.0; 4]);
+        assert!(close(constant.evaluate(50), 0.0));
+        let next = keys(key_flags::INTERPOLATION_CONSTANT | key_flags::CONSTANT_NEXT, [0.0; 4]);
+        assert!(close(next.evaluate(50), 1.0));
+        let linear = keys(key_flags::INTERPOLATION_LINEAR, [0.0; 4]);
+        assert!(close(linear.evaluate(25), 0.25));
+
+        // Flat tangents: smoothstep.
+        let cubic = keys(key_flags::INTERPOLATION_CUBIC | key_flags::TANGENT_USER, [0.0; 4]);
+        assert!(close(cubic.evaluate(50), 0.5));
+        assert!(close(cubic.evaluate(25), 0.15625));
+
+        // Explicit weights of 1/3 are the same as unweighted tangents.
+        let w = (3333u32 << 16) | 3333;
+        let weighted = keys(
+            key_flags::INTERPOLATION_CUBIC | key_flags::TANGENT_USER | key_flags::WEIGHTED_RIGHT |
+                key_flags::WEIGHTED_NEXT_LEFT,
+            [0.0, 0.0, f32::from_bits(w), 0.0],
+        );
+        assert!((weighted.evaluate(25).unwrap() - 0.15625).abs() < 1e-3);
+
+        // TCB with zero parameters on a straight line is linear.
+        let tcb = keys(key_flags::INTERPOLATION_CUBIC | key_flags::TANGENT_TCB, [0.0; 4]);
+        assert!(close(tcb.evaluate(25), 0.25));
+
+        // Clamped TCB keys don't overshoot the plateau.
+        let attr = KeyAttribute {
+            flags: key_flags::INTERPOLATION_CUBIC | key_flags::TANGENT_TCB |
+                key_flags::TANGENT_GENERIC_CLAMP,
+            data: [0.0; 4],
+        };
+        let clamped = CurveKeys {
+            times: vec![0, 100, 200].into(),
+            values: vec![0.0, 1.0, 1.0].into(),
+            attributes: vec![attr; 3].into(),
+        };
+        assert!(close(clamped.evaluate(150), 1.0));
+    }
+
+    #[test]
+    fn test_bake_visibility() {
+        use std::io::Cursor;
+        use pull_parser::RootParser;
+        use pull_parser::ascii::AsciiDocument;
+
+        let source = r#"; FBX 7.4.0 project file
+Objects:  {
+	Model: 2000, "Model::Cube", "Mesh" {
+	}
+	AnimationLayer: 3100, "AnimationLayer::BaseLayer", "" {
+	}
+	AnimationCurveNode: 3200, "AnimationCurveNode::Visibility", "" {
+		Properties70:  {
+			P: "d|Visibility", "Visibility", "", "A",1.0
+		}
+	}
+	AnimationCurve: 3300, "AnimationCurve::", "" {
+		KeyTime: *2 {
+			a: 0,100
+		}
+		KeyValueFloat: *2 {
+			a: 1,0
+		}
+		KeyAttrFlags: *1 {
+			a: 4
+		}
+		KeyAttrDataFloat: *4 {
+			a: 0,0,0,0
+		}
+		KeyAttrRefCount: *1 {
+			a: 2
+		}
+	}
+}
+Connections:  {
+	C: "OO",3200,3100
+	C: "OP",3200,2000, "Visibility"
+	C: "OP",3300,3200, "d|Visibility"
+}
+"#;
+        let bytes = AsciiDocument::parse(source).unwrap().to_binary().unwrap();
+        let doc = Fbx7400Document::load(RootParser::new(Cursor::new(bytes))).unwrap();
+        let tracks = bake_properties(&doc, 3100, &[KTime(0), KTime(25), KTime(200)]);
+        assert_eq!(tracks.len(), 1);
+        assert_eq!(tracks[0].object, 2000);
+        assert_eq!(tracks[0].property, "Visibility");
+        assert_eq!(tracks[0].channels, vec![CurveChannel::Other("Visibility".to_owned())]);
+        assert_eq!(tracks[0].samples, vec![vec![1.0], vec![0.75], vec![0.0]]);
+    }
+}
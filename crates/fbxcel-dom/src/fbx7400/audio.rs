@@ -0,0 +1,129 @@
+//! `Audio` and `AudioLayer` objects.
+//!
+//! These objects are emitted by MotionBuilder and FBX 7.5 or later exporters.
+//! Other MotionBuilder-specific objects (such as `TimelineX` with timeline markers) are kept as
+//! raw generic objects by `TypedObjectsLoader` (`TypedObject::Other`).
+
+use crate::OwnedAttribute;
+use crate::fbx7400::{GenericObject, ObjectProperties, Properties70};
+use approx::ApproxEq;
+use memory::MemoryUsage;
+
+
+/// `Audio` object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Audio {
+    /// Properties common to object nodes.
+    pub props: ObjectProperties,
+    /// File name.
+    pub filename: String,
+    /// Relative file name.
+    pub relative_filename: String,
+    /// Embedded content.
+    ///
+    /// `None` if the content is not embedded (or is empty).
+    pub content: Option<Vec<u8>>,
+    /// Other properties.
+    pub properties: Properties70,
+}
+
+impl Audio {
+    /// Creates an `Audio` from the given generic object.
+    ///
+    /// File names are read from `Filename` and `RelativeFilename` child nodes, or from `Path`
+    /// and `RelPath` properties if the child nodes don't exist.
+    ///
+    /// Returns `None` if the object is not an `Audio` object.
+    pub fn from_generic(obj: &GenericObject) -> Option<Self> {
+        if obj.props.class != "Audio" {
+            return None;
+        }
+        let child_attr = |name: &str| {
+            obj.nodes
+                .iter()
+                .find(|node| node.name == name.as_bytes())
+                .and_then(|node| node.attributes.first())
+        };
+        let child_string = |name: &str, prop: &str| match child_attr(name) {
+            Some(OwnedAttribute::String(Ok(v))) => v.clone(),
+            _ => {
+                obj.properties
+                    .values_string
+                    .get(prop)
+                    .map_or_else(String::new, |v| v.value().clone())
+            },
+        };
+        let content = match child_attr("Content") {
+            Some(OwnedAttribute::Binary(v)) if !v.is_empty() => Some(v.to_vec()),
+            _ => None,
+        };
+        Some(Audio {
+            props: obj.props.clone(),
+            filename: child_string("Filename", "Path"),
+            relative_filename: child_string("RelativeFilename", "RelPath"),
+            content,
+            properties: obj.properties.clone(),
+        })
+    }
+}
+
+
+/// `AudioLayer` object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioLayer {
+    /// Properties common to object nodes.
+    pub props: ObjectProperties,
+    /// Properties (such as `Volume` and `Mute`).
+    pub properties: Properties70,
+}
+
+impl AudioLayer {
+    /// Creates an `AudioLayer` from the given generic object.
+    ///
+    /// Returns `None` if the object is not an `AudioLayer` object.
+    pub fn from_generic(obj: &GenericObject) -> Option<Self> {
+        if obj.props.class != "AudioLayer" {
+            return None;
+        }
+        Some(AudioLayer {
+            props: obj.props.clone(),
+            properties: obj.properties.clone(),
+        })
+    }
+}
+
+
+impl MemoryUsage for Audio {
+    fn heap_usage(&self) -> usize {
+        self.props.heap_usage() +
+            self.filename.heap_usage() +
+            self.relative_filename.heap_usage() +
+            self.content.heap_usage() +
+            self.properties.heap_usage()
+    }
+}
+
+
+impl MemoryUsage for AudioLayer {
+    fn heap_usage(&self) -> usize {
+        self.props.heap_usage() + self.properties.heap_usage()
+    }
+}
+
+
+impl ApproxEq for Audio {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.props == other.props &&
+            self.filename == other.filename &&
+            self.relative_filename == other.relative_filename &&
+            self.content == other.content &&
+            self.properties.approx_eq(&other.properties, epsilon)
+    }
+}
+
+
+impl ApproxEq for AudioLayer {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.props == other.props && self.properties.approx_eq(&other.properties, epsilon)
+    }
+}
@@ -0,0 +1,451 @@
+//! Axis system and unit conversion of documents.
+//!
+//! Scenes built by applications often use the axis system and the unit of the engine, such as
+//! Z-up meters.
+//! `convert_scene()` rewrites the document into another axis system and unit before writing, and
+//! `convert_to_maya()` converts it into the Maya defaults (Y-up right-handed, centimeters) which
+//! most importers expect.
+//!
+//! As the FBX SDK does, the axis conversion is applied to the root models rather than to
+//! geometries, so that local transforms and animations of the other models are kept as is.
+
+use fnv::FnvHashSet;
+use crate::{GenericNode, OwnedAttribute};
+use crate::fbx7400::{AxisSystem, Fbx7400Document, GenericObject, GlobalSettings, PropertyValue};
+use crate::fbx7400::{flip_handedness, set_unit_scale, SignedAxis};
+use crate::fbx7400::geometry::Axis;
+use crate::fbx7400::property_names;
+
+
+/// Column-major 3x3 matrix (`m[column][row]`).
+type Mat3 = [[f64; 3]; 3];
+
+/// Child nodes of `Deformer` objects which have 4x4 matrices.
+const DEFORMER_MATRIX_NODES: &[&str] = &["Transform", "TransformLink", "TransformAssociateModel"];
+
+
+/// Converts the document into the given axis system and unit scale factor (length of the unit in
+/// centimeters, for example `100.0` for meters).
+///
+/// The source axis system and unit are read from `GlobalSettings`, so documents authored in
+/// another convention should record it there (missing values are the FBX defaults, Y-up
+/// right-handed centimeters).
+/// `GlobalSettings` is created if missing, and updated to the target.
+///
+/// The conversion is done in the steps below:
+///
+/// 1. Lengths are scaled by `set_unit_scale()`.
+/// 2. If the handedness differs, the document is mirrored by `flip_handedness()`.
+/// 3. The remaining rotation is applied to the translations, rotation offsets, and pre-rotations
+///    of the root models (models connected to the scene root), and to the matrices of clusters
+///    and bind poses.
+///
+/// Animation curves of the translations of root models are not rotated, and a warning is
+/// emitted for them.
+///
+/// # Panics
+/// Panics if `unit_scale` is not positive.
+pub fn convert_scene(doc: &mut Fbx7400Document, target: &AxisSystem, unit_scale: f64) {
+    let source = doc.global_settings.get_or_insert_with(GlobalSettings::new).axis_system();
+    set_unit_scale(doc, unit_scale);
+    convert_axis_system(doc, source, target);
+    if let Some(ref mut gs) = doc.global_settings {
+        set_axis_system(gs, target);
+    }
+}
+
+
+/// Converts the document into Y-up right-handed centimeters (the defaults of Maya and the FBX
+/// SDK).
+///
+/// See `convert_scene()` for details.
+pub fn convert_to_maya(doc: &mut Fbx7400Document) {
+    convert_scene(doc, &AxisSystem::Y_UP_RIGHT_HANDED, 1.0);
+}
+
+
+/// Identity matrix.
+const IDENTITY: Mat3 = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+
+/// Mirrors and rotates the document from the source axis system into the target.
+fn convert_axis_system(doc: &mut Fbx7400Document, mut source: AxisSystem, target: &AxisSystem) {
+    if !is_valid(&source) || !is_valid(target) {
+        warn!(
+            "Skipping axis conversion with degenerate axis system: source={:?}, target={:?}",
+            source, target
+        );
+        return;
+    }
+    if source.is_right_handed() != target.is_right_handed() {
+        let (axis, mirrored) = mirrored_source(&source, target);
+        flip_handedness(doc, axis);
+        source = mirrored;
+    }
+    let rotation = to_mat3(&source.conversion_matrix(target));
+    if rotation != IDENTITY {
+        rotate_scene(doc, &rotation);
+    }
+}
+
+
+/// Writes the axis system into `GlobalSettings`.
+fn set_axis_system(gs: &mut GlobalSettings, axes: &AxisSystem) {
+    let props = [
+        (property_names::UP_AXIS, property_names::UP_AXIS_SIGN, axes.up),
+        (property_names::FRONT_AXIS, property_names::FRONT_AXIS_SIGN, axes.front),
+        (property_names::COORD_AXIS, property_names::COORD_AXIS_SIGN, axes.coord),
+    ];
+    let values = &mut gs.properties.values_i64;
+    for &(axis_name, sign_name, axis) in &props {
+        values.insert(axis_name.to_owned(), PropertyValue::new(axis.axis.index() as i64));
+        values.insert(sign_name.to_owned(), PropertyValue::new(i64::from(axis.sign)));
+    }
+}
+
+
+/// Returns `true` if the up, front, and coord axes are distinct.
+fn is_valid(axes: &AxisSystem) -> bool {
+    axes.up.axis != axes.front.axis &&
+        axes.up.axis != axes.coord.axis &&
+        axes.front.axis != axes.coord.axis
+}
+
+
+/// Returns the axis to mirror the scene across, and the source axis system after mirroring.
+///
+/// The axis is chosen so that the remaining rotation is closest to the identity, for example
+/// `Z` for Y-up left-handed to Y-up right-handed.
+fn mirrored_source(source: &AxisSystem, target: &AxisSystem) -> (Axis, AxisSystem) {
+    let flip = |v: SignedAxis, axis: Axis| if v.axis == axis {
+        SignedAxis { axis, sign: -v.sign }
+    } else {
+        v
+    };
+    let trace = |axes: &AxisSystem| {
+        let mat = axes.conversion_matrix(target);
+        mat[0][0] + mat[1][1] + mat[2][2]
+    };
+    let mut best: Option<(Axis, AxisSystem)> = None;
+    for &axis in &[Axis::X, Axis::Y, Axis::Z] {
+        let mirrored = AxisSystem {
+            up: flip(source.up, axis),
+            front: flip(source.front, axis),
+            coord: flip(source.coord, axis),
+        };
+        if best.as_ref().is_none_or(|(_, b)| trace(&mirrored) > trace(b)) {
+            best = Some((axis, mirrored));
+        }
+    }
+    best.expect("Should never fail: there are candidate axes")
+}
+
+
+/// Applies the rotation to the root models and to the global matrices.
+fn rotate_scene(doc: &mut Fbx7400Document, rotation: &Mat3) {
+    let objects = match doc.objects {
+        Some(ref mut v) => v,
+        None => return,
+    };
+    let mut roots = FnvHashSet::default();
+    if let Some(ref connections) = doc.connections {
+        let models: FnvHashSet<i64> = objects
+            .iter()
+            .filter(|obj| obj.props.class == "Model")
+            .map(|obj| obj.props.id)
+            .collect();
+        for conn in &connections.0 {
+            if conn.destination == 0 && conn.property.is_none() && models.contains(&conn.source) {
+                roots.insert(conn.source);
+            }
+        }
+        for conn in &connections.0 {
+            let is_translation =
+                conn.property.as_ref().is_some_and(|p| p == property_names::LCL_TRANSLATION);
+            if is_translation && roots.contains(&conn.destination) {
+                warn!(
+                    "Animation of the translation of the root model (id={}) is not converted",
+                    conn.destination
+                );
+            }
+        }
+    }
+
+    for obj in objects.iter_mut() {
+        match obj.props.class.as_str() {
+            "Model" if roots.contains(&obj.props.id) => rotate_root_model(obj, rotation),
+            "Deformer" => {
+                for name in DEFORMER_MATRIX_NODES {
+                    rotate_child_matrices(&mut obj.nodes, name, rotation);
+                }
+            },
+            "Pose" => {
+                for pose_node in obj.nodes.iter_mut().filter(|node| node.name == b"PoseNode") {
+                    rotate_child_matrices(&mut pose_node.children, "Matrix", rotation);
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+
+/// Applies the rotation to the local transform of the root model.
+///
+/// The rotation is folded into `PreRotation` (which is always in XYZ order), so that
+/// `Lcl Rotation` and its animation are kept.
+/// The rotation pivot is kept, and the rotation offset absorbs the difference.
+fn rotate_root_model(obj: &mut GenericObject, rotation: &Mat3) {
+    let values = &mut obj.properties.values_f64_3;
+    let get = |values: &::fnv::FnvHashMap<String, PropertyValue<[f64; 3]>>, name: &str| {
+        values.get(name).map_or([0.0; 3], |v| *v.value())
+    };
+
+    let translation = get(values, property_names::LCL_TRANSLATION);
+    let offset = get(values, property_names::ROTATION_OFFSET);
+    let pivot = get(values, property_names::ROTATION_PIVOT);
+    let rotated_offset = mul_vec(rotation, &offset);
+    let rotated_pivot = mul_vec(rotation, &pivot);
+    let new_offset = [
+        rotated_offset[0] + rotated_pivot[0] - pivot[0],
+        rotated_offset[1] + rotated_pivot[1] - pivot[1],
+        rotated_offset[2] + rotated_pivot[2] - pivot[2],
+    ];
+
+    let is_active = obj.properties.values_i64
+        .get(property_names::ROTATION_ACTIVE)
+        .is_some_and(|v| *v.value() != 0);
+    let pre_rotation = if is_active {
+        get(values, property_names::PRE_ROTATION)
+    } else {
+        // Pre- and post-rotations were ignored, so they should not be activated.
+        values.remove(property_names::POST_ROTATION);
+        [0.0; 3]
+    };
+    let new_pre_rotation = mat3_to_euler_xyz(&mul(rotation, &euler_xyz_to_mat3(&pre_rotation)));
+
+    let mut set = |name: &str, v: [f64; 3]| {
+        values.insert(name.to_owned(), PropertyValue::new(v));
+    };
+    set(property_names::LCL_TRANSLATION, mul_vec(rotation, &translation));
+    if new_offset != [0.0; 3] || offset != [0.0; 3] {
+        set(property_names::ROTATION_OFFSET, new_offset);
+    }
+    set(property_names::PRE_ROTATION, new_pre_rotation);
+    obj.properties
+        .values_i64
+        .insert(property_names::ROTATION_ACTIVE.to_owned(), PropertyValue::new(1));
+}
+
+
+/// Applies the rotation to 4x4 matrices (stored as 16 `f64`s in column-major order) of the
+/// child nodes with the given name.
+fn rotate_child_matrices(nodes: &mut [GenericNode], name: &str, rotation: &Mat3) {
+    for node in nodes.iter_mut().filter(|node| node.name == name.as_bytes()) {
+        if let Some(OwnedAttribute::ArrF64(v)) = node.attributes.first_mut() {
+            if v.len() != 16 {
+                continue;
+            }
+            for column in v.chunks_mut(4) {
+                let rotated = mul_vec(rotation, &[column[0], column[1], column[2]]);
+                column[..3].copy_from_slice(&rotated);
+            }
+        }
+    }
+}
+
+
+/// Returns the upper-left 3x3 part of the 4x4 matrix.
+fn to_mat3(mat: &[[f64; 4]; 4]) -> Mat3 {
+    let mut result = [[0.0; 3]; 3];
+    for (dest, src) in result.iter_mut().zip(mat) {
+        dest.copy_from_slice(&src[..3]);
+    }
+    result
+}
+
+
+/// Returns `a * b`.
+fn mul(a: &Mat3, b: &Mat3) -> Mat3 {
+    let mut result = [[0.0; 3]; 3];
+    for (dest, column) in result.iter_mut().zip(b) {
+        *dest = mul_vec(a, column);
+    }
+    result
+}
+
+
+/// Returns `mat * v`.
+fn mul_vec(mat: &Mat3, v: &[f64; 3]) -> [f64; 3] {
+    let mut result = [0.0; 3];
+    for (row, dest) in result.iter_mut().enumerate() {
+        *dest = (0..3).map(|col| mat[col][row] * v[col]).sum();
+    }
+    result
+}
+
+
+/// Returns the rotation matrix of Euler angles in degrees, in XYZ order (`Rz * Ry * Rx`).
+fn euler_xyz_to_mat3(angles: &[f64; 3]) -> Mat3 {
+    let (sx, cx) = angles[0].to_radians().sin_cos();
+    let (sy, cy) = angles[1].to_radians().sin_cos();
+    let (sz, cz) = angles[2].to_radians().sin_cos();
+    [
+        [cy * cz, cy * sz, -sy],
+        [sx * sy * cz - cx * sz, sx * sy * sz + cx * cz, sx * cy],
+        [cx * sy * cz + sx * sz, cx * sy * sz - sx * cz, cx * cy],
+    ]
+}
+
+
+/// Returns Euler angles in degrees, in XYZ order, of the rotation matrix.
+///
+/// Values are rounded to remove floating point noise for axis-aligned rotations.
+fn mat3_to_euler_xyz(mat: &Mat3) -> [f64; 3] {
+    let sy = (-mat[0][2]).clamp(-1.0, 1.0);
+    let (x, y, z) = if sy.abs() < 1.0 - 1e-9 {
+        (mat[1][2].atan2(mat[2][2]), sy.asin(), mat[0][1].atan2(mat[0][0]))
+    } else {
+        // Gimbal lock: only `x - z` (or `x + z`) is determined, so `z` is fixed to zero.
+        ((-mat[2][1]).atan2(mat[1][1]), sy.asin(), 0.0)
+    };
+    let round = |v: f64| {
+        let degrees = v.to_degrees();
+        let rounded = degrees.round();
+        if (degrees - rounded).abs() < 1e-9 {
+            rounded + 0.0
+        } else {
+            degrees
+        }
+    };
+    [round(x), round(y), round(z)]
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::fbx7400::{Connection, Connections, ObjectProperties, Properties70};
+    use crate::fbx7400::{unit_scale_factor, AxisSystem};
+    use super::*;
+
+    fn object(id: i64, class: &str, subclass: &str) -> GenericObject {
+        GenericObject::new(ObjectProperties {
+            id,
+            name: "Cube".to_owned(),
+            class: class.to_owned(),
+            subclass: subclass.to_owned(),
+        })
+    }
+
+    fn connection(source: i64, destination: i64) -> Connection {
+        Connection {
+            source,
+            destination,
+            property: None,
+            source_is_prop: false,
+            destination_is_prop: false,
+            extra_attributes: Vec::new(),
+        }
+    }
+
+    /// Creates a scene with a root model and its geometry, in Z-up meters.
+    fn z_up_meters_scene() -> Fbx7400Document {
+        let mut settings = Properties70::new();
+        settings
+            .values_f64
+            .insert(property_names::UNIT_SCALE_FACTOR.to_owned(), PropertyValue::new(100.0));
+        let mut gs = GlobalSettings {
+            version: 1000,
+            properties: settings,
+        };
+        set_axis_system(&mut gs, &AxisSystem::Z_UP_RIGHT_HANDED);
+        let mut model = object(1, "Model", "Mesh");
+        model.properties.values_f64_3.insert(
+            property_names::LCL_TRANSLATION.to_owned(),
+            PropertyValue::new([1.0, 2.0, 3.0]),
+        );
+        let mut geometry = object(2, "Geometry", "Mesh");
+        geometry.nodes.push(GenericNode {
+            name: b"Vertices".to_vec(),
+            attributes: vec![OwnedAttribute::ArrF64(vec![0.5, 0.0, 1.0].into_boxed_slice())],
+            children: Vec::new(),
+        });
+        Fbx7400Document {
+            version: 7400,
+            fbx_header_extension: None,
+            file_id: None,
+            creation_time: None,
+            creator: None,
+            global_settings: Some(gs),
+            documents: None,
+            references: None,
+            definitions: None,
+            objects: Some(vec![model, geometry]),
+            connections: Some(Connections(vec![connection(1, 0), connection(2, 1)])),
+            takes: None,
+            footer: None,
+        }
+    }
+
+    #[test]
+    fn euler_round_trip() {
+        let cases = [[0.0, 0.0, 0.0], [30.0, -45.0, 60.0], [-90.0, 0.0, 0.0], [10.0, 20.0, 170.0]];
+        for angles in &cases {
+            let result = mat3_to_euler_xyz(&euler_xyz_to_mat3(angles));
+            for (a, b) in angles.iter().zip(&result) {
+                assert!((a - b).abs() < 1e-6, "angles={:?}, result={:?}", angles, result);
+            }
+        }
+        // Rotation by 90 degrees around the X axis maps Y to Z.
+        let mat = euler_xyz_to_mat3(&[90.0, 0.0, 0.0]);
+        let v = mul_vec(&mat, &[0.0, 1.0, 0.0]);
+        assert!((v[2] - 1.0).abs() < 1e-9 && v[1].abs() < 1e-9);
+    }
+
+    #[test]
+    fn z_up_meters_to_maya() {
+        let mut doc = z_up_meters_scene();
+        convert_to_maya(&mut doc);
+
+        let gs = doc.global_settings.as_ref().unwrap();
+        assert_eq!(gs.axis_system(), AxisSystem::Y_UP_RIGHT_HANDED);
+        assert_eq!(gs.properties.get_i64(property_names::FRONT_AXIS_SIGN), Some(1));
+        assert_eq!(unit_scale_factor(&doc), 1.0);
+
+        let objects = doc.objects.as_ref().unwrap();
+        let model = &objects[0].properties;
+        assert_eq!(
+            model.values_f64_3[property_names::LCL_TRANSLATION].value(),
+            &[100.0, 300.0, -200.0]
+        );
+        assert_eq!(model.values_f64_3[property_names::PRE_ROTATION].value(), &[-90.0, 0.0, 0.0]);
+        assert_eq!(model.get_i64(property_names::ROTATION_ACTIVE), Some(1));
+        // Geometries are only scaled, as they are rotated with the root model.
+        assert_eq!(
+            objects[1].nodes[0].attributes[0],
+            OwnedAttribute::ArrF64(vec![50.0, 0.0, 100.0].into_boxed_slice())
+        );
+    }
+
+    #[test]
+    fn left_handed_to_maya() {
+        let mut doc = z_up_meters_scene();
+        set_axis_system(doc.global_settings.as_mut().unwrap(), &AxisSystem::Y_UP_LEFT_HANDED);
+        convert_scene(&mut doc, &AxisSystem::Y_UP_RIGHT_HANDED, 100.0);
+
+        let gs = doc.global_settings.as_ref().unwrap();
+        assert_eq!(gs.axis_system(), AxisSystem::Y_UP_RIGHT_HANDED);
+        let objects = doc.objects.as_ref().unwrap();
+        let model = &objects[0].properties;
+        // Mirrored across the Z axis, without rotations.
+        assert_eq!(
+            model.values_f64_3[property_names::LCL_TRANSLATION].value(),
+            &[1.0, 2.0, -3.0]
+        );
+        assert!(!model.values_f64_3.contains_key(property_names::PRE_ROTATION));
+        assert_eq!(
+            objects[1].nodes[0].attributes[0],
+            OwnedAttribute::ArrF64(vec![0.5, 0.0, -1.0].into_boxed_slice())
+        );
+    }
+}
@@ -0,0 +1,697 @@
+//! `Connections` node and its children.
+
+use std::fmt;
+use fnv::{FnvHashMap, FnvHashSet};
+use pull_parser::{Parser, ParserSource, Attributes};
+use crate::{Result, Error, OwnedAttribute};
+use crate::fbx7400::{Definitions, property_names};
+use approx::ApproxEq;
+use memory::MemoryUsage;
+
+
+/// `Connections` node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Connections(pub Vec<Connection>);
+
+impl Connections {
+    /// Loads node contents from the parser.
+    pub fn load<R, P>(mut parser: P) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        let mut connections = Vec::new();
+
+        loop {
+            let attrs = try_get_node_attrs!(parser, ConnectionAttrs::load);
+            connections.push(Connection::load(parser.subtree_parser(), attrs)?);
+        }
+        Ok(Connections(connections))
+    }
+
+    /// Returns the ID of the first object connected as the parent (OO destination) of the given
+    /// object.
+    pub fn parent_object(&self, child: i64) -> Option<i64> {
+        self.0
+            .iter()
+            .find(|conn| {
+                conn.source == child && !conn.source_is_prop && !conn.destination_is_prop
+            })
+            .map(|conn| conn.destination)
+    }
+
+    /// Returns the connections whose destination is the given object, i.e. connections to the
+    /// children of the object.
+    pub fn children_of(&self, id: i64) -> impl Iterator<Item = &Connection> {
+        self.0.iter().filter(move |conn| conn.destination == id)
+    }
+
+    /// Returns the connections whose source is the given object, i.e. connections to the parents
+    /// of the object.
+    pub fn parents_of(&self, id: i64) -> impl Iterator<Item = &Connection> {
+        self.0.iter().filter(move |conn| conn.source == id)
+    }
+
+    /// Returns the OP connections to the given property of the given object.
+    pub fn property_connections<'a>(
+        &'a self,
+        id: i64,
+        property: &'a str,
+    ) -> impl Iterator<Item = &'a Connection> {
+        self.children_of(id).filter(move |conn| {
+            conn.destination_is_prop && conn.property.as_ref().is_some_and(|p| p == property)
+        })
+    }
+
+    /// Builds the indexed graph for repeated queries.
+    pub fn graph(&self) -> ConnectionGraph<'_> {
+        ConnectionGraph::new(&self.0)
+    }
+
+    /// Returns the connections referring to unknown objects.
+    ///
+    /// `is_known` returns whether the object with the given ID exists.
+    /// The implicit scene root (`0`) is always known.
+    pub fn dangling<F>(&self, mut is_known: F) -> Vec<DanglingConnection>
+    where
+        F: FnMut(i64) -> bool,
+    {
+        self.0
+            .iter()
+            .enumerate()
+            .filter_map(|(index, conn)| DanglingConnection::new(index, conn, &mut is_known))
+            .collect()
+    }
+
+    /// Removes the connections referring to unknown objects, and returns them.
+    ///
+    /// See `dangling()` for `is_known`.
+    /// Indices of the returned connections are the ones before the removal.
+    pub fn prune_dangling<F>(&mut self, is_known: F) -> Vec<DanglingConnection>
+    where
+        F: FnMut(i64) -> bool,
+    {
+        let dangling = self.dangling(is_known);
+        if !dangling.is_empty() {
+            let mut index = 0;
+            let mut pruned = dangling.iter().map(|d| d.index).peekable();
+            self.0.retain(|_| {
+                let keep = pruned.peek() != Some(&index);
+                if !keep {
+                    pruned.next();
+                }
+                index += 1;
+                keep
+            });
+            warn!("Pruned {} dangling connections", dangling.len());
+        }
+        dangling
+    }
+}
+
+
+/// Connection referring to objects which don't exist.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DanglingConnection {
+    /// Index of the connection in `Connections`.
+    pub index: usize,
+    /// Connection.
+    pub connection: Connection,
+    /// `true` if the source object is missing.
+    pub missing_source: bool,
+    /// `true` if the destination object is missing.
+    pub missing_destination: bool,
+}
+
+impl DanglingConnection {
+    /// Creates a `DanglingConnection` if the connection refers to unknown objects.
+    fn new<F>(index: usize, conn: &Connection, is_known: &mut F) -> Option<Self>
+    where
+        F: FnMut(i64) -> bool,
+    {
+        let mut is_known = |id| id == 0 || is_known(id);
+        let missing_source = !is_known(conn.source);
+        let missing_destination = !is_known(conn.destination);
+        if !missing_source && !missing_destination {
+            return None;
+        }
+        Some(DanglingConnection {
+            index,
+            connection: conn.clone(),
+            missing_source,
+            missing_destination,
+        })
+    }
+}
+
+
+/// Object graph indexed by sources, destinations, and destination properties.
+///
+/// Queries of `ConnectionGraph` return the same connections in the same order as the ones of
+/// `Connections`, without scanning all connections.
+#[derive(Debug, Clone)]
+pub struct ConnectionGraph<'a> {
+    /// Connections.
+    connections: &'a [Connection],
+    /// Map from the source ID to the connection indices.
+    by_source: FnvHashMap<i64, Vec<usize>>,
+    /// Map from the destination ID to the connection indices.
+    by_destination: FnvHashMap<i64, Vec<usize>>,
+    /// Map from the destination ID and the property to the OP connection indices.
+    by_property: FnvHashMap<i64, FnvHashMap<&'a str, Vec<usize>>>,
+}
+
+impl<'a> ConnectionGraph<'a> {
+    /// Creates a new `ConnectionGraph`.
+    pub fn new(connections: &'a [Connection]) -> Self {
+        let mut by_source = FnvHashMap::<_, Vec<_>>::default();
+        let mut by_destination = FnvHashMap::<_, Vec<_>>::default();
+        let mut by_property = FnvHashMap::<_, FnvHashMap<_, Vec<_>>>::default();
+        for (i, conn) in connections.iter().enumerate() {
+            by_source.entry(conn.source).or_default().push(i);
+            by_destination.entry(conn.destination).or_default().push(i);
+            if let (true, Some(property)) = (conn.destination_is_prop, conn.property.as_ref()) {
+                by_property
+                    .entry(conn.destination)
+                    .or_default()
+                    .entry(property.as_str())
+                    .or_default()
+                    .push(i);
+            }
+        }
+        ConnectionGraph {
+            connections,
+            by_source,
+            by_destination,
+            by_property,
+        }
+    }
+
+    /// Returns the connections at the given indices.
+    fn select<'s>(
+        &'s self,
+        indices: Option<&'s Vec<usize>>,
+    ) -> impl Iterator<Item = &'a Connection> + 's {
+        let connections = self.connections;
+        indices.into_iter().flatten().map(move |&i| &connections[i])
+    }
+
+    /// Returns the connections whose destination is the given object.
+    pub fn children_of(&self, id: i64) -> impl Iterator<Item = &'a Connection> + '_ {
+        self.select(self.by_destination.get(&id))
+    }
+
+    /// Returns the connections whose source is the given object.
+    pub fn parents_of(&self, id: i64) -> impl Iterator<Item = &'a Connection> + '_ {
+        self.select(self.by_source.get(&id))
+    }
+
+    /// Returns the OP connections to the given property of the given object.
+    pub fn property_connections(
+        &self,
+        id: i64,
+        property: &str,
+    ) -> impl Iterator<Item = &'a Connection> + '_ {
+        self.select(self.by_property.get(&id).and_then(|props| props.get(property)))
+    }
+
+    /// Returns the IDs of the objects connected to the given object as OO children.
+    pub fn child_objects(&self, id: i64) -> impl Iterator<Item = i64> + '_ {
+        self.children_of(id)
+            .filter(|conn| !conn.source_is_prop && !conn.destination_is_prop)
+            .map(|conn| conn.source)
+    }
+
+    /// Returns the IDs of the objects which the given object is connected to as an OO child.
+    pub fn parent_objects(&self, id: i64) -> impl Iterator<Item = i64> + '_ {
+        self.parents_of(id)
+            .filter(|conn| !conn.source_is_prop && !conn.destination_is_prop)
+            .map(|conn| conn.destination)
+    }
+}
+
+
+/// Builder of `Connections` with validation.
+///
+/// This checks that connections refer to registered objects, OO connections do not make cycles,
+/// and OP connections refer to properties defined in property templates.
+/// The object with ID `0` (the scene root) is always registered.
+#[derive(Debug, Clone)]
+pub struct ConnectionsBuilder<'a> {
+    /// Definitions of property templates.
+    definitions: &'a Definitions,
+    /// Object types (node names of the objects) of the registered objects.
+    object_types: FnvHashMap<i64, Option<String>>,
+    /// Parents of objects connected by OO connections.
+    parents: FnvHashMap<i64, Vec<i64>>,
+    /// Connections.
+    connections: Vec<Connection>,
+}
+
+impl<'a> ConnectionsBuilder<'a> {
+    /// Creates a new `ConnectionsBuilder`.
+    pub fn new(definitions: &'a Definitions) -> Self {
+        let mut object_types = FnvHashMap::default();
+        object_types.insert(0, None);
+        ConnectionsBuilder {
+            definitions,
+            object_types,
+            parents: FnvHashMap::default(),
+            connections: Vec::new(),
+        }
+    }
+
+    /// Registers an object with the given ID and object type (such as `Model` and `Geometry`).
+    pub fn add_object<S: Into<String>>(&mut self, id: i64, object_type: S) {
+        self.object_types.insert(id, Some(object_type.into()));
+    }
+
+    /// Adds an OO connection, i.e. makes the source object a child of the destination object.
+    pub fn connect_oo(&mut self, source: i64, destination: i64) -> Result<()> {
+        self.ensure_object_exists(source)?;
+        self.ensure_object_exists(destination)?;
+        if self.is_ancestor_or_self(source, destination) {
+            return Err(Error::ConnectionCycle {
+                source,
+                destination,
+            });
+        }
+        self.parents.entry(source).or_default().push(destination);
+        self.connections.push(Connection {
+            source,
+            destination,
+            property: None,
+            source_is_prop: false,
+            destination_is_prop: false,
+            extra_attributes: Vec::new(),
+        });
+        Ok(())
+    }
+
+    /// Adds an OP connection, i.e. connects the source object to the property of the
+    /// destination object.
+    pub fn connect_op(&mut self, source: i64, destination: i64, property: &str) -> Result<()> {
+        self.ensure_object_exists(source)?;
+        self.ensure_object_exists(destination)?;
+        if !self.has_property(destination, property) {
+            return Err(Error::UnknownProperty {
+                object: destination,
+                property: property.to_owned(),
+            });
+        }
+        self.connections.push(Connection {
+            source,
+            destination,
+            property: Some(property.to_owned()),
+            source_is_prop: false,
+            destination_is_prop: true,
+            extra_attributes: Vec::new(),
+        });
+        Ok(())
+    }
+
+    /// Builds `Connections`.
+    pub fn build(self) -> Connections {
+        Connections(self.connections)
+    }
+
+    /// Returns `Err(Error::UnknownObject(_))` if the object is not registered.
+    fn ensure_object_exists(&self, id: i64) -> Result<()> {
+        if self.object_types.contains_key(&id) {
+            Ok(())
+        } else {
+            Err(Error::UnknownObject(id))
+        }
+    }
+
+    /// Returns `true` if `ancestor` is `id` itself or reachable from `id` by OO connections.
+    fn is_ancestor_or_self(&self, ancestor: i64, id: i64) -> bool {
+        let mut visited = FnvHashSet::default();
+        let mut stack = vec![id];
+        while let Some(current) = stack.pop() {
+            if current == ancestor {
+                return true;
+            }
+            if visited.insert(current) {
+                if let Some(parents) = self.parents.get(&current) {
+                    stack.extend(parents);
+                }
+            }
+        }
+        false
+    }
+
+    /// Returns `true` if the property templates for the object define the given property.
+    fn has_property(&self, id: i64, property: &str) -> bool {
+        let object_type = match self.object_types.get(&id) {
+            Some(Some(ty)) => ty,
+            _ => return false,
+        };
+        self.definitions
+            .object_types
+            .iter()
+            .filter(|def| def.object_type == *object_type)
+            .flat_map(|def| def.property_template.values())
+            .any(|template| template.contains(property))
+    }
+}
+
+
+/// Attributes read from a `C` node.
+struct ConnectionAttrs {
+    pub source_id: i64,
+    pub destination_id: i64,
+    pub property: Option<String>,
+    pub source_is_prop: bool,
+    pub destination_is_prop: bool,
+    pub extra_attributes: Vec<OwnedAttribute>,
+}
+
+impl ConnectionAttrs {
+    /// Loads attributes.
+    pub fn load<R>(name: &str, mut attrs: Attributes<R>) -> Result<Self>
+    where
+        R: ParserSource,
+    {
+        use pull_parser::utils::AttributeValues;
+
+        if name == "C" {
+            let (ty, source_id, destination_id) =
+                <(String, i64, i64)>::from_attributes(&mut attrs)?
+                    .ok_or_else(|| Error::InvalidAttribute("C".to_owned()))?;
+            let (source_is_prop, destination_is_prop) = match ty.as_str() {
+                "OO" => (false, false),
+                "OP" => (false, true),
+                "PO" => (true, false),
+                "PP" => (true, true),
+                _ => return Err(Error::InvalidAttribute("C".to_owned())),
+            };
+            let property = if attrs.rest_attributes() > 0 {
+                Some(String::from_attributes(&mut attrs)?.ok_or_else(|| {
+                    Error::InvalidAttribute("C".to_owned())
+                })?)
+            } else {
+                None
+            };
+            let extra_attributes = if attrs.rest_attributes() > 0 {
+                warn!(
+                    "`C` node has {} extra attributes, preserving them as raw values \
+                     (source={}, destination={})",
+                    attrs.rest_attributes(),
+                    source_id,
+                    destination_id
+                );
+                OwnedAttribute::load_attrs_from_parser_event(attrs)?
+            } else {
+                Vec::new()
+            };
+            Ok(ConnectionAttrs {
+                source_id,
+                destination_id,
+                property,
+                source_is_prop,
+                destination_is_prop,
+                extra_attributes,
+            })
+        } else {
+            Err(Error::UnexpectedNode(name.to_owned()))
+        }
+    }
+}
+
+
+/// `C` node.
+///
+/// Note that "the child node will be a **source object** of the parent node", and "the parent node
+/// will be the **destination object** of the child node".
+/// See [FBX 2018 Developer Help:
+/// Connections](https://help.autodesk.com/view/FBX/2018/ENU/?guid=__files_GUID_BB63A93A_7663_4256_B060_8EA35CB0FF3A_htm)
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct Connection {
+    /// Object ID of the source object.
+    pub source: i64,
+    /// Object ID of the destination object.
+    pub destination: i64,
+    /// Property of the connection.
+    pub property: Option<String>,
+    /// `true` if the source is property.
+    pub source_is_prop: bool,
+    /// `true` if the destination is property.
+    pub destination_is_prop: bool,
+    /// Extra attributes after the property name.
+    ///
+    /// Some exporters append attributes unknown to the official SDK; they are preserved as is.
+    pub extra_attributes: Vec<OwnedAttribute>,
+}
+
+impl Connection {
+    /// Returns the animation curve channel if the property is a curve channel (such as `d|X`).
+    pub fn curve_channel(&self) -> Option<CurveChannel> {
+        self.property.as_ref().and_then(|prop| CurveChannel::parse(prop))
+    }
+
+    /// Loads node contents from the parser.
+    fn load<R, P>(mut parser: P, attrs: ConnectionAttrs) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        parser.skip_current_node()?;
+        Ok(Connection {
+            source: attrs.source_id,
+            destination: attrs.destination_id,
+            property: attrs.property,
+            source_is_prop: attrs.source_is_prop,
+            destination_is_prop: attrs.destination_is_prop,
+            extra_attributes: attrs.extra_attributes,
+        })
+    }
+}
+
+
+/// Channel of animation curve.
+///
+/// Animation curves are connected to animation curve nodes with property names prefixed by
+/// `d|`, for example `d|X` for X component of `Lcl Translation`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CurveChannel {
+    /// `d|X`.
+    X,
+    /// `d|Y`.
+    Y,
+    /// `d|Z`.
+    Z,
+    /// Other channel (such as `d|DeformPercent`).
+    ///
+    /// This contains the name without `d|` prefix.
+    Other(String),
+}
+
+impl CurveChannel {
+    /// Parses the property name of a connection.
+    ///
+    /// Returns `None` if the name is not a curve channel name (i.e. the name doesn't start with
+    /// `d|`).
+    pub fn parse(prop: &str) -> Option<Self> {
+        if !prop.starts_with(property_names::CURVE_CHANNEL_PREFIX) {
+            return None;
+        }
+        let name = &prop[property_names::CURVE_CHANNEL_PREFIX.len()..];
+        Some(match name {
+            "X" => CurveChannel::X,
+            "Y" => CurveChannel::Y,
+            "Z" => CurveChannel::Z,
+            _ => CurveChannel::Other(name.to_owned()),
+        })
+    }
+
+    /// Returns the channel name without `d|` prefix.
+    pub fn name(&self) -> &str {
+        match *self {
+            CurveChannel::X => "X",
+            CurveChannel::Y => "Y",
+            CurveChannel::Z => "Z",
+            CurveChannel::Other(ref name) => name,
+        }
+    }
+
+    /// Returns the index of the vector component (`0` for `X`, `1` for `Y`, `2` for `Z`).
+    ///
+    /// Returns `None` for `CurveChannel::Other(_)`.
+    pub fn component_index(&self) -> Option<usize> {
+        match *self {
+            CurveChannel::X => Some(0),
+            CurveChannel::Y => Some(1),
+            CurveChannel::Z => Some(2),
+            CurveChannel::Other(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for CurveChannel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", property_names::CURVE_CHANNEL_PREFIX, self.name())
+    }
+}
+
+
+impl MemoryUsage for Connections {
+    fn heap_usage(&self) -> usize {
+        self.0.heap_usage()
+    }
+}
+
+
+impl MemoryUsage for Connection {
+    fn heap_usage(&self) -> usize {
+        self.property.heap_usage() + self.extra_attributes.heap_usage()
+    }
+}
+
+
+impl MemoryUsage for CurveChannel {
+    fn heap_usage(&self) -> usize {
+        match *self {
+            CurveChannel::X | CurveChannel::Y | CurveChannel::Z => 0,
+            CurveChannel::Other(ref name) => name.heap_usage(),
+        }
+    }
+}
+
+
+impl ApproxEq for Connections {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.0.approx_eq(&other.0, epsilon)
+    }
+}
+
+
+impl ApproxEq for Connection {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.source == other.source &&
+            self.destination == other.destination &&
+            self.property == other.property &&
+            self.source_is_prop == other.source_is_prop &&
+            self.destination_is_prop == other.destination_is_prop &&
+            self.extra_attributes.approx_eq(&other.extra_attributes, epsilon)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use fnv::FnvHashMap;
+    use crate::Error;
+    use crate::fbx7400::{Definitions, ObjectType, Properties70};
+    use super::{Connection, Connections, ConnectionsBuilder};
+    use super::DanglingConnection;
+
+    #[test]
+    fn test_connections_builder() {
+        let mut template = Properties70::new();
+        template.values_empty.insert("Visibility".to_owned());
+        let mut property_template = FnvHashMap::default();
+        property_template.insert("FbxNode".to_owned(), template);
+        let definitions = Definitions {
+            version: 100,
+            count: 1,
+            object_types: vec![ObjectType {
+                object_type: "Model".to_owned(),
+                count: 2,
+                property_template,
+            }],
+        };
+        let mut builder = ConnectionsBuilder::new(&definitions);
+        builder.add_object(1, "Model");
+        builder.add_object(2, "Model");
+        builder.add_object(3, "AnimationCurveNode");
+
+        builder.connect_oo(1, 0).expect("Failed to connect");
+        builder.connect_oo(2, 1).expect("Failed to connect");
+        match builder.connect_oo(1, 2) {
+            Err(Error::ConnectionCycle { source: 1, destination: 2 }) => {},
+            res => panic!("Unexpected result: {:?}", res),
+        }
+        match builder.connect_oo(4, 0) {
+            Err(Error::UnknownObject(4)) => {},
+            res => panic!("Unexpected result: {:?}", res),
+        }
+        builder.connect_op(3, 2, "Visibility").expect("Failed to connect");
+        match builder.connect_op(3, 2, "Lcl Translation") {
+            Err(Error::UnknownProperty { object: 2, .. }) => {},
+            res => panic!("Unexpected result: {:?}", res),
+        }
+        assert_eq!(builder.build().0.len(), 3);
+    }
+
+    #[test]
+    fn test_graph_queries() {
+        let conn = |source, destination, property: Option<&str>| Connection {
+            source,
+            destination,
+            property: property.map(str::to_owned),
+            source_is_prop: false,
+            destination_is_prop: property.is_some(),
+            extra_attributes: Vec::new(),
+        };
+        let connections = Connections(vec![
+            conn(1, 0, None),
+            conn(2, 1, None),
+            conn(3, 1, Some("Lcl Translation")),
+            conn(4, 1, Some("Visibility")),
+            conn(5, 3, Some("d|X")),
+        ]);
+        let graph = connections.graph();
+        let sources = |iter: &mut dyn Iterator<Item = &Connection>| {
+            iter.map(|c| c.source).collect::<Vec<_>>()
+        };
+
+        assert_eq!(sources(&mut connections.children_of(1)), vec![2, 3, 4]);
+        assert_eq!(sources(&mut graph.children_of(1)), vec![2, 3, 4]);
+        assert_eq!(graph.child_objects(1).collect::<Vec<_>>(), vec![2]);
+        assert_eq!(graph.parent_objects(2).collect::<Vec<_>>(), vec![1]);
+        assert_eq!(graph.parents_of(5).map(|c| c.destination).collect::<Vec<_>>(), vec![3]);
+        assert_eq!(sources(&mut connections.property_connections(1, "Visibility")), vec![4]);
+        assert_eq!(sources(&mut graph.property_connections(1, "Visibility")), vec![4]);
+        assert_eq!(graph.property_connections(1, "d|X").count(), 0);
+        assert_eq!(graph.children_of(42).count(), 0);
+    }
+
+    #[test]
+    fn test_extra_attributes() {
+        use std::io::Cursor;
+        use crate::OwnedAttribute;
+        use crate::fbx7400::Fbx7400Document;
+        use pull_parser::RootParser;
+        use pull_parser::ascii::AsciiDocument;
+
+        let source = "; FBX 7.4.0 project file\n\
+                      Connections:  {\n\tC: \"OO\",1,0\n\tC: \"OP\",2,1, \"Visibility\", 7\n}\n";
+        let bytes = AsciiDocument::parse(source).unwrap().to_binary().unwrap();
+        let doc = Fbx7400Document::load(RootParser::new(Cursor::new(bytes))).unwrap();
+        let connections = doc.connections.unwrap().0;
+        assert!(connections[0].extra_attributes.is_empty());
+        assert_eq!(connections[1].property.as_deref(), Some("Visibility"));
+        assert_eq!(connections[1].extra_attributes, vec![OwnedAttribute::I64(7)]);
+    }
+
+    #[test]
+    fn test_prune_dangling() {
+        let conn = |source, destination| Connection {
+            source,
+            destination,
+            property: None,
+            source_is_prop: false,
+            destination_is_prop: false,
+            extra_attributes: Vec::new(),
+        };
+        let mut connections = Connections(vec![conn(1, 0), conn(2, 9), conn(8, 1), conn(2, 1)]);
+        let dangling = connections.prune_dangling(|id| id == 1 || id == 2);
+        let summary = dangling
+            .iter()
+            .map(|d: &DanglingConnection| (d.index, d.missing_source, d.missing_destination))
+            .collect::<Vec<_>>();
+        assert_eq!(summary, vec![(1, false, true), (2, true, false)]);
+        assert_eq!(connections.0, vec![conn(1, 0), conn(2, 1)]);
+    }
+}
@@ -1,13 +1,16 @@
 //! `Definitions` node and its children.
 
 use fnv::FnvHashMap;
-use parser::binary::{Parser, ParserSource, Attributes};
-use loader::binary::simple::{Result, Error};
-use loader::binary::simple::fbx7400::{Properties70, PropertyMap, PropertyValue};
+use pull_parser::{Parser, ParserSource, Attributes};
+use crate::{Result, Error};
+use crate::fbx7400::{Properties70, PropertyMap, PropertyValue};
+use crate::fbx7400::node_versions::DefinitionsVersion;
+use approx::ApproxEq;
+use memory::MemoryUsage;
 
 
 /// `Definitions` node.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Default, Debug, Clone, PartialEq)]
 pub struct Definitions {
     /// Version of the node.
     pub version: i32,
@@ -44,13 +47,22 @@ impl Definitions {
                 },
             }
         }
+        let version = ensure_node_exists!(version, "Definitions", "Version");
+        if !DefinitionsVersion::from_raw(version).is_known() {
+            warn!("Unknown `Definitions` version: {}", version);
+        }
         Ok(Definitions {
-            version: ensure_node_exists!(version, "Definitions", "Version"),
+            version,
             count: ensure_node_exists!(count, "Definitions", "Count"),
-            object_types: object_types,
+            object_types,
         })
     }
 
+    /// Returns the version of the node.
+    pub fn known_version(&self) -> DefinitionsVersion {
+        DefinitionsVersion::from_raw(self.version)
+    }
+
     /// Returns reference to `Properties70` object of the specified node.
     pub fn get_properties70(&self, object_type: &str, node_type: &str) -> Option<&Properties70> {
         self.object_types
@@ -127,7 +139,7 @@ impl ObjectType {
         Ok(ObjectType {
             object_type: attrs,
             count: ensure_node_exists!(count, "ObjectType", "Count"),
-            property_template: property_template,
+            property_template,
         })
     }
 }
@@ -160,3 +172,35 @@ where
         "Properties70"
     ))
 }
+
+
+impl MemoryUsage for Definitions {
+    fn heap_usage(&self) -> usize {
+        self.object_types.heap_usage()
+    }
+}
+
+
+impl MemoryUsage for ObjectType {
+    fn heap_usage(&self) -> usize {
+        self.object_type.heap_usage() + self.property_template.heap_usage()
+    }
+}
+
+
+impl ApproxEq for Definitions {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.version == other.version &&
+            self.count == other.count &&
+            self.object_types.approx_eq(&other.object_types, epsilon)
+    }
+}
+
+
+impl ApproxEq for ObjectType {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.object_type == other.object_type &&
+            self.count == other.count &&
+            self.property_template.approx_eq(&other.property_template, epsilon)
+    }
+}
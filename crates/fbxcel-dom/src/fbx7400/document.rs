@@ -0,0 +1,476 @@
+//! Lenient loader of FBX 7.4 documents.
+
+use fnv::FnvHashSet;
+use pull_parser::{Parser, ParserSource, FbxFooter, Event};
+use approx::ApproxEq;
+use crate::{Result, Error};
+use crate::fbx7400::{FbxHeaderExtension, GlobalSettings, Definitions};
+use crate::fbx7400::{Connections, GenericObject, ObjectProperties, Takes};
+use crate::fbx7400::{FileId, CreationTime, Creator, Documents, References};
+use crate::fbx7400::{LoadPreset, NodeType};
+use crate::fbx7400::{DanglingConnection, DuplicateIdPolicy, ObjectMap};
+
+
+/// Callbacks invoked while loading `Fbx7400Document`.
+///
+/// Consumers can start processing objects (e.g. uploading geometries to GPU) while the rest of
+/// the file is still being parsed.
+/// All methods do nothing by default.
+pub trait DocumentListener {
+    /// Called when an object has been loaded.
+    ///
+    /// This is called for every object, before the class-specific callbacks.
+    fn on_object(&mut self, _obj: &GenericObject) {}
+
+    /// Called when a `Geometry` object has been loaded.
+    fn on_geometry(&mut self, _obj: &GenericObject) {}
+
+    /// Called when a `Model` object has been loaded.
+    fn on_model(&mut self, _obj: &GenericObject) {}
+
+    /// Called when a toplevel section has been loaded.
+    fn on_section(&mut self, _name: &str) {}
+}
+
+impl DocumentListener for () {}
+
+
+/// FBX 7.4 or later document, with every toplevel section optional.
+///
+/// Unlike `Fbx7400`, this can load files exported with unusual subsets of sections (such as
+/// animation-only files without `Objects`).
+/// Objects are loaded as `GenericObject`s.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fbx7400Document {
+    /// FBX version.
+    pub version: u32,
+    /// `FBXHeaderExtension`.
+    pub fbx_header_extension: Option<FbxHeaderExtension>,
+    /// `FileId`.
+    pub file_id: Option<FileId>,
+    /// `CreationTime`.
+    pub creation_time: Option<CreationTime>,
+    /// `Creator`.
+    pub creator: Option<Creator>,
+    /// `GlobalSettings`.
+    pub global_settings: Option<GlobalSettings>,
+    /// `Documents`.
+    pub documents: Option<Documents>,
+    /// `References`.
+    pub references: Option<References>,
+    /// `Definitions`.
+    pub definitions: Option<Definitions>,
+    /// `Objects`.
+    pub objects: Option<Vec<GenericObject>>,
+    /// `Connections`.
+    pub connections: Option<Connections>,
+    /// `Takes`.
+    pub takes: Option<Takes>,
+    /// FBX footer.
+    pub footer: Option<FbxFooter>,
+}
+
+impl Fbx7400Document {
+    /// Loads the document from the given parser.
+    ///
+    /// Unknown toplevel nodes are skipped with warnings, and the first one is used if a section
+    /// appears multiple times.
+    ///
+    /// Returns `Err(Error::UnexpectedEvent(_))` if the parser has already emitted some event (i.e.
+    /// if the given parser didn't return the `StartFbx` first).
+    pub fn load<R, P>(parser: P) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        Self::load_with_listener(parser, &mut ())
+    }
+
+    /// Loads the document from the given parser, invoking the listener callbacks as each object
+    /// and section finishes loading.
+    ///
+    /// Returns `Err(Error::UnexpectedEvent(_))` if the parser has already emitted some event (i.e.
+    /// if the given parser didn't return the `StartFbx` first).
+    pub fn load_with_listener<R, P, L>(parser: P, listener: &mut L) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+        L: DocumentListener,
+    {
+        Self::load_with_options(parser, LoadPreset::Full, listener)
+    }
+
+    /// Loads the document from the given parser, loading only the objects accepted by the preset.
+    ///
+    /// With `LoadPreset::MetadataOnly`, `objects` is always `None` and `Objects` and `Takes` nodes
+    /// are skipped.
+    ///
+    /// Returns `Err(Error::UnexpectedEvent(_))` if the parser has already emitted some event (i.e.
+    /// if the given parser didn't return the `StartFbx` first).
+    pub fn load_with_preset<R, P>(parser: P, preset: LoadPreset) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        Self::load_with_options(parser, preset, &mut ())
+    }
+
+    /// Loads the document from the given parser with the preset and the listener.
+    fn load_with_options<R, P, L>(
+        mut parser: P,
+        preset: LoadPreset,
+        listener: &mut L,
+    ) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+        L: DocumentListener,
+    {
+        let version = match parser.next_event()? {
+            Event::StartFbx(header) => header.version,
+            ev => return Err(Error::UnexpectedEvent(format!("{:?}", ev))),
+        };
+        info!("FBX version: {}, loading as FBX 7400 document", version);
+
+        let mut doc = Fbx7400Document {
+            version,
+            fbx_header_extension: None,
+            file_id: None,
+            creation_time: None,
+            creator: None,
+            global_settings: None,
+            documents: None,
+            references: None,
+            definitions: None,
+            objects: None,
+            connections: None,
+            takes: None,
+            footer: None,
+        };
+        loop {
+            let (node_type, name) = match parser.next_event()? {
+                Event::StartFbx(_) |
+                Event::EndNode => unreachable!(),
+                Event::EndFbx(f) => {
+                    doc.footer = f.ok();
+                    break;
+                },
+                Event::StartNode(info) => {
+                    let name = info.name()?;
+                    match NodeType::load(name, info.attributes) {
+                        Ok(v) => (v, name.to_owned()),
+                        Err(Error::UnexpectedNode(name)) => {
+                            warn!("Unknown toplevel node `{}`, ignoring", name);
+                            parser.skip_current_node()?;
+                            continue;
+                        },
+                        Err(err) => return Err(err),
+                    }
+                },
+            };
+            let is_skipped = match node_type {
+                NodeType::Objects | NodeType::Takes => preset == LoadPreset::MetadataOnly,
+                _ => false,
+            };
+            if is_skipped {
+                parser.skip_current_node()?;
+                continue;
+            }
+            if doc.has_section(&node_type) {
+                warn!("Multiple `{:?}` nodes found, ignoring", node_type);
+                parser.skip_current_node()?;
+                continue;
+            }
+            match node_type {
+                NodeType::FbxHeaderExtension => {
+                    doc.fbx_header_extension =
+                        Some(FbxHeaderExtension::load(parser.subtree_parser())?);
+                },
+                NodeType::FileId(attrs) => {
+                    doc.file_id = Some(FileId::load(parser.subtree_parser(), attrs)?);
+                },
+                NodeType::CreationTime(attrs) => {
+                    doc.creation_time = Some(CreationTime::load(parser.subtree_parser(), attrs)?);
+                },
+                NodeType::Creator(attrs) => {
+                    doc.creator = Some(Creator::load(parser.subtree_parser(), attrs)?);
+                },
+                NodeType::GlobalSettings => {
+                    doc.global_settings = Some(GlobalSettings::load(parser.subtree_parser())?);
+                },
+                NodeType::Documents => {
+                    doc.documents = Some(Documents::load(parser.subtree_parser())?);
+                },
+                NodeType::References => {
+                    doc.references = Some(References::load(parser.subtree_parser())?);
+                },
+                NodeType::Definitions => {
+                    doc.definitions = Some(Definitions::load(parser.subtree_parser())?);
+                },
+                NodeType::Objects => {
+                    doc.objects =
+                        Some(load_generic_objects(parser.subtree_parser(), preset, listener)?);
+                },
+                NodeType::Connections => {
+                    doc.connections = Some(Connections::load(parser.subtree_parser())?);
+                },
+                NodeType::Takes => {
+                    doc.takes = Some(Takes::load(parser.subtree_parser())?);
+                },
+            }
+            listener.on_section(&name);
+        }
+        Ok(doc)
+    }
+
+    /// Takes the objects as an `ObjectMap`, handling objects with duplicate IDs by the policy.
+    ///
+    /// `objects` becomes `None` even if it fails.
+    /// Returns `Ok(None)` if the document has no objects.
+    pub fn take_object_map(&mut self, policy: DuplicateIdPolicy) -> Result<Option<ObjectMap>> {
+        match self.objects.take() {
+            Some(objects) => ObjectMap::new(objects, policy).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the connections referring to objects which are not in `objects`.
+    ///
+    /// Note that objects skipped by `LoadPreset` are also regarded as missing.
+    /// Returns an empty vector if `objects` or `connections` is not loaded.
+    pub fn dangling_connections(&self) -> Vec<DanglingConnection> {
+        match (self.objects.as_ref(), self.connections.as_ref()) {
+            (Some(objects), Some(connections)) => {
+                let ids = objects.iter().map(|obj| obj.props.id).collect::<FnvHashSet<_>>();
+                connections.dangling(|id| ids.contains(&id))
+            },
+            _ => Vec::new(),
+        }
+    }
+
+    /// Removes the connections referring to objects which are not in `objects`, and returns them.
+    ///
+    /// See `dangling_connections()` for details.
+    pub fn prune_dangling_connections(&mut self) -> Vec<DanglingConnection> {
+        match (self.objects.as_ref(), self.connections.as_mut()) {
+            (Some(objects), Some(connections)) => {
+                let ids = objects.iter().map(|obj| obj.props.id).collect::<FnvHashSet<_>>();
+                connections.prune_dangling(|id| ids.contains(&id))
+            },
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns whether the section of the given type is already loaded.
+    fn has_section(&self, node_type: &NodeType) -> bool {
+        match *node_type {
+            NodeType::FbxHeaderExtension => self.fbx_header_extension.is_some(),
+            NodeType::FileId(_) => self.file_id.is_some(),
+            NodeType::CreationTime(_) => self.creation_time.is_some(),
+            NodeType::Creator(_) => self.creator.is_some(),
+            NodeType::GlobalSettings => self.global_settings.is_some(),
+            NodeType::Documents => self.documents.is_some(),
+            NodeType::References => self.references.is_some(),
+            NodeType::Definitions => self.definitions.is_some(),
+            NodeType::Objects => self.objects.is_some(),
+            NodeType::Connections => self.connections.is_some(),
+            NodeType::Takes => self.takes.is_some(),
+        }
+    }
+}
+
+
+/// Loads children of `Objects` node as generic objects.
+fn load_generic_objects<R, P, L>(
+    mut parser: P,
+    preset: LoadPreset,
+    listener: &mut L,
+) -> Result<Vec<GenericObject>>
+where
+    R: ParserSource,
+    P: Parser<R>,
+    L: DocumentListener,
+{
+    let mut objects = Vec::new();
+    loop {
+        let props = try_get_node_attrs!(parser, ObjectProperties::load);
+        if !preset.accepts(&props) {
+            parser.skip_current_node()?;
+            continue;
+        }
+        let obj = GenericObject::load(parser.subtree_parser(), props)?;
+        listener.on_object(&obj);
+        match obj.props.class.as_str() {
+            "Geometry" => listener.on_geometry(&obj),
+            "Model" => listener.on_model(&obj),
+            _ => {},
+        }
+        objects.push(obj);
+    }
+    Ok(objects)
+}
+
+
+impl ApproxEq for Fbx7400Document {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.version == other.version &&
+            self.fbx_header_extension.approx_eq(&other.fbx_header_extension, epsilon) &&
+            self.file_id == other.file_id &&
+            self.creation_time == other.creation_time &&
+            self.creator == other.creator &&
+            self.global_settings.approx_eq(&other.global_settings, epsilon) &&
+            self.documents.approx_eq(&other.documents, epsilon) &&
+            self.references.approx_eq(&other.references, epsilon) &&
+            self.definitions.approx_eq(&other.definitions, epsilon) &&
+            self.objects.approx_eq(&other.objects, epsilon) &&
+            self.connections.approx_eq(&other.connections, epsilon) &&
+            self.takes == other.takes &&
+            self.footer == other.footer
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use pull_parser::RootParser;
+    use pull_parser::ascii::AsciiDocument;
+    use super::*;
+
+    fn load(source: &str) -> Result<Fbx7400Document> {
+        let bytes = AsciiDocument::parse(source).unwrap().to_binary().unwrap();
+        Fbx7400Document::load(RootParser::new(Cursor::new(bytes)))
+    }
+
+    #[test]
+    fn load_without_objects_and_takes() {
+        let source = "; FBX 7.4.0 project file\n\
+                      Creator: \"exporter\"\n\
+                      UnknownSection: 1 {\n\tChild: 2\n}\n\
+                      Connections:  {\n\tC: \"OO\",1,0\n}\n\
+                      Creator: \"ignored\"\n";
+        let doc = load(source).unwrap();
+        assert_eq!(doc.version, 7400);
+        assert_eq!(doc.creator, Some(Creator("exporter".to_owned())));
+        assert_eq!(doc.objects, None);
+        assert_eq!(doc.takes, None);
+        assert_eq!(doc.global_settings, None);
+        assert_eq!(doc.connections.as_ref().and_then(|c| c.parent_object(1)), Some(0));
+        // Connections to objects are dangling only when objects are loaded.
+        assert!(doc.dangling_connections().is_empty());
+    }
+
+    #[test]
+    fn load_takes_only() {
+        let source = "; FBX 7.4.0 project file\n\
+                      Takes:  {\n\tCurrent: \"Take 001\"\n}\n";
+        let doc = load(source).unwrap();
+        assert_eq!(doc.takes.as_ref().map(|takes| takes.current.as_str()), Some("Take 001"));
+        assert_eq!(doc.objects, None);
+        assert_eq!(doc.connections, None);
+        assert!(doc.footer.is_some());
+    }
+
+    /// Listener recording the callbacks.
+    #[derive(Default)]
+    struct Recorder(Vec<String>);
+
+    impl DocumentListener for Recorder {
+        fn on_object(&mut self, obj: &GenericObject) {
+            self.0.push(format!("object {}", obj.props.id));
+        }
+
+        fn on_geometry(&mut self, obj: &GenericObject) {
+            self.0.push(format!("geometry {}", obj.props.id));
+        }
+
+        fn on_model(&mut self, obj: &GenericObject) {
+            self.0.push(format!("model {}", obj.props.id));
+        }
+
+        fn on_section(&mut self, name: &str) {
+            self.0.push(format!("section {}", name));
+        }
+    }
+
+    #[test]
+    fn listener_callbacks() {
+        let source = "; FBX 7.4.0 project file\n\
+                      Creator: \"exporter\"\n\
+                      Objects:  {\n\
+                      \tGeometry: 1, \"Geometry::Cube\", \"Mesh\" {\n\t}\n\
+                      \tModel: 2, \"Model::Cube\", \"Mesh\" {\n\t}\n\
+                      \tMaterial: 3, \"Material::Red\", \"\" {\n\t}\n\
+                      }\n\
+                      Connections:  {\n\tC: \"OO\",1,2\n}\n";
+        let bytes = AsciiDocument::parse(source).unwrap().to_binary().unwrap();
+        let mut recorder = Recorder::default();
+        let parser = RootParser::new(Cursor::new(bytes));
+        let doc = Fbx7400Document::load_with_listener(parser, &mut recorder).unwrap();
+        assert_eq!(doc.objects.map(|objects| objects.len()), Some(3));
+        assert_eq!(
+            recorder.0,
+            vec![
+                "section Creator",
+                "object 1",
+                "geometry 1",
+                "object 2",
+                "model 2",
+                "object 3",
+                "section Objects",
+                "section Connections",
+            ]
+        );
+    }
+
+    #[test]
+    fn load_presets() {
+        let source = "; FBX 7.4.0 project file\n\
+                      Creator: \"exporter\"\n\
+                      Objects:  {\n\
+                      \tModel: 1, \"Model::Cube\", \"Mesh\" {\n\t}\n\
+                      \tModel: 2, \"Model::Bone\", \"LimbNode\" {\n\t}\n\
+                      \tModel: 3, \"Model::Group\", \"Null\" {\n\t}\n\
+                      \tNodeAttribute: 4, \"NodeAttribute::Bone\", \"LimbNode\" {\n\t}\n\
+                      \tNodeAttribute: 5, \"NodeAttribute::Lamp\", \"Light\" {\n\t}\n\
+                      \tGeometry: 6, \"Geometry::Cube\", \"Mesh\" {\n\t}\n\
+                      \tMaterial: 7, \"Material::Red\", \"\" {\n\t}\n\
+                      \tTexture: 8, \"Texture::Red\", \"\" {\n\t}\n\
+                      \tVideo: 9, \"Video::Red\", \"Clip\" {\n\t}\n\
+                      \tDeformer: 10, \"Deformer::Skin\", \"Skin\" {\n\t}\n\
+                      \tPose: 11, \"Pose::BindPose\", \"BindPose\" {\n\t}\n\
+                      \tAnimationStack: 12, \"AnimationStack::Take\", \"\" {\n\t}\n\
+                      \tAnimationLayer: 13, \"AnimationLayer::Base\", \"\" {\n\t}\n\
+                      \tAnimationCurveNode: 14, \"AnimationCurveNode::T\", \"\" {\n\t}\n\
+                      \tAnimationCurve: 15, \"AnimationCurve::\", \"\" {\n\t}\n\
+                      \tCollectionExclusive: 16, \"DisplayLayer::Layer\", \"DisplayLayer\" {\n\t}\n\
+                      }\n\
+                      Takes:  {\n\tCurrent: \"Take\"\n}\n";
+        let bytes = AsciiDocument::parse(source).unwrap().to_binary().unwrap();
+        let presets: &[(LoadPreset, Option<&[i64]>)] = &[
+            (LoadPreset::Full, Some(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16])),
+            (LoadPreset::AnimationOnly, Some(&[1, 2, 3, 12, 13, 14, 15])),
+            (LoadPreset::GeometryOnly, Some(&[1, 2, 3, 6, 7, 8, 9])),
+            (LoadPreset::SkeletonOnly, Some(&[2, 3, 4, 10, 11])),
+            (LoadPreset::MetadataOnly, None),
+        ];
+        for &(preset, expected) in presets {
+            let docs = vec![
+                Fbx7400Document::load_with_preset(RootParser::new(Cursor::new(&bytes)), preset),
+                Fbx7400Document::load_with_preset(
+                    RootParser::from_seekable(Cursor::new(&bytes)),
+                    preset,
+                ),
+            ];
+            for doc in docs {
+                let doc = doc.unwrap();
+                let ids = doc.objects
+                    .as_ref()
+                    .map(|objs| objs.iter().map(|obj| obj.props.id).collect::<Vec<_>>());
+                assert_eq!(ids.as_deref(), expected, "{:?}", preset);
+                assert_eq!(doc.creator, Some(Creator("exporter".to_owned())));
+                assert_eq!(doc.takes.is_some(), preset != LoadPreset::MetadataOnly);
+            }
+        }
+    }
+}
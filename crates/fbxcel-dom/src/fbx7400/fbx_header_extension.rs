@@ -1,12 +1,14 @@
 //! `Definitions` node and its children.
 
-use parser::binary::{Parser, ParserSource, Attributes};
-use loader::binary::simple::{Result, Error};
-use loader::binary::simple::fbx7400::Properties70;
+use pull_parser::{Parser, ParserSource, Attributes};
+use crate::{Result, Error};
+use crate::fbx7400::Properties70;
+use approx::ApproxEq;
+use memory::MemoryUsage;
 
 
 /// `FBXHeaderExtension` node.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Default, Debug, Clone, PartialEq)]
 pub struct FbxHeaderExtension {
     /// Version of the node.
     pub fbx_header_version: i32,
@@ -98,7 +100,7 @@ child_attr_loader! { FbxHeaderExtensionChildAttrs {
 
 
 /// Creation time stamp.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct CreationTimeStamp {
     /// Version.
     pub version: i32,
@@ -191,7 +193,7 @@ child_attr_loader! { CreationTimeStampChildAttrs {
 
 
 /// Scene info.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Default, Debug, Clone, PartialEq)]
 pub struct SceneInfo {
     /// Object name?
     pub name: String,
@@ -254,9 +256,9 @@ impl SceneInfo {
             }
         }
         Ok(SceneInfo {
-            name: name,
-            class: class,
-            subclass: subclass,
+            name,
+            class,
+            subclass,
             type_: ensure_node_exists!(type_, "SceneInfo", "Type"),
             version: ensure_node_exists!(version, "SceneInfo", "Version"),
             metadata: ensure_node_exists!(metadata, "SceneInfo", "MetaData"),
@@ -275,7 +277,7 @@ child_attr_loader! { SceneInfoChildAttrs {
 
 
 /// FBX metadata.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct MetaData {
     /// Version.
     pub version: i32,
@@ -365,3 +367,59 @@ fn separate_name_class(name_class: &str) -> Option<(&str, &str)> {
         (&name_class[0..sep_pos], &name_class[sep_pos + 2..])
     })
 }
+
+
+impl MemoryUsage for FbxHeaderExtension {
+    fn heap_usage(&self) -> usize {
+        self.creator.heap_usage() + self.scene_info.heap_usage()
+    }
+}
+
+
+impl MemoryUsage for SceneInfo {
+    fn heap_usage(&self) -> usize {
+        self.name.heap_usage() +
+            self.class.heap_usage() +
+            self.subclass.heap_usage() +
+            self.type_.heap_usage() +
+            self.metadata.heap_usage() +
+            self.properties.heap_usage()
+    }
+}
+
+
+impl MemoryUsage for MetaData {
+    fn heap_usage(&self) -> usize {
+        self.title.heap_usage() +
+            self.subject.heap_usage() +
+            self.author.heap_usage() +
+            self.keywords.heap_usage() +
+            self.revision.heap_usage() +
+            self.comment.heap_usage()
+    }
+}
+
+
+impl ApproxEq for FbxHeaderExtension {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.fbx_header_version == other.fbx_header_version &&
+            self.fbx_version == other.fbx_version &&
+            self.encryption_type == other.encryption_type &&
+            self.creation_timestamp == other.creation_timestamp &&
+            self.creator == other.creator &&
+            self.scene_info.approx_eq(&other.scene_info, epsilon)
+    }
+}
+
+
+impl ApproxEq for SceneInfo {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.name == other.name &&
+            self.class == other.class &&
+            self.subclass == other.subclass &&
+            self.type_ == other.type_ &&
+            self.version == other.version &&
+            self.metadata == other.metadata &&
+            self.properties.approx_eq(&other.properties, epsilon)
+    }
+}
@@ -0,0 +1,1320 @@
+//! Mesh geometry utilities.
+//!
+//! These work on `Geometry` objects loaded as `GenericObject`s, reading and rewriting their child
+//! nodes (such as `Vertices`, `PolygonVertexIndex`, and `LayerElement*`).
+//! `Mesh` is a read-only view of typed `objects::Geometry`.
+
+use std::ops::Range;
+use std::sync::Arc;
+use fnv::{FnvHashMap, FnvHashSet};
+use crate::{GenericNode, OwnedAttribute};
+use crate::fbx7400::GenericObject;
+use crate::fbx7400::node_versions::LayerElementVersion;
+use crate::fbx7400::objects::Geometry;
+use crate::utils::map_slice;
+
+
+/// Version of `LayerElementNormal` nodes created by this module.
+pub const LAYER_ELEMENT_NORMAL_VERSION: i32 = 102;
+
+
+/// Axis of 3D space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Axis {
+    /// X axis.
+    X,
+    /// Y axis.
+    Y,
+    /// Z axis.
+    Z,
+}
+
+impl Axis {
+    /// Returns the index of the vector component (`0` for `X`, `1` for `Y`, `2` for `Z`).
+    pub fn index(self) -> usize {
+        match self {
+            Axis::X => 0,
+            Axis::Y => 1,
+            Axis::Z => 2,
+        }
+    }
+}
+
+
+/// Mapping mode of layer elements (`MappingInformationType`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MappingMode {
+    /// `ByPolygonVertex`.
+    ByPolygonVertex,
+    /// `ByVertice` (or `ByVertex`).
+    ByVertex,
+    /// `ByPolygon`.
+    ByPolygon,
+    /// `ByEdge`.
+    ByEdge,
+    /// `AllSame`.
+    AllSame,
+}
+
+impl MappingMode {
+    /// Parses the value of `MappingInformationType` node.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "ByPolygonVertex" => Some(MappingMode::ByPolygonVertex),
+            "ByVertice" | "ByVertex" => Some(MappingMode::ByVertex),
+            "ByPolygon" => Some(MappingMode::ByPolygon),
+            "ByEdge" => Some(MappingMode::ByEdge),
+            "AllSame" => Some(MappingMode::AllSame),
+            _ => None,
+        }
+    }
+
+    /// Returns the mapping mode of the given layer element node.
+    pub fn of_layer_element(node: &GenericNode) -> Option<Self> {
+        child_string(&node.children, "MappingInformationType").and_then(Self::parse)
+    }
+}
+
+
+/// Reference mode of layer elements (`ReferenceInformationType`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReferenceMode {
+    /// `Direct`.
+    Direct,
+    /// `IndexToDirect` (or `Index`).
+    IndexToDirect,
+}
+
+impl ReferenceMode {
+    /// Parses the value of `ReferenceInformationType` node.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "Direct" => Some(ReferenceMode::Direct),
+            "IndexToDirect" | "Index" => Some(ReferenceMode::IndexToDirect),
+            _ => None,
+        }
+    }
+
+    /// Returns the reference mode of the given layer element node.
+    pub fn of_layer_element(node: &GenericNode) -> Option<Self> {
+        child_string(&node.children, "ReferenceInformationType").and_then(Self::parse)
+    }
+}
+
+
+/// Decodes a raw value of `PolygonVertexIndex`.
+///
+/// The last vertex of each polygon is stored as bitwise negation of the index.
+pub fn vertex_index(raw: i32) -> usize {
+    if raw < 0 {
+        !raw as usize
+    } else {
+        raw as usize
+    }
+}
+
+
+/// Returns the ranges of polygons in the `PolygonVertexIndex` array.
+///
+/// A trailing polygon without the end mark is also returned.
+pub fn polygon_ranges(indices: &[i32]) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    for (i, &raw) in indices.iter().enumerate() {
+        if raw < 0 {
+            ranges.push(start..i + 1);
+            start = i + 1;
+        }
+    }
+    if start < indices.len() {
+        ranges.push(start..indices.len());
+    }
+    ranges
+}
+
+
+/// Returns the `PolygonVertexIndex` of the mesh.
+pub fn polygon_vertex_indices(obj: &GenericObject) -> Option<&[i32]> {
+    match child(&obj.nodes, "PolygonVertexIndex")?.attributes.first() {
+        Some(OwnedAttribute::ArrI32(v)) => Some(v),
+        _ => None,
+    }
+}
+
+
+/// Returns the control points (`Vertices`) of the mesh.
+pub fn vertices(obj: &GenericObject) -> Option<Vec<[f64; 3]>> {
+    match child(&obj.nodes, "Vertices")?.attributes.first() {
+        Some(OwnedAttribute::ArrF64(v)) => {
+            Some(v.chunks_exact(3).map(|c| [c[0], c[1], c[2]]).collect())
+        },
+        Some(OwnedAttribute::ArrF32(v)) => {
+            let v = map_slice(v, |&v| f64::from(v));
+            Some(v.chunks_exact(3).map(|c| [c[0], c[1], c[2]]).collect())
+        },
+        _ => None,
+    }
+}
+
+
+/// Returns the layer element nodes (such as `LayerElementNormal`) of the mesh.
+pub fn layer_elements(obj: &GenericObject) -> impl Iterator<Item = &GenericNode> {
+    obj.nodes.iter().filter(|node| node.name.starts_with(b"LayerElement"))
+}
+
+
+/// Layer elements which split control points into several vertices on import, with the names of
+/// the data and index nodes and the number of values per element.
+const SPLITTING_LAYER_ELEMENTS: &[(&str, &str, &str, usize)] = &[
+    ("LayerElementNormal", "Normals", "NormalsIndex", 3),
+    ("LayerElementBinormal", "Binormals", "BinormalsIndex", 3),
+    ("LayerElementTangent", "Tangents", "TangentsIndex", 3),
+    ("LayerElementUV", "UV", "UVIndex", 2),
+    ("LayerElementColor", "Colors", "ColorIndex", 4),
+];
+
+
+/// Returns the number of vertices created by engines on import.
+///
+/// Polygon vertices (from `PolygonVertexIndex`) are merged if they have the same control point
+/// and the same values of all normal, binormal, tangent, UV, and color layer elements.
+/// Returns `None` if the mesh doesn't have polygons.
+pub fn num_split_vertices(obj: &GenericObject) -> Option<usize> {
+    let indices = polygon_vertex_indices(obj)?;
+    let layers: Vec<LayerData> = layer_elements(obj)
+        .filter_map(|elem| {
+            let &(_, data, index, stride) = SPLITTING_LAYER_ELEMENTS
+                .iter()
+                .find(|&&(name, ..)| elem.name == name.as_bytes())?;
+            LayerData::new(elem, data, index, stride)
+        })
+        .collect();
+    let mut vertices = FnvHashSet::<Vec<u64>>::default();
+    for (polygon, range) in polygon_ranges(indices).into_iter().enumerate() {
+        for pv in range {
+            let pos = MappedPosition {
+                polygon_vertex: pv,
+                control_point: vertex_index(indices[pv]),
+                polygon,
+            };
+            let mut key = vec![pos.control_point as u64];
+            for layer in &layers {
+                match layer.index(&pos) {
+                    Some(i) => key.extend(layer.values(i).iter().map(|v| v.to_bits())),
+                    None => key.extend(::std::iter::repeat_n(u64::MAX, layer.stride)),
+                }
+            }
+            vertices.insert(key);
+        }
+    }
+    Some(vertices.len())
+}
+
+
+/// Mirrors the mesh across the plane perpendicular to the given axis.
+///
+/// Vertices and directions (normals, binormals, and tangents) are mirrored, and the winding of
+/// polygons is reversed (with per-polygon-vertex layer elements and edges reordered) so that the
+/// faces are kept front-facing.
+pub fn mirror_mesh(obj: &mut GenericObject, axis: Axis) {
+    let axis = axis.index();
+    // Blend shapes (`Shape` geometries) have `Normals` directly.
+    for node in &mut obj.nodes {
+        if node.name != b"Vertices" && node.name != b"Normals" {
+            continue;
+        }
+        if let Some(attr) = first_attr_mut(node) {
+            negate_component(attr, axis);
+        }
+    }
+    for elem in obj.nodes.iter_mut().filter(|node| node.name.starts_with(b"LayerElement")) {
+        for node in &mut elem.children {
+            let is_direction =
+                node.name == b"Normals" || node.name == b"Binormals" || node.name == b"Tangents";
+            if let Some(attr) = first_attr_mut(node).filter(|_| is_direction) {
+                negate_component(attr, axis);
+            }
+        }
+    }
+
+    let (order, ranges) = match polygon_vertex_indices(obj) {
+        Some(indices) => {
+            let ranges = polygon_ranges(indices);
+            (reversed_winding_order(&ranges, indices.len()), ranges)
+        },
+        None => return,
+    };
+    if let Some(node) = child_mut(&mut obj.nodes, "PolygonVertexIndex") {
+        if let Some(OwnedAttribute::ArrI32(indices)) = node.attributes.first_mut() {
+            let old = indices.to_vec();
+            for range in &ranges {
+                for pos in range.clone() {
+                    let index = vertex_index(old[order[pos]]) as i32;
+                    indices[pos] = if pos + 1 == range.end { !index } else { index };
+                }
+            }
+        }
+    }
+    if let Some(node) = child_mut(&mut obj.nodes, "Edges") {
+        if let Some(OwnedAttribute::ArrI32(edges)) = node.attributes.first_mut() {
+            let mut polygon_of = vec![0..0; order.len()];
+            for range in &ranges {
+                for pos in range.clone() {
+                    polygon_of[pos] = range.clone();
+                }
+            }
+            for edge in edges.iter_mut() {
+                let pos = *edge as usize;
+                if pos >= polygon_of.len() {
+                    continue;
+                }
+                // Edge `v[k] -> v[k+1]` becomes `v'[j] -> v'[j+1]`, where `v'[j] == v[k+1]`.
+                let range = &polygon_of[pos];
+                let len = range.end - range.start;
+                let k = pos - range.start;
+                *edge = (range.start + (len - (k + 1) % len) % len) as i32;
+            }
+        }
+    }
+    for elem in obj.nodes.iter_mut().filter(|node| node.name.starts_with(b"LayerElement")) {
+        if MappingMode::of_layer_element(elem) != Some(MappingMode::ByPolygonVertex) {
+            continue;
+        }
+        let by_index = ReferenceMode::of_layer_element(elem) == Some(ReferenceMode::IndexToDirect);
+        for node in &mut elem.children {
+            if by_index && !node.name.ends_with(b"Index") {
+                continue;
+            }
+            if let Some(attr) = node.attributes.first_mut() {
+                permute(attr, &order);
+            }
+        }
+    }
+}
+
+
+/// Computes smoothed normals of the mesh and returns them as a `LayerElementNormal` node.
+///
+/// Normals are mapped by polygon vertex, and the normals of adjacent polygons are averaged if the
+/// angle between them is less than or equal to `smoothing_angle` (in degrees).
+/// Use `0.0` for faceted normals and `180.0` for fully smoothed normals.
+///
+/// Returns `None` if the mesh doesn't have vertices or polygons.
+pub fn recompute_normals(obj: &GenericObject, smoothing_angle: f64) -> Option<GenericNode> {
+    let points = vertices(obj)?;
+    let indices = polygon_vertex_indices(obj)?;
+    let ranges = polygon_ranges(indices);
+    if indices.iter().any(|&raw| vertex_index(raw) >= points.len()) {
+        return None;
+    }
+
+    let face_normals: Vec<[f64; 3]> = ranges
+        .iter()
+        .map(|range| polygon_normal(&points, &indices[range.clone()]))
+        .collect();
+    // Polygons around each control point.
+    let mut adjacent = vec![Vec::new(); points.len()];
+    for (polygon, range) in ranges.iter().enumerate() {
+        for &raw in &indices[range.clone()] {
+            let list = &mut adjacent[vertex_index(raw)];
+            if list.last() != Some(&polygon) {
+                list.push(polygon);
+            }
+        }
+    }
+
+    let min_cos = smoothing_angle.to_radians().cos();
+    let unit_face_normals: Vec<[f64; 3]> = face_normals.iter().map(|&n| normalize(n)).collect();
+    let mut normals = Vec::with_capacity(indices.len() * 3);
+    for (polygon, range) in ranges.iter().enumerate() {
+        let own = unit_face_normals[polygon];
+        for &raw in &indices[range.clone()] {
+            let mut sum = [0.0; 3];
+            for &other in &adjacent[vertex_index(raw)] {
+                if other == polygon || dot(own, unit_face_normals[other]) >= min_cos {
+                    let n = face_normals[other];
+                    sum = [sum[0] + n[0], sum[1] + n[1], sum[2] + n[2]];
+                }
+            }
+            let normal = if sum == [0.0; 3] { own } else { normalize(sum) };
+            normals.extend_from_slice(&normal);
+        }
+    }
+
+    let string_node = |name: &str, value: &str| GenericNode {
+        name: name.into(),
+        attributes: vec![OwnedAttribute::String(Ok(value.to_owned()))],
+        children: Vec::new(),
+    };
+    Some(GenericNode {
+        name: b"LayerElementNormal".to_vec(),
+        attributes: vec![OwnedAttribute::I32(0)],
+        children: vec![
+            GenericNode {
+                name: b"Version".to_vec(),
+                attributes: vec![OwnedAttribute::I32(LAYER_ELEMENT_NORMAL_VERSION)],
+                children: Vec::new(),
+            },
+            string_node("Name", ""),
+            string_node("MappingInformationType", "ByPolygonVertex"),
+            string_node("ReferenceInformationType", "Direct"),
+            GenericNode {
+                name: b"Normals".to_vec(),
+                attributes: vec![OwnedAttribute::ArrF64(normals.into_boxed_slice())],
+                children: Vec::new(),
+            },
+        ],
+    })
+}
+
+
+/// Replaces the normals of the mesh with the recomputed ones (see `recompute_normals()`).
+///
+/// Existing `LayerElementNormal` nodes are removed, and the new one is inserted as the first
+/// normal layer element, which is referred from the first `Layer` node.
+///
+/// Returns `false` if the normals cannot be computed (and the mesh is unchanged).
+pub fn replace_normals(obj: &mut GenericObject, smoothing_angle: f64) -> bool {
+    let normals = match recompute_normals(obj, smoothing_angle) {
+        Some(v) => v,
+        None => return false,
+    };
+    let pos = obj.nodes
+        .iter()
+        .position(|node| node.name.starts_with(b"LayerElement") || node.name == b"Layer")
+        .unwrap_or(obj.nodes.len());
+    obj.nodes.retain(|node| node.name != b"LayerElementNormal");
+    obj.nodes.insert(pos.min(obj.nodes.len()), normals);
+
+    if let Some(layer) = obj.nodes.iter_mut().find(|node| node.name == b"Layer") {
+        let refers_normal = layer.children.iter().any(|elem| {
+            child_string(&elem.children, "Type") == Some("LayerElementNormal")
+        });
+        if !refers_normal {
+            layer.children.push(GenericNode {
+                name: b"LayerElement".to_vec(),
+                attributes: Vec::new(),
+                children: vec![
+                    GenericNode {
+                        name: b"Type".to_vec(),
+                        attributes: vec![
+                            OwnedAttribute::String(Ok("LayerElementNormal".to_owned())),
+                        ],
+                        children: Vec::new(),
+                    },
+                    GenericNode {
+                        name: b"TypedIndex".to_vec(),
+                        attributes: vec![OwnedAttribute::I32(0)],
+                        children: Vec::new(),
+                    },
+                ],
+            });
+        }
+    }
+    true
+}
+
+
+/// Mesh resolved into an indexed triangle list, ready to be uploaded to GPU.
+///
+/// Each vertex is a unique combination of a control point and its normal and UV (of the first
+/// layer elements), so the vertices are shared among polygons as much as possible.
+///
+/// Arrays are immutable and shared by `Arc`, so the mesh can be cheaply cloned and shared
+/// between threads (for example, between rendering and physics).
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct ResolvedMesh {
+    /// Positions of vertices.
+    pub positions: Arc<[[f64; 3]]>,
+    /// Normals of vertices.
+    ///
+    /// Empty if the mesh has no normals.
+    pub normals: Arc<[[f64; 3]]>,
+    /// UVs of vertices.
+    ///
+    /// Empty if the mesh has no UVs.
+    pub uvs: Arc<[[f64; 2]]>,
+    /// Vertex indices, three for each triangle.
+    pub indices: Arc<[u32]>,
+    /// Material index of each triangle.
+    ///
+    /// This is `0` for all triangles if the mesh has no `LayerElementMaterial`.
+    pub materials: Arc<[i32]>,
+}
+
+impl ResolvedMesh {
+    /// Resolves the mesh geometry.
+    ///
+    /// Polygons are triangulated as fans, so concave polygons may be triangulated incorrectly.
+    ///
+    /// Returns `None` if the mesh doesn't have vertices or polygons, or has broken indices.
+    pub fn from_geometry(obj: &GenericObject) -> Option<Self> {
+        let points = vertices(obj)?;
+        let indices = polygon_vertex_indices(obj)?;
+        let ranges = polygon_ranges(indices);
+        if indices.iter().any(|&raw| vertex_index(raw) >= points.len()) {
+            return None;
+        }
+        let first_elem = |name: &str| layer_elements(obj).find(|node| node.name == name.as_bytes());
+        let normals = first_elem("LayerElementNormal")
+            .and_then(|elem| LayerData::new(elem, "Normals", "NormalsIndex", 3));
+        let uvs = first_elem("LayerElementUV")
+            .and_then(|elem| LayerData::new(elem, "UV", "UVIndex", 2));
+        let materials = first_elem("LayerElementMaterial")
+            .and_then(|elem| LayerData::new(elem, "Materials", "", 1));
+
+        let mut mesh = MeshBuilder::default();
+        // Map from (control point, normal, UV) to the vertex index.
+        let mut vertex_ids = FnvHashMap::<(usize, usize, usize), u32>::default();
+        for (polygon, range) in ranges.iter().enumerate() {
+            let mut polygon_vertices = Vec::with_capacity(range.len());
+            for pv in range.clone() {
+                let cp = vertex_index(indices[pv]);
+                let pos = MappedPosition {
+                    polygon_vertex: pv,
+                    control_point: cp,
+                    polygon,
+                };
+                let normal = normals.as_ref().and_then(|data| data.index(&pos));
+                let uv = uvs.as_ref().and_then(|data| data.index(&pos));
+                let key = (cp, normal.map_or(0, |i| i + 1), uv.map_or(0, |i| i + 1));
+                let next_id = mesh.positions.len() as u32;
+                let id = *vertex_ids.entry(key).or_insert(next_id);
+                if id == next_id {
+                    mesh.positions.push(points[cp]);
+                    if let Some(ref data) = normals {
+                        let v = normal.map_or([0.0; 3], |i| data.get(i));
+                        mesh.normals.push([v[0], v[1], v[2]]);
+                    }
+                    if let Some(ref data) = uvs {
+                        let v = uv.map_or([0.0; 3], |i| data.get(i));
+                        mesh.uvs.push([v[0], v[1]]);
+                    }
+                }
+                polygon_vertices.push(id);
+            }
+            let pos = MappedPosition {
+                polygon_vertex: range.start,
+                control_point: vertex_index(indices[range.start]),
+                polygon,
+            };
+            let material = materials
+                .as_ref()
+                .and_then(|data| data.index(&pos).map(|i| data.get(i)[0] as i32))
+                .unwrap_or(0);
+            for i in 1..polygon_vertices.len().saturating_sub(1) {
+                mesh.indices.extend_from_slice(&[
+                    polygon_vertices[0],
+                    polygon_vertices[i],
+                    polygon_vertices[i + 1],
+                ]);
+                mesh.materials.push(material);
+            }
+        }
+        Some(mesh.build())
+    }
+
+    /// Returns the number of vertices.
+    pub fn num_vertices(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Returns the number of triangles.
+    pub fn num_triangles(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    /// Returns the indices as `u16`, or `None` if some index doesn't fit.
+    pub fn narrow_indices(&self) -> Option<Vec<u16>> {
+        self.indices.iter().map(|&i| narrow_index(i)).collect()
+    }
+
+    /// Splits the mesh into chunks with at most `max_vertices` vertices each.
+    ///
+    /// Triangles are never split and keep their material indices, and vertices shared across
+    /// chunks are duplicated.
+    /// Use `import_checks::MAX_VERTICES_16BIT_INDEX` for runtimes which require 16-bit indices.
+    ///
+    /// # Panics
+    /// Panics if `max_vertices < 3`.
+    pub fn split(&self, max_vertices: usize) -> Vec<ResolvedMesh> {
+        assert!(max_vertices >= 3, "Chunks should be able to have at least one triangle");
+        if self.num_vertices() <= max_vertices {
+            return vec![self.clone()];
+        }
+        let mut chunks = Vec::new();
+        let mut chunk = MeshBuilder::default();
+        // Map from the vertex index in `self` to the one in `chunk`.
+        let mut remap = FnvHashMap::<u32, u32>::default();
+        for (triangle, material) in self.indices.chunks_exact(3).zip(self.materials.iter()) {
+            let new_vertices = triangle.iter().filter(|i| !remap.contains_key(i)).count();
+            if chunk.positions.len() + new_vertices > max_vertices {
+                chunks.push(::std::mem::take(&mut chunk).build());
+                remap.clear();
+            }
+            self.copy_triangle(triangle, *material, &mut chunk, &mut remap);
+        }
+        if !chunk.indices.is_empty() {
+            chunks.push(chunk.build());
+        }
+        chunks
+    }
+
+    /// Creates a mesh with only the triangles of the given submesh.
+    ///
+    /// Unused vertices are removed.
+    ///
+    /// # Panics
+    /// Panics if the index range of the submesh is out of range.
+    pub fn submesh(&self, submesh: &Submesh) -> ResolvedMesh {
+        let mut mesh = MeshBuilder::default();
+        let mut remap = FnvHashMap::<u32, u32>::default();
+        let triangles = submesh.indices.start / 3..submesh.indices.end / 3;
+        let materials = &self.materials[triangles];
+        for (triangle, material) in self.indices[submesh.indices.clone()]
+            .chunks_exact(3)
+            .zip(materials)
+        {
+            self.copy_triangle(triangle, *material, &mut mesh, &mut remap);
+        }
+        mesh.build()
+    }
+
+    /// Copies the triangle to the other mesh, adding vertices not yet in `remap`.
+    fn copy_triangle(
+        &self,
+        triangle: &[u32],
+        material: i32,
+        dest: &mut MeshBuilder,
+        remap: &mut FnvHashMap<u32, u32>,
+    ) {
+        for &index in triangle {
+            let next_id = dest.positions.len() as u32;
+            let id = *remap.entry(index).or_insert(next_id);
+            if id == next_id {
+                let index = index as usize;
+                dest.positions.push(self.positions[index]);
+                if !self.normals.is_empty() {
+                    dest.normals.push(self.normals[index]);
+                }
+                if !self.uvs.is_empty() {
+                    dest.uvs.push(self.uvs[index]);
+                }
+            }
+            dest.indices.push(id);
+        }
+        dest.materials.push(material);
+    }
+}
+
+
+/// Builder of `ResolvedMesh`.
+#[derive(Default)]
+struct MeshBuilder {
+    /// Positions of vertices.
+    positions: Vec<[f64; 3]>,
+    /// Normals of vertices.
+    normals: Vec<[f64; 3]>,
+    /// UVs of vertices.
+    uvs: Vec<[f64; 2]>,
+    /// Vertex indices.
+    indices: Vec<u32>,
+    /// Material index of each triangle.
+    materials: Vec<i32>,
+}
+
+impl MeshBuilder {
+    /// Builds the mesh.
+    fn build(self) -> ResolvedMesh {
+        ResolvedMesh {
+            positions: self.positions.into(),
+            normals: self.normals.into(),
+            uvs: self.uvs.into(),
+            indices: self.indices.into(),
+            materials: self.materials.into(),
+        }
+    }
+}
+
+
+/// Part of a resolved mesh which uses a single material.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Submesh {
+    /// Material index (of `LayerElementMaterial`).
+    pub material: i32,
+    /// Range in `ResolvedMesh::indices`.
+    pub indices: Range<usize>,
+}
+
+
+/// Sorts the triangles of the mesh by material, and returns the submesh for each material.
+///
+/// The order of triangles with the same material is preserved, and submeshes are sorted by
+/// material index.
+/// Use `ResolvedMesh::submesh()` to extract a submesh as a separate mesh.
+pub fn split_by_material(mesh: &mut ResolvedMesh) -> Vec<Submesh> {
+    let mut order: Vec<usize> = (0..mesh.num_triangles()).collect();
+    order.sort_by_key(|&i| mesh.materials[i]);
+    let indices: Vec<u32> = order
+        .iter()
+        .flat_map(|&i| mesh.indices[i * 3..i * 3 + 3].iter().cloned())
+        .collect();
+    let materials: Vec<i32> = order.iter().map(|&i| mesh.materials[i]).collect();
+    mesh.indices = indices.into();
+
+    let mut submeshes: Vec<Submesh> = Vec::new();
+    for (i, &material) in materials.iter().enumerate() {
+        match submeshes.last_mut() {
+            Some(last) if last.material == material => last.indices.end = i * 3 + 3,
+            _ => {
+                submeshes.push(Submesh {
+                    material,
+                    indices: i * 3..i * 3 + 3,
+                })
+            },
+        }
+    }
+    mesh.materials = materials.into();
+    submeshes
+}
+
+
+/// Converts a 32-bit index to a 16-bit index, or returns `None` if it doesn't fit.
+pub fn narrow_index(index: u32) -> Option<u16> {
+    if index <= u32::from(u16::MAX) {
+        Some(index as u16)
+    } else {
+        None
+    }
+}
+
+
+/// Widens 16-bit indices to 32-bit indices.
+pub fn widen_indices(indices: &[u16]) -> Vec<u32> {
+    indices.iter().map(|&i| u32::from(i)).collect()
+}
+
+
+/// View of a `Geometry` object which resolves polygons and layer elements into flat arrays.
+///
+/// Arrays returned by `positions()`, `normals()`, and `uvs()` have one element for each polygon
+/// vertex (in order of `PolygonVertexIndex`), and `triangles()` refers to them.
+/// Layer elements other than the first one of each kind are ignored.
+#[derive(Debug, Clone)]
+pub struct Mesh<'a> {
+    /// Geometry.
+    geometry: &'a Geometry,
+    /// Ranges of polygons in `PolygonVertexIndex`.
+    polygons: Vec<Range<usize>>,
+}
+
+impl<'a> Mesh<'a> {
+    /// Creates a mesh view of the geometry.
+    ///
+    /// Returns `None` if `PolygonVertexIndex` refers to nonexistent control points.
+    pub fn new(geometry: &'a Geometry) -> Option<Self> {
+        let num_points = geometry.vertices.len() / 3;
+        if geometry.polygon_vertex_index.iter().any(|&raw| vertex_index(raw) >= num_points) {
+            return None;
+        }
+        Some(Mesh {
+            geometry,
+            polygons: polygon_ranges(&geometry.polygon_vertex_index),
+        })
+    }
+
+    /// Returns the geometry.
+    pub fn geometry(&self) -> &'a Geometry {
+        self.geometry
+    }
+
+    /// Returns the number of polygons.
+    pub fn num_polygons(&self) -> usize {
+        self.polygons.len()
+    }
+
+    /// Returns the number of polygon vertices.
+    pub fn num_polygon_vertices(&self) -> usize {
+        self.geometry.polygon_vertex_index.len()
+    }
+
+    /// Returns the range of polygon vertices of the polygon.
+    pub fn polygon(&self, index: usize) -> Option<Range<usize>> {
+        self.polygons.get(index).cloned()
+    }
+
+    /// Returns the control point index of each polygon vertex.
+    pub fn control_point_indices(&self) -> Vec<usize> {
+        map_slice(&self.geometry.polygon_vertex_index, |&raw| vertex_index(raw))
+    }
+
+    /// Returns the position of each polygon vertex.
+    pub fn positions(&self) -> Vec<[f64; 3]> {
+        let v = &self.geometry.vertices;
+        map_slice(&self.geometry.polygon_vertex_index, |&raw| {
+            let i = vertex_index(raw) * 3;
+            [v[i], v[i + 1], v[i + 2]]
+        })
+    }
+
+    /// Returns the normal of each polygon vertex, from the first `LayerElementNormal`.
+    ///
+    /// Returns `None` if the geometry has no supported normals.
+    pub fn normals(&self) -> Option<Vec<[f64; 3]>> {
+        let data = self.layer_data("LayerElementNormal", "Normals", "NormalsIndex", 3)?;
+        Some(self.per_polygon_vertex(&data).map(|v| [v[0], v[1], v[2]]).collect())
+    }
+
+    /// Returns the UV of each polygon vertex, from the first `LayerElementUV`.
+    ///
+    /// Returns `None` if the geometry has no supported UVs.
+    pub fn uvs(&self) -> Option<Vec<[f64; 2]>> {
+        let data = self.layer_data("LayerElementUV", "UV", "UVIndex", 2)?;
+        Some(self.per_polygon_vertex(&data).map(|v| [v[0], v[1]]).collect())
+    }
+
+    /// Returns the material index of each polygon, from the first `LayerElementMaterial`.
+    ///
+    /// Returns `None` if the geometry has no supported material layer element.
+    pub fn materials(&self) -> Option<Vec<i32>> {
+        let data = self.layer_data("LayerElementMaterial", "Materials", "", 1)?;
+        let materials = self.polygons
+            .iter()
+            .enumerate()
+            .map(|(polygon, range)| {
+                let pos = self.position(polygon, range.start);
+                data.index(&pos).map_or(0, |i| data.get(i)[0] as i32)
+            })
+            .collect();
+        Some(materials)
+    }
+
+    /// Triangulates the polygons as fans, and returns the polygon vertex indices of the
+    /// triangles.
+    ///
+    /// Concave polygons may be triangulated incorrectly.
+    pub fn triangles(&self) -> Vec<[usize; 3]> {
+        let mut triangles = Vec::new();
+        for range in &self.polygons {
+            for i in range.start + 1..range.end.saturating_sub(1) {
+                triangles.push([range.start, i, i + 1]);
+            }
+        }
+        triangles
+    }
+
+    /// Reads the first layer element with the given name.
+    fn layer_data(
+        &self,
+        name: &str,
+        data_name: &str,
+        index_name: &str,
+        stride: usize,
+    ) -> Option<LayerData> {
+        let elem = self.geometry.layer_elements().find(|node| node.name == name.as_bytes())?;
+        LayerData::new(elem, data_name, index_name, stride)
+    }
+
+    /// Returns the mapped position of the polygon vertex.
+    fn position(&self, polygon: usize, polygon_vertex: usize) -> MappedPosition {
+        MappedPosition {
+            polygon_vertex,
+            control_point: vertex_index(self.geometry.polygon_vertex_index[polygon_vertex]),
+            polygon,
+        }
+    }
+
+    /// Returns the layer element data for each polygon vertex, with zeros for missing data.
+    fn per_polygon_vertex<'b>(
+        &'b self,
+        data: &'b LayerData,
+    ) -> impl Iterator<Item = [f64; 3]> + 'b {
+        self.polygons.iter().enumerate().flat_map(move |(polygon, range)| {
+            range.clone().map(move |pv| {
+                data.index(&self.position(polygon, pv)).map_or([0.0; 3], |i| data.get(i))
+            })
+        })
+    }
+}
+
+
+/// Position in the polygon vertex index array, used to look up layer element data.
+struct MappedPosition {
+    /// Index of the polygon vertex.
+    polygon_vertex: usize,
+    /// Index of the control point.
+    control_point: usize,
+    /// Index of the polygon.
+    polygon: usize,
+}
+
+
+/// Data of a layer element.
+struct LayerData {
+    /// Mapping mode.
+    mapping: MappingMode,
+    /// Index array, if the reference mode is `IndexToDirect`.
+    index: Option<Vec<i32>>,
+    /// Direct data.
+    data: Vec<f64>,
+    /// Number of values per element.
+    stride: usize,
+}
+
+impl LayerData {
+    /// Reads the layer element.
+    ///
+    /// The layout depends on the version of the layer element (see
+    /// `LayerElementVersion::has_w_components()`): W components array (such as `NormalsW`) is
+    /// checked only if the version has it, and ignored otherwise.
+    ///
+    /// Returns `None` if the layer element is not supported or is broken.
+    fn new(elem: &GenericNode, data_name: &str, index_name: &str, stride: usize) -> Option<Self> {
+        let mapping = MappingMode::of_layer_element(elem)?;
+        let reference = ReferenceMode::of_layer_element(elem).unwrap_or(ReferenceMode::Direct);
+        let data = match child(&elem.children, data_name)?.attributes.first()? {
+            OwnedAttribute::ArrF64(v) => v.to_vec(),
+            OwnedAttribute::ArrF32(v) => map_slice(v, |&v| f64::from(v)),
+            OwnedAttribute::ArrI32(v) => map_slice(v, |&v| f64::from(v)),
+            _ => return None,
+        };
+        let has_w = LayerElementVersion::of_node(elem).is_some_and(|v| v.has_w_components());
+        if has_w {
+            if let Some(w) = child(&elem.children, &format!("{}W", data_name)) {
+                let len = match w.attributes.first()? {
+                    OwnedAttribute::ArrF64(v) => v.len(),
+                    OwnedAttribute::ArrF32(v) => v.len(),
+                    _ => return None,
+                };
+                // One W component for each element.
+                if len * stride != data.len() {
+                    return None;
+                }
+            }
+        }
+        // `LayerElementMaterial` has `IndexToDirect` reference without separate index array.
+        let index = match reference {
+            ReferenceMode::IndexToDirect if !index_name.is_empty() => {
+                match child(&elem.children, index_name)?.attributes.first()? {
+                    OwnedAttribute::ArrI32(v) => Some(v.to_vec()),
+                    _ => return None,
+                }
+            },
+            _ => None,
+        };
+        Some(LayerData {
+            mapping,
+            index,
+            data,
+            stride,
+        })
+    }
+
+    /// Returns the index of the element for the given position.
+    fn index(&self, pos: &MappedPosition) -> Option<usize> {
+        let i = match self.mapping {
+            MappingMode::ByPolygonVertex => pos.polygon_vertex,
+            MappingMode::ByVertex => pos.control_point,
+            MappingMode::ByPolygon => pos.polygon,
+            MappingMode::AllSame => 0,
+            MappingMode::ByEdge => return None,
+        };
+        let i = match self.index {
+            Some(ref index) => {
+                let v = *index.get(i)?;
+                if v < 0 {
+                    return None;
+                }
+                v as usize
+            },
+            None => i,
+        };
+        if (i + 1) * self.stride <= self.data.len() {
+            Some(i)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the values of the element.
+    fn values(&self, i: usize) -> &[f64] {
+        &self.data[i * self.stride..(i + 1) * self.stride]
+    }
+
+    /// Returns the element (padded with zeros).
+    fn get(&self, i: usize) -> [f64; 3] {
+        let mut v = [0.0; 3];
+        v[..self.stride].copy_from_slice(&self.data[i * self.stride..(i + 1) * self.stride]);
+        v
+    }
+}
+
+
+/// Returns the normal of the polygon (with the length of twice the area), using Newell's method.
+fn polygon_normal(points: &[[f64; 3]], polygon: &[i32]) -> [f64; 3] {
+    let mut n = [0.0; 3];
+    for (i, &raw) in polygon.iter().enumerate() {
+        let cur = points[vertex_index(raw)];
+        let next = points[vertex_index(polygon[(i + 1) % polygon.len()])];
+        n[0] += (cur[1] - next[1]) * (cur[2] + next[2]);
+        n[1] += (cur[2] - next[2]) * (cur[0] + next[0]);
+        n[2] += (cur[0] - next[0]) * (cur[1] + next[1]);
+    }
+    n
+}
+
+
+/// Returns the dot product of the vectors.
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+
+/// Returns the normalized vector, or the zero vector as is.
+fn normalize(v: [f64; 3]) -> [f64; 3] {
+    let len = dot(v, v).sqrt();
+    if len == 0.0 {
+        v
+    } else {
+        [v[0] / len, v[1] / len, v[2] / len]
+    }
+}
+
+
+/// Returns the polygon vertex order (new position to old position) which reverses the winding
+/// of polygons, keeping the first vertex of each polygon.
+fn reversed_winding_order(ranges: &[Range<usize>], len: usize) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..len).collect();
+    for range in ranges {
+        let n = range.end - range.start;
+        for j in 0..n {
+            order[range.start + j] = range.start + (n - j) % n;
+        }
+    }
+    order
+}
+
+
+/// Negates the given component of 3-component vectors in the array.
+fn negate_component(attr: &mut OwnedAttribute, index: usize) {
+    match *attr {
+        OwnedAttribute::ArrF64(ref mut v) => {
+            v.chunks_exact_mut(3).for_each(|c| c[index] = -c[index]);
+        },
+        OwnedAttribute::ArrF32(ref mut v) => {
+            v.chunks_exact_mut(3).for_each(|c| c[index] = -c[index]);
+        },
+        _ => {},
+    }
+}
+
+
+/// Reorders the elements of the array in the given order (new position to old position).
+///
+/// Arrays with multiple values per element (such as normals) are supported, and arrays whose
+/// length is not a multiple of the order length are left unchanged.
+fn permute(attr: &mut OwnedAttribute, order: &[usize]) {
+    /// Reorders the chunks of the slice.
+    fn permute_slice<T: Copy>(v: &mut [T], order: &[usize]) {
+        if order.is_empty() || v.is_empty() || !v.len().is_multiple_of(order.len()) {
+            return;
+        }
+        let stride = v.len() / order.len();
+        let old = v.to_vec();
+        for (new_pos, &old_pos) in order.iter().enumerate() {
+            v[new_pos * stride..(new_pos + 1) * stride]
+                .copy_from_slice(&old[old_pos * stride..(old_pos + 1) * stride]);
+        }
+    }
+
+    match *attr {
+        OwnedAttribute::ArrBool(ref mut v) => permute_slice(v, order),
+        OwnedAttribute::ArrI32(ref mut v) => permute_slice(v, order),
+        OwnedAttribute::ArrI64(ref mut v) => permute_slice(v, order),
+        OwnedAttribute::ArrF32(ref mut v) => permute_slice(v, order),
+        OwnedAttribute::ArrF64(ref mut v) => permute_slice(v, order),
+        _ => {},
+    }
+}
+
+
+/// Returns the first child node with the given name.
+fn child<'a>(nodes: &'a [GenericNode], name: &str) -> Option<&'a GenericNode> {
+    nodes.iter().find(|node| node.name == name.as_bytes())
+}
+
+
+/// Returns the first child node with the given name.
+fn child_mut<'a>(nodes: &'a mut [GenericNode], name: &str) -> Option<&'a mut GenericNode> {
+    nodes.iter_mut().find(|node| node.name == name.as_bytes())
+}
+
+
+/// Returns the first attribute of the node.
+fn first_attr_mut(node: &mut GenericNode) -> Option<&mut OwnedAttribute> {
+    node.attributes.first_mut()
+}
+
+
+/// Returns the string attribute of the first child node with the given name.
+fn child_string<'a>(nodes: &'a [GenericNode], name: &str) -> Option<&'a str> {
+    match child(nodes, name)?.attributes.first() {
+        Some(OwnedAttribute::String(Ok(v))) => Some(v),
+        _ => None,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::fbx7400::ObjectProperties;
+    use super::*;
+
+    fn node(name: &str, attr: OwnedAttribute) -> GenericNode {
+        GenericNode {
+            name: name.into(),
+            attributes: vec![attr],
+            children: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn mirror_quad() {
+        let mut obj = GenericObject::new(ObjectProperties {
+            id: 1,
+            name: "Quad".to_owned(),
+            class: "Geometry".to_owned(),
+            subclass: "Mesh".to_owned(),
+        });
+        let points = vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 0.0];
+        let uv = node("LayerElementUV", OwnedAttribute::I32(0));
+        obj.nodes = vec![
+            node("Vertices", OwnedAttribute::ArrF64(points.into_boxed_slice())),
+            node("PolygonVertexIndex", OwnedAttribute::ArrI32(vec![0, 1, 2, !3].into())),
+            node("Edges", OwnedAttribute::ArrI32(vec![0, 1, 2, 3].into())),
+            GenericNode {
+                children: vec![
+                    node(
+                        "MappingInformationType",
+                        OwnedAttribute::String(Ok("ByPolygonVertex".to_owned())),
+                    ),
+                    node(
+                        "ReferenceInformationType",
+                        OwnedAttribute::String(Ok("IndexToDirect".to_owned())),
+                    ),
+                    node("UV", OwnedAttribute::ArrF64(vec![0.0; 8].into_boxed_slice())),
+                    node("UVIndex", OwnedAttribute::ArrI32(vec![10, 11, 12, 13].into())),
+                ],
+                ..uv
+            },
+        ];
+
+        mirror_mesh(&mut obj, Axis::X);
+        assert_eq!(
+            vertices(&obj).unwrap(),
+            vec![[0.0, 0.0, 0.0], [-1.0, 0.0, 0.0], [-1.0, 1.0, 0.0], [0.0, 1.0, 0.0]]
+        );
+        assert_eq!(polygon_vertex_indices(&obj).unwrap(), &[0, 3, 2, !1]);
+        // Edges `0-1`, `1-2`, `2-3`, `3-0` start at the new positions 3, 2, 1, 0.
+        assert_eq!(obj.nodes[2].attributes[0], OwnedAttribute::ArrI32(vec![3, 2, 1, 0].into()));
+        assert_eq!(
+            obj.nodes[3].children[3].attributes[0],
+            OwnedAttribute::ArrI32(vec![10, 13, 12, 11].into())
+        );
+    }
+    #[test]
+    fn normals_of_folded_quads() {
+        let mut obj = GenericObject::new(ObjectProperties {
+            id: 1,
+            name: "Fold".to_owned(),
+            class: "Geometry".to_owned(),
+            subclass: "Mesh".to_owned(),
+        });
+        // Two quads sharing the edge `1-2`, folded by 90 degrees.
+        let points = vec![
+            0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 0.0, 1.0, 0.0, -1.0, 1.0, 1.0,
+            -1.0,
+        ];
+        obj.nodes = vec![
+            node("Vertices", OwnedAttribute::ArrF64(points.into_boxed_slice())),
+            node(
+                "PolygonVertexIndex",
+                OwnedAttribute::ArrI32(vec![0, 1, 2, !3, 1, 4, 5, !2].into()),
+            ),
+        ];
+        let normals = |angle| {
+            let elem = recompute_normals(&obj, angle).unwrap();
+            match elem.children[4].attributes[0] {
+                OwnedAttribute::ArrF64(ref v) => v.to_vec(),
+                _ => unreachable!(),
+            }
+        };
+
+        let faceted = normals(30.0);
+        assert_eq!(&faceted[0..3], &[0.0, 0.0, 1.0]);
+        assert_eq!(&faceted[12..15], &[1.0, 0.0, 0.0]);
+        let smoothed = normals(100.0);
+        let d = 0.5f64.sqrt();
+        assert!((smoothed[3] - d).abs() < 1e-12 && (smoothed[5] - d).abs() < 1e-12);
+        assert_eq!(&smoothed[0..3], &[0.0, 0.0, 1.0]);
+    }
+    #[test]
+    fn split_resolved_mesh() {
+        let mut obj = GenericObject::new(ObjectProperties {
+            id: 1,
+            name: "Strip".to_owned(),
+            class: "Geometry".to_owned(),
+            subclass: "Mesh".to_owned(),
+        });
+        let points = vec![0.0; 3 * 6];
+        obj.nodes = vec![
+            node("Vertices", OwnedAttribute::ArrF64(points.into_boxed_slice())),
+            node(
+                "PolygonVertexIndex",
+                OwnedAttribute::ArrI32(vec![0, 1, 2, !3, 2, 3, 4, !5].into()),
+            ),
+        ];
+        let mesh = ResolvedMesh::from_geometry(&obj).unwrap();
+        assert_eq!(mesh.num_vertices(), 6);
+        assert_eq!(&mesh.indices[..], &[0, 1, 2, 0, 2, 3, 2, 3, 4, 2, 4, 5]);
+        assert_eq!(&mesh.materials[..], &[0; 4]);
+
+        let chunks = mesh.split(4);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(&chunks[0].indices[..], &[0, 1, 2, 0, 2, 3]);
+        assert_eq!(&chunks[1].indices[..], &[0, 1, 2, 0, 2, 3]);
+        assert_eq!(chunks[1].num_vertices(), 4);
+        assert_eq!(chunks[1].narrow_indices(), Some(vec![0, 1, 2, 0, 2, 3]));
+    }
+    #[test]
+    fn submeshes_by_material() {
+        let mut mesh = ResolvedMesh {
+            positions: vec![[0.0; 3]; 5].into(),
+            indices: vec![0, 1, 2, 1, 2, 3, 2, 3, 4].into(),
+            materials: vec![1, 0, 1].into(),
+            ..ResolvedMesh::default()
+        };
+        let submeshes = split_by_material(&mut mesh);
+        assert_eq!(&mesh.indices[..], &[1, 2, 3, 0, 1, 2, 2, 3, 4]);
+        assert_eq!(
+            submeshes,
+            vec![
+                Submesh {
+                    material: 0,
+                    indices: 0..3,
+                },
+                Submesh {
+                    material: 1,
+                    indices: 3..9,
+                },
+            ]
+        );
+        let sub = mesh.submesh(&submeshes[0]);
+        assert_eq!(sub.num_vertices(), 3);
+        assert_eq!(&sub.indices[..], &[0, 1, 2]);
+        assert_eq!(&sub.materials[..], &[0]);
+    }
+
+    #[test]
+    fn mesh_view() {
+        let string = |v: &str| OwnedAttribute::String(Ok(v.to_owned()));
+        let elem = |name: &str, mapping: &str, children: Vec<GenericNode>| GenericNode {
+            name: name.into(),
+            attributes: vec![OwnedAttribute::I32(0)],
+            children: vec![
+                node("MappingInformationType", string(mapping)),
+                node("ReferenceInformationType", string("IndexToDirect")),
+            ]
+                .into_iter()
+                .chain(children)
+                .collect(),
+        };
+        let geometry = Geometry {
+            props: ObjectProperties {
+                id: 1,
+                name: "Quad".to_owned(),
+                class: "Geometry".to_owned(),
+                subclass: "Mesh".to_owned(),
+            },
+            version: Some(124),
+            vertices: vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 0.0],
+            polygon_vertex_index: vec![0, 1, 2, !3],
+            edges: Vec::new(),
+            properties: Default::default(),
+            nodes: vec![
+                elem(
+                    "LayerElementUV",
+                    "ByVertex",
+                    vec![
+                        node("UV", OwnedAttribute::ArrF64(vec![0.0, 0.0, 1.0, 1.0].into())),
+                        node("UVIndex", OwnedAttribute::ArrI32(vec![0, 1, 1, 0].into())),
+                    ],
+                ),
+                elem(
+                    "LayerElementMaterial",
+                    "AllSame",
+                    vec![node("Materials", OwnedAttribute::ArrI32(vec![2].into()))],
+                ),
+            ],
+        };
+        let mesh = Mesh::new(&geometry).unwrap();
+        assert_eq!(mesh.num_polygons(), 1);
+        assert_eq!(mesh.positions()[3], [0.0, 1.0, 0.0]);
+        assert_eq!(mesh.triangles(), vec![[0, 1, 2], [0, 2, 3]]);
+        assert_eq!(mesh.normals(), None);
+        assert_eq!(
+            mesh.uvs().unwrap(),
+            vec![[0.0, 0.0], [1.0, 1.0], [1.0, 1.0], [0.0, 0.0]]
+        );
+        assert_eq!(mesh.materials(), Some(vec![2]));
+    }
+
+    #[test]
+    fn layer_element_uv_versions() {
+        let string = |v: &str| OwnedAttribute::String(Ok(v.to_owned()));
+        let uv_elem = |version: i32, w: Option<usize>| {
+            let mut children = vec![
+                node("Version", OwnedAttribute::I32(version)),
+                node("MappingInformationType", string("ByPolygonVertex")),
+                node("ReferenceInformationType", string("IndexToDirect")),
+                node("UV", OwnedAttribute::ArrF64(vec![0.0, 0.0, 1.0, 0.0, 0.0, 1.0].into())),
+            ];
+            if let Some(len) = w {
+                children.push(node("UVW", OwnedAttribute::ArrF64(vec![1.0; len].into())));
+            }
+            children.push(node("UVIndex", OwnedAttribute::ArrI32(vec![0, 1, 2].into())));
+            GenericNode {
+                name: b"LayerElementUV".to_vec(),
+                attributes: vec![OwnedAttribute::I32(0)],
+                children,
+            }
+        };
+        let uvs = |elem: GenericNode| {
+            let version = LayerElementVersion::of_node(&elem);
+            let geometry = Geometry {
+                props: ObjectProperties {
+                    id: 1,
+                    name: "Tri".to_owned(),
+                    class: "Geometry".to_owned(),
+                    subclass: "Mesh".to_owned(),
+                },
+                version: Some(124),
+                vertices: vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0],
+                polygon_vertex_index: vec![0, 1, !2],
+                edges: Vec::new(),
+                properties: Default::default(),
+                nodes: vec![elem],
+            };
+            (version, Mesh::new(&geometry).unwrap().uvs())
+        };
+        let expected = Some(vec![[0.0, 0.0], [1.0, 0.0], [0.0, 1.0]]);
+
+        // Version 101 has no W components, and `UVW` is ignored.
+        assert_eq!(uvs(uv_elem(101, None)), (Some(LayerElementVersion::V101), expected.clone()));
+        assert_eq!(uvs(uv_elem(101, Some(2))).1, expected);
+        // Version 102 has W components array with one value for each UV.
+        assert_eq!(uvs(uv_elem(102, None)), (Some(LayerElementVersion::V102), expected.clone()));
+        assert_eq!(uvs(uv_elem(102, Some(3))).1, expected);
+        assert_eq!(uvs(uv_elem(102, Some(2))).1, None);
+    }
+}
@@ -0,0 +1,407 @@
+//! `GlobalSettings` node and its children.
+//!
+//! `GlobalSettings` has the axis system, the unit, and the time mode of the scene.
+//! `GlobalSettings::axis_conversion_matrix()` and `GlobalSettings::unit_scale_to()` help to
+//! normalize scenes to the conventions of the application.
+
+use std::convert::TryFrom;
+use pull_parser::{Parser, ParserSource, Attributes};
+use crate::{Result, Error};
+use crate::fbx7400::{KTime, Properties70};
+use crate::fbx7400::geometry::Axis;
+use crate::fbx7400::node_versions::GlobalSettingsVersion;
+use crate::fbx7400::property_names;
+use crate::fbx7400::units::DEFAULT_UNIT_SCALE_FACTOR;
+use approx::ApproxEq;
+use memory::MemoryUsage;
+
+
+/// `GlobalSettings` node.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct GlobalSettings {
+    /// Version of the node.
+    pub version: i32,
+    /// Properties.
+    pub properties: Properties70,
+}
+
+impl GlobalSettings {
+    /// Version of the node written by the official SDK.
+    pub const VERSION: i32 = 1000;
+
+    /// Creates an empty `GlobalSettings` of the current version.
+    ///
+    /// This is used for documents without `GlobalSettings`, whose properties are the defaults.
+    pub fn new() -> Self {
+        GlobalSettings {
+            version: Self::VERSION,
+            properties: Properties70::new(),
+        }
+    }
+
+    /// Loads node contents from the parser.
+    pub fn load<R, P>(mut parser: P) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        let mut version = None;
+        let mut properties = None;
+
+        loop {
+            let node_type = try_get_node_attrs!(parser, GlobalSettingsChildAttrs::load);
+            match node_type {
+                GlobalSettingsChildAttrs::Version(v) => {
+                    version = Some(v);
+                    parser.skip_current_node()?;
+                },
+                GlobalSettingsChildAttrs::Properties70 => {
+                    properties = Some(Properties70::load(parser.subtree_parser())?);
+                },
+            }
+        }
+        let version = ensure_node_exists!(version, "Definitions", "Version");
+        if !GlobalSettingsVersion::from_raw(version).is_known() {
+            warn!("Unknown `GlobalSettings` version: {}", version);
+        }
+        Ok(GlobalSettings {
+            version,
+            properties: ensure_node_exists!(properties, "Definitions", "Properties70"),
+        })
+    }
+
+    /// Returns the version of the node.
+    pub fn known_version(&self) -> GlobalSettingsVersion {
+        GlobalSettingsVersion::from_raw(self.version)
+    }
+
+    /// Returns the axis system.
+    ///
+    /// Missing or invalid axes are replaced with the ones of `AxisSystem::default()`.
+    pub fn axis_system(&self) -> AxisSystem {
+        let default = AxisSystem::default();
+        let axis = |name: &str, sign_name: &str, default: SignedAxis| {
+            let axis = match self.properties.get_i64(name) {
+                Some(0) => Axis::X,
+                Some(1) => Axis::Y,
+                Some(2) => Axis::Z,
+                _ => return default,
+            };
+            let sign = self.properties.get_i64(sign_name).map_or(1, |v| v.signum() as i8);
+            SignedAxis {
+                axis,
+                sign: if sign < 0 { -1 } else { 1 },
+            }
+        };
+        AxisSystem {
+            up: axis(property_names::UP_AXIS, property_names::UP_AXIS_SIGN, default.up),
+            front: axis(property_names::FRONT_AXIS, property_names::FRONT_AXIS_SIGN, default.front),
+            coord: axis(property_names::COORD_AXIS, property_names::COORD_AXIS_SIGN, default.coord),
+        }
+    }
+
+    /// Returns the matrix which converts coordinates in the scene into the target axis system.
+    ///
+    /// The matrix is column major, as matrices in FBX.
+    pub fn axis_conversion_matrix(&self, target: &AxisSystem) -> [[f64; 4]; 4] {
+        self.axis_system().conversion_matrix(target)
+    }
+
+    /// Returns the unit scale factor (length of the unit in centimeters).
+    pub fn unit_scale_factor(&self) -> f64 {
+        self.properties
+            .get_f64(property_names::UNIT_SCALE_FACTOR)
+            .unwrap_or(DEFAULT_UNIT_SCALE_FACTOR)
+    }
+
+    /// Returns the factor to convert lengths in the scene into the target unit (length of the unit
+    /// in centimeters, for example `100.0` for meters).
+    pub fn unit_scale_to(&self, target: f64) -> f64 {
+        self.unit_scale_factor() / target
+    }
+
+    /// Returns the time mode.
+    ///
+    /// Returns `None` if the property is missing or has an unknown value.
+    pub fn time_mode(&self) -> Option<TimeMode> {
+        self.properties.get_enum(property_names::TIME_MODE)
+    }
+
+    /// Returns the frame rate (frames per second).
+    ///
+    /// `CustomFrameRate` is used for `TimeMode::Custom`.
+    /// Returns `None` if the time mode is unknown or has no fixed frame rate.
+    pub fn frame_rate(&self) -> Option<f64> {
+        match self.time_mode()? {
+            TimeMode::Custom => self.properties.get_f64(property_names::CUSTOM_FRAME_RATE),
+            mode => mode.frame_rate(),
+        }
+    }
+
+    /// Returns the start and stop times of the scene.
+    pub fn time_span(&self) -> Option<(KTime, KTime)> {
+        let start = self.properties.get_as(property_names::TIME_SPAN_START).unwrap_or(None)?;
+        let stop = self.properties.get_as(property_names::TIME_SPAN_STOP).unwrap_or(None)?;
+        Some((start, stop))
+    }
+}
+
+
+/// Axis with the direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SignedAxis {
+    /// Axis.
+    pub axis: Axis,
+    /// Direction, `1` or `-1`.
+    pub sign: i8,
+}
+
+impl SignedAxis {
+    /// Creates a new `SignedAxis` with the positive direction.
+    pub fn positive(axis: Axis) -> Self {
+        SignedAxis { axis, sign: 1 }
+    }
+
+    /// Creates a new `SignedAxis` with the negative direction.
+    pub fn negative(axis: Axis) -> Self {
+        SignedAxis { axis, sign: -1 }
+    }
+}
+
+
+/// Axis system of a scene.
+///
+/// `up` is the up vector, `front` is the front vector (the direction the viewer faces from),
+/// and `coord` is the remaining (right) vector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AxisSystem {
+    /// Up axis.
+    pub up: SignedAxis,
+    /// Front axis.
+    pub front: SignedAxis,
+    /// Coord axis.
+    pub coord: SignedAxis,
+}
+
+impl AxisSystem {
+    /// Y-up right-handed axis system (used by Maya, OpenGL, and the FBX SDK by default).
+    pub const Y_UP_RIGHT_HANDED: AxisSystem = AxisSystem {
+        up: SignedAxis { axis: Axis::Y, sign: 1 },
+        front: SignedAxis { axis: Axis::Z, sign: 1 },
+        coord: SignedAxis { axis: Axis::X, sign: 1 },
+    };
+
+    /// Z-up right-handed axis system (used by 3ds Max and Blender).
+    pub const Z_UP_RIGHT_HANDED: AxisSystem = AxisSystem {
+        up: SignedAxis { axis: Axis::Z, sign: 1 },
+        front: SignedAxis { axis: Axis::Y, sign: -1 },
+        coord: SignedAxis { axis: Axis::X, sign: 1 },
+    };
+
+    /// Y-up left-handed axis system (used by DirectX and Unity).
+    pub const Y_UP_LEFT_HANDED: AxisSystem = AxisSystem {
+        up: SignedAxis { axis: Axis::Y, sign: 1 },
+        front: SignedAxis { axis: Axis::Z, sign: -1 },
+        coord: SignedAxis { axis: Axis::X, sign: 1 },
+    };
+
+    /// Returns `true` if the axis system is right-handed.
+    pub fn is_right_handed(&self) -> bool {
+        // Determinant of the matrix whose columns are coord, up, and front.
+        let basis = [self.coord, self.up, self.front];
+        let mut inversions = 0;
+        for i in 0..3 {
+            for j in (i + 1)..3 {
+                if basis[i].axis.index() > basis[j].axis.index() {
+                    inversions += 1;
+                }
+            }
+        }
+        let sign = basis.iter().fold(1, |acc, axis| acc * axis.sign);
+        (inversions % 2 == 0) == (sign > 0)
+    }
+
+    /// Returns the matrix which converts coordinates in this axis system into the target axis
+    /// system.
+    ///
+    /// The matrix maps the up, front, and coord vectors of `self` into the ones of `target`.
+    /// It is column major, as matrices in FBX.
+    pub fn conversion_matrix(&self, target: &AxisSystem) -> [[f64; 4]; 4] {
+        let mut mat = [[0.0; 4]; 4];
+        mat[3][3] = 1.0;
+        let pairs = [(self.up, target.up), (self.front, target.front), (self.coord, target.coord)];
+        for &(src, dest) in &pairs {
+            mat[src.axis.index()][dest.axis.index()] = f64::from(src.sign * dest.sign);
+        }
+        mat
+    }
+}
+
+impl Default for AxisSystem {
+    fn default() -> Self {
+        AxisSystem::Y_UP_RIGHT_HANDED
+    }
+}
+
+
+/// Time mode (frame rate) of a scene.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TimeMode {
+    /// Default mode.
+    Default,
+    /// 120 fps.
+    Frames120,
+    /// 100 fps.
+    Frames100,
+    /// 60 fps.
+    Frames60,
+    /// 50 fps.
+    Frames50,
+    /// 48 fps.
+    Frames48,
+    /// 30 fps.
+    Frames30,
+    /// 30 fps with drop frames.
+    Frames30Drop,
+    /// 29.97 fps with drop frames (NTSC).
+    NtscDropFrame,
+    /// 29.97 fps (NTSC).
+    NtscFullFrame,
+    /// 25 fps (PAL).
+    Pal,
+    /// 24 fps.
+    Frames24,
+    /// 1000 fps.
+    Frames1000,
+    /// 23.976 fps.
+    FilmFullFrame,
+    /// Custom frame rate (`CustomFrameRate` property).
+    Custom,
+    /// 96 fps.
+    Frames96,
+    /// 72 fps.
+    Frames72,
+    /// 59.94 fps.
+    Frames59Dot94,
+    /// 119.88 fps.
+    Frames119Dot88,
+}
+
+impl TimeMode {
+    /// Returns the frame rate (frames per second).
+    ///
+    /// Returns `None` for `Default` and `Custom`.
+    pub fn frame_rate(&self) -> Option<f64> {
+        Some(match *self {
+            TimeMode::Default | TimeMode::Custom => return None,
+            TimeMode::Frames120 => 120.0,
+            TimeMode::Frames100 => 100.0,
+            TimeMode::Frames60 => 60.0,
+            TimeMode::Frames50 => 50.0,
+            TimeMode::Frames48 => 48.0,
+            TimeMode::Frames30 | TimeMode::Frames30Drop => 30.0,
+            TimeMode::NtscDropFrame | TimeMode::NtscFullFrame => 29.970_029_97,
+            TimeMode::Pal => 25.0,
+            TimeMode::Frames24 => 24.0,
+            TimeMode::Frames1000 => 1000.0,
+            TimeMode::FilmFullFrame => 23.976,
+            TimeMode::Frames96 => 96.0,
+            TimeMode::Frames72 => 72.0,
+            TimeMode::Frames59Dot94 => 59.94,
+            TimeMode::Frames119Dot88 => 119.88,
+        })
+    }
+}
+
+impl TryFrom<i64> for TimeMode {
+    type Error = i64;
+
+    fn try_from(v: i64) -> ::std::result::Result<Self, i64> {
+        Ok(match v {
+            0 => TimeMode::Default,
+            1 => TimeMode::Frames120,
+            2 => TimeMode::Frames100,
+            3 => TimeMode::Frames60,
+            4 => TimeMode::Frames50,
+            5 => TimeMode::Frames48,
+            6 => TimeMode::Frames30,
+            7 => TimeMode::Frames30Drop,
+            8 => TimeMode::NtscDropFrame,
+            9 => TimeMode::NtscFullFrame,
+            10 => TimeMode::Pal,
+            11 => TimeMode::Frames24,
+            12 => TimeMode::Frames1000,
+            13 => TimeMode::FilmFullFrame,
+            14 => TimeMode::Custom,
+            15 => TimeMode::Frames96,
+            16 => TimeMode::Frames72,
+            17 => TimeMode::Frames59Dot94,
+            18 => TimeMode::Frames119Dot88,
+            v => return Err(v),
+        })
+    }
+}
+
+
+child_attr_loader! { GlobalSettingsChildAttrs {
+    "Version" => Version(i32),
+    "Properties70" => Properties70,
+}}
+
+
+impl MemoryUsage for GlobalSettings {
+    fn heap_usage(&self) -> usize {
+        self.properties.heap_usage()
+    }
+}
+
+
+impl ApproxEq for GlobalSettings {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.version == other.version && self.properties.approx_eq(&other.properties, epsilon)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::fbx7400::Properties70;
+    use super::*;
+
+    #[test]
+    fn axis_conversion() {
+        let mut properties = Properties70::new();
+        for &(name, value) in &[
+            (property_names::UP_AXIS, 2),
+            (property_names::UP_AXIS_SIGN, 1),
+            (property_names::FRONT_AXIS, 1),
+            (property_names::FRONT_AXIS_SIGN, -1),
+            (property_names::COORD_AXIS, 0),
+            (property_names::COORD_AXIS_SIGN, 1),
+            (property_names::TIME_MODE, 11),
+        ] {
+            properties.values_i64.insert(name.to_owned(), value.into());
+        }
+        properties.values_f64.insert(property_names::UNIT_SCALE_FACTOR.to_owned(), 100.0.into());
+        let gs = GlobalSettings {
+            version: 1000,
+            properties,
+        };
+        let axes = gs.axis_system();
+        assert_eq!(axes, AxisSystem::Z_UP_RIGHT_HANDED);
+        assert!(axes.is_right_handed());
+        assert!(!AxisSystem::Y_UP_LEFT_HANDED.is_right_handed());
+
+        // Z-up to Y-up: (x, y, z) -> (x, z, -y).
+        let mat = gs.axis_conversion_matrix(&AxisSystem::Y_UP_RIGHT_HANDED);
+        let v = [1.0, 2.0, 3.0];
+        let converted = (0..3)
+            .map(|row| (0..3).map(|col| mat[col][row] * v[col]).sum::<f64>())
+            .collect::<Vec<_>>();
+        assert_eq!(converted, vec![1.0, 3.0, -2.0]);
+
+        assert_eq!(gs.unit_scale_to(1.0), 100.0);
+        assert_eq!(gs.time_mode(), Some(TimeMode::Frames24));
+        assert_eq!(gs.frame_rate(), Some(24.0));
+    }
+}
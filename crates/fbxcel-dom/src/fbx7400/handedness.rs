@@ -0,0 +1,226 @@
+//! Handedness conversion of documents.
+//!
+//! Some engines prefer the conversion between right-handed and left-handed coordinate systems
+//! baked into the data, rather than applied as a runtime matrix.
+//! `flip_handedness()` mirrors the whole document across an axis.
+
+use fnv::{FnvHashMap, FnvHashSet};
+use crate::{GenericNode, OwnedAttribute};
+use crate::fbx7400::{CurveChannel, Fbx7400Document, GenericObject};
+use crate::fbx7400::PropertyValue;
+use crate::fbx7400::geometry::{self, Axis};
+use crate::fbx7400::property_names;
+
+
+/// `Model` properties which have positions.
+const MODEL_POSITION_PROPERTIES: &[&str] = &[
+    property_names::LCL_TRANSLATION,
+    property_names::ROTATION_OFFSET,
+    property_names::ROTATION_PIVOT,
+    property_names::SCALING_OFFSET,
+    property_names::SCALING_PIVOT,
+    property_names::GEOMETRIC_TRANSLATION,
+];
+
+/// `Model` properties which have Euler angles.
+const MODEL_ROTATION_PROPERTIES: &[&str] = &[
+    property_names::LCL_ROTATION,
+    property_names::PRE_ROTATION,
+    property_names::POST_ROTATION,
+    property_names::GEOMETRIC_ROTATION,
+];
+
+/// Child nodes of `Deformer` objects which have 4x4 matrices.
+const DEFORMER_MATRIX_NODES: &[&str] = &["Transform", "TransformLink", "TransformAssociateModel"];
+
+
+/// Kind of a vector property, which determines how it is mirrored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VectorKind {
+    /// Position, whose component along the axis is negated.
+    Position,
+    /// Euler angles, whose components other than the axis are negated.
+    Rotation,
+}
+
+impl VectorKind {
+    /// Returns the kind of the given model property.
+    fn of_model_property(name: &str) -> Option<Self> {
+        if MODEL_POSITION_PROPERTIES.contains(&name) {
+            Some(VectorKind::Position)
+        } else if MODEL_ROTATION_PROPERTIES.contains(&name) {
+            Some(VectorKind::Rotation)
+        } else {
+            None
+        }
+    }
+
+    /// Returns whether the given component should be negated.
+    fn negates(self, component: usize, axis: Axis) -> bool {
+        match self {
+            VectorKind::Position => component == axis.index(),
+            VectorKind::Rotation => component != axis.index(),
+        }
+    }
+}
+
+
+/// Converts the document between right-handed and left-handed coordinate systems, by mirroring
+/// it across the plane perpendicular to the given axis.
+///
+/// This mirrors the values below:
+///
+/// - Geometries (see `geometry::mirror_mesh()`), with the winding of polygons reversed.
+/// - Translations, rotations, pivots, and offsets of models.
+/// - Animation curves connected to these model properties.
+/// - Matrices of clusters and bind poses.
+/// - Axis signs in `GlobalSettings`.
+pub fn flip_handedness(doc: &mut Fbx7400Document, axis: Axis) {
+    if let Some(ref mut gs) = doc.global_settings {
+        flip_axis_signs(&mut gs.properties.values_i64, axis);
+    }
+    let objects = match doc.objects {
+        Some(ref mut v) => v,
+        None => return,
+    };
+
+    let mut curve_nodes = FnvHashMap::default();
+    let mut curves = FnvHashSet::default();
+    if let Some(ref connections) = doc.connections {
+        let models = objects
+            .iter()
+            .filter(|obj| obj.props.class == "Model")
+            .map(|obj| obj.props.id)
+            .collect::<Vec<_>>();
+        for conn in &connections.0 {
+            let kind = conn.property.as_ref().and_then(|prop| VectorKind::of_model_property(prop));
+            if let Some(kind) = kind.filter(|_| models.contains(&conn.destination)) {
+                curve_nodes.insert(conn.source, kind);
+            }
+        }
+        for conn in &connections.0 {
+            let (kind, component) = match (
+                curve_nodes.get(&conn.destination),
+                conn.curve_channel().and_then(|ch| ch.component_index()),
+            ) {
+                (Some(&kind), Some(component)) => (kind, component),
+                _ => continue,
+            };
+            if kind.negates(component, axis) {
+                curves.insert(conn.source);
+            }
+        }
+    }
+
+    for obj in objects.iter_mut() {
+        let id = obj.props.id;
+        match obj.props.class.as_str() {
+            "Geometry" => geometry::mirror_mesh(obj, axis),
+            "Model" => flip_model(obj, axis),
+            "AnimationCurveNode" => {
+                if let Some(&kind) = curve_nodes.get(&id) {
+                    flip_curve_node_defaults(obj, kind, axis);
+                }
+            },
+            "AnimationCurve" if curves.contains(&id) => {
+                negate_child_values(&mut obj.nodes, "KeyValueFloat");
+                negate_child_values(&mut obj.nodes, "Default");
+            },
+            "Deformer" => {
+                for name in DEFORMER_MATRIX_NODES {
+                    flip_child_matrices(&mut obj.nodes, name, axis);
+                }
+            },
+            "Pose" => {
+                for pose_node in obj.nodes.iter_mut().filter(|node| node.name == b"PoseNode") {
+                    flip_child_matrices(&mut pose_node.children, "Matrix", axis);
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+
+/// Negates the signs of `GlobalSettings` axes which are the given axis.
+fn flip_axis_signs(values: &mut FnvHashMap<String, PropertyValue<i64>>, axis: Axis) {
+    let pairs = [
+        (property_names::UP_AXIS, property_names::UP_AXIS_SIGN),
+        (property_names::FRONT_AXIS, property_names::FRONT_AXIS_SIGN),
+        (property_names::COORD_AXIS, property_names::COORD_AXIS_SIGN),
+    ];
+    for &(axis_name, sign_name) in &pairs {
+        let is_target = values
+            .get(axis_name)
+            .is_some_and(|v| *v.value() == axis.index() as i64);
+        if !is_target {
+            continue;
+        }
+        if let Some(sign) = values.get_mut(sign_name) {
+            *sign = PropertyValue::new(-sign.value());
+        }
+    }
+}
+
+
+/// Mirrors vector properties of the model.
+fn flip_model(obj: &mut GenericObject, axis: Axis) {
+    for (name, value) in &mut obj.properties.values_f64_3 {
+        let kind = match VectorKind::of_model_property(name) {
+            Some(v) => v,
+            None => continue,
+        };
+        let mut v = *value.value();
+        for (i, component) in v.iter_mut().enumerate() {
+            if kind.negates(i, axis) {
+                *component = -*component;
+            }
+        }
+        *value = PropertyValue::new(v);
+    }
+}
+
+
+/// Mirrors default channel values of the animation curve node.
+fn flip_curve_node_defaults(obj: &mut GenericObject, kind: VectorKind, axis: Axis) {
+    for (name, value) in &mut obj.properties.values_f64 {
+        let component = CurveChannel::parse(name).and_then(|ch| ch.component_index());
+        if component.is_some_and(|i| kind.negates(i, axis)) {
+            *value = PropertyValue::new(-value.value());
+        }
+    }
+}
+
+
+/// Negates all values of the first attribute of the child nodes with the given name.
+fn negate_child_values(nodes: &mut [GenericNode], name: &str) {
+    for node in nodes.iter_mut().filter(|node| node.name == name.as_bytes()) {
+        match node.attributes.first_mut() {
+            Some(OwnedAttribute::ArrF64(v)) => v.iter_mut().for_each(|v| *v = -*v),
+            Some(OwnedAttribute::ArrF32(v)) => v.iter_mut().for_each(|v| *v = -*v),
+            Some(OwnedAttribute::F64(v)) => *v = -*v,
+            Some(OwnedAttribute::F32(v)) => *v = -*v,
+            _ => {},
+        }
+    }
+}
+
+
+/// Mirrors 4x4 matrices (stored as 16 `f64`s in column-major order) of the child nodes with the
+/// given name, i.e. converts `M` to `S * M * S` where `S` is the reflection.
+fn flip_child_matrices(nodes: &mut [GenericNode], name: &str, axis: Axis) {
+    let axis = axis.index();
+    for node in nodes.iter_mut().filter(|node| node.name == name.as_bytes()) {
+        if let Some(OwnedAttribute::ArrF64(v)) = node.attributes.first_mut() {
+            if v.len() != 16 {
+                continue;
+            }
+            for (i, elem) in v.iter_mut().enumerate() {
+                let (col, row) = (i / 4, i % 4);
+                if (row == axis) != (col == axis) {
+                    *elem = -*elem;
+                }
+            }
+        }
+    }
+}
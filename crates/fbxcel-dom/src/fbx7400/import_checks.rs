@@ -0,0 +1,358 @@
+//! Checks for constructs known to break importers of major game engines.
+//!
+//! These checks are opinionated: files which fail them are valid FBX, but engines such as Unity
+//! and Unreal Engine may fail to import them or import them incorrectly.
+
+use std::fmt;
+use fnv::FnvHashMap;
+use crate::OwnedAttribute;
+use crate::fbx7400::{Connections, GenericObject};
+use crate::fbx7400::geometry::num_split_vertices;
+use crate::fbx7400::property_names;
+
+
+/// Max number of vertices which can be indexed by 16-bit indices.
+pub const MAX_VERTICES_16BIT_INDEX: usize = 65_535;
+
+/// Max number of bones affecting a vertex, which is supported by engines without extra settings.
+pub const MAX_BONES_PER_VERTEX: usize = 4;
+
+
+/// Kind of an import issue.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportIssueKind {
+    /// Mesh has too many vertices to be indexed by 16-bit indices.
+    TooManyVertices {
+        /// Number of vertices after splitting control points by normals, UVs, and so on (see
+        /// `geometry::num_split_vertices()`).
+        count: usize,
+    },
+    /// Model has negative and non-uniform scale.
+    NegativeNonUniformScale {
+        /// Scale.
+        scale: [f64; 3],
+    },
+    /// Mesh has neither smoothing groups nor normals.
+    MissingSmoothing,
+    /// Vertices of the mesh are affected by too many bones.
+    TooManyBonesPerVertex {
+        /// Max number of bones affecting a vertex.
+        max_bones: usize,
+        /// Number of vertices affected by too many bones.
+        num_vertices: usize,
+    },
+}
+
+
+/// An issue found by the import checks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportIssue {
+    /// ID of the object with the issue.
+    pub object_id: i64,
+    /// Name of the object with the issue.
+    pub object_name: String,
+    /// Kind of the issue.
+    pub kind: ImportIssueKind,
+}
+
+impl fmt::Display for ImportIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?} (id={}): ", self.object_name, self.object_id)?;
+        match self.kind {
+            ImportIssueKind::TooManyVertices { count } => {
+                write!(
+                    f,
+                    "mesh has {} vertices, which exceeds {}; enable 32-bit indices on import or \
+                     split the mesh",
+                    count,
+                    MAX_VERTICES_16BIT_INDEX
+                )
+            },
+            ImportIssueKind::NegativeNonUniformScale { scale } => {
+                write!(
+                    f,
+                    "model has negative non-uniform scale {:?}; apply the scale before export",
+                    scale
+                )
+            },
+            ImportIssueKind::MissingSmoothing => {
+                write!(
+                    f,
+                    "mesh has neither smoothing groups nor normals; export with smoothing or \
+                     normals"
+                )
+            },
+            ImportIssueKind::TooManyBonesPerVertex {
+                max_bones,
+                num_vertices,
+            } => {
+                write!(
+                    f,
+                    "{} vertices are affected by more than {} bones (max {}); limit influences \
+                     per vertex before export",
+                    num_vertices,
+                    MAX_BONES_PER_VERTEX,
+                    max_bones
+                )
+            },
+        }
+    }
+}
+
+
+/// Checks the objects and returns the found issues.
+pub fn check_objects(objects: &[GenericObject], connections: &Connections) -> Vec<ImportIssue> {
+    let mut issues = Vec::new();
+    for obj in objects {
+        match (obj.props.class.as_str(), obj.props.subclass.as_str()) {
+            ("Geometry", "Mesh") => check_mesh(obj, &mut issues),
+            ("Model", _) => check_model(obj, &mut issues),
+            _ => {},
+        }
+    }
+    check_skins(objects, connections, &mut issues);
+    issues
+}
+
+
+/// Creates an issue for the object.
+fn issue(obj: &GenericObject, kind: ImportIssueKind) -> ImportIssue {
+    ImportIssue {
+        object_id: obj.props.id,
+        object_name: obj.props.name.clone(),
+        kind,
+    }
+}
+
+
+/// Checks a mesh geometry.
+fn check_mesh(obj: &GenericObject, issues: &mut Vec<ImportIssue>) {
+    // Engines limit the number of vertices after splitting, not the number of control points.
+    let num_vertices = num_split_vertices(obj).unwrap_or(0);
+    if num_vertices > MAX_VERTICES_16BIT_INDEX {
+        issues.push(issue(
+            obj,
+            ImportIssueKind::TooManyVertices { count: num_vertices },
+        ));
+    }
+
+    let has_smoothing = obj.nodes.iter().any(|node| {
+        node.name == b"LayerElementSmoothing" || node.name == b"LayerElementNormal"
+    });
+    if !has_smoothing {
+        issues.push(issue(obj, ImportIssueKind::MissingSmoothing));
+    }
+}
+
+
+/// Checks a model.
+fn check_model(obj: &GenericObject, issues: &mut Vec<ImportIssue>) {
+    let scale = match obj.properties.values_f64_3.get(property_names::LCL_SCALING) {
+        Some(v) => *v.value(),
+        None => return,
+    };
+    let is_negative = scale.iter().any(|&v| v < 0.0);
+    let is_uniform = scale[0] == scale[1] && scale[1] == scale[2];
+    if is_negative && !is_uniform {
+        issues.push(issue(
+            obj,
+            ImportIssueKind::NegativeNonUniformScale { scale },
+        ));
+    }
+}
+
+
+/// Checks the number of bones affecting each vertex of skins.
+fn check_skins(
+    objects: &[GenericObject],
+    connections: &Connections,
+    issues: &mut Vec<ImportIssue>,
+) {
+    // Number of bones for each vertex, for each skin.
+    let mut bones = FnvHashMap::<i64, FnvHashMap<i32, usize>>::default();
+    let clusters = objects.iter().filter(|obj| {
+        obj.props.class == "Deformer" && obj.props.subclass == "Cluster"
+    });
+    for cluster in clusters {
+        let skin = match connections.parent_object(cluster.props.id) {
+            Some(v) => v,
+            None => continue,
+        };
+        let indices = cluster
+            .nodes
+            .iter()
+            .find(|node| node.name == b"Indexes")
+            .and_then(|node| match node.attributes.first() {
+                Some(OwnedAttribute::ArrI32(v)) => Some(v),
+                _ => None,
+            });
+        if let Some(indices) = indices {
+            let counts = bones.entry(skin).or_default();
+            for &index in indices.iter() {
+                *counts.entry(index).or_insert(0) += 1;
+            }
+        }
+    }
+    for skin in objects.iter().filter(|obj| bones.contains_key(&obj.props.id)) {
+        let counts = &bones[&skin.props.id];
+        let num_vertices = counts.values().filter(|&&c| c > MAX_BONES_PER_VERTEX).count();
+        if num_vertices > 0 {
+            let max_bones = counts.values().cloned().max().unwrap_or(0);
+            issues.push(issue(
+                skin,
+                ImportIssueKind::TooManyBonesPerVertex {
+                    max_bones,
+                    num_vertices,
+                },
+            ));
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::GenericNode;
+    use crate::fbx7400::{Connection, ObjectProperties, PropertyValue};
+    use super::*;
+
+    fn object(id: i64, class: &str, subclass: &str) -> GenericObject {
+        GenericObject::new(ObjectProperties {
+            id,
+            name: format!("obj{}", id),
+            class: class.to_owned(),
+            subclass: subclass.to_owned(),
+        })
+    }
+
+    fn node(
+        name: &str,
+        attributes: Vec<OwnedAttribute>,
+        children: Vec<GenericNode>,
+    ) -> GenericNode {
+        GenericNode {
+            name: name.as_bytes().to_vec(),
+            attributes,
+            children,
+        }
+    }
+
+    fn connection(source: i64, destination: i64) -> Connection {
+        Connection {
+            source,
+            destination,
+            property: None,
+            source_is_prop: false,
+            destination_is_prop: false,
+            extra_attributes: Vec::new(),
+        }
+    }
+
+    /// Creates a mesh of `num_triangles` triangles sharing the same three control points, with
+    /// normals.
+    fn mesh(id: i64, num_triangles: usize) -> GenericObject {
+        let mut obj = object(id, "Geometry", "Mesh");
+        let vertices = vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0];
+        let pvi: Vec<i32> = (0..num_triangles).flat_map(|_| vec![0, 1, !2]).collect();
+        obj.nodes.push(node("Vertices", vec![OwnedAttribute::from(vertices)], vec![]));
+        obj.nodes.push(node("PolygonVertexIndex", vec![OwnedAttribute::from(pvi)], vec![]));
+        obj.nodes.push(node(
+            "LayerElementNormal",
+            vec![OwnedAttribute::I32(0)],
+            vec![
+                node("MappingInformationType", vec![OwnedAttribute::from("AllSame")], vec![]),
+                node("ReferenceInformationType", vec![OwnedAttribute::from("Direct")], vec![]),
+                node("Normals", vec![OwnedAttribute::from(vec![0.0, 0.0, 1.0])], vec![]),
+            ],
+        ));
+        obj
+    }
+
+    fn kinds(issues: &[ImportIssue]) -> Vec<&ImportIssueKind> {
+        issues.iter().map(|issue| &issue.kind).collect()
+    }
+
+    #[test]
+    fn too_many_vertices_counts_split_vertices() {
+        let num_triangles = 22_000;
+        let shared = mesh(1, num_triangles);
+        let no_connections = Connections(Vec::new());
+        assert!(check_objects(::std::slice::from_ref(&shared), &no_connections).is_empty());
+
+        // Three control points, but distinct UVs for each polygon vertex.
+        let mut split = shared;
+        let uvs: Vec<f64> = (0..num_triangles * 3).flat_map(|i| vec![i as f64, 0.0]).collect();
+        split.nodes.push(node(
+            "LayerElementUV",
+            vec![OwnedAttribute::I32(0)],
+            vec![
+                node(
+                    "MappingInformationType",
+                    vec![OwnedAttribute::from("ByPolygonVertex")],
+                    vec![],
+                ),
+                node("ReferenceInformationType", vec![OwnedAttribute::from("Direct")], vec![]),
+                node("UV", vec![OwnedAttribute::from(uvs)], vec![]),
+            ],
+        ));
+        let issues = check_objects(&[split], &Connections(Vec::new()));
+        assert_eq!(
+            kinds(&issues),
+            vec![&ImportIssueKind::TooManyVertices { count: num_triangles * 3 }]
+        );
+        assert!(issues[0].to_string().contains("66000 vertices"));
+    }
+
+    #[test]
+    fn negative_non_uniform_scale() {
+        let mut uniform = object(1, "Model", "Mesh");
+        uniform
+            .properties
+            .values_f64_3
+            .insert(property_names::LCL_SCALING.to_owned(), PropertyValue::new([-2.0; 3]));
+        let mut non_uniform = object(2, "Model", "Mesh");
+        non_uniform.properties.values_f64_3.insert(
+            property_names::LCL_SCALING.to_owned(),
+            PropertyValue::new([-1.0, 1.0, 1.0]),
+        );
+        let issues = check_objects(&[uniform, non_uniform], &Connections(Vec::new()));
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].object_id, 2);
+        assert_eq!(
+            issues[0].kind,
+            ImportIssueKind::NegativeNonUniformScale { scale: [-1.0, 1.0, 1.0] }
+        );
+    }
+
+    #[test]
+    fn missing_smoothing() {
+        let mut obj = mesh(1, 1);
+        obj.nodes.retain(|node| node.name != b"LayerElementNormal");
+        let issues = check_objects(&[obj], &Connections(Vec::new()));
+        assert_eq!(kinds(&issues), vec![&ImportIssueKind::MissingSmoothing]);
+    }
+
+    #[test]
+    fn too_many_bones_per_vertex() {
+        // Vertex 0 is affected by all 6 clusters, vertex 1 by 5, and vertex 2 by 1.
+        let mut objects = vec![object(100, "Deformer", "Skin")];
+        let mut connections = Vec::new();
+        for i in 0..6 {
+            let mut cluster = object(i + 1, "Deformer", "Cluster");
+            let indices = if i == 0 { vec![0, 1, 2] } else if i < 5 { vec![0, 1] } else { vec![0] };
+            cluster.nodes.push(node("Indexes", vec![OwnedAttribute::from(indices)], vec![]));
+            objects.push(cluster);
+            connections.push(connection(i + 1, 100));
+        }
+        let issues = check_objects(&objects, &Connections(connections));
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].object_id, 100);
+        assert_eq!(
+            issues[0].kind,
+            ImportIssueKind::TooManyBonesPerVertex {
+                max_bones: 6,
+                num_vertices: 2,
+            }
+        );
+    }
+}
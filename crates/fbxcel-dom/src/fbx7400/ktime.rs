@@ -0,0 +1,179 @@
+//! FBX time values.
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::{Add, Neg, Sub};
+use std::time::Duration;
+use approx::ApproxEq;
+use pull_parser::{Attribute, ParserSource};
+use pull_parser::Result as ParseResult;
+use pull_parser::utils::AttributeValue;
+use crate::fbx7400::{FromPropertyValue, PropertyRef, TimeMode};
+
+
+/// Number of ticks in a second.
+pub const TICKS_PER_SECOND: i64 = 46_186_158_000;
+
+/// Number of ticks in a millisecond.
+const TICKS_PER_MILLISECOND: i64 = TICKS_PER_SECOND / 1000;
+
+
+/// Time in FBX ticks (`KTime` in the FBX SDK).
+///
+/// A second is `TICKS_PER_SECOND` ticks, which is divisible by all the standard frame rates.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct KTime(pub i64);
+
+impl KTime {
+    /// Zero.
+    pub const ZERO: KTime = KTime(0);
+
+    /// One second.
+    pub const SECOND: KTime = KTime(TICKS_PER_SECOND);
+
+    /// Creates a time from seconds, rounded to the nearest tick.
+    pub fn from_seconds(seconds: f64) -> Self {
+        KTime((seconds * TICKS_PER_SECOND as f64).round() as i64)
+    }
+
+    /// Returns the time in seconds.
+    pub fn as_seconds(self) -> f64 {
+        self.0 as f64 / TICKS_PER_SECOND as f64
+    }
+
+    /// Creates a time from the duration, rounded down to a tick.
+    ///
+    /// Returns `None` if the duration is out of range.
+    pub fn from_duration(duration: Duration) -> Option<Self> {
+        let secs = i64::try_from(duration.as_secs()).ok()?;
+        let subsec = i64::from(duration.subsec_nanos()) * TICKS_PER_MILLISECOND / 1_000_000;
+        secs.checked_mul(TICKS_PER_SECOND)?.checked_add(subsec).map(KTime)
+    }
+
+    /// Returns the time as a duration, rounded down to a nanosecond.
+    ///
+    /// Returns `None` if the time is negative.
+    pub fn to_duration(self) -> Option<Duration> {
+        if self.0 < 0 {
+            return None;
+        }
+        let secs = self.0 / TICKS_PER_SECOND;
+        let nanos = (self.0 % TICKS_PER_SECOND) * 1_000_000 / TICKS_PER_MILLISECOND;
+        Some(Duration::new(secs as u64, nanos as u32))
+    }
+
+    /// Creates a time from the (possibly fractional) frame number at the given frame rate.
+    pub fn from_frames(frames: f64, frame_rate: f64) -> Self {
+        Self::from_seconds(frames / frame_rate)
+    }
+
+    /// Returns the (possibly fractional) frame number at the given frame rate.
+    pub fn frames(self, frame_rate: f64) -> f64 {
+        self.as_seconds() * frame_rate
+    }
+
+    /// Creates a time from the frame number in the given time mode.
+    ///
+    /// Returns `None` if the time mode has no fixed frame rate (see `TimeMode::frame_rate()`).
+    pub fn from_frame(frame: f64, mode: TimeMode) -> Option<Self> {
+        mode.frame_rate().map(|rate| Self::from_frames(frame, rate))
+    }
+
+    /// Returns the frame number in the given time mode.
+    ///
+    /// Returns `None` if the time mode has no fixed frame rate (see `TimeMode::frame_rate()`).
+    pub fn frame(self, mode: TimeMode) -> Option<f64> {
+        mode.frame_rate().map(|rate| self.frames(rate))
+    }
+}
+
+impl From<i64> for KTime {
+    fn from(ticks: i64) -> Self {
+        KTime(ticks)
+    }
+}
+
+impl From<KTime> for i64 {
+    fn from(time: KTime) -> Self {
+        time.0
+    }
+}
+
+impl Add for KTime {
+    type Output = KTime;
+
+    fn add(self, rhs: KTime) -> KTime {
+        KTime(self.0 + rhs.0)
+    }
+}
+
+impl Sub for KTime {
+    type Output = KTime;
+
+    fn sub(self, rhs: KTime) -> KTime {
+        KTime(self.0 - rhs.0)
+    }
+}
+
+impl Neg for KTime {
+    type Output = KTime;
+
+    fn neg(self) -> KTime {
+        KTime(-self.0)
+    }
+}
+
+impl fmt::Display for KTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}s", self.as_seconds())
+    }
+}
+
+impl ApproxEq for KTime {
+    fn approx_eq(&self, other: &Self, _epsilon: f64) -> bool {
+        self == other
+    }
+}
+
+impl AttributeValue for KTime {
+    fn from_attribute<R>(attr: Attribute<R>) -> ParseResult<Option<Self>>
+    where
+        R: ParserSource,
+    {
+        i64::from_attribute(attr).map(|v| v.map(KTime))
+    }
+
+    fn from_attribute_loose<R>(attr: Attribute<R>) -> ParseResult<Option<Self>>
+    where
+        R: ParserSource,
+    {
+        i64::from_attribute_loose(attr).map(|v| v.map(KTime))
+    }
+}
+
+impl FromPropertyValue for KTime {
+    const TYPE_NAME: &'static str = "KTime";
+
+    fn from_property_value(value: PropertyRef) -> Option<Self> {
+        i64::from_property_value(value).map(KTime)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(KTime::from_seconds(1.5), KTime(TICKS_PER_SECOND * 3 / 2));
+        assert_eq!(KTime::SECOND.as_seconds(), 1.0);
+        let duration = Duration::from_millis(2500);
+        assert_eq!(KTime::from_duration(duration).unwrap().to_duration(), Some(duration));
+        assert_eq!(KTime(-1).to_duration(), None);
+        let half = KTime(TICKS_PER_SECOND / 2);
+        assert_eq!(KTime::from_frame(12.0, TimeMode::Frames24), Some(half));
+        assert_eq!(KTime::SECOND.frame(TimeMode::Pal), Some(25.0));
+        assert_eq!(KTime::SECOND.frame(TimeMode::Custom), None);
+    }
+}
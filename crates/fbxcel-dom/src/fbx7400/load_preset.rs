@@ -0,0 +1,110 @@
+//! Preset filters of objects to load.
+
+use pull_parser::SubtreeParser;
+use crate::Result;
+use crate::fbx7400::{LoadObjects7400, NodesBeforeObjects, ObjectProperties};
+
+
+/// Preset of objects to load, for common use cases.
+///
+/// Objects not accepted by the preset are skipped without being parsed (the seekable source
+/// skips them without reading).
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LoadPreset {
+    /// Loads all objects.
+    #[default]
+    Full,
+    /// Loads models and animation objects (stacks, layers, curve nodes, and curves).
+    AnimationOnly,
+    /// Loads models, geometries, materials, textures, and videos.
+    GeometryOnly,
+    /// Loads skeleton models and node attributes (limb nodes, nulls, and roots), deformers, and
+    /// poses.
+    SkeletonOnly,
+    /// Loads no objects, i.e. only the sections other than `Objects` are loaded.
+    MetadataOnly,
+}
+
+impl LoadPreset {
+    /// Returns `true` if the preset accepts the object.
+    pub fn accepts(&self, props: &ObjectProperties) -> bool {
+        let is_skeleton = matches!(props.subclass.as_str(), "LimbNode" | "Null" | "Root");
+        match *self {
+            LoadPreset::Full => true,
+            LoadPreset::AnimationOnly => {
+                matches!(
+                    props.class.as_str(),
+                    "Model" | "AnimationStack" | "AnimationLayer" | "AnimationCurveNode" |
+                        "AnimationCurve"
+                )
+            },
+            LoadPreset::GeometryOnly => {
+                matches!(
+                    props.class.as_str(),
+                    "Model" | "Geometry" | "Material" | "Texture" | "Video"
+                )
+            },
+            LoadPreset::SkeletonOnly => {
+                match props.class.as_str() {
+                    "Model" | "NodeAttribute" => is_skeleton,
+                    "Deformer" | "Pose" => true,
+                    _ => false,
+                }
+            },
+            LoadPreset::MetadataOnly => false,
+        }
+    }
+}
+
+
+/// Objects loader which passes only the objects accepted by the preset to the inner loader.
+#[derive(Debug, Clone)]
+pub struct FilteredObjectsLoader<L> {
+    /// Inner loader.
+    inner: L,
+    /// Preset.
+    preset: LoadPreset,
+    /// Number of skipped objects.
+    num_skipped: usize,
+}
+
+impl<L: LoadObjects7400> FilteredObjectsLoader<L> {
+    /// Creates a new `FilteredObjectsLoader`.
+    pub fn new(inner: L, preset: LoadPreset) -> Self {
+        FilteredObjectsLoader {
+            inner,
+            preset,
+            num_skipped: 0,
+        }
+    }
+
+    /// Returns the number of skipped objects so far.
+    pub fn num_skipped(&self) -> usize {
+        self.num_skipped
+    }
+}
+
+impl<L: LoadObjects7400> LoadObjects7400 for FilteredObjectsLoader<L> {
+    type Reader = L::Reader;
+    type Objects = L::Objects;
+
+    fn build(self) -> Result<Self::Objects> {
+        debug!("Skipped {} objects by the load preset {:?}", self.num_skipped, self.preset);
+        self.inner.build()
+    }
+
+    fn load(
+        &mut self,
+        props: ObjectProperties,
+        subtree_parser: &mut SubtreeParser<Self::Reader>,
+        nodes_before_objects: &NodesBeforeObjects,
+    ) -> Result<()> {
+        if self.preset.accepts(&props) {
+            self.inner.load(props, subtree_parser, nodes_before_objects)
+        } else {
+            // The object node is skipped by the caller.
+            self.num_skipped += 1;
+            Ok(())
+        }
+    }
+}
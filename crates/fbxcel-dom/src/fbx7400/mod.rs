@@ -0,0 +1,996 @@
+//! Simple FBX 7.4 binary loader.
+
+use std::time::Instant;
+use fnv::FnvHashSet;
+use pull_parser::{Parser, ParserSource, FbxFooter, Event, Attributes};
+use crate::{Result, Error, GenericNode, LoadProfile, NodeTiming, OwnedAttribute};
+use approx::ApproxEq;
+use memory::MemoryUsage;
+pub use self::audio::{Audio, AudioLayer};
+pub use self::axis_conversion::{convert_scene, convert_to_maya};
+pub use self::animation::{CurveNodeDefaults, CurveExtrapolation, Extrapolation};
+pub use self::animation::{ExtrapolationMode, ExtrapolatedTime};
+pub use self::animation::{CurveKeys, KeyAttribute, Interpolation, TangentMode};
+pub use self::animation::{bake_properties, PropertyTrack};
+pub use self::connections::{Connections, ConnectionsBuilder, Connection, CurveChannel};
+pub use self::connections::{ConnectionGraph, DanglingConnection};
+pub use self::definitions::{Definitions, ObjectType};
+pub use self::document::{Fbx7400Document, DocumentListener};
+pub use self::fbx_header_extension::{FbxHeaderExtension, CreationTimeStamp, SceneInfo};
+pub use self::geometry::{Mesh, ResolvedMesh, Submesh};
+pub use self::global_settings::{AxisSystem, GlobalSettings, SignedAxis, TimeMode};
+pub use self::handedness::flip_handedness;
+pub use self::ktime::KTime;
+pub use self::load_preset::{LoadPreset, FilteredObjectsLoader};
+pub use self::object_map::{DuplicateIdPolicy, DuplicateObject, ObjectMap};
+pub use self::objects::{LoadObjects7400, ObjectProperties};
+pub use self::objects::{GenericObject, GenericObjectsLoader};
+pub use self::objects::{TypedObject, TypedObjectsLoader};
+pub use self::properties70::{Properties70, PropertyMap, PropertyValue};
+pub use self::properties70::{FromPropertyValue, PropertyRef};
+pub use self::property_defaults::ResolvedProperties;
+pub use self::registry::{ObjectLoader, ObjectRegistry, LoadedObject, RegistryObjectsLoader};
+pub use self::skin::{normalize_skin_weights, SkinWeightReport};
+pub use self::takes::{Takes, Take};
+pub use self::units::{set_unit_scale, unit_scale_factor};
+pub use self::video::{Video, MediaKind};
+
+
+/// Tries to load the node attributes for parsing a child node.
+///
+/// The type of `$parser` should be `P: Parser<R> where R: ParserSource`, and
+/// the type of `$load_attr` should be `F: FnOnce(&str, &mut Attributes) -> Result<T, _>`.
+///
+/// This will returns from the parent function on errors.
+macro_rules! try_get_node_attrs {
+    ($parser:expr, $load_attr:expr) => {{
+        use $crate::pull_parser::Event;
+        match $parser.next_event()? {
+            Event::StartNode(info) => {
+                let name = info.name()?;
+                $load_attr(name, info.attributes)?
+            },
+            Event::EndNode => break,
+            ev => panic!("Unexpected node event: {:?}", ev),
+        }
+    }}
+}
+
+
+/// Unwraps `$node_op` or returns `Error::MissingNode` error.
+macro_rules! ensure_node_exists {
+    ($node_opt:expr, $parent:expr, $child:expr) => {
+        $node_opt.ok_or_else(|| {
+            $crate::Error::missing_node($parent, $child)
+        })?
+    };
+}
+
+
+/// Defines an enum of child nodes and its `load()` function for `try_get_node_attrs!`.
+///
+/// With `EnumName(Other) { ... }`, unknown child nodes are loaded as
+/// `Other(name, attributes)` instead of `Error::UnexpectedNode`.
+macro_rules! child_attr_loader {
+    ($enum_name:ident($other:ident) {
+        $($node_name:expr => $variant:ident$(($content:ty))* $(=> $load:block)*),*,
+    }) => {
+        #[derive(Debug)]
+        enum $enum_name {
+            $($variant$(($content))*),*,
+            $other(String, Vec<$crate::OwnedAttribute>),
+        }
+        impl $enum_name {
+            pub fn load<R>(name: &str, mut attrs: Attributes<R>)
+                -> $crate::Result<Self>
+                where R: ParserSource
+            {
+                use pull_parser::utils::AttributeValues;
+
+                match name {
+                    $($node_name => child_attr_loader!{
+                        @load $enum_name; name; attrs; $variant$(($content))*; $(=> $load)*
+                    }),*,
+                    _ => {
+                        let attrs = $crate::OwnedAttribute::load_attrs_from_parser_event(
+                            attrs
+                        )?;
+                        Ok($enum_name::$other(name.to_owned(), attrs))
+                    },
+                }
+            }
+        }
+    };
+    (@load $enum_name:ident; $_name:ident; $_attrs:ident; $variant:ident($content:ty);
+        => $load:block $(=> $_rest_load:block)*) => {
+        $load
+    };
+    (@load $enum_name:ident; $name:ident; $attrs:ident; $variant:ident($content:ty);) => {
+        <$content>::from_attributes(&mut $attrs)
+            ?
+            .ok_or_else(|| Error::InvalidAttribute($name.to_owned()))
+            .map($enum_name::$variant)
+    };
+    (@load $enum_name:ident; $name:ident; $attrs:ident; $variant:ident;) => {
+        Ok($enum_name::$variant)
+    };
+    ($enum_name:ident {
+        $($node_name:expr => $variant:ident$(($content:ty))* $(=> $load:block)*),*,
+    }) => {
+        #[derive(Debug)]
+        enum $enum_name {
+            $($variant$(($content))*),*,
+        }
+        impl $enum_name {
+            pub fn load<R>(name: &str, mut attrs: Attributes<R>)
+                -> $crate::Result<Self>
+                where R: ParserSource
+            {
+                use pull_parser::utils::AttributeValues;
+
+                match name {
+                    $($node_name => child_attr_loader!{
+                        @load $enum_name; name; attrs; $variant$(($content))*; $(=> $load)*
+                    }),*,
+                    _ => Err(Error::UnexpectedNode(name.to_owned())),
+                }
+            }
+        }
+    };
+}
+
+
+/// Defines typed property getters on a type which has the `get(name, f)` and `get_ref(name)`
+/// methods.
+///
+/// The signature of `get` should be
+/// `fn get<T, F>(&self, name: &str, f: F) -> Option<&T> where F: Fn(&Properties70) ->
+/// &PropertyMap<T>`, and of `get_ref` should be
+/// `fn get_ref(&self, name: &str) -> Option<PropertyRef>`.
+macro_rules! impl_typed_property_getters {
+    () => {
+        /// Looks up and returns the property value converted into the given type.
+        ///
+        /// Numeric values are converted between compatible types (see `FromPropertyValue`).
+        /// Returns `Ok(None)` if the property does not exist, and
+        /// `Err(Error::IncompatibleProperty { .. })` if the value cannot be converted.
+        pub fn get_as<T>(&self, name: &str) -> $crate::Result<Option<T>>
+        where
+            T: $crate::fbx7400::FromPropertyValue,
+        {
+            self.get_ref(name).map_or(Ok(None), |v| v.coerce(name).map(Some))
+        }
+
+        /// Looks up and returns the `i64` property value.
+        ///
+        /// Returns `None` if the property does not exist or cannot be converted.
+        pub fn get_i64(&self, name: &str) -> Option<i64> {
+            self.get_as(name).unwrap_or(None)
+        }
+
+        /// Looks up and returns the `bool` property value, which is stored as a number.
+        pub fn get_bool(&self, name: &str) -> Option<bool> {
+            self.get_as(name).unwrap_or(None)
+        }
+
+        /// Looks up and returns the enum property value, which is stored as an integer.
+        ///
+        /// Returns `None` if the value is not valid for the enum type.
+        pub fn get_enum<E>(&self, name: &str) -> Option<E>
+        where
+            E: ::std::convert::TryFrom<i64>,
+        {
+            self.get_i64(name).and_then(|v| E::try_from(v).ok())
+        }
+
+        /// Looks up and returns the `f64` property value.
+        pub fn get_f64(&self, name: &str) -> Option<f64> {
+            self.get_as(name).unwrap_or(None)
+        }
+
+        /// Looks up and returns the `[f64; 2]` property value.
+        pub fn get_vec2(&self, name: &str) -> Option<[f64; 2]> {
+            self.get_as(name).unwrap_or(None)
+        }
+
+        /// Looks up and returns the `[f64; 3]` property value.
+        pub fn get_vec3(&self, name: &str) -> Option<[f64; 3]> {
+            self.get_as(name).unwrap_or(None)
+        }
+
+        /// Looks up and returns the `[f64; 4]` property value.
+        pub fn get_vec4(&self, name: &str) -> Option<[f64; 4]> {
+            self.get_as(name).unwrap_or(None)
+        }
+
+        /// Looks up and returns the RGB color property value.
+        ///
+        /// The alpha component is dropped if the property has one (`ColorAndAlpha`).
+        pub fn get_color(&self, name: &str) -> Option<[f64; 3]> {
+            use $crate::fbx7400::PropertyRef;
+
+            match self.get_ref(name)? {
+                PropertyRef::F64x3(c) => Some(*c),
+                PropertyRef::F64x4(c) => Some([c[0], c[1], c[2]]),
+                _ => None,
+            }
+        }
+
+        /// Looks up and returns the `[[f64; 4]; 4]` property value.
+        pub fn get_matrix(&self, name: &str) -> Option<[[f64; 4]; 4]> {
+            self.get_as(name).unwrap_or(None)
+        }
+
+        /// Looks up and returns the string property value.
+        pub fn get_string(&self, name: &str) -> Option<&str> {
+            self.get(name, |p| &p.values_string).map(|s| s.as_str())
+        }
+
+        /// Looks up and returns the binary property value.
+        pub fn get_binary(&self, name: &str) -> Option<&[u8]> {
+            self.get(name, |p| &p.values_binary).map(|v| v.as_slice())
+        }
+    };
+}
+
+
+pub mod animation;
+pub mod audio;
+pub mod axis_conversion;
+pub mod connections;
+pub mod definitions;
+pub mod document;
+pub mod fbx_header_extension;
+pub mod geometry;
+pub mod global_settings;
+pub mod handedness;
+pub mod import_checks;
+pub mod ktime;
+pub mod load_preset;
+pub mod node_versions;
+pub mod object_map;
+pub mod objects;
+pub mod properties70;
+pub mod property_defaults;
+pub mod property_names;
+pub mod registry;
+pub mod schema;
+pub mod schema_inference;
+pub mod skin;
+pub mod takes;
+pub mod units;
+pub mod video;
+
+
+/// FBX 7.4 or later.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fbx7400<O: LoadObjects7400> {
+    /// FBX version.
+    pub version: u32,
+    /// `FBXHeaderExtension`.
+    pub fbx_header_extension: FbxHeaderExtension,
+    /// `FileId`.
+    pub file_id: FileId,
+    /// `CreationTime`.
+    pub creation_time: CreationTime,
+    /// `Creator`.
+    pub creator: Creator,
+    /// `References`.
+    pub references: References,
+    /// `GlobalSettings`.
+    pub global_settings: GlobalSettings,
+    /// `Documents`.
+    pub documents: Documents,
+    /// `Definitions`.
+    pub definitions: Definitions,
+    /// `Objects`.
+    pub objects: O::Objects,
+    /// `Connections`.
+    pub connections: Connections,
+    /// `Takes`.
+    pub takes: Option<Takes>,
+    /// FBX footer.
+    pub footer: Option<FbxFooter>,
+}
+
+impl<O: LoadObjects7400> Fbx7400<O> {
+    /// Loads FBX 7400 (or later) structure from the given parser.
+    ///
+    /// Toplevel nodes are accepted in any order.
+    /// See `NodesBeforeObjects` for objects appearing before the other toplevel nodes.
+    pub fn load_from_parser<P>(version: u32, parser: P, objs_loader: O) -> Result<Self>
+    where
+        P: Parser<O::Reader>,
+    {
+        Self::load_from_parser_impl(version, parser, objs_loader, None)
+    }
+
+    /// Loads FBX 7400 (or later) structure from the given parser, recording time spent to load
+    /// each node.
+    pub fn load_from_parser_profiled<P>(
+        version: u32,
+        parser: P,
+        objs_loader: O,
+    ) -> Result<(Self, LoadProfile)>
+    where
+        P: Parser<O::Reader>,
+    {
+        let mut profile = LoadProfile::new();
+        let fbx = Self::load_from_parser_impl(version, parser, objs_loader, Some(&mut profile))?;
+        Ok((fbx, profile))
+    }
+
+    /// Loads FBX 7400 (or later) structure from the given parser.
+    ///
+    /// If `profile` is given, time spent to load each node is recorded to it.
+    fn load_from_parser_impl<P>(
+        version: u32,
+        mut parser: P,
+        objs_loader: O,
+        mut profile: Option<&mut LoadProfile>,
+    ) -> Result<Self>
+    where
+        P: Parser<O::Reader>,
+    {
+        info!("FBX version: {}, loading in FBX 7400 mode", version);
+
+        let mut objs_loader = Some(objs_loader);
+        let footer;
+        let mut fbx_header_extension = None;
+        let mut file_id = None;
+        let mut creation_time = None;
+        let mut creator = None;
+        let mut global_settings = None;
+        let mut documents = None;
+        let mut references = None;
+        let mut definitions = None;
+        let mut objects = None;
+        let mut connections = None;
+        let mut takes = None;
+        loop {
+            let (node_type, started) = match parser.next_event()? {
+                Event::StartFbx(_) |
+                Event::EndNode => unreachable!(),
+                Event::EndFbx(f) => {
+                    footer = f.ok();
+                    break;
+                },
+                Event::StartNode(info) => {
+                    let name = info.name()?;
+                    let started = profile.as_ref().map(|_| (name.to_owned(), Instant::now()));
+                    (NodeType::load(name, info.attributes)?, started)
+                },
+            };
+            debug!("node_type: {:?}", node_type);
+            match node_type {
+                NodeType::FbxHeaderExtension => {
+                    fbx_header_extension = Some(FbxHeaderExtension::load(parser.subtree_parser())?);
+                },
+                NodeType::FileId(attrs) => {
+                    file_id = Some(FileId::load(parser.subtree_parser(), attrs)?);
+                },
+                NodeType::CreationTime(attrs) => {
+                    creation_time = Some(CreationTime::load(parser.subtree_parser(), attrs)?);
+                },
+                NodeType::Creator(attrs) => {
+                    creator = Some(Creator::load(parser.subtree_parser(), attrs)?);
+                },
+                NodeType::GlobalSettings => {
+                    global_settings = Some(GlobalSettings::load(parser.subtree_parser())?);
+                },
+                NodeType::Documents => {
+                    documents = Some(Documents::load(parser.subtree_parser())?);
+                },
+                NodeType::References => {
+                    references = Some(References::load(parser.subtree_parser())?);
+                },
+                NodeType::Definitions => {
+                    definitions = Some(Definitions::load(parser.subtree_parser())?);
+                },
+                NodeType::Objects => {
+                    if let Some(objs_loader) = objs_loader.take() {
+                        let nodes_before_objects = NodesBeforeObjects {
+                            version,
+                            fbx_header_extension: fbx_header_extension.clone().unwrap_or_default(),
+                            file_id: file_id.clone().unwrap_or_default(),
+                            creation_time: creation_time.clone().unwrap_or_default(),
+                            creator: creator.clone().unwrap_or_default(),
+                            global_settings: global_settings.clone().unwrap_or_default(),
+                            documents: documents.clone().unwrap_or_default(),
+                            references: references.clone().unwrap_or_default(),
+                            definitions: definitions.clone().unwrap_or_default(),
+                        };
+                        let missing = [
+                            ("FBXHeaderExtension", fbx_header_extension.is_none()),
+                            ("FileId", file_id.is_none()),
+                            ("CreationTime", creation_time.is_none()),
+                            ("Creator", creator.is_none()),
+                            ("GlobalSettings", global_settings.is_none()),
+                            ("Documents", documents.is_none()),
+                            ("References", references.is_none()),
+                            ("Definitions", definitions.is_none()),
+                        ];
+                        for &(name, _) in missing.iter().filter(|&&(_, is_missing)| is_missing) {
+                            warn!(
+                                "`Objects` appears before `{}`, loading objects with empty `{}`",
+                                name,
+                                name
+                            );
+                        }
+                        objects = Some(load_objects(
+                            parser.subtree_parser(),
+                            objs_loader,
+                            &nodes_before_objects,
+                            profile.as_deref_mut(),
+                        )?);
+                    } else {
+                        warn!("Multiple `Objects` node found, ignoring.");
+                    }
+                },
+                NodeType::Connections => {
+                    connections = Some(Connections::load(parser.subtree_parser())?);
+                },
+                NodeType::Takes => {
+                    takes = Some(Takes::load(parser.subtree_parser())?);
+                },
+            }
+            if let (Some(profile), Some((name, started))) = (profile.as_mut(), started) {
+                profile.toplevel.push(NodeTiming {
+                    name,
+                    object_id: None,
+                    duration: started.elapsed(),
+                });
+            }
+        }
+
+        Ok(Fbx7400 {
+            version,
+            fbx_header_extension: ensure_node_exists!(
+                fbx_header_extension,
+                "(root)",
+                "FBXHeaderExtension"
+            ),
+            file_id: ensure_node_exists!(file_id, "(root)", "FileId"),
+            creation_time: ensure_node_exists!(creation_time, "(root)", "CreationTime"),
+            creator: ensure_node_exists!(creator, "(root)", "Creator"),
+            global_settings: ensure_node_exists!(global_settings, "(root)", "GlobalSettings"),
+            documents: ensure_node_exists!(documents, "(root)", "Documents"),
+            references: ensure_node_exists!(references, "(root)", "References"),
+            definitions: ensure_node_exists!(definitions, "(root)", "Definitions"),
+            objects: ensure_node_exists!(objects, "(root)", "Objects"),
+            connections: ensure_node_exists!(connections, "(root)", "Connections"),
+            takes,
+            footer,
+        })
+    }
+
+    /// Returns the ID of the document the given object belongs to.
+    ///
+    /// The document is found by following OO connections from the object to the root node of a
+    /// document.
+    /// If an object has multiple parents, the first connection is used.
+    /// Returns `None` if the object is not reachable from any document root.
+    pub fn document_of(&self, object_id: i64) -> Option<i64> {
+        let documents = self.documents.documents();
+        let mut visited = FnvHashSet::default();
+        let mut current = object_id;
+        loop {
+            if let Some(doc) = documents.iter().find(|doc| doc.root_node == current) {
+                return Some(doc.id);
+            }
+            if !visited.insert(current) {
+                // Connections have a cycle.
+                return None;
+            }
+            current = self.connections.parent_object(current)?;
+        }
+    }
+}
+
+
+/// Toplevel nodes before `Objects`.
+///
+/// These nodes would be referred by objects loader.
+/// Usually all of them precede `Objects`, but some exporters write them in unusual order.
+/// In such case, nodes not yet loaded are empty (`Default::default()`) here, and the nodes
+/// loaded afterward are available only in the resulting `Fbx7400`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodesBeforeObjects {
+    /// FBX version.
+    pub version: u32,
+    /// `FBXHeaderExtension`.
+    pub fbx_header_extension: FbxHeaderExtension,
+    /// `FileId`.
+    pub file_id: FileId,
+    /// `CreationTime`.
+    pub creation_time: CreationTime,
+    /// `Creator`.
+    pub creator: Creator,
+    /// `References`.
+    pub references: References,
+    /// `GlobalSettings`.
+    pub global_settings: GlobalSettings,
+    /// `Documents`.
+    pub documents: Documents,
+    /// `Definitions`.
+    pub definitions: Definitions,
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum NodeType {
+    FbxHeaderExtension,
+    FileId(Vec<u8>),
+    CreationTime(String),
+    Creator(String),
+    GlobalSettings,
+    Documents,
+    References,
+    Definitions,
+    Objects,
+    Connections,
+    Takes,
+}
+
+impl NodeType {
+    /// Creates `NodeType` from the given node name.
+    pub fn load<R>(name: &str, mut attrs: Attributes<R>) -> Result<Self>
+    where
+        R: ParserSource,
+    {
+        use pull_parser::utils::AttributeValues;
+
+        match name {
+            "FBXHeaderExtension" => Ok(NodeType::FbxHeaderExtension),
+            "FileId" => {
+                <Vec<u8>>::from_attributes(&mut attrs)?
+                    .ok_or_else(|| Error::InvalidAttribute(name.to_owned()))
+                    .map(NodeType::FileId)
+            },
+            "CreationTime" => {
+                <String>::from_attributes(&mut attrs)?
+                    .ok_or_else(|| Error::InvalidAttribute(name.to_owned()))
+                    .map(NodeType::CreationTime)
+            },
+            "Creator" => {
+                <String>::from_attributes(&mut attrs)?
+                    .ok_or_else(|| Error::InvalidAttribute(name.to_owned()))
+                    .map(NodeType::Creator)
+            },
+            "GlobalSettings" => Ok(NodeType::GlobalSettings),
+            "Documents" => Ok(NodeType::Documents),
+            "References" => Ok(NodeType::References),
+            "Definitions" => Ok(NodeType::Definitions),
+            "Objects" => Ok(NodeType::Objects),
+            "Connections" => Ok(NodeType::Connections),
+            "Takes" => Ok(NodeType::Takes),
+            _ => Err(Error::UnexpectedNode(name.to_owned())),
+        }
+    }
+}
+
+
+/// `FileId`.
+#[derive(Default, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FileId(pub Vec<u8>);
+
+impl FileId {
+    /// Loads node contents from the parser.
+    pub fn load<R, P>(mut parser: P, attrs: Vec<u8>) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        parser.skip_current_node()?;
+        Ok(FileId(attrs))
+    }
+}
+
+
+/// `CreationTime`.
+#[derive(Default, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CreationTime(pub String);
+
+impl CreationTime {
+    /// Loads node contents from the parser.
+    pub fn load<R, P>(mut parser: P, attrs: String) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        parser.skip_current_node()?;
+        Ok(CreationTime(attrs))
+    }
+}
+
+
+/// `Creator`.
+#[derive(Default, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Creator(pub String);
+
+impl Creator {
+    /// Loads node contents from the parser.
+    pub fn load<R, P>(mut parser: P, attrs: String) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        parser.skip_current_node()?;
+        Ok(Creator(attrs))
+    }
+}
+
+
+/// `Documents`.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct Documents {
+    /// Child nodes.
+    pub nodes: Vec<GenericNode>,
+}
+
+impl Documents {
+    /// Loads node contents from the parser.
+    pub fn load<R, P>(mut parser: P) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        let nodes = GenericNode::load_from_parser(&mut parser)?.0;
+        Ok(Documents { nodes })
+    }
+
+    /// Creates `Documents` with the given `Document` entries.
+    ///
+    /// `Count` is set to the number of the entries.
+    pub fn from_documents(documents: &[DocumentInfo]) -> Self {
+        let count = GenericNode {
+            name: b"Count".to_vec(),
+            attributes: vec![OwnedAttribute::I32(documents.len() as i32)],
+            children: Vec::new(),
+        };
+        let nodes = ::std::iter::once(count)
+            .chain(documents.iter().map(DocumentInfo::to_node))
+            .collect();
+        Documents { nodes }
+    }
+
+    /// Returns the `Document` entries.
+    ///
+    /// `Document` nodes with unexpected attributes are ignored.
+    pub fn documents(&self) -> Vec<DocumentInfo> {
+        self.nodes
+            .iter()
+            .filter(|node| node.name == b"Document")
+            .filter_map(DocumentInfo::from_node)
+            .collect()
+    }
+
+    /// Returns the `Documents` node to be written.
+    pub fn to_node(&self) -> GenericNode {
+        GenericNode {
+            name: b"Documents".to_vec(),
+            attributes: Vec::new(),
+            children: self.nodes.clone(),
+        }
+    }
+}
+
+
+/// `Document` entry of `Documents`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DocumentInfo {
+    /// Document ID.
+    pub id: i64,
+    /// Document name.
+    pub name: String,
+    /// Object ID of the root node of the document.
+    pub root_node: i64,
+}
+
+impl DocumentInfo {
+    /// Creates a `DocumentInfo` from the given `Document` node.
+    fn from_node(node: &GenericNode) -> Option<Self> {
+        let id = match node.attributes.first() {
+            Some(&OwnedAttribute::I64(v)) => v,
+            _ => return None,
+        };
+        let name = match node.attributes.get(1) {
+            Some(&OwnedAttribute::String(Ok(ref v))) => v.clone(),
+            _ => return None,
+        };
+        // Root node is the implicit scene root (`0`) if not specified.
+        let root_node = node.children_by_name("RootNode")
+            .next()
+            .and_then(|child| match child.attributes.first() {
+                Some(&OwnedAttribute::I64(v)) => Some(v),
+                _ => None,
+            })
+            .unwrap_or(0);
+        Some(DocumentInfo {
+            id,
+            name,
+            root_node,
+        })
+    }
+
+    /// Returns the `Document` node of the entry.
+    ///
+    /// The node has the `"Scene"` class and `RootNode` child, as written by the official SDK.
+    pub fn to_node(&self) -> GenericNode {
+        GenericNode {
+            name: b"Document".to_vec(),
+            attributes: vec![
+                OwnedAttribute::I64(self.id),
+                OwnedAttribute::String(Ok(self.name.clone())),
+                OwnedAttribute::String(Ok("Scene".to_owned())),
+            ],
+            children: vec![GenericNode {
+                name: b"RootNode".to_vec(),
+                attributes: vec![OwnedAttribute::I64(self.root_node)],
+                children: Vec::new(),
+            }],
+        }
+    }
+}
+
+
+/// `References`.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct References {
+    /// Child nodes.
+    pub nodes: Vec<GenericNode>,
+}
+
+impl References {
+    /// Loads node contents from the parser.
+    pub fn load<R, P>(mut parser: P) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        let nodes = GenericNode::load_from_parser(&mut parser)?.0;
+        Ok(References { nodes })
+    }
+}
+
+
+/// Returns `Option<(name: &'a str, class: &'a str)>`
+pub fn separate_name_class(name_class: &str) -> Option<(&str, &str)> {
+    name_class.find("\u{0}\u{1}").map(|sep_pos| {
+        (&name_class[0..sep_pos], &name_class[sep_pos + 2..])
+    })
+}
+
+
+/// Loads node contents from the parser.
+fn load_objects<R, P, O>(
+    mut parser: P,
+    mut objs_loader: O,
+    nodes_before_objects: &NodesBeforeObjects,
+    mut profile: Option<&mut LoadProfile>,
+) -> Result<O::Objects>
+where
+    R: ParserSource,
+    P: Parser<R>,
+    O: LoadObjects7400<Reader = R>,
+{
+    loop {
+        let started = Instant::now();
+        let (props, name) = try_get_node_attrs!(parser, |name: &str, attrs| -> Result<_> {
+            let props = ObjectProperties::load(name, attrs)?;
+            Ok((props, profile.as_ref().map(|_| name.to_owned())))
+        });
+        let object_id = props.id;
+        let mut sub_parser = parser.subtree_parser();
+        objs_loader.load(
+            props,
+            &mut sub_parser,
+            nodes_before_objects,
+        )?;
+        sub_parser.skip_to_end()?;
+        if let (Some(profile), Some(name)) = (profile.as_mut(), name) {
+            profile.objects.push(NodeTiming {
+                name,
+                object_id: Some(object_id),
+                duration: started.elapsed(),
+            });
+        }
+    }
+    objs_loader.build()
+}
+
+
+impl<O> MemoryUsage for Fbx7400<O>
+where
+    O: LoadObjects7400,
+    O::Objects: MemoryUsage,
+{
+    fn heap_usage(&self) -> usize {
+        self.fbx_header_extension.heap_usage() +
+            self.file_id.heap_usage() +
+            self.creation_time.heap_usage() +
+            self.creator.heap_usage() +
+            self.references.heap_usage() +
+            self.global_settings.heap_usage() +
+            self.documents.heap_usage() +
+            self.definitions.heap_usage() +
+            self.objects.heap_usage() +
+            self.connections.heap_usage() +
+            self.takes.heap_usage()
+    }
+}
+
+
+impl MemoryUsage for FileId {
+    fn heap_usage(&self) -> usize {
+        self.0.heap_usage()
+    }
+}
+
+
+impl MemoryUsage for CreationTime {
+    fn heap_usage(&self) -> usize {
+        self.0.heap_usage()
+    }
+}
+
+
+impl MemoryUsage for Creator {
+    fn heap_usage(&self) -> usize {
+        self.0.heap_usage()
+    }
+}
+
+
+impl MemoryUsage for Documents {
+    fn heap_usage(&self) -> usize {
+        self.nodes.heap_usage()
+    }
+}
+
+
+impl MemoryUsage for References {
+    fn heap_usage(&self) -> usize {
+        self.nodes.heap_usage()
+    }
+}
+
+
+impl ApproxEq for Documents {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.nodes.approx_eq(&other.nodes, epsilon)
+    }
+}
+
+
+impl ApproxEq for References {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.nodes.approx_eq(&other.nodes, epsilon)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use pull_parser::BasicSource;
+    use super::*;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_loaded_types_are_send_sync() {
+        type R = BasicSource<Cursor<Vec<u8>>>;
+
+        assert_send_sync::<Fbx7400<GenericObjectsLoader<R>>>();
+        assert_send_sync::<Fbx7400<RegistryObjectsLoader<R>>>();
+        assert_send_sync::<ObjectRegistry<R>>();
+        assert_send_sync::<GenericObject>();
+        assert_send_sync::<LoadedObject>();
+        assert_send_sync::<Connections>();
+        assert_send_sync::<ResolvedMesh>();
+        assert_send_sync::<CurveKeys>();
+    }
+
+    #[test]
+    fn test_load_7500() {
+        use crate::{Error, FbxLoader};
+        use pull_parser::RootParser;
+        use pull_parser::ascii::AsciiDocument;
+
+        let source = "; FBX 7.5.0 project file\n\
+                      Objects:  {\n\tModel: 1, \"Model::Cube\", \"Mesh\" {\n\t}\n}\n";
+        let bytes = AsciiDocument::parse(source).unwrap().to_binary().unwrap();
+        let parser = RootParser::new(Cursor::new(&bytes));
+        match FbxLoader::load_from_parser(parser).unwrap() {
+            FbxLoader::Fbx7400(loader) => assert_eq!(loader.fbx_version(), 7500),
+            loader => panic!("Unexpected loader for {}", loader.fbx_version()),
+        }
+        let doc = Fbx7400Document::load(RootParser::new(Cursor::new(&bytes))).unwrap();
+        assert_eq!(doc.version, 7500);
+        assert_eq!(doc.objects.unwrap()[0].props.name, "Cube");
+
+        // Version in the header.
+        let mut bytes = bytes;
+        bytes[23..27].copy_from_slice(&7000u32.to_le_bytes());
+        match FbxLoader::load_from_parser(RootParser::new(Cursor::new(&bytes))) {
+            Err(Error::UnsupportedVersion(7000)) => {},
+            res => panic!("Unexpected result: {:?}", res.map(|l| l.fbx_version())),
+        }
+    }
+
+    #[test]
+    fn test_load_objects_before_definitions() {
+        use crate::FbxLoader;
+        use pull_parser::RootParser;
+        use pull_parser::ascii::AsciiDocument;
+
+        let source = r#"; FBX 7.4.0 project file
+Connections:  {
+	C: "OO",1,0
+}
+Objects:  {
+	Model: 1, "Model::Cube", "Mesh" {
+	}
+}
+FBXHeaderExtension:  {
+	FBXHeaderVersion: 1003
+	FBXVersion: 7400
+	EncryptionType: 0
+	CreationTimeStamp:  {
+		Version: 1000
+		Year: 2020
+		Month: 1
+		Day: 1
+		Hour: 0
+		Minute: 0
+		Second: 0
+		Millisecond: 0
+	}
+	Creator: "test"
+	SceneInfo: "SceneInfo::GlobalInfo", "UserData" {
+		Type: "UserData"
+		Version: 100
+		MetaData:  {
+			Version: 100
+			Title: ""
+			Subject: ""
+			Author: ""
+			Keywords: ""
+			Revision: ""
+			Comment: ""
+		}
+		Properties70:  {
+		}
+	}
+}
+FileId: "id"
+CreationTime: "2020-01-01 00:00:00:000"
+Creator: "test"
+GlobalSettings:  {
+	Version: 1000
+	Properties70:  {
+	}
+}
+Documents:  {
+}
+References:  {
+}
+Definitions:  {
+	Version: 100
+	Count: 1
+}
+"#;
+        let bytes = AsciiDocument::parse(source).unwrap().to_binary().unwrap();
+        let parser = RootParser::new(Cursor::new(&bytes));
+        let fbx = match FbxLoader::load_from_parser(parser).unwrap() {
+            FbxLoader::Fbx7400(loader) => loader.load(GenericObjectsLoader::new()).unwrap(),
+            loader => panic!("Unexpected loader for {}", loader.fbx_version()),
+        };
+        assert_eq!(fbx.objects[0].props.name, "Cube");
+        assert_eq!(fbx.definitions.count, 1);
+        assert_eq!(fbx.creator.0, "test");
+        assert_eq!(fbx.connections.parent_object(1), Some(0));
+    }
+
+}
@@ -0,0 +1,144 @@
+//! Known values of `Version` child nodes.
+//!
+//! Many nodes have `Version` child nodes, and the layout of some nodes depends on the version.
+//! Unknown versions are kept as raw values, and loaders should treat them with care.
+
+use crate::{GenericNode, OwnedAttribute};
+
+
+macro_rules! known_versions {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident {
+            $($(#[$var_meta:meta])* $variant:ident = $value:expr,)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($(#[$var_meta])* $variant,)*
+            /// Unknown version.
+            Unknown(i32),
+        }
+
+        impl $name {
+            /// Creates a value from the raw version number.
+            pub fn from_raw(raw: i32) -> Self {
+                match raw {
+                    $($value => $name::$variant,)*
+                    v => $name::Unknown(v),
+                }
+            }
+
+            /// Returns the raw version number.
+            pub fn raw(&self) -> i32 {
+                match *self {
+                    $($name::$variant => $value,)*
+                    $name::Unknown(v) => v,
+                }
+            }
+
+            /// Returns whether the version is known.
+            pub fn is_known(&self) -> bool {
+                match *self {
+                    $name::Unknown(_) => false,
+                    _ => true,
+                }
+            }
+
+            /// Reads the version from the `Version` child node of the given node.
+            ///
+            /// Returns `None` if the node has no valid `Version` child.
+            pub fn of_node(node: &GenericNode) -> Option<Self> {
+                version_of_node(node).map(Self::from_raw)
+            }
+        }
+    }
+}
+
+
+known_versions! {
+    /// Version of `FBXHeaderExtension` (`FBXHeaderVersion` node).
+    pub enum FbxHeaderVersion {
+        /// 1003.
+        V1003 = 1003,
+    }
+}
+
+known_versions! {
+    /// Version of `CreationTimeStamp`.
+    pub enum CreationTimeStampVersion {
+        /// 1000.
+        V1000 = 1000,
+    }
+}
+
+known_versions! {
+    /// Version of `GlobalSettings`.
+    pub enum GlobalSettingsVersion {
+        /// 1000.
+        V1000 = 1000,
+    }
+}
+
+known_versions! {
+    /// Version of `Definitions`.
+    pub enum DefinitionsVersion {
+        /// 100.
+        V100 = 100,
+    }
+}
+
+known_versions! {
+    /// Version of `Model` objects.
+    pub enum ModelVersion {
+        /// 232.
+        V232 = 232,
+    }
+}
+
+known_versions! {
+    /// Version of `Geometry` objects.
+    pub enum GeometryVersion {
+        /// 124.
+        V124 = 124,
+    }
+}
+
+known_versions! {
+    /// Version of `LayerElement*` nodes.
+    pub enum LayerElementVersion {
+        /// 100.
+        V100 = 100,
+        /// 101.
+        V101 = 101,
+        /// 102.
+        V102 = 102,
+    }
+}
+
+impl LayerElementVersion {
+    /// Returns whether the layer elements have W components array next to the data array (such
+    /// as `NormalsW` of `LayerElementNormal`), with one value for each element.
+    ///
+    /// Older layouts have no W components, and they should be treated as `1.0`.
+    pub fn has_w_components(&self) -> bool {
+        match *self {
+            LayerElementVersion::V100 | LayerElementVersion::V101 => false,
+            LayerElementVersion::V102 => true,
+            LayerElementVersion::Unknown(v) => v > 102,
+        }
+    }
+}
+
+
+/// Reads the raw version from the `Version` child node of the given node.
+fn version_of_node(node: &GenericNode) -> Option<i32> {
+    node.children_by_name("Version")
+        .next()
+        .and_then(|child| match child.attributes.first() {
+            Some(&OwnedAttribute::I32(v)) => Some(v),
+            Some(&OwnedAttribute::I16(v)) => Some(i32::from(v)),
+            _ => None,
+        })
+}
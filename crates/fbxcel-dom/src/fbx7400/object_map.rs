@@ -0,0 +1,183 @@
+//! Map of objects keyed by object IDs.
+//!
+//! Object IDs should be unique in a document, but some buggy exporters write multiple objects
+//! with the same ID.
+//! `DuplicateIdPolicy` specifies how such objects are handled, so that no object is lost
+//! silently.
+
+use fnv::{FnvHashMap, FnvHashSet};
+use crate::{Error, Result};
+use crate::fbx7400::GenericObject;
+
+
+/// Policy for objects with duplicate IDs.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DuplicateIdPolicy {
+    /// Returns `Err(Error::DuplicateObjectId(_))`.
+    #[default]
+    Error,
+    /// Keeps the first object and drops the later ones.
+    KeepFirst,
+    /// Keeps all objects, assigning unused IDs to the later ones.
+    ///
+    /// Connections still refer to the first object with the ID.
+    Reassign,
+}
+
+
+/// Object with a duplicate ID.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DuplicateObject {
+    /// Index of the object in the original objects.
+    pub index: usize,
+    /// ID in the file.
+    pub original_id: i64,
+    /// Newly assigned ID, or `None` if the object is dropped.
+    pub new_id: Option<i64>,
+    /// Name of the object.
+    pub name: String,
+    /// Class of the object.
+    pub class: String,
+}
+
+
+/// Objects keyed by object IDs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectMap {
+    /// Objects.
+    objects: Vec<GenericObject>,
+    /// Indices of the objects keyed by IDs.
+    indices: FnvHashMap<i64, usize>,
+    /// Objects with duplicate IDs.
+    duplicates: Vec<DuplicateObject>,
+}
+
+impl ObjectMap {
+    /// Creates a new `ObjectMap` from the objects, handling duplicate IDs by the policy.
+    pub fn new(objects: Vec<GenericObject>, policy: DuplicateIdPolicy) -> Result<Self> {
+        let used_ids = objects.iter().map(|obj| obj.props.id).collect::<FnvHashSet<_>>();
+        let mut next_id = used_ids.iter().cloned().max().unwrap_or(0).wrapping_add(1);
+        let mut map = ObjectMap {
+            objects: Vec::with_capacity(objects.len()),
+            indices: FnvHashMap::default(),
+            duplicates: Vec::new(),
+        };
+        for (index, mut obj) in objects.into_iter().enumerate() {
+            let id = obj.props.id;
+            if !map.indices.contains_key(&id) {
+                map.indices.insert(id, map.objects.len());
+                map.objects.push(obj);
+                continue;
+            }
+            let new_id = match policy {
+                DuplicateIdPolicy::Error => return Err(Error::DuplicateObjectId(id)),
+                DuplicateIdPolicy::KeepFirst => None,
+                DuplicateIdPolicy::Reassign => {
+                    // `0` is the implicit scene root.
+                    while next_id == 0 || used_ids.contains(&next_id) ||
+                        map.indices.contains_key(&next_id)
+                    {
+                        next_id = next_id.wrapping_add(1);
+                    }
+                    Some(next_id)
+                },
+            };
+            warn!(
+                "Duplicate object ID {} (name={:?}, class={:?}), new ID: {:?}",
+                id,
+                obj.props.name,
+                obj.props.class,
+                new_id
+            );
+            map.duplicates.push(DuplicateObject {
+                index,
+                original_id: id,
+                new_id,
+                name: obj.props.name.clone(),
+                class: obj.props.class.clone(),
+            });
+            if let Some(new_id) = new_id {
+                obj.props.id = new_id;
+                map.indices.insert(new_id, map.objects.len());
+                map.objects.push(obj);
+            }
+        }
+        Ok(map)
+    }
+
+    /// Returns the object with the given ID.
+    pub fn get(&self, id: i64) -> Option<&GenericObject> {
+        self.indices.get(&id).map(|&index| &self.objects[index])
+    }
+
+    /// Returns the mutable reference to the object with the given ID.
+    ///
+    /// Note that the ID of the object should not be changed.
+    pub fn get_mut(&mut self, id: i64) -> Option<&mut GenericObject> {
+        let index = *self.indices.get(&id)?;
+        Some(&mut self.objects[index])
+    }
+
+    /// Returns the number of objects.
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    /// Returns `true` if there are no objects.
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    /// Returns the objects, in the original order.
+    pub fn objects(&self) -> &[GenericObject] {
+        &self.objects
+    }
+
+    /// Returns the objects with duplicate IDs, which are dropped or given new IDs.
+    pub fn duplicates(&self) -> &[DuplicateObject] {
+        &self.duplicates
+    }
+
+    /// Returns the objects.
+    pub fn into_objects(self) -> Vec<GenericObject> {
+        self.objects
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::Error;
+    use crate::fbx7400::{GenericObject, ObjectProperties};
+    use super::*;
+
+    fn object(id: i64, name: &str) -> GenericObject {
+        GenericObject::new(ObjectProperties {
+            id,
+            name: name.to_owned(),
+            class: "Model".to_owned(),
+            subclass: "Null".to_owned(),
+        })
+    }
+
+    #[test]
+    fn duplicate_ids() {
+        let objects = vec![object(1, "a"), object(2, "b"), object(1, "c")];
+
+        match ObjectMap::new(objects.clone(), DuplicateIdPolicy::Error) {
+            Err(Error::DuplicateObjectId(1)) => {},
+            res => panic!("Unexpected result: {:?}", res),
+        }
+
+        let map = ObjectMap::new(objects.clone(), DuplicateIdPolicy::KeepFirst).unwrap();
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get(1).unwrap().props.name, "a");
+        assert_eq!(map.duplicates()[0].new_id, None);
+
+        let map = ObjectMap::new(objects, DuplicateIdPolicy::Reassign).unwrap();
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.duplicates()[0].index, 2);
+        assert_eq!(map.duplicates()[0].new_id, Some(3));
+        assert_eq!(map.get(3).unwrap().props.name, "c");
+    }
+}
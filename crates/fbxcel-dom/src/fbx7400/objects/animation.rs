@@ -0,0 +1,324 @@
+//! `AnimationStack`, `AnimationLayer`, `AnimationCurveNode`, and `AnimationCurve` objects.
+
+use std::mem;
+use pull_parser::{Parser, ParserSource, Attributes};
+use crate::{Result, Error, GenericNode};
+use crate::fbx7400::{KTime, ObjectProperties, Properties70};
+use crate::fbx7400::{CurveExtrapolation, CurveKeys, CurveNodeDefaults, KeyAttribute};
+use crate::fbx7400::objects::{load_child_node, GenericObject};
+use crate::fbx7400::property_names;
+use approx::ApproxEq;
+use memory::MemoryUsage;
+
+
+/// Default weight of an animation layer, in percent.
+pub const DEFAULT_LAYER_WEIGHT: f64 = 100.0;
+
+
+/// `AnimationStack` object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationStack {
+    /// Properties common to object nodes.
+    pub props: ObjectProperties,
+    /// `Properties70`.
+    pub properties: Properties70,
+    /// Other child nodes.
+    pub nodes: Vec<GenericNode>,
+}
+
+impl AnimationStack {
+    /// Loads node contents from the parser.
+    pub fn load<R, P>(parser: P, props: ObjectProperties) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        let obj = GenericObject::load(parser, props)?;
+        Ok(AnimationStack {
+            props: obj.props,
+            properties: obj.properties,
+            nodes: obj.nodes,
+        })
+    }
+
+    /// Returns the start time of the stack.
+    pub fn local_start(&self) -> Option<KTime> {
+        self.properties.get_as(property_names::LOCAL_START).unwrap_or(None)
+    }
+
+    /// Returns the stop time of the stack.
+    pub fn local_stop(&self) -> Option<KTime> {
+        self.properties.get_as(property_names::LOCAL_STOP).unwrap_or(None)
+    }
+
+    /// Returns the reference start time of the stack.
+    pub fn reference_start(&self) -> Option<KTime> {
+        self.properties.get_as(property_names::REFERENCE_START).unwrap_or(None)
+    }
+
+    /// Returns the reference stop time of the stack.
+    pub fn reference_stop(&self) -> Option<KTime> {
+        self.properties.get_as(property_names::REFERENCE_STOP).unwrap_or(None)
+    }
+}
+
+
+/// `AnimationLayer` object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationLayer {
+    /// Properties common to object nodes.
+    pub props: ObjectProperties,
+    /// `Properties70`.
+    pub properties: Properties70,
+    /// Other child nodes.
+    pub nodes: Vec<GenericNode>,
+}
+
+impl AnimationLayer {
+    /// Loads node contents from the parser.
+    pub fn load<R, P>(parser: P, props: ObjectProperties) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        let obj = GenericObject::load(parser, props)?;
+        Ok(AnimationLayer {
+            props: obj.props,
+            properties: obj.properties,
+            nodes: obj.nodes,
+        })
+    }
+
+    /// Returns the weight of the layer in percent.
+    ///
+    /// Returns `DEFAULT_LAYER_WEIGHT` if the property is absent.
+    pub fn weight(&self) -> f64 {
+        self.properties.get_f64(property_names::WEIGHT).unwrap_or(DEFAULT_LAYER_WEIGHT)
+    }
+}
+
+
+/// `AnimationCurveNode` object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationCurveNode {
+    /// Properties common to object nodes.
+    pub props: ObjectProperties,
+    /// `Properties70`.
+    pub properties: Properties70,
+    /// Other child nodes.
+    pub nodes: Vec<GenericNode>,
+}
+
+impl AnimationCurveNode {
+    /// Loads node contents from the parser.
+    pub fn load<R, P>(parser: P, props: ObjectProperties) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        let obj = GenericObject::load(parser, props)?;
+        Ok(AnimationCurveNode {
+            props: obj.props,
+            properties: obj.properties,
+            nodes: obj.nodes,
+        })
+    }
+
+    /// Returns the default values of the channels.
+    pub fn defaults(&self) -> CurveNodeDefaults {
+        CurveNodeDefaults::from_properties(&self.properties)
+    }
+}
+
+
+/// `AnimationCurve` object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationCurve {
+    /// Properties common to object nodes.
+    pub props: ObjectProperties,
+    /// `Default`.
+    pub default: Option<f64>,
+    /// `KeyVer`.
+    pub key_version: Option<i32>,
+    /// Keys decoded from `KeyTime`, `KeyValueFloat`, `KeyAttrFlags`, `KeyAttrDataFloat`, and
+    /// `KeyAttrRefCount`.
+    pub keys: CurveKeys,
+    /// `Properties70`.
+    pub properties: Properties70,
+    /// Other child nodes.
+    pub nodes: Vec<GenericNode>,
+}
+
+impl AnimationCurve {
+    /// Loads node contents from the parser.
+    ///
+    /// Returns `Err(_)` if the key arrays are inconsistent.
+    pub fn load<R, P>(mut parser: P, props: ObjectProperties) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        let mut default = None;
+        let mut key_version = None;
+        let mut times = Vec::new();
+        let mut values = Vec::new();
+        let mut flags = Vec::new();
+        let mut data = Vec::new();
+        let mut ref_counts = Vec::new();
+        let mut properties = None;
+        let mut nodes = Vec::new();
+
+        loop {
+            let node_type = try_get_node_attrs!(parser, AnimationCurveChildAttrs::load);
+            match node_type {
+                AnimationCurveChildAttrs::Default(v) => {
+                    default = Some(v);
+                    parser.skip_current_node()?;
+                },
+                AnimationCurveChildAttrs::KeyVer(v) => {
+                    key_version = Some(v);
+                    parser.skip_current_node()?;
+                },
+                AnimationCurveChildAttrs::KeyTime(v) => {
+                    times = v;
+                    parser.skip_current_node()?;
+                },
+                AnimationCurveChildAttrs::KeyValueFloat(v) => {
+                    values = v;
+                    parser.skip_current_node()?;
+                },
+                AnimationCurveChildAttrs::KeyAttrFlags(v) => {
+                    flags = v;
+                    parser.skip_current_node()?;
+                },
+                AnimationCurveChildAttrs::KeyAttrDataFloat(v) => {
+                    data = v;
+                    parser.skip_current_node()?;
+                },
+                AnimationCurveChildAttrs::KeyAttrRefCount(v) => {
+                    ref_counts = v;
+                    parser.skip_current_node()?;
+                },
+                AnimationCurveChildAttrs::Properties70 => {
+                    properties = Some(Properties70::load(parser.subtree_parser())?);
+                },
+                AnimationCurveChildAttrs::Other(name, attrs) => {
+                    nodes.push(load_child_node(&mut parser, &props.class, name, attrs)?);
+                },
+            }
+        }
+        let keys = CurveKeys::from_arrays(times, values, &flags, &data, &ref_counts)
+            .ok_or_else(|| Error::InvalidAttribute("AnimationCurve".to_owned()))?;
+        Ok(AnimationCurve {
+            props,
+            default,
+            key_version,
+            keys,
+            properties: properties.unwrap_or_default(),
+            nodes,
+        })
+    }
+
+    /// Returns the extrapolation settings of the curve.
+    pub fn extrapolation(&self) -> CurveExtrapolation {
+        CurveExtrapolation::from_nodes(&self.nodes)
+    }
+
+    /// Evaluates the curve at the given time.
+    ///
+    /// Segments are interpolated as specified by `KeyAttrFlags` (constant, linear, or cubic with
+    /// auto, TCB, or user tangents), and the extrapolation is applied outside the keyed range.
+    /// Returns `default` (or `0` if absent) if the curve has no keys.
+    pub fn evaluate(&self, time: KTime) -> f32 {
+        match self.keys.evaluate_extrapolated(time.0, &self.extrapolation()) {
+            Some(v) => v as f32,
+            None => self.default.unwrap_or(0.0) as f32,
+        }
+    }
+}
+
+
+child_attr_loader! { AnimationCurveChildAttrs(Other) {
+    "Default" => Default(f64),
+    "KeyVer" => KeyVer(i32),
+    "KeyTime" => KeyTime(Vec<i64>),
+    "KeyValueFloat" => KeyValueFloat(Vec<f32>),
+    "KeyAttrFlags" => KeyAttrFlags(Vec<i32>),
+    "KeyAttrDataFloat" => KeyAttrDataFloat(Vec<f32>),
+    "KeyAttrRefCount" => KeyAttrRefCount(Vec<i32>),
+    "Properties70" => Properties70,
+}}
+
+
+impl MemoryUsage for AnimationStack {
+    fn heap_usage(&self) -> usize {
+        self.props.heap_usage() + self.properties.heap_usage() + self.nodes.heap_usage()
+    }
+}
+
+
+impl MemoryUsage for AnimationLayer {
+    fn heap_usage(&self) -> usize {
+        self.props.heap_usage() + self.properties.heap_usage() + self.nodes.heap_usage()
+    }
+}
+
+
+impl MemoryUsage for AnimationCurveNode {
+    fn heap_usage(&self) -> usize {
+        self.props.heap_usage() + self.properties.heap_usage() + self.nodes.heap_usage()
+    }
+}
+
+
+impl MemoryUsage for AnimationCurve {
+    fn heap_usage(&self) -> usize {
+        // Key arrays may be shared with other curves, but are counted as owned.
+        let keys = self.keys.times.len() * mem::size_of::<i64>() +
+            self.keys.values.len() * mem::size_of::<f32>() +
+            self.keys.attributes.len() * mem::size_of::<KeyAttribute>();
+        self.props.heap_usage() +
+            keys +
+            self.properties.heap_usage() +
+            self.nodes.heap_usage()
+    }
+}
+
+
+impl ApproxEq for AnimationStack {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.props == other.props &&
+            self.properties.approx_eq(&other.properties, epsilon) &&
+            self.nodes.approx_eq(&other.nodes, epsilon)
+    }
+}
+
+
+impl ApproxEq for AnimationLayer {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.props == other.props &&
+            self.properties.approx_eq(&other.properties, epsilon) &&
+            self.nodes.approx_eq(&other.nodes, epsilon)
+    }
+}
+
+
+impl ApproxEq for AnimationCurveNode {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.props == other.props &&
+            self.properties.approx_eq(&other.properties, epsilon) &&
+            self.nodes.approx_eq(&other.nodes, epsilon)
+    }
+}
+
+
+impl ApproxEq for AnimationCurve {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.props == other.props &&
+            self.default.approx_eq(&other.default, epsilon) &&
+            self.key_version.approx_eq(&other.key_version, epsilon) &&
+            self.keys.approx_eq(&other.keys, epsilon) &&
+            self.properties.approx_eq(&other.properties, epsilon) &&
+            self.nodes.approx_eq(&other.nodes, epsilon)
+    }
+}
@@ -0,0 +1,679 @@
+//! `Deformer` objects of `Skin`, `Cluster`, `BlendShape`, and `BlendShapeChannel` subclasses.
+//!
+//! Clusters are connected to their skin, and each cluster is connected to its bone (link) model.
+//! Likewise, blend shape channels are connected to their blend shape, and `Shape` geometries
+//! (see `objects::geometry::Shape`) are connected to the channels.
+//! Object nodes of clusters and channels have `SubDeformer` as the class in the name, and they
+//! are also loaded as typed objects.
+
+use std::convert::TryFrom;
+use pull_parser::{Parser, ParserSource, Attributes};
+use crate::{Result, Error, GenericNode};
+use crate::fbx7400::{ObjectProperties, Properties70};
+use crate::fbx7400::objects::load_child_node;
+use crate::fbx7400::property_names;
+use approx::ApproxEq;
+use memory::MemoryUsage;
+
+
+/// Skinning type of a skin deformer (`SkinningType`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SkinningType {
+    /// `Rigid`.
+    Rigid,
+    /// `Linear`.
+    Linear,
+    /// `DualQuaternion`.
+    DualQuaternion,
+    /// `Blend`.
+    Blend,
+}
+
+impl SkinningType {
+    /// Parses the value of `SkinningType` node.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "Rigid" => Some(SkinningType::Rigid),
+            "Linear" => Some(SkinningType::Linear),
+            "DualQuaternion" => Some(SkinningType::DualQuaternion),
+            "Blend" => Some(SkinningType::Blend),
+            _ => None,
+        }
+    }
+}
+
+
+/// Link mode of a cluster deformer (`Mode`).
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LinkMode {
+    /// `Normalize`: weights of each vertex are normalized to sum to 1.
+    #[default]
+    Normalize,
+    /// `Additive`: weights are used as is, and the rest is left to the original position.
+    Additive,
+    /// `Total1`: weights of each vertex already sum to 1.
+    TotalOne,
+}
+
+impl LinkMode {
+    /// Parses the value of `Mode` node.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "Normalize" => Some(LinkMode::Normalize),
+            "Additive" => Some(LinkMode::Additive),
+            "Total1" | "TotalOne" => Some(LinkMode::TotalOne),
+            _ => None,
+        }
+    }
+}
+
+
+/// `Deformer` object of `Skin` subclass.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Skin {
+    /// Properties common to object nodes.
+    pub props: ObjectProperties,
+    /// `Version`.
+    pub version: Option<i32>,
+    /// `Link_DeformAcuracy` (sic).
+    pub link_deform_accuracy: Option<f64>,
+    /// `SkinningType`.
+    pub skinning_type: Option<SkinningType>,
+    /// `Properties70`.
+    pub properties: Properties70,
+    /// Other child nodes, including `BlendWeights` of blend skinning.
+    pub nodes: Vec<GenericNode>,
+}
+
+impl Skin {
+    /// Loads node contents from the parser.
+    pub fn load<R, P>(mut parser: P, props: ObjectProperties) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        let mut version = None;
+        let mut link_deform_accuracy = None;
+        let mut skinning_type = None;
+        let mut properties = None;
+        let mut nodes = Vec::new();
+
+        loop {
+            let node_type = try_get_node_attrs!(parser, SkinChildAttrs::load);
+            match node_type {
+                SkinChildAttrs::Version(v) => {
+                    version = Some(v);
+                    parser.skip_current_node()?;
+                },
+                SkinChildAttrs::LinkDeformAccuracy(v) => {
+                    link_deform_accuracy = Some(v);
+                    parser.skip_current_node()?;
+                },
+                SkinChildAttrs::SkinningType(v) => {
+                    skinning_type = Some(SkinningType::parse(&v).ok_or_else(|| {
+                        Error::InvalidAttribute("SkinningType".to_owned())
+                    })?);
+                    parser.skip_current_node()?;
+                },
+                SkinChildAttrs::Properties70 => {
+                    properties = Some(Properties70::load(parser.subtree_parser())?);
+                },
+                SkinChildAttrs::Other(name, attrs) => {
+                    nodes.push(load_child_node(&mut parser, &props.class, name, attrs)?);
+                },
+            }
+        }
+        Ok(Skin {
+            props,
+            version,
+            link_deform_accuracy,
+            skinning_type,
+            properties: properties.unwrap_or_default(),
+            nodes,
+        })
+    }
+}
+
+
+/// `Deformer` object of `Cluster` subclass.
+///
+/// A cluster binds vertices of a geometry to a bone.
+/// Matrices are column major, as matrices in FBX.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cluster {
+    /// Properties common to object nodes.
+    pub props: ObjectProperties,
+    /// `Version`.
+    pub version: Option<i32>,
+    /// `Mode`.
+    ///
+    /// `LinkMode::Normalize` is used if this is `None`.
+    pub link_mode: Option<LinkMode>,
+    /// `Indexes`, control point indices of the geometry.
+    pub indexes: Vec<i32>,
+    /// `Weights`, weight for each element of `indexes`.
+    pub weights: Vec<f64>,
+    /// `Transform`, global transform of the geometry at the binding moment.
+    pub transform: Option<[f64; 16]>,
+    /// `TransformLink`, global transform of the bone at the binding moment.
+    pub transform_link: Option<[f64; 16]>,
+    /// `TransformAssociateModel`, used in `Additive` link mode.
+    pub transform_associate_model: Option<[f64; 16]>,
+    /// `Properties70`.
+    pub properties: Properties70,
+    /// Other child nodes.
+    pub nodes: Vec<GenericNode>,
+}
+
+impl Cluster {
+    /// Loads node contents from the parser.
+    pub fn load<R, P>(mut parser: P, props: ObjectProperties) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        let mut version = None;
+        let mut link_mode = None;
+        let mut indexes = Vec::new();
+        let mut weights = Vec::new();
+        let mut transform = None;
+        let mut transform_link = None;
+        let mut transform_associate_model = None;
+        let mut properties = None;
+        let mut nodes = Vec::new();
+
+        loop {
+            let node_type = try_get_node_attrs!(parser, ClusterChildAttrs::load);
+            match node_type {
+                ClusterChildAttrs::Version(v) => {
+                    version = Some(v);
+                    parser.skip_current_node()?;
+                },
+                ClusterChildAttrs::Mode(v) => {
+                    link_mode = Some(LinkMode::parse(&v).ok_or_else(|| {
+                        Error::InvalidAttribute("Mode".to_owned())
+                    })?);
+                    parser.skip_current_node()?;
+                },
+                ClusterChildAttrs::Indexes(v) => {
+                    indexes = v;
+                    parser.skip_current_node()?;
+                },
+                ClusterChildAttrs::Weights(v) => {
+                    weights = v;
+                    parser.skip_current_node()?;
+                },
+                ClusterChildAttrs::Transform(v) => {
+                    transform = Some(to_matrix("Transform", &v)?);
+                    parser.skip_current_node()?;
+                },
+                ClusterChildAttrs::TransformLink(v) => {
+                    transform_link = Some(to_matrix("TransformLink", &v)?);
+                    parser.skip_current_node()?;
+                },
+                ClusterChildAttrs::TransformAssociateModel(v) => {
+                    transform_associate_model = Some(to_matrix("TransformAssociateModel", &v)?);
+                    parser.skip_current_node()?;
+                },
+                ClusterChildAttrs::Properties70 => {
+                    properties = Some(Properties70::load(parser.subtree_parser())?);
+                },
+                ClusterChildAttrs::Other(name, attrs) => {
+                    nodes.push(load_child_node(&mut parser, &props.class, name, attrs)?);
+                },
+            }
+        }
+        if indexes.len() != weights.len() {
+            return Err(Error::InvalidAttribute("Weights".to_owned()));
+        }
+        Ok(Cluster {
+            props,
+            version,
+            link_mode,
+            indexes,
+            weights,
+            transform,
+            transform_link,
+            transform_associate_model,
+            properties: properties.unwrap_or_default(),
+            nodes,
+        })
+    }
+
+    /// Returns the link mode, defaulting to `LinkMode::Normalize`.
+    pub fn link_mode(&self) -> LinkMode {
+        self.link_mode.unwrap_or_default()
+    }
+
+    /// Returns the pairs of control point index and weight.
+    ///
+    /// Negative indices are skipped.
+    pub fn influences(&self) -> impl Iterator<Item = (usize, f64)> + '_ {
+        self.indexes
+            .iter()
+            .zip(&self.weights)
+            .filter(|&(&index, _)| index >= 0)
+            .map(|(&index, &weight)| (index as usize, weight))
+    }
+}
+
+
+/// `Deformer` object of `BlendShape` subclass.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlendShape {
+    /// Properties common to object nodes.
+    pub props: ObjectProperties,
+    /// `Version`.
+    pub version: Option<i32>,
+    /// `Properties70`.
+    pub properties: Properties70,
+    /// Other child nodes.
+    pub nodes: Vec<GenericNode>,
+}
+
+impl BlendShape {
+    /// Loads node contents from the parser.
+    pub fn load<R, P>(mut parser: P, props: ObjectProperties) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        let mut version = None;
+        let mut properties = None;
+        let mut nodes = Vec::new();
+
+        loop {
+            let node_type = try_get_node_attrs!(parser, BlendShapeChildAttrs::load);
+            match node_type {
+                BlendShapeChildAttrs::Version(v) => {
+                    version = Some(v);
+                    parser.skip_current_node()?;
+                },
+                BlendShapeChildAttrs::Properties70 => {
+                    properties = Some(Properties70::load(parser.subtree_parser())?);
+                },
+                BlendShapeChildAttrs::Other(name, attrs) => {
+                    nodes.push(load_child_node(&mut parser, &props.class, name, attrs)?);
+                },
+            }
+        }
+        Ok(BlendShape {
+            props,
+            version,
+            properties: properties.unwrap_or_default(),
+            nodes,
+        })
+    }
+}
+
+
+/// `Deformer` object of `BlendShapeChannel` subclass.
+///
+/// A channel blends its target shapes by the deform percent (0 to 100).
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlendShapeChannel {
+    /// Properties common to object nodes.
+    pub props: ObjectProperties,
+    /// `Version`.
+    pub version: Option<i32>,
+    /// `DeformPercent`.
+    pub deform_percent: Option<f64>,
+    /// `FullWeights`, deform percent at which each target shape is fully applied.
+    pub full_weights: Vec<f64>,
+    /// `Properties70`.
+    pub properties: Properties70,
+    /// Other child nodes.
+    pub nodes: Vec<GenericNode>,
+}
+
+impl BlendShapeChannel {
+    /// Loads node contents from the parser.
+    pub fn load<R, P>(mut parser: P, props: ObjectProperties) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        let mut version = None;
+        let mut deform_percent = None;
+        let mut full_weights = Vec::new();
+        let mut properties = None;
+        let mut nodes = Vec::new();
+
+        loop {
+            let node_type = try_get_node_attrs!(parser, BlendShapeChannelChildAttrs::load);
+            match node_type {
+                BlendShapeChannelChildAttrs::Version(v) => {
+                    version = Some(v);
+                    parser.skip_current_node()?;
+                },
+                BlendShapeChannelChildAttrs::DeformPercent(v) => {
+                    deform_percent = Some(v);
+                    parser.skip_current_node()?;
+                },
+                BlendShapeChannelChildAttrs::FullWeights(v) => {
+                    full_weights = v;
+                    parser.skip_current_node()?;
+                },
+                BlendShapeChannelChildAttrs::Properties70 => {
+                    properties = Some(Properties70::load(parser.subtree_parser())?);
+                },
+                BlendShapeChannelChildAttrs::Other(name, attrs) => {
+                    nodes.push(load_child_node(&mut parser, &props.class, name, attrs)?);
+                },
+            }
+        }
+        Ok(BlendShapeChannel {
+            props,
+            version,
+            deform_percent,
+            full_weights,
+            properties: properties.unwrap_or_default(),
+            nodes,
+        })
+    }
+
+    /// Returns the deform percent of the channel.
+    ///
+    /// The `DeformPercent` property is preferred to the node, and `0` is returned if both are
+    /// absent.
+    pub fn deform_percent(&self) -> f64 {
+        self.properties
+            .get_f64(property_names::DEFORM_PERCENT)
+            .or(self.deform_percent)
+            .unwrap_or(0.0)
+    }
+}
+
+
+/// Converts the array into a 4x4 matrix.
+fn to_matrix(name: &str, v: &[f64]) -> Result<[f64; 16]> {
+    <[f64; 16]>::try_from(v).map_err(|_| Error::InvalidAttribute(name.to_owned()))
+}
+
+
+child_attr_loader! { SkinChildAttrs(Other) {
+    "Version" => Version(i32),
+    "Link_DeformAcuracy" => LinkDeformAccuracy(f64),
+    "SkinningType" => SkinningType(String),
+    "Properties70" => Properties70,
+}}
+
+
+child_attr_loader! { BlendShapeChildAttrs(Other) {
+    "Version" => Version(i32),
+    "Properties70" => Properties70,
+}}
+
+
+child_attr_loader! { BlendShapeChannelChildAttrs(Other) {
+    "Version" => Version(i32),
+    "DeformPercent" => DeformPercent(f64),
+    "FullWeights" => FullWeights(Vec<f64>),
+    "Properties70" => Properties70,
+}}
+
+
+child_attr_loader! { ClusterChildAttrs(Other) {
+    "Version" => Version(i32),
+    "Mode" => Mode(String),
+    "Indexes" => Indexes(Vec<i32>),
+    "Weights" => Weights(Vec<f64>),
+    "Transform" => Transform(Vec<f64>),
+    "TransformLink" => TransformLink(Vec<f64>),
+    "TransformAssociateModel" => TransformAssociateModel(Vec<f64>),
+    "Properties70" => Properties70,
+}}
+
+
+impl MemoryUsage for Skin {
+    fn heap_usage(&self) -> usize {
+        self.props.heap_usage() +
+            self.properties.heap_usage() +
+            self.nodes.heap_usage()
+    }
+}
+
+
+impl MemoryUsage for Cluster {
+    fn heap_usage(&self) -> usize {
+        self.props.heap_usage() +
+            self.indexes.heap_usage() +
+            self.weights.heap_usage() +
+            self.properties.heap_usage() +
+            self.nodes.heap_usage()
+    }
+}
+
+
+impl MemoryUsage for BlendShape {
+    fn heap_usage(&self) -> usize {
+        self.props.heap_usage() +
+            self.properties.heap_usage() +
+            self.nodes.heap_usage()
+    }
+}
+
+
+impl MemoryUsage for BlendShapeChannel {
+    fn heap_usage(&self) -> usize {
+        self.props.heap_usage() +
+            self.full_weights.heap_usage() +
+            self.properties.heap_usage() +
+            self.nodes.heap_usage()
+    }
+}
+
+
+impl ApproxEq for Skin {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.props == other.props &&
+            self.version.approx_eq(&other.version, epsilon) &&
+            self.link_deform_accuracy.approx_eq(&other.link_deform_accuracy, epsilon) &&
+            self.skinning_type == other.skinning_type &&
+            self.properties.approx_eq(&other.properties, epsilon) &&
+            self.nodes.approx_eq(&other.nodes, epsilon)
+    }
+}
+
+
+impl ApproxEq for Cluster {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.props == other.props &&
+            self.version.approx_eq(&other.version, epsilon) &&
+            self.link_mode == other.link_mode &&
+            self.indexes.approx_eq(&other.indexes, epsilon) &&
+            self.weights.approx_eq(&other.weights, epsilon) &&
+            self.transform.approx_eq(&other.transform, epsilon) &&
+            self.transform_link.approx_eq(&other.transform_link, epsilon) &&
+            self.transform_associate_model.approx_eq(&other.transform_associate_model, epsilon) &&
+            self.properties.approx_eq(&other.properties, epsilon) &&
+            self.nodes.approx_eq(&other.nodes, epsilon)
+    }
+}
+
+
+impl ApproxEq for BlendShape {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.props == other.props &&
+            self.version.approx_eq(&other.version, epsilon) &&
+            self.properties.approx_eq(&other.properties, epsilon) &&
+            self.nodes.approx_eq(&other.nodes, epsilon)
+    }
+}
+
+
+impl ApproxEq for BlendShapeChannel {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.props == other.props &&
+            self.version.approx_eq(&other.version, epsilon) &&
+            self.deform_percent.approx_eq(&other.deform_percent, epsilon) &&
+            self.full_weights.approx_eq(&other.full_weights, epsilon) &&
+            self.properties.approx_eq(&other.properties, epsilon) &&
+            self.nodes.approx_eq(&other.nodes, epsilon)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use pull_parser::{Event, RootParser};
+    use pull_parser::ascii::AsciiDocument;
+    use crate::fbx7400::TypedObject;
+    use super::*;
+
+    /// Loads the children of the toplevel `Objects` node in the given FBX ASCII source.
+    fn load_objects(source: &str) -> Result<Vec<TypedObject>> {
+        let bytes = AsciiDocument::parse(source).unwrap().to_binary().unwrap();
+        let mut parser = RootParser::new(Cursor::new(bytes));
+        let mut objects = Vec::new();
+        loop {
+            match parser.next_event().unwrap() {
+                Event::StartNode(node) => {
+                    let name = node.name().unwrap().to_owned();
+                    if name == "Objects" {
+                        continue;
+                    }
+                    let props = ObjectProperties::load(&name, node.attributes)?;
+                    objects.push(TypedObject::load(parser.subtree_parser(), props)?);
+                },
+                Event::EndFbx(_) => break,
+                _ => {},
+            }
+        }
+        Ok(objects)
+    }
+
+    #[test]
+    fn load_skin_and_cluster() {
+        let source = "; FBX 7.4.0 project file\n\
+                      Objects:  {\n\
+                      \tDeformer: 1, \"Deformer::Skin\", \"Skin\" {\n\
+                      \t\tVersion: 101\n\
+                      \t\tLink_DeformAcuracy: 50\n\
+                      \t\tSkinningType: \"DualQuaternion\"\n\
+                      \t}\n\
+                      \tDeformer: 2, \"SubDeformer::Bone\", \"Cluster\" {\n\
+                      \t\tVersion: 100\n\
+                      \t\tIndexes: *3 {\n\t\t\ta: 0,1,3\n\t\t}\n\
+                      \t\tWeights: *3 {\n\t\t\ta: 1,0.25,0.75\n\t\t}\n\
+                      \t\tTransform: *16 {\n\t\t\ta: 1,0,0,0,0,1,0,0,0,0,1,0,3,0,0,1\n\t\t}\n\
+                      \t\tTransformLink: *16 {\n\t\t\ta: 1,0,0,0,0,1,0,0,0,0,1,0,0,0,4,1\n\t\t}\n\
+                      \t}\n\
+                      }\n";
+        let objects = load_objects(source).unwrap();
+        assert_eq!(objects.len(), 2);
+        match objects[0] {
+            TypedObject::Skin(ref skin) => {
+                assert_eq!(skin.props.id, 1);
+                assert_eq!(skin.version, Some(101));
+                assert_eq!(skin.link_deform_accuracy, Some(50.0));
+                assert_eq!(skin.skinning_type, Some(SkinningType::DualQuaternion));
+            },
+            ref obj => panic!("Unexpected object: {:?}", obj),
+        }
+        match objects[1] {
+            TypedObject::Cluster(ref cluster) => {
+                assert_eq!(cluster.props.class, "SubDeformer");
+                assert_eq!(cluster.props.name, "Bone");
+                assert_eq!(cluster.version, Some(100));
+                assert_eq!(cluster.link_mode(), LinkMode::Normalize);
+                let influences = cluster.influences().collect::<Vec<_>>();
+                assert_eq!(influences, vec![(0, 1.0), (1, 0.25), (3, 0.75)]);
+                assert_eq!(cluster.transform.map(|m| m[12]), Some(3.0));
+                assert_eq!(cluster.transform_link.map(|m| m[14]), Some(4.0));
+                assert_eq!(cluster.transform_associate_model, None);
+            },
+            ref obj => panic!("Unexpected object: {:?}", obj),
+        }
+    }
+
+    #[test]
+    fn reject_invalid_cluster() {
+        let cluster = |body: &str| {
+            format!(
+                "; FBX 7.4.0 project file\nObjects:  {{\n\
+                 \tDeformer: 1, \"SubDeformer::\", \"Cluster\" {{\n{}\t}}\n}}\n",
+                body
+            )
+        };
+        let matrix_too_short = "\t\tTransform: *3 {\n\t\t\ta: 1,0,0\n\t\t}\n";
+        match load_objects(&cluster(matrix_too_short)) {
+            Err(Error::InvalidAttribute(ref name)) => assert_eq!(name, "Transform"),
+            res => panic!("Unexpected result: {:?}", res),
+        }
+        let weights_mismatch = "\t\tIndexes: *2 {\n\t\t\ta: 0,1\n\t\t}\n\
+                                \t\tWeights: *1 {\n\t\t\ta: 1\n\t\t}\n";
+        match load_objects(&cluster(weights_mismatch)) {
+            Err(Error::InvalidAttribute(ref name)) => assert_eq!(name, "Weights"),
+            res => panic!("Unexpected result: {:?}", res),
+        }
+        let unknown_mode = "\t\tMode: \"Unknown\"\n";
+        match load_objects(&cluster(unknown_mode)) {
+            Err(Error::InvalidAttribute(ref name)) => assert_eq!(name, "Mode"),
+            res => panic!("Unexpected result: {:?}", res),
+        }
+    }
+
+    #[test]
+    fn load_blend_shape() {
+        let source = "; FBX 7.4.0 project file\n\
+                      Objects:  {\n\
+                      \tDeformer: 1, \"Deformer::Face\", \"BlendShape\" {\n\
+                      \t\tVersion: 100\n\
+                      \t}\n\
+                      \tDeformer: 2, \"SubDeformer::Smile\", \"BlendShapeChannel\" {\n\
+                      \t\tVersion: 100\n\
+                      \t\tDeformPercent: 25\n\
+                      \t\tFullWeights: *2 {\n\t\t\ta: 50,100\n\t\t}\n\
+                      \t\tProperties70:  {\n\
+                      \t\t\tP: \"DeformPercent\", \"Number\", \"\", \"A\",75.5\n\
+                      \t\t}\n\
+                      \t}\n\
+                      \tDeformer: 3, \"SubDeformer::Blink\", \"BlendShapeChannel\" {\n\
+                      \t\tDeformPercent: 25\n\
+                      \t}\n\
+                      \tGeometry: 4, \"Geometry::Smile\", \"Shape\" {\n\
+                      \t\tVersion: 100\n\
+                      \t\tIndexes: *1 {\n\t\t\ta: 7\n\t\t}\n\
+                      \t\tVertices: *3 {\n\t\t\ta: 0,1.5,0\n\t\t}\n\
+                      \t\tNormals: *3 {\n\t\t\ta: 0,0,-0.5\n\t\t}\n\
+                      \t}\n\
+                      }\n";
+        let objects = load_objects(source).unwrap();
+        assert_eq!(objects.len(), 4);
+        match objects[0] {
+            TypedObject::BlendShape(ref shape) => {
+                assert_eq!(shape.props.name, "Face");
+                assert_eq!(shape.version, Some(100));
+            },
+            ref obj => panic!("Unexpected object: {:?}", obj),
+        }
+        match objects[1] {
+            TypedObject::BlendShapeChannel(ref channel) => {
+                assert_eq!(channel.props.class, "SubDeformer");
+                assert_eq!(channel.deform_percent, Some(25.0));
+                // The property takes precedence over the node.
+                assert_eq!(channel.deform_percent(), 75.5);
+                assert_eq!(channel.full_weights, vec![50.0, 100.0]);
+            },
+            ref obj => panic!("Unexpected object: {:?}", obj),
+        }
+        match objects[2] {
+            TypedObject::BlendShapeChannel(ref channel) => {
+                assert_eq!(channel.deform_percent(), 25.0);
+                assert!(channel.full_weights.is_empty());
+            },
+            ref obj => panic!("Unexpected object: {:?}", obj),
+        }
+        match objects[3] {
+            TypedObject::Shape(ref shape) => {
+                assert_eq!(shape.version, Some(100));
+                assert_eq!(shape.vertex_deltas().collect::<Vec<_>>(), vec![(7, [0.0, 1.5, 0.0])]);
+                let normals = shape.normal_deltas().collect::<Vec<_>>();
+                assert_eq!(normals, vec![(7, [0.0, 0.0, -0.5])]);
+            },
+            ref obj => panic!("Unexpected object: {:?}", obj),
+        }
+    }
+}
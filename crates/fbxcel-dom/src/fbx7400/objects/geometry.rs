@@ -0,0 +1,272 @@
+//! `Geometry` objects, including `Shape` geometries of blend shapes.
+//!
+//! Layer elements (such as `LayerElementNormal`) are kept as generic nodes; see
+//! `fbx7400::geometry` for helpers to interpret them.
+
+use pull_parser::{Parser, ParserSource, Attributes};
+use crate::{Result, Error, GenericNode};
+use crate::fbx7400::{ObjectProperties, Properties70};
+use crate::fbx7400::objects::load_child_node;
+use approx::ApproxEq;
+use memory::MemoryUsage;
+
+
+/// `Geometry` object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Geometry {
+    /// Properties common to object nodes.
+    pub props: ObjectProperties,
+    /// `GeometryVersion`.
+    pub version: Option<i32>,
+    /// `Vertices`, three components for each control point.
+    pub vertices: Vec<f64>,
+    /// `PolygonVertexIndex`.
+    ///
+    /// The last index of each polygon is stored as bitwise negation (`!index`).
+    pub polygon_vertex_index: Vec<i32>,
+    /// `Edges`.
+    pub edges: Vec<i32>,
+    /// `Properties70`.
+    pub properties: Properties70,
+    /// Other child nodes, including layer elements and `Layer`s.
+    pub nodes: Vec<GenericNode>,
+}
+
+impl Geometry {
+    /// Loads node contents from the parser.
+    pub fn load<R, P>(mut parser: P, props: ObjectProperties) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        let mut version = None;
+        let mut vertices = Vec::new();
+        let mut polygon_vertex_index = Vec::new();
+        let mut edges = Vec::new();
+        let mut properties = None;
+        let mut nodes = Vec::new();
+
+        loop {
+            let node_type = try_get_node_attrs!(parser, GeometryChildAttrs::load);
+            match node_type {
+                GeometryChildAttrs::GeometryVersion(v) => {
+                    version = Some(v);
+                    parser.skip_current_node()?;
+                },
+                GeometryChildAttrs::Vertices(v) => {
+                    vertices = v;
+                    parser.skip_current_node()?;
+                },
+                GeometryChildAttrs::PolygonVertexIndex(v) => {
+                    polygon_vertex_index = v;
+                    parser.skip_current_node()?;
+                },
+                GeometryChildAttrs::Edges(v) => {
+                    edges = v;
+                    parser.skip_current_node()?;
+                },
+                GeometryChildAttrs::Properties70 => {
+                    properties = Some(Properties70::load(parser.subtree_parser())?);
+                },
+                GeometryChildAttrs::Other(name, attrs) => {
+                    nodes.push(load_child_node(&mut parser, &props.class, name, attrs)?);
+                },
+            }
+        }
+        if vertices.len() % 3 != 0 {
+            return Err(Error::InvalidAttribute("Vertices".to_owned()));
+        }
+        Ok(Geometry {
+            props,
+            version,
+            vertices,
+            polygon_vertex_index,
+            edges,
+            properties: properties.unwrap_or_default(),
+            nodes,
+        })
+    }
+
+    /// Returns the layer element nodes (such as `LayerElementNormal`).
+    pub fn layer_elements(&self) -> impl Iterator<Item = &GenericNode> {
+        self.nodes.iter().filter(|node| node.name.starts_with(b"LayerElement"))
+    }
+}
+
+
+/// `Geometry` object of `Shape` subclass, a target shape of a blend shape channel.
+///
+/// Vertices and normals are deltas relative to the base geometry, stored only for the control
+/// points in `indexes`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shape {
+    /// Properties common to object nodes.
+    pub props: ObjectProperties,
+    /// `Version`.
+    pub version: Option<i32>,
+    /// `Indexes`, control point indices of the base geometry.
+    pub indexes: Vec<i32>,
+    /// `Vertices`, three components of the position delta for each element of `indexes`.
+    pub vertices: Vec<f64>,
+    /// `Normals`, three components of the normal delta for each element of `indexes`.
+    ///
+    /// This is empty if the shape has no normals.
+    pub normals: Vec<f64>,
+    /// `Properties70`.
+    pub properties: Properties70,
+    /// Other child nodes.
+    pub nodes: Vec<GenericNode>,
+}
+
+impl Shape {
+    /// Loads node contents from the parser.
+    pub fn load<R, P>(mut parser: P, props: ObjectProperties) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        let mut version = None;
+        let mut indexes = Vec::new();
+        let mut vertices = Vec::new();
+        let mut normals = Vec::new();
+        let mut properties = None;
+        let mut nodes = Vec::new();
+
+        loop {
+            let node_type = try_get_node_attrs!(parser, ShapeChildAttrs::load);
+            match node_type {
+                ShapeChildAttrs::Version(v) => {
+                    version = Some(v);
+                    parser.skip_current_node()?;
+                },
+                ShapeChildAttrs::Indexes(v) => {
+                    indexes = v;
+                    parser.skip_current_node()?;
+                },
+                ShapeChildAttrs::Vertices(v) => {
+                    vertices = v;
+                    parser.skip_current_node()?;
+                },
+                ShapeChildAttrs::Normals(v) => {
+                    normals = v;
+                    parser.skip_current_node()?;
+                },
+                ShapeChildAttrs::Properties70 => {
+                    properties = Some(Properties70::load(parser.subtree_parser())?);
+                },
+                ShapeChildAttrs::Other(name, attrs) => {
+                    nodes.push(load_child_node(&mut parser, &props.class, name, attrs)?);
+                },
+            }
+        }
+        if vertices.len() != indexes.len() * 3 {
+            return Err(Error::InvalidAttribute("Vertices".to_owned()));
+        }
+        if !normals.is_empty() && normals.len() != vertices.len() {
+            return Err(Error::InvalidAttribute("Normals".to_owned()));
+        }
+        Ok(Shape {
+            props,
+            version,
+            indexes,
+            vertices,
+            normals,
+            properties: properties.unwrap_or_default(),
+            nodes,
+        })
+    }
+
+    /// Returns the pairs of control point index and position delta.
+    ///
+    /// Negative indices are skipped.
+    pub fn vertex_deltas(&self) -> impl Iterator<Item = (usize, [f64; 3])> + '_ {
+        deltas(&self.indexes, &self.vertices)
+    }
+
+    /// Returns the pairs of control point index and normal delta.
+    ///
+    /// Negative indices are skipped, and nothing is returned if the shape has no normals.
+    pub fn normal_deltas(&self) -> impl Iterator<Item = (usize, [f64; 3])> + '_ {
+        deltas(&self.indexes, &self.normals)
+    }
+}
+
+
+/// Zips the indices with the 3-component deltas, skipping negative indices.
+fn deltas<'a>(indexes: &'a [i32], values: &'a [f64])
+    -> impl Iterator<Item = (usize, [f64; 3])> + 'a
+{
+    indexes
+        .iter()
+        .zip(values.chunks_exact(3))
+        .filter(|&(&index, _)| index >= 0)
+        .map(|(&index, v)| (index as usize, [v[0], v[1], v[2]]))
+}
+
+
+child_attr_loader! { GeometryChildAttrs(Other) {
+    "GeometryVersion" => GeometryVersion(i32),
+    "Vertices" => Vertices(Vec<f64>),
+    "PolygonVertexIndex" => PolygonVertexIndex(Vec<i32>),
+    "Edges" => Edges(Vec<i32>),
+    "Properties70" => Properties70,
+}}
+
+
+child_attr_loader! { ShapeChildAttrs(Other) {
+    "Version" => Version(i32),
+    "Indexes" => Indexes(Vec<i32>),
+    "Vertices" => Vertices(Vec<f64>),
+    "Normals" => Normals(Vec<f64>),
+    "Properties70" => Properties70,
+}}
+
+
+impl MemoryUsage for Geometry {
+    fn heap_usage(&self) -> usize {
+        self.props.heap_usage() +
+            self.vertices.heap_usage() +
+            self.polygon_vertex_index.heap_usage() +
+            self.edges.heap_usage() +
+            self.properties.heap_usage() +
+            self.nodes.heap_usage()
+    }
+}
+
+
+impl ApproxEq for Geometry {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.props == other.props &&
+            self.version.approx_eq(&other.version, epsilon) &&
+            self.vertices.approx_eq(&other.vertices, epsilon) &&
+            self.polygon_vertex_index.approx_eq(&other.polygon_vertex_index, epsilon) &&
+            self.edges.approx_eq(&other.edges, epsilon) &&
+            self.properties.approx_eq(&other.properties, epsilon) &&
+            self.nodes.approx_eq(&other.nodes, epsilon)
+    }
+}
+
+
+impl MemoryUsage for Shape {
+    fn heap_usage(&self) -> usize {
+        self.props.heap_usage() +
+            self.indexes.heap_usage() +
+            self.vertices.heap_usage() +
+            self.normals.heap_usage() +
+            self.properties.heap_usage() +
+            self.nodes.heap_usage()
+    }
+}
+
+
+impl ApproxEq for Shape {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.props == other.props &&
+            self.version.approx_eq(&other.version, epsilon) &&
+            self.indexes.approx_eq(&other.indexes, epsilon) &&
+            self.vertices.approx_eq(&other.vertices, epsilon) &&
+            self.normals.approx_eq(&other.normals, epsilon) &&
+            self.properties.approx_eq(&other.properties, epsilon) &&
+            self.nodes.approx_eq(&other.nodes, epsilon)
+    }
+}
@@ -0,0 +1,103 @@
+//! `Material` object.
+
+use pull_parser::{Parser, ParserSource, Attributes};
+use crate::{Result, Error, GenericNode};
+use crate::fbx7400::{ObjectProperties, Properties70};
+use crate::fbx7400::objects::load_child_node;
+use approx::ApproxEq;
+use memory::MemoryUsage;
+
+
+/// `Material` object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Material {
+    /// Properties common to object nodes.
+    pub props: ObjectProperties,
+    /// `Version`.
+    pub version: Option<i32>,
+    /// `ShadingModel` (such as `phong` and `lambert`).
+    pub shading_model: Option<String>,
+    /// `MultiLayer`.
+    pub multi_layer: Option<i32>,
+    /// `Properties70`.
+    pub properties: Properties70,
+    /// Other child nodes.
+    pub nodes: Vec<GenericNode>,
+}
+
+impl Material {
+    /// Loads node contents from the parser.
+    pub fn load<R, P>(mut parser: P, props: ObjectProperties) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        let mut version = None;
+        let mut shading_model = None;
+        let mut multi_layer = None;
+        let mut properties = None;
+        let mut nodes = Vec::new();
+
+        loop {
+            let node_type = try_get_node_attrs!(parser, MaterialChildAttrs::load);
+            match node_type {
+                MaterialChildAttrs::Version(v) => {
+                    version = Some(v);
+                    parser.skip_current_node()?;
+                },
+                MaterialChildAttrs::ShadingModel(v) => {
+                    shading_model = Some(v);
+                    parser.skip_current_node()?;
+                },
+                MaterialChildAttrs::MultiLayer(v) => {
+                    multi_layer = Some(v);
+                    parser.skip_current_node()?;
+                },
+                MaterialChildAttrs::Properties70 => {
+                    properties = Some(Properties70::load(parser.subtree_parser())?);
+                },
+                MaterialChildAttrs::Other(name, attrs) => {
+                    nodes.push(load_child_node(&mut parser, &props.class, name, attrs)?);
+                },
+            }
+        }
+        Ok(Material {
+            props,
+            version,
+            shading_model,
+            multi_layer,
+            properties: properties.unwrap_or_default(),
+            nodes,
+        })
+    }
+}
+
+
+child_attr_loader! { MaterialChildAttrs(Other) {
+    "Version" => Version(i32),
+    "ShadingModel" => ShadingModel(String),
+    "MultiLayer" => MultiLayer(i32),
+    "Properties70" => Properties70,
+}}
+
+
+impl MemoryUsage for Material {
+    fn heap_usage(&self) -> usize {
+        self.props.heap_usage() +
+            self.shading_model.heap_usage() +
+            self.properties.heap_usage() +
+            self.nodes.heap_usage()
+    }
+}
+
+
+impl ApproxEq for Material {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.props == other.props &&
+            self.version.approx_eq(&other.version, epsilon) &&
+            self.shading_model.approx_eq(&other.shading_model, epsilon) &&
+            self.multi_layer.approx_eq(&other.multi_layer, epsilon) &&
+            self.properties.approx_eq(&other.properties, epsilon) &&
+            self.nodes.approx_eq(&other.nodes, epsilon)
+    }
+}
@@ -0,0 +1,781 @@
+//! Objects.
+//!
+//! Objects can be loaded as `GenericObject`s (by `GenericObjectsLoader`), or as typed structs
+//! of well-known classes (by `TypedObjectsLoader`).
+
+use std::marker::PhantomData;
+use std::mem;
+use pull_parser::{Parser, ParserSource, Event, Attributes, SubtreeParser};
+use pull_parser::Error as ParseError;
+use crate::{Result, GenericNode, OwnedAttribute};
+use crate::fbx7400::{Audio, AudioLayer, NodesBeforeObjects, Properties70};
+use crate::fbx7400::separate_name_class;
+use crate::fbx7400::schema;
+use approx::ApproxEq;
+use memory::MemoryUsage;
+pub use self::animation::{AnimationCurve, AnimationCurveNode, AnimationLayer, AnimationStack};
+pub use self::deformer::{BlendShape, BlendShapeChannel, Cluster, LinkMode, Skin, SkinningType};
+pub use self::geometry::{Geometry, Shape};
+pub use self::material::Material;
+pub use self::model::Model;
+pub use self::node_attribute::NodeAttribute;
+pub use self::texture::Texture;
+
+pub mod animation;
+pub mod deformer;
+pub mod geometry;
+pub mod material;
+pub mod model;
+pub mod node_attribute;
+pub mod texture;
+
+
+/// A trait for objects nodes loader of FBX 7.4 compatible data.
+pub trait LoadObjects7400: Sized {
+    /// Reader type.
+    type Reader: ParserSource;
+
+    /// Result objects.
+    type Objects;
+
+    /// Builds objects from the loader.
+    fn build(self) -> Result<Self::Objects>;
+
+    /// Loads an object.
+    fn load(
+        &mut self,
+        props: ObjectProperties,
+        subtree_parser: &mut SubtreeParser<Self::Reader>,
+        nodes_before_objects: &NodesBeforeObjects,
+    ) -> Result<()>;
+}
+
+
+/// Properties common to object nodes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectProperties {
+    /// ID.
+    pub id: i64,
+    /// Name.
+    pub name: String,
+    /// Class.
+    pub class: String,
+    /// Subclass.
+    pub subclass: String,
+}
+
+impl ObjectProperties {
+    /// Loads `ObjectProperties` in the same manner as usual child node attributes.
+    pub fn load<R>(name: &str, mut attrs: Attributes<R>) -> Result<ObjectProperties>
+    where
+        R: ParserSource,
+    {
+        use pull_parser::utils::AttributeValues;
+        use crate::Error;
+
+        Self::from_attributes(&mut attrs)?.ok_or_else(|| {
+            Error::InvalidAttribute(name.to_owned())
+        })
+    }
+}
+
+impl ::pull_parser::utils::AttributeValues for ObjectProperties {
+    fn from_attributes<R>(
+        attrs: &mut Attributes<R>,
+    ) -> ::std::result::Result<Option<Self>, ParseError>
+    where
+        R: ParserSource,
+    {
+        let (id, name_class, subclass) = match <(i64, String, String)>::from_attributes(attrs)? {
+            Some(v) => v,
+            None => return Ok(None),
+        };
+        Ok(separate_name_class(&name_class).map(|(name, class)| {
+            ObjectProperties {
+                id,
+                name: name.to_owned(),
+                class: class.to_owned(),
+                subclass,
+            }
+        }))
+    }
+}
+
+
+/// Object with its raw properties and child nodes.
+///
+/// Object-specific child nodes are not interpreted, but `Properties70` is loaded as is, so that
+/// any property of the object can be queried.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenericObject {
+    /// Properties common to object nodes.
+    pub props: ObjectProperties,
+    /// `Properties70` of the object.
+    ///
+    /// This is empty if the object has no `Properties70` node.
+    pub properties: Properties70,
+    /// Child nodes other than `Properties70`.
+    pub nodes: Vec<GenericNode>,
+}
+
+impl GenericObject {
+    /// Creates a new `GenericObject` without properties and child nodes.
+    pub fn new(props: ObjectProperties) -> Self {
+        GenericObject {
+            props,
+            properties: Properties70::new(),
+            nodes: Vec::new(),
+        }
+    }
+
+    /// Creates an object node from the object.
+    ///
+    /// The node name is the class of the object (such as `Model` and `NodeAttribute`).
+    /// Custom objects carrying pipeline metadata can be created with any class name and
+    /// arbitrary properties.
+    ///
+    /// Returns `Err(_)` if the node (or its descendant, including properties) cannot be written
+    /// as FBX binary (see `GenericNode::validate()`).
+    pub fn to_generic_node(&self) -> Result<GenericNode> {
+        let name_class = format!("{}\u{0}\u{1}{}", self.props.name, self.props.class);
+        let mut children = Vec::with_capacity(self.nodes.len() + 1);
+        children.push(self.properties.to_generic_node());
+        children.extend(self.nodes.iter().cloned());
+        let node = GenericNode {
+            name: self.props.class.clone().into_bytes(),
+            attributes: vec![
+                OwnedAttribute::I64(self.props.id),
+                OwnedAttribute::String(Ok(name_class)),
+                OwnedAttribute::String(Ok(self.props.subclass.clone())),
+            ],
+            children,
+        };
+        node.validate()?;
+        Ok(node)
+    }
+
+    /// Loads the object contents from the parser.
+    pub fn load<R, P>(mut parser: P, props: ObjectProperties) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        let mut properties = None;
+        let mut nodes = Vec::new();
+
+        loop {
+            let (name, attrs) = match parser.next_event()? {
+                Event::StartNode(info) => {
+                    let name = info.name_bytes().to_owned();
+                    let attrs = OwnedAttribute::load_attrs_from_parser_event(info.attributes)?;
+                    (name, attrs)
+                },
+                Event::EndNode => break,
+                ev => panic!("Unexpected node event: {:?}", ev),
+            };
+            if name == b"Properties70" {
+                if properties.is_some() {
+                    warn!(
+                        "Multiple `Properties70` nodes found for the object (id={}), ignoring",
+                        props.id
+                    );
+                    parser.skip_current_node()?;
+                } else {
+                    properties = Some(Properties70::load(parser.subtree_parser())?);
+                }
+            } else {
+                let children = GenericNode::load_from_parser(&mut parser.subtree_parser())?.0;
+                let node = GenericNode {
+                    name,
+                    attributes: attrs,
+                    children,
+                };
+                validate_child_node(&props.class, &node)?;
+                nodes.push(node);
+            }
+        }
+
+        Ok(GenericObject {
+            props,
+            properties: properties.unwrap_or_default(),
+            nodes,
+        })
+    }
+}
+
+
+/// Objects loader which loads all objects as `GenericObject`.
+#[derive(Debug, Clone)]
+pub struct GenericObjectsLoader<R> {
+    /// Loaded objects.
+    objects: Vec<GenericObject>,
+    /// Dummy.
+    _r: PhantomData<R>,
+}
+
+impl<R> GenericObjectsLoader<R> {
+    /// Creates a new `GenericObjectsLoader`.
+    pub fn new() -> Self {
+        GenericObjectsLoader {
+            objects: Vec::new(),
+            _r: PhantomData,
+        }
+    }
+}
+
+impl<R> Default for GenericObjectsLoader<R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R: ParserSource> LoadObjects7400 for GenericObjectsLoader<R> {
+    type Reader = R;
+    type Objects = Vec<GenericObject>;
+
+    fn build(self) -> Result<Self::Objects> {
+        Ok(self.objects)
+    }
+
+    fn load(
+        &mut self,
+        props: ObjectProperties,
+        subtree_parser: &mut SubtreeParser<Self::Reader>,
+        _nodes_before_objects: &NodesBeforeObjects,
+    ) -> Result<()> {
+        self.objects.push(GenericObject::load(subtree_parser, props)?);
+        Ok(())
+    }
+}
+
+
+/// Object loaded as a typed struct if the class is well-known.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypedObject {
+    /// `AnimationCurve`.
+    AnimationCurve(AnimationCurve),
+    /// `AnimationCurveNode`.
+    AnimationCurveNode(AnimationCurveNode),
+    /// `AnimationLayer`.
+    AnimationLayer(AnimationLayer),
+    /// `AnimationStack`.
+    AnimationStack(AnimationStack),
+    /// `Audio`.
+    Audio(Audio),
+    /// `AudioLayer`.
+    AudioLayer(AudioLayer),
+    /// `Deformer` of `BlendShape` subclass.
+    BlendShape(BlendShape),
+    /// `Deformer` (or `SubDeformer`) of `BlendShapeChannel` subclass.
+    BlendShapeChannel(BlendShapeChannel),
+    /// `Deformer` (or `SubDeformer`) of `Cluster` subclass.
+    ///
+    /// This is boxed since the struct has inline matrices.
+    Cluster(Box<Cluster>),
+    /// `Geometry`.
+    Geometry(Geometry),
+    /// `Material`.
+    Material(Material),
+    /// `Model`.
+    Model(Model),
+    /// `NodeAttribute`.
+    NodeAttribute(NodeAttribute),
+    /// `Geometry` of `Shape` subclass.
+    Shape(Shape),
+    /// `Deformer` of `Skin` subclass.
+    Skin(Skin),
+    /// `Texture`.
+    Texture(Texture),
+    /// Object of other classes.
+    ///
+    /// Objects specific to some applications (such as `TimelineX` of MotionBuilder) are kept
+    /// as is, so that files with them can be loaded and tools can read them.
+    Other(GenericObject),
+}
+
+impl TypedObject {
+    /// Loads the object contents from the parser, selecting the struct by the class.
+    pub fn load<R, P>(parser: P, props: ObjectProperties) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        Ok(match props.class.as_str() {
+            "AnimationCurve" => TypedObject::AnimationCurve(AnimationCurve::load(parser, props)?),
+            "AnimationCurveNode" => {
+                TypedObject::AnimationCurveNode(AnimationCurveNode::load(parser, props)?)
+            },
+            "AnimationLayer" => TypedObject::AnimationLayer(AnimationLayer::load(parser, props)?),
+            "AnimationStack" => TypedObject::AnimationStack(AnimationStack::load(parser, props)?),
+            "Audio" => {
+                let obj = GenericObject::load(parser, props)?;
+                Audio::from_generic(&obj).map_or(TypedObject::Other(obj), TypedObject::Audio)
+            },
+            "AudioLayer" => {
+                let obj = GenericObject::load(parser, props)?;
+                AudioLayer::from_generic(&obj)
+                    .map_or(TypedObject::Other(obj), TypedObject::AudioLayer)
+            },
+            "Deformer" | "SubDeformer" => match props.subclass.as_str() {
+                "BlendShape" => TypedObject::BlendShape(BlendShape::load(parser, props)?),
+                "BlendShapeChannel" => {
+                    TypedObject::BlendShapeChannel(BlendShapeChannel::load(parser, props)?)
+                },
+                "Cluster" => TypedObject::Cluster(Box::new(Cluster::load(parser, props)?)),
+                "Skin" => TypedObject::Skin(Skin::load(parser, props)?),
+                _ => TypedObject::Other(GenericObject::load(parser, props)?),
+            },
+            "Geometry" if props.subclass == "Shape" => {
+                TypedObject::Shape(Shape::load(parser, props)?)
+            },
+            "Geometry" => TypedObject::Geometry(Geometry::load(parser, props)?),
+            "Material" => TypedObject::Material(Material::load(parser, props)?),
+            "Model" => TypedObject::Model(Model::load(parser, props)?),
+            "NodeAttribute" => TypedObject::NodeAttribute(NodeAttribute::load(parser, props)?),
+            "Texture" => TypedObject::Texture(Texture::load(parser, props)?),
+            _ => TypedObject::Other(GenericObject::load(parser, props)?),
+        })
+    }
+
+    /// Returns the properties common to object nodes.
+    pub fn props(&self) -> &ObjectProperties {
+        match *self {
+            TypedObject::AnimationCurve(ref v) => &v.props,
+            TypedObject::AnimationCurveNode(ref v) => &v.props,
+            TypedObject::AnimationLayer(ref v) => &v.props,
+            TypedObject::AnimationStack(ref v) => &v.props,
+            TypedObject::Audio(ref v) => &v.props,
+            TypedObject::AudioLayer(ref v) => &v.props,
+            TypedObject::BlendShape(ref v) => &v.props,
+            TypedObject::BlendShapeChannel(ref v) => &v.props,
+            TypedObject::Cluster(ref v) => &v.props,
+            TypedObject::Geometry(ref v) => &v.props,
+            TypedObject::Material(ref v) => &v.props,
+            TypedObject::Model(ref v) => &v.props,
+            TypedObject::NodeAttribute(ref v) => &v.props,
+            TypedObject::Shape(ref v) => &v.props,
+            TypedObject::Skin(ref v) => &v.props,
+            TypedObject::Texture(ref v) => &v.props,
+            TypedObject::Other(ref v) => &v.props,
+        }
+    }
+}
+
+
+/// Objects loader which loads objects as `TypedObject`.
+#[derive(Debug, Clone)]
+pub struct TypedObjectsLoader<R> {
+    /// Loaded objects.
+    objects: Vec<TypedObject>,
+    /// Dummy.
+    _r: PhantomData<R>,
+}
+
+impl<R> TypedObjectsLoader<R> {
+    /// Creates a new `TypedObjectsLoader`.
+    pub fn new() -> Self {
+        TypedObjectsLoader {
+            objects: Vec::new(),
+            _r: PhantomData,
+        }
+    }
+}
+
+impl<R> Default for TypedObjectsLoader<R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R: ParserSource> LoadObjects7400 for TypedObjectsLoader<R> {
+    type Reader = R;
+    type Objects = Vec<TypedObject>;
+
+    fn build(self) -> Result<Self::Objects> {
+        Ok(self.objects)
+    }
+
+    fn load(
+        &mut self,
+        props: ObjectProperties,
+        subtree_parser: &mut SubtreeParser<Self::Reader>,
+        _nodes_before_objects: &NodesBeforeObjects,
+    ) -> Result<()> {
+        self.objects.push(TypedObject::load(subtree_parser, props)?);
+        Ok(())
+    }
+}
+
+
+/// Loads the child node whose name and attributes are already read, with its descendants.
+///
+/// The node is validated against the schema of the children of `class` objects.
+fn load_child_node<R, P>(parser: &mut P, class: &str, name: String, attrs: Vec<OwnedAttribute>)
+    -> Result<GenericNode>
+where
+    R: ParserSource,
+    P: Parser<R>,
+{
+    let children = GenericNode::load_from_parser(&mut parser.subtree_parser())?.0;
+    let node = GenericNode {
+        name: name.into_bytes(),
+        attributes: attrs,
+        children,
+    };
+    validate_child_node(class, &node)?;
+    Ok(node)
+}
+
+
+/// Validates the child node of an object of the given class against the schema.
+///
+/// Nodes unknown to the schema are not checked.
+/// See `fbx7400::schema::validate_tree()`.
+fn validate_child_node(class: &str, node: &GenericNode) -> Result<()> {
+    // Sub-deformers are stored as `Deformer` nodes.
+    let parent = match class {
+        "SubDeformer" => "Deformer",
+        class => class,
+    };
+    schema::validate_tree(node, Some(parent), true)
+}
+
+
+impl MemoryUsage for ObjectProperties {
+    fn heap_usage(&self) -> usize {
+        self.name.heap_usage() +
+            self.class.heap_usage() +
+            self.subclass.heap_usage()
+    }
+}
+
+
+impl MemoryUsage for GenericObject {
+    fn heap_usage(&self) -> usize {
+        self.props.heap_usage() +
+            self.properties.heap_usage() +
+            self.nodes.heap_usage()
+    }
+}
+
+
+impl MemoryUsage for TypedObject {
+    fn heap_usage(&self) -> usize {
+        match *self {
+            TypedObject::AnimationCurve(ref v) => v.heap_usage(),
+            TypedObject::AnimationCurveNode(ref v) => v.heap_usage(),
+            TypedObject::AnimationLayer(ref v) => v.heap_usage(),
+            TypedObject::AnimationStack(ref v) => v.heap_usage(),
+            TypedObject::Audio(ref v) => v.heap_usage(),
+            TypedObject::AudioLayer(ref v) => v.heap_usage(),
+            TypedObject::BlendShape(ref v) => v.heap_usage(),
+            TypedObject::BlendShapeChannel(ref v) => v.heap_usage(),
+            TypedObject::Cluster(ref v) => mem::size_of::<Cluster>() + v.heap_usage(),
+            TypedObject::Geometry(ref v) => v.heap_usage(),
+            TypedObject::Material(ref v) => v.heap_usage(),
+            TypedObject::Model(ref v) => v.heap_usage(),
+            TypedObject::NodeAttribute(ref v) => v.heap_usage(),
+            TypedObject::Shape(ref v) => v.heap_usage(),
+            TypedObject::Skin(ref v) => v.heap_usage(),
+            TypedObject::Texture(ref v) => v.heap_usage(),
+            TypedObject::Other(ref v) => v.heap_usage(),
+        }
+    }
+}
+
+
+impl ApproxEq for GenericObject {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.props == other.props &&
+            self.properties.approx_eq(&other.properties, epsilon) &&
+            self.nodes.approx_eq(&other.nodes, epsilon)
+    }
+}
+
+
+impl ApproxEq for TypedObject {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        use self::TypedObject::*;
+
+        match (self, other) {
+            (AnimationCurve(a), AnimationCurve(b)) => a.approx_eq(b, epsilon),
+            (AnimationCurveNode(a), AnimationCurveNode(b)) => a.approx_eq(b, epsilon),
+            (AnimationLayer(a), AnimationLayer(b)) => a.approx_eq(b, epsilon),
+            (AnimationStack(a), AnimationStack(b)) => a.approx_eq(b, epsilon),
+            (Audio(a), Audio(b)) => a.approx_eq(b, epsilon),
+            (AudioLayer(a), AudioLayer(b)) => a.approx_eq(b, epsilon),
+            (BlendShape(a), BlendShape(b)) => a.approx_eq(b, epsilon),
+            (BlendShapeChannel(a), BlendShapeChannel(b)) => a.approx_eq(b, epsilon),
+            (Cluster(a), Cluster(b)) => a.approx_eq(b, epsilon),
+            (Geometry(a), Geometry(b)) => a.approx_eq(b, epsilon),
+            (Material(a), Material(b)) => a.approx_eq(b, epsilon),
+            (Model(a), Model(b)) => a.approx_eq(b, epsilon),
+            (NodeAttribute(a), NodeAttribute(b)) => a.approx_eq(b, epsilon),
+            (Shape(a), Shape(b)) => a.approx_eq(b, epsilon),
+            (Skin(a), Skin(b)) => a.approx_eq(b, epsilon),
+            (Texture(a), Texture(b)) => a.approx_eq(b, epsilon),
+            (Other(a), Other(b)) => a.approx_eq(b, epsilon),
+            _ => false,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use pull_parser::RootParser;
+    use pull_parser::ascii::AsciiDocument;
+    use crate::fbx7400::KTime;
+    use super::*;
+
+    #[test]
+    fn load_typed_objects() {
+        let source = "; FBX 7.4.0 project file\n\
+                      Objects:  {\n\
+                      \tGeometry: 1, \"Geometry::Tri\", \"Mesh\" {\n\
+                      \t\tVertices: *9 {\n\t\t\ta: 0,0,0,1,0,0,0,1,0\n\t\t}\n\
+                      \t\tPolygonVertexIndex: *3 {\n\t\t\ta: 0,1,-3\n\t\t}\n\
+                      \t\tGeometryVersion: 124\n\
+                      \t\tLayerElementNormal: 0 {\n\t\t\tVersion: 102\n\t\t}\n\
+                      \t}\n\
+                      \tTexture: 2, \"Texture::Wood\", \"\" {\n\
+                      \t\tFileName: \"wood.png\"\n\
+                      \t\tModelUVScaling: 1,2\n\
+                      \t}\n\
+                      \tPose: 3, \"Pose::Bind\", \"BindPose\" {\n\t\tNbPoseNodes: 0\n\t}\n\
+                      \tDeformer: 4, \"SubDeformer::\", \"Cluster\" {\n\
+                      \t\tMode: \"Total1\"\n\
+                      \t\tIndexes: *2 {\n\t\t\ta: 0,2\n\t\t}\n\
+                      \t\tWeights: *2 {\n\t\t\ta: 1,0.5\n\t\t}\n\
+                      \t\tTransformLink: *16 {\n\t\t\ta: 1,0,0,0,0,1,0,0,0,0,1,0,0,2,0,1\n\t\t}\n\
+                      \t}\n\
+                      \tGeometry: 5, \"Geometry::Smile\", \"Shape\" {\n\
+                      \t\tIndexes: *2 {\n\t\t\ta: 1,2\n\t\t}\n\
+                      \t\tVertices: *6 {\n\t\t\ta: 0,0.5,0,0,0,0.25\n\t\t}\n\
+                      \t}\n\
+                      \tDeformer: 6, \"SubDeformer::Smile\", \"BlendShapeChannel\" {\n\
+                      \t\tDeformPercent: 50\n\
+                      \t\tFullWeights: *1 {\n\t\t\ta: 100\n\t\t}\n\
+                      \t}\n\
+                      }\n";
+        let bytes = AsciiDocument::parse(source).unwrap().to_binary().unwrap();
+        let mut parser = RootParser::new(Cursor::new(bytes));
+        let mut objects = Vec::new();
+        loop {
+            match parser.next_event().unwrap() {
+                Event::StartNode(node) => {
+                    let name = node.name().unwrap().to_owned();
+                    if name == "Objects" {
+                        continue;
+                    }
+                    let props = ObjectProperties::load(&name, node.attributes).unwrap();
+                    objects.push(TypedObject::load(parser.subtree_parser(), props).unwrap());
+                },
+                Event::EndFbx(_) => break,
+                _ => {},
+            }
+        }
+
+        assert_eq!(objects.len(), 6);
+        match objects[0] {
+            TypedObject::Geometry(ref geom) => {
+                assert_eq!(geom.version, Some(124));
+                assert_eq!(geom.vertices.len(), 9);
+                assert_eq!(geom.polygon_vertex_index, vec![0, 1, -3]);
+                assert_eq!(geom.layer_elements().count(), 1);
+            },
+            ref obj => panic!("Unexpected object: {:?}", obj),
+        }
+        match objects[1] {
+            TypedObject::Texture(ref tex) => {
+                assert_eq!(tex.filename.as_deref(), Some("wood.png"));
+                assert_eq!(tex.model_uv_scaling, Some((1.0, 2.0)));
+            },
+            ref obj => panic!("Unexpected object: {:?}", obj),
+        }
+        assert_eq!(objects[2].props().class, "Pose");
+        match objects[3] {
+            TypedObject::Cluster(ref cluster) => {
+                assert_eq!(cluster.link_mode(), LinkMode::TotalOne);
+                assert_eq!(cluster.influences().collect::<Vec<_>>(), vec![(0, 1.0), (2, 0.5)]);
+                assert_eq!(cluster.transform_link.map(|m| m[13]), Some(2.0));
+                assert_eq!(cluster.transform, None);
+            },
+            ref obj => panic!("Unexpected object: {:?}", obj),
+        }
+        match objects[4] {
+            TypedObject::Shape(ref shape) => {
+                let deltas = shape.vertex_deltas().collect::<Vec<_>>();
+                assert_eq!(deltas, vec![(1, [0.0, 0.5, 0.0]), (2, [0.0, 0.0, 0.25])]);
+                assert_eq!(shape.normal_deltas().count(), 0);
+            },
+            ref obj => panic!("Unexpected object: {:?}", obj),
+        }
+        match objects[5] {
+            TypedObject::BlendShapeChannel(ref channel) => {
+                assert_eq!(channel.deform_percent(), 50.0);
+                assert_eq!(channel.full_weights, vec![100.0]);
+            },
+            ref obj => panic!("Unexpected object: {:?}", obj),
+        }
+    }
+
+
+    #[test]
+    fn load_animation_curve() {
+        let source = "; FBX 7.4.0 project file\n\
+                      Objects:  {\n\
+                      \tAnimationCurve: 1, \"AnimationCurve::\", \"\" {\n\
+                      \t\tDefault: 0\n\
+                      \t\tKeyVer: 4009\n\
+                      \t\tKeyTime: *2 {\n\t\t\ta: 0,100\n\t\t}\n\
+                      \t\tKeyValueFloat: *2 {\n\t\t\ta: 0,10\n\t\t}\n\
+                      \t\tKeyAttrFlags: *1 {\n\t\t\ta: 4\n\t\t}\n\
+                      \t\tKeyAttrDataFloat: *4 {\n\t\t\ta: 0,0,0,0\n\t\t}\n\
+                      \t\tKeyAttrRefCount: *1 {\n\t\t\ta: 2\n\t\t}\n\
+                      \t}\n\
+                      }\n";
+        let bytes = AsciiDocument::parse(source).unwrap().to_binary().unwrap();
+        let mut parser = RootParser::new(Cursor::new(bytes));
+        let mut object = None;
+        loop {
+            match parser.next_event().unwrap() {
+                Event::StartNode(node) => {
+                    let name = node.name().unwrap().to_owned();
+                    if name == "Objects" {
+                        continue;
+                    }
+                    let props = ObjectProperties::load(&name, node.attributes).unwrap();
+                    object = Some(TypedObject::load(parser.subtree_parser(), props).unwrap());
+                },
+                Event::EndFbx(_) => break,
+                _ => {},
+            }
+        }
+
+        match object {
+            Some(TypedObject::AnimationCurve(ref curve)) => {
+                assert_eq!(curve.key_version, Some(4009));
+                assert_eq!(&curve.keys.times[..], &[0, 100]);
+                assert_eq!(curve.keys.attributes.len(), 2);
+                assert_eq!(curve.evaluate(KTime(50)), 5.0);
+                assert_eq!(curve.evaluate(KTime(200)), 10.0);
+            },
+            ref obj => panic!("Unexpected object: {:?}", obj),
+        }
+    }
+
+
+    #[test]
+    fn validate_object_children_on_load() {
+        let source = "; FBX 7.4.0 project file\n\
+                      Objects:  {\n\
+                      \tDeformer: 1, \"SubDeformer::\", \"Cluster\" {\n\
+                      \t\tMode: \"Normalize\"\n\
+                      \t\tUserData: 1, 2\n\
+                      \t}\n\
+                      }\n";
+        let bytes = AsciiDocument::parse(source).unwrap().to_binary().unwrap();
+        let load = |typed: bool| {
+            let mut parser = RootParser::new(Cursor::new(bytes.clone()));
+            loop {
+                match parser.next_event().unwrap() {
+                    Event::StartNode(node) => {
+                        let name = node.name().unwrap().to_owned();
+                        if name == "Objects" {
+                            continue;
+                        }
+                        let props = ObjectProperties::load(&name, node.attributes).unwrap();
+                        return if typed {
+                            TypedObject::load(parser.subtree_parser(), props).map(|_| ())
+                        } else {
+                            GenericObject::load(parser.subtree_parser(), props).map(|_| ())
+                        };
+                    },
+                    Event::EndFbx(_) => panic!("No objects found"),
+                    _ => {},
+                }
+            }
+        };
+
+        // `UserData` of `Deformer` nodes should have two strings.
+        // The typed loader keeps it as a generic node, which is checked in the same way.
+        for &typed in &[false, true] {
+            match load(typed) {
+                Err(crate::Error::InvalidAttribute(ref name)) => {
+                    assert_eq!(name, "UserData")
+                },
+                v => panic!("Unexpected result: {:?}", v),
+            }
+        }
+    }
+
+
+    #[test]
+    fn load_motion_builder_objects() {
+        let source = "; FBX 7.4.0 project file\n\
+                      Objects:  {\n\
+                      \tAudio: 1, \"Audio::Clap\", \"Clip\" {\n\
+                      \t\tFilename: \"/sounds/clap.wav\"\n\
+                      \t\tRelativeFilename: \"sounds/clap.wav\"\n\
+                      \t}\n\
+                      \tAudioLayer: 2, \"AudioLayer::Dialogue\", \"\" {\n\
+                      \t\tProperties70:  {\n\
+                      \t\t\tP: \"Mute\", \"bool\", \"\", \"\",1\n\
+                      \t\t}\n\
+                      \t}\n\
+                      \tTimelineX: 3, \"TimelineX::Markers\", \"\" {\n\
+                      \t\tProperties70:  {\n\
+                      \t\t\tP: \"Shot 1\", \"KTime\", \"Time\", \"\",46186158000\n\
+                      \t\t}\n\
+                      \t\tMarkerColor: 1,0,0\n\
+                      \t}\n\
+                      }\n";
+        let bytes = AsciiDocument::parse(source).unwrap().to_binary().unwrap();
+        let mut parser = RootParser::new(Cursor::new(bytes));
+        let mut objects = Vec::new();
+        loop {
+            match parser.next_event().unwrap() {
+                Event::StartNode(node) => {
+                    let name = node.name().unwrap().to_owned();
+                    if name == "Objects" {
+                        continue;
+                    }
+                    let props = ObjectProperties::load(&name, node.attributes).unwrap();
+                    objects.push(TypedObject::load(parser.subtree_parser(), props).unwrap());
+                },
+                Event::EndFbx(_) => break,
+                _ => {},
+            }
+        }
+
+        assert_eq!(objects.len(), 3);
+        match objects[0] {
+            TypedObject::Audio(ref audio) => {
+                assert_eq!(audio.filename, "/sounds/clap.wav");
+                assert_eq!(audio.relative_filename, "sounds/clap.wav");
+                assert_eq!(audio.content, None);
+            },
+            ref obj => panic!("Unexpected object: {:?}", obj),
+        }
+        match objects[1] {
+            TypedObject::AudioLayer(ref layer) => {
+                assert_eq!(layer.props.name, "Dialogue");
+                assert!(layer.properties.contains("Mute"));
+            },
+            ref obj => panic!("Unexpected object: {:?}", obj),
+        }
+        // Unknown classes are kept as raw objects, including their properties and child nodes.
+        match objects[2] {
+            TypedObject::Other(ref timeline) => {
+                assert_eq!(timeline.props.class, "TimelineX");
+                let marker = timeline.properties.get_ref("Shot 1").unwrap();
+                assert_eq!(marker.coerce::<i64>("Shot 1").unwrap(), 46186158000);
+                assert_eq!(timeline.nodes[0].name, b"MarkerColor");
+                assert_eq!(timeline.nodes[0].attributes.len(), 3);
+            },
+            ref obj => panic!("Unexpected object: {:?}", obj),
+        }
+    }
+}
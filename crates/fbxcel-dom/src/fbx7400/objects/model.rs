@@ -0,0 +1,103 @@
+//! `Model` object.
+
+use pull_parser::{Parser, ParserSource, Attributes};
+use crate::{Result, Error, GenericNode};
+use crate::fbx7400::{ObjectProperties, Properties70};
+use crate::fbx7400::objects::load_child_node;
+use approx::ApproxEq;
+use memory::MemoryUsage;
+
+
+/// `Model` object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Model {
+    /// Properties common to object nodes.
+    pub props: ObjectProperties,
+    /// `Version`.
+    pub version: Option<i32>,
+    /// `Shading`.
+    pub shading: Option<bool>,
+    /// `Culling` (such as `CullingOff`).
+    pub culling: Option<String>,
+    /// `Properties70`.
+    pub properties: Properties70,
+    /// Other child nodes.
+    pub nodes: Vec<GenericNode>,
+}
+
+impl Model {
+    /// Loads node contents from the parser.
+    pub fn load<R, P>(mut parser: P, props: ObjectProperties) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        let mut version = None;
+        let mut shading = None;
+        let mut culling = None;
+        let mut properties = None;
+        let mut nodes = Vec::new();
+
+        loop {
+            let node_type = try_get_node_attrs!(parser, ModelChildAttrs::load);
+            match node_type {
+                ModelChildAttrs::Version(v) => {
+                    version = Some(v);
+                    parser.skip_current_node()?;
+                },
+                ModelChildAttrs::Shading(v) => {
+                    shading = Some(v);
+                    parser.skip_current_node()?;
+                },
+                ModelChildAttrs::Culling(v) => {
+                    culling = Some(v);
+                    parser.skip_current_node()?;
+                },
+                ModelChildAttrs::Properties70 => {
+                    properties = Some(Properties70::load(parser.subtree_parser())?);
+                },
+                ModelChildAttrs::Other(name, attrs) => {
+                    nodes.push(load_child_node(&mut parser, &props.class, name, attrs)?);
+                },
+            }
+        }
+        Ok(Model {
+            props,
+            version,
+            shading,
+            culling,
+            properties: properties.unwrap_or_default(),
+            nodes,
+        })
+    }
+}
+
+
+child_attr_loader! { ModelChildAttrs(Other) {
+    "Version" => Version(i32),
+    "Shading" => Shading(bool),
+    "Culling" => Culling(String),
+    "Properties70" => Properties70,
+}}
+
+
+impl MemoryUsage for Model {
+    fn heap_usage(&self) -> usize {
+        self.props.heap_usage() +
+            self.culling.heap_usage() +
+            self.properties.heap_usage() +
+            self.nodes.heap_usage()
+    }
+}
+
+
+impl ApproxEq for Model {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.props == other.props &&
+            self.version.approx_eq(&other.version, epsilon) &&
+            self.shading.approx_eq(&other.shading, epsilon) &&
+            self.culling.approx_eq(&other.culling, epsilon) &&
+            self.properties.approx_eq(&other.properties, epsilon) &&
+            self.nodes.approx_eq(&other.nodes, epsilon)
+    }
+}
@@ -0,0 +1,86 @@
+//! `NodeAttribute` object.
+
+use pull_parser::{Parser, ParserSource, Attributes};
+use crate::{Result, Error, GenericNode};
+use crate::fbx7400::{ObjectProperties, Properties70};
+use crate::fbx7400::objects::load_child_node;
+use approx::ApproxEq;
+use memory::MemoryUsage;
+
+
+/// `NodeAttribute` object.
+///
+/// The kind of the attribute (such as `Light`, `Camera`, and `LimbNode`) is in
+/// `props.subclass`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeAttribute {
+    /// Properties common to object nodes.
+    pub props: ObjectProperties,
+    /// `TypeFlags` (such as `Light` and `Skeleton`).
+    pub type_flags: Option<String>,
+    /// `Properties70`.
+    pub properties: Properties70,
+    /// Other child nodes.
+    pub nodes: Vec<GenericNode>,
+}
+
+impl NodeAttribute {
+    /// Loads node contents from the parser.
+    pub fn load<R, P>(mut parser: P, props: ObjectProperties) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        let mut type_flags = None;
+        let mut properties = None;
+        let mut nodes = Vec::new();
+
+        loop {
+            let node_type = try_get_node_attrs!(parser, NodeAttributeChildAttrs::load);
+            match node_type {
+                NodeAttributeChildAttrs::TypeFlags(v) => {
+                    type_flags = Some(v);
+                    parser.skip_current_node()?;
+                },
+                NodeAttributeChildAttrs::Properties70 => {
+                    properties = Some(Properties70::load(parser.subtree_parser())?);
+                },
+                NodeAttributeChildAttrs::Other(name, attrs) => {
+                    nodes.push(load_child_node(&mut parser, &props.class, name, attrs)?);
+                },
+            }
+        }
+        Ok(NodeAttribute {
+            props,
+            type_flags,
+            properties: properties.unwrap_or_default(),
+            nodes,
+        })
+    }
+}
+
+
+child_attr_loader! { NodeAttributeChildAttrs(Other) {
+    "TypeFlags" => TypeFlags(String),
+    "Properties70" => Properties70,
+}}
+
+
+impl MemoryUsage for NodeAttribute {
+    fn heap_usage(&self) -> usize {
+        self.props.heap_usage() +
+            self.type_flags.heap_usage() +
+            self.properties.heap_usage() +
+            self.nodes.heap_usage()
+    }
+}
+
+
+impl ApproxEq for NodeAttribute {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.props == other.props &&
+            self.type_flags.approx_eq(&other.type_flags, epsilon) &&
+            self.properties.approx_eq(&other.properties, epsilon) &&
+            self.nodes.approx_eq(&other.nodes, epsilon)
+    }
+}
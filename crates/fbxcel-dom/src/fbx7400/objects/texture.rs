@@ -0,0 +1,143 @@
+//! `Texture` object.
+
+use pull_parser::{Parser, ParserSource, Attributes};
+use crate::{Result, Error, GenericNode};
+use crate::fbx7400::{ObjectProperties, Properties70};
+use crate::fbx7400::objects::load_child_node;
+use approx::ApproxEq;
+use memory::MemoryUsage;
+
+
+/// `Texture` object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Texture {
+    /// Properties common to object nodes.
+    pub props: ObjectProperties,
+    /// `Type` (usually `TextureVideoClip`).
+    pub type_: Option<String>,
+    /// `Version`.
+    pub version: Option<i32>,
+    /// `TextureName`.
+    pub texture_name: Option<String>,
+    /// `Media`.
+    pub media: Option<String>,
+    /// `FileName`.
+    pub filename: Option<String>,
+    /// `RelativeFilename`.
+    pub relative_filename: Option<String>,
+    /// `ModelUVTranslation`.
+    pub model_uv_translation: Option<(f64, f64)>,
+    /// `ModelUVScaling`.
+    pub model_uv_scaling: Option<(f64, f64)>,
+    /// `Texture_Alpha_Source` (such as `None`).
+    pub texture_alpha_source: Option<String>,
+    /// `Cropping`.
+    pub cropping: Option<(i32, i32, i32, i32)>,
+    /// `Properties70`.
+    pub properties: Properties70,
+    /// Other child nodes.
+    pub nodes: Vec<GenericNode>,
+}
+
+impl Texture {
+    /// Loads node contents from the parser.
+    pub fn load<R, P>(mut parser: P, props: ObjectProperties) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        let mut texture = Texture {
+            props,
+            type_: None,
+            version: None,
+            texture_name: None,
+            media: None,
+            filename: None,
+            relative_filename: None,
+            model_uv_translation: None,
+            model_uv_scaling: None,
+            texture_alpha_source: None,
+            cropping: None,
+            properties: Properties70::new(),
+            nodes: Vec::new(),
+        };
+
+        loop {
+            let node_type = try_get_node_attrs!(parser, TextureChildAttrs::load);
+            match node_type {
+                TextureChildAttrs::Type(v) => texture.type_ = Some(v),
+                TextureChildAttrs::Version(v) => texture.version = Some(v),
+                TextureChildAttrs::TextureName(v) => texture.texture_name = Some(v),
+                TextureChildAttrs::Media(v) => texture.media = Some(v),
+                TextureChildAttrs::FileName(v) => texture.filename = Some(v),
+                TextureChildAttrs::RelativeFilename(v) => texture.relative_filename = Some(v),
+                TextureChildAttrs::ModelUvTranslation(v) => texture.model_uv_translation = Some(v),
+                TextureChildAttrs::ModelUvScaling(v) => texture.model_uv_scaling = Some(v),
+                TextureChildAttrs::TextureAlphaSource(v) => {
+                    texture.texture_alpha_source = Some(v)
+                },
+                TextureChildAttrs::Cropping(v) => texture.cropping = Some(v),
+                TextureChildAttrs::Properties70 => {
+                    texture.properties = Properties70::load(parser.subtree_parser())?;
+                    continue;
+                },
+                TextureChildAttrs::Other(name, attrs) => {
+                    let node = load_child_node(&mut parser, &texture.props.class, name, attrs)?;
+                    texture.nodes.push(node);
+                    continue;
+                },
+            }
+            parser.skip_current_node()?;
+        }
+        Ok(texture)
+    }
+}
+
+
+child_attr_loader! { TextureChildAttrs(Other) {
+    "Type" => Type(String),
+    "Version" => Version(i32),
+    "TextureName" => TextureName(String),
+    "Media" => Media(String),
+    "FileName" => FileName(String),
+    "RelativeFilename" => RelativeFilename(String),
+    "ModelUVTranslation" => ModelUvTranslation((f64, f64)),
+    "ModelUVScaling" => ModelUvScaling((f64, f64)),
+    "Texture_Alpha_Source" => TextureAlphaSource(String),
+    "Cropping" => Cropping((i32, i32, i32, i32)),
+    "Properties70" => Properties70,
+}}
+
+
+impl MemoryUsage for Texture {
+    fn heap_usage(&self) -> usize {
+        self.props.heap_usage() +
+            self.type_.heap_usage() +
+            self.texture_name.heap_usage() +
+            self.media.heap_usage() +
+            self.filename.heap_usage() +
+            self.relative_filename.heap_usage() +
+            self.texture_alpha_source.heap_usage() +
+            self.properties.heap_usage() +
+            self.nodes.heap_usage()
+    }
+}
+
+
+impl ApproxEq for Texture {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.props == other.props &&
+            self.type_.approx_eq(&other.type_, epsilon) &&
+            self.version.approx_eq(&other.version, epsilon) &&
+            self.texture_name.approx_eq(&other.texture_name, epsilon) &&
+            self.media.approx_eq(&other.media, epsilon) &&
+            self.filename.approx_eq(&other.filename, epsilon) &&
+            self.relative_filename.approx_eq(&other.relative_filename, epsilon) &&
+            self.model_uv_translation.approx_eq(&other.model_uv_translation, epsilon) &&
+            self.model_uv_scaling.approx_eq(&other.model_uv_scaling, epsilon) &&
+            self.texture_alpha_source.approx_eq(&other.texture_alpha_source, epsilon) &&
+            self.cropping.approx_eq(&other.cropping, epsilon) &&
+            self.properties.approx_eq(&other.properties, epsilon) &&
+            self.nodes.approx_eq(&other.nodes, epsilon)
+    }
+}
@@ -0,0 +1,619 @@
+//! `Properties70` node and its children.
+
+use std::fmt;
+use fnv::{FnvHashSet, FnvHashMap};
+use pull_parser::{Parser, ParserSource, Attributes};
+use pull_parser::{Attribute, PrimitiveAttribute};
+use crate::{Result, Error, DisplayPath, GenericNode, OwnedAttribute};
+use crate::fbx7400::{Definitions, ResolvedProperties};
+use approx::ApproxEq;
+use memory::MemoryUsage;
+
+
+/// A type of map from property name to value of the specific type.
+pub type PropertyMap<T> = FnvHashMap<String, PropertyValue<T>>;
+
+
+/// Struct to store `Properties70` node data.
+#[derive(Default, Clone, PartialEq)]
+pub struct Properties70 {
+    /// Properties without values.
+    pub values_empty: FnvHashSet<String>,
+    /// Values with `i64`, `i32`, or `i16` type.
+    pub values_i64: PropertyMap<i64>,
+    /// Values with `f64` or `f32` type.
+    pub values_f64: PropertyMap<f64>,
+    /// Values with `[f64; 2]` type.
+    pub values_f64_2: PropertyMap<[f64; 2]>,
+    /// Values with `[f64; 3]` type.
+    pub values_f64_3: PropertyMap<[f64; 3]>,
+    /// Values with `[f64; 4]` type.
+    pub values_f64_4: PropertyMap<[f64; 4]>,
+    /// Values with `[[f64; 4]; 4]` type.
+    pub values_f64_4x4: PropertyMap<[[f64; 4]; 4]>,
+    /// Values with `String` type.
+    pub values_string: PropertyMap<String>,
+    /// Values with `Vec<u8>` type (called "blob").
+    pub values_binary: PropertyMap<Vec<u8>>,
+}
+
+impl Properties70 {
+    /// Creates a new `Properties70`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Loads a node from the parser.
+    pub fn load<R, P>(parser: P) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        load_properties70(parser)
+    }
+
+    /// Returns `true` if the property with the given name exists.
+    pub fn contains(&self, name: &str) -> bool {
+        self.values_empty.contains(name) || self.values_i64.contains_key(name) ||
+            self.values_f64.contains_key(name) ||
+            self.values_f64_2.contains_key(name) ||
+            self.values_f64_3.contains_key(name) ||
+            self.values_f64_4.contains_key(name) ||
+            self.values_f64_4x4.contains_key(name) ||
+            self.values_string.contains_key(name) ||
+            self.values_binary.contains_key(name)
+    }
+
+    /// Returns the property value, regardless of the type.
+    pub fn get_ref(&self, name: &str) -> Option<PropertyRef<'_>> {
+        if self.values_empty.contains(name) {
+            return Some(PropertyRef::Empty);
+        }
+        macro_rules! find {
+            ($field:ident, $variant:ident, $conv:expr) => {
+                if let Some(v) = self.$field.get(name) {
+                    return Some(PropertyRef::$variant($conv(v.value())));
+                }
+            }
+        }
+        find!(values_i64, I64, |v: &i64| *v);
+        find!(values_f64, F64, |v: &f64| *v);
+        find!(values_f64_2, F64x2, |v| v);
+        find!(values_f64_3, F64x3, |v| v);
+        find!(values_f64_4, F64x4, |v| v);
+        find!(values_f64_4x4, F64x4x4, |v| v);
+        find!(values_string, String, String::as_str);
+        find!(values_binary, Binary, Vec::as_slice);
+        None
+    }
+
+    /// Returns the property value.
+    ///
+    /// `f` selects the map for the value type, for example `|p| &p.values_f64`.
+    /// Typed getters such as `get_f64()` are usually more convenient.
+    pub fn get<T, F>(&self, name: &str, f: F) -> Option<&T>
+    where
+        F: Fn(&Properties70) -> &PropertyMap<T>,
+    {
+        f(self).get(name).map(|v| v.value())
+    }
+
+    impl_typed_property_getters!();
+
+    /// Returns the properties with fallback to the property template in `Definitions` (and
+    /// to the FBX SDK defaults).
+    ///
+    /// See `ResolvedProperties::new()` for `object_type` and `node_type`.
+    pub fn resolve<'a>(
+        &'a self,
+        definitions: &'a Definitions,
+        object_type: &str,
+        node_type: &str,
+    ) -> ResolvedProperties<'a> {
+        ResolvedProperties::new(self, definitions, object_type, node_type)
+    }
+
+    /// Creates a `Properties70` node with `P` children, sorted by the property names.
+    ///
+    /// Types, labels, and flags of the properties are not stored in `Properties70`, so
+    /// the common ones for the value types are used.
+    /// Use `GenericNode::validate()` to check whether the node can be written as FBX binary; the
+    /// errors point to the offending properties by name.
+    pub fn to_generic_node(&self) -> GenericNode {
+        /// Creates a `P` node.
+        fn prop(
+            name: &str,
+            type_name: &str,
+            label: &str,
+            values: Vec<OwnedAttribute>,
+        ) -> GenericNode {
+            let mut attributes = vec![
+                OwnedAttribute::String(Ok(name.to_owned())),
+                OwnedAttribute::String(Ok(type_name.to_owned())),
+                OwnedAttribute::String(Ok(label.to_owned())),
+                OwnedAttribute::String(Ok(String::new())),
+            ];
+            attributes.extend(values);
+            GenericNode {
+                name: b"P".to_vec(),
+                attributes,
+                children: Vec::new(),
+            }
+        }
+        /// Creates `f64` attributes.
+        fn f64s(values: &[f64]) -> Vec<OwnedAttribute> {
+            values.iter().map(|&v| OwnedAttribute::F64(v)).collect()
+        }
+
+        let mut props = Vec::new();
+        for name in &self.values_empty {
+            props.push(prop(name, "Compound", "", vec![]));
+        }
+        for (name, v) in &self.values_i64 {
+            let v = *v.value();
+            props.push(if v >= i64::from(i32::MIN) && v <= i64::from(i32::MAX) {
+                prop(name, "int", "Integer", vec![OwnedAttribute::I32(v as i32)])
+            } else {
+                prop(name, "ULongLong", "", vec![OwnedAttribute::I64(v)])
+            });
+        }
+        for (name, v) in &self.values_f64 {
+            props.push(prop(name, "double", "Number", f64s(&[*v.value()])));
+        }
+        for (name, v) in &self.values_f64_2 {
+            props.push(prop(name, "Vector2D", "Vector2", f64s(v.value())));
+        }
+        for (name, v) in &self.values_f64_3 {
+            props.push(prop(name, "Vector3D", "Vector", f64s(v.value())));
+        }
+        for (name, v) in &self.values_f64_4 {
+            props.push(prop(name, "ColorAndAlpha", "", f64s(v.value())));
+        }
+        for (name, v) in &self.values_f64_4x4 {
+            let values = v.value().iter().flat_map(|row| f64s(row)).collect();
+            props.push(prop(name, "Matrix4x4", "", values));
+        }
+        for (name, v) in &self.values_string {
+            let value = OwnedAttribute::String(Ok(v.value().clone()));
+            props.push(prop(name, "KString", "", vec![value]));
+        }
+        for (name, v) in &self.values_binary {
+            let value = OwnedAttribute::Binary(v.value().clone().into_boxed_slice());
+            props.push(prop(name, "Blob", "", vec![value]));
+        }
+        // The first attribute is the property name.
+        props.sort_by(|a, b| a.attributes[0].partial_cmp(&b.attributes[0]).unwrap());
+
+        GenericNode {
+            name: b"Properties70".to_vec(),
+            attributes: Vec::new(),
+            children: props,
+        }
+    }
+}
+
+impl fmt::Debug for Properties70 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut format = f.debug_struct("Properties70");
+        macro_rules! show {
+            ($field:ident) => {
+                if !self.$field.is_empty() {
+                    format.field(stringify!($field), &self.$field);
+                }
+            }
+        }
+        show!(values_empty);
+        show!(values_i64);
+        show!(values_f64);
+        show!(values_f64_2);
+        show!(values_f64_3);
+        show!(values_f64_4);
+        show!(values_f64_4x4);
+        show!(values_string);
+        show!(values_binary);
+        format.finish()
+    }
+}
+
+
+/// Reference to a property value of any type.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PropertyRef<'a> {
+    /// Property without values.
+    Empty,
+    /// `i64` value.
+    I64(i64),
+    /// `f64` value.
+    F64(f64),
+    /// `[f64; 2]` value.
+    F64x2(&'a [f64; 2]),
+    /// `[f64; 3]` value.
+    F64x3(&'a [f64; 3]),
+    /// `[f64; 4]` value.
+    F64x4(&'a [f64; 4]),
+    /// `[[f64; 4]; 4]` value.
+    F64x4x4(&'a [[f64; 4]; 4]),
+    /// String value.
+    String(&'a str),
+    /// Binary value.
+    Binary(&'a [u8]),
+}
+
+impl<'a> PropertyRef<'a> {
+    /// Returns the name of the value type.
+    pub fn type_name(&self) -> &'static str {
+        match *self {
+            PropertyRef::Empty => "()",
+            PropertyRef::I64(_) => "i64",
+            PropertyRef::F64(_) => "f64",
+            PropertyRef::F64x2(_) => "[f64; 2]",
+            PropertyRef::F64x3(_) => "[f64; 3]",
+            PropertyRef::F64x4(_) => "[f64; 4]",
+            PropertyRef::F64x4x4(_) => "[[f64; 4]; 4]",
+            PropertyRef::String(_) => "String",
+            PropertyRef::Binary(_) => "Vec<u8>",
+        }
+    }
+
+    /// Converts the value into the given type, coercing between compatible numeric types.
+    ///
+    /// Returns `Err(Error::IncompatibleProperty)` if the value cannot be converted.
+    pub fn coerce<T: FromPropertyValue>(self, name: &str) -> Result<T> {
+        T::from_property_value(self).ok_or_else(|| {
+            Error::IncompatibleProperty {
+                property: name.to_owned(),
+                expected: T::TYPE_NAME,
+                found: self.type_name(),
+            }
+        })
+    }
+}
+
+
+/// Types which property values can be converted into.
+///
+/// Exporters are inconsistent about the storage types of properties, so numeric values are
+/// converted between compatible types:
+///
+/// * integers are converted into `f64`,
+/// * `f64` values without fractional parts are converted into integers, and
+/// * integers and `f64` values are converted into `bool` (non-zero is `true`).
+pub trait FromPropertyValue: Sized {
+    /// Name of the type.
+    const TYPE_NAME: &'static str;
+
+    /// Converts the value, or returns `None` if the type is incompatible.
+    fn from_property_value(value: PropertyRef) -> Option<Self>;
+}
+
+impl FromPropertyValue for i64 {
+    const TYPE_NAME: &'static str = "i64";
+
+    fn from_property_value(value: PropertyRef) -> Option<Self> {
+        match value {
+            PropertyRef::I64(v) => Some(v),
+            PropertyRef::F64(v) if v.fract() == 0.0 && v.abs() < (1u64 << 63) as f64 => {
+                Some(v as i64)
+            },
+            _ => None,
+        }
+    }
+}
+
+impl FromPropertyValue for bool {
+    const TYPE_NAME: &'static str = "bool";
+
+    fn from_property_value(value: PropertyRef) -> Option<Self> {
+        match value {
+            PropertyRef::I64(v) => Some(v != 0),
+            PropertyRef::F64(v) => Some(v != 0.0),
+            _ => None,
+        }
+    }
+}
+
+impl FromPropertyValue for f64 {
+    const TYPE_NAME: &'static str = "f64";
+
+    fn from_property_value(value: PropertyRef) -> Option<Self> {
+        match value {
+            PropertyRef::I64(v) => Some(v as f64),
+            PropertyRef::F64(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+macro_rules! impl_from_property_value_for_f64_array {
+    ($t:ty, $variant:ident) => {
+        impl FromPropertyValue for $t {
+            const TYPE_NAME: &'static str = stringify!($t);
+
+            fn from_property_value(value: PropertyRef) -> Option<Self> {
+                match value {
+                    PropertyRef::$variant(v) => Some(*v),
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+impl_from_property_value_for_f64_array!([f64; 2], F64x2);
+impl_from_property_value_for_f64_array!([f64; 3], F64x3);
+impl_from_property_value_for_f64_array!([f64; 4], F64x4);
+impl_from_property_value_for_f64_array!([[f64; 4]; 4], F64x4x4);
+
+impl FromPropertyValue for String {
+    const TYPE_NAME: &'static str = "String";
+
+    fn from_property_value(value: PropertyRef) -> Option<Self> {
+        match value {
+            PropertyRef::String(v) => Some(v.to_owned()),
+            _ => None,
+        }
+    }
+}
+
+impl FromPropertyValue for Vec<u8> {
+    const TYPE_NAME: &'static str = "Vec<u8>";
+
+    fn from_property_value(value: PropertyRef) -> Option<Self> {
+        match value {
+            PropertyRef::Binary(v) => Some(v.to_owned()),
+            PropertyRef::String(v) => Some(v.as_bytes().to_owned()),
+            _ => None,
+        }
+    }
+}
+
+
+/// A type of property value and its metadata.
+///
+/// Type, label, and flags will be ignored currently.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertyValue<T>(T);
+
+impl<T> PropertyValue<T> {
+    /// Creates a new `PropertyValue` with the given value.
+    pub fn new(v: T) -> Self {
+        PropertyValue(v)
+    }
+
+    /// Returns the value.
+    pub fn value(&self) -> &T {
+        &self.0
+    }
+
+    /// Returns the value with the ownership.
+    pub fn take_value(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for PropertyValue<T> {
+    fn from(v: T) -> Self {
+        PropertyValue::new(v)
+    }
+}
+
+
+/// Loads a `Properties70` node.
+fn load_properties70<R, P>(mut parser: P) -> Result<Properties70>
+where
+    R: ParserSource,
+    P: Parser<R>,
+{
+    let mut props = Properties70::new();
+
+    loop {
+        try_get_node_attrs!(parser, |name: &str, attrs| if name == "P" {
+            load_property(&mut props, attrs)
+        } else {
+            warn!("Expected `P` node but got `{}` in `Properties70`", DisplayPath(name));
+            Err(Error::UnexpectedNode(name.to_owned()))
+        });
+        parser.skip_current_node()?;
+    }
+    Ok(props)
+}
+
+
+/// Loads a `P` node in `Properties70`.
+fn load_property<R>(props: &mut Properties70, mut attrs: Attributes<R>) -> Result<()>
+where
+    R: ParserSource,
+{
+    use pull_parser::utils::AttributeValues;
+
+    // `type_name`, `label`, `flags` are `String`s, but ignore here because they are currently
+    // unused.
+    let (name, _type_name, _label, _flags) =
+        <(String, (), (), ())>::from_attributes(&mut attrs)?
+            .ok_or_else(|| Error::InvalidAttribute("P".to_owned()))?;
+
+    if attrs.rest_attributes() == 0 {
+        // Empty attribute.
+        props.values_empty.insert(name);
+        return Ok(());
+    }
+
+    // From multiple attributes, only `[f64]` property can be created.
+    // Actually, there are property nodes with name=`filmboxTypeID` seems to have values
+    // `5i16, 5i16, 5i16`. However, it seems that it can be regarded as single `5i16`.
+    // Some exporters write vectors (such as `Lcl Translation`) as `i32` or `i64` values, and they
+    // are converted into `f64`s.
+    let has_multiple_values = attrs.rest_attributes() > 1;
+    let first: f64 = match attrs.next_attribute()? {
+        None => {
+            props.values_empty.insert(name);
+            return Ok(());
+        },
+        Some(Attribute::Primitive(PrimitiveAttribute::I16(val))) => {
+            props.values_i64.insert(name, (val as i64).into());
+            return Ok(());
+        },
+        Some(Attribute::Primitive(PrimitiveAttribute::I32(val)))
+            if has_multiple_values => val as f64,
+        Some(Attribute::Primitive(PrimitiveAttribute::I32(val))) => {
+            props.values_i64.insert(name, (val as i64).into());
+            return Ok(());
+        },
+        Some(Attribute::Primitive(PrimitiveAttribute::I64(val)))
+            if has_multiple_values => val as f64,
+        Some(Attribute::Primitive(PrimitiveAttribute::I64(val))) => {
+            props.values_i64.insert(name, val.into());
+            return Ok(());
+        },
+        Some(Attribute::Primitive(PrimitiveAttribute::F32(val))) => {
+            props.values_f64.insert(name, (val as f64).into());
+            return Ok(());
+        },
+        Some(Attribute::Primitive(PrimitiveAttribute::F64(val))) => val,
+        Some(Attribute::Special(attr)) => {
+            use pull_parser::SpecialAttributeType;
+
+            let value_type = attr.value_type();
+            let vec = attr.into_vec()?;
+            match value_type {
+                SpecialAttributeType::String => {
+                    match String::from_utf8(vec) {
+                        Ok(val) => {
+                            props.values_string.insert(name, val.into());
+                        },
+                        Err(err) => {
+                            props.values_binary.insert(name, err.into_bytes().into());
+                        },
+                    }
+                },
+                SpecialAttributeType::Binary => {
+                    props.values_binary.insert(name, vec.into());
+                },
+            }
+            return Ok(());
+        },
+        _ => return Err(Error::InvalidAttribute("P".into())),
+    };
+
+    load_property_rest_f64s(props, attrs, name, first)
+}
+
+
+/// Loads rest `f64` values of a `P` node in `Properties70`.
+fn load_property_rest_f64s<R>(
+    props: &mut Properties70,
+    mut attrs: Attributes<R>,
+    name: String,
+    first: f64,
+) -> Result<()>
+where
+    R: ParserSource,
+{
+    let invalid_attr = || Error::InvalidAttribute("P".into());
+
+    match attrs.rest_attributes() {
+        0 => {
+            props.values_f64.insert(name, first.into());
+            Ok(())
+        },
+        1 => {
+            let second = attrs.convert_into()?.ok_or_else(invalid_attr)?;
+            props.values_f64_2.insert(name, [first, second].into());
+            Ok(())
+        },
+        2 => {
+            let second = attrs.convert_into()?.ok_or_else(&invalid_attr)?;
+            let third = attrs.convert_into()?.ok_or_else(&invalid_attr)?;
+            props.values_f64_3.insert(
+                name,
+                [first, second, third].into(),
+            );
+            Ok(())
+        },
+        3 => {
+            let second = attrs.convert_into()?.ok_or_else(&invalid_attr)?;
+            let third = attrs.convert_into()?.ok_or_else(&invalid_attr)?;
+            let fourth = attrs.convert_into()?.ok_or_else(&invalid_attr)?;
+            props.values_f64_4.insert(
+                name,
+                [first, second, third, fourth].into(),
+            );
+            Ok(())
+        },
+        15 => {
+            let second = attrs.convert_into()?.ok_or_else(&invalid_attr)?;
+            let third = attrs.convert_into()?.ok_or_else(&invalid_attr)?;
+            let fourth = attrs.convert_into()?.ok_or_else(&invalid_attr)?;
+            let vec1 = [first, second, third, fourth];
+            let vec2 = {
+                let t: (f64, f64, f64, f64) = attrs.convert_into()?.ok_or_else(&invalid_attr)?;
+                [t.0, t.1, t.2, t.3]
+            };
+            let vec3 = {
+                let t: (f64, f64, f64, f64) = attrs.convert_into()?.ok_or_else(&invalid_attr)?;
+                [t.0, t.1, t.2, t.3]
+            };
+            let vec4 = {
+                let t: (f64, f64, f64, f64) = attrs.convert_into()?.ok_or_else(&invalid_attr)?;
+                [t.0, t.1, t.2, t.3]
+            };
+            props.values_f64_4x4.insert(
+                name,
+                [vec1, vec2, vec3, vec4].into(),
+            );
+            Ok(())
+        },
+        n => {
+            error!(
+                "Supported length of node property with f64 values are 1, 2, 3, 4 and 16, but \
+                    got {}",
+                n + 1
+            );
+            Err(Error::InvalidAttribute("P".into()))
+        },
+    }
+}
+
+
+impl MemoryUsage for Properties70 {
+    fn heap_usage(&self) -> usize {
+        self.values_empty.heap_usage() +
+            self.values_i64.heap_usage() +
+            self.values_f64.heap_usage() +
+            self.values_f64_2.heap_usage() +
+            self.values_f64_3.heap_usage() +
+            self.values_f64_4.heap_usage() +
+            self.values_f64_4x4.heap_usage() +
+            self.values_string.heap_usage() +
+            self.values_binary.heap_usage()
+    }
+}
+
+
+impl<T: MemoryUsage> MemoryUsage for PropertyValue<T> {
+    fn heap_usage(&self) -> usize {
+        self.0.heap_usage()
+    }
+}
+
+
+impl ApproxEq for Properties70 {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.values_empty == other.values_empty &&
+            self.values_i64 == other.values_i64 &&
+            self.values_f64.approx_eq(&other.values_f64, epsilon) &&
+            self.values_f64_2.approx_eq(&other.values_f64_2, epsilon) &&
+            self.values_f64_3.approx_eq(&other.values_f64_3, epsilon) &&
+            self.values_f64_4.approx_eq(&other.values_f64_4, epsilon) &&
+            self.values_f64_4x4.approx_eq(&other.values_f64_4x4, epsilon) &&
+            self.values_string == other.values_string &&
+            self.values_binary == other.values_binary
+    }
+}
+
+
+impl<T: ApproxEq> ApproxEq for PropertyValue<T> {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.0.approx_eq(&other.0, epsilon)
+    }
+}
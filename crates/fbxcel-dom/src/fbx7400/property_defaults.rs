@@ -0,0 +1,223 @@
+//! Default values of well-known properties.
+//!
+//! FBX exporters may omit properties with default values both from object instances and from
+//! property templates (in `Definitions`).
+//! The tables here are the defaults used by the FBX SDK in such cases.
+
+use crate::fbx7400::{Definitions, Properties70, PropertyMap, PropertyRef};
+use crate::fbx7400::property_names as names;
+
+
+/// Default `[f64; 3]` values, as `(node type, property name, value)`.
+const DEFAULTS_F64_3: &[(&str, &str, [f64; 3])] = &[
+    ("FbxNode", names::LCL_TRANSLATION, [0.0, 0.0, 0.0]),
+    ("FbxNode", names::LCL_ROTATION, [0.0, 0.0, 0.0]),
+    ("FbxNode", names::LCL_SCALING, [1.0, 1.0, 1.0]),
+    ("FbxNode", "PreRotation", [0.0, 0.0, 0.0]),
+    ("FbxNode", "PostRotation", [0.0, 0.0, 0.0]),
+    ("FbxNode", "RotationOffset", [0.0, 0.0, 0.0]),
+    ("FbxNode", "RotationPivot", [0.0, 0.0, 0.0]),
+    ("FbxNode", "ScalingOffset", [0.0, 0.0, 0.0]),
+    ("FbxNode", "ScalingPivot", [0.0, 0.0, 0.0]),
+    ("FbxLight", names::COLOR, [1.0, 1.0, 1.0]),
+    ("FbxSurfaceLambert", names::AMBIENT_COLOR, [0.2, 0.2, 0.2]),
+    ("FbxSurfaceLambert", names::DIFFUSE_COLOR, [0.8, 0.8, 0.8]),
+    ("FbxSurfaceLambert", names::EMISSIVE_COLOR, [0.0, 0.0, 0.0]),
+    ("FbxSurfaceLambert", names::TRANSPARENT_COLOR, [0.0, 0.0, 0.0]),
+    ("FbxSurfacePhong", names::AMBIENT_COLOR, [0.2, 0.2, 0.2]),
+    ("FbxSurfacePhong", names::DIFFUSE_COLOR, [0.8, 0.8, 0.8]),
+    ("FbxSurfacePhong", names::EMISSIVE_COLOR, [0.0, 0.0, 0.0]),
+    ("FbxSurfacePhong", names::TRANSPARENT_COLOR, [0.0, 0.0, 0.0]),
+    ("FbxSurfacePhong", names::SPECULAR_COLOR, [0.2, 0.2, 0.2]),
+    ("FbxSurfacePhong", names::REFLECTION_COLOR, [0.0, 0.0, 0.0]),
+];
+
+/// Default `f64` values, as `(node type, property name, value)`.
+const DEFAULTS_F64: &[(&str, &str, f64)] = &[
+    ("FbxNode", names::VISIBILITY, 1.0),
+    ("FbxCamera", names::FIELD_OF_VIEW, 25.114_999),
+    ("FbxCamera", names::FOCAL_LENGTH, 34.893_270),
+    ("FbxCamera", "NearPlane", 10.0),
+    ("FbxCamera", "FarPlane", 4000.0),
+    ("FbxCamera", "AspectWidth", 320.0),
+    ("FbxCamera", "AspectHeight", 200.0),
+    ("FbxCamera", "FilmWidth", 0.816),
+    ("FbxCamera", "FilmHeight", 0.612),
+    ("FbxLight", names::INTENSITY, 100.0),
+    ("FbxLight", "InnerAngle", 0.0),
+    ("FbxLight", "OuterAngle", 45.0),
+    ("FbxSurfaceLambert", names::DIFFUSE_FACTOR, 1.0),
+    ("FbxSurfaceLambert", "AmbientFactor", 1.0),
+    ("FbxSurfaceLambert", names::EMISSIVE_FACTOR, 1.0),
+    ("FbxSurfaceLambert", names::TRANSPARENCY_FACTOR, 0.0),
+    ("FbxSurfacePhong", names::DIFFUSE_FACTOR, 1.0),
+    ("FbxSurfacePhong", "AmbientFactor", 1.0),
+    ("FbxSurfacePhong", names::EMISSIVE_FACTOR, 1.0),
+    ("FbxSurfacePhong", names::TRANSPARENCY_FACTOR, 0.0),
+    ("FbxSurfacePhong", names::SPECULAR_FACTOR, 1.0),
+    ("FbxSurfacePhong", names::SHININESS_EXPONENT, 20.0),
+    ("FbxSurfacePhong", "ReflectionFactor", 1.0),
+    ("FbxBlendShapeChannel", names::DEFORM_PERCENT, 0.0),
+];
+
+/// Default `i64` values, as `(node type, property name, value)`.
+const DEFAULTS_I64: &[(&str, &str, i64)] = &[
+    ("FbxNode", "RotationOrder", 0),
+    ("FbxNode", "InheritType", 0),
+    ("FbxLight", "LightType", 0),
+    ("FbxLight", "CastLight", 1),
+    ("FbxLight", "CastShadows", 0),
+];
+
+
+/// Returns the FBX SDK default values of well-known properties of the given node type (such as
+/// `FbxNode` and `FbxCamera`).
+///
+/// Returns empty properties if the node type is unknown.
+pub fn sdk_defaults(node_type: &str) -> Properties70 {
+    let mut props = Properties70::new();
+    for &(_, name, v) in DEFAULTS_F64_3.iter().filter(|e| e.0 == node_type) {
+        props.values_f64_3.insert(name.to_owned(), v.into());
+    }
+    for &(_, name, v) in DEFAULTS_F64.iter().filter(|e| e.0 == node_type) {
+        props.values_f64.insert(name.to_owned(), v.into());
+    }
+    for &(_, name, v) in DEFAULTS_I64.iter().filter(|e| e.0 == node_type) {
+        props.values_i64.insert(name.to_owned(), v.into());
+    }
+    props
+}
+
+
+/// Properties of an object, resolved with the property template and the SDK defaults.
+///
+/// Values are looked up in the following order:
+///
+/// 1. Properties of the object instance.
+/// 2. Property template in `Definitions`.
+/// 3. FBX SDK defaults (see `sdk_defaults()`).
+#[derive(Debug, Clone)]
+pub struct ResolvedProperties<'a> {
+    /// Properties of the object instance.
+    instance: &'a Properties70,
+    /// Property template.
+    template: Option<&'a Properties70>,
+    /// SDK defaults.
+    defaults: Properties70,
+}
+
+impl<'a> ResolvedProperties<'a> {
+    /// Creates a new `ResolvedProperties`.
+    ///
+    /// `object_type` is the object type in `Definitions` (such as `Model`), and `node_type` is the
+    /// node type of the template (such as `FbxNode`).
+    pub fn new(
+        instance: &'a Properties70,
+        definitions: &'a Definitions,
+        object_type: &str,
+        node_type: &str,
+    ) -> Self {
+        ResolvedProperties {
+            instance,
+            template: definitions.get_properties70(object_type, node_type),
+            defaults: sdk_defaults(node_type),
+        }
+    }
+
+    /// Looks up and returns the property value.
+    pub fn get<T, F>(&self, name: &str, f: F) -> Option<&T>
+    where
+        F: Fn(&Properties70) -> &PropertyMap<T>,
+    {
+        f(self.instance)
+            .get(name)
+            .or_else(|| self.template.and_then(|template| f(template).get(name)))
+            .or_else(|| f(&self.defaults).get(name))
+            .map(|v| v.value())
+    }
+
+    /// Looks up and returns the property value, regardless of the type.
+    pub fn get_ref(&self, name: &str) -> Option<PropertyRef<'_>> {
+        self.instance
+            .get_ref(name)
+            .or_else(|| self.template.and_then(|template| template.get_ref(name)))
+            .or_else(|| self.defaults.get_ref(name))
+    }
+
+    /// Looks up and returns the `[f64; 3]` property value.
+    pub fn get_f64_3(&self, name: &str) -> Option<[f64; 3]> {
+        self.get_vec3(name)
+    }
+
+    impl_typed_property_getters!();
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::fbx7400::{Definitions, ObjectType, Properties70};
+    use crate::fbx7400::property_names as names;
+    use super::ResolvedProperties;
+
+    #[test]
+    fn test_resolve_with_sdk_defaults() {
+        let definitions = Definitions {
+            version: 100,
+            count: 0,
+            object_types: Vec::new(),
+        };
+        let mut instance = Properties70::new();
+        instance.values_f64_3.insert(names::LCL_TRANSLATION.to_owned(), [1.0, 2.0, 3.0].into());
+        let props = ResolvedProperties::new(&instance, &definitions, "Model", "FbxNode");
+        assert_eq!(props.get_f64_3(names::LCL_TRANSLATION), Some([1.0, 2.0, 3.0]));
+        assert_eq!(props.get_f64_3(names::LCL_SCALING), Some([1.0, 1.0, 1.0]));
+        assert_eq!(props.get_f64("NoSuchProperty"), None);
+    }
+
+    #[test]
+    fn test_typed_getters_with_template() {
+        let mut template = Properties70::new();
+        template.values_f64_4.insert("Color".to_owned(), [0.5, 0.5, 0.5, 1.0].into());
+        template.values_string.insert("Name".to_owned(), "template".to_owned().into());
+        let definitions = Definitions {
+            version: 100,
+            count: 1,
+            object_types: vec![ObjectType {
+                object_type: "Material".to_owned(),
+                count: 1,
+                property_template: vec![("FbxSurfacePhong".to_owned(), template)]
+                    .into_iter()
+                    .collect(),
+            }],
+        };
+        let mut instance = Properties70::new();
+        instance.values_i64.insert("Flag".to_owned(), 1.into());
+        assert_eq!(instance.get_bool("Flag"), Some(true));
+        assert_eq!(instance.get_string("Name"), None);
+
+        let props = instance.resolve(&definitions, "Material", "FbxSurfacePhong");
+        assert_eq!(props.get_enum::<u8>("Flag"), Some(1));
+        assert_eq!(props.get_color("Color"), Some([0.5, 0.5, 0.5]));
+        assert_eq!(props.get_string("Name"), Some("template"));
+        assert_eq!(props.get_color(names::DIFFUSE_COLOR), Some([0.8, 0.8, 0.8]));
+    }
+
+    #[test]
+    fn test_coercion() {
+        use crate::Error;
+
+        let mut props = Properties70::new();
+        props.values_i64.insert("Int".to_owned(), 1.into());
+        props.values_f64.insert("Float".to_owned(), 2.0.into());
+        props.values_string.insert("Name".to_owned(), "foo".to_owned().into());
+        assert_eq!(props.get_f64("Int"), Some(1.0));
+        assert_eq!(props.get_i64("Float"), Some(2));
+        assert_eq!(props.get_bool("Float"), Some(true));
+        assert_eq!(props.get_vec3("Name"), None);
+        match props.get_as::<[f64; 3]>("Name") {
+            Err(Error::IncompatibleProperty { expected: "[f64; 3]", found: "String", .. }) => {},
+            res => panic!("Unexpected result: {:?}", res),
+        }
+        assert_eq!(props.get_as::<f64>("NoSuchProperty").unwrap(), None);
+    }
+}
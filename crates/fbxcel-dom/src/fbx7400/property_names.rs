@@ -0,0 +1,120 @@
+//! Names of standard properties.
+//!
+//! These names are used as property names in `Properties70` and in `OP` connections.
+
+/// `Lcl Translation`.
+pub const LCL_TRANSLATION: &str = "Lcl Translation";
+/// `Lcl Rotation`.
+pub const LCL_ROTATION: &str = "Lcl Rotation";
+/// `Lcl Scaling`.
+pub const LCL_SCALING: &str = "Lcl Scaling";
+/// `RotationOffset`.
+pub const ROTATION_OFFSET: &str = "RotationOffset";
+/// `RotationPivot`.
+pub const ROTATION_PIVOT: &str = "RotationPivot";
+/// `ScalingOffset`.
+pub const SCALING_OFFSET: &str = "ScalingOffset";
+/// `ScalingPivot`.
+pub const SCALING_PIVOT: &str = "ScalingPivot";
+/// `GeometricTranslation`.
+pub const GEOMETRIC_TRANSLATION: &str = "GeometricTranslation";
+/// `GeometricRotation`.
+pub const GEOMETRIC_ROTATION: &str = "GeometricRotation";
+/// `PreRotation`.
+pub const PRE_ROTATION: &str = "PreRotation";
+/// `PostRotation`.
+pub const POST_ROTATION: &str = "PostRotation";
+/// `RotationActive`.
+///
+/// `PreRotation`, `PostRotation`, and rotation limits are ignored unless this is `1`.
+pub const ROTATION_ACTIVE: &str = "RotationActive";
+/// `Visibility`.
+pub const VISIBILITY: &str = "Visibility";
+/// `DeformPercent` (of blend shape channels).
+pub const DEFORM_PERCENT: &str = "DeformPercent";
+/// `FieldOfView` (of cameras).
+pub const FIELD_OF_VIEW: &str = "FieldOfView";
+/// `FocalLength` (of cameras).
+pub const FOCAL_LENGTH: &str = "FocalLength";
+/// `Intensity` (of lights).
+pub const INTENSITY: &str = "Intensity";
+/// `Color` (of lights).
+pub const COLOR: &str = "Color";
+
+/// `AmbientColor` (of materials).
+pub const AMBIENT_COLOR: &str = "AmbientColor";
+/// `DiffuseColor` (of materials).
+pub const DIFFUSE_COLOR: &str = "DiffuseColor";
+/// `DiffuseFactor` (of materials).
+pub const DIFFUSE_FACTOR: &str = "DiffuseFactor";
+/// `SpecularColor` (of materials).
+pub const SPECULAR_COLOR: &str = "SpecularColor";
+/// `SpecularFactor` (of materials).
+pub const SPECULAR_FACTOR: &str = "SpecularFactor";
+/// `ShininessExponent` (of materials).
+pub const SHININESS_EXPONENT: &str = "ShininessExponent";
+/// `EmissiveColor` (of materials).
+pub const EMISSIVE_COLOR: &str = "EmissiveColor";
+/// `EmissiveFactor` (of materials).
+pub const EMISSIVE_FACTOR: &str = "EmissiveFactor";
+/// `TransparentColor` (of materials).
+pub const TRANSPARENT_COLOR: &str = "TransparentColor";
+/// `TransparencyFactor` (of materials).
+pub const TRANSPARENCY_FACTOR: &str = "TransparencyFactor";
+/// `ReflectionColor` (of materials).
+pub const REFLECTION_COLOR: &str = "ReflectionColor";
+/// `NormalMap` (of materials).
+pub const NORMAL_MAP: &str = "NormalMap";
+/// `Bump` (of materials).
+pub const BUMP: &str = "Bump";
+
+/// Prefix of animation curve channel names.
+///
+/// Animation curves are connected to animation curve nodes with property names such as `d|X`.
+pub const CURVE_CHANNEL_PREFIX: &str = "d|";
+/// `d|X`.
+pub const CURVE_CHANNEL_X: &str = "d|X";
+/// `d|Y`.
+pub const CURVE_CHANNEL_Y: &str = "d|Y";
+/// `d|Z`.
+pub const CURVE_CHANNEL_Z: &str = "d|Z";
+
+/// `UnitScaleFactor` (of global settings).
+///
+/// This is the length of the unit in centimeters.
+pub const UNIT_SCALE_FACTOR: &str = "UnitScaleFactor";
+/// `OriginalUnitScaleFactor` (of global settings).
+pub const ORIGINAL_UNIT_SCALE_FACTOR: &str = "OriginalUnitScaleFactor";
+/// `UpAxis` (of global settings).
+pub const UP_AXIS: &str = "UpAxis";
+/// `UpAxisSign` (of global settings).
+pub const UP_AXIS_SIGN: &str = "UpAxisSign";
+/// `FrontAxis` (of global settings).
+pub const FRONT_AXIS: &str = "FrontAxis";
+/// `FrontAxisSign` (of global settings).
+pub const FRONT_AXIS_SIGN: &str = "FrontAxisSign";
+/// `CoordAxis` (of global settings).
+pub const COORD_AXIS: &str = "CoordAxis";
+/// `CoordAxisSign` (of global settings).
+pub const COORD_AXIS_SIGN: &str = "CoordAxisSign";
+/// `TimeMode` (of global settings).
+pub const TIME_MODE: &str = "TimeMode";
+/// `CustomFrameRate` (of global settings).
+pub const CUSTOM_FRAME_RATE: &str = "CustomFrameRate";
+/// `TimeSpanStart` (of global settings).
+pub const TIME_SPAN_START: &str = "TimeSpanStart";
+/// `TimeSpanStop` (of global settings).
+pub const TIME_SPAN_STOP: &str = "TimeSpanStop";
+
+/// `LocalStart` (of animation stacks).
+pub const LOCAL_START: &str = "LocalStart";
+/// `LocalStop` (of animation stacks).
+pub const LOCAL_STOP: &str = "LocalStop";
+/// `ReferenceStart` (of animation stacks).
+pub const REFERENCE_START: &str = "ReferenceStart";
+/// `ReferenceStop` (of animation stacks).
+pub const REFERENCE_STOP: &str = "ReferenceStop";
+/// `Weight` (of animation layers).
+///
+/// This is the weight in percent.
+pub const WEIGHT: &str = "Weight";
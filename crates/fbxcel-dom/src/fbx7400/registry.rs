@@ -0,0 +1,311 @@
+//! Registry of object loaders.
+//!
+//! Crates can register loaders for specific `(class, subclass)` pairs, and `RegistryObjectsLoader`
+//! dispatches object nodes to them.
+//! Objects without registered loaders are loaded as `GenericObject`.
+
+use std::any::Any;
+use std::fmt;
+use fnv::FnvHashMap;
+use pull_parser::{ParserSource, SubtreeParser};
+use crate::Result;
+use crate::fbx7400::{GenericObject, LoadObjects7400, NodesBeforeObjects};
+use crate::fbx7400::ObjectProperties;
+
+
+/// Loader of objects with specific class and subclass.
+pub trait ObjectLoader<R: ParserSource> {
+    /// Loads an object.
+    ///
+    /// The returned value can be retrieved by `LoadedObject::downcast_ref()`.
+    fn load(
+        &self,
+        props: &ObjectProperties,
+        subtree_parser: &mut SubtreeParser<R>,
+        nodes_before_objects: &NodesBeforeObjects,
+    ) -> Result<Box<dyn Any + Send + Sync>>;
+}
+
+impl<R, F> ObjectLoader<R> for F
+where
+    R: ParserSource,
+    F: Fn(&ObjectProperties, &mut SubtreeParser<R>, &NodesBeforeObjects)
+        -> Result<Box<dyn Any + Send + Sync>>,
+{
+    fn load(
+        &self,
+        props: &ObjectProperties,
+        subtree_parser: &mut SubtreeParser<R>,
+        nodes_before_objects: &NodesBeforeObjects,
+    ) -> Result<Box<dyn Any + Send + Sync>> {
+        self(props, subtree_parser, nodes_before_objects)
+    }
+}
+
+
+/// Registry of object loaders, keyed by `(class, subclass)`.
+pub struct ObjectRegistry<R> {
+    /// Loaders.
+    loaders: FnvHashMap<(String, String), Box<dyn ObjectLoader<R> + Send + Sync>>,
+}
+
+impl<R: ParserSource> ObjectRegistry<R> {
+    /// Creates a new empty `ObjectRegistry`.
+    pub fn new() -> Self {
+        ObjectRegistry { loaders: FnvHashMap::default() }
+    }
+
+    /// Registers the loader for the given class and subclass.
+    ///
+    /// Returns the previously registered loader for the pair, if any.
+    pub fn register<L>(
+        &mut self,
+        class: &str,
+        subclass: &str,
+        loader: L,
+    ) -> Option<Box<dyn ObjectLoader<R> + Send + Sync>>
+    where
+        L: ObjectLoader<R> + Send + Sync + 'static,
+    {
+        self.loaders.insert(
+            (class.to_owned(), subclass.to_owned()),
+            Box::new(loader),
+        )
+    }
+
+    /// Returns whether a loader is registered for the given class and subclass.
+    pub fn contains(&self, class: &str, subclass: &str) -> bool {
+        self.loaders.contains_key(&(class.to_owned(), subclass.to_owned()))
+    }
+
+    /// Returns the loader for the given class and subclass.
+    pub fn get(
+        &self,
+        class: &str,
+        subclass: &str,
+    ) -> Option<&(dyn ObjectLoader<R> + Send + Sync)> {
+        self.loaders
+            .get(&(class.to_owned(), subclass.to_owned()))
+            .map(|loader| loader.as_ref())
+    }
+}
+
+impl<R: ParserSource> Default for ObjectRegistry<R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R> fmt::Debug for ObjectRegistry<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.loaders.keys()).finish()
+    }
+}
+
+
+/// Object loaded by `RegistryObjectsLoader`.
+pub enum LoadedObject {
+    /// Object loaded by the registered loader.
+    Custom {
+        /// Properties common to object nodes.
+        props: ObjectProperties,
+        /// Value returned by the loader.
+        value: Box<dyn Any + Send + Sync>,
+    },
+    /// Object without registered loader.
+    Generic(Box<GenericObject>),
+}
+
+impl LoadedObject {
+    /// Returns the properties common to object nodes.
+    pub fn props(&self) -> &ObjectProperties {
+        match *self {
+            LoadedObject::Custom { ref props, .. } => props,
+            LoadedObject::Generic(ref obj) => &obj.props,
+        }
+    }
+
+    /// Returns the reference to the value loaded by the registered loader, if the type matches.
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        match *self {
+            LoadedObject::Custom { ref value, .. } => value.downcast_ref(),
+            LoadedObject::Generic(_) => None,
+        }
+    }
+
+    /// Returns the generic object, if the object has no registered loader.
+    pub fn as_generic(&self) -> Option<&GenericObject> {
+        match *self {
+            LoadedObject::Custom { .. } => None,
+            LoadedObject::Generic(ref obj) => Some(obj),
+        }
+    }
+}
+
+impl fmt::Debug for LoadedObject {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            LoadedObject::Custom { ref props, .. } => {
+                f.debug_struct("Custom").field("props", props).finish()
+            },
+            LoadedObject::Generic(ref obj) => f.debug_tuple("Generic").field(obj).finish(),
+        }
+    }
+}
+
+
+/// Objects loader which dispatches objects to the loaders in the registry.
+#[derive(Debug)]
+pub struct RegistryObjectsLoader<R> {
+    /// Registry.
+    registry: ObjectRegistry<R>,
+    /// Loaded objects.
+    objects: Vec<LoadedObject>,
+}
+
+impl<R: ParserSource> RegistryObjectsLoader<R> {
+    /// Creates a new `RegistryObjectsLoader`.
+    pub fn new(registry: ObjectRegistry<R>) -> Self {
+        RegistryObjectsLoader {
+            registry,
+            objects: Vec::new(),
+        }
+    }
+}
+
+impl<R: ParserSource> LoadObjects7400 for RegistryObjectsLoader<R> {
+    type Reader = R;
+    type Objects = Vec<LoadedObject>;
+
+    fn build(self) -> Result<Self::Objects> {
+        Ok(self.objects)
+    }
+
+    fn load(
+        &mut self,
+        props: ObjectProperties,
+        subtree_parser: &mut SubtreeParser<Self::Reader>,
+        nodes_before_objects: &NodesBeforeObjects,
+    ) -> Result<()> {
+        let obj = match self.registry.get(&props.class, &props.subclass) {
+            Some(loader) => {
+                let value = loader.load(&props, subtree_parser, nodes_before_objects)?;
+                LoadedObject::Custom {
+                    props,
+                    value,
+                }
+            },
+            None => LoadedObject::Generic(Box::new(GenericObject::load(subtree_parser, props)?)),
+        };
+        self.objects.push(obj);
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use pull_parser::{BasicSource, Event, Parser, RootParser};
+    use pull_parser::ascii::AsciiDocument;
+    use crate::{GenericNode, OwnedAttribute};
+    use super::*;
+
+    type R = BasicSource<Cursor<Vec<u8>>>;
+
+    /// Value loaded by the custom loader for meshes.
+    #[derive(Debug, PartialEq)]
+    struct MeshSummary {
+        name: String,
+        num_vertices: usize,
+    }
+
+    fn load_mesh(
+        props: &ObjectProperties,
+        subtree_parser: &mut SubtreeParser<R>,
+        _nodes_before_objects: &NodesBeforeObjects,
+    ) -> Result<Box<dyn Any + Send + Sync>> {
+        let (nodes, _) = GenericNode::load_from_parser(subtree_parser)?;
+        let num_vertices = nodes
+            .iter()
+            .find(|node| node.name == b"Vertices")
+            .and_then(|node| match node.attributes.first() {
+                Some(OwnedAttribute::ArrF64(vertices)) => Some(vertices.len() / 3),
+                _ => None,
+            })
+            .unwrap_or(0);
+        Ok(Box::new(MeshSummary {
+            name: props.name.clone(),
+            num_vertices,
+        }))
+    }
+
+    #[test]
+    fn dispatch_by_class_and_subclass() {
+        let source = "; FBX 7.4.0 project file\n\
+                      Objects:  {\n\
+                      \tGeometry: 1, \"Geometry::Tri\", \"Mesh\" {\n\
+                      \t\tVertices: *9 {\n\t\t\ta: 0,0,0,1,0,0,0,1,0\n\t\t}\n\
+                      \t}\n\
+                      \tGeometry: 2, \"Geometry::Curve\", \"NurbsCurve\" {\n\
+                      \t\tOrder: 4\n\
+                      \t}\n\
+                      \tTexture: 3, \"Texture::Wood\", \"\" {\n\
+                      \t}\n\
+                      }\n";
+        let mut registry = ObjectRegistry::<R>::new();
+        assert!(registry.register("Geometry", "Mesh", load_mesh).is_none());
+        assert!(registry.register("Geometry", "Mesh", load_mesh).is_some());
+        assert!(registry.contains("Geometry", "Mesh"));
+        assert!(!registry.contains("Geometry", "NurbsCurve"));
+        let mut objs_loader = RegistryObjectsLoader::new(registry);
+
+        let nodes_before_objects = NodesBeforeObjects {
+            version: 7400,
+            fbx_header_extension: Default::default(),
+            file_id: Default::default(),
+            creation_time: Default::default(),
+            creator: Default::default(),
+            global_settings: Default::default(),
+            documents: Default::default(),
+            references: Default::default(),
+            definitions: Default::default(),
+        };
+        let bytes = AsciiDocument::parse(source).unwrap().to_binary().unwrap();
+        let mut parser = RootParser::new(Cursor::new(bytes));
+        loop {
+            match parser.next_event().unwrap() {
+                Event::StartNode(node) => {
+                    let name = node.name().unwrap().to_owned();
+                    if name == "Objects" {
+                        continue;
+                    }
+                    let props = ObjectProperties::load(&name, node.attributes).unwrap();
+                    objs_loader
+                        .load(props, &mut parser.subtree_parser(), &nodes_before_objects)
+                        .unwrap();
+                },
+                Event::EndFbx(_) => break,
+                _ => {},
+            }
+        }
+        let objects = objs_loader.build().unwrap();
+
+        assert_eq!(objects.len(), 3);
+        assert_eq!(
+            objects[0].downcast_ref::<MeshSummary>(),
+            Some(&MeshSummary {
+                name: "Tri".to_owned(),
+                num_vertices: 3,
+            })
+        );
+        assert!(objects[0].downcast_ref::<String>().is_none());
+        assert!(objects[0].as_generic().is_none());
+        let curve = objects[1].as_generic().expect("Curve should be loaded as generic object");
+        assert_eq!(curve.props.subclass, "NurbsCurve");
+        assert_eq!(curve.nodes[0].name, b"Order");
+        assert!(objects[1].downcast_ref::<MeshSummary>().is_none());
+        assert_eq!(objects[2].props().class, "Texture");
+        assert!(objects[2].as_generic().is_some());
+    }
+}
@@ -0,0 +1,524 @@
+//! Schema of known FBX 7.4 nodes.
+//!
+//! This describes expected children and attribute types of nodes which are known to this crate.
+//! Note that real files sometimes have values with different (but compatible) types, for example
+//! `i64` version fields where `i32` is usual.
+//! Such differences are accepted by loose validation.
+
+use pull_parser::{Attribute, ArrayAttribute, ParserSource, PrimitiveAttribute};
+use pull_parser::SpecialAttributeType;
+use crate::{Result, Error, DisplayPath, GenericNode, OwnedAttribute};
+
+
+/// Type of a node attribute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AttributeType {
+    /// `bool`.
+    Bool,
+    /// `i16`.
+    I16,
+    /// `i32`.
+    I32,
+    /// `i64`.
+    I64,
+    /// `f32`.
+    F32,
+    /// `f64`.
+    F64,
+    /// Array of `bool`.
+    ArrBool,
+    /// Array of `i32`.
+    ArrI32,
+    /// Array of `i64`.
+    ArrI64,
+    /// Array of `f32`.
+    ArrF32,
+    /// Array of `f64`.
+    ArrF64,
+    /// String.
+    String,
+    /// Binary.
+    Binary,
+}
+
+impl AttributeType {
+    /// Returns the type of the given attribute.
+    pub fn of(attr: &OwnedAttribute) -> Self {
+        match *attr {
+            OwnedAttribute::Bool(_) => AttributeType::Bool,
+            OwnedAttribute::I16(_) => AttributeType::I16,
+            OwnedAttribute::I32(_) => AttributeType::I32,
+            OwnedAttribute::I64(_) => AttributeType::I64,
+            OwnedAttribute::F32(_) => AttributeType::F32,
+            OwnedAttribute::F64(_) => AttributeType::F64,
+            OwnedAttribute::ArrBool(_) => AttributeType::ArrBool,
+            OwnedAttribute::ArrI32(_) => AttributeType::ArrI32,
+            OwnedAttribute::ArrI64(_) => AttributeType::ArrI64,
+            OwnedAttribute::ArrF32(_) => AttributeType::ArrF32,
+            OwnedAttribute::ArrF64(_) => AttributeType::ArrF64,
+            OwnedAttribute::String(_) => AttributeType::String,
+            OwnedAttribute::Binary(_) => AttributeType::Binary,
+        }
+    }
+
+    /// Returns the type of the given parser attribute, without reading the value.
+    pub fn of_parser_attribute<R: ParserSource>(attr: &Attribute<R>) -> Self {
+        match *attr {
+            Attribute::Primitive(PrimitiveAttribute::Bool(_)) => AttributeType::Bool,
+            Attribute::Primitive(PrimitiveAttribute::I16(_)) => AttributeType::I16,
+            Attribute::Primitive(PrimitiveAttribute::I32(_)) => AttributeType::I32,
+            Attribute::Primitive(PrimitiveAttribute::I64(_)) => AttributeType::I64,
+            Attribute::Primitive(PrimitiveAttribute::F32(_)) => AttributeType::F32,
+            Attribute::Primitive(PrimitiveAttribute::F64(_)) => AttributeType::F64,
+            Attribute::Array(ArrayAttribute::Bool(_)) => AttributeType::ArrBool,
+            Attribute::Array(ArrayAttribute::I32(_)) => AttributeType::ArrI32,
+            Attribute::Array(ArrayAttribute::I64(_)) => AttributeType::ArrI64,
+            Attribute::Array(ArrayAttribute::F32(_)) => AttributeType::ArrF32,
+            Attribute::Array(ArrayAttribute::F64(_)) => AttributeType::ArrF64,
+            Attribute::Special(ref attr) => match attr.value_type() {
+                SpecialAttributeType::String => AttributeType::String,
+                SpecialAttributeType::Binary => AttributeType::Binary,
+            },
+        }
+    }
+
+    /// Returns `true` if the type is an integer type.
+    pub fn is_integer(&self) -> bool {
+        matches!(
+            *self,
+            AttributeType::I16 | AttributeType::I32 | AttributeType::I64
+        )
+    }
+
+    /// Returns `true` if the type is a floating-point number type.
+    pub fn is_float(&self) -> bool {
+        matches!(*self, AttributeType::F32 | AttributeType::F64)
+    }
+
+    /// Checks whether a value of the type `got` can be accepted as the type `self`.
+    ///
+    /// If `loose` is `false`, types should be exactly same.
+    /// If `loose` is `true`, integer types of different width are accepted, floating-point number
+    /// types of different precision are accepted, and so are arrays of them.
+    pub fn accepts(&self, got: AttributeType, loose: bool) -> bool {
+        if *self == got {
+            return true;
+        }
+        if !loose {
+            return false;
+        }
+        if (self.is_integer() && got.is_integer()) || (self.is_float() && got.is_float()) {
+            return true;
+        }
+        matches!(
+            (*self, got),
+            (AttributeType::ArrI32, AttributeType::ArrI64) |
+                (AttributeType::ArrI64, AttributeType::ArrI32) |
+                (AttributeType::ArrF32, AttributeType::ArrF64) |
+                (AttributeType::ArrF64, AttributeType::ArrF32)
+        )
+    }
+}
+
+
+/// Schema of a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeSchema {
+    /// Name of the parent node.
+    ///
+    /// `None` for toplevel nodes.
+    pub parent: Option<&'static str>,
+    /// Node name.
+    pub name: &'static str,
+    /// Attribute types.
+    pub attributes: &'static [AttributeType],
+    /// Number of required attributes.
+    ///
+    /// Attributes after the first `min_attributes` ones are optional.
+    pub min_attributes: usize,
+    /// `true` if the node can have more attributes than `attributes`.
+    pub extra_attributes: bool,
+    /// Known child node names.
+    pub children: &'static [&'static str],
+}
+
+impl NodeSchema {
+    /// Checks attribute types of a node.
+    ///
+    /// See [`AttributeType::accepts`](enum.AttributeType.html#method.accepts) for `loose`.
+    pub fn validate_attributes(&self, attrs: &[OwnedAttribute], loose: bool) -> Result<()> {
+        let invalid_attr = || Error::InvalidAttribute(self.name.to_owned());
+        if attrs.len() < self.min_attributes {
+            return Err(invalid_attr());
+        }
+        if !self.extra_attributes && attrs.len() > self.attributes.len() {
+            return Err(invalid_attr());
+        }
+        for (expected, attr) in self.attributes.iter().zip(attrs) {
+            if !expected.accepts(AttributeType::of(attr), loose) {
+                return Err(invalid_attr());
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns `true` if the given name is a known child node name.
+    pub fn has_child(&self, name: &str) -> bool {
+        self.children.contains(&name)
+    }
+}
+
+
+/// Returns the schema of the node with the given parent and name.
+///
+/// `parent` should be `None` for toplevel nodes.
+pub fn lookup(parent: Option<&str>, name: &str) -> Option<&'static NodeSchema> {
+    SCHEMAS.iter().find(|schema| {
+        schema.parent == parent && schema.name == name
+    })
+}
+
+
+/// Validates the given node and its descendants.
+///
+/// `parent` should be the name of the parent node, or `None` for toplevel nodes.
+/// Nodes not described by the schema are not checked (but their children are).
+pub fn validate_tree(node: &GenericNode, parent: Option<&str>, loose: bool) -> Result<()> {
+    if let Some(schema) = lookup(parent, &node.name_lossy()) {
+        schema.validate_attributes(&node.attributes, loose)?;
+        for child in &node.children {
+            if !schema.has_child(&child.name_lossy()) {
+                warn!(
+                    "Unknown child node `{}` for the node `{}` (parent={})",
+                    DisplayPath(&child.name_lossy()),
+                    DisplayPath(&node.name_lossy()),
+                    DisplayPath(parent.unwrap_or("(root)"))
+                );
+            }
+        }
+    }
+    for child in &node.children {
+        validate_tree(child, Some(&node.name_lossy()), loose)?;
+    }
+    Ok(())
+}
+
+
+macro_rules! node_schemas {
+    ($(
+        $parent:expr, $name:expr, [$($attr:ident),*], $min:expr, $extra:expr, [$($child:expr),*];
+    )*) => {
+        /// Schemas of known nodes.
+        pub static SCHEMAS: &[NodeSchema] = &[
+            $(NodeSchema {
+                parent: $parent,
+                name: $name,
+                attributes: &[$(AttributeType::$attr),*],
+                min_attributes: $min,
+                extra_attributes: $extra,
+                children: &[$($child),*],
+            }),*
+        ];
+    };
+}
+
+node_schemas! {
+    // Toplevel nodes.
+    None, "FBXHeaderExtension", [], 0, false, [
+        "FBXHeaderVersion", "FBXVersion", "EncryptionType", "CreationTimeStamp", "Creator",
+        "SceneInfo"
+    ];
+    None, "FileId", [Binary], 1, false, [];
+    None, "CreationTime", [String], 1, false, [];
+    None, "Creator", [String], 1, false, [];
+    None, "GlobalSettings", [], 0, false, ["Version", "Properties70"];
+    None, "Documents", [], 0, false, ["Count", "Document"];
+    None, "References", [], 0, false, [];
+    None, "Definitions", [], 0, false, ["Version", "Count", "ObjectType"];
+    None, "Objects", [], 0, false, [
+        "GlobalSettings", "NodeAttribute", "Geometry", "Model", "Pose", "Deformer", "Material",
+        "Texture", "LayeredTexture", "Video", "Audio", "AudioLayer", "Implementation",
+        "BindingTable", "CollectionExclusive", "DisplayLayer", "SelectionSet", "AnimationStack",
+        "AnimationLayer", "AnimationCurveNode", "AnimationCurve", "TimelineX"
+    ];
+    None, "Connections", [], 0, false, ["C"];
+    None, "Takes", [], 0, false, ["Current", "Take"];
+    // `FBXHeaderExtension`.
+    Some("FBXHeaderExtension"), "FBXHeaderVersion", [I32], 1, false, [];
+    Some("FBXHeaderExtension"), "FBXVersion", [I32], 1, false, [];
+    Some("FBXHeaderExtension"), "EncryptionType", [I32], 1, false, [];
+    Some("FBXHeaderExtension"), "CreationTimeStamp", [], 0, false, [
+        "Version", "Year", "Month", "Day", "Hour", "Minute", "Second", "Millisecond"
+    ];
+    Some("FBXHeaderExtension"), "Creator", [String], 1, false, [];
+    Some("FBXHeaderExtension"), "SceneInfo", [String, String], 2, false, [
+        "Type", "Version", "MetaData", "Properties70"
+    ];
+    Some("CreationTimeStamp"), "Version", [I32], 1, false, [];
+    Some("CreationTimeStamp"), "Year", [I32], 1, false, [];
+    Some("CreationTimeStamp"), "Month", [I32], 1, false, [];
+    Some("CreationTimeStamp"), "Day", [I32], 1, false, [];
+    Some("CreationTimeStamp"), "Hour", [I32], 1, false, [];
+    Some("CreationTimeStamp"), "Minute", [I32], 1, false, [];
+    Some("CreationTimeStamp"), "Second", [I32], 1, false, [];
+    Some("CreationTimeStamp"), "Millisecond", [I32], 1, false, [];
+    Some("SceneInfo"), "Type", [String], 1, false, [];
+    Some("SceneInfo"), "Version", [I32], 1, false, [];
+    Some("SceneInfo"), "MetaData", [], 0, false, [
+        "Version", "Title", "Subject", "Author", "Keywords", "Revision", "Comment"
+    ];
+    Some("SceneInfo"), "Properties70", [], 0, false, ["P"];
+    Some("MetaData"), "Version", [I32], 1, false, [];
+    Some("MetaData"), "Title", [String], 1, false, [];
+    Some("MetaData"), "Subject", [String], 1, false, [];
+    Some("MetaData"), "Author", [String], 1, false, [];
+    Some("MetaData"), "Keywords", [String], 1, false, [];
+    Some("MetaData"), "Revision", [String], 1, false, [];
+    Some("MetaData"), "Comment", [String], 1, false, [];
+    // `GlobalSettings`.
+    Some("GlobalSettings"), "Version", [I32], 1, false, [];
+    Some("GlobalSettings"), "Properties70", [], 0, false, ["P"];
+    // `Documents`.
+    Some("Documents"), "Count", [I32], 1, false, [];
+    Some("Documents"), "Document", [I64, String, String], 3, false, [
+        "Properties70", "RootNode"
+    ];
+    Some("Document"), "Properties70", [], 0, false, ["P"];
+    Some("Document"), "RootNode", [I64], 1, false, [];
+    // `Definitions`.
+    Some("Definitions"), "Version", [I32], 1, false, [];
+    Some("Definitions"), "Count", [I32], 1, false, [];
+    Some("Definitions"), "ObjectType", [String], 1, false, ["Count", "PropertyTemplate"];
+    Some("ObjectType"), "Count", [I32], 1, false, [];
+    Some("ObjectType"), "PropertyTemplate", [String], 1, false, ["Properties70"];
+    Some("PropertyTemplate"), "Properties70", [], 0, false, ["P"];
+    // `Objects`.
+    Some("Objects"), "NodeAttribute", [I64, String, String], 3, false, [
+        "Properties70", "TypeFlags"
+    ];
+    Some("Objects"), "Geometry", [I64, String, String], 3, false, [
+        "Properties70", "GeometryVersion", "Version", "Vertices", "PolygonVertexIndex", "Edges",
+        "Indexes", "Normals", "LayerElementNormal", "LayerElementBinormal",
+        "LayerElementTangent", "LayerElementUV", "LayerElementColor", "LayerElementMaterial",
+        "LayerElementSmoothing", "Layer"
+    ];
+    Some("Objects"), "Model", [I64, String, String], 3, false, [
+        "Version", "Properties70", "MultiLayer", "MultiTake", "Shading", "Culling"
+    ];
+    Some("Objects"), "Pose", [I64, String, String], 3, false, [
+        "Type", "Version", "NbPoseNodes", "PoseNode"
+    ];
+    Some("Objects"), "Deformer", [I64, String, String], 3, false, [
+        "Version", "Properties70", "Link_DeformAcuracy", "SkinningType", "BlendWeights",
+        "UserData", "Mode", "Indexes", "Weights", "Transform", "TransformLink",
+        "TransformAssociateModel", "DeformPercent", "FullWeights"
+    ];
+    Some("Objects"), "Material", [I64, String, String], 3, false, [
+        "Version", "ShadingModel", "MultiLayer", "Properties70"
+    ];
+    Some("Objects"), "Texture", [I64, String, String], 3, false, [
+        "Type", "Version", "TextureName", "Properties70", "Media", "FileName",
+        "RelativeFilename", "ModelUVTranslation", "ModelUVScaling", "Texture_Alpha_Source",
+        "Cropping"
+    ];
+    Some("Objects"), "AnimationStack", [I64, String, String], 3, false, ["Properties70"];
+    Some("Objects"), "AnimationLayer", [I64, String, String], 3, false, ["Properties70"];
+    Some("Objects"), "AnimationCurveNode", [I64, String, String], 3, false, ["Properties70"];
+    Some("Objects"), "AnimationCurve", [I64, String, String], 3, false, [
+        "Default", "KeyVer", "KeyTime", "KeyValueFloat", "KeyAttrFlags", "KeyAttrDataFloat",
+        "KeyAttrRefCount"
+    ];
+    // Object classes.
+    // Child nodes of objects are looked up with the class (the node name of the object) as the
+    // parent name.
+    Some("NodeAttribute"), "Properties70", [], 0, false, ["P"];
+    Some("NodeAttribute"), "TypeFlags", [String], 1, true, [];
+    Some("Geometry"), "Properties70", [], 0, false, ["P"];
+    Some("Geometry"), "GeometryVersion", [I32], 1, false, [];
+    Some("Geometry"), "Version", [I32], 1, false, [];
+    Some("Geometry"), "Vertices", [ArrF64], 1, false, [];
+    Some("Geometry"), "PolygonVertexIndex", [ArrI32], 1, false, [];
+    Some("Geometry"), "Edges", [ArrI32], 1, false, [];
+    Some("Geometry"), "Indexes", [ArrI32], 1, false, [];
+    Some("Geometry"), "Normals", [ArrF64], 1, false, [];
+    Some("Geometry"), "LayerElementNormal", [I32], 1, false, [
+        "Version", "Name", "MappingInformationType", "ReferenceInformationType", "Normals",
+        "NormalsIndex", "NormalsW"
+    ];
+    Some("Geometry"), "LayerElementBinormal", [I32], 1, false, [
+        "Version", "Name", "MappingInformationType", "ReferenceInformationType", "Binormals",
+        "BinormalsIndex", "BinormalsW"
+    ];
+    Some("Geometry"), "LayerElementTangent", [I32], 1, false, [
+        "Version", "Name", "MappingInformationType", "ReferenceInformationType", "Tangents",
+        "TangentsIndex", "TangentsW"
+    ];
+    Some("Geometry"), "LayerElementUV", [I32], 1, false, [
+        "Version", "Name", "MappingInformationType", "ReferenceInformationType", "UV", "UVIndex"
+    ];
+    Some("Geometry"), "LayerElementColor", [I32], 1, false, [
+        "Version", "Name", "MappingInformationType", "ReferenceInformationType", "Colors",
+        "ColorIndex"
+    ];
+    Some("Geometry"), "LayerElementMaterial", [I32], 1, false, [
+        "Version", "Name", "MappingInformationType", "ReferenceInformationType", "Materials"
+    ];
+    Some("Geometry"), "LayerElementSmoothing", [I32], 1, false, [
+        "Version", "Name", "MappingInformationType", "ReferenceInformationType", "Smoothing"
+    ];
+    Some("Geometry"), "Layer", [I32], 1, false, ["Version", "LayerElement"];
+    Some("LayerElementNormal"), "Normals", [ArrF64], 1, false, [];
+    Some("LayerElementNormal"), "NormalsIndex", [ArrI32], 1, false, [];
+    Some("LayerElementNormal"), "NormalsW", [ArrF64], 1, false, [];
+    Some("LayerElementBinormal"), "Binormals", [ArrF64], 1, false, [];
+    Some("LayerElementBinormal"), "BinormalsIndex", [ArrI32], 1, false, [];
+    Some("LayerElementBinormal"), "BinormalsW", [ArrF64], 1, false, [];
+    Some("LayerElementTangent"), "Tangents", [ArrF64], 1, false, [];
+    Some("LayerElementTangent"), "TangentsIndex", [ArrI32], 1, false, [];
+    Some("LayerElementTangent"), "TangentsW", [ArrF64], 1, false, [];
+    Some("LayerElementUV"), "UV", [ArrF64], 1, false, [];
+    Some("LayerElementUV"), "UVIndex", [ArrI32], 1, false, [];
+    Some("LayerElementColor"), "Colors", [ArrF64], 1, false, [];
+    Some("LayerElementColor"), "ColorIndex", [ArrI32], 1, false, [];
+    Some("LayerElementMaterial"), "Materials", [ArrI32], 1, false, [];
+    Some("LayerElementSmoothing"), "Smoothing", [ArrI32], 1, false, [];
+    Some("Layer"), "Version", [I32], 1, false, [];
+    Some("Layer"), "LayerElement", [], 0, false, ["Type", "TypedIndex"];
+    Some("LayerElement"), "Type", [String], 1, false, [];
+    Some("LayerElement"), "TypedIndex", [I32], 1, false, [];
+    Some("Model"), "Version", [I32], 1, false, [];
+    Some("Model"), "Properties70", [], 0, false, ["P"];
+    Some("Model"), "Culling", [String], 1, false, [];
+    Some("Pose"), "Type", [String], 1, false, [];
+    Some("Pose"), "Version", [I32], 1, false, [];
+    Some("Pose"), "NbPoseNodes", [I32], 1, false, [];
+    Some("Pose"), "PoseNode", [], 0, false, ["Node", "Matrix"];
+    Some("PoseNode"), "Node", [I64], 1, false, [];
+    Some("PoseNode"), "Matrix", [ArrF64], 1, false, [];
+    Some("Deformer"), "Version", [I32], 1, false, [];
+    Some("Deformer"), "Properties70", [], 0, false, ["P"];
+    Some("Deformer"), "Link_DeformAcuracy", [F64], 1, false, [];
+    Some("Deformer"), "SkinningType", [String], 1, false, [];
+    Some("Deformer"), "BlendWeights", [ArrF64], 1, false, [];
+    Some("Deformer"), "UserData", [String, String], 2, false, [];
+    Some("Deformer"), "Mode", [String], 1, false, [];
+    Some("Deformer"), "Indexes", [ArrI32], 1, false, [];
+    Some("Deformer"), "Weights", [ArrF64], 1, false, [];
+    Some("Deformer"), "Transform", [ArrF64], 1, false, [];
+    Some("Deformer"), "TransformLink", [ArrF64], 1, false, [];
+    Some("Deformer"), "TransformAssociateModel", [ArrF64], 1, false, [];
+    Some("Deformer"), "DeformPercent", [F64], 1, false, [];
+    Some("Deformer"), "FullWeights", [ArrF64], 1, false, [];
+    Some("Material"), "Version", [I32], 1, false, [];
+    Some("Material"), "ShadingModel", [String], 1, false, [];
+    Some("Material"), "MultiLayer", [I32], 1, false, [];
+    Some("Material"), "Properties70", [], 0, false, ["P"];
+    Some("Texture"), "Type", [String], 1, false, [];
+    Some("Texture"), "Version", [I32], 1, false, [];
+    Some("Texture"), "TextureName", [String], 1, false, [];
+    Some("Texture"), "Properties70", [], 0, false, ["P"];
+    Some("Texture"), "Media", [String], 1, false, [];
+    Some("Texture"), "FileName", [String], 1, false, [];
+    Some("Texture"), "RelativeFilename", [String], 1, false, [];
+    Some("Texture"), "ModelUVTranslation", [F64, F64], 2, false, [];
+    Some("Texture"), "ModelUVScaling", [F64, F64], 2, false, [];
+    Some("Texture"), "Texture_Alpha_Source", [String], 1, false, [];
+    Some("Texture"), "Cropping", [I32, I32, I32, I32], 4, false, [];
+    Some("AnimationStack"), "Properties70", [], 0, false, ["P"];
+    Some("AnimationLayer"), "Properties70", [], 0, false, ["P"];
+    Some("AnimationCurveNode"), "Properties70", [], 0, false, ["P"];
+    Some("AnimationCurve"), "Default", [F64], 1, false, [];
+    Some("AnimationCurve"), "KeyVer", [I32], 1, false, [];
+    Some("AnimationCurve"), "KeyTime", [ArrI64], 1, false, [];
+    Some("AnimationCurve"), "KeyValueFloat", [ArrF32], 1, false, [];
+    Some("AnimationCurve"), "KeyAttrFlags", [ArrI32], 1, false, [];
+    Some("AnimationCurve"), "KeyAttrDataFloat", [ArrF32], 1, false, [];
+    Some("AnimationCurve"), "KeyAttrRefCount", [ArrI32], 1, false, [];
+    // `Properties70`.
+    Some("Properties70"), "P", [String, String, String, String], 4, true, [];
+    // `Connections`.
+    Some("Connections"), "C", [String, I64, I64, String], 3, true, [];
+    // `Takes`.
+    Some("Takes"), "Current", [String], 1, false, [];
+    Some("Takes"), "Take", [String], 1, false, ["FileName", "LocalTime", "ReferenceTime"];
+    Some("Take"), "FileName", [String], 1, false, [];
+    Some("Take"), "LocalTime", [I64, I64], 2, false, [];
+    Some("Take"), "ReferenceTime", [I64, I64], 2, false, [];
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(name: &str, attributes: Vec<OwnedAttribute>, children: Vec<GenericNode>)
+        -> GenericNode
+    {
+        GenericNode {
+            name: name.as_bytes().to_vec(),
+            attributes,
+            children,
+        }
+    }
+
+    #[test]
+    fn schemas_are_unique() {
+        for (i, schema) in SCHEMAS.iter().enumerate() {
+            assert!(
+                SCHEMAS[..i].iter().all(|other| {
+                    other.parent != schema.parent || other.name != schema.name
+                }),
+                "Duplicate schema: parent={:?}, name={:?}",
+                schema.parent,
+                schema.name
+            );
+        }
+    }
+
+    #[test]
+    fn objects_children_are_known() {
+        let objects = lookup(None, "Objects").unwrap();
+        for class in &["Geometry", "Model", "Deformer", "AnimationCurve", "AnimationCurveNode"] {
+            assert!(objects.has_child(class));
+            let schema = lookup(Some("Objects"), class).unwrap();
+            assert_eq!(
+                schema.attributes,
+                &[AttributeType::I64, AttributeType::String, AttributeType::String]
+            );
+        }
+        assert!(lookup(Some("Geometry"), "Vertices").is_some());
+        assert!(lookup(Some("LayerElementUV"), "UVIndex").is_some());
+        assert!(lookup(Some("Deformer"), "TransformLink").is_some());
+        assert!(lookup(Some("AnimationCurve"), "KeyTime").is_some());
+    }
+
+    #[test]
+    fn validate_object_children() {
+        let uv = node(
+            "LayerElementUV",
+            vec![OwnedAttribute::I32(0)],
+            vec![
+                node("UV", vec![OwnedAttribute::ArrF32(vec![0.0; 2].into())], Vec::new()),
+                node("UVIndex", vec![OwnedAttribute::ArrI64(vec![0].into())], Vec::new()),
+            ],
+        );
+        // Loose validation accepts arrays of different width.
+        assert!(validate_tree(&uv, Some("Geometry"), true).is_ok());
+        assert!(validate_tree(&uv, Some("Geometry"), false).is_err());
+
+        let vertices = node(
+            "Vertices",
+            vec![OwnedAttribute::String(Ok("0,0,0".to_owned()))],
+            Vec::new(),
+        );
+        match validate_tree(&vertices, Some("Geometry"), true) {
+            Err(Error::InvalidAttribute(ref name)) => assert_eq!(name, "Vertices"),
+            v => panic!("Unexpected result: {:?}", v),
+        }
+        // Nodes with the same name under another parent are not affected.
+        assert!(validate_tree(&vertices, Some("Foo"), true).is_ok());
+
+        let key_time = node("KeyTime", vec![OwnedAttribute::ArrF64(vec![0.0].into())], Vec::new());
+        assert!(validate_tree(&key_time, Some("AnimationCurve"), true).is_err());
+    }
+}
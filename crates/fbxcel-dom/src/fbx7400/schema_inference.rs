@@ -0,0 +1,205 @@
+//! Inference of the schema observed in files.
+//!
+//! `InferredSchema` collects child names and attribute type signatures of nodes with occurrence
+//! counts, without loading attribute values.
+//! The report (`Display` of `InferredSchema`) helps to extend typed loaders for nodes written by
+//! specific exporters: nodes not described by the known schema (see `schema::lookup()`) are
+//! marked as unknown.
+//!
+//! ```
+//! # extern crate fbxcel_core;
+//! # extern crate fbxcel_dom;
+//! # use std::io::Cursor;
+//! # use fbxcel_core::pull_parser::ascii::AsciiDocument;
+//! use fbxcel_core::pull_parser::RootParser;
+//! use fbxcel_dom::fbx7400::schema_inference::InferredSchema;
+//!
+//! # let source = "; FBX 7.4.0 project file\nObjects:  {\n}\n";
+//! # let bytes = AsciiDocument::parse(source).unwrap().to_binary().unwrap();
+//! let mut schema = InferredSchema::new();
+//! schema.add_document(RootParser::new(Cursor::new(bytes))).unwrap();
+//! println!("{}", schema);
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+use fnv::FnvHashMap;
+use pull_parser::{Event, Parser, ParserSource};
+use pull_parser::Result as ParseResult;
+use crate::DisplayPath;
+use crate::fbx7400::schema::{self, AttributeType};
+
+
+/// Observed schema of nodes with the same parent name and node name.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct InferredNode {
+    /// Name of the parent node.
+    ///
+    /// `None` for toplevel nodes.
+    pub parent: Option<String>,
+    /// Node name.
+    pub name: String,
+    /// Number of the nodes.
+    pub count: u64,
+    /// Attribute type signatures and their occurrence counts.
+    pub signatures: FnvHashMap<Vec<AttributeType>, u64>,
+    /// Child node names and their occurrence counts.
+    pub children: BTreeMap<String, u64>,
+}
+
+impl InferredNode {
+    /// Returns `true` if the node is described by the known schema.
+    pub fn is_known(&self) -> bool {
+        schema::lookup(self.parent.as_deref(), &self.name).is_some()
+    }
+
+    /// Returns the attribute type signatures, most frequent first.
+    pub fn signatures_by_count(&self) -> Vec<(&[AttributeType], u64)> {
+        let mut signatures = self.signatures
+            .iter()
+            .map(|(sig, &count)| (sig.as_slice(), count))
+            .collect::<Vec<_>>();
+        signatures.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.len().cmp(&b.0.len())));
+        signatures
+    }
+}
+
+
+/// Observed schema of documents.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct InferredSchema {
+    /// Nodes keyed by the parent name and the node name.
+    nodes: FnvHashMap<(Option<String>, String), InferredNode>,
+}
+
+impl InferredSchema {
+    /// Creates a new empty `InferredSchema`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Reads the whole document from the parser and adds the observed nodes.
+    ///
+    /// Multiple documents can be added to one schema.
+    pub fn add_document<R, P>(&mut self, mut parser: P) -> ParseResult<()>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        // Names of the open nodes.
+        let mut open_nodes: Vec<String> = Vec::new();
+        loop {
+            match parser.next_event()? {
+                Event::StartFbx(_) => {},
+                Event::EndFbx(_) => return Ok(()),
+                Event::EndNode => {
+                    open_nodes.pop();
+                },
+                Event::StartNode(mut node) => {
+                    let name = node.name()?.to_owned();
+                    let mut signature = Vec::new();
+                    while let Some(attr) = node.attributes.next_attribute()? {
+                        signature.push(AttributeType::of_parser_attribute(&attr));
+                    }
+                    let parent = open_nodes.last().cloned();
+                    if let Some(ref parent_name) = parent {
+                        let grandparent = open_nodes.iter().rev().nth(1).cloned();
+                        let parent_node = self.entry(grandparent, parent_name.clone());
+                        *parent_node.children.entry(name.clone()).or_insert(0) += 1;
+                    }
+                    let entry = self.entry(parent, name.clone());
+                    entry.count += 1;
+                    *entry.signatures.entry(signature).or_insert(0) += 1;
+                    open_nodes.push(name);
+                },
+            }
+        }
+    }
+
+    /// Returns the observed schema of the node.
+    ///
+    /// `parent` should be `None` for toplevel nodes.
+    pub fn get(&self, parent: Option<&str>, name: &str) -> Option<&InferredNode> {
+        self.nodes.get(&(parent.map(ToOwned::to_owned), name.to_owned()))
+    }
+
+    /// Returns the observed nodes, sorted by the parent names and the node names.
+    pub fn nodes(&self) -> Vec<&InferredNode> {
+        let mut nodes = self.nodes.values().collect::<Vec<_>>();
+        nodes.sort_by(|a, b| (&a.parent, &a.name).cmp(&(&b.parent, &b.name)));
+        nodes
+    }
+
+    /// Returns the entry for the node.
+    fn entry(&mut self, parent: Option<String>, name: String) -> &mut InferredNode {
+        self.nodes.entry((parent.clone(), name.clone())).or_insert_with(|| {
+            InferredNode {
+                parent,
+                name,
+                ..Default::default()
+            }
+        })
+    }
+}
+
+impl fmt::Display for InferredSchema {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for node in self.nodes() {
+            writeln!(
+                f,
+                "{}/{}: {} nodes{}",
+                DisplayPath(node.parent.as_deref().unwrap_or("")),
+                DisplayPath(&node.name),
+                node.count,
+                if node.is_known() { "" } else { " (unknown)" }
+            )?;
+            for (signature, count) in node.signatures_by_count() {
+                writeln!(f, "    attributes {:?}: {}", signature, count)?;
+            }
+            for (child, count) in &node.children {
+                writeln!(f, "    child {}: {}", DisplayPath(child), count)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use pull_parser::RootParser;
+    use pull_parser::ascii::AsciiDocument;
+    use crate::fbx7400::schema::AttributeType;
+    use super::*;
+
+    #[test]
+    fn infer_schema() {
+        let source = r#"; FBX 7.4.0 project file
+Objects:  {
+	Model: 1, "Model::a", "Null" {
+		Version: 232
+		MyExporterData: "x"
+	}
+	Model: 2, "Model::b", "Null" {
+		Version: 232
+	}
+}
+"#;
+        let bytes = AsciiDocument::parse(source).unwrap().to_binary().unwrap();
+        let mut schema = InferredSchema::new();
+        schema.add_document(RootParser::new(Cursor::new(&bytes))).unwrap();
+
+        let model = schema.get(Some("Objects"), "Model").unwrap();
+        assert_eq!(model.count, 2);
+        assert_eq!(
+            model.signatures_by_count(),
+            vec![(&[AttributeType::I64, AttributeType::String, AttributeType::String][..], 2)]
+        );
+        assert_eq!(model.children["Version"], 2);
+        assert_eq!(model.children["MyExporterData"], 1);
+        let custom = schema.get(Some("Model"), "MyExporterData").unwrap();
+        assert!(!custom.is_known());
+        assert!(schema.to_string().contains("\nModel/MyExporterData: 1 nodes (unknown)\n"));
+    }
+}
@@ -0,0 +1,211 @@
+//! Post-processing of skin weights.
+//!
+//! Engines usually require skin weights of each vertex to sum to 1 and to have a limited number
+//! of bone influences (see `import_checks::MAX_BONES_PER_VERTEX`).
+
+use fnv::FnvHashMap;
+use crate::fbx7400::{Connections, TypedObject};
+
+
+/// Report of skin weight normalization.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct SkinWeightReport {
+    /// Number of vertices whose weights are changed.
+    pub modified_vertices: usize,
+    /// Number of removed bone influences.
+    pub removed_influences: usize,
+    /// Sum of the weights discarded by influence limiting, relative to the total weight of each
+    /// vertex.
+    pub discarded_weight: f64,
+    /// Max weight discarded from a vertex, relative to the total weight of the vertex.
+    pub max_discarded_weight: f64,
+}
+
+
+/// An influence of a bone (cluster) on a vertex.
+#[derive(Debug, Clone, Copy)]
+struct Influence {
+    /// Index of the cluster object.
+    cluster: usize,
+    /// Index in the `indexes` and `weights` of the cluster.
+    entry: usize,
+    /// Weight.
+    weight: f64,
+}
+
+
+/// Normalizes the weights of each vertex of skins, keeping at most `max_influences` influences
+/// with the largest weights.
+///
+/// `indexes` and `weights` of `Cluster` objects are rewritten: dropped influences are removed,
+/// and the remaining weights are scaled to sum to 1.
+/// Clusters are grouped by the skin they are connected to.
+/// Vertices whose weights are all zero are left unchanged.
+///
+/// # Panics
+/// Panics if `max_influences` is zero.
+pub fn normalize_skin_weights(
+    objects: &mut [TypedObject],
+    connections: &Connections,
+    max_influences: usize,
+) -> SkinWeightReport {
+    assert!(max_influences > 0, "At least one influence should be kept for each vertex");
+    let mut report = SkinWeightReport::default();
+
+    // Influences on each vertex, for each skin.
+    let mut skins = FnvHashMap::<i64, FnvHashMap<i32, Vec<Influence>>>::default();
+    for (cluster, obj) in objects.iter().enumerate() {
+        let obj = match *obj {
+            TypedObject::Cluster(ref v) => v,
+            _ => continue,
+        };
+        let skin = match connections.parent_object(obj.props.id) {
+            Some(v) => v,
+            None => continue,
+        };
+        let vertices = skins.entry(skin).or_default();
+        for (entry, (&index, &weight)) in obj.indexes.iter().zip(&obj.weights).enumerate() {
+            vertices.entry(index).or_default().push(Influence {
+                cluster,
+                entry,
+                weight,
+            });
+        }
+    }
+
+    // New weights of each cluster entry, or `None` if the entry should be removed.
+    let mut updates = FnvHashMap::<usize, FnvHashMap<usize, Option<f64>>>::default();
+    for vertices in skins.values_mut() {
+        for influences in vertices.values_mut() {
+            let total: f64 = influences.iter().map(|inf| inf.weight).sum();
+            if total <= 0.0 {
+                continue;
+            }
+            influences.sort_by(|a, b| b.weight.total_cmp(&a.weight));
+            let kept_len = influences.len().min(max_influences);
+            let kept_total: f64 = influences[..kept_len].iter().map(|inf| inf.weight).sum();
+            let discarded = (total - kept_total) / total;
+            let is_modified = kept_len < influences.len() || (total - 1.0).abs() > 1e-6;
+            if !is_modified {
+                continue;
+            }
+            report.modified_vertices += 1;
+            report.removed_influences += influences.len() - kept_len;
+            report.discarded_weight += discarded;
+            report.max_discarded_weight = report.max_discarded_weight.max(discarded);
+            for (i, inf) in influences.iter().enumerate() {
+                let weight = if i < kept_len && kept_total > 0.0 {
+                    Some(inf.weight / kept_total)
+                } else {
+                    None
+                };
+                updates.entry(inf.cluster).or_default().insert(inf.entry, weight);
+            }
+        }
+    }
+
+    for (cluster, entries) in updates {
+        let obj = match objects[cluster] {
+            TypedObject::Cluster(ref mut v) => v,
+            _ => unreachable!("Only clusters should be updated"),
+        };
+        let mut new_indexes = Vec::with_capacity(obj.indexes.len());
+        let mut new_weights = Vec::with_capacity(obj.weights.len());
+        for (entry, (&index, &weight)) in obj.indexes.iter().zip(&obj.weights).enumerate() {
+            match entries.get(&entry) {
+                Some(&Some(w)) => {
+                    new_indexes.push(index);
+                    new_weights.push(w);
+                },
+                Some(&None) => {},
+                None => {
+                    new_indexes.push(index);
+                    new_weights.push(weight);
+                },
+            }
+        }
+        obj.indexes = new_indexes;
+        obj.weights = new_weights;
+    }
+    report
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use pull_parser::{Event, Parser, RootParser};
+    use pull_parser::ascii::AsciiDocument;
+    use crate::fbx7400::{Connection, ObjectProperties};
+    use super::*;
+
+    #[test]
+    fn limit_influences() {
+        let cluster = |id: i64, indexes: &str, weights: &str| {
+            format!(
+                "\tDeformer: {}, \"SubDeformer::\", \"Cluster\" {{\n\
+                 \t\tIndexes: *{} {{\n\t\t\ta: {}\n\t\t}}\n\
+                 \t\tWeights: *{} {{\n\t\t\ta: {}\n\t\t}}\n\
+                 \t}}\n",
+                id,
+                indexes.split(',').count(),
+                indexes,
+                weights.split(',').count(),
+                weights
+            )
+        };
+        let source = format!(
+            "; FBX 7.4.0 project file\nObjects:  {{\n\
+             \tDeformer: 100, \"Deformer::\", \"Skin\" {{\n\t}}\n{}{}{}}}\n",
+            cluster(1, "0,1", "0.5,1"),
+            cluster(2, "0", "0.3"),
+            cluster(3, "0", "0.2"),
+        );
+        let bytes = AsciiDocument::parse(&source).unwrap().to_binary().unwrap();
+        let mut parser = RootParser::new(Cursor::new(bytes));
+        let mut objects = Vec::new();
+        loop {
+            match parser.next_event().unwrap() {
+                Event::StartNode(node) => {
+                    let name = node.name().unwrap().to_owned();
+                    if name == "Objects" {
+                        continue;
+                    }
+                    let props = ObjectProperties::load(&name, node.attributes).unwrap();
+                    objects.push(TypedObject::load(parser.subtree_parser(), props).unwrap());
+                },
+                Event::EndFbx(_) => break,
+                _ => {},
+            }
+        }
+        let connections = Connections(
+            (1..4)
+                .map(|id| Connection {
+                    source: id,
+                    destination: 100,
+                    property: None,
+                    source_is_prop: false,
+                    destination_is_prop: false,
+                    extra_attributes: Vec::new(),
+                })
+                .collect(),
+        );
+
+        let report = normalize_skin_weights(&mut objects, &connections, 2);
+        assert_eq!(report.modified_vertices, 1);
+        assert_eq!(report.removed_influences, 1);
+        assert!((report.max_discarded_weight - 0.2).abs() < 1e-12);
+        let clusters = objects
+            .iter()
+            .filter_map(|obj| match *obj {
+                TypedObject::Cluster(ref v) => Some(v),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(clusters.len(), 3);
+        assert_eq!(clusters[0].indexes, vec![0, 1]);
+        assert!((clusters[0].weights[0] - 0.625).abs() < 1e-12 && clusters[0].weights[1] == 1.0);
+        assert!((clusters[1].weights[0] - 0.375).abs() < 1e-12);
+        assert!(clusters[2].indexes.is_empty() && clusters[2].weights.is_empty());
+    }
+}
@@ -1,7 +1,9 @@
 //! `Takes` node and its children.
 
-use parser::binary::{Parser, ParserSource, Attributes};
-use loader::binary::simple::{Result, Error};
+use pull_parser::{Parser, ParserSource, Attributes};
+use crate::{Result, Error};
+use crate::fbx7400::KTime;
+use memory::MemoryUsage;
 
 
 /// `Takes` node.
@@ -37,7 +39,7 @@ impl Takes {
         }
         Ok(Takes {
             current: ensure_node_exists!(current, "Takes", "Current"),
-            takes: takes,
+            takes,
         })
     }
 }
@@ -57,9 +59,9 @@ pub struct Take {
     /// `FileName`.
     pub filename: String,
     /// `LocalTime`.
-    pub local_time: (i64, i64),
+    pub local_time: (KTime, KTime),
     /// `ReferenceTime`.
-    pub reference_time: (i64, i64),
+    pub reference_time: (KTime, KTime),
 }
 
 impl Take {
@@ -100,6 +102,20 @@ impl Take {
 
 child_attr_loader! { TakeChildAttrs {
     "FileName" => FileName(String),
-    "LocalTime" => LocalTime((i64, i64)),
-    "ReferenceTime" => ReferenceTime((i64, i64)),
+    "LocalTime" => LocalTime((KTime, KTime)),
+    "ReferenceTime" => ReferenceTime((KTime, KTime)),
 }}
+
+
+impl MemoryUsage for Takes {
+    fn heap_usage(&self) -> usize {
+        self.current.heap_usage() + self.takes.heap_usage()
+    }
+}
+
+
+impl MemoryUsage for Take {
+    fn heap_usage(&self) -> usize {
+        self.name.heap_usage() + self.filename.heap_usage()
+    }
+}
@@ -0,0 +1,271 @@
+//! Unit scale conversion of documents.
+//!
+//! Asset libraries often mix files exported in centimeters and meters.
+//! `set_unit_scale()` rewrites the loaded document so that it uses the given unit, scaling
+//! length values consistently.
+
+use fnv::FnvHashSet;
+use crate::{GenericNode, OwnedAttribute};
+use crate::fbx7400::{Fbx7400Document, GenericObject, GlobalSettings, PropertyValue};
+use crate::fbx7400::property_names;
+use crate::utils::scale_in_place;
+
+
+/// Unit scale factor used if `GlobalSettings` doesn't have it (i.e. centimeters).
+pub const DEFAULT_UNIT_SCALE_FACTOR: f64 = 1.0;
+
+/// `Model` properties which have length values.
+const MODEL_LENGTH_PROPERTIES: &[&str] = &[
+    property_names::LCL_TRANSLATION,
+    property_names::ROTATION_OFFSET,
+    property_names::ROTATION_PIVOT,
+    property_names::SCALING_OFFSET,
+    property_names::SCALING_PIVOT,
+    property_names::GEOMETRIC_TRANSLATION,
+];
+
+/// Child nodes of `Deformer` objects which have 4x4 matrices.
+const DEFORMER_MATRIX_NODES: &[&str] = &["Transform", "TransformLink", "TransformAssociateModel"];
+
+
+/// Returns the unit scale factor (length of the unit in centimeters) of the document.
+pub fn unit_scale_factor(doc: &Fbx7400Document) -> f64 {
+    doc.global_settings
+        .as_ref()
+        .and_then(|gs| gs.properties.values_f64.get(property_names::UNIT_SCALE_FACTOR))
+        .map_or(DEFAULT_UNIT_SCALE_FACTOR, |v| *v.value())
+}
+
+
+/// Converts the document to use the given unit scale factor (length of the unit in
+/// centimeters, for example `100.0` for meters).
+///
+/// This updates `UnitScaleFactor` of `GlobalSettings`, and scales the values below:
+///
+/// - `Vertices` of geometries (including blend shapes).
+/// - Translations, pivots, and offsets of models.
+/// - Animation curves connected to these model properties.
+/// - Translations of matrices of clusters and bind poses.
+///
+/// `OriginalUnitScaleFactor` is kept unchanged.
+/// Returns the factor the length values are multiplied by.
+///
+/// # Panics
+/// Panics if `target` is not positive.
+pub fn set_unit_scale(doc: &mut Fbx7400Document, target: f64) -> f64 {
+    assert!(target > 0.0, "Unit scale factor should be positive, but got {}", target);
+    let factor = unit_scale_factor(doc) / target;
+    // The new factor is stored even without `GlobalSettings`, so that the data is not scaled
+    // again by later calls.
+    doc.global_settings.get_or_insert_with(GlobalSettings::new).properties.values_f64.insert(
+        property_names::UNIT_SCALE_FACTOR.to_owned(),
+        PropertyValue::new(target),
+    );
+    if factor == 1.0 {
+        return factor;
+    }
+
+    let objects = match doc.objects {
+        Some(ref mut v) => v,
+        None => return factor,
+    };
+    let models: FnvHashSet<i64> = objects
+        .iter()
+        .filter(|obj| obj.props.class == "Model")
+        .map(|obj| obj.props.id)
+        .collect();
+    let mut curve_nodes = FnvHashSet::default();
+    let mut curves = FnvHashSet::default();
+    if let Some(ref connections) = doc.connections {
+        for conn in &connections.0 {
+            let is_length_prop = conn.property
+                .as_ref()
+                .is_some_and(|prop| MODEL_LENGTH_PROPERTIES.contains(&prop.as_str()));
+            if is_length_prop && models.contains(&conn.destination) {
+                curve_nodes.insert(conn.source);
+            }
+        }
+        for conn in &connections.0 {
+            if conn.curve_channel().is_some() && curve_nodes.contains(&conn.destination) {
+                curves.insert(conn.source);
+            }
+        }
+    }
+
+    for obj in objects.iter_mut() {
+        let id = obj.props.id;
+        match obj.props.class.as_str() {
+            "Geometry" => scale_child_arrays(&mut obj.nodes, "Vertices", factor),
+            "Model" => scale_model(obj, factor),
+            "AnimationCurveNode" if curve_nodes.contains(&id) => {
+                for (name, value) in &mut obj.properties.values_f64 {
+                    if name.starts_with(property_names::CURVE_CHANNEL_PREFIX) {
+                        *value = PropertyValue::new(value.value() * factor);
+                    }
+                }
+            },
+            "AnimationCurve" if curves.contains(&id) => {
+                scale_child_arrays(&mut obj.nodes, "KeyValueFloat", factor);
+                scale_child_arrays(&mut obj.nodes, "Default", factor);
+            },
+            "Deformer" => {
+                for name in DEFORMER_MATRIX_NODES {
+                    scale_child_matrices(&mut obj.nodes, name, factor);
+                }
+            },
+            "Pose" => {
+                for pose_node in obj.nodes.iter_mut().filter(|node| node.name == b"PoseNode") {
+                    scale_child_matrices(&mut pose_node.children, "Matrix", factor);
+                }
+            },
+            _ => {},
+        }
+    }
+    factor
+}
+
+
+/// Scales length properties of the model.
+fn scale_model(obj: &mut GenericObject, factor: f64) {
+    for name in MODEL_LENGTH_PROPERTIES {
+        if let Some(value) = obj.properties.values_f64_3.get_mut(*name) {
+            let mut v = *value.value();
+            scale_in_place(&mut v, factor);
+            *value = PropertyValue::new(v);
+        }
+    }
+}
+
+
+/// Scales all values of the first attribute of the child nodes with the given name.
+fn scale_child_arrays(nodes: &mut [GenericNode], name: &str, factor: f64) {
+    for node in nodes.iter_mut().filter(|node| node.name == name.as_bytes()) {
+        match node.attributes.first_mut() {
+            Some(OwnedAttribute::ArrF64(v)) => scale_in_place(v, factor),
+            Some(OwnedAttribute::ArrF32(v)) => scale_in_place(v, factor as f32),
+            Some(OwnedAttribute::F64(v)) => *v *= factor,
+            Some(OwnedAttribute::F32(v)) => *v *= factor as f32,
+            _ => {},
+        }
+    }
+}
+
+
+/// Scales translations of 4x4 matrices (stored as 16 `f64`s in column-major order) of the child
+/// nodes with the given name.
+fn scale_child_matrices(nodes: &mut [GenericNode], name: &str, factor: f64) {
+    for node in nodes.iter_mut().filter(|node| node.name == name.as_bytes()) {
+        if let Some(OwnedAttribute::ArrF64(v)) = node.attributes.first_mut() {
+            if v.len() == 16 {
+                scale_in_place(&mut v[12..15], factor);
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::fbx7400::{ObjectProperties, Properties70};
+    use super::*;
+
+    #[test]
+    fn centimeters_to_meters() {
+        let mut settings = Properties70::new();
+        settings
+            .values_f64
+            .insert(property_names::UNIT_SCALE_FACTOR.to_owned(), PropertyValue::new(1.0));
+        let mut model = GenericObject::new(ObjectProperties {
+            id: 1,
+            name: "Cube".to_owned(),
+            class: "Model".to_owned(),
+            subclass: "Mesh".to_owned(),
+        });
+        model.properties.values_f64_3.insert(
+            property_names::LCL_TRANSLATION.to_owned(),
+            PropertyValue::new([100.0, 0.0, -50.0]),
+        );
+        let mut geometry = GenericObject::new(ObjectProperties {
+            id: 2,
+            name: "Cube".to_owned(),
+            class: "Geometry".to_owned(),
+            subclass: "Mesh".to_owned(),
+        });
+        geometry.nodes.push(GenericNode {
+            name: b"Vertices".to_vec(),
+            attributes: vec![OwnedAttribute::ArrF64(vec![200.0, 0.0, 10.0].into_boxed_slice())],
+            children: Vec::new(),
+        });
+        let mut doc = Fbx7400Document {
+            version: 7400,
+            fbx_header_extension: None,
+            file_id: None,
+            creation_time: None,
+            creator: None,
+            global_settings: Some(GlobalSettings {
+                version: 1000,
+                properties: settings,
+            }),
+            documents: None,
+            references: None,
+            definitions: None,
+            objects: Some(vec![model, geometry]),
+            connections: None,
+            takes: None,
+            footer: None,
+        };
+
+        assert_eq!(set_unit_scale(&mut doc, 100.0), 0.01);
+        assert_eq!(unit_scale_factor(&doc), 100.0);
+        let objects = doc.objects.as_ref().unwrap();
+        assert_eq!(
+            objects[0].properties.values_f64_3[property_names::LCL_TRANSLATION].value(),
+            &[1.0, 0.0, -0.5]
+        );
+        assert_eq!(
+            objects[1].nodes[0].attributes[0],
+            OwnedAttribute::ArrF64(vec![2.0, 0.0, 0.1].into_boxed_slice())
+        );
+    }
+
+    #[test]
+    fn idempotent_without_global_settings() {
+        let mut geometry = GenericObject::new(ObjectProperties {
+            id: 1,
+            name: "Cube".to_owned(),
+            class: "Geometry".to_owned(),
+            subclass: "Mesh".to_owned(),
+        });
+        geometry.nodes.push(GenericNode {
+            name: b"Vertices".to_vec(),
+            attributes: vec![OwnedAttribute::ArrF32(vec![100.0, 0.0, 10.0].into_boxed_slice())],
+            children: Vec::new(),
+        });
+        let mut doc = Fbx7400Document {
+            version: 7400,
+            fbx_header_extension: None,
+            file_id: None,
+            creation_time: None,
+            creator: None,
+            global_settings: None,
+            documents: None,
+            references: None,
+            definitions: None,
+            objects: Some(vec![geometry]),
+            connections: None,
+            takes: None,
+            footer: None,
+        };
+
+        assert_eq!(set_unit_scale(&mut doc, 0.5), 2.0);
+        let gs = doc.global_settings.as_ref().expect("GlobalSettings should be created");
+        assert_eq!(gs.version, GlobalSettings::VERSION);
+        assert_eq!(unit_scale_factor(&doc), 0.5);
+        let scaled = OwnedAttribute::ArrF32(vec![200.0, 0.0, 20.0].into_boxed_slice());
+        assert_eq!(doc.objects.as_ref().unwrap()[0].nodes[0].attributes[0], scaled);
+
+        // The second call doesn't scale the data again.
+        assert_eq!(set_unit_scale(&mut doc, 0.5), 1.0);
+        assert_eq!(doc.objects.as_ref().unwrap()[0].nodes[0].attributes[0], scaled);
+    }
+}
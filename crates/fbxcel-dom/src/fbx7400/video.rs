@@ -0,0 +1,158 @@
+//! `Video` object.
+//!
+//! Note that FBX 7.5 or later may emit `Audio` objects in a similar way as `Video` objects.
+//! `Video::from_generic()` returns `None` for them; use `Audio::from_generic()` instead.
+
+use crate::OwnedAttribute;
+use crate::fbx7400::{GenericObject, ObjectProperties};
+
+
+/// Kind of media, detected from the content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MediaKind {
+    /// PNG image.
+    Png,
+    /// JPEG image.
+    Jpeg,
+    /// TGA (Truevision TGA) image.
+    Tga,
+    /// DDS (DirectDraw Surface) image.
+    Dds,
+}
+
+impl MediaKind {
+    /// Detects the media kind from the content.
+    ///
+    /// Returns `None` if the content is not known media type.
+    pub fn detect(content: &[u8]) -> Option<Self> {
+        /// Footer of TGA 2.0 files.
+        const TGA_FOOTER: &[u8] = b"TRUEVISION-XFILE.\0";
+
+        if content.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Some(MediaKind::Png)
+        } else if content.starts_with(b"\xff\xd8\xff") {
+            Some(MediaKind::Jpeg)
+        } else if content.starts_with(b"DDS ") {
+            Some(MediaKind::Dds)
+        } else if content.ends_with(TGA_FOOTER) || is_tga_header(content) {
+            Some(MediaKind::Tga)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the common file extension of the media kind.
+    pub fn extension(&self) -> &'static str {
+        match *self {
+            MediaKind::Png => "png",
+            MediaKind::Jpeg => "jpg",
+            MediaKind::Tga => "tga",
+            MediaKind::Dds => "dds",
+        }
+    }
+}
+
+
+/// Checks whether the content seems to start with a TGA header.
+///
+/// TGA before 2.0 has no magic bytes, so this checks some header fields.
+fn is_tga_header(content: &[u8]) -> bool {
+    /// Length of TGA header.
+    const TGA_HEADER_LEN: usize = 18;
+
+    if content.len() < TGA_HEADER_LEN {
+        return false;
+    }
+    let color_map_type = content[1];
+    let image_type = content[2];
+    let pixel_depth = content[16];
+    color_map_type <= 1 && matches!(image_type, 1 | 2 | 3 | 9 | 10 | 11) &&
+        matches!(pixel_depth, 8 | 15 | 16 | 24 | 32)
+}
+
+
+/// `Video` object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Video {
+    /// Properties common to object nodes.
+    pub props: ObjectProperties,
+    /// Video type (usually `Clip`).
+    pub type_: String,
+    /// Whether to use mipmaps.
+    pub use_mip_map: bool,
+    /// File name.
+    pub filename: String,
+    /// Relative file name.
+    pub relative_filename: String,
+    /// Embedded content.
+    ///
+    /// `None` if the content is not embedded (or is empty).
+    pub content: Option<Vec<u8>>,
+}
+
+impl Video {
+    /// Creates a `Video` from the given generic object.
+    ///
+    /// Returns `None` if the object is not a `Video` object.
+    pub fn from_generic(obj: &GenericObject) -> Option<Self> {
+        if obj.props.class != "Video" {
+            return None;
+        }
+        let child_attr = |name: &str| {
+            obj.nodes
+                .iter()
+                .find(|node| node.name == name.as_bytes())
+                .and_then(|node| node.attributes.first())
+        };
+        let child_string = |name: &str| match child_attr(name) {
+            Some(OwnedAttribute::String(Ok(v))) => v.clone(),
+            _ => String::new(),
+        };
+        let use_mip_map = match child_attr("UseMipMap") {
+            Some(&OwnedAttribute::Bool(v)) => v,
+            Some(&OwnedAttribute::I16(v)) => v != 0,
+            Some(&OwnedAttribute::I32(v)) => v != 0,
+            Some(&OwnedAttribute::I64(v)) => v != 0,
+            _ => false,
+        };
+        let content = match child_attr("Content") {
+            Some(OwnedAttribute::Binary(v)) if !v.is_empty() => Some(v.to_vec()),
+            _ => None,
+        };
+        Some(Video {
+            props: obj.props.clone(),
+            type_: child_string("Type"),
+            use_mip_map,
+            filename: child_string("Filename"),
+            relative_filename: child_string("RelativeFilename"),
+            content,
+        })
+    }
+
+    /// Returns the media kind detected from the embedded content.
+    ///
+    /// Returns `None` if the content is not embedded or the media kind is unknown.
+    pub fn media_kind(&self) -> Option<MediaKind> {
+        self.content.as_ref().and_then(|content| MediaKind::detect(content))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::MediaKind;
+
+    #[test]
+    fn test_media_kind_detection() {
+        assert_eq!(
+            MediaKind::detect(b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR"),
+            Some(MediaKind::Png)
+        );
+        assert_eq!(MediaKind::detect(b"\xff\xd8\xff\xe0\0\x10JFIF"), Some(MediaKind::Jpeg));
+        assert_eq!(MediaKind::detect(b"DDS \x7c\0\0\0"), Some(MediaKind::Dds));
+        let mut tga = vec![0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 1, 0, 32, 8];
+        tga.extend_from_slice(&[0; 4]);
+        assert_eq!(MediaKind::detect(&tga), Some(MediaKind::Tga));
+        assert_eq!(MediaKind::detect(b"plain text"), None);
+    }
+}
@@ -0,0 +1,294 @@
+//! Generic node and node attribute.
+
+use std::borrow::Cow;
+use std::str;
+
+use pull_parser::{Parser, ParserSource, Event, FbxFooter};
+use pull_parser::Result as ParseResult;
+use pull_parser::limits;
+use pull_parser::ascii::AsciiNode;
+use crate::{Result, Error};
+use approx::ApproxEq;
+use memory::MemoryUsage;
+pub use pull_parser::OwnedAttribute;
+
+
+/// Max length of a node name in bytes.
+///
+/// FBX binary stores the length of a node name as `u8`.
+pub const MAX_NODE_NAME_LEN: usize = limits::MAX_NODE_NAME_LEN;
+
+
+/// Generic FBX node.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct GenericNode {
+    /// Node name.
+    ///
+    /// Node names are usually ASCII, but they are kept as bytes so that names which are not valid
+    /// UTF-8 are written back as is.
+    pub name: Vec<u8>,
+    /// Node attributes.
+    pub attributes: Vec<OwnedAttribute>,
+    /// Child nodes.
+    pub children: Vec<GenericNode>,
+}
+
+impl GenericNode {
+    /// Creates a new `GenericNode`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Returns the node name as a string.
+    ///
+    /// Returns `None` if the name is not valid UTF-8.
+    pub fn name_str(&self) -> Option<&str> {
+        str::from_utf8(&self.name).ok()
+    }
+
+    /// Returns the node name as a string, replacing invalid UTF-8 sequences.
+    pub fn name_lossy(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(&self.name)
+    }
+
+    /// Loads all sibling nodes from the given parser.
+    ///
+    /// This reads N `StartNode` and N+1 { `EndNode` or `EndFbx` }.
+    pub fn load_from_parser<R, P>(
+        parser: &mut P,
+    ) -> ParseResult<(Vec<GenericNode>, Option<FbxFooter>)>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        let mut nodes = Vec::new();
+        let mut footer = None;
+        loop {
+            let (name, attrs) = match parser.next_event()? {
+                Event::StartFbx(_) => continue,
+                Event::EndFbx(f) => {
+                    footer = f.ok();
+                    break;
+                },
+                Event::EndNode => break,
+                Event::StartNode(node) => {
+                    let name = node.name_bytes().to_owned();
+                    let attrs = OwnedAttribute::load_attrs_from_parser_event(node.attributes)?;
+                    (name, attrs)
+                },
+            };
+            let children = GenericNode::load_from_parser(&mut parser.subtree_parser())?
+                .0;
+            let node = GenericNode {
+                name,
+                attributes: attrs,
+                children,
+            };
+            nodes.push(node);
+        }
+        nodes.shrink_to_fit();
+        Ok((nodes, footer))
+    }
+
+    /// Converts the FBX ASCII nodes and their descendants into generic nodes.
+    ///
+    /// `parent` is the name of the parent node (empty for toplevel nodes), and the attribute
+    /// types are guessed as `AsciiNode::typed_attributes()`.
+    pub fn from_ascii_nodes(nodes: &[AsciiNode], parent: &str) -> Vec<Self> {
+        nodes
+            .iter()
+            .map(|node| GenericNode {
+                name: node.name.clone().into_bytes(),
+                attributes: node.typed_attributes(parent),
+                children: Self::from_ascii_nodes(&node.children, &node.name),
+            })
+            .collect()
+    }
+
+    /// Returns an iterator of the child nodes with the given name.
+    pub fn children_by_name<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a GenericNode> {
+        self.children.iter().filter(move |child| child.name == name.as_bytes())
+    }
+
+    /// Appends the given node as the last child.
+    ///
+    /// Returns `Err(_)` if the node (or its descendant) cannot be written as FBX binary
+    /// (see `validate()`).
+    pub fn add_child(&mut self, child: GenericNode) -> Result<()> {
+        child.validate()?;
+        self.children.push(child);
+        Ok(())
+    }
+
+    /// Inserts the given node as a child at the given position.
+    ///
+    /// Returns `Err(_)` if the node (or its descendant) cannot be written as FBX binary
+    /// (see `validate()`).
+    ///
+    /// # Panics
+    /// Panics if `index > self.children.len()`.
+    pub fn insert_child(&mut self, index: usize, child: GenericNode) -> Result<()> {
+        child.validate()?;
+        self.children.insert(index, child);
+        Ok(())
+    }
+
+    /// Removes the child node at the given position and returns it.
+    ///
+    /// Returns `None` if the index is out of range.
+    pub fn remove_node(&mut self, index: usize) -> Option<GenericNode> {
+        if index < self.children.len() {
+            Some(self.children.remove(index))
+        } else {
+            None
+        }
+    }
+
+    /// Removes all child nodes with the given name and returns them.
+    pub fn remove_children_by_name(&mut self, name: &str) -> Vec<GenericNode> {
+        let name = name.as_bytes();
+        let (removed, kept) = self.children.drain(..).partition(|child| child.name == name);
+        self.children = kept;
+        removed
+    }
+
+    /// Replaces the node attributes and returns the old ones.
+    ///
+    /// Returns `Err(Error::AttributeTooLarge { .. })` if some attribute value is too large to be
+    /// written as FBX binary, and the attributes are not changed then.
+    /// Attribute types are not checked, since the parent of the node is unknown here.
+    /// Use `Tree::set_attributes()` to check them against the schema of known nodes.
+    pub fn set_attributes(
+        &mut self,
+        attributes: Vec<OwnedAttribute>,
+    ) -> Result<Vec<OwnedAttribute>> {
+        validate_attribute_sizes(&attributes, || path_segment(&self.name, &attributes))?;
+        Ok(::std::mem::replace(&mut self.attributes, attributes))
+    }
+
+    /// Checks whether the node and its descendants can be written as FBX binary.
+    ///
+    /// Returns `Err(Error::NodeNameTooLong { .. })` if some node name is longer than
+    /// `MAX_NODE_NAME_LEN` bytes, and `Err(Error::AttributeTooLarge { .. })` if some attribute
+    /// value is too large for the 32-bit length fields.
+    /// The errors have the path to the offending node, and `P` nodes in the path are shown with
+    /// their property names (such as `Properties70/P[Lcl Translation]`).
+    pub fn validate(&self) -> Result<()> {
+        self.validate_with_ancestors(&mut Vec::new())
+    }
+
+    /// Checks whether the node and its descendants can be written as FBX binary.
+    fn validate_with_ancestors<'a>(&'a self, ancestors: &mut Vec<&'a GenericNode>) -> Result<()> {
+        ancestors.push(self);
+        if self.name.len() > MAX_NODE_NAME_LEN {
+            return Err(Error::NodeNameTooLong {
+                path: node_path(ancestors),
+                len: self.name.len(),
+            });
+        }
+        validate_attribute_sizes(&self.attributes, || node_path(ancestors))?;
+        for child in &self.children {
+            child.validate_with_ancestors(ancestors)?;
+        }
+        ancestors.pop();
+        Ok(())
+    }
+}
+
+
+/// Creates a path string of the last node in the given list.
+///
+/// Node names are joined with `/`, and the property name is appended to `P` nodes.
+fn node_path(nodes: &[&GenericNode]) -> String {
+    let segments = nodes.iter().map(|node| path_segment(&node.name, &node.attributes));
+    segments.collect::<Vec<_>>().join("/")
+}
+
+
+/// Checks whether the attribute values are small enough to be written as FBX binary.
+///
+/// `path` is called to get the path to the node on error.
+pub(crate) fn validate_attribute_sizes<F>(attributes: &[OwnedAttribute], path: F) -> Result<()>
+where
+    F: FnOnce() -> String,
+{
+    for (index, attr) in attributes.iter().enumerate() {
+        let len = attr.payload_len();
+        if len > u64::from(u32::MAX) {
+            return Err(Error::AttributeTooLarge {
+                path: path(),
+                index,
+                len,
+            });
+        }
+    }
+    Ok(())
+}
+
+
+/// Returns the path segment of the node.
+///
+/// `P` nodes are shown with their property names (such as `P[Lcl Translation]`).
+/// Names which are not valid UTF-8 are shown lossily.
+pub fn path_segment(name: &[u8], attributes: &[OwnedAttribute]) -> String {
+    match attributes.first() {
+        Some(&OwnedAttribute::String(Ok(ref prop))) if name == b"P" => format!("P[{}]", prop),
+        _ => String::from_utf8_lossy(name).into_owned(),
+    }
+}
+
+
+impl MemoryUsage for GenericNode {
+    fn heap_usage(&self) -> usize {
+        self.name.heap_usage() +
+            self.attributes.heap_usage() +
+            self.children.heap_usage()
+    }
+}
+
+
+impl ApproxEq for GenericNode {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.name == other.name &&
+            self.attributes.approx_eq(&other.attributes, epsilon) &&
+            self.children.approx_eq(&other.children, epsilon)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_reports_path() {
+        let mut prop = GenericNode::new();
+        prop.name = b"P".to_vec();
+        prop.attributes = vec![OwnedAttribute::String(Ok("Foo".to_owned()))];
+        prop.children.push(GenericNode {
+            name: vec![b'x'; MAX_NODE_NAME_LEN + 1],
+            ..GenericNode::new()
+        });
+        let mut props = GenericNode::new();
+        props.name = b"Properties70".to_vec();
+        props.children.push(prop);
+
+        match props.validate() {
+            Err(Error::NodeNameTooLong { path, len }) => {
+                assert_eq!(len, MAX_NODE_NAME_LEN + 1);
+                assert!(path.starts_with("Properties70/P[Foo]/xxx"));
+            },
+            v => panic!("Unexpected result: {:?}", v),
+        }
+    }
+
+    #[test]
+    fn set_attributes() {
+        let mut node = GenericNode::new();
+        node.name = b"Version".to_vec();
+        node.attributes = vec![OwnedAttribute::I32(100)];
+        let old = node.set_attributes(vec![OwnedAttribute::I32(101)]).unwrap();
+        assert_eq!(old, vec![OwnedAttribute::I32(100)]);
+        assert_eq!(node.attributes, vec![OwnedAttribute::I32(101)]);
+    }
+}
@@ -0,0 +1,155 @@
+//! Loaders of FBX documents into owned structures.
+//!
+//! `GenericNode` is a generic node tree, `fbx7400` has typed structures of FBX 7.4 and 7.5
+//! documents, and `fbx6000` has structures of legacy FBX 6.x documents.
+#![warn(missing_docs)]
+
+extern crate fbxcel_core;
+extern crate fnv;
+#[macro_use]
+extern crate log;
+#[cfg(feature = "rayon")]
+extern crate rayon;
+
+use fbxcel_core::{approx, memory, pull_parser};
+
+use pull_parser::{Parser, ParserSource, Event};
+pub use self::display_path::DisplayPath;
+pub use self::error::{Result, Error};
+pub use self::generic::{GenericNode, OwnedAttribute, MAX_NODE_NAME_LEN};
+pub use self::profile::{LoadProfile, NodeTiming};
+
+pub mod display_path;
+pub mod error;
+pub mod generic;
+pub mod fbx6000;
+pub mod fbx7400;
+pub mod profile;
+pub mod tree;
+pub mod utils;
+
+
+/// FBX loader.
+#[derive(Debug)]
+struct FbxLoaderInner<R, P> {
+    /// FBX version.
+    version: u32,
+    /// Parser.
+    parser: P,
+    /// Dummy.
+    _r: ::std::marker::PhantomData<R>,
+}
+
+
+/// FBX 7.4 compatible loader.
+///
+/// This also loads FBX 7.5 (written by FBX SDK 2016 or later), whose only difference in structure
+/// is 64-bit node headers handled by the parser.
+#[derive(Debug)]
+pub struct FbxLoader7400<R, P> {
+    /// Inner loader.
+    inner: FbxLoaderInner<R, P>,
+}
+
+impl<R: ParserSource, P: Parser<R>> FbxLoader7400<R, P> {
+    /// Creates a new `FbxLoader7400` from the given inner loader data.
+    fn new(inner: FbxLoaderInner<R, P>) -> Self {
+        FbxLoader7400 { inner }
+    }
+
+    /// Returns the FBX version.
+    pub fn fbx_version(&self) -> u32 {
+        self.inner.version
+    }
+
+    /// Load FBX 7.4 compatible data.
+    pub fn load<O>(self, objs_loader: O) -> Result<fbx7400::Fbx7400<O>>
+    where
+        O: fbx7400::LoadObjects7400<Reader = R>,
+    {
+        fbx7400::Fbx7400::load_from_parser(self.inner.version, self.inner.parser, objs_loader)
+    }
+
+    /// Load FBX 7.4 compatible data, recording time spent to load each node.
+    pub fn load_profiled<O>(self, objs_loader: O) -> Result<(fbx7400::Fbx7400<O>, LoadProfile)>
+    where
+        O: fbx7400::LoadObjects7400<Reader = R>,
+    {
+        fbx7400::Fbx7400::load_from_parser_profiled(
+            self.inner.version,
+            self.inner.parser,
+            objs_loader,
+        )
+    }
+}
+
+
+/// FBX 6.x compatible loader.
+#[derive(Debug)]
+pub struct FbxLoader6000<R, P> {
+    /// Inner loader.
+    inner: FbxLoaderInner<R, P>,
+}
+
+impl<R: ParserSource, P: Parser<R>> FbxLoader6000<R, P> {
+    /// Creates a new `FbxLoader6000` from the given inner loader data.
+    fn new(inner: FbxLoaderInner<R, P>) -> Self {
+        FbxLoader6000 { inner }
+    }
+
+    /// Returns the FBX version.
+    pub fn fbx_version(&self) -> u32 {
+        self.inner.version
+    }
+
+    /// Load FBX 6.x compatible data.
+    pub fn load(self) -> Result<fbx6000::Fbx6000Document> {
+        fbx6000::Fbx6000Document::load_from_parser(self.inner.version, self.inner.parser)
+    }
+}
+
+
+/// FBX loader.
+#[derive(Debug)]
+pub enum FbxLoader<R, P> {
+    /// FBX 6.x compatible (6000 to 6199).
+    Fbx6000(FbxLoader6000<R, P>),
+    /// FBX 7.4 compatible (7400 to 7599, i.e. FBX 7.4 and 7.5).
+    Fbx7400(FbxLoader7400<R, P>),
+}
+
+impl<R: ParserSource, P: Parser<R>> FbxLoader<R, P> {
+    /// Loads FBX structure from the given parser.
+    ///
+    /// Returns `Err(Error::UnsupportedVersion(_))` if no loader supports the FBX version.
+    ///
+    /// Returns `Err(Error::UnexpectedEvent(_))` if the parser has already emitted some event (i.e.
+    /// if the given parser didn't return the `StartFbx` first).
+    pub fn load_from_parser(mut parser: P) -> Result<Self> {
+        let version = match parser.next_event()? {
+            Event::StartFbx(header) => header.version,
+            ev => return Err(Error::UnexpectedEvent(format!("{:?}", ev))),
+        };
+        let inner = FbxLoaderInner {
+            version,
+            parser,
+            _r: Default::default(),
+        };
+        match version {
+            6000..=6199 => Ok(FbxLoader::Fbx6000(FbxLoader6000::new(inner))),
+            7400..=7599 => Ok(FbxLoader::Fbx7400(FbxLoader7400::new(inner))),
+            _ => {
+                error!("Unsupported FBX version: {}", version);
+                Err(Error::UnsupportedVersion(version))
+            },
+        }
+    }
+
+    /// Returns the FBX version.
+    pub fn fbx_version(&self) -> u32 {
+        match *self {
+            FbxLoader::Fbx6000(ref loader) => loader.fbx_version(),
+            FbxLoader::Fbx7400(ref loader) => loader.fbx_version(),
+        }
+    }
+}
@@ -0,0 +1,48 @@
+//! Load time profiler.
+
+use std::cmp::Reverse;
+use std::time::Duration;
+
+
+/// Time spent to load a node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeTiming {
+    /// Node name.
+    pub name: String,
+    /// Object ID, if the node is an object node.
+    pub object_id: Option<i64>,
+    /// Time spent to load the node and its descendants.
+    pub duration: Duration,
+}
+
+
+/// Report of time spent to load FBX data.
+///
+/// Time spent to load each toplevel node and each object node is recorded.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct LoadProfile {
+    /// Toplevel nodes, in the order of appearance.
+    pub toplevel: Vec<NodeTiming>,
+    /// Object nodes (children of `Objects`), in the order of appearance.
+    pub objects: Vec<NodeTiming>,
+}
+
+impl LoadProfile {
+    /// Creates a new `LoadProfile`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Returns the total time spent to load the toplevel nodes.
+    pub fn total(&self) -> Duration {
+        self.toplevel.iter().map(|timing| timing.duration).sum()
+    }
+
+    /// Returns the `n` object nodes which took the longest time, slowest first.
+    pub fn slowest_objects(&self, n: usize) -> Vec<&NodeTiming> {
+        let mut objects = self.objects.iter().collect::<Vec<_>>();
+        objects.sort_by_key(|timing| Reverse(timing.duration));
+        objects.truncate(n);
+        objects
+    }
+}
@@ -0,0 +1,1180 @@
+//! DOM-style node tree.
+//!
+//! `Tree` stores all nodes of a document in an arena, and `Node` is a lightweight handle to
+//! traverse the tree (children and ancestors) and to read names and attributes.
+//! Trees loaded by `Tree::load_with_spans()` also record the byte range of each node in the
+//! source (see `Node::span()`).
+//! `Tree::parse_with_deadline()` loads a document partially within a time budget, and the loading
+//! can be resumed by `PartialTree::resume()`.
+//! `Tree::load_with_max_depth()` loads only the shallow nodes for outline views, and the deeper
+//! nodes can be loaded later by `Tree::expand()` using the recorded spans.
+//! `Tree::add_child()`, `Tree::remove_node()`, and `Tree::set_attributes()` edit the tree, and
+//! check that the result can be written back as a valid FBX binary.
+//!
+//! Node names and the data of array attributes are bump-allocated in buffers shared by the whole
+//! tree (arrays in one buffer for each element type, see `AttributeRef`), and `Tree::reload()`
+//! loads another document into the existing buffers, so that a tool processing many files
+//! doesn't reallocate the arena for each of them (see also `Tree::with_capacity()`).
+//!
+//! ```
+//! use fbxcel_dom::GenericNode;
+//! use fbxcel_dom::tree::Tree;
+//!
+//! let mut objects = GenericNode::new();
+//! objects.name = b"Objects".to_vec();
+//! let tree = Tree::from_generic_nodes(vec![objects]);
+//! let objects = tree.root().first_child_by_name("Objects").unwrap();
+//! assert_eq!(objects.parent().map(|n| n.is_root()), Some(true));
+//! ```
+
+use std::borrow::Cow;
+use std::fmt;
+use std::io::{Read, Seek};
+use std::mem;
+use std::ops::Range;
+use std::str;
+use std::time::{Duration, Instant};
+use pull_parser::{Error as ParseError, Event, FbxFooter, NodeSpan, Parser, ParserSource};
+use pull_parser::{ArrayAttribute, Attribute, Attributes, RootParser, SeekableSource};
+use pull_parser::Result as ParseResult;
+use crate::{GenericNode, OwnedAttribute, Result};
+use crate::fbx7400::schema;
+use crate::generic::validate_attribute_sizes;
+
+
+/// ID of a node in a `Tree`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId(usize);
+
+impl NodeId {
+    /// Returns the index of the node in the arena.
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+
+/// Node data in the arena.
+#[derive(Debug, Clone, PartialEq)]
+struct NodeData {
+    /// Byte range of the name in the name buffer of the tree.
+    name: Range<usize>,
+    /// Attributes.
+    attributes: Vec<AttributeData>,
+    /// Parent node.
+    parent: Option<NodeId>,
+    /// Child nodes.
+    children: Vec<NodeId>,
+    /// Byte range in the source.
+    span: Option<NodeSpan>,
+    /// Whether the child nodes are not loaded.
+    is_stub: bool,
+}
+
+
+/// Attribute data in the arena.
+#[derive(Debug, Clone, PartialEq)]
+enum AttributeData {
+    /// Attribute other than arrays.
+    Value(OwnedAttribute),
+    /// Range of the array of `bool` in the arena.
+    ArrBool(Range<usize>),
+    /// Range of the array of `i32` in the arena.
+    ArrI32(Range<usize>),
+    /// Range of the array of `i64` in the arena.
+    ArrI64(Range<usize>),
+    /// Range of the array of `f32` in the arena.
+    ArrF32(Range<usize>),
+    /// Range of the array of `f64` in the arena.
+    ArrF64(Range<usize>),
+}
+
+
+/// Attribute of a node in a `Tree`.
+///
+/// Arrays borrow the buffers of the tree.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AttributeRef<'a> {
+    /// Attribute other than arrays.
+    Value(&'a OwnedAttribute),
+    /// Array of `bool`.
+    ArrBool(&'a [bool]),
+    /// Array of `i32`.
+    ArrI32(&'a [i32]),
+    /// Array of `i64`.
+    ArrI64(&'a [i64]),
+    /// Array of `f32`.
+    ArrF32(&'a [f32]),
+    /// Array of `f64`.
+    ArrF64(&'a [f64]),
+}
+
+impl<'a> AttributeRef<'a> {
+    /// Copies the attribute into an `OwnedAttribute`.
+    pub fn to_owned_attribute(&self) -> OwnedAttribute {
+        match *self {
+            AttributeRef::Value(v) => v.clone(),
+            AttributeRef::ArrBool(v) => OwnedAttribute::ArrBool(v.into()),
+            AttributeRef::ArrI32(v) => OwnedAttribute::ArrI32(v.into()),
+            AttributeRef::ArrI64(v) => OwnedAttribute::ArrI64(v.into()),
+            AttributeRef::ArrF32(v) => OwnedAttribute::ArrF32(v.into()),
+            AttributeRef::ArrF64(v) => OwnedAttribute::ArrF64(v.into()),
+        }
+    }
+}
+
+impl<'a> PartialEq<OwnedAttribute> for AttributeRef<'a> {
+    fn eq(&self, other: &OwnedAttribute) -> bool {
+        match (*self, other) {
+            (AttributeRef::Value(v), other) => v == other,
+            (AttributeRef::ArrBool(v), OwnedAttribute::ArrBool(o)) => v == &o[..],
+            (AttributeRef::ArrI32(v), OwnedAttribute::ArrI32(o)) => v == &o[..],
+            (AttributeRef::ArrI64(v), OwnedAttribute::ArrI64(o)) => v == &o[..],
+            (AttributeRef::ArrF32(v), OwnedAttribute::ArrF32(o)) => v == &o[..],
+            (AttributeRef::ArrF64(v), OwnedAttribute::ArrF64(o)) => v == &o[..],
+            _ => false,
+        }
+    }
+}
+
+
+/// Capacity of the array buffers of a `Tree`, in elements.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ArrayCapacity {
+    /// Elements of `bool` arrays.
+    pub bools: usize,
+    /// Elements of `i32` arrays.
+    pub i32s: usize,
+    /// Elements of `i64` arrays.
+    pub i64s: usize,
+    /// Elements of `f32` arrays.
+    pub f32s: usize,
+    /// Elements of `f64` arrays.
+    pub f64s: usize,
+}
+
+
+/// Data of array attributes of all nodes in a `Tree`.
+#[derive(Debug, Default, Clone, PartialEq)]
+struct ArrayArena {
+    /// Elements of `bool` arrays.
+    bools: Vec<bool>,
+    /// Elements of `i32` arrays.
+    i32s: Vec<i32>,
+    /// Elements of `i64` arrays.
+    i64s: Vec<i64>,
+    /// Elements of `f32` arrays.
+    f32s: Vec<f32>,
+    /// Elements of `f64` arrays.
+    f64s: Vec<f64>,
+}
+
+impl ArrayArena {
+    /// Creates a new empty `ArrayArena` with the given capacity.
+    fn with_capacity(capacity: ArrayCapacity) -> Self {
+        ArrayArena {
+            bools: Vec::with_capacity(capacity.bools),
+            i32s: Vec::with_capacity(capacity.i32s),
+            i64s: Vec::with_capacity(capacity.i64s),
+            f32s: Vec::with_capacity(capacity.f32s),
+            f64s: Vec::with_capacity(capacity.f64s),
+        }
+    }
+
+    /// Returns the capacity.
+    fn capacity(&self) -> ArrayCapacity {
+        ArrayCapacity {
+            bools: self.bools.capacity(),
+            i32s: self.i32s.capacity(),
+            i64s: self.i64s.capacity(),
+            f32s: self.f32s.capacity(),
+            f64s: self.f64s.capacity(),
+        }
+    }
+
+    /// Removes all elements, keeping the allocated buffers.
+    fn clear(&mut self) {
+        self.bools.clear();
+        self.i32s.clear();
+        self.i64s.clear();
+        self.f32s.clear();
+        self.f64s.clear();
+    }
+
+    /// Shrinks the buffers to fit the elements.
+    fn shrink_to_fit(&mut self) {
+        self.bools.shrink_to_fit();
+        self.i32s.shrink_to_fit();
+        self.i64s.shrink_to_fit();
+        self.f32s.shrink_to_fit();
+        self.f64s.shrink_to_fit();
+    }
+
+    /// Copies the array of the attribute (if any) into the arena.
+    fn store(&mut self, attr: OwnedAttribute) -> AttributeData {
+        /// Appends the elements and returns their range.
+        fn push<T: Copy>(buf: &mut Vec<T>, arr: &[T]) -> Range<usize> {
+            let start = buf.len();
+            buf.extend_from_slice(arr);
+            start..buf.len()
+        }
+
+        match attr {
+            OwnedAttribute::ArrBool(v) => AttributeData::ArrBool(push(&mut self.bools, &v)),
+            OwnedAttribute::ArrI32(v) => AttributeData::ArrI32(push(&mut self.i32s, &v)),
+            OwnedAttribute::ArrI64(v) => AttributeData::ArrI64(push(&mut self.i64s, &v)),
+            OwnedAttribute::ArrF32(v) => AttributeData::ArrF32(push(&mut self.f32s, &v)),
+            OwnedAttribute::ArrF64(v) => AttributeData::ArrF64(push(&mut self.f64s, &v)),
+            attr => AttributeData::Value(attr),
+        }
+    }
+
+    /// Loads the attributes from the parser, reading arrays directly into the arena.
+    fn load_attrs<R>(&mut self, mut attrs: Attributes<R>) -> ParseResult<Vec<AttributeData>>
+    where
+        R: ParserSource,
+    {
+        /// Reads the array by `read` and returns the range of the elements.
+        fn append<T, F>(buf: &mut Vec<T>, read: F) -> ParseResult<Range<usize>>
+        where
+            F: FnOnce(&mut Vec<T>) -> ParseResult<()>,
+        {
+            let start = buf.len();
+            read(buf)?;
+            Ok(start..buf.len())
+        }
+
+        // The attribute count comes from the input and is not trusted for preallocation.
+        let mut result = Vec::with_capacity(attrs.num_attributes().min(64) as usize);
+        while let Some(attr) = attrs.next_attribute()? {
+            let data = match attr {
+                Attribute::Array(ArrayAttribute::Bool(arr)) => {
+                    AttributeData::ArrBool(append(&mut self.bools, |buf| arr.append_to_vec(buf))?)
+                },
+                Attribute::Array(ArrayAttribute::I32(arr)) => {
+                    AttributeData::ArrI32(append(&mut self.i32s, |buf| arr.append_to_vec(buf))?)
+                },
+                Attribute::Array(ArrayAttribute::I64(arr)) => {
+                    AttributeData::ArrI64(append(&mut self.i64s, |buf| arr.append_to_vec(buf))?)
+                },
+                Attribute::Array(ArrayAttribute::F32(arr)) => {
+                    AttributeData::ArrF32(append(&mut self.f32s, |buf| arr.append_to_vec(buf))?)
+                },
+                Attribute::Array(ArrayAttribute::F64(arr)) => {
+                    AttributeData::ArrF64(append(&mut self.f64s, |buf| arr.append_to_vec(buf))?)
+                },
+                attr => AttributeData::Value(OwnedAttribute::load_from_parser_event(attr)?),
+            };
+            result.push(data);
+        }
+        Ok(result)
+    }
+
+    /// Returns the attribute borrowing the arena.
+    fn get<'a>(&'a self, data: &'a AttributeData) -> AttributeRef<'a> {
+        match *data {
+            AttributeData::Value(ref v) => AttributeRef::Value(v),
+            AttributeData::ArrBool(ref range) => AttributeRef::ArrBool(&self.bools[range.clone()]),
+            AttributeData::ArrI32(ref range) => AttributeRef::ArrI32(&self.i32s[range.clone()]),
+            AttributeData::ArrI64(ref range) => AttributeRef::ArrI64(&self.i64s[range.clone()]),
+            AttributeData::ArrF32(ref range) => AttributeRef::ArrF32(&self.f32s[range.clone()]),
+            AttributeData::ArrF64(ref range) => AttributeRef::ArrF64(&self.f64s[range.clone()]),
+        }
+    }
+}
+
+
+/// Capacity of the arena of a `Tree`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TreeCapacity {
+    /// Number of nodes, including the implicit root node.
+    pub nodes: usize,
+    /// Total length of node names in bytes.
+    pub name_bytes: usize,
+    /// Total numbers of array elements.
+    pub arrays: ArrayCapacity,
+}
+
+
+/// Arena-backed FBX node tree.
+///
+/// The tree has an implicit root node (with empty name and no attributes), whose children are the
+/// toplevel nodes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tree {
+    /// Nodes.
+    nodes: Vec<NodeData>,
+    /// Node names.
+    names: Vec<u8>,
+    /// Data of array attributes.
+    arrays: ArrayArena,
+    /// FBX version.
+    fbx_version: Option<u32>,
+    /// FBX footer.
+    footer: Option<FbxFooter>,
+}
+
+impl Tree {
+    /// Creates a new `Tree` with only the implicit root node.
+    pub fn new() -> Self {
+        Self::with_capacity(TreeCapacity::default())
+    }
+
+    /// Creates a new `Tree` with only the implicit root node, which can hold at least the given
+    /// numbers of nodes, name bytes, and array elements without reallocating the arena.
+    pub fn with_capacity(capacity: TreeCapacity) -> Self {
+        let mut tree = Tree {
+            nodes: Vec::with_capacity(capacity.nodes.max(1)),
+            names: Vec::with_capacity(capacity.name_bytes),
+            arrays: ArrayArena::with_capacity(capacity.arrays),
+            fbx_version: None,
+            footer: None,
+        };
+        tree.push_root();
+        tree
+    }
+
+    /// Returns the capacity of the arena.
+    pub fn capacity(&self) -> TreeCapacity {
+        TreeCapacity {
+            nodes: self.nodes.capacity(),
+            name_bytes: self.names.capacity(),
+            arrays: self.arrays.capacity(),
+        }
+    }
+
+    /// Removes all nodes except the implicit root node, keeping the allocated arena.
+    ///
+    /// IDs of the removed nodes are invalidated.
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.names.clear();
+        self.arrays.clear();
+        self.fbx_version = None;
+        self.footer = None;
+        self.push_root();
+    }
+
+    /// Replaces the tree with the whole document loaded from the given parser, reusing the
+    /// allocated arena.
+    ///
+    /// IDs of the old nodes are invalidated.
+    /// On error, the tree has the nodes loaded before the error.
+    pub fn reload<R, P>(&mut self, parser: P) -> ParseResult<()>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        self.clear();
+        let mut partial = PartialTree::new(parser, false);
+        partial.tree = mem::take(self);
+        partial.keep_capacity = true;
+        let result = partial.load_until(None);
+        *self = partial.tree;
+        result.map(|_| ())
+    }
+
+    /// Loads the whole document from the given parser.
+    pub fn load<R, P>(parser: P) -> ParseResult<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        Self::load_impl(parser, false)
+    }
+
+    /// Loads the whole document from the given parser, recording the byte range of each node.
+    pub fn load_with_spans<R, P>(parser: P) -> ParseResult<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        Self::load_impl(parser, true)
+    }
+
+    /// Loads the document from the given parser as much as possible within the time budget.
+    ///
+    /// The budget is checked each time a node ends, so nodes are never split in the middle of
+    /// their attributes, and a node which has started loading is finished even if the budget is
+    /// exceeded while loading its attributes.
+    /// Use `PartialTree::resume()` to load the rest.
+    pub fn parse_with_deadline<R, P>(parser: P, budget: Duration) -> ParseResult<PartialTree<P>>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        let mut partial = PartialTree::new(parser, false);
+        partial.resume(budget)?;
+        Ok(partial)
+    }
+
+    /// Loads the nodes up to the given depth from the given parser, recording the byte range of
+    /// each node.
+    ///
+    /// Toplevel nodes have depth `1`, and at least they are loaded.
+    /// Nodes at the maximum depth are loaded without their children, and such nodes with
+    /// children are marked as stubs (see `Node::is_stub()`).
+    /// Stubs can be loaded later by `expand()`.
+    pub fn load_with_max_depth<R, P>(parser: P, max_depth: usize) -> ParseResult<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        let mut partial = PartialTree::new(parser, true);
+        partial.max_depth = Some(max_depth);
+        partial.load_until(None)?;
+        Ok(partial.tree)
+    }
+
+    /// Loads the children of the stub node from the source of the tree.
+    ///
+    /// `max_depth` limits the depth of the loaded nodes relative to the given node, as
+    /// `load_with_max_depth()`.
+    /// Does nothing if the node is not a stub.
+    ///
+    /// # Panics
+    /// Panics if the ID is not of this tree.
+    pub fn expand<R>(&mut self, id: NodeId, source: R, max_depth: Option<usize>) -> ParseResult<()>
+    where
+        R: Read + Seek,
+    {
+        if !self.node(id).is_stub() {
+            return Ok(());
+        }
+        let (span, fbx_version) = match (self.nodes[id.0].span, self.fbx_version) {
+            (Some(span), Some(fbx_version)) => (span, fbx_version),
+            _ => return Err(ParseError::InternalState("Stub node has no span")),
+        };
+        let mut source = SeekableSource::new(source);
+        source.skip_to(span.offset)?;
+        let mut parser = RootParser::from_source_with_version(source, fbx_version);
+        match parser.next_event()? {
+            Event::StartNode(_) => {},
+            _ => return Err(ParseError::InternalState("Stub node span does not point to a node")),
+        }
+
+        let mut partial = PartialTree::new(parser, true);
+        partial.tree = mem::take(self);
+        partial.tree.nodes[id.0].is_stub = false;
+        partial.current = id;
+        partial.base = id;
+        partial.max_depth = max_depth;
+        let result = partial.load_until(None);
+        *self = partial.tree;
+        result.map(|_| ())
+    }
+
+    /// Loads the whole document from the given parser.
+    fn load_impl<R, P>(parser: P, record_spans: bool) -> ParseResult<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        let mut partial = PartialTree::new(parser, record_spans);
+        partial.load_until(None)?;
+        Ok(partial.tree)
+    }
+
+    /// Creates a new `Tree` from the toplevel generic nodes.
+    pub fn from_generic_nodes(nodes: Vec<GenericNode>) -> Self {
+        let mut tree = Tree::new();
+        let root = tree.root_id();
+        for node in nodes {
+            tree.push_generic(root, node);
+        }
+        tree
+    }
+
+    /// Returns the FBX version if the tree is loaded from a parser.
+    pub fn fbx_version(&self) -> Option<u32> {
+        self.fbx_version
+    }
+
+    /// Returns the FBX footer if the tree is loaded from a parser.
+    pub fn footer(&self) -> Option<&FbxFooter> {
+        self.footer.as_ref()
+    }
+
+    /// Returns the number of nodes, including the implicit root node and removed nodes.
+    pub fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns the ID of the implicit root node.
+    pub fn root_id(&self) -> NodeId {
+        NodeId(0)
+    }
+
+    /// Returns the implicit root node.
+    pub fn root(&self) -> Node<'_> {
+        Node {
+            tree: self,
+            id: self.root_id(),
+        }
+    }
+
+    /// Returns the node with the given ID.
+    ///
+    /// # Panics
+    /// Panics if the ID is not of this tree.
+    pub fn node(&self, id: NodeId) -> Node<'_> {
+        assert!(id.0 < self.nodes.len(), "Node ID out of range: {:?}", id);
+        Node { tree: self, id }
+    }
+
+    /// Appends a new node as the last child of the given parent, and returns its ID.
+    ///
+    /// The node is not checked. Use `add_child()` to check it before appending.
+    ///
+    /// # Panics
+    /// Panics if the parent ID is not of this tree.
+    pub fn append(
+        &mut self,
+        parent: NodeId,
+        name: Vec<u8>,
+        attributes: Vec<OwnedAttribute>,
+    ) -> NodeId {
+        assert!(parent.0 < self.nodes.len(), "Node ID out of range: {:?}", parent);
+        self.push(parent, &name, attributes)
+    }
+
+    /// Appends the generic node and its descendants as the last child of the given parent, and
+    /// returns the ID of the appended node.
+    ///
+    /// Returns `Err(_)` if the nodes cannot be written as FBX binary (see
+    /// `GenericNode::validate()`), or if attributes of known nodes have unexpected types (see
+    /// `fbx7400::schema::validate_tree()`, with loose validation).
+    /// The tree is not changed on error.
+    ///
+    /// # Panics
+    /// Panics if the parent ID is not of this tree.
+    pub fn add_child(&mut self, parent: NodeId, node: GenericNode) -> Result<NodeId> {
+        node.validate()?;
+        {
+            let parent = self.node(parent);
+            let parent_name = parent.schema_name();
+            schema::validate_tree(&node, parent_name.as_deref(), true)?;
+        }
+        Ok(self.push_generic(parent, node))
+    }
+
+    /// Removes the node and its descendants from the tree, and returns them as a generic node.
+    ///
+    /// The removed nodes are detached from the parent and no longer reachable from the root, but
+    /// their IDs are not reused.
+    /// Returns `None` if the node has no parent (i.e. it is the implicit root node or already
+    /// removed).
+    ///
+    /// # Panics
+    /// Panics if the ID is not of this tree.
+    pub fn remove_node(&mut self, id: NodeId) -> Option<GenericNode> {
+        let parent = self.node(id).data().parent?;
+        let removed = self.node(id).to_generic_node();
+        self.nodes[parent.0].children.retain(|&child| child != id);
+        self.nodes[id.0].parent = None;
+        Some(removed)
+    }
+
+    /// Replaces the attributes of the node and returns the old ones.
+    ///
+    /// The arrays of the old attributes keep occupying the arena until the tree is cleared.
+    /// Returns `Err(Error::AttributeTooLarge { .. })` if some attribute value is too large to be
+    /// written as FBX binary, and `Err(Error::InvalidAttribute(_))` if the node is known by the
+    /// schema and the attribute types don't match (with loose validation).
+    /// The attributes are not changed on error.
+    ///
+    /// # Panics
+    /// Panics if the ID is not of this tree, or the node is the implicit root node.
+    pub fn set_attributes(
+        &mut self,
+        id: NodeId,
+        attributes: Vec<OwnedAttribute>,
+    ) -> Result<Vec<OwnedAttribute>> {
+        {
+            let node = self.node(id);
+            assert!(!node.is_root(), "The implicit root node cannot have attributes");
+            validate_attribute_sizes(&attributes, || node.path())?;
+            let parent_name = node.parent().and_then(|parent| parent.schema_name());
+            if let Some(schema) = schema::lookup(parent_name.as_deref(), &node.name_lossy()) {
+                schema.validate_attributes(&attributes, true)?;
+            }
+        }
+        let attributes = attributes.into_iter().map(|attr| self.arrays.store(attr)).collect();
+        let old = mem::replace(&mut self.nodes[id.0].attributes, attributes);
+        Ok(old.iter().map(|attr| self.arrays.get(attr).to_owned_attribute()).collect())
+    }
+
+    /// Converts the descendants of the given node into generic nodes.
+    pub fn to_generic_nodes(&self, id: NodeId) -> Vec<GenericNode> {
+        self.node(id).children().map(|child| child.to_generic_node()).collect()
+    }
+
+    /// Pushes the implicit root node.
+    fn push_root(&mut self) {
+        self.nodes.push(NodeData {
+            name: 0..0,
+            attributes: Vec::new(),
+            parent: None,
+            children: Vec::new(),
+            span: None,
+            is_stub: false,
+        });
+    }
+
+    /// Pushes a new node.
+    fn push(&mut self, parent: NodeId, name: &[u8], attributes: Vec<OwnedAttribute>) -> NodeId {
+        let attributes = attributes.into_iter().map(|attr| self.arrays.store(attr)).collect();
+        self.push_data(parent, name, attributes)
+    }
+
+    /// Pushes a new node with the attributes already in the arena.
+    fn push_data(&mut self, parent: NodeId, name: &[u8], attributes: Vec<AttributeData>) -> NodeId {
+        let id = NodeId(self.nodes.len());
+        let start = self.names.len();
+        self.names.extend_from_slice(name);
+        self.nodes.push(NodeData {
+            name: start..self.names.len(),
+            attributes,
+            parent: Some(parent),
+            children: Vec::new(),
+            span: None,
+            is_stub: false,
+        });
+        self.nodes[parent.0].children.push(id);
+        id
+    }
+
+    /// Pushes the generic node and its descendants, and returns the ID of the node.
+    fn push_generic(&mut self, parent: NodeId, node: GenericNode) -> NodeId {
+        let id = self.push(parent, &node.name, node.attributes);
+        for child in node.children {
+            self.push_generic(id, child);
+        }
+        id
+    }
+}
+
+impl Default for Tree {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+/// Partially loaded tree, which can resume loading.
+#[derive(Debug)]
+pub struct PartialTree<P> {
+    /// Tree loaded so far.
+    tree: Tree,
+    /// Parser.
+    parser: P,
+    /// The most recent open node.
+    current: NodeId,
+    /// Node whose end finishes the loading.
+    base: NodeId,
+    /// Depth of the current node relative to the base node.
+    depth: usize,
+    /// Maximum depth of the nodes to be loaded, relative to the base node.
+    max_depth: Option<usize>,
+    /// Whether to record the byte ranges of nodes.
+    record_spans: bool,
+    /// Whether the whole document is loaded.
+    is_complete: bool,
+    /// Whether to keep the unused capacity of the arena after loading.
+    keep_capacity: bool,
+}
+
+impl<P> PartialTree<P> {
+    /// Creates a new `PartialTree` with an empty tree.
+    fn new(parser: P, record_spans: bool) -> Self {
+        let tree = Tree::new();
+        let root = tree.root_id();
+        PartialTree {
+            tree,
+            parser,
+            current: root,
+            base: root,
+            depth: 0,
+            max_depth: None,
+            record_spans,
+            is_complete: false,
+            keep_capacity: false,
+        }
+    }
+
+    /// Returns `true` if the whole document is loaded.
+    pub fn is_complete(&self) -> bool {
+        self.is_complete
+    }
+
+    /// Returns the tree loaded so far.
+    ///
+    /// If the tree is not complete, the most recent open node and its ancestors may lack some
+    /// children.
+    pub fn tree(&self) -> &Tree {
+        &self.tree
+    }
+
+    /// Returns the tree loaded so far.
+    pub fn into_tree(self) -> Tree {
+        self.tree
+    }
+
+    /// Continues loading within the time budget.
+    ///
+    /// Returns `Ok(true)` if the whole document is loaded.
+    pub fn resume<R>(&mut self, budget: Duration) -> ParseResult<bool>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        self.load_until(Some(Instant::now() + budget))
+    }
+
+    /// Loads nodes until the deadline (if any) passes.
+    fn load_until<R>(&mut self, deadline: Option<Instant>) -> ParseResult<bool>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        while !self.is_complete {
+            match self.parser.next_event()? {
+                Event::StartFbx(header) => {
+                    self.tree.fbx_version = Some(header.version);
+                    continue;
+                },
+                Event::EndFbx(footer) => {
+                    self.tree.footer = footer.ok();
+                    self.is_complete = true;
+                    continue;
+                },
+                Event::EndNode => self.close_current(),
+                Event::StartNode(node) => {
+                    let name = node.name_bytes();
+                    let attrs = self.tree.arrays.load_attrs(node.attributes)?;
+                    self.current = self.tree.push_data(self.current, name, attrs);
+                    self.depth += 1;
+                    let root_parser = self.parser.root_parser();
+                    let span = root_parser.current_node_span();
+                    if self.record_spans {
+                        self.tree.nodes[self.current.0].span = span;
+                    }
+                    if self.max_depth.is_none_or(|max_depth| self.depth < max_depth) {
+                        continue;
+                    }
+                    // Attributes are already read, so the rest is children.
+                    let position = root_parser.source().position();
+                    self.tree.nodes[self.current.0].is_stub =
+                        span.is_some_and(|span| span.end() > position);
+                    self.parser.skip_current_node()?;
+                    self.close_current();
+                },
+            }
+            // A node is ended here.
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                break;
+            }
+        }
+        if self.is_complete && !self.keep_capacity {
+            self.tree.nodes.shrink_to_fit();
+            self.tree.names.shrink_to_fit();
+            self.tree.arrays.shrink_to_fit();
+        }
+        Ok(self.is_complete)
+    }
+
+    /// Closes the current node.
+    fn close_current(&mut self) {
+        if self.current == self.base {
+            self.is_complete = true;
+            return;
+        }
+        match self.tree.nodes[self.current.0].parent {
+            Some(parent) => {
+                self.current = parent;
+                self.depth -= 1;
+            },
+            None => self.is_complete = true,
+        }
+    }
+}
+
+
+/// Handle of a node in a `Tree`.
+#[derive(Clone, Copy)]
+pub struct Node<'a> {
+    /// Tree.
+    tree: &'a Tree,
+    /// Node ID.
+    id: NodeId,
+}
+
+impl<'a> Node<'a> {
+    /// Returns the node data.
+    fn data(&self) -> &'a NodeData {
+        &self.tree.nodes[self.id.0]
+    }
+
+    /// Returns the node ID.
+    pub fn id(&self) -> NodeId {
+        self.id
+    }
+
+    /// Returns the tree.
+    pub fn tree(&self) -> &'a Tree {
+        self.tree
+    }
+
+    /// Returns `true` if the node is the implicit root node.
+    pub fn is_root(&self) -> bool {
+        self.id == self.tree.root_id()
+    }
+
+    /// Returns the node name.
+    pub fn name(&self) -> &'a [u8] {
+        &self.tree.names[self.data().name.clone()]
+    }
+
+    /// Returns the node name as a string.
+    ///
+    /// Returns `None` if the name is not valid UTF-8.
+    pub fn name_str(&self) -> Option<&'a str> {
+        str::from_utf8(self.name()).ok()
+    }
+
+    /// Returns the node name as a string, replacing invalid UTF-8 sequences.
+    pub fn name_lossy(&self) -> Cow<'a, str> {
+        String::from_utf8_lossy(self.name())
+    }
+
+    /// Returns the number of the node attributes.
+    pub fn num_attributes(&self) -> usize {
+        self.data().attributes.len()
+    }
+
+    /// Returns an iterator of the node attributes.
+    pub fn attributes(&self) -> impl ExactSizeIterator<Item = AttributeRef<'a>> {
+        let tree = self.tree;
+        self.data().attributes.iter().map(move |attr| tree.arrays.get(attr))
+    }
+
+    /// Returns the attribute at the given index.
+    pub fn attribute(&self, index: usize) -> Option<AttributeRef<'a>> {
+        self.data().attributes.get(index).map(|attr| self.tree.arrays.get(attr))
+    }
+
+    /// Returns the byte range of the node in the source.
+    ///
+    /// Returns `None` if the tree is not loaded by `Tree::load_with_spans()`, or the node is the
+    /// implicit root node or appended later.
+    pub fn span(&self) -> Option<NodeSpan> {
+        self.data().span
+    }
+
+    /// Returns `true` if the node may have children which are not loaded.
+    ///
+    /// See `Tree::load_with_max_depth()` and `Tree::expand()`.
+    pub fn is_stub(&self) -> bool {
+        self.data().is_stub
+    }
+
+    /// Returns the parent node, or `None` for the implicit root node.
+    pub fn parent(&self) -> Option<Node<'a>> {
+        self.data().parent.map(|id| self.tree.node(id))
+    }
+
+    /// Returns an iterator of the ancestors, from the parent to the implicit root node.
+    pub fn ancestors(&self) -> impl Iterator<Item = Node<'a>> {
+        let mut current = self.parent();
+        ::std::iter::from_fn(move || {
+            let node = current?;
+            current = node.parent();
+            Some(node)
+        })
+    }
+
+    /// Returns an iterator of the child nodes.
+    pub fn children(&self) -> impl Iterator<Item = Node<'a>> {
+        let tree = self.tree;
+        self.data().children.iter().map(move |&id| tree.node(id))
+    }
+
+    /// Returns an iterator of the child nodes with the given name.
+    pub fn children_by_name<'b>(&self, name: &'b str) -> impl Iterator<Item = Node<'a>> + 'b
+    where
+        'a: 'b,
+    {
+        self.children().filter(move |child| child.name() == name.as_bytes())
+    }
+
+    /// Returns the first child node with the given name.
+    pub fn first_child_by_name(&self, name: &str) -> Option<Node<'a>> {
+        self.children().find(|child| child.name() == name.as_bytes())
+    }
+
+    /// Returns the node at the given path of names from this node, such as
+    /// `["GlobalSettings", "Properties70"]`.
+    ///
+    /// The first child is used if there are multiple children with the same name.
+    pub fn descendant_by_path(&self, path: &[&str]) -> Option<Node<'a>> {
+        path.iter()
+            .try_fold(*self, |node, name| node.first_child_by_name(name))
+    }
+
+    /// Returns the path of the node names from the toplevel node, joined with `/`.
+    ///
+    /// Names which are not valid UTF-8 are converted lossily.
+    pub fn path(&self) -> String {
+        let mut names = self.ancestors()
+            .filter(|node| !node.is_root())
+            .map(|node| node.name_lossy())
+            .collect::<Vec<_>>();
+        names.reverse();
+        if !self.is_root() {
+            names.push(self.name_lossy());
+        }
+        names.join("/")
+    }
+
+    /// Returns the name of the node as the parent name for the schema lookup.
+    ///
+    /// Returns `None` for the implicit root node, since toplevel nodes have no parent in the
+    /// schema.
+    fn schema_name(&self) -> Option<Cow<'a, str>> {
+        if self.is_root() {
+            None
+        } else {
+            Some(self.name_lossy())
+        }
+    }
+
+    /// Converts the node and its descendants into a generic node.
+    pub fn to_generic_node(&self) -> GenericNode {
+        GenericNode {
+            name: self.name().to_owned(),
+            attributes: self.attributes().map(|attr| attr.to_owned_attribute()).collect(),
+            children: self.children().map(|child| child.to_generic_node()).collect(),
+        }
+    }
+}
+
+impl<'a> fmt::Debug for Node<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Node")
+            .field("id", &self.id)
+            .field("name", &self.name_lossy())
+            .field("attributes", &self.attributes().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use crate::Error;
+    use pull_parser::RootParser;
+    use pull_parser::ascii::AsciiDocument;
+    use super::*;
+
+    #[test]
+    fn load_and_traverse() {
+        let source = "; FBX 7.4.0 project file\n\
+                      GlobalSettings:  {\n\
+                      \tProperties70:  {\n\
+                      \t\tP: \"UpAxis\", \"int\", \"Integer\", \"\",1\n\
+                      \t\tP: \"FrontAxis\", \"int\", \"Integer\", \"\",2\n\
+                      \t}\n\
+                      }\n";
+        let bytes = AsciiDocument::parse(source).unwrap().to_binary().unwrap();
+        let tree = Tree::load(RootParser::new(Cursor::new(bytes))).unwrap();
+        assert_eq!(tree.fbx_version(), Some(7400));
+        assert_eq!(tree.num_nodes(), 5);
+
+        let props = tree.root().descendant_by_path(&["GlobalSettings", "Properties70"]).unwrap();
+        assert_eq!(props.path(), "GlobalSettings/Properties70");
+        let front = props.children_by_name("P").nth(1).unwrap();
+        assert_eq!(front.attribute(4).unwrap(), OwnedAttribute::I32(2));
+        assert_eq!(front.ancestors().count(), 3);
+        let generic = tree.to_generic_nodes(tree.root_id());
+        assert_eq!(Tree::from_generic_nodes(generic).nodes, tree.nodes);
+        assert_eq!(props.span(), None);
+    }
+
+    #[test]
+    fn reload_reuses_arena() {
+        let load = |source: &str| {
+            let bytes = AsciiDocument::parse(source).unwrap().to_binary().unwrap();
+            RootParser::new(Cursor::new(bytes))
+        };
+        let f64_array = |node: Node<'_>| match node.attribute(0) {
+            Some(AttributeRef::ArrF64(v)) => v.to_vec(),
+            v => panic!("Unexpected attribute: {:?}", v),
+        };
+        let big = "; FBX 7.4.0 project file\n\
+                   Objects: {\n\
+                   \tGeometry: 1 {\n\t\tVertices: *3 {\n\t\t\ta: 0.5,1.5,2.5\n\t\t}\n\t}\n\
+                   \tModel: 2\n\
+                   }\n";
+        let small = "; FBX 7.4.0 project file\nTakes: {\n\tArr: *2 {\n\t\ta: 7.5,8.5\n\t}\n}\n";
+
+        let capacity = TreeCapacity {
+            nodes: 16,
+            name_bytes: 64,
+            arrays: ArrayCapacity {
+                f64s: 32,
+                ..ArrayCapacity::default()
+            },
+        };
+        let mut tree = Tree::with_capacity(capacity);
+        tree.reload(load(big)).unwrap();
+        assert_eq!(tree.num_nodes(), 5);
+        assert_eq!(tree.capacity(), capacity);
+        let vertices = tree.root().descendant_by_path(&["Objects", "Geometry", "Vertices"]);
+        assert_eq!(f64_array(vertices.unwrap()), [0.5, 1.5, 2.5]);
+        let arena_ptr = tree.arrays.f64s.as_ptr();
+
+        tree.reload(load(small)).unwrap();
+        assert_eq!(tree, Tree::load(load(small)).unwrap());
+        assert_eq!(tree.capacity(), capacity);
+        let arr = tree.root().descendant_by_path(&["Takes", "Arr"]).unwrap();
+        assert_eq!(f64_array(arr), [7.5, 8.5]);
+        // The array is stored at the start of the same buffer.
+        assert_eq!(tree.arrays.f64s.as_ptr(), arena_ptr);
+        match arr.attribute(0) {
+            Some(AttributeRef::ArrF64(v)) => assert_eq!(v.as_ptr(), arena_ptr),
+            v => panic!("Unexpected attribute: {:?}", v),
+        }
+        assert_eq!(arr.attribute(0).unwrap(), arr.attribute(0).unwrap().to_owned_attribute());
+
+        tree.clear();
+        assert_eq!(tree.num_nodes(), 1);
+        assert_eq!(tree.fbx_version(), None);
+        assert_eq!(tree.capacity(), capacity);
+    }
+
+    #[test]
+    fn load_with_spans() {
+        let source = "; FBX 7.4.0 project file\nA: 1 {\n\tB: 2\n}\nC: 3\n";
+        let bytes = AsciiDocument::parse(source).unwrap().to_binary().unwrap();
+        let tree = Tree::load_with_spans(RootParser::new(Cursor::new(&bytes))).unwrap();
+        let a = tree.root().first_child_by_name("A").unwrap();
+        let b = a.first_child_by_name("B").unwrap();
+        let c = tree.root().first_child_by_name("C").unwrap();
+        // Header (27 bytes), node header (13 bytes), name, and an `i32` attribute.
+        let a_span = a.span().unwrap();
+        assert_eq!(a_span.offset, 27);
+        assert_eq!(b.span().unwrap().offset, 27 + 13 + 1 + 5);
+        // `A` ends with the null record.
+        assert_eq!(b.span().unwrap().end() + 13, a_span.end());
+        assert_eq!(c.span().unwrap().offset, a_span.end());
+        assert_eq!(&bytes[c.span().unwrap().offset as usize + 13..][..1], b"C");
+    }
+
+    #[test]
+    fn parse_with_deadline() {
+        let source = "; FBX 7.4.0 project file\nA:  {\n\tB: 1\n\tC: 2\n}\nD: 3\n";
+        let bytes = AsciiDocument::parse(source).unwrap().to_binary().unwrap();
+        let full = Tree::load(RootParser::new(Cursor::new(&bytes))).unwrap();
+
+        let parser = RootParser::new(Cursor::new(&bytes));
+        let mut partial = Tree::parse_with_deadline(parser, Duration::from_secs(0)).unwrap();
+        assert!(!partial.is_complete());
+        let a = partial.tree().root().children().next().unwrap();
+        assert_eq!(a.children().count(), 1);
+        while !partial.resume(Duration::from_secs(0)).unwrap() {}
+        assert_eq!(partial.into_tree(), full);
+    }
+
+    #[test]
+    fn load_with_max_depth_and_expand() {
+        let source = "; FBX 7.4.0 project file\nA:  {\n\tB: 1 {\n\t\tC: 2\n\t}\n}\nD: 3\n";
+        let bytes = AsciiDocument::parse(source).unwrap().to_binary().unwrap();
+        let full = Tree::load(RootParser::new(Cursor::new(&bytes))).unwrap();
+
+        let mut tree = Tree::load_with_max_depth(RootParser::new(Cursor::new(&bytes)), 1).unwrap();
+        let a = tree.root().first_child_by_name("A").unwrap().id();
+        assert!(tree.node(a).is_stub());
+        assert_eq!(tree.node(a).children().count(), 0);
+        assert!(!tree.root().first_child_by_name("D").unwrap().is_stub());
+
+        tree.expand(a, Cursor::new(&bytes), Some(1)).unwrap();
+        let b = tree.node(a).first_child_by_name("B").unwrap().id();
+        assert!(tree.node(b).is_stub());
+        tree.expand(b, Cursor::new(&bytes), None).unwrap();
+        assert_eq!(tree.to_generic_nodes(tree.root_id()), full.to_generic_nodes(full.root_id()));
+    }
+
+    #[test]
+    fn edit_nodes() {
+        let source = "; FBX 7.4.0 project file\n\
+                      GlobalSettings:  {\n\
+                      \tProperties70:  {\n\
+                      \t\tP: \"UpAxis\", \"int\", \"Integer\", \"\",1\n\
+                      \t}\n\
+                      }\n\
+                      Custom: 1\n";
+        let bytes = AsciiDocument::parse(source).unwrap().to_binary().unwrap();
+        let mut tree = Tree::load(RootParser::new(Cursor::new(bytes))).unwrap();
+        let props = tree.root().descendant_by_path(&["GlobalSettings", "Properties70"]).unwrap();
+        let props = props.id();
+        let prop = |name: &str, value: i32| GenericNode {
+            name: b"P".to_vec(),
+            attributes: vec![
+                OwnedAttribute::from(name),
+                OwnedAttribute::from("int"),
+                OwnedAttribute::from("Integer"),
+                OwnedAttribute::from(""),
+                OwnedAttribute::I32(value),
+            ],
+            children: Vec::new(),
+        };
+
+        // Accepted edits.
+        let front = tree.add_child(props, prop("FrontAxis", 2)).unwrap();
+        assert_eq!(tree.node(front).path(), "GlobalSettings/Properties70/P");
+        assert_eq!(tree.node(props).children().count(), 2);
+        let old = tree.set_attributes(front, prop("FrontAxis", 1).attributes).unwrap();
+        assert_eq!(old[4], OwnedAttribute::I32(2));
+        assert_eq!(tree.node(front).attribute(4).unwrap(), OwnedAttribute::I32(1));
+        // Nodes unknown to the schema accept any attributes.
+        let custom = tree.root().first_child_by_name("Custom").unwrap().id();
+        tree.set_attributes(custom, vec![OwnedAttribute::from("a")]).unwrap();
+
+        // Rejected edits don't change the tree.
+        let num_nodes = tree.num_nodes();
+        let invalid = GenericNode {
+            name: b"P".to_vec(),
+            attributes: vec![OwnedAttribute::I32(1)],
+            children: Vec::new(),
+        };
+        match tree.add_child(props, invalid.clone()) {
+            Err(Error::InvalidAttribute(ref name)) if name == "P" => {},
+            res => panic!("Unexpected result: {:?}", res),
+        }
+        let too_long = GenericNode {
+            name: vec![b'x'; crate::MAX_NODE_NAME_LEN + 1],
+            ..GenericNode::new()
+        };
+        match tree.add_child(props, too_long) {
+            Err(Error::NodeNameTooLong { .. }) => {},
+            res => panic!("Unexpected result: {:?}", res),
+        }
+        assert_eq!(tree.num_nodes(), num_nodes);
+        match tree.set_attributes(front, invalid.attributes) {
+            Err(Error::InvalidAttribute(ref name)) if name == "P" => {},
+            res => panic!("Unexpected result: {:?}", res),
+        }
+        assert_eq!(tree.node(front).attribute(4).unwrap(), OwnedAttribute::I32(1));
+
+        // Removal.
+        let up = tree.node(props).children().next().unwrap().id();
+        let removed = tree.remove_node(up).unwrap();
+        assert_eq!(removed.attributes[0], OwnedAttribute::from("UpAxis"));
+        assert_eq!(tree.node(props).children().map(|n| n.id()).collect::<Vec<_>>(), [front]);
+        assert_eq!(tree.remove_node(up), None);
+        assert_eq!(tree.remove_node(tree.root_id()), None);
+        let nodes = tree.to_generic_nodes(tree.root_id());
+        assert_eq!(nodes[0].children[0].children, vec![prop("FrontAxis", 1)]);
+    }
+}
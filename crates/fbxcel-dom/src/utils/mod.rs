@@ -0,0 +1,119 @@
+//! Useful functionalities for loading FBX.
+
+use std::ops::MulAssign;
+
+
+/// Applies the given function to each polygon.
+///
+/// Returns `Ok(n)` on success where `n` is the number of not completed vrtices at pvi's tail,
+/// returns `Err(e)` if `f` returned `Err(_)`.
+///
+/// If `f` returned `Err(_)`, the loop will no longer be continued.
+///
+/// This can be used for polygon vertex indices of FBX 7.4 or later.
+pub fn for_each_polygon<E, F>(pvi: &[i32], mut f: F) -> Result<usize, E>
+where
+    F: FnMut(&[u32]) -> Result<(), E>,
+{
+    let mut polys = Vec::new();
+    let mut pvi_iter = pvi.iter();
+    'all: loop {
+        let mut polygon_closed = false;
+        'get_poly: for &i in &mut pvi_iter {
+            if i < 0 {
+                polys.push(!i as u32);
+                polygon_closed = true;
+                break 'get_poly;
+            } else {
+                polys.push(i as u32);
+            }
+        }
+        // Note that if `polys.is_empty() == true` then `polygon_closed == false`.
+        if !polygon_closed {
+            // Polygon is not closed (i.e. the last pvi is not negative) or there are no more pvi.
+            break 'all;
+        }
+        f(&polys)?;
+        polys.clear();
+    }
+    Ok(polys.len())
+}
+
+
+/// Minimum number of elements to process arrays in parallel.
+///
+/// Smaller arrays are processed sequentially, because splitting them costs more than it saves.
+#[cfg_attr(not(feature = "rayon"), allow(dead_code))]
+const PARALLEL_THRESHOLD: usize = 16 * 1024;
+
+
+/// Maps each element of the slice, such as `f32` to `f64` conversion of array attributes.
+///
+/// With `rayon` feature, large arrays are mapped in parallel.
+/// The order of the elements is preserved in any case.
+pub fn map_slice<S, T, F>(src: &[S], f: F) -> Vec<T>
+where
+    S: Sync,
+    T: Send,
+    F: Fn(&S) -> T + Sync + Send,
+{
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        if src.len() >= PARALLEL_THRESHOLD {
+            return src.par_iter().map(f).collect();
+        }
+    }
+    src.iter().map(f).collect()
+}
+
+
+/// Multiplies each value by the given factor.
+///
+/// This can be used for unit conversion of positions.
+/// With `rayon` feature, large arrays are processed in parallel.
+pub fn scale_in_place<T>(values: &mut [T], factor: T)
+where
+    T: Copy + MulAssign + Send + Sync,
+{
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        if values.len() >= PARALLEL_THRESHOLD {
+            values.par_iter_mut().for_each(|v| *v *= factor);
+            return;
+        }
+    }
+    for v in values {
+        *v *= factor;
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn map_slice_keeps_order() {
+        // Below and above the threshold, i.e. sequential and (with `rayon`) parallel paths.
+        for &len in &[PARALLEL_THRESHOLD - 1, PARALLEL_THRESHOLD * 3 + 1] {
+            let src: Vec<f32> = (0..len).map(|i| i as f32 * 0.5).collect();
+            let dst = map_slice(&src, |&v| f64::from(v));
+            assert_eq!(dst.len(), len);
+            assert!(dst.iter().enumerate().all(|(i, &v)| v == i as f64 * 0.5));
+        }
+    }
+
+    #[test]
+    fn scale_in_place_scales_all() {
+        for &len in &[PARALLEL_THRESHOLD - 1, PARALLEL_THRESHOLD * 3 + 1] {
+            let mut values: Vec<f64> = (0..len).map(|i| i as f64).collect();
+            scale_in_place(&mut values, 0.01);
+            assert!(values.iter().enumerate().all(|(i, &v)| v == i as f64 * 0.01));
+            let mut values = vec![2.0f32; len];
+            scale_in_place(&mut values, 1.5);
+            assert!(values.iter().all(|&v| v == 3.0));
+        }
+    }
+}
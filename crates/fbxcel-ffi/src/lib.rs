@@ -0,0 +1,553 @@
+//! Minimal C API of the binary parser of fbxcel.
+//!
+//! To build a shared library and a static library, run `cargo build --release -p fbxcel-ffi`
+//! (the crate is built as `cdylib` and `staticlib`).
+//!
+//! Panics never unwind across the C boundary: they are caught in every entry point, and the
+//! function returns its failure value (null, `false`, `0`, or `FBXCEL_EVENT_ERROR`).
+//! A parser which panicked while reading returns `FBXCEL_EVENT_ERROR` afterward, and
+//! `fbxcel_parser_last_error_code()` returns `FBXCEL_ERROR_PANIC`.
+//!
+//! Typical usage from C:
+//!
+//! ```c
+//! FbxcelParser *parser = fbxcel_parser_open_path("model.fbx");
+//! int32_t ev;
+//! while ((ev = fbxcel_parser_next_event(parser)) >= 0 && ev != FBXCEL_EVENT_END_FBX) {
+//!     if (ev == FBXCEL_EVENT_START_NODE) {
+//!         size_t len;
+//!         const uint8_t *name = fbxcel_parser_node_name(parser, &len);
+//!         /* ... */
+//!     }
+//! }
+//! fbxcel_parser_free(parser);
+//! ```
+//!
+//! Pointers returned by accessors are valid until the next call of `fbxcel_parser_next_event()`
+//! or `fbxcel_parser_free()`.
+#![warn(missing_docs)]
+
+extern crate fbxcel_core;
+extern crate fbxcel_dom;
+
+use std::ffi::CStr;
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read};
+use std::os::raw::{c_char, c_void};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::slice;
+use fbxcel_core::pull_parser::{RootParser, BasicSource, Parser, Event};
+use fbxcel_dom::OwnedAttribute;
+
+
+/// Event code for `StartFbx`.
+pub const FBXCEL_EVENT_START_FBX: i32 = 0;
+/// Event code for `EndFbx`.
+pub const FBXCEL_EVENT_END_FBX: i32 = 1;
+/// Event code for `StartNode`.
+pub const FBXCEL_EVENT_START_NODE: i32 = 2;
+/// Event code for `EndNode`.
+pub const FBXCEL_EVENT_END_NODE: i32 = 3;
+/// Event code for errors.
+///
+/// The error code is available by `fbxcel_parser_last_error_code()`.
+pub const FBXCEL_EVENT_ERROR: i32 = -1;
+
+
+/// Error code for a panic inside the parser.
+///
+/// This is out of the ranges of the error codes of the Rust API.
+pub const FBXCEL_ERROR_PANIC: u32 = 9000;
+
+
+/// Parser handle.
+pub struct FbxcelParser {
+    /// Parser.
+    parser: RootParser<BasicSource<Box<dyn Read>>>,
+    /// Name of the current node.
+    node_name: Vec<u8>,
+    /// Attributes of the current node.
+    attributes: Vec<OwnedAttribute>,
+    /// Code of the last error, or `0` if no errors occurred.
+    last_error: u32,
+    /// Whether the parser panicked.
+    panicked: bool,
+}
+
+impl FbxcelParser {
+    /// Creates a new handle.
+    fn new_boxed(source: Box<dyn Read>) -> *mut FbxcelParser {
+        Box::into_raw(Box::new(FbxcelParser {
+            parser: RootParser::new(source),
+            node_name: Vec::new(),
+            attributes: Vec::new(),
+            last_error: 0,
+            panicked: false,
+        }))
+    }
+
+    /// Reads the next event and returns the event code.
+    fn next_event(&mut self) -> i32 {
+        if self.panicked {
+            return FBXCEL_EVENT_ERROR;
+        }
+        match panic::catch_unwind(AssertUnwindSafe(|| self.next_event_impl())) {
+            Ok(code) => code,
+            Err(_) => {
+                self.node_name.clear();
+                self.attributes.clear();
+                self.last_error = FBXCEL_ERROR_PANIC;
+                self.panicked = true;
+                FBXCEL_EVENT_ERROR
+            },
+        }
+    }
+
+    /// Reads the next event and returns the event code, without catching panics.
+    fn next_event_impl(&mut self) -> i32 {
+        self.node_name.clear();
+        self.attributes.clear();
+        let result = match self.parser.next_event() {
+            Ok(Event::StartFbx(_)) => Ok(FBXCEL_EVENT_START_FBX),
+            Ok(Event::EndFbx(_)) => Ok(FBXCEL_EVENT_END_FBX),
+            Ok(Event::EndNode) => Ok(FBXCEL_EVENT_END_NODE),
+            Ok(Event::StartNode(info)) => {
+                self.node_name.extend_from_slice(info.name_bytes());
+                OwnedAttribute::load_attrs_from_parser_event(info.attributes)
+                    .map(|attrs| self.attributes = attrs)
+                    .map(|_| FBXCEL_EVENT_START_NODE)
+            },
+            Err(err) => Err(err),
+        };
+        match result {
+            Ok(code) => code,
+            Err(err) => {
+                self.last_error = err.code();
+                FBXCEL_EVENT_ERROR
+            },
+        }
+    }
+}
+
+
+/// Calls the function, and returns `default` if it panics.
+fn catch_panic<T, F: FnOnce() -> T>(default: T, f: F) -> T {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(default)
+}
+
+
+/// Opens the FBX file at the given path.
+///
+/// Returns null on failure.
+///
+/// # Safety
+/// `path` should be a valid null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn fbxcel_parser_open_path(path: *const c_char) -> *mut FbxcelParser {
+    catch_panic(ptr::null_mut(), || {
+        if path.is_null() {
+            return ptr::null_mut();
+        }
+        let path = match CStr::from_ptr(path).to_str() {
+            Ok(v) => v,
+            Err(_) => return ptr::null_mut(),
+        };
+        match File::open(path) {
+            Ok(file) => FbxcelParser::new_boxed(Box::new(BufReader::new(file))),
+            Err(_) => ptr::null_mut(),
+        }
+    })
+}
+
+
+/// Creates a parser for the given bytes.
+///
+/// The bytes are copied, so the buffer can be freed after the call.
+/// Returns null on failure.
+///
+/// # Safety
+/// `data` should point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn fbxcel_parser_open_bytes(
+    data: *const u8,
+    len: usize,
+) -> *mut FbxcelParser {
+    catch_panic(ptr::null_mut(), || {
+        if data.is_null() && len != 0 {
+            return ptr::null_mut();
+        }
+        let bytes = if len == 0 {
+            Vec::new()
+        } else {
+            slice::from_raw_parts(data, len).to_vec()
+        };
+        FbxcelParser::new_boxed(Box::new(Cursor::new(bytes)))
+    })
+}
+
+
+/// Frees the parser.
+///
+/// # Safety
+/// `parser` should be a handle returned by `fbxcel_parser_open_*()` or null, and should not be
+/// used after the call.
+#[no_mangle]
+pub unsafe extern "C" fn fbxcel_parser_free(parser: *mut FbxcelParser) {
+    catch_panic((), || {
+        if !parser.is_null() {
+            drop(Box::from_raw(parser));
+        }
+    })
+}
+
+
+/// Reads the next event and returns the event code (`FBXCEL_EVENT_*`).
+///
+/// # Safety
+/// `parser` should be a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn fbxcel_parser_next_event(parser: *mut FbxcelParser) -> i32 {
+    catch_panic(FBXCEL_EVENT_ERROR, || {
+        match parser.as_mut() {
+            Some(parser) => parser.next_event(),
+            None => FBXCEL_EVENT_ERROR,
+        }
+    })
+}
+
+
+/// Returns the code of the last error (see `pull_parser::Error::code()`), or `0` if no
+/// errors occurred.
+///
+/// # Safety
+/// `parser` should be a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn fbxcel_parser_last_error_code(parser: *const FbxcelParser) -> u32 {
+    catch_panic(0, || {
+        parser.as_ref().map_or(0, |parser| parser.last_error)
+    })
+}
+
+
+/// Returns the FBX version, or `0` if it is not read yet.
+///
+/// # Safety
+/// `parser` should be a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn fbxcel_parser_fbx_version(parser: *const FbxcelParser) -> u32 {
+    catch_panic(0, || {
+        parser
+            .as_ref()
+            .and_then(|parser| parser.parser.fbx_version())
+            .unwrap_or(0)
+    })
+}
+
+
+/// Returns the name of the current node (not null-terminated), and writes its length to
+/// `len_out`.
+///
+/// # Safety
+/// `parser` should be a valid handle, and `len_out` should be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn fbxcel_parser_node_name(
+    parser: *const FbxcelParser,
+    len_out: *mut usize,
+) -> *const u8 {
+    catch_panic(ptr::null(), || {
+        let parser = match parser.as_ref() {
+            Some(v) => v,
+            None => return ptr::null(),
+        };
+        if let Some(len_out) = len_out.as_mut() {
+            *len_out = parser.node_name.len();
+        }
+        parser.node_name.as_ptr()
+    })
+}
+
+
+/// Returns the number of attributes of the current node.
+///
+/// # Safety
+/// `parser` should be a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn fbxcel_parser_num_attributes(parser: *const FbxcelParser) -> usize {
+    catch_panic(0, || {
+        parser.as_ref().map_or(0, |parser| parser.attributes.len())
+    })
+}
+
+
+/// Returns the attribute of the current node.
+unsafe fn attribute<'a>(parser: *const FbxcelParser, index: usize) -> Option<&'a OwnedAttribute> {
+    parser.as_ref().and_then(|parser| parser.attributes.get(index))
+}
+
+
+/// Returns the FBX type code of the attribute (such as `'I'` for `i32` and `'d'` for `f64`
+/// array), or `0` if the attribute does not exist.
+///
+/// # Safety
+/// `parser` should be a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn fbxcel_parser_attribute_type(
+    parser: *const FbxcelParser,
+    index: usize,
+) -> u8 {
+    catch_panic(0, || {
+        match attribute(parser, index) {
+            Some(&OwnedAttribute::Bool(_)) => b'C',
+            Some(&OwnedAttribute::I16(_)) => b'Y',
+            Some(&OwnedAttribute::I32(_)) => b'I',
+            Some(&OwnedAttribute::I64(_)) => b'L',
+            Some(&OwnedAttribute::F32(_)) => b'F',
+            Some(&OwnedAttribute::F64(_)) => b'D',
+            Some(&OwnedAttribute::ArrBool(_)) => b'b',
+            Some(&OwnedAttribute::ArrI32(_)) => b'i',
+            Some(&OwnedAttribute::ArrI64(_)) => b'l',
+            Some(&OwnedAttribute::ArrF32(_)) => b'f',
+            Some(&OwnedAttribute::ArrF64(_)) => b'd',
+            Some(&OwnedAttribute::String(_)) => b'S',
+            Some(&OwnedAttribute::Binary(_)) => b'R',
+            None => 0,
+        }
+    })
+}
+
+
+/// Writes the integer (or boolean) attribute value to `out`.
+///
+/// Returns `false` if the attribute is not an integer or boolean.
+///
+/// # Safety
+/// `parser` should be a valid handle, and `out` should be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn fbxcel_parser_attribute_i64(
+    parser: *const FbxcelParser,
+    index: usize,
+    out: *mut i64,
+) -> bool {
+    catch_panic(false, || {
+        let value = match attribute(parser, index) {
+            Some(&OwnedAttribute::Bool(v)) => i64::from(v),
+            Some(&OwnedAttribute::I16(v)) => i64::from(v),
+            Some(&OwnedAttribute::I32(v)) => i64::from(v),
+            Some(&OwnedAttribute::I64(v)) => v,
+            _ => return false,
+        };
+        match out.as_mut() {
+            Some(out) => {
+                *out = value;
+                true
+            },
+            None => false,
+        }
+    })
+}
+
+
+/// Writes the floating point attribute value to `out`.
+///
+/// Returns `false` if the attribute is not a floating point number.
+///
+/// # Safety
+/// `parser` should be a valid handle, and `out` should be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn fbxcel_parser_attribute_f64(
+    parser: *const FbxcelParser,
+    index: usize,
+    out: *mut f64,
+) -> bool {
+    catch_panic(false, || {
+        let value = match attribute(parser, index) {
+            Some(&OwnedAttribute::F32(v)) => f64::from(v),
+            Some(&OwnedAttribute::F64(v)) => v,
+            _ => return false,
+        };
+        match out.as_mut() {
+            Some(out) => {
+                *out = value;
+                true
+            },
+            None => false,
+        }
+    })
+}
+
+
+/// Returns the bytes of the string or binary attribute, and writes the length to `len_out`.
+///
+/// Strings are not null-terminated, and may be invalid UTF-8.
+/// Returns null if the attribute is not a string nor a binary.
+///
+/// # Safety
+/// `parser` should be a valid handle, and `len_out` should be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn fbxcel_parser_attribute_bytes(
+    parser: *const FbxcelParser,
+    index: usize,
+    len_out: *mut usize,
+) -> *const u8 {
+    catch_panic(ptr::null(), || {
+        let bytes: &[u8] = match attribute(parser, index) {
+            Some(OwnedAttribute::String(Ok(v))) => v.as_bytes(),
+            Some(OwnedAttribute::String(Err(v))) => v,
+            Some(OwnedAttribute::Binary(v)) => v,
+            _ => return ptr::null(),
+        };
+        if let Some(len_out) = len_out.as_mut() {
+            *len_out = bytes.len();
+        }
+        bytes.as_ptr()
+    })
+}
+
+
+/// Returns the elements of the array attribute, and writes the number of elements to `len_out`.
+///
+/// The element type is determined by `fbxcel_parser_attribute_type()`: `bool` (1 byte) for
+/// `'b'`, `int32_t` for `'i'`, `int64_t` for `'l'`, `float` for `'f'`, and `double` for `'d'`.
+/// Returns null if the attribute is not an array.
+///
+/// # Safety
+/// `parser` should be a valid handle, and `len_out` should be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn fbxcel_parser_attribute_array(
+    parser: *const FbxcelParser,
+    index: usize,
+    len_out: *mut usize,
+) -> *const c_void {
+    catch_panic(ptr::null(), || {
+        let (ptr, len) = match attribute(parser, index) {
+            Some(OwnedAttribute::ArrBool(v)) => (v.as_ptr() as *const c_void, v.len()),
+            Some(OwnedAttribute::ArrI32(v)) => (v.as_ptr() as *const c_void, v.len()),
+            Some(OwnedAttribute::ArrI64(v)) => (v.as_ptr() as *const c_void, v.len()),
+            Some(OwnedAttribute::ArrF32(v)) => (v.as_ptr() as *const c_void, v.len()),
+            Some(OwnedAttribute::ArrF64(v)) => (v.as_ptr() as *const c_void, v.len()),
+            _ => return ptr::null(),
+        };
+        if let Some(len_out) = len_out.as_mut() {
+            *len_out = len;
+        }
+        ptr
+    })
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CString;
+    use std::slice;
+    use fbxcel_core::pull_parser::ascii::AsciiDocument;
+    use super::*;
+
+    const SOURCE: &str = "; FBX 7.4.0 project file\n\
+                          Node: 42, 2.5, \"str\" {\n\
+                          \tArray: *3 {\n\t\ta: 1,2,3\n\t}\n\
+                          }\n";
+
+    #[test]
+    fn read_events() {
+        let bytes = AsciiDocument::parse(SOURCE).unwrap().to_binary().unwrap();
+        unsafe {
+            let parser = fbxcel_parser_open_bytes(bytes.as_ptr(), bytes.len());
+            assert!(!parser.is_null());
+            assert_eq!(fbxcel_parser_next_event(parser), FBXCEL_EVENT_START_FBX);
+            assert_eq!(fbxcel_parser_fbx_version(parser), 7400);
+
+            assert_eq!(fbxcel_parser_next_event(parser), FBXCEL_EVENT_START_NODE);
+            let mut len = 0;
+            let name = fbxcel_parser_node_name(parser, &mut len);
+            assert_eq!(slice::from_raw_parts(name, len), b"Node");
+            assert_eq!(fbxcel_parser_num_attributes(parser), 3);
+            assert_eq!(fbxcel_parser_attribute_type(parser, 0), b'I');
+            let mut int = 0;
+            assert!(fbxcel_parser_attribute_i64(parser, 0, &mut int));
+            assert_eq!(int, 42);
+            let mut float = 0.0;
+            assert!(fbxcel_parser_attribute_f64(parser, 1, &mut float));
+            assert_eq!(float, 2.5);
+            assert!(!fbxcel_parser_attribute_f64(parser, 0, &mut float));
+            let s = fbxcel_parser_attribute_bytes(parser, 2, &mut len);
+            assert_eq!(slice::from_raw_parts(s, len), b"str");
+
+            assert_eq!(fbxcel_parser_next_event(parser), FBXCEL_EVENT_START_NODE);
+            assert_eq!(fbxcel_parser_attribute_type(parser, 0), b'i');
+            let arr = fbxcel_parser_attribute_array(parser, 0, &mut len) as *const i32;
+            assert_eq!(slice::from_raw_parts(arr, len), &[1, 2, 3]);
+
+            assert_eq!(fbxcel_parser_next_event(parser), FBXCEL_EVENT_END_NODE);
+            assert_eq!(fbxcel_parser_next_event(parser), FBXCEL_EVENT_END_NODE);
+            assert_eq!(fbxcel_parser_next_event(parser), FBXCEL_EVENT_END_FBX);
+            assert_eq!(fbxcel_parser_last_error_code(parser), 0);
+            fbxcel_parser_free(parser);
+        }
+    }
+
+    #[test]
+    fn null_and_invalid_arguments() {
+        unsafe {
+            let null = ptr::null_mut();
+            assert!(fbxcel_parser_open_path(ptr::null()).is_null());
+            let invalid_utf8 = CString::new(vec![0xff, 0xfe]).unwrap();
+            assert!(fbxcel_parser_open_path(invalid_utf8.as_ptr()).is_null());
+            let missing = CString::new("/nonexistent/fbxcel/missing.fbx").unwrap();
+            assert!(fbxcel_parser_open_path(missing.as_ptr()).is_null());
+            assert!(fbxcel_parser_open_bytes(ptr::null(), 16).is_null());
+
+            assert_eq!(fbxcel_parser_next_event(null), FBXCEL_EVENT_ERROR);
+            assert_eq!(fbxcel_parser_last_error_code(null), 0);
+            assert_eq!(fbxcel_parser_fbx_version(null), 0);
+            assert!(fbxcel_parser_node_name(null, ptr::null_mut()).is_null());
+            assert_eq!(fbxcel_parser_num_attributes(null), 0);
+            assert_eq!(fbxcel_parser_attribute_type(null, 0), 0);
+            assert!(!fbxcel_parser_attribute_i64(null, 0, ptr::null_mut()));
+            assert!(!fbxcel_parser_attribute_f64(null, 0, ptr::null_mut()));
+            assert!(fbxcel_parser_attribute_bytes(null, 0, ptr::null_mut()).is_null());
+            assert!(fbxcel_parser_attribute_array(null, 0, ptr::null_mut()).is_null());
+            fbxcel_parser_free(null);
+
+            // Empty and broken sources.
+            let parser = fbxcel_parser_open_bytes(ptr::null(), 0);
+            assert!(!parser.is_null());
+            assert_eq!(fbxcel_parser_next_event(parser), FBXCEL_EVENT_ERROR);
+            assert_ne!(fbxcel_parser_last_error_code(parser), 0);
+            fbxcel_parser_free(parser);
+            let garbage = [0xffu8; 64];
+            let parser = fbxcel_parser_open_bytes(garbage.as_ptr(), garbage.len());
+            assert_eq!(fbxcel_parser_next_event(parser), FBXCEL_EVENT_ERROR);
+            assert_eq!(fbxcel_parser_last_error_code(parser), 1005);
+
+            // Out-of-range indices and null output pointers.
+            assert_eq!(fbxcel_parser_num_attributes(parser), 0);
+            assert_eq!(fbxcel_parser_attribute_type(parser, 100), 0);
+            assert!(!fbxcel_parser_attribute_i64(parser, usize::MAX, ptr::null_mut()));
+            assert!(fbxcel_parser_attribute_array(parser, 0, ptr::null_mut()).is_null());
+            fbxcel_parser_free(parser);
+        }
+    }
+
+    #[test]
+    fn catch_panic_in_parser() {
+        /// Reader which panics.
+        struct PanickingReader;
+
+        impl Read for PanickingReader {
+            fn read(&mut self, _buf: &mut [u8]) -> ::std::io::Result<usize> {
+                panic!("Reader panicked");
+            }
+        }
+
+        unsafe {
+            let parser = FbxcelParser::new_boxed(Box::new(PanickingReader));
+            assert_eq!(fbxcel_parser_next_event(parser), FBXCEL_EVENT_ERROR);
+            assert_eq!(fbxcel_parser_last_error_code(parser), FBXCEL_ERROR_PANIC);
+            // The parser stays failed after a panic.
+            assert_eq!(fbxcel_parser_next_event(parser), FBXCEL_EVENT_ERROR);
+            fbxcel_parser_free(parser);
+        }
+    }
+}
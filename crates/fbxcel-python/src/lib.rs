@@ -0,0 +1,38 @@
+//! Python bindings of fbxcel.
+//!
+//! The `fbxcel` Python module is enabled by `pyo3` feature, so that building the workspace does
+//! not require a Python toolchain.
+//! To build and install the module into the current virtualenv, run `maturin develop` in this
+//! directory.
+//!
+//! ```python
+//! import fbxcel
+//!
+//! tree = fbxcel.load_tree("model.fbx")
+//! vertices = tree.root().descendant_by_path(["Objects", "Geometry", "Vertices"])
+//! positions = vertices.attributes()[0].reshape(-1, 3)  # Read-only view, not a copy.
+//!
+//! for mesh in fbxcel.load_scene("model.fbx").meshes():
+//!     print(mesh.name, mesh.positions.shape, mesh.indices.shape)
+//!
+//! print(fbxcel.dump_to_string("model.fbx"))
+//! ```
+//!
+//! Array attributes and mesh buffers are exposed as read-only NumPy arrays borrowing the Rust
+//! data, which stays alive as long as any of the arrays (or the tree or the mesh) is alive.
+//!
+//! Tests of the module are not run by `cargo test --workspace`, and require Python and NumPy:
+//!
+//! ```sh
+//! cargo test -p fbxcel-python --features fbxcel-python/pyo3
+//! ```
+#![warn(missing_docs)]
+
+extern crate fbxcel;
+#[cfg(feature = "pyo3")]
+extern crate numpy;
+#[cfg(feature = "pyo3")]
+extern crate pyo3;
+
+#[cfg(feature = "pyo3")]
+pub mod python;
@@ -0,0 +1,532 @@
+//! `fbxcel` Python module.
+//!
+//! This module is enabled by `pyo3` feature.
+//! Errors are raised as `fbxcel.FbxError`, whose message ends with the stable error code (see
+//! `dom::Error::code()`).
+//! Files are loaded without holding the GIL, so other Python threads can run meanwhile.
+
+use std::path::PathBuf;
+use fbxcel::{dump_to_string as dump_file, load_scene as load_file, open_parser};
+use fbxcel::dom::{Error, OwnedAttribute};
+use fbxcel::dom::fbx7400::Fbx7400Document;
+use fbxcel::dom::fbx7400::geometry::ResolvedMesh;
+use fbxcel::dom::tree::{AttributeRef, NodeId, Tree};
+use fbxcel::pull_parser::any::AnyParser;
+use numpy::{Element, PyArray1, PyArray2, PyArrayMethods};
+use numpy::ndarray::{ArrayView1, ArrayView2};
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use pyo3::IntoPyObjectExt;
+
+
+create_exception!(fbxcel, FbxError, PyException, "Error of loading FBX files.");
+
+
+/// Converts the loader error into `FbxError`.
+fn to_py_err(err: Error) -> PyErr {
+    FbxError::new_err(format!("{} (code {})", err, err.code()))
+}
+
+
+/// Creates a read-only 1-dimensional NumPy array borrowing the slice.
+///
+/// # Safety
+///
+/// `slice` must not be modified nor deallocated while `owner` is alive.
+unsafe fn borrow_array1<'py, T: Element>(
+    slice: &[T],
+    owner: &Bound<'py, PyAny>,
+) -> Bound<'py, PyAny> {
+    let array = PyArray1::borrow_from_array(&ArrayView1::from(slice), owner.clone());
+    array.readwrite().make_nonwriteable();
+    array.into_any()
+}
+
+
+/// Creates a read-only NumPy array of shape `(n, N)` borrowing the slice.
+///
+/// # Safety
+///
+/// `slice` must not be modified nor deallocated while `owner` is alive.
+unsafe fn borrow_array2<'py, const N: usize>(
+    slice: &[[f64; N]],
+    owner: &Bound<'py, PyAny>,
+) -> Bound<'py, PyAny> {
+    let view = ArrayView2::from_shape((slice.len(), N), slice.as_flattened())
+        .expect("Shape of `[[f64; N]]` should be valid");
+    let array = PyArray2::borrow_from_array(&view, owner.clone());
+    array.readwrite().make_nonwriteable();
+    array.into_any()
+}
+
+
+/// Converts the attribute into a Python object.
+///
+/// Arrays are read-only NumPy arrays borrowing the tree, and kept alive by `owner`.
+fn attribute_to_py<'py>(
+    attr: AttributeRef<'_>,
+    owner: &Bound<'py, PyAny>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let py = owner.py();
+    // SAFETY: The owner is a frozen `PyTree`, whose nodes and arrays are never modified nor
+    // reallocated.
+    unsafe {
+        match attr {
+            AttributeRef::ArrBool(v) => Ok(borrow_array1(v, owner)),
+            AttributeRef::ArrI32(v) => Ok(borrow_array1(v, owner)),
+            AttributeRef::ArrI64(v) => Ok(borrow_array1(v, owner)),
+            AttributeRef::ArrF32(v) => Ok(borrow_array1(v, owner)),
+            AttributeRef::ArrF64(v) => Ok(borrow_array1(v, owner)),
+            AttributeRef::Value(v) => match *v {
+                OwnedAttribute::Bool(v) => v.into_bound_py_any(py),
+                OwnedAttribute::I16(v) => v.into_bound_py_any(py),
+                OwnedAttribute::I32(v) => v.into_bound_py_any(py),
+                OwnedAttribute::I64(v) => v.into_bound_py_any(py),
+                OwnedAttribute::F32(v) => v.into_bound_py_any(py),
+                OwnedAttribute::F64(v) => v.into_bound_py_any(py),
+                OwnedAttribute::ArrBool(ref v) => Ok(borrow_array1(v, owner)),
+                OwnedAttribute::ArrI32(ref v) => Ok(borrow_array1(v, owner)),
+                OwnedAttribute::ArrI64(ref v) => Ok(borrow_array1(v, owner)),
+                OwnedAttribute::ArrF32(ref v) => Ok(borrow_array1(v, owner)),
+                OwnedAttribute::ArrF64(ref v) => Ok(borrow_array1(v, owner)),
+                OwnedAttribute::String(Ok(ref v)) => v.into_bound_py_any(py),
+                OwnedAttribute::String(Err(ref v)) => PyBytes::new(py, v).into_bound_py_any(py),
+                OwnedAttribute::Binary(ref v) => PyBytes::new(py, v).into_bound_py_any(py),
+            },
+        }
+    }
+}
+
+
+/// Node tree of a FBX file (`fbxcel.Tree`).
+#[pyclass(name = "Tree", module = "fbxcel", frozen)]
+pub struct PyTree {
+    /// Tree.
+    tree: Tree,
+}
+
+#[pymethods]
+impl PyTree {
+    /// FBX version, or `None` if unknown.
+    #[getter]
+    fn fbx_version(&self) -> Option<u32> {
+        self.tree.fbx_version()
+    }
+
+    /// Number of nodes, including the implicit root node.
+    #[getter]
+    fn num_nodes(&self) -> usize {
+        self.tree.num_nodes()
+    }
+
+    /// Returns the implicit root node, whose children are the toplevel nodes.
+    fn root(slf: &Bound<'_, Self>) -> PyNode {
+        PyNode {
+            tree: slf.clone().unbind(),
+            id: slf.get().tree.root_id(),
+        }
+    }
+}
+
+
+/// Node of a `fbxcel.Tree` (`fbxcel.Node`).
+#[pyclass(name = "Node", module = "fbxcel", frozen)]
+pub struct PyNode {
+    /// Tree.
+    tree: Py<PyTree>,
+    /// Node ID.
+    id: NodeId,
+}
+
+impl PyNode {
+    /// Returns the node handle of the same tree.
+    fn with_id(&self, py: Python<'_>, id: NodeId) -> PyNode {
+        PyNode {
+            tree: self.tree.clone_ref(py),
+            id,
+        }
+    }
+
+    /// Returns the tree.
+    fn tree(&self) -> &Tree {
+        &self.tree.get().tree
+    }
+}
+
+#[pymethods]
+impl PyNode {
+    /// Node name (non-UTF-8 bytes are replaced).
+    #[getter]
+    fn name(&self) -> String {
+        self.tree().node(self.id).name_lossy().into_owned()
+    }
+
+    /// Node name as bytes.
+    #[getter]
+    fn name_bytes<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, self.tree().node(self.id).name())
+    }
+
+    /// Path of the node from the root (such as `Objects/Model/Properties70/P[Lcl Translation]`).
+    #[getter]
+    fn path(&self) -> String {
+        self.tree().node(self.id).path()
+    }
+
+    /// Returns the attributes.
+    ///
+    /// Arrays are read-only NumPy arrays borrowing the tree.
+    fn attributes<'py>(&self, py: Python<'py>) -> PyResult<Vec<Bound<'py, PyAny>>> {
+        let owner = self.tree.bind(py).as_any();
+        self.tree()
+            .node(self.id)
+            .attributes()
+            .map(|attr| attribute_to_py(attr, owner))
+            .collect()
+    }
+
+    /// Returns the parent node, or `None` for the root node.
+    fn parent(&self, py: Python<'_>) -> Option<PyNode> {
+        self.tree().node(self.id).parent().map(|node| self.with_id(py, node.id()))
+    }
+
+    /// Returns the child nodes.
+    fn children(&self, py: Python<'_>) -> Vec<PyNode> {
+        self.tree().node(self.id).children().map(|node| self.with_id(py, node.id())).collect()
+    }
+
+    /// Returns the child nodes with the given name.
+    fn children_by_name(&self, py: Python<'_>, name: &str) -> Vec<PyNode> {
+        self.tree()
+            .node(self.id)
+            .children_by_name(name)
+            .map(|node| self.with_id(py, node.id()))
+            .collect()
+    }
+
+    /// Returns the first child node with the given name.
+    fn first_child_by_name(&self, py: Python<'_>, name: &str) -> Option<PyNode> {
+        self.tree()
+            .node(self.id)
+            .first_child_by_name(name)
+            .map(|node| self.with_id(py, node.id()))
+    }
+
+    /// Returns the descendant node following the first child nodes with the given names.
+    fn descendant_by_path(&self, py: Python<'_>, path: Vec<String>) -> Option<PyNode> {
+        let path = path.iter().map(String::as_str).collect::<Vec<_>>();
+        self.tree()
+            .node(self.id)
+            .descendant_by_path(&path)
+            .map(|node| self.with_id(py, node.id()))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("<fbxcel.Node {}>", self.path())
+    }
+}
+
+
+/// FBX 7.4 or 7.5 scene (`fbxcel.Scene`).
+#[pyclass(name = "Scene", module = "fbxcel", frozen)]
+pub struct PyScene {
+    /// Document.
+    doc: Fbx7400Document,
+}
+
+#[pymethods]
+impl PyScene {
+    /// FBX version.
+    #[getter]
+    fn version(&self) -> u32 {
+        self.doc.version
+    }
+
+    /// Name of the application which wrote the file, or `None` if unknown.
+    #[getter]
+    fn creator(&self) -> Option<&str> {
+        self.doc.creator.as_ref().map(|creator| creator.0.as_str())
+    }
+
+    /// Returns `(id, name, class, subclass)` of the objects.
+    fn objects(&self) -> Vec<(i64, String, String, String)> {
+        self.doc
+            .objects
+            .iter()
+            .flat_map(|objs| objs.iter())
+            .map(|obj| {
+                let props = &obj.props;
+                (props.id, props.name.clone(), props.class.clone(), props.subclass.clone())
+            })
+            .collect()
+    }
+
+    /// Returns the triangulated meshes of the `Geometry` objects.
+    ///
+    /// Broken geometries are skipped.
+    fn meshes(&self, py: Python<'_>) -> Vec<PyMesh> {
+        py.detach(|| {
+            self.doc
+                .objects
+                .iter()
+                .flat_map(|objs| objs.iter())
+                .filter(|obj| obj.props.class == "Geometry")
+                .filter_map(|obj| {
+                    ResolvedMesh::from_geometry(obj).map(|mesh| PyMesh {
+                        id: obj.props.id,
+                        name: obj.props.name.clone(),
+                        mesh,
+                    })
+                })
+                .collect()
+        })
+    }
+}
+
+
+/// Triangulated mesh (`fbxcel.Mesh`).
+///
+/// Buffers are read-only NumPy arrays borrowing the mesh.
+#[pyclass(name = "Mesh", module = "fbxcel", frozen)]
+pub struct PyMesh {
+    /// Object ID of the geometry.
+    id: i64,
+    /// Object name of the geometry.
+    name: String,
+    /// Mesh.
+    mesh: ResolvedMesh,
+}
+
+// SAFETY (of the array getters): The mesh is frozen, and its buffers are never modified nor
+// reallocated.
+#[pymethods]
+impl PyMesh {
+    /// Object ID of the geometry.
+    #[getter]
+    fn id(&self) -> i64 {
+        self.id
+    }
+
+    /// Object name of the geometry.
+    #[getter]
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Positions of vertices, of shape `(num_vertices, 3)`.
+    #[getter]
+    fn positions<'py>(slf: &Bound<'py, Self>) -> Bound<'py, PyAny> {
+        unsafe { borrow_array2(&slf.get().mesh.positions, slf.as_any()) }
+    }
+
+    /// Normals of vertices, of shape `(num_vertices, 3)` (or `(0, 3)` without normals).
+    #[getter]
+    fn normals<'py>(slf: &Bound<'py, Self>) -> Bound<'py, PyAny> {
+        unsafe { borrow_array2(&slf.get().mesh.normals, slf.as_any()) }
+    }
+
+    /// UVs of vertices, of shape `(num_vertices, 2)` (or `(0, 2)` without UVs).
+    #[getter]
+    fn uvs<'py>(slf: &Bound<'py, Self>) -> Bound<'py, PyAny> {
+        unsafe { borrow_array2(&slf.get().mesh.uvs, slf.as_any()) }
+    }
+
+    /// Vertex indices, three for each triangle.
+    #[getter]
+    fn indices<'py>(slf: &Bound<'py, Self>) -> Bound<'py, PyAny> {
+        unsafe { borrow_array1(&slf.get().mesh.indices, slf.as_any()) }
+    }
+
+    /// Material index of each triangle.
+    #[getter]
+    fn materials<'py>(slf: &Bound<'py, Self>) -> Bound<'py, PyAny> {
+        unsafe { borrow_array1(&slf.get().mesh.materials, slf.as_any()) }
+    }
+
+    fn __repr__(&self) -> String {
+        format!("<fbxcel.Mesh {} {:?}>", self.id, self.name)
+    }
+}
+
+
+/// Loads the FBX file (binary or ASCII) as `fbxcel.Tree`.
+#[pyfunction]
+fn load_tree(py: Python<'_>, path: PathBuf) -> PyResult<PyTree> {
+    let tree = py.detach(|| -> Result<Tree, Error> {
+        let tree = match open_parser(path)? {
+            AnyParser::Binary(mut parser) => Tree::load(&mut parser)?,
+            AnyParser::Ascii(mut parser) => Tree::load(&mut parser)?,
+        };
+        Ok(tree)
+    });
+    tree.map(|tree| PyTree { tree }).map_err(to_py_err)
+}
+
+
+/// Loads the FBX file (binary or ASCII) as `fbxcel.Scene`.
+#[pyfunction]
+fn load_scene(py: Python<'_>, path: PathBuf) -> PyResult<PyScene> {
+    py.detach(|| load_file(path)).map(|doc| PyScene { doc }).map_err(to_py_err)
+}
+
+
+/// Loads the FBX file (binary or ASCII) and dumps the node tree in a format similar to FBX
+/// ASCII.
+#[pyfunction]
+fn dump_to_string(py: Python<'_>, path: PathBuf) -> PyResult<String> {
+    py.detach(|| dump_file(path)).map_err(to_py_err)
+}
+
+
+/// Initializes the `fbxcel` Python module.
+#[pymodule]
+#[pyo3(name = "fbxcel")]
+fn fbxcel_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("FbxError", m.py().get_type::<FbxError>())?;
+    m.add_class::<PyTree>()?;
+    m.add_class::<PyNode>()?;
+    m.add_class::<PyScene>()?;
+    m.add_class::<PyMesh>()?;
+    m.add_function(wrap_pyfunction!(load_tree, m)?)?;
+    m.add_function(wrap_pyfunction!(load_scene, m)?)?;
+    m.add_function(wrap_pyfunction!(dump_to_string, m)?)?;
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::ffi::CString;
+    use std::fs;
+    use pyo3::types::{PyDict, PyModule};
+    use super::*;
+
+    /// Runs the Python code with the `fbxcel` module bound to `fbxcel` and `path` bound to the
+    /// given path.
+    fn run(path: &PathBuf, code: &str) {
+        run_with(path, code, |_| {});
+    }
+
+    /// Runs the Python code as `run()`, and then calls `check` with the globals.
+    fn run_with<F>(path: &PathBuf, code: &str, check: F)
+    where
+        F: FnOnce(&Bound<'_, PyDict>),
+    {
+        Python::initialize();
+        Python::attach(|py| {
+            let module = PyModule::new(py, "fbxcel").unwrap();
+            fbxcel_module(&module).unwrap();
+            let globals = PyDict::new(py);
+            globals.set_item("fbxcel", module).unwrap();
+            globals.set_item("path", path).unwrap();
+            let code = CString::new(code).unwrap();
+            if let Err(err) = py.run(&code, Some(&globals), None) {
+                panic!("Python error: {}", err);
+            }
+            check(&globals);
+        });
+    }
+
+    /// Writes the FBX ASCII source to a temporary file, and returns the directory and the path.
+    fn write_fixture(name: &str, source: &str) -> (PathBuf, PathBuf) {
+        let dir = env::temp_dir().join(format!("fbxcel-python-{}-{}", name, ::std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("scene.fbx");
+        fs::write(&path, source).unwrap();
+        (dir, path)
+    }
+
+    #[test]
+    fn tree_and_dump() {
+        let source = "; FBX 7.4.0 project file\n\
+                      Creator: \"fbxcel\"\n\
+                      Objects:  {\n\
+                      \tModel: 1, \"Model::Cube\", \"Mesh\" {\n\t\tVersion: 232\n\t}\n}\n";
+        let (dir, path) = write_fixture("tree", source);
+        run(
+            &path,
+            r#"
+tree = fbxcel.load_tree(path)
+assert tree.fbx_version == 7400
+root = tree.root()
+assert [node.name for node in root.children()] == ["Creator", "Objects"]
+model = root.descendant_by_path(["Objects", "Model"])
+assert model.attributes() == [1, "Cube\x00\x01Model", "Mesh"]
+assert model.parent().name == "Objects"
+assert model.first_child_by_name("Version").attributes() == [232]
+assert root.first_child_by_name("Takes") is None
+assert fbxcel.dump_to_string(path).startswith('Creator: "fbxcel"\n')
+
+try:
+    fbxcel.load_tree(str(path) + ".missing")
+    assert False
+except fbxcel.FbxError as err:
+    assert "code" in str(err)
+"#,
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn arrays_are_read_only_views() {
+        let source = "; FBX 7.4.0 project file\n\
+                      Objects:  {\n\
+                      \tGeometry: 2, \"Geometry::Tri\", \"Mesh\" {\n\
+                      \t\tVertices: *9 {\n\t\t\ta: 0,0,0,1,0,0,0,1,0\n\t\t}\n\
+                      \t\tPolygonVertexIndex: *3 {\n\t\t\ta: 0,1,-3\n\t\t}\n\t}\n}\n";
+        let (dir, path) = write_fixture("arrays", source);
+        run_with(
+            &path,
+            r#"
+tree = fbxcel.load_tree(path)
+vertices = tree.root().descendant_by_path(["Objects", "Geometry", "Vertices"])
+positions = vertices.attributes()[0]
+
+import numpy
+assert positions.dtype == numpy.float64
+assert positions.shape == (9,)
+assert not positions.flags.writeable
+assert not positions.flags.owndata
+assert positions.base is tree
+try:
+    positions[0] = 42.0
+    assert False
+except ValueError:
+    pass
+assert numpy.shares_memory(positions, vertices.attributes()[0])
+vertices_ptr = positions.__array_interface__["data"][0]
+
+mesh = fbxcel.load_scene(path).meshes()[0]
+assert mesh.positions.shape == (3, 3)
+assert not mesh.positions.flags.writeable
+assert mesh.positions.base is mesh
+assert numpy.shares_memory(mesh.positions, mesh.positions)
+try:
+    mesh.indices[0] = 1
+    assert False
+except ValueError:
+    pass
+"#,
+            |globals| {
+                let tree = globals.get_item("tree").unwrap().unwrap();
+                let tree: Py<PyTree> = tree.extract().unwrap();
+                let tree = &tree.get().tree;
+                let vertices = tree
+                    .root()
+                    .descendant_by_path(&["Objects", "Geometry", "Vertices"])
+                    .unwrap();
+                let ptr = match vertices.attribute(0) {
+                    Some(AttributeRef::ArrF64(v)) => v.as_ptr() as usize,
+                    attr => panic!("Unexpected attribute: {:?}", attr),
+                };
+                let vertices_ptr: usize =
+                    globals.get_item("vertices_ptr").unwrap().unwrap().extract().unwrap();
+                assert_eq!(vertices_ptr, ptr);
+            },
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,102 @@
+//! FBX version conversion.
+
+use std::io::{Read, Seek, Write};
+use fbxcel_dom::OwnedAttribute;
+use pull_parser::{Event, Parser, RootParser};
+use crate::binary::{BinaryWriter, Result};
+
+
+/// Rewrites the FBX binary document with the given FBX version, and returns the sink.
+///
+/// This converts node headers between 32-bit (FBX 7.4 and older) and 64-bit (FBX 7.5) offsets,
+/// such as 7500 to 7400 for tools which only accept FBX 7.4 or older.
+/// The version in the header and the footer, and `FBXHeaderExtension/FBXVersion` are updated to
+/// the given version.
+/// Other nodes are copied as is, but arrays are recompressed according to the default
+/// compression threshold of `BinaryWriter`.
+///
+/// Returns `Err(Error::NodeOffsetOverflow { .. })` if the document is too large for 32-bit
+/// offsets, and `Err(Error::UnsupportedVersion(_))` if the version is not supported by
+/// `BinaryWriter`.
+pub fn convert_version<R, W>(source: R, sink: W, version: u32) -> Result<W>
+where
+    R: Read,
+    W: Write + Seek,
+{
+    let mut parser = RootParser::new(source);
+    let mut writer = BinaryWriter::new(sink, version)?;
+    let mut parents = Vec::new();
+    loop {
+        match parser.next_event()? {
+            Event::StartFbx(_) => {},
+            Event::EndFbx(_) => break,
+            Event::StartNode(node) => {
+                let name = node.name_bytes().to_owned();
+                let mut attrs = OwnedAttribute::load_attrs_from_parser_event(node.attributes)?;
+                let is_version_node = name == b"FBXVersion" && parents.len() == 1 &&
+                    parents[0] == b"FBXHeaderExtension";
+                if is_version_node && attrs.len() == 1 {
+                    attrs[0] = OwnedAttribute::I32(version as i32);
+                }
+                writer.start_node_raw(&name, &attrs)?;
+                parents.push(name);
+            },
+            Event::EndNode => {
+                parents.pop();
+                writer.end_node()?;
+            },
+        }
+    }
+    writer.finish()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use fbxcel_dom::GenericNode;
+    use super::*;
+
+    /// Returns the FBX binary with the given version.
+    fn source(version: u32) -> Vec<u8> {
+        let mut writer = BinaryWriter::new(Cursor::new(Vec::new()), version).unwrap();
+        writer.start_node("FBXHeaderExtension", &[]).unwrap();
+        writer.start_node("FBXVersion", &[OwnedAttribute::I32(version as i32)]).unwrap();
+        writer.end_node().unwrap();
+        writer.end_node().unwrap();
+        writer.start_node("Objects", &[]).unwrap();
+        let vertices = OwnedAttribute::from(vec![0.5f64; 256]);
+        writer.start_node("Geometry", &[OwnedAttribute::I64(42), vertices]).unwrap();
+        writer.end_node().unwrap();
+        writer.end_node().unwrap();
+        writer.finish().unwrap().into_inner()
+    }
+
+    /// Loads the nodes and checks the version.
+    fn load(bytes: &[u8], version: u32) -> Vec<GenericNode> {
+        let mut parser = RootParser::new(Cursor::new(bytes));
+        let (nodes, footer) = GenericNode::load_from_parser(&mut parser).unwrap();
+        assert_eq!(parser.fbx_version(), Some(version));
+        assert_eq!(footer.map(|f| f.version), Some(version));
+        assert!(parser.warnings().is_empty());
+        assert_eq!(nodes[0].children[0].attributes, [OwnedAttribute::I32(version as i32)]);
+        nodes
+    }
+
+    #[test]
+    fn convert_both_ways() {
+        let v7500 = source(7500);
+        let v7400 = convert_version(Cursor::new(&v7500), Cursor::new(Vec::new()), 7400)
+            .unwrap()
+            .into_inner();
+        assert_eq!(v7400, source(7400));
+        let nodes = load(&v7400, 7400);
+        assert_eq!(nodes[1], load(&v7500, 7500)[1]);
+
+        let back = convert_version(Cursor::new(&v7400), Cursor::new(Vec::new()), 7500)
+            .unwrap()
+            .into_inner();
+        assert_eq!(back, v7500);
+        assert_eq!(load(&back, 7500)[1], nodes[1]);
+    }
+}
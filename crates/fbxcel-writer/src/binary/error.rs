@@ -0,0 +1,174 @@
+//! FBX binary writing error.
+
+use std::error;
+use std::fmt;
+use std::io;
+use fbxcel_dom::DisplayPath;
+use pull_parser::Error as ParseError;
+
+
+/// Result of writer function.
+pub type Result<T> = ::std::result::Result<T, Error>;
+
+
+/// FBX binary writing error.
+///
+/// Errors about nodes have the slash-separated path to the node, such as
+/// `Objects/Model/Properties70/P[Lcl Translation]`.
+#[derive(Debug)]
+pub enum Error {
+    /// Attribute is pushed while no nodes are accepting attributes.
+    ///
+    /// Attributes should be pushed right after the node is started, before its children.
+    AttributeOutsideNode {
+        /// Path to the innermost open node (empty if no nodes are open).
+        path: String,
+    },
+    /// Attribute value is too large to be written.
+    AttributeTooLarge {
+        /// Path to the node.
+        path: String,
+        /// Index of the attribute.
+        index: usize,
+        /// Length of the value in bytes.
+        len: u64,
+    },
+    /// I/O error.
+    Io(io::Error),
+    /// Node name is too long.
+    NodeNameTooLong {
+        /// Path to the node, including the name.
+        path: String,
+        /// Length of the name in bytes.
+        len: usize,
+    },
+    /// Node offset cannot be represented in the FBX version.
+    NodeOffsetOverflow {
+        /// Path to the node.
+        path: String,
+        /// Position of the node.
+        position: u64,
+        /// FBX version.
+        fbx_version: u32,
+    },
+    /// `end_node()` is called while no nodes are open.
+    NoOpenNode,
+    /// Parser error while copying parser events.
+    Parse(ParseError),
+    /// The writer is finished while some nodes are open.
+    UnclosedNodes {
+        /// Path to the innermost open node.
+        path: String,
+        /// Number of open nodes.
+        num: usize,
+    },
+    /// FBX version is not supported by the writer.
+    UnsupportedVersion(u32),
+}
+
+impl Error {
+    /// Returns the stable numeric code of the error.
+    ///
+    /// Writer error codes are in the range `5000..6000`.
+    /// Codes are never reused nor changed, so they can be used by FFI wrappers and logging
+    /// systems.
+    pub fn code(&self) -> u32 {
+        match *self {
+            Error::AttributeOutsideNode { .. } => 5007,
+            Error::AttributeTooLarge { .. } => 5000,
+            Error::Io(_) => 5001,
+            Error::NodeNameTooLong { .. } => 5002,
+            Error::NodeOffsetOverflow { .. } => 5003,
+            Error::NoOpenNode => 5004,
+            Error::Parse(_) => 5008,
+            Error::UnclosedNodes { .. } => 5005,
+            Error::UnsupportedVersion(_) => 5006,
+        }
+    }
+
+    /// Returns the path to the node the error is about.
+    ///
+    /// Returns `None` for errors not about a specific node.
+    pub fn path(&self) -> Option<&str> {
+        match *self {
+            Error::AttributeOutsideNode { ref path } |
+            Error::AttributeTooLarge { ref path, .. } |
+            Error::NodeNameTooLong { ref path, .. } |
+            Error::NodeOffsetOverflow { ref path, .. } |
+            Error::UnclosedNodes { ref path, .. } => Some(path),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::AttributeOutsideNode { ref path } => {
+                write!(
+                    f,
+                    "Attribute is pushed while no nodes are accepting attributes: node={}",
+                    DisplayPath(path)
+                )
+            },
+            Error::AttributeTooLarge {
+                ref path,
+                index,
+                len,
+            } => {
+                write!(
+                    f,
+                    "Attribute #{} ({} bytes) is too large to be written: node={}",
+                    index,
+                    len,
+                    DisplayPath(path)
+                )
+            },
+            Error::Io(ref err) => write!(f, "I/O error: {}", err),
+            Error::NodeNameTooLong { ref path, len } => {
+                write!(f, "Node name is too long ({} bytes): {}", len, DisplayPath(path))
+            },
+            Error::NodeOffsetOverflow {
+                ref path,
+                position,
+                fbx_version,
+            } => {
+                write!(
+                    f,
+                    "Node at position {} cannot be represented in FBX version {}: node={}",
+                    position,
+                    fbx_version,
+                    DisplayPath(path)
+                )
+            },
+            Error::NoOpenNode => write!(f, "No nodes are open"),
+            Error::Parse(ref err) => write!(f, "Parser error: {}", err),
+            Error::UnclosedNodes { ref path, num } => {
+                write!(f, "{} nodes are not closed: node={}", num, DisplayPath(path))
+            },
+            Error::UnsupportedVersion(ver) => write!(f, "Unsupported FBX version: {}", ver),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Io(ref err) => Some(err),
+            Error::Parse(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Self {
+        Error::Parse(e)
+    }
+}
@@ -0,0 +1,224 @@
+//! Event-based writer.
+
+use std::io::{Seek, Write};
+use fbxcel_dom::{OwnedAttribute, MAX_NODE_NAME_LEN};
+use pull_parser::{Event, ParserSource};
+use crate::binary::{BinaryWriter, Error, Result};
+
+
+/// Owned node event, which can be written by `BinaryWriter::write_events()`.
+///
+/// Unlike parser events, `StartNode` has all the attributes of the node, so the node header can
+/// be written immediately.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OwnedEvent {
+    /// Start of a node.
+    StartNode {
+        /// Node name.
+        name: Vec<u8>,
+        /// Node attributes.
+        attributes: Vec<OwnedAttribute>,
+    },
+    /// End of a node.
+    EndNode,
+}
+
+
+/// Node whose attributes are still being pushed.
+#[derive(Debug, Clone)]
+struct PendingNode {
+    /// Node name.
+    name: Vec<u8>,
+    /// Attributes.
+    attributes: Vec<OwnedAttribute>,
+}
+
+
+/// Low-level writer which accepts nodes and attributes one by one, like parser events.
+///
+/// Attributes of a node are buffered until the first child node or the end of the node, and the
+/// node header is written then.
+/// This is useful to transform a document in a streaming way (read events, modify, and write
+/// events) without loading the whole tree.
+///
+/// ```
+/// # extern crate fbxcel_core;
+/// # extern crate fbxcel_writer;
+/// use std::io::Cursor;
+/// use fbxcel_core::pull_parser::OwnedAttribute;
+/// use fbxcel_writer::binary::{BinaryWriter, EventWriter};
+///
+/// let writer = BinaryWriter::new(Cursor::new(Vec::new()), 7400).unwrap();
+/// let mut writer = EventWriter::new(writer);
+/// writer.start_node("Creator").unwrap();
+/// writer.push_attribute(OwnedAttribute::String(Ok("fbxcel".to_owned()))).unwrap();
+/// writer.end_node().unwrap();
+/// let bytes = writer.finish().unwrap().into_inner();
+/// ```
+#[derive(Debug)]
+pub struct EventWriter<W> {
+    /// Binary writer.
+    writer: BinaryWriter<W>,
+    /// Node whose header is not yet written.
+    pending: Option<PendingNode>,
+}
+
+impl<W: Write + Seek> EventWriter<W> {
+    /// Creates a new `EventWriter`.
+    pub fn new(writer: BinaryWriter<W>) -> Self {
+        EventWriter {
+            writer,
+            pending: None,
+        }
+    }
+
+    /// Returns the number of open nodes, including the node accepting attributes.
+    pub fn num_open_nodes(&self) -> usize {
+        self.writer.num_open_nodes() + self.pending.iter().count()
+    }
+
+    /// Starts a node.
+    ///
+    /// Attributes of the node can be pushed until its first child is started or it is ended.
+    pub fn start_node(&mut self, name: &str) -> Result<()> {
+        self.start_node_raw(name.as_bytes())
+    }
+
+    /// Starts a node with the given name bytes.
+    ///
+    /// The name is written as is, even if it is not valid UTF-8.
+    pub fn start_node_raw(&mut self, name: &[u8]) -> Result<()> {
+        self.flush_pending()?;
+        if name.len() > MAX_NODE_NAME_LEN {
+            return Err(Error::NodeNameTooLong {
+                path: self.writer.child_path(&String::from_utf8_lossy(name)),
+                len: name.len(),
+            });
+        }
+        self.pending = Some(PendingNode {
+            name: name.to_owned(),
+            attributes: Vec::new(),
+        });
+        Ok(())
+    }
+
+    /// Pushes an attribute of the current node.
+    ///
+    /// Returns `Err(Error::AttributeOutsideNode { .. })` if no nodes are started, or if the
+    /// current node already has children.
+    pub fn push_attribute(&mut self, attr: OwnedAttribute) -> Result<()> {
+        match self.pending {
+            Some(ref mut node) => {
+                node.attributes.push(attr);
+                Ok(())
+            },
+            None => Err(Error::AttributeOutsideNode {
+                path: self.writer.path(),
+            }),
+        }
+    }
+
+    /// Ends the current node.
+    pub fn end_node(&mut self) -> Result<()> {
+        self.flush_pending()?;
+        self.writer.end_node()
+    }
+
+    /// Writes the parser event.
+    ///
+    /// `StartFbx` and `EndFbx` are ignored, since the version is given to the `BinaryWriter` and
+    /// the footer is written by `finish()`.
+    pub fn write_event<R: ParserSource>(&mut self, event: Event<R>) -> Result<()> {
+        match event {
+            Event::StartFbx(_) | Event::EndFbx(_) => Ok(()),
+            Event::StartNode(node) => {
+                self.start_node_raw(node.name_bytes())?;
+                let attrs = OwnedAttribute::load_attrs_from_parser_event(node.attributes)?;
+                for attr in attrs {
+                    self.push_attribute(attr)?;
+                }
+                Ok(())
+            },
+            Event::EndNode => self.end_node(),
+        }
+    }
+
+    /// Returns the binary writer.
+    ///
+    /// Returns an error if the header of the pending node cannot be written.
+    pub fn into_inner(mut self) -> Result<BinaryWriter<W>> {
+        self.flush_pending()?;
+        Ok(self.writer)
+    }
+
+    /// Writes the FBX footer, and returns the sink.
+    pub fn finish(self) -> Result<W> {
+        self.into_inner()?.finish()
+    }
+
+    /// Writes the header of the pending node.
+    fn flush_pending(&mut self) -> Result<()> {
+        match self.pending.take() {
+            Some(node) => self.writer.start_node_raw(&node.name, &node.attributes),
+            None => Ok(()),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use fbxcel_dom::GenericNode;
+    use pull_parser::{Event, Parser, RootParser};
+    use pull_parser::ascii::AsciiDocument;
+    use crate::binary::{BinaryWriter, Error};
+    use super::*;
+
+    #[test]
+    fn copy_events() {
+        let source = "; FBX 7.4.0 project file\n\
+                      Objects:  {\n\tModel: 1, \"Model::Cube\", \"Mesh\" {\n\
+                      \t\tVersion: 232\n\t}\n}\n";
+        let bytes = AsciiDocument::parse(source).unwrap().to_binary().unwrap();
+
+        let mut parser = RootParser::new(Cursor::new(&bytes));
+        let sink = BinaryWriter::new(Cursor::new(Vec::new()), 7400).unwrap();
+        let mut writer = EventWriter::new(sink);
+        loop {
+            let event = parser.next_event().unwrap();
+            let is_end = matches!(event, Event::EndFbx(_));
+            writer.write_event(event).unwrap();
+            if is_end {
+                break;
+            }
+        }
+        let copied = writer.finish().unwrap().into_inner();
+
+        let load = |bytes: &[u8]| {
+            GenericNode::load_from_parser(&mut RootParser::new(Cursor::new(bytes))).unwrap().0
+        };
+        assert_eq!(load(&bytes), load(&copied));
+    }
+
+    #[test]
+    fn attribute_after_child() {
+        let sink = BinaryWriter::new(Cursor::new(Vec::new()), 7400).unwrap();
+        let mut writer = EventWriter::new(sink);
+        assert!(matches!(
+            writer.push_attribute(OwnedAttribute::I32(1)),
+            Err(Error::AttributeOutsideNode { ref path }) if path.is_empty()
+        ));
+        writer.start_node("Parent").unwrap();
+        writer.push_attribute(OwnedAttribute::I32(1)).unwrap();
+        writer.start_node("Child").unwrap();
+        writer.end_node().unwrap();
+        assert_eq!(writer.num_open_nodes(), 1);
+        assert!(matches!(
+            writer.push_attribute(OwnedAttribute::I32(2)),
+            Err(Error::AttributeOutsideNode { ref path }) if path == "Parent"
+        ));
+        writer.end_node().unwrap();
+        assert!(matches!(writer.end_node(), Err(Error::NoOpenNode)));
+    }
+}
@@ -0,0 +1,893 @@
+//! FBX binary writer.
+//!
+//! `BinaryWriter` emits FBX 7.4 and 7.5 binary files, which can be read by
+//! `pull_parser::RootParser`.
+//!
+//! ```
+//! # extern crate fbxcel_core;
+//! # extern crate fbxcel_writer;
+//! use std::io::Cursor;
+//! use fbxcel_core::pull_parser::OwnedAttribute;
+//! use fbxcel_writer::binary::BinaryWriter;
+//!
+//! let mut writer = BinaryWriter::new(Cursor::new(Vec::new()), 7400).unwrap();
+//! writer.start_node("Creator", &[OwnedAttribute::String(Ok("fbxcel".to_owned()))]).unwrap();
+//! writer.end_node().unwrap();
+//! let bytes = writer.finish().unwrap().into_inner();
+//! ```
+//!
+//! `EventWriter` accepts nodes and attributes one by one, and can write parser events as is.
+//! `BinaryWriter::write_events()` writes nodes from an iterator of `OwnedEvent`s, so generated
+//! scenes can be streamed without building the whole tree.
+//! `convert_version()` rewrites documents between FBX 7.4 and 7.5.
+//! `BinaryWriter::write_documents()` writes files with several documents.
+
+use std::io::{self, Seek, SeekFrom, Write};
+#[cfg(feature = "flate2")]
+use flate2::{Compression, write::ZlibEncoder};
+#[cfg(all(feature = "libflate", not(feature = "flate2")))]
+use libflate::zlib;
+use fbxcel_dom::{GenericNode, OwnedAttribute, MAX_NODE_NAME_LEN};
+use fbxcel_dom::fbx7400::{DocumentInfo, Documents};
+use fbxcel_dom::generic::path_segment;
+
+pub use self::convert::convert_version;
+pub use self::error::{Error, Result};
+pub use self::event::{EventWriter, OwnedEvent};
+pub use self::report::{NodeSizeReport, WriteReport};
+
+mod convert;
+mod error;
+mod event;
+mod report;
+
+
+/// Magic binary at the head of FBX binary files.
+const MAGIC: &[u8; 21] = b"Kaydara FBX Binary  \x00";
+
+/// Unknown part 1 of the footer.
+///
+/// The official SDK derives this from the creation time stamp of the file. Neither the SDK nor
+/// `pull_parser` validates it, so this is a fixed constant taken from an SDK-written file, rather
+/// than derived from `FBXHeaderExtension/CreationTimeStamp`.
+const FOOTER_UNKNOWN1: [u8; 16] = [
+    0xfa, 0xbc, 0xab, 0x09, 0xd0, 0xc8, 0xd4, 0x66, 0xb1, 0x76, 0xfb, 0x83, 0x1c, 0xf7, 0x26, 0x7e,
+];
+
+/// Unknown part 2 of the footer, which is written by the official SDK.
+const FOOTER_UNKNOWN2: [u8; 16] = [
+    0xf8, 0x5a, 0x8c, 0x6a, 0xde, 0xf5, 0xd9, 0x7e, 0xec, 0xe9, 0x0c, 0xe3, 0x75, 0x8f, 0x29, 0x0b,
+];
+
+/// Default minimum byte length of arrays to be compressed.
+pub const DEFAULT_COMPRESSION_THRESHOLD: usize = 128;
+
+
+/// Policy of null node records at the end of nodes.
+///
+/// The parser accepts all of them, but some importers expect specific conventions.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NullRecordPolicy {
+    /// Writes null records for nodes with children or without attributes.
+    ///
+    /// This is the convention of the official SDK (and Blender's exporter).
+    #[default]
+    Sdk,
+    /// Writes null records only for nodes with children.
+    ChildrenOnly,
+    /// Writes null records for all nodes.
+    Always,
+}
+
+
+/// Information about an open node.
+#[derive(Debug, Clone)]
+struct OpenNode {
+    /// Path segment of the node, used in error messages.
+    segment: String,
+    /// Position of the node header.
+    header_pos: u64,
+    /// Whether the node has attributes.
+    has_attributes: bool,
+    /// Whether the node has children.
+    has_children: bool,
+}
+
+
+/// FBX binary writer.
+///
+/// Node end offsets are patched by seeking back to the node headers.
+#[derive(Debug)]
+pub struct BinaryWriter<W> {
+    /// Sink.
+    sink: W,
+    /// Position of the file head in the sink.
+    base: u64,
+    /// Current position relative to the file head.
+    pos: u64,
+    /// FBX version.
+    version: u32,
+    /// Open nodes.
+    open_nodes: Vec<OpenNode>,
+    /// Minimum byte length of arrays to be compressed.
+    compression_threshold: Option<usize>,
+    /// Size report.
+    report: WriteReport,
+    /// Policy of null node records.
+    null_record_policy: NullRecordPolicy,
+    /// Whether `write_node()` omits empty nodes.
+    omit_empty_nodes: bool,
+}
+
+impl<W: Write + Seek> BinaryWriter<W> {
+    /// Creates a new `BinaryWriter` and writes the FBX header.
+    ///
+    /// Supported versions are `6000..7600`.
+    /// FBX 6.x and 7.4 share the binary layout, and 7.5 uses 64-bit node headers.
+    pub fn new(mut sink: W, version: u32) -> Result<Self> {
+        if !(6000..7600).contains(&version) {
+            return Err(Error::UnsupportedVersion(version));
+        }
+        let base = sink.stream_position()?;
+        let mut writer = BinaryWriter {
+            sink,
+            base,
+            pos: 0,
+            version,
+            open_nodes: Vec::new(),
+            compression_threshold: Some(DEFAULT_COMPRESSION_THRESHOLD),
+            report: WriteReport::default(),
+            null_record_policy: NullRecordPolicy::default(),
+            omit_empty_nodes: false,
+        };
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[0x1a, 0x00])?;
+        writer.write_all(&version.to_le_bytes())?;
+        Ok(writer)
+    }
+
+    /// Returns the FBX version.
+    pub fn fbx_version(&self) -> u32 {
+        self.version
+    }
+
+    /// Returns the number of open nodes.
+    pub fn num_open_nodes(&self) -> usize {
+        self.open_nodes.len()
+    }
+
+    /// Sets the minimum byte length of arrays to be zlib-compressed.
+    ///
+    /// `None` disables compression.
+    /// Arrays are written uncompressed if neither `flate2` nor `libflate` feature is enabled, or
+    /// if compression doesn't make them smaller.
+    pub fn set_compression_threshold(&mut self, threshold: Option<usize>) {
+        self.compression_threshold = threshold;
+    }
+
+    /// Sets the policy of null node records written at the end of nodes.
+    pub fn set_null_record_policy(&mut self, policy: NullRecordPolicy) {
+        self.null_record_policy = policy;
+    }
+
+    /// Sets whether `write_node()` omits empty nodes.
+    ///
+    /// A node is empty if it has no attributes and all of its children (if any) are empty.
+    /// Note that this also omits nodes which some importers require even if empty (such as
+    /// `Objects`), so use this only with documents which don't have such nodes empty.
+    /// Nodes written by `start_node()` and `end_node()` are never omitted.
+    pub fn set_omit_empty_nodes(&mut self, omit: bool) {
+        self.omit_empty_nodes = omit;
+    }
+
+    /// Returns the size report of the nodes written so far.
+    pub fn report(&self) -> &WriteReport {
+        &self.report
+    }
+
+    /// Starts a node with the given attributes.
+    pub fn start_node(&mut self, name: &str, attributes: &[OwnedAttribute]) -> Result<()> {
+        self.start_node_raw(name.as_bytes(), attributes)
+    }
+
+    /// Starts a node with the given name bytes and attributes.
+    ///
+    /// The name is written as is, even if it is not valid UTF-8.
+    pub fn start_node_raw(&mut self, name: &[u8], attributes: &[OwnedAttribute]) -> Result<()> {
+        let segment = path_segment(name, attributes);
+        if name.len() > MAX_NODE_NAME_LEN {
+            return Err(Error::NodeNameTooLong {
+                path: self.child_path(&segment),
+                len: name.len(),
+            });
+        }
+        if self.version < 7500 && self.pos > u64::from(u32::MAX) {
+            return Err(Error::NodeOffsetOverflow {
+                path: self.child_path(&segment),
+                position: self.pos,
+                fbx_version: self.version,
+            });
+        }
+        for (index, attr) in attributes.iter().enumerate() {
+            let len = attr.payload_len();
+            if len > u64::from(u32::MAX) {
+                return Err(Error::AttributeTooLarge {
+                    path: self.child_path(&segment),
+                    index,
+                    len,
+                });
+            }
+        }
+        if self.open_nodes.is_empty() {
+            self.report.nodes.push(NodeSizeReport {
+                name: String::from_utf8_lossy(name).into_owned(),
+                ..Default::default()
+            });
+        }
+        let mut attrs_buf = Vec::new();
+        for attr in attributes {
+            self.encode_attribute(&mut attrs_buf, attr)?;
+        }
+
+        if let Some(parent) = self.open_nodes.last_mut() {
+            parent.has_children = true;
+        }
+        self.open_nodes.push(OpenNode {
+            segment,
+            header_pos: self.pos,
+            has_attributes: !attributes.is_empty(),
+            has_children: false,
+        });
+        // End offset is patched by `end_node()`.
+        self.write_header_value(0)?;
+        self.write_header_value(attributes.len() as u64)?;
+        self.write_header_value(attrs_buf.len() as u64)?;
+        self.write_all(&[name.len() as u8])?;
+        self.write_all(name)?;
+        self.write_all(&attrs_buf)
+    }
+
+    /// Ends the most recent open node.
+    ///
+    /// Null node records are written according to the null record policy (by default, for nodes
+    /// with children or without attributes like the official SDK).
+    pub fn end_node(&mut self) -> Result<()> {
+        let path = self.path();
+        let node = self.open_nodes.pop().ok_or(Error::NoOpenNode)?;
+        let write_null = match self.null_record_policy {
+            NullRecordPolicy::Sdk => node.has_children || !node.has_attributes,
+            NullRecordPolicy::ChildrenOnly => node.has_children,
+            NullRecordPolicy::Always => true,
+        };
+        if write_null {
+            self.write_null_record()?;
+        }
+        let end = self.pos;
+        if self.version < 7500 && end > u64::from(u32::MAX) {
+            return Err(Error::NodeOffsetOverflow {
+                path,
+                position: node.header_pos,
+                fbx_version: self.version,
+            });
+        }
+        self.sink.seek(SeekFrom::Start(self.base + node.header_pos))?;
+        self.write_header_value(end)?;
+        self.pos = end;
+        self.sink.seek(SeekFrom::Start(self.base + end))?;
+        if self.open_nodes.is_empty() {
+            if let Some(report) = self.report.nodes.last_mut() {
+                report.size = end - node.header_pos;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the node and its descendants.
+    ///
+    /// If omitting empty nodes is enabled, empty nodes are not written.
+    pub fn write_node(&mut self, node: &GenericNode) -> Result<()> {
+        if self.omit_empty_nodes && is_empty_node(node) {
+            return Ok(());
+        }
+        self.start_node_raw(&node.name, &node.attributes)?;
+        for child in &node.children {
+            self.write_node(child)?;
+        }
+        self.end_node()
+    }
+
+    /// Writes the `Documents` node with the given `Document` entries.
+    ///
+    /// Each document has its own root node, which objects of the document are connected to (see
+    /// `Fbx7400::document_of()`).
+    pub fn write_documents(&mut self, documents: &[DocumentInfo]) -> Result<()> {
+        self.write_node(&Documents::from_documents(documents).to_node())
+    }
+
+    /// Writes the nodes from the events.
+    ///
+    /// Events are written one by one, so the events can be generated lazily without keeping the
+    /// whole tree in memory.
+    /// Nodes may be left open at the end of the events, to be closed by later calls.
+    pub fn write_events<I>(&mut self, events: I) -> Result<()>
+    where
+        I: IntoIterator<Item = OwnedEvent>,
+    {
+        for event in events {
+            match event {
+                OwnedEvent::StartNode { name, attributes } => {
+                    self.start_node_raw(&name, &attributes)?;
+                },
+                OwnedEvent::EndNode => self.end_node()?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the end of the implicit root node and the FBX footer, and returns the sink.
+    pub fn finish(self) -> Result<W> {
+        self.finish_with_report().map(|(sink, _)| sink)
+    }
+
+    /// Writes the end of the implicit root node and the FBX footer, and returns the sink and the
+    /// size report.
+    pub fn finish_with_report(mut self) -> Result<(W, WriteReport)> {
+        if !self.open_nodes.is_empty() {
+            return Err(Error::UnclosedNodes {
+                path: self.path(),
+                num: self.open_nodes.len(),
+            });
+        }
+        self.write_null_record()?;
+        self.write_all(&FOOTER_UNKNOWN1)?;
+        let padding_len = ((16 - (self.pos & 0x0f)) & 0x0f) as usize;
+        self.write_all(&vec![0; padding_len + 4])?;
+        let version = self.version;
+        self.write_all(&version.to_le_bytes())?;
+        self.write_all(&[0; 120])?;
+        self.write_all(&FOOTER_UNKNOWN2)?;
+        self.sink.flush()?;
+        self.report.file_size = self.pos;
+        Ok((self.sink, self.report))
+    }
+
+    /// Returns the path to the innermost open node.
+    fn path(&self) -> String {
+        let segments = self.open_nodes.iter().map(|node| &node.segment[..]);
+        segments.collect::<Vec<_>>().join("/")
+    }
+
+    /// Returns the path to the child node of the innermost open node.
+    fn child_path(&self, segment: &str) -> String {
+        if self.open_nodes.is_empty() {
+            segment.to_owned()
+        } else {
+            format!("{}/{}", self.path(), segment)
+        }
+    }
+
+    /// Writes the bytes.
+    fn write_all(&mut self, bytes: &[u8]) -> Result<()> {
+        self.sink.write_all(bytes)?;
+        self.pos += bytes.len() as u64;
+        Ok(())
+    }
+
+    /// Writes an offset or a length in a node header.
+    fn write_header_value(&mut self, v: u64) -> Result<()> {
+        if self.version >= 7500 {
+            self.write_all(&v.to_le_bytes())
+        } else {
+            self.write_all(&(v as u32).to_le_bytes())
+        }
+    }
+
+    /// Writes a null node record.
+    fn write_null_record(&mut self) -> Result<()> {
+        let len = if self.version >= 7500 { 25 } else { 13 };
+        self.write_all(&vec![0; len])
+    }
+
+    /// Encodes an attribute into the buffer.
+    ///
+    /// The attribute should be checked not to be too large beforehand.
+    fn encode_attribute(&mut self, buf: &mut Vec<u8>, attr: &OwnedAttribute) -> Result<()> {
+        match *attr {
+            OwnedAttribute::Bool(v) => {
+                buf.push(b'C');
+                buf.push(if v { b'Y' } else { b'T' });
+            },
+            OwnedAttribute::I16(v) => primitive(buf, b'Y', &v.to_le_bytes()),
+            OwnedAttribute::I32(v) => primitive(buf, b'I', &v.to_le_bytes()),
+            OwnedAttribute::I64(v) => primitive(buf, b'L', &v.to_le_bytes()),
+            OwnedAttribute::F32(v) => primitive(buf, b'F', &v.to_le_bytes()),
+            OwnedAttribute::F64(v) => primitive(buf, b'D', &v.to_le_bytes()),
+            OwnedAttribute::ArrBool(ref v) => {
+                let bytes = v.iter().map(|&v| v as u8).collect::<Vec<_>>();
+                self.encode_array(buf, b'b', v.len(), &bytes)?;
+            },
+            OwnedAttribute::ArrI32(ref v) => {
+                let bytes = v.iter().flat_map(|v| v.to_le_bytes()).collect::<Vec<_>>();
+                self.encode_array(buf, b'i', v.len(), &bytes)?;
+            },
+            OwnedAttribute::ArrI64(ref v) => {
+                let bytes = v.iter().flat_map(|v| v.to_le_bytes()).collect::<Vec<_>>();
+                self.encode_array(buf, b'l', v.len(), &bytes)?;
+            },
+            OwnedAttribute::ArrF32(ref v) => {
+                let bytes = v.iter().flat_map(|v| v.to_le_bytes()).collect::<Vec<_>>();
+                self.encode_array(buf, b'f', v.len(), &bytes)?;
+            },
+            OwnedAttribute::ArrF64(ref v) => {
+                let bytes = v.iter().flat_map(|v| v.to_le_bytes()).collect::<Vec<_>>();
+                self.encode_array(buf, b'd', v.len(), &bytes)?;
+            },
+            OwnedAttribute::String(Ok(ref v)) => special(buf, b'S', v.as_bytes()),
+            OwnedAttribute::String(Err(ref v)) => special(buf, b'S', v),
+            OwnedAttribute::Binary(ref v) => {
+                special(buf, b'R', v);
+                if let Some(report) = self.report.nodes.last_mut() {
+                    report.binary_bytes += v.len() as u64;
+                }
+            },
+        }
+        Ok(())
+    }
+
+    /// Encodes an array attribute into the buffer, compressing it if appropriate.
+    fn encode_array(
+        &mut self,
+        buf: &mut Vec<u8>,
+        type_code: u8,
+        num_elements: usize,
+        bytes: &[u8],
+    ) -> Result<()> {
+        let compressed = match self.compression_threshold {
+            Some(threshold) if bytes.len() >= threshold => {
+                zlib_compress(bytes)?.filter(|c| c.len() < bytes.len())
+            },
+            _ => None,
+        };
+        let (encoding, payload) = match compressed {
+            Some(ref c) => (1u32, &c[..]),
+            None => (0u32, bytes),
+        };
+        buf.push(type_code);
+        buf.extend_from_slice(&(num_elements as u32).to_le_bytes());
+        buf.extend_from_slice(&encoding.to_le_bytes());
+        buf.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        buf.extend_from_slice(payload);
+        if let Some(report) = self.report.nodes.last_mut() {
+            report.array_raw_bytes += bytes.len() as u64;
+            report.array_written_bytes += payload.len() as u64;
+        }
+        Ok(())
+    }
+}
+
+
+/// Encodes a primitive attribute into the buffer.
+fn primitive(buf: &mut Vec<u8>, type_code: u8, bytes: &[u8]) {
+    buf.push(type_code);
+    buf.extend_from_slice(bytes);
+}
+
+
+/// Encodes a special (string or binary) attribute into the buffer.
+fn special(buf: &mut Vec<u8>, type_code: u8, bytes: &[u8]) {
+    buf.push(type_code);
+    buf.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    buf.extend_from_slice(bytes);
+}
+
+
+/// Compresses the bytes as a zlib stream.
+///
+/// Returns `Ok(None)` if no compression backend is enabled.
+#[cfg(feature = "flate2")]
+fn zlib_compress(bytes: &[u8]) -> io::Result<Option<Vec<u8>>> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::Default);
+    encoder.write_all(bytes)?;
+    encoder.finish().map(Some)
+}
+
+
+/// Compresses the bytes as a zlib stream.
+///
+/// Returns `Ok(None)` if no compression backend is enabled.
+#[cfg(all(feature = "libflate", not(feature = "flate2")))]
+fn zlib_compress(bytes: &[u8]) -> io::Result<Option<Vec<u8>>> {
+    let mut encoder = zlib::Encoder::new(Vec::new())?;
+    encoder.write_all(bytes)?;
+    encoder.finish().into_result().map(Some)
+}
+
+
+/// Compresses the bytes as a zlib stream.
+///
+/// Returns `Ok(None)` if no compression backend is enabled.
+#[cfg(not(any(feature = "flate2", feature = "libflate")))]
+fn zlib_compress(_bytes: &[u8]) -> io::Result<Option<Vec<u8>>> {
+    Ok(None)
+}
+
+
+/// Returns whether the node has no attributes and no non-empty children.
+fn is_empty_node(node: &GenericNode) -> bool {
+    node.attributes.is_empty() && node.children.iter().all(is_empty_node)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use pull_parser::RootParser;
+    use pull_parser::ascii::AsciiDocument;
+    use super::*;
+
+    fn node(
+        name: &str,
+        attributes: Vec<OwnedAttribute>,
+        children: Vec<GenericNode>,
+    ) -> GenericNode {
+        GenericNode {
+            name: name.into(),
+            attributes,
+            children,
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        for &version in &[7400, 7500] {
+            let nodes = vec![
+                node(
+                    "Objects",
+                    vec![],
+                    vec![
+                        node(
+                            "Geometry",
+                            vec![
+                                OwnedAttribute::I64(42),
+                                OwnedAttribute::String(Ok("Cube\u{0}\u{1}Geometry".to_owned())),
+                                OwnedAttribute::Bool(true),
+                            ],
+                            vec![
+                                node(
+                                    "Vertices",
+                                    vec![OwnedAttribute::ArrF64(vec![0.5; 256].into())],
+                                    vec![],
+                                ),
+                                node("Empty", vec![], vec![]),
+                            ],
+                        ),
+                    ],
+                ),
+                node("Content", vec![OwnedAttribute::Binary(vec![1, 2, 3].into())], vec![]),
+            ];
+            let mut writer = BinaryWriter::new(Cursor::new(Vec::new()), version).unwrap();
+            for node in &nodes {
+                writer.write_node(node).unwrap();
+            }
+            let (sink, report) = writer.finish_with_report().unwrap();
+            let bytes = sink.into_inner();
+            assert_eq!(report.file_size, bytes.len() as u64);
+            assert_eq!(report.nodes.len(), 2);
+            assert_eq!(report.nodes[0].array_raw_bytes, 256 * 8);
+            assert_eq!(report.nodes[1].binary_bytes, 3);
+            assert_eq!(report.nodes[1].compression_ratio(), None);
+            assert_eq!(report.largest_nodes()[0].name, "Objects");
+
+            let mut parser = RootParser::new(Cursor::new(bytes));
+            let (loaded, footer) = GenericNode::load_from_parser(&mut parser).unwrap();
+            assert_eq!(loaded, nodes);
+            assert_eq!(footer.map(|f| f.version), Some(version));
+            assert!(parser.warnings().is_empty());
+        }
+    }
+
+    #[test]
+    fn null_record_policy_and_empty_nodes() {
+        let nodes = vec![
+            node("A", vec![OwnedAttribute::I32(1)], vec![node("B", vec![], vec![])]),
+            node("C", vec![OwnedAttribute::I32(2)], vec![]),
+        ];
+        let write = |policy: NullRecordPolicy, omit: bool| {
+            let mut writer = BinaryWriter::new(Cursor::new(Vec::new()), 7400).unwrap();
+            writer.set_null_record_policy(policy);
+            writer.set_omit_empty_nodes(omit);
+            for node in &nodes {
+                writer.write_node(node).unwrap();
+            }
+            let (sink, report) = writer.finish_with_report().unwrap();
+            let mut parser = RootParser::new(Cursor::new(sink.into_inner()));
+            let (loaded, _) = GenericNode::load_from_parser(&mut parser).unwrap();
+            assert!(parser.warnings().is_empty());
+            // Footer has padding, so compare the sizes of nodes.
+            (report.nodes.iter().map(|n| n.size).sum::<u64>(), loaded)
+        };
+        let (sdk_len, sdk_loaded) = write(NullRecordPolicy::Sdk, false);
+        assert_eq!(sdk_loaded, nodes);
+        // `A` and `B` have null records, and `C` does not.
+        let (always_len, always_loaded) = write(NullRecordPolicy::Always, false);
+        assert_eq!(always_loaded, nodes);
+        assert_eq!(always_len, sdk_len + 13);
+        let (children_len, _) = write(NullRecordPolicy::ChildrenOnly, false);
+        assert_eq!(children_len, sdk_len - 13);
+
+        let (_, omitted) = write(NullRecordPolicy::Sdk, true);
+        assert_eq!(omitted[0].children, vec![]);
+        assert_eq!(omitted.len(), 2);
+    }
+
+    #[test]
+    fn error_path() {
+        let mut writer = BinaryWriter::new(Cursor::new(Vec::new()), 7400).unwrap();
+        writer.start_node("Objects", &[]).unwrap();
+        let model = [
+            OwnedAttribute::I64(42),
+            OwnedAttribute::String(Ok("Cube\u{0}\u{1}Model".to_owned())),
+        ];
+        writer.start_node("Model", &model).unwrap();
+        writer.start_node("Properties70", &[]).unwrap();
+        let prop = [OwnedAttribute::String(Ok("Lcl Translation".to_owned()))];
+        writer.start_node("P", &prop).unwrap();
+
+        let parent = "Objects/Model/Properties70/P[Lcl Translation]";
+        let name = "x".repeat(MAX_NODE_NAME_LEN + 1);
+        match writer.start_node(&name, &[]) {
+            Err(Error::NodeNameTooLong { ref path, len }) => {
+                assert_eq!(*path, format!("{}/{}", parent, name));
+                assert_eq!(len, MAX_NODE_NAME_LEN + 1);
+            },
+            res => panic!("Unexpected result: {:?}", res),
+        }
+        match writer.finish() {
+            Err(err) => {
+                assert_eq!(err.path(), Some(parent));
+                assert!(matches!(err, Error::UnclosedNodes { num: 4, .. }));
+                assert!(err.to_string().ends_with(&format!("node={}", parent)));
+            },
+            res => panic!("Unexpected result: {:?}", res.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn non_utf8_name_round_trip() {
+        use fbxcel_dom::tree::Tree;
+        use pull_parser::{Event, Parser};
+
+        let name = b"Bad\xff\xfeName";
+        let mut writer = BinaryWriter::new(Cursor::new(Vec::new()), 7400).unwrap();
+        writer.start_node("Objects", &[]).unwrap();
+        writer.start_node_raw(name, &[OwnedAttribute::I32(1)]).unwrap();
+        writer.end_node().unwrap();
+        writer.end_node().unwrap();
+        let bytes = writer.finish().unwrap().into_inner();
+
+        let (nodes, _) =
+            GenericNode::load_from_parser(&mut RootParser::new(Cursor::new(&bytes))).unwrap();
+        let child = &nodes[0].children[0];
+        assert_eq!(child.name, name);
+        assert_eq!(child.name_str(), None);
+        let mut writer = BinaryWriter::new(Cursor::new(Vec::new()), 7400).unwrap();
+        writer.write_node(&nodes[0]).unwrap();
+        assert_eq!(writer.finish().unwrap().into_inner(), bytes);
+
+        let tree = Tree::load(RootParser::new(Cursor::new(&bytes))).unwrap();
+        assert_eq!(tree.to_generic_nodes(tree.root().id()), nodes);
+
+        let mut parser = RootParser::new(Cursor::new(&bytes));
+        let sink = BinaryWriter::new(Cursor::new(Vec::new()), 7400).unwrap();
+        let mut writer = EventWriter::new(sink);
+        loop {
+            let event = parser.next_event().unwrap();
+            let is_end = matches!(event, Event::EndFbx(_));
+            writer.write_event(event).unwrap();
+            if is_end {
+                break;
+            }
+        }
+        assert_eq!(writer.finish().unwrap().into_inner(), bytes);
+    }
+
+    #[test]
+    fn multiple_documents_round_trip() {
+        use fbxcel_dom::FbxLoader;
+        use fbxcel_dom::fbx7400::GenericObjectsLoader;
+
+        let header = r#"; FBX 7.4.0 project file
+FBXHeaderExtension:  {
+	FBXHeaderVersion: 1003
+	FBXVersion: 7400
+	EncryptionType: 0
+	CreationTimeStamp:  {
+		Version: 1000
+		Year: 2020
+		Month: 1
+		Day: 1
+		Hour: 0
+		Minute: 0
+		Second: 0
+		Millisecond: 0
+	}
+	Creator: "test"
+	SceneInfo: "SceneInfo::GlobalInfo", "UserData" {
+		Type: "UserData"
+		Version: 100
+		MetaData:  {
+			Version: 100
+			Title: ""
+			Subject: ""
+			Author: ""
+			Keywords: ""
+			Revision: ""
+			Comment: ""
+		}
+		Properties70:  {
+		}
+	}
+}
+FileId: "id"
+CreationTime: "2020-01-01 00:00:00:000"
+Creator: "test"
+GlobalSettings:  {
+	Version: 1000
+	Properties70:  {
+	}
+}
+"#;
+        let rest = r#"; FBX 7.4.0 project file
+References:  {
+}
+Definitions:  {
+	Version: 100
+	Count: 0
+}
+Objects:  {
+	Model: 1, "Model::A", "Null" {
+	}
+	Model: 2, "Model::A_child", "Mesh" {
+	}
+	Model: 20, "Model::B", "Null" {
+	}
+	Model: 21, "Model::B_child", "Mesh" {
+	}
+	Model: 99, "Model::Orphan", "Null" {
+	}
+}
+Connections:  {
+	C: "OO",1,0
+	C: "OO",2,1
+	C: "OO",21,20
+}
+"#;
+        let documents = vec![
+            DocumentInfo {
+                id: 100,
+                name: "SceneA".to_owned(),
+                root_node: 0,
+            },
+            DocumentInfo {
+                id: 200,
+                name: "SceneB".to_owned(),
+                root_node: 20,
+            },
+        ];
+        let mut writer = BinaryWriter::new(Cursor::new(Vec::new()), 7400).unwrap();
+        let header = AsciiDocument::parse(header).unwrap();
+        for node in GenericNode::from_ascii_nodes(&header.nodes, "") {
+            writer.write_node(&node).unwrap();
+        }
+        writer.write_documents(&documents).unwrap();
+        let rest = AsciiDocument::parse(rest).unwrap();
+        for node in GenericNode::from_ascii_nodes(&rest.nodes, "") {
+            writer.write_node(&node).unwrap();
+        }
+        let bytes = writer.finish().unwrap().into_inner();
+
+        let fbx = match FbxLoader::load_from_parser(RootParser::new(Cursor::new(&bytes))).unwrap() {
+            FbxLoader::Fbx7400(loader) => loader.load(GenericObjectsLoader::new()).unwrap(),
+            loader => panic!("Unexpected loader for {}", loader.fbx_version()),
+        };
+        assert_eq!(fbx.documents.documents(), documents);
+        assert_eq!(fbx.documents.nodes[0].attributes, vec![OwnedAttribute::I32(2)]);
+        assert_eq!(fbx.document_of(1), Some(100));
+        assert_eq!(fbx.document_of(2), Some(100));
+        assert_eq!(fbx.document_of(20), Some(200));
+        assert_eq!(fbx.document_of(21), Some(200));
+        assert_eq!(fbx.document_of(99), None);
+    }
+
+    #[test]
+    fn write_events() {
+        // Generates `Objects` with geometries of terrain tiles, without building the tree.
+        let start = |name: &str, attributes: Vec<OwnedAttribute>| OwnedEvent::StartNode {
+            name: name.into(),
+            attributes,
+        };
+        let tile = |i: i64| {
+            let vertices = (0..12).map(|v| (i * 12 + v) as f64).collect::<Vec<_>>();
+            vec![
+                start("Geometry", vec![OwnedAttribute::I64(i), OwnedAttribute::from("Mesh")]),
+                start("Vertices", vec![OwnedAttribute::from(vertices)]),
+                OwnedEvent::EndNode,
+                OwnedEvent::EndNode,
+            ]
+        };
+        let events = ::std::iter::once(start("Objects", vec![]))
+            .chain((0..16).flat_map(tile))
+            .chain(::std::iter::once(OwnedEvent::EndNode));
+        let mut writer = BinaryWriter::new(Cursor::new(Vec::new()), 7500).unwrap();
+        writer.write_events(events).unwrap();
+        let bytes = writer.finish().unwrap().into_inner();
+
+        let (nodes, _) =
+            GenericNode::load_from_parser(&mut RootParser::new(Cursor::new(&bytes))).unwrap();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].children.len(), 16);
+        let last = &nodes[0].children[15];
+        assert_eq!(last.attributes[0], OwnedAttribute::I64(15));
+        match last.children[0].attributes[0] {
+            OwnedAttribute::ArrF64(ref v) => assert_eq!(v[0], 180.0),
+            ref attr => panic!("Unexpected attribute: {:?}", attr),
+        }
+        // Same as writing the loaded tree.
+        let mut writer = BinaryWriter::new(Cursor::new(Vec::new()), 7500).unwrap();
+        writer.write_node(&nodes[0]).unwrap();
+        assert_eq!(writer.finish().unwrap().into_inner(), bytes);
+    }
+
+    #[test]
+    fn ascii_encoding_matches_writer() {
+        let source = "; FBX 7.5.0 project file\n\
+                      Objects: {\n\
+                      \tGeometry: 1, \"Geometry::Cube\", \"Mesh\" {\n\
+                      \t\tVertices: *3 {\n\t\t\ta: 0,1.5,2\n\t\t}\n\
+                      \t\tGeometryVersion: 124\n\
+                      \t}\n\
+                      \tEmpty: {\n\t}\n\
+                      }\n";
+        let doc = AsciiDocument::parse(source).unwrap();
+        let mut writer = BinaryWriter::new(Cursor::new(Vec::new()), doc.version).unwrap();
+        writer.set_compression_threshold(None);
+        for node in &GenericNode::from_ascii_nodes(&doc.nodes, "") {
+            writer.write_node(node).unwrap();
+        }
+        assert_eq!(doc.to_binary().unwrap(), writer.finish().unwrap().into_inner());
+    }
+
+    #[test]
+    #[cfg(any(feature = "flate2", feature = "libflate"))]
+    fn compressed_array_read_into_and_iter() {
+        use std::io;
+        use pull_parser::{ArrayAttribute, Attribute, Event, Parser};
+
+        let values = (0..3000).map(|i| (i % 7) as f64).collect::<Vec<_>>();
+        let mut writer = BinaryWriter::new(io::Cursor::new(Vec::new()), 7400).unwrap();
+        writer.start_node("A", &[OwnedAttribute::ArrF64(values.clone().into())]).unwrap();
+        writer.end_node().unwrap();
+        let bytes = writer.finish().unwrap().into_inner();
+        // The array should be compressed.
+        assert!(bytes.len() < 3000 * 8);
+
+        let mut parser = RootParser::new(io::Cursor::new(&bytes));
+        parser.next_event().expect("Failed to read FBX header");
+        match parser.next_event().expect("Failed to read node `A`") {
+            Event::StartNode(mut node) => match node.attributes.next_attribute() {
+                Ok(Some(Attribute::Array(ArrayAttribute::F64(mut arr)))) => {
+                    let mut head = [0.0; 100];
+                    assert_eq!(arr.read_into(&mut head).expect("Invalid array"), 100);
+                    assert_eq!(&head[..], &values[..100]);
+                    let iter = arr.iter();
+                    assert_eq!(iter.size_hint(), (2900, Some(2900)));
+                    let read = iter.collect::<io::Result<Vec<_>>>().expect("Invalid array");
+                    assert_eq!(read, &values[100..]);
+                    assert_eq!(arr.rest_elements(), 0);
+                },
+                attr => panic!("Unexpected attribute: {:?}", attr),
+            },
+            ev => panic!("Unexpected event: {:?}", ev),
+        }
+    }
+}
@@ -0,0 +1,75 @@
+//! Size report of written FBX binary.
+
+use std::fmt;
+
+
+/// Sizes of a toplevel node and its descendants.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct NodeSizeReport {
+    /// Node name.
+    pub name: String,
+    /// Byte length of the node, including the header and the descendants.
+    pub size: u64,
+    /// Byte length of array attribute contents before compression.
+    pub array_raw_bytes: u64,
+    /// Byte length of array attribute contents as written (after compression if applied).
+    pub array_written_bytes: u64,
+    /// Byte length of binary attribute contents (such as embedded media).
+    pub binary_bytes: u64,
+}
+
+impl NodeSizeReport {
+    /// Returns the ratio of written array bytes to raw array bytes.
+    ///
+    /// Returns `None` if the node has no array attributes.
+    pub fn compression_ratio(&self) -> Option<f64> {
+        if self.array_raw_bytes == 0 {
+            return None;
+        }
+        Some(self.array_written_bytes as f64 / self.array_raw_bytes as f64)
+    }
+}
+
+
+/// Size report of FBX binary written by `BinaryWriter`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct WriteReport {
+    /// Reports of the toplevel nodes, in the written order.
+    pub nodes: Vec<NodeSizeReport>,
+    /// Byte length of the whole file.
+    ///
+    /// This is `0` until the writer is finished.
+    pub file_size: u64,
+}
+
+impl WriteReport {
+    /// Returns the toplevel node reports sorted by the size, in descending order.
+    pub fn largest_nodes(&self) -> Vec<&NodeSizeReport> {
+        let mut nodes = self.nodes.iter().collect::<Vec<_>>();
+        nodes.sort_by_key(|node| ::std::cmp::Reverse(node.size));
+        nodes
+    }
+}
+
+impl fmt::Display for WriteReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Total: {} bytes", self.file_size)?;
+        for node in self.largest_nodes() {
+            write!(f, "{}: {} bytes", node.name, node.size)?;
+            if let Some(ratio) = node.compression_ratio() {
+                write!(
+                    f,
+                    ", arrays {} -> {} bytes ({:.1}%)",
+                    node.array_raw_bytes,
+                    node.array_written_bytes,
+                    ratio * 100.0
+                )?;
+            }
+            if node.binary_bytes != 0 {
+                write!(f, ", binary {} bytes", node.binary_bytes)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
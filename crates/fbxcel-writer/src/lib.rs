@@ -0,0 +1,13 @@
+//! FBX writers.
+#![warn(missing_docs)]
+
+extern crate fbxcel_core;
+extern crate fbxcel_dom;
+#[cfg(feature = "flate2")]
+extern crate flate2;
+#[cfg(feature = "libflate")]
+extern crate libflate;
+
+use fbxcel_core::pull_parser;
+
+pub mod binary;
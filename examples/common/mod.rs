@@ -0,0 +1,93 @@
+//! Fixture shared by the examples.
+//!
+//! The examples read the FBX file given as the first argument, or a small generated scene (a
+//! quad with a translation animation) if no argument is given.
+
+#![allow(dead_code)]
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use fbxcel::pull_parser::ascii::AsciiDocument;
+
+
+/// FBX ASCII source of the generated fixture.
+pub const FIXTURE_ASCII: &str = r#"; FBX 7.4.0 project file
+Creator: "fbxcel examples"
+Objects:  {
+	Geometry: 1000, "Geometry::Quad", "Mesh" {
+		Vertices: *12 {
+			a: -1,-1,0,1,-1,0,1,1,0,-1,1,0
+		}
+		PolygonVertexIndex: *4 {
+			a: 0,1,2,-4
+		}
+		GeometryVersion: 124
+	}
+	Model: 2000, "Model::Quad", "Mesh" {
+		Version: 232
+	}
+	AnimationStack: 3000, "AnimationStack::Take 001", "" {
+	}
+	AnimationLayer: 3100, "AnimationLayer::BaseLayer", "" {
+	}
+	AnimationCurveNode: 3200, "AnimationCurveNode::T", "" {
+		Properties70:  {
+			P: "d|X", "Number", "", "A",0
+			P: "d|Y", "Number", "", "A",0
+			P: "d|Z", "Number", "", "A",0
+		}
+	}
+	AnimationCurve: 3300, "AnimationCurve::", "" {
+		Default: 0
+		KeyVer: 4008
+		KeyTime: *2 {
+			a: 0,46186158000
+		}
+		KeyValueFloat: *2 {
+			a: 0,10
+		}
+		KeyAttrFlags: *1 {
+			a: 4
+		}
+		KeyAttrDataFloat: *4 {
+			a: 0,0,0,0
+		}
+		KeyAttrRefCount: *1 {
+			a: 2
+		}
+	}
+}
+Connections:  {
+	C: "OO",2000,0
+	C: "OO",1000,2000
+	C: "OO",3100,3000
+	C: "OO",3200,3100
+	C: "OP",3200,2000, "Lcl Translation"
+	C: "OP",3300,3200, "d|X"
+}
+"#;
+
+
+/// Returns the path of the FBX file to read.
+///
+/// If no path is given as the first argument, the fixture is written to the temporary directory
+/// as FBX binary with the given file name.
+pub fn input_path(fixture_name: &str) -> PathBuf {
+    match env::args_os().nth(1) {
+        Some(path) => PathBuf::from(path),
+        None => write_fixture(fixture_name),
+    }
+}
+
+
+/// Writes the fixture to the temporary directory as FBX binary and returns the path.
+pub fn write_fixture(name: &str) -> PathBuf {
+    let bytes = AsciiDocument::parse(FIXTURE_ASCII)
+        .expect("Fixture should be valid FBX ASCII")
+        .to_binary()
+        .expect("Fixture should be convertible to FBX binary");
+    let path = env::temp_dir().join(name);
+    fs::write(&path, bytes).expect("Failed to write the fixture");
+    path
+}
@@ -0,0 +1,75 @@
+//! Extracts triangulated meshes from the FBX file.
+//!
+//! Usage: `cargo run --example extract_mesh [path/to/file.fbx]`
+
+extern crate fbxcel;
+
+mod common;
+
+use std::path::Path;
+use std::process;
+use fbxcel::dom;
+use fbxcel::dom::fbx7400::geometry::ResolvedMesh;
+
+
+/// Resolves the `Geometry` objects and prints their vertices and triangles.
+fn extract_mesh<P: AsRef<Path>>(path: P) -> dom::Result<String> {
+    let doc = fbxcel::load_scene(path)?;
+    let mut out = String::new();
+    for obj in doc.objects.iter().flat_map(|objs| objs.iter()) {
+        if obj.props.class != "Geometry" {
+            continue;
+        }
+        let mesh = match ResolvedMesh::from_geometry(obj) {
+            Some(mesh) => mesh,
+            None => {
+                let msg = format!("Geometry {} ({:?}): broken\n", obj.props.id, obj.props.name);
+                out.push_str(&msg);
+                continue;
+            },
+        };
+        out.push_str(&format!(
+            "Geometry {} ({:?}): {} vertices, {} triangles\n",
+            obj.props.id,
+            obj.props.name,
+            mesh.num_vertices(),
+            mesh.num_triangles()
+        ));
+        for pos in mesh.positions.iter() {
+            out.push_str(&format!("  v {} {} {}\n", pos[0], pos[1], pos[2]));
+        }
+        for tri in mesh.indices.chunks(3) {
+            out.push_str(&format!("  f {} {} {}\n", tri[0], tri[1], tri[2]));
+        }
+    }
+    Ok(out)
+}
+
+
+fn main() {
+    let path = common::input_path("fbxcel-example-extract-mesh.fbx");
+    match extract_mesh(&path) {
+        Ok(out) => print!("{}", out),
+        Err(err) => {
+            eprintln!("Failed to load {}: {}", path.display(), err);
+            process::exit(1);
+        },
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixture() {
+        let out = extract_mesh(common::write_fixture("fbxcel-test-extract-mesh.fbx")).unwrap();
+        assert_eq!(
+            out,
+            "Geometry 1000 (\"Quad\"): 4 vertices, 2 triangles\n\
+             \x20 v -1 -1 0\n  v 1 -1 0\n  v 1 1 0\n  v -1 1 0\n\
+             \x20 f 0 1 2\n  f 0 2 3\n"
+        );
+    }
+}
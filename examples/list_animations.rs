@@ -0,0 +1,127 @@
+//! Lists animation stacks, layers, and curves of the FBX file.
+//!
+//! Usage: `cargo run --example list_animations [path/to/file.fbx]`
+
+extern crate fbxcel;
+
+mod common;
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::process;
+use fbxcel::dom;
+use fbxcel::dom::fbx7400::{Connection, GenericObject, KTime};
+use fbxcel::dom::fbx7400::animation::CurveKeys;
+
+
+/// Prints the animation stacks, and the curves of each layer with their key ranges.
+fn list_animations<P: AsRef<Path>>(path: P) -> dom::Result<String> {
+    let doc = fbxcel::load_scene(path)?;
+    let objects = doc.objects
+        .iter()
+        .flat_map(|objs| objs.iter())
+        .map(|obj| (obj.props.id, obj))
+        .collect::<HashMap<_, _>>();
+    let connections = doc.connections.as_ref().map_or(&[][..], |c| &c.0[..]);
+    // Returns the sources connected to the destination.
+    let sources = |dest: i64| {
+        connections
+            .iter()
+            .filter(move |c| c.destination == dest)
+            .filter_map(|c| objects.get(&c.source).map(|&obj| (c, obj)))
+    };
+
+    let mut out = String::new();
+    let mut stacks = objects
+        .values()
+        .filter(|obj| obj.props.class == "AnimationStack")
+        .collect::<Vec<_>>();
+    stacks.sort_by_key(|obj| obj.props.id);
+    for stack in stacks {
+        out.push_str(&format!("AnimationStack {:?}\n", stack.props.name));
+        for (_, layer) in sources(stack.props.id).filter(|&(_, o)| is_class(o, "AnimationLayer")) {
+            out.push_str(&format!("  AnimationLayer {:?}\n", layer.props.name));
+            for (_, node) in sources(layer.props.id) {
+                let target = connections
+                    .iter()
+                    .find(|c| c.source == node.props.id && c.property.is_some())
+                    .map(|c| describe_target(c, &objects));
+                out.push_str(&format!(
+                    "    AnimationCurveNode {:?} -> {}\n",
+                    node.props.name,
+                    target.unwrap_or_else(|| "(unconnected)".to_owned())
+                ));
+                for (conn, curve) in sources(node.props.id) {
+                    let channel = conn.curve_channel();
+                    let channel = channel.as_ref().map_or("?", |ch| ch.name());
+                    out.push_str(&format!("      {}: {}\n", channel, describe_curve(curve)));
+                }
+            }
+        }
+    }
+    Ok(out)
+}
+
+
+/// Returns `true` if the object is of the given class.
+fn is_class(obj: &GenericObject, class: &str) -> bool {
+    obj.props.class == class
+}
+
+
+/// Describes the object property animated by the curve node.
+fn describe_target(conn: &Connection, objects: &HashMap<i64, &GenericObject>) -> String {
+    let name = objects.get(&conn.destination).map_or("?", |obj| &obj.props.name);
+    format!("{:?}.{}", name, conn.property.as_ref().map_or("", |s| s.as_str()))
+}
+
+
+/// Describes the number of keys and the keyed range of the curve.
+fn describe_curve(curve: &GenericObject) -> String {
+    match CurveKeys::from_generic(curve) {
+        Some(ref keys) if !keys.times.is_empty() => {
+            let first = keys.times[0];
+            let last = keys.times[keys.times.len() - 1];
+            format!(
+                "{} keys, {}s..{}s, {}..{}",
+                keys.times.len(),
+                KTime(first).as_seconds(),
+                KTime(last).as_seconds(),
+                keys.evaluate(first).unwrap_or(0.0),
+                keys.evaluate(last).unwrap_or(0.0)
+            )
+        },
+        Some(_) => "no keys".to_owned(),
+        None => "broken curve".to_owned(),
+    }
+}
+
+
+fn main() {
+    let path = common::input_path("fbxcel-example-list-animations.fbx");
+    match list_animations(&path) {
+        Ok(out) => print!("{}", out),
+        Err(err) => {
+            eprintln!("Failed to load {}: {}", path.display(), err);
+            process::exit(1);
+        },
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixture() {
+        let path = common::write_fixture("fbxcel-test-list-animations.fbx");
+        assert_eq!(
+            list_animations(path).unwrap(),
+            "AnimationStack \"Take 001\"\n\
+             \x20 AnimationLayer \"BaseLayer\"\n\
+             \x20   AnimationCurveNode \"T\" -> \"Quad\".Lcl Translation\n\
+             \x20     X: 2 keys, 0s..1s, 0..10\n"
+        );
+    }
+}
@@ -0,0 +1,67 @@
+//! Prints the node tree of the FBX file.
+//!
+//! Usage: `cargo run --example print_tree [path/to/file.fbx]`
+
+extern crate fbxcel;
+
+mod common;
+
+use std::path::Path;
+use std::process;
+use fbxcel::dom;
+use fbxcel::dom::tree::{Node, Tree};
+use fbxcel::pull_parser::any::AnyParser;
+
+
+/// Prints the names and the number of attributes of the nodes, indented by depth.
+fn print_tree<P: AsRef<Path>>(path: P) -> dom::Result<String> {
+    let tree = match fbxcel::open_parser(path)? {
+        AnyParser::Binary(parser) => Tree::load(parser)?,
+        AnyParser::Ascii(parser) => Tree::load(parser)?,
+    };
+    let mut out = format!("FBX version: {:?}\n", tree.fbx_version());
+    for node in tree.root().children() {
+        print_node(&mut out, node, 0);
+    }
+    Ok(out)
+}
+
+
+/// Prints the node and its descendants.
+fn print_node(out: &mut String, node: Node<'_>, depth: usize) {
+    out.push_str(&format!(
+        "{}{} ({} attributes)\n",
+        "  ".repeat(depth),
+        node.name_lossy(),
+        node.num_attributes()
+    ));
+    for child in node.children() {
+        print_node(out, child, depth + 1);
+    }
+}
+
+
+fn main() {
+    let path = common::input_path("fbxcel-example-print-tree.fbx");
+    match print_tree(&path) {
+        Ok(out) => print!("{}", out),
+        Err(err) => {
+            eprintln!("Failed to load {}: {}", path.display(), err);
+            process::exit(1);
+        },
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixture() {
+        let out = print_tree(common::write_fixture("fbxcel-test-print-tree.fbx")).unwrap();
+        assert!(out.starts_with("FBX version: Some(7400)\nCreator (1 attributes)\n"));
+        assert!(out.contains("\n  Geometry (3 attributes)\n    Vertices (1 attributes)\n"));
+        assert!(out.contains("\n  C (4 attributes)\n"));
+    }
+}
@@ -0,0 +1,232 @@
+//! Batch processing of many FBX files.
+//!
+//! This is intended for validation jobs which load thousands of files and report the results.
+//!
+//! ```no_run
+//! use fbxcel::batch::{self, BatchConfig};
+//!
+//! let paths = ["a.fbx", "b.fbx"];
+//! let report = batch::process(&paths, &BatchConfig::default(), |path, doc| {
+//!     let doc = doc?;
+//!     println!("{}: FBX {}", path.display(), doc.version);
+//!     Ok::<_, fbxcel::dom::Error>(())
+//! });
+//! println!("{}", report);
+//! ```
+
+use std::any::Any;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use convenience::load_scene;
+use dom::Result;
+use dom::fbx7400::Fbx7400Document;
+
+
+/// Batch processing config.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BatchConfig {
+    /// Whether to process files in parallel.
+    ///
+    /// This has no effect without `rayon` feature.
+    pub parallel: bool,
+}
+
+
+/// Result of processing a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileReport {
+    /// Path of the file.
+    pub path: PathBuf,
+    /// Error message if the callback failed or panicked.
+    pub error: Option<String>,
+    /// Time spent to load and process the file.
+    pub elapsed: Duration,
+}
+
+impl FileReport {
+    /// Returns whether the file is successfully processed.
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+
+/// Aggregated result of batch processing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchReport {
+    /// Reports for each file, in the order of the given paths.
+    pub files: Vec<FileReport>,
+    /// Wall-clock time of the whole batch.
+    pub elapsed: Duration,
+}
+
+impl BatchReport {
+    /// Returns the number of successfully processed files.
+    pub fn num_succeeded(&self) -> usize {
+        self.files.iter().filter(|file| file.is_ok()).count()
+    }
+
+    /// Returns the number of failed files.
+    pub fn num_failed(&self) -> usize {
+        self.files.len() - self.num_succeeded()
+    }
+
+    /// Returns an iterator of the failed files.
+    pub fn failures(&self) -> impl Iterator<Item = &FileReport> {
+        self.files.iter().filter(|file| !file.is_ok())
+    }
+}
+
+impl fmt::Display for BatchReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{} files: {} succeeded, {} failed ({:.3}s)",
+            self.files.len(),
+            self.num_succeeded(),
+            self.num_failed(),
+            self.elapsed.as_secs_f64()
+        )?;
+        for file in self.failures() {
+            writeln!(
+                f,
+                "{}: {}",
+                file.path.display(),
+                file.error.as_ref().map_or("", |s| s.as_str())
+            )?;
+        }
+        Ok(())
+    }
+}
+
+
+/// Loads the given files as `Fbx7400Document` and passes them to the callback.
+///
+/// The callback receives the path and the load result, and returns whether the file is
+/// successfully processed.
+/// Loading errors are not recorded by themselves; return them from the callback (for example by
+/// `?`) to report them.
+/// Panics while loading or processing a file are caught and recorded as the error of the file,
+/// and the remaining files are processed as usual.
+///
+/// With `parallel` config and `rayon` feature, files are processed in parallel and the callback
+/// is called from multiple threads.
+/// The order of the reports is the same as the order of the paths in any case.
+pub fn process<P, F, E>(paths: &[P], config: &BatchConfig, callback: F) -> BatchReport
+where
+    P: AsRef<Path> + Sync,
+    F: Fn(&Path, Result<Fbx7400Document>) -> ::std::result::Result<(), E> + Sync,
+    E: fmt::Display,
+{
+    let start = Instant::now();
+    let process_file = |path: &P| {
+        let path = path.as_ref();
+        let file_start = Instant::now();
+        let res = panic::catch_unwind(AssertUnwindSafe(|| callback(path, load_scene(path))));
+        let error = match res {
+            Ok(res) => res.err().map(|err| err.to_string()),
+            Err(payload) => Some(format!("panicked: {}", panic_message(payload.as_ref()))),
+        };
+        FileReport {
+            path: path.to_owned(),
+            error,
+            elapsed: file_start.elapsed(),
+        }
+    };
+    let files = if config.parallel {
+        process_parallel(paths, &process_file)
+    } else {
+        paths.iter().map(&process_file).collect()
+    };
+    BatchReport {
+        files,
+        elapsed: start.elapsed(),
+    }
+}
+
+
+/// Returns the message of the panic payload.
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        msg
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg
+    } else {
+        "(non-string panic payload)"
+    }
+}
+
+
+/// Processes the files in parallel.
+#[cfg(feature = "rayon")]
+fn process_parallel<P, F>(paths: &[P], process_file: &F) -> Vec<FileReport>
+where
+    P: Sync,
+    F: Fn(&P) -> FileReport + Sync,
+{
+    use rayon::prelude::*;
+    paths.par_iter().map(process_file).collect()
+}
+
+
+/// Processes the files sequentially (parallel processing requires `rayon` feature).
+#[cfg(not(feature = "rayon"))]
+fn process_parallel<P, F>(paths: &[P], process_file: &F) -> Vec<FileReport>
+where
+    F: Fn(&P) -> FileReport,
+{
+    paths.iter().map(process_file).collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use super::{process, BatchConfig};
+
+    #[test]
+    fn batch_report() {
+        let dir = env::temp_dir().join(format!("fbxcel-batch-{}", ::std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let good = dir.join("good.fbx");
+        fs::write(&good, "; FBX 7.4.0 project file\nObjects:  {\n}\n").unwrap();
+        let paths = vec![good, dir.join("missing.fbx")];
+        for &parallel in &[false, true] {
+            let config = BatchConfig { parallel };
+            let report = process(&paths, &config, |_, doc| doc.map(|_| ()));
+            assert_eq!(report.files.len(), 2);
+            assert!(report.files[0].is_ok());
+            assert!(!report.files[1].is_ok());
+            assert_eq!(report.num_failed(), 1);
+            assert!(report.to_string().contains("missing.fbx"));
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn catch_panic() {
+        let dir = env::temp_dir().join(format!("fbxcel-batch-panic-{}", ::std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let paths = vec![dir.join("panic.fbx"), dir.join("good.fbx")];
+        for path in &paths {
+            fs::write(path, "; FBX 7.4.0 project file\nObjects:  {\n}\n").unwrap();
+        }
+        for &parallel in &[false, true] {
+            let config = BatchConfig { parallel };
+            let report = process(&paths, &config, |path, doc| {
+                if path.ends_with("panic.fbx") {
+                    panic!("callback panicked on {}", path.display());
+                }
+                doc.map(|_| ())
+            });
+            assert_eq!(report.num_failed(), 1);
+            let error = report.files[0].error.as_ref().expect("Panic should be recorded");
+            assert!(error.contains("callback panicked on"), "error = {:?}", error);
+            assert!(report.files[1].is_ok());
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
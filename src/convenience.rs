@@ -0,0 +1,139 @@
+//! One-liners for common tasks.
+//!
+//! These pick sensible defaults: files are read with buffering, the format (binary or ASCII) is
+//! detected from the magic bytes, and documents are loaded with the lenient loader
+//! (`Fbx7400Document`).
+
+use std::fmt::{self, Write as FmtWrite};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use dom::{Error, GenericNode, OwnedAttribute, Result};
+use dom::fbx7400::Fbx7400Document;
+use pull_parser::SeekableSource;
+use pull_parser::any::{self, AnyParser};
+use pull_parser::ascii::Error as AsciiError;
+
+
+/// Source of the binary parser returned by `open_parser()`.
+pub type FileSource = SeekableSource<BufReader<File>>;
+
+
+/// Parser returned by `open_parser()`.
+pub type FileParser = AnyParser<BufReader<File>>;
+
+
+/// Opens the FBX file (binary or ASCII) and returns the parser.
+///
+/// ASCII files are read to the end here.
+pub fn open_parser<P: AsRef<Path>>(path: P) -> Result<FileParser> {
+    any::from_reader(BufReader::new(File::open(path)?)).map_err(|err| match err {
+        AsciiError::Io(err) => err.into(),
+        err => Error::AsciiParse(err),
+    })
+}
+
+
+/// Loads the FBX file (binary or ASCII) as `Fbx7400Document`.
+pub fn load_scene<P: AsRef<Path>>(path: P) -> Result<Fbx7400Document> {
+    match open_parser(path)? {
+        AnyParser::Binary(mut parser) => Fbx7400Document::load(&mut parser),
+        AnyParser::Ascii(mut parser) => Fbx7400Document::load(&mut parser),
+    }
+}
+
+
+/// Loads the FBX file (binary or ASCII) and dumps the node tree in a format similar to FBX
+/// ASCII.
+///
+/// Object names with classes are shown as is (i.e. `"name\u{0}\u{1}class"`).
+pub fn dump_to_string<P: AsRef<Path>>(path: P) -> Result<String> {
+    let (nodes, _) = match open_parser(path)? {
+        AnyParser::Binary(mut parser) => GenericNode::load_from_parser(&mut parser)?,
+        AnyParser::Ascii(mut parser) => GenericNode::load_from_parser(&mut parser)?,
+    };
+    let mut out = String::new();
+    for node in &nodes {
+        dump_node(&mut out, node, 0).expect("Writing to `String` should never fail");
+    }
+    Ok(out)
+}
+
+
+/// Dumps the node and its descendants.
+fn dump_node(out: &mut String, node: &GenericNode, depth: usize) -> fmt::Result {
+    let indent = "\t".repeat(depth);
+    write!(out, "{}{}: ", indent, node.name_lossy())?;
+    for (i, attr) in node.attributes.iter().enumerate() {
+        if i != 0 {
+            out.push_str(", ");
+        }
+        match *attr {
+            OwnedAttribute::Bool(v) => write!(out, "{}", if v { 'Y' } else { 'T' })?,
+            OwnedAttribute::I16(v) => write!(out, "{}", v)?,
+            OwnedAttribute::I32(v) => write!(out, "{}", v)?,
+            OwnedAttribute::I64(v) => write!(out, "{}", v)?,
+            OwnedAttribute::F32(v) => write!(out, "{:?}", v)?,
+            OwnedAttribute::F64(v) => write!(out, "{:?}", v)?,
+            OwnedAttribute::ArrBool(ref v) => {
+                dump_array(out, &v.iter().map(|&v| v as u8).collect::<Vec<_>>())?
+            },
+            OwnedAttribute::ArrI32(ref v) => dump_array(out, v)?,
+            OwnedAttribute::ArrI64(ref v) => dump_array(out, v)?,
+            OwnedAttribute::ArrF32(ref v) => dump_array(out, v)?,
+            OwnedAttribute::ArrF64(ref v) => dump_array(out, v)?,
+            OwnedAttribute::String(Ok(ref v)) => write!(out, "{:?}", v)?,
+            OwnedAttribute::String(Err(ref v)) => write!(out, "<{} bytes>", v.len())?,
+            OwnedAttribute::Binary(ref v) => write!(out, "<{} bytes>", v.len())?,
+        }
+    }
+    if node.children.is_empty() {
+        return writeln!(out);
+    }
+    writeln!(out, " {{")?;
+    for child in &node.children {
+        dump_node(out, child, depth + 1)?;
+    }
+    writeln!(out, "{}}}", indent)
+}
+
+
+/// Dumps array elements.
+fn dump_array<T: fmt::Display>(out: &mut String, arr: &[T]) -> fmt::Result {
+    write!(out, "*{} {{ a: ", arr.len())?;
+    for (i, v) in arr.iter().enumerate() {
+        if i != 0 {
+            out.push(',');
+        }
+        write!(out, "{}", v)?;
+    }
+    out.push_str(" }");
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use pull_parser::ascii::AsciiDocument;
+    use super::*;
+
+    #[test]
+    fn detect_format() {
+        let ascii = "; FBX 7.4.0 project file\n\
+                     Creator: \"fbxcel\"\nDocuments:  {\n\tCount: 1\n}\n";
+        let dir = env::temp_dir().join(format!("fbxcel-convenience-{}", ::std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let ascii_path = dir.join("ascii.fbx");
+        fs::write(&ascii_path, ascii).unwrap();
+        let dump = dump_to_string(&ascii_path).unwrap();
+        assert_eq!(dump, "Creator: \"fbxcel\"\nDocuments:  {\n\tCount: 1\n}\n");
+
+        let binary = AsciiDocument::parse(ascii).unwrap().to_binary().unwrap();
+        let binary_path = dir.join("binary.fbx");
+        fs::write(&binary_path, binary).unwrap();
+        assert_eq!(dump_to_string(&binary_path).unwrap(), dump);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,13 +1,191 @@
 //! Excellent FBX loader for Rust programming language.
 #![warn(missing_docs)]
 
-#[cfg(feature = "flate2")]
-extern crate flate2;
-extern crate fnv;
-#[cfg(feature = "libflate")]
-extern crate libflate;
-#[macro_use]
-extern crate log;
+extern crate fbxcel_core;
+pub extern crate fbxcel_dom as dom;
+pub extern crate fbxcel_writer as writer;
+#[cfg(feature = "rayon")]
+extern crate rayon;
 
+pub use convenience::{dump_to_string, load_scene, open_parser, FileParser, FileSource};
+pub use fbxcel_core::{approx, memory, pull_parser};
+
+pub mod batch;
+mod convenience;
+#[deprecated(since = "0.0.3", note = "Use `fbxcel::dom` instead")]
 pub mod loader;
+pub mod low;
+#[deprecated(since = "0.0.3", note = "Use `fbxcel::pull_parser` instead")]
 pub mod parser;
+pub mod pipeline;
+pub mod prelude;
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::io;
+    use std::str;
+    use pull_parser::{Error as ParseError, Warning};
+    use pull_parser::ascii::Error as AsciiError;
+    use dom::Error as DomError;
+    use writer::binary::Error as WriteError;
+
+    fn io_error() -> io::Error {
+        io::Error::new(io::ErrorKind::UnexpectedEof, "eof")
+    }
+
+    fn path() -> String {
+        "Objects/Model".to_owned()
+    }
+
+    /// Returns the parser errors and their pinned codes.
+    fn parse_errors() -> Vec<(ParseError, u32)> {
+        let invalid_utf8 = vec![0xff];
+        let utf8_err = str::from_utf8(&invalid_utf8).unwrap_err();
+        vec![
+            (ParseError::AttributeTooLargeForPlatform { bytelen: 1 }, 1000),
+            (ParseError::BrokenFbxFooter, 1001),
+            (ParseError::Finished, 1002),
+            (ParseError::HeaderFooterVersionMismatch { header: 7400, footer: 7500 }, 1003),
+            (ParseError::InvalidNodeAttributeTypeCode { got: 0, position: 0 }, 1004),
+            (ParseError::MagicNotDetected([0; 21]), 1005),
+            (ParseError::NodeOffsetOverflow { position: 0, fbx_version: 7400 }, 1006),
+            (ParseError::NodeNameInvalidUtf8(utf8_err), 1007),
+            (ParseError::Io(io_error()), 1008),
+            (ParseError::UnknownArrayAttributeEncoding(2), 1009),
+            (ParseError::WrongNodeEndOffset { begin: 0, expected_end: 1, real_end: 2 }, 1010),
+            (ParseError::InternalState("state"), 1011),
+            (
+                ParseError::InvalidNodeStructure { begin: 0, end: 1, min_end: 2, max_end: None },
+                1012,
+            ),
+        ]
+    }
+
+    /// Returns the parser warnings and their pinned codes.
+    fn warnings() -> Vec<(Warning, u32)> {
+        vec![
+            (Warning::AttributeCountMismatch { declared: 2, read: 1, position: 0 }, 2000),
+            (
+                Warning::InvalidBooleanAttributeValue { got: 1, assumed: true, position: 0 },
+                2001,
+            ),
+            (Warning::InvalidPaddingInFbxFooter { expected: 16, actual: 0 }, 2002),
+            (Warning::NodeTooDeep { depth: 1, position: 0 }, 2003),
+            (Warning::TooManyAttributes { num_attributes: 1, position: 0 }, 2004),
+            (Warning::UnexpectedBytesAfterMagic([0; 2]), 2005),
+        ]
+    }
+
+    /// Returns the loader errors and their pinned codes.
+    fn dom_errors() -> Vec<(DomError, u32)> {
+        vec![
+            (DomError::AttributeTooLarge { path: path(), index: 0, len: 1 }, 3008),
+            (DomError::ConnectionCycle { source: 1, destination: 2 }, 3000),
+            (DomError::DuplicateObjectId(1), 3010),
+            (
+                DomError::IncompatibleProperty {
+                    property: "Visibility".to_owned(),
+                    expected: "f64",
+                    found: "String",
+                },
+                3011,
+            ),
+            (DomError::InvalidAttribute("Model".to_owned()), 3001),
+            (DomError::LoadObject(From::from("object")), 3002),
+            (DomError::missing_node("Objects", "Model"), 3003),
+            (DomError::NodeNameTooLong { path: path(), len: 256 }, 3004),
+            (DomError::UnexpectedEvent("EndFbx".to_owned()), 3012),
+            (DomError::UnexpectedNode("Model".to_owned()), 3005),
+            (DomError::UnknownObject(1), 3006),
+            (DomError::UnknownProperty { object: 1, property: "Visibility".to_owned() }, 3007),
+            (DomError::UnsupportedVersion(6100), 3009),
+        ]
+    }
+
+    /// Returns the ASCII parser errors and their pinned codes.
+    fn ascii_errors() -> Vec<(AsciiError, u32)> {
+        vec![
+            (AsciiError::ArrayLengthMismatch { declared: 2, actual: 1, line: 1 }, 4000),
+            (AsciiError::InvalidUtf8, 4001),
+            (AsciiError::InvalidNumber { literal: "1.2.3".to_owned(), line: 1 }, 4002),
+            (AsciiError::Io(io_error()), 4003),
+            (AsciiError::MissingVersion, 4004),
+            (AsciiError::UnexpectedChar { got: '#', line: 1 }, 4005),
+            (AsciiError::UnexpectedEof, 4006),
+            (AsciiError::UnexpectedToken { expected: "value", line: 1 }, 4007),
+            (AsciiError::Transcode("transcode".to_owned()), 4008),
+            (AsciiError::InvalidVersion(-1), 4009),
+            (AsciiError::NodeTooDeep { line: 1 }, 4010),
+            (AsciiError::UnknownFormat, 4011),
+        ]
+    }
+
+    /// Returns the writer errors and their pinned codes.
+    fn write_errors() -> Vec<(WriteError, u32)> {
+        vec![
+            (WriteError::AttributeOutsideNode { path: path() }, 5007),
+            (WriteError::AttributeTooLarge { path: path(), index: 0, len: 1 }, 5000),
+            (WriteError::Io(io_error()), 5001),
+            (WriteError::NodeNameTooLong { path: path(), len: 256 }, 5002),
+            (
+                WriteError::NodeOffsetOverflow { path: path(), position: 0, fbx_version: 7400 },
+                5003,
+            ),
+            (WriteError::NoOpenNode, 5004),
+            (WriteError::UnclosedNodes { path: path(), num: 1 }, 5005),
+            (WriteError::UnsupportedVersion(6100), 5006),
+            (WriteError::Parse(ParseError::BrokenFbxFooter), 5008),
+        ]
+    }
+
+    #[test]
+    fn error_codes_are_pinned() {
+        for (err, code) in parse_errors() {
+            assert_eq!(err.code(), code, "{:?}", err);
+            assert!((1000..2000).contains(&code));
+        }
+        for (warning, code) in warnings() {
+            assert_eq!(warning.code(), code, "{:?}", warning);
+            assert!((2000..3000).contains(&code));
+        }
+        for (err, code) in dom_errors() {
+            assert_eq!(err.code(), code, "{:?}", err);
+            assert!((3000..4000).contains(&code));
+        }
+        for (err, code) in ascii_errors() {
+            assert_eq!(err.code(), code, "{:?}", err);
+            assert!((4000..5000).contains(&code));
+        }
+        for (err, code) in write_errors() {
+            assert_eq!(err.code(), code, "{:?}", err);
+            assert!((5000..6000).contains(&code));
+        }
+        // Loader errors wrapping parser errors have the codes of the inner errors.
+        for (err, code) in parse_errors() {
+            assert_eq!(DomError::Parse(err).code(), code);
+        }
+        for (err, code) in ascii_errors() {
+            assert_eq!(DomError::AsciiParse(err).code(), code);
+        }
+    }
+
+    #[test]
+    fn error_codes_are_unique() {
+        let codes = parse_errors()
+            .into_iter()
+            .map(|(err, code)| (code, format!("{:?}", err)))
+            .chain(warnings().into_iter().map(|(w, code)| (code, format!("{:?}", w))))
+            .chain(dom_errors().into_iter().map(|(err, code)| (code, format!("{:?}", err))))
+            .chain(ascii_errors().into_iter().map(|(err, code)| (code, format!("{:?}", err))))
+            .chain(write_errors().into_iter().map(|(err, code)| (code, format!("{:?}", err))));
+        let mut seen = HashMap::new();
+        for (code, err) in codes {
+            if let Some(prev) = seen.insert(code, err.clone()) {
+                panic!("Code {} is used by both {} and {}", code, prev, err);
+            }
+        }
+        assert_eq!(seen.len(), 13 + 6 + 13 + 12 + 9);
+    }
+}
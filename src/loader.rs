@@ -0,0 +1,15 @@
+//! Loader.
+//!
+//! Deprecated aliases of `dom`, kept for one release.
+
+pub use dom::utils;
+
+/// Binary loaders.
+pub mod binary {
+    /// Simple binary loader.
+    ///
+    /// Deprecated alias of `dom`.
+    pub mod simple {
+        pub use dom::*;
+    }
+}
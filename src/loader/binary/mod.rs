@@ -1,3 +0,0 @@
-//! Binary loaders.
-
-pub mod simple;
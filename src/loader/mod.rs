@@ -1,4 +0,0 @@
-//! Loader.
-
-pub mod binary;
-pub mod utils;
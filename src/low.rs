@@ -0,0 +1,10 @@
+//! Low-level types of the FBX format.
+//!
+//! These are the building blocks shared by `pull_parser` and `writer`.
+
+pub use pull_parser::{FbxFooter, FbxHeader};
+pub use pull_parser::{ArrayAttribute, Attribute, PrimitiveAttribute, SpecialAttribute};
+pub use pull_parser::SpecialAttributeType;
+pub use pull_parser::limits;
+pub use pull_parser::utils::StringTable;
+pub use dom::MAX_NODE_NAME_LEN;
@@ -0,0 +1,12 @@
+//! FBX parsers.
+//!
+//! Deprecated aliases of `pull_parser`, kept for one release.
+
+/// FBX binary parser.
+///
+/// Deprecated alias of `pull_parser`.
+pub mod binary {
+    pub use pull_parser::*;
+}
+
+pub use pull_parser::{any, ascii};
@@ -1,3 +0,0 @@
-//! FBX parsers.
-
-pub mod binary;
@@ -0,0 +1,392 @@
+//! Streaming transformation of FBX documents.
+//!
+//! `Filter` wraps a parser and applies a closure to each node, which can drop the node (with its
+//! descendants), rename it, or modify its attributes.
+//! The result can be written by `Filter::write_to()` without loading the whole tree.
+//!
+//! Attributes are decoded only when the closure reads them or the node is kept, so skipping
+//! nodes by name (such as embedded media) doesn't decode their large attributes.
+//!
+//! ```
+//! # use std::io::Cursor;
+//! # use fbxcel::pull_parser::ascii::AsciiDocument;
+//! use fbxcel::pipeline::{Action, Filter, FilterNode};
+//! use fbxcel::pull_parser::RootParser;
+//! use fbxcel::writer::binary::{BinaryWriter, EventWriter};
+//!
+//! # let source = "; FBX 7.4.0 project file\nObjects:  {\n}\n";
+//! # let bytes = AsciiDocument::parse(source).unwrap().to_binary().unwrap();
+//! let parser = RootParser::new(Cursor::new(bytes));
+//! let writer = BinaryWriter::new(Cursor::new(Vec::new()), 7400).unwrap();
+//! let mut writer = EventWriter::new(writer);
+//! // Strip all embedded textures.
+//! let filter = Filter::new(parser, |parents: &[Vec<u8>], node: &mut FilterNode<'_>| {
+//!     if parents.last().map_or(false, |p| p == b"Video") && node.name == b"Content" {
+//!         Action::Skip
+//!     } else {
+//!         Action::Keep
+//!     }
+//! });
+//! filter.write_to(&mut writer).unwrap();
+//! let bytes = writer.finish().unwrap().into_inner();
+//! ```
+
+use std::fmt;
+use std::io::{Seek, Write};
+use std::mem;
+use std::str;
+use dom::OwnedAttribute;
+use pull_parser::{Event, Parser, ParserSource};
+use pull_parser::Error as ParseError;
+use writer::binary::{EventWriter, OwnedEvent, Result};
+
+
+/// Loader of the node attributes.
+type LoadAttributes<'a> = Box<dyn FnOnce() -> ::pull_parser::Result<Vec<OwnedAttribute>> + 'a>;
+
+
+/// Attributes of `FilterNode`.
+enum LazyAttributes<'a> {
+    /// Not loaded yet.
+    Unloaded(LoadAttributes<'a>),
+    /// Loaded.
+    Loaded(Vec<OwnedAttribute>),
+    /// Failed to load.
+    Failed(ParseError),
+    /// Temporary state during loading.
+    Loading,
+}
+
+
+/// Node passed to the filter closure.
+///
+/// Attributes are loaded on the first call of `attributes()` or `attributes_mut()`.
+pub struct FilterNode<'a> {
+    /// Node name.
+    ///
+    /// Names which are not valid UTF-8 are kept as is.
+    pub name: Vec<u8>,
+    /// Node attributes.
+    attributes: LazyAttributes<'a>,
+}
+
+impl<'a> FilterNode<'a> {
+    /// Returns the node name as a string.
+    ///
+    /// Returns `None` if the name is not valid UTF-8.
+    pub fn name_str(&self) -> Option<&str> {
+        str::from_utf8(&self.name).ok()
+    }
+
+    /// Returns the attributes, loading them if necessary.
+    ///
+    /// Returns `None` if the attributes cannot be loaded.
+    /// In this case, the error is returned by `Filter::next_event()` after the closure returns.
+    pub fn attributes(&mut self) -> Option<&[OwnedAttribute]> {
+        self.attributes_mut().map(|attrs| &attrs[..])
+    }
+
+    /// Returns the mutable attributes, loading them if necessary.
+    ///
+    /// Returns `None` if the attributes cannot be loaded (see `attributes()`).
+    pub fn attributes_mut(&mut self) -> Option<&mut Vec<OwnedAttribute>> {
+        if let LazyAttributes::Unloaded(_) = self.attributes {
+            self.attributes = match mem::replace(&mut self.attributes, LazyAttributes::Loading) {
+                LazyAttributes::Unloaded(load) => match load() {
+                    Ok(attrs) => LazyAttributes::Loaded(attrs),
+                    Err(err) => LazyAttributes::Failed(err),
+                },
+                _ => unreachable!("Attributes should be unloaded"),
+            };
+        }
+        match self.attributes {
+            LazyAttributes::Loaded(ref mut attrs) => Some(attrs),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the attributes are already loaded.
+    pub fn is_attributes_loaded(&self) -> bool {
+        !matches!(self.attributes, LazyAttributes::Unloaded(_))
+    }
+
+    /// Loads the attributes if necessary, and returns them.
+    fn into_attributes(mut self) -> ::pull_parser::Result<Vec<OwnedAttribute>> {
+        self.attributes_mut();
+        match self.attributes {
+            LazyAttributes::Loaded(attrs) => Ok(attrs),
+            LazyAttributes::Failed(err) => Err(err),
+            _ => unreachable!("Attributes should be loaded or failed"),
+        }
+    }
+}
+
+impl<'a> fmt::Debug for FilterNode<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut s = f.debug_struct("FilterNode");
+        s.field("name", &String::from_utf8_lossy(&self.name));
+        match self.attributes {
+            LazyAttributes::Loaded(ref attrs) => s.field("attributes", attrs),
+            LazyAttributes::Failed(ref err) => s.field("attributes", err),
+            _ => s.field("attributes", &"(not loaded)"),
+        };
+        s.finish()
+    }
+}
+
+
+/// Action for a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    /// Keeps the node (with modifications, if any).
+    Keep,
+    /// Drops the node and its descendants.
+    Skip,
+}
+
+
+/// Event emitted by `Filter`.
+///
+/// This is an event of a kept node, and can be written by `BinaryWriter::write_events()`.
+pub type FilterEvent = OwnedEvent;
+
+
+/// Parser adapter which filters and transforms nodes.
+///
+/// The closure receives names of the ancestor nodes and the node, and returns the action for the
+/// node.
+/// Descendants of skipped nodes are not passed to the closure.
+/// Attributes of skipped nodes are not decoded unless the closure reads them.
+#[derive(Debug)]
+pub struct Filter<P, F> {
+    /// Parser.
+    parser: P,
+    /// Filter closure.
+    filter: F,
+    /// Names of the open nodes.
+    parents: Vec<Vec<u8>>,
+}
+
+impl<P, F> Filter<P, F>
+where
+    F: FnMut(&[Vec<u8>], &mut FilterNode<'_>) -> Action,
+{
+    /// Creates a new `Filter`.
+    pub fn new(parser: P, filter: F) -> Self {
+        Filter {
+            parser,
+            filter,
+            parents: Vec::new(),
+        }
+    }
+
+    /// Returns the next event of kept nodes, or `None` at the end of the document.
+    ///
+    /// `StartFbx` is skipped.
+    pub fn next_event<R>(&mut self) -> Result<Option<FilterEvent>>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        loop {
+            let (name, attributes) = match self.parser.next_event()? {
+                Event::StartFbx(_) => continue,
+                Event::EndFbx(_) => return Ok(None),
+                Event::EndNode => {
+                    self.parents.pop();
+                    return Ok(Some(FilterEvent::EndNode));
+                },
+                Event::StartNode(node) => {
+                    let name = node.name_bytes().to_owned();
+                    let attrs = node.attributes;
+                    let mut node = FilterNode {
+                        name,
+                        attributes: LazyAttributes::Unloaded(Box::new(move || {
+                            OwnedAttribute::load_attrs_from_parser_event(attrs)
+                        })),
+                    };
+                    match (self.filter)(&self.parents, &mut node) {
+                        Action::Keep => {
+                            let name = mem::take(&mut node.name);
+                            (name, Some(node.into_attributes()?))
+                        },
+                        Action::Skip => {
+                            if let LazyAttributes::Failed(err) = node.attributes {
+                                return Err(err.into());
+                            }
+                            (node.name, None)
+                        },
+                    }
+                },
+            };
+            match attributes {
+                Some(attributes) => {
+                    self.parents.push(name.clone());
+                    return Ok(Some(FilterEvent::StartNode { name, attributes }));
+                },
+                None => {
+                    self.parser.skip_current_node()?;
+                },
+            }
+        }
+    }
+
+    /// Writes all the rest events to the writer.
+    ///
+    /// The writer is not finished, so that more nodes can be appended.
+    pub fn write_to<R, W>(mut self, writer: &mut EventWriter<W>) -> Result<()>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+        W: Write + Seek,
+    {
+        while let Some(event) = self.next_event()? {
+            match event {
+                FilterEvent::StartNode { name, attributes } => {
+                    writer.start_node_raw(&name)?;
+                    for attr in attributes {
+                        writer.push_attribute(attr)?;
+                    }
+                },
+                FilterEvent::EndNode => writer.end_node()?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the parser.
+    pub fn into_inner(self) -> P {
+        self.parser
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use dom::{GenericNode, OwnedAttribute};
+    use pull_parser::RootParser;
+    use pull_parser::ascii::AsciiDocument;
+    use writer::binary::{BinaryWriter, EventWriter};
+    use super::*;
+
+    #[test]
+    fn rename_and_skip() {
+        let source = r#"; FBX 7.4.0 project file
+Objects:  {
+	Video: 1, "Video::tex", "Clip" {
+		Content: "AAAA"
+		RelativeFilename: "tex.png"
+	}
+}
+Takes:  {
+	Take: "Take 001" {
+	}
+}
+"#;
+        let bytes = AsciiDocument::parse(source).unwrap().to_binary().unwrap();
+        let parser = RootParser::new(Cursor::new(&bytes));
+        let filter = Filter::new(parser, |parents: &[Vec<u8>], node: &mut FilterNode<'_>| {
+            if parents.last().is_some_and(|p| p == b"Video") && node.name == b"Content" {
+                return Action::Skip;
+            }
+            if node.name == b"Take" {
+                node.attributes_mut().unwrap()[0] =
+                    OwnedAttribute::String(Ok("Renamed".to_owned()));
+            }
+            Action::Keep
+        });
+        let sink = BinaryWriter::new(Cursor::new(Vec::new()), 7400).unwrap();
+        let mut writer = EventWriter::new(sink);
+        filter.write_to(&mut writer).unwrap();
+        let bytes = writer.finish().unwrap().into_inner();
+
+        let nodes = GenericNode::load_from_parser(&mut RootParser::new(Cursor::new(&bytes)))
+            .unwrap()
+            .0;
+        let video = &nodes[0].children[0];
+        assert_eq!(video.children.len(), 1);
+        assert_eq!(video.children[0].name, b"RelativeFilename");
+        let take = &nodes[1].children[0];
+        assert_eq!(take.attributes[0], OwnedAttribute::String(Ok("Renamed".to_owned())));
+    }
+
+    /// Returns FBX binary with the nodes `Keep` and `Broken`, where `Broken` has an array
+    /// attribute of unknown encoding.
+    fn broken_array_source() -> Vec<u8> {
+        let mut writer = BinaryWriter::new(Cursor::new(Vec::new()), 7400).unwrap();
+        writer.set_compression_threshold(None);
+        writer.start_node("Keep", &[OwnedAttribute::I32(1)]).unwrap();
+        writer.end_node().unwrap();
+        let values = vec![1.0; 16];
+        writer.start_node("Broken", &[OwnedAttribute::ArrF64(values.into())]).unwrap();
+        writer.end_node().unwrap();
+        let mut bytes = writer.finish().unwrap().into_inner();
+        // Type code, number of elements, and encoding.
+        let header = [&b"d"[..], &16u32.to_le_bytes(), &0u32.to_le_bytes()].concat();
+        let pos = bytes
+            .windows(header.len())
+            .position(|w| w == &header[..])
+            .expect("Array header should be written");
+        bytes[pos + 5] = 0xff;
+        bytes
+    }
+
+    #[test]
+    fn skip_without_loading_attributes() {
+        let bytes = broken_array_source();
+        let parser = RootParser::new(Cursor::new(&bytes));
+        let mut filter = Filter::new(parser, |_: &[Vec<u8>], node: &mut FilterNode<'_>| {
+            if node.name == b"Broken" {
+                assert!(!node.is_attributes_loaded());
+                Action::Skip
+            } else {
+                Action::Keep
+            }
+        });
+        let expected = FilterEvent::StartNode {
+            name: b"Keep".to_vec(),
+            attributes: vec![OwnedAttribute::I32(1)],
+        };
+        assert_eq!(filter.next_event().unwrap(), Some(expected));
+        assert_eq!(filter.next_event().unwrap(), Some(FilterEvent::EndNode));
+        assert_eq!(filter.next_event().unwrap(), None);
+
+        // Keeping the node, or reading its attributes, reports the error.
+        for &read_attributes in &[false, true] {
+            let parser = RootParser::new(Cursor::new(&bytes));
+            let mut filter = Filter::new(parser, |_: &[Vec<u8>], node: &mut FilterNode<'_>| {
+                if node.name != b"Broken" {
+                    return Action::Keep;
+                }
+                if read_attributes {
+                    assert!(node.attributes().is_none());
+                    Action::Skip
+                } else {
+                    Action::Keep
+                }
+            });
+            filter.next_event().unwrap();
+            filter.next_event().unwrap();
+            assert!(filter.next_event().is_err());
+        }
+    }
+
+    #[test]
+    fn non_utf8_name_round_trip() {
+        let name = b"Bad\xff\xfeName";
+        let mut writer = BinaryWriter::new(Cursor::new(Vec::new()), 7400).unwrap();
+        writer.start_node("Objects", &[]).unwrap();
+        writer.start_node_raw(name, &[OwnedAttribute::I32(1)]).unwrap();
+        writer.end_node().unwrap();
+        writer.end_node().unwrap();
+        let bytes = writer.finish().unwrap().into_inner();
+
+        let parser = RootParser::new(Cursor::new(&bytes));
+        let filter = Filter::new(parser, |_: &[Vec<u8>], _: &mut FilterNode<'_>| Action::Keep);
+        let sink = BinaryWriter::new(Cursor::new(Vec::new()), 7400).unwrap();
+        let mut writer = EventWriter::new(sink);
+        filter.write_to(&mut writer).unwrap();
+        assert_eq!(writer.finish().unwrap().into_inner(), bytes);
+    }
+}
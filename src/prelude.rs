@@ -0,0 +1,16 @@
+//! Commonly used traits and types.
+//!
+//! ```
+//! use fbxcel::prelude::*;
+//! ```
+//!
+//! Loader `Result` and `Error` are re-exported as `LoadResult` and `LoadError`, so that the glob
+//! import doesn't shadow `std::result::Result`.
+
+pub use approx::ApproxEq;
+pub use dom::{Error as LoadError, Result as LoadResult};
+pub use dom::{FbxLoader, GenericNode, OwnedAttribute};
+pub use dom::fbx7400::LoadObjects7400;
+pub use memory::MemoryUsage;
+pub use pull_parser::{Attribute, Attributes, Event, Parser, ParserSource, RootParser};
+pub use pull_parser::utils::{AttributeValue, AttributeValues};